@@ -0,0 +1,347 @@
+use clap::{Parser, ValueEnum};
+use log::{info, warn};
+use scip_core::logging::init_logger;
+use scip_core::{
+    build_call_graph_with_options, parse_scip_json, sarif_log, CallGraph, CallGraphOptions,
+    D3Graph, FunctionNode, SarifFinding, SarifLevel, SarifRule,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use verus_metrics::{find_escape_hatches, EscapeHatchKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Markdown,
+    /// SARIF 2.1.0, for code review tools
+    Sarif,
+}
+
+/// List every assume/admit/external_body/assume_specification and the
+/// functions that transitively rely on it
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Input SCIP JSON file
+    input_scip_json: String,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: ReportFormat,
+
+    /// D3 graph JSON (from export_call_graph_d3) to annotate in place with
+    /// `unsafety_flags` on every function containing an escape hatch
+    #[arg(long, value_name = "JSON")]
+    annotate_graph: Option<String>,
+
+    /// Leave out source files whose relative path matches this glob (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Enable debug logging
+    #[arg(short, long)]
+    debug: bool,
+}
+
+/// One escape hatch, where it is and who depends on it
+#[derive(Debug, Serialize)]
+struct Assumption {
+    kind: EscapeHatchKind,
+    symbol: String,
+    display_name: String,
+    relative_path: String,
+    /// 1-based line in the source file
+    line: usize,
+    /// Functions transitively calling the function containing the
+    /// assumption, sorted by display name
+    dependents: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditReport {
+    /// Number of escape hatches by kind
+    counts: BTreeMap<&'static str, usize>,
+    functions_with_assumptions: usize,
+    /// Functions relying on at least one assumption (excluding the
+    /// functions containing them)
+    dependent_functions: usize,
+    /// Sorted by number of dependents (most first), then path and line
+    assumptions: Vec<Assumption>,
+}
+
+/// Source text of a function with the attribute lines just above its
+/// definition (which SCIP ranges leave out), and the 1-based file line the
+/// text starts on
+fn source_with_attributes(
+    node: &FunctionNode,
+    files: &mut HashMap<String, Option<Vec<String>>>,
+) -> Option<(String, usize)> {
+    let body = node.body.as_deref()?;
+    let start = *node.range.first()? as usize;
+    let lines = files
+        .entry(node.file_path.clone())
+        .or_insert_with(|| {
+            std::fs::read_to_string(&node.file_path)
+                .ok()
+                .map(|content| content.lines().map(str::to_string).collect())
+        })
+        .as_deref()
+        .unwrap_or_default();
+
+    let mut first = start.min(lines.len());
+    while first > 0 {
+        let line = lines[first - 1].trim_start();
+        if line.starts_with("#[") || line.starts_with("///") {
+            first -= 1;
+        } else {
+            break;
+        }
+    }
+    let mut source = lines[first..start.min(lines.len())].join("\n");
+    if !source.is_empty() {
+        source.push('\n');
+    }
+    source.push_str(body);
+    Some((source, first + 1))
+}
+
+/// Every function transitively calling `symbol`, excluding `symbol` itself
+fn transitive_callers<'a>(call_graph: &'a CallGraph, symbol: &str) -> HashSet<&'a str> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut queue: VecDeque<&str> = VecDeque::new();
+    if let Some(node) = call_graph.get(symbol) {
+        queue.extend(node.callers.iter().map(String::as_str));
+    }
+    while let Some(caller) = queue.pop_front() {
+        if caller == symbol || !seen.insert(caller) {
+            continue;
+        }
+        if let Some(node) = call_graph.get(caller) {
+            queue.extend(node.callers.iter().map(String::as_str));
+        }
+    }
+    seen
+}
+
+fn audit(call_graph: &CallGraph) -> (AuditReport, BTreeMap<String, BTreeSet<&'static str>>) {
+    let mut files = HashMap::new();
+    let mut flags: BTreeMap<String, BTreeSet<&'static str>> = BTreeMap::new();
+    let mut counts: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut all_dependents: HashSet<&str> = HashSet::new();
+    let mut assumptions = Vec::new();
+
+    let mut nodes: Vec<&FunctionNode> = call_graph
+        .values()
+        .filter(|node| !node.file_path.starts_with("external:"))
+        .collect();
+    nodes.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    for node in nodes {
+        let Some((source, first_line)) = source_with_attributes(node, &mut files) else {
+            continue;
+        };
+        let hatches = match find_escape_hatches(&source) {
+            Ok(hatches) => hatches,
+            Err(e) => {
+                warn!("Skipping {}: {}", node.display_name, e);
+                continue;
+            }
+        };
+        if hatches.is_empty() {
+            continue;
+        }
+
+        let callers = transitive_callers(call_graph, &node.symbol);
+        all_dependents.extend(callers.iter().copied());
+        let mut dependents: Vec<String> = callers
+            .iter()
+            .filter_map(|symbol| call_graph.get(*symbol))
+            .map(|caller| caller.display_name.clone())
+            .collect();
+        dependents.sort();
+        for hatch in hatches {
+            *counts.entry(hatch.kind.as_str()).or_default() += 1;
+            flags
+                .entry(node.symbol.clone())
+                .or_default()
+                .insert(hatch.kind.as_str());
+            assumptions.push(Assumption {
+                kind: hatch.kind,
+                symbol: node.symbol.clone(),
+                display_name: node.display_name.clone(),
+                relative_path: node.relative_path.clone(),
+                line: first_line + hatch.line - 1,
+                dependents: dependents.clone(),
+            });
+        }
+    }
+    for symbol in flags.keys() {
+        all_dependents.remove(symbol.as_str());
+    }
+    assumptions.sort_by(|a, b| {
+        b.dependents
+            .len()
+            .cmp(&a.dependents.len())
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+
+    let report = AuditReport {
+        counts,
+        functions_with_assumptions: flags.len(),
+        dependent_functions: all_dependents.len(),
+        assumptions,
+    };
+    (report, flags)
+}
+
+fn to_markdown(report: &AuditReport) -> String {
+    let mut out = String::from("# Assumption audit\n\n");
+    let _ = writeln!(out, "| | Count |\n|---|---:|");
+    for (kind, count) in &report.counts {
+        let _ = writeln!(out, "| `{}` | {} |", kind, count);
+    }
+    let _ = writeln!(
+        out,
+        "| Functions with assumptions | {} |",
+        report.functions_with_assumptions
+    );
+    let _ = writeln!(
+        out,
+        "| Functions relying on them | {} |",
+        report.dependent_functions
+    );
+
+    if report.assumptions.is_empty() {
+        out.push_str("\nNo assumptions found.\n");
+        return out;
+    }
+
+    out.push_str("\n## Assumptions\n\n");
+    out.push_str("| Kind | Function | Location | Dependents |\n");
+    out.push_str("|---|---|---|---|\n");
+    for assumption in &report.assumptions {
+        let dependents = match assumption.dependents.len() {
+            0 => "-".to_string(),
+            n if n <= 5 => assumption.dependents.join(", "),
+            n => format!("{}, … ({} total)", assumption.dependents[..5].join(", "), n),
+        };
+        let _ = writeln!(
+            out,
+            "| {} | `{}` | {}:{} | {} |",
+            assumption.kind.as_str(),
+            assumption.display_name,
+            assumption.relative_path,
+            assumption.line,
+            dependents
+        );
+    }
+    out
+}
+
+/// The audit as a SARIF log: `assume`/`admit` are warnings, trusted
+/// `external_body` and `assume_specification` items notes
+fn to_sarif(report: &AuditReport) -> serde_json::Value {
+    let level = |kind: EscapeHatchKind| match kind {
+        EscapeHatchKind::Assume | EscapeHatchKind::Admit => SarifLevel::Warning,
+        EscapeHatchKind::ExternalBody | EscapeHatchKind::AssumeSpecification => SarifLevel::Note,
+    };
+    let rules: Vec<SarifRule> = [
+        (EscapeHatchKind::Assume, "`assume` in a proof or body"),
+        (EscapeHatchKind::Admit, "`admit()` in a proof"),
+        (
+            EscapeHatchKind::ExternalBody,
+            "`#[verifier::external_body]` function, trusted without proof",
+        ),
+        (
+            EscapeHatchKind::AssumeSpecification,
+            "`assume_specification`, a trusted spec of external code",
+        ),
+    ]
+    .into_iter()
+    .map(|(kind, description)| SarifRule {
+        id: kind.as_str(),
+        description,
+        default_level: level(kind),
+    })
+    .collect();
+    let findings: Vec<SarifFinding> = report
+        .assumptions
+        .iter()
+        .map(|assumption| SarifFinding {
+            rule_id: assumption.kind.as_str(),
+            level: level(assumption.kind),
+            message: format!(
+                "`{}` in `{}` ({} functions rely on it)",
+                assumption.kind.as_str(),
+                assumption.display_name,
+                assumption.dependents.len()
+            ),
+            relative_path: assumption.relative_path.clone(),
+            line: Some(assumption.line as u32),
+            symbol: Some(assumption.symbol.clone()),
+        })
+        .collect();
+    sarif_log(&rules, &findings)
+}
+
+/// Set `unsafety_flags` on the graph nodes of functions with escape hatches
+fn annotate_graph(
+    path: &str,
+    flags: &BTreeMap<String, BTreeSet<&'static str>>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut graph: D3Graph = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let mut annotated = 0;
+    for node in &mut graph.nodes {
+        let Some(kinds) = flags.get(&node.symbol).or_else(|| flags.get(&node.id)) else {
+            continue;
+        };
+        node.unsafety_flags = kinds.iter().map(|kind| kind.to_string()).collect();
+        annotated += 1;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&graph)?)?;
+    Ok(annotated)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    init_logger(args.debug);
+
+    info!("Parsing SCIP JSON from {}...", args.input_scip_json);
+    let scip_data = parse_scip_json(&args.input_scip_json)?;
+    let options = CallGraphOptions::default().with_exclude_globs(args.exclude.clone());
+    let call_graph = build_call_graph_with_options(&scip_data, &options);
+    info!("Call graph contains {} functions", call_graph.len());
+
+    let (report, flags) = audit(&call_graph);
+    info!(
+        "{} assumptions in {} functions, {} functions rely on them",
+        report.assumptions.len(),
+        report.functions_with_assumptions,
+        report.dependent_functions
+    );
+
+    if let Some(path) = &args.annotate_graph {
+        let annotated = annotate_graph(path, &flags)?;
+        info!("✓ Annotated {} nodes in {}", annotated, path);
+    }
+
+    let rendered = match args.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        ReportFormat::Markdown => to_markdown(&report),
+        ReportFormat::Sarif => serde_json::to_string_pretty(&to_sarif(&report))?,
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            info!("✓ Wrote assumption audit to {}", path);
+        }
+        None => println!("{}", rendered),
+    }
+
+    Ok(())
+}
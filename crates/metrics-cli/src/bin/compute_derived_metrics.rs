@@ -0,0 +1,74 @@
+use clap::Parser;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use verus_metrics::derived::DerivedMetrics;
+
+/// Compute spec-to-body and proof-to-exec ratios from atoms with metrics
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Atoms JSON enriched by compute_metrics
+    input: String,
+
+    /// Write per-function and per-module ratios as JSON
+    #[arg(long)]
+    json: Option<String>,
+
+    /// Write per-function ratios as CSV
+    #[arg(long)]
+    functions_csv: Option<String>,
+
+    /// Write per-module ratios as CSV
+    #[arg(long)]
+    modules_csv: Option<String>,
+}
+
+fn format_ratio(ratio: Option<f64>) -> String {
+    ratio.map_or_else(|| "-".to_string(), |r| format!("{:.2}", r))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    println!("Reading atoms with metrics from {}...", args.input);
+    let json = fs::read_to_string(&args.input)?;
+    let derived = DerivedMetrics::from_json_str(&json)?;
+
+    println!(
+        "Computed ratios for {} functions in {} modules",
+        derived.functions.len(),
+        derived.modules.len()
+    );
+
+    if let Some(path) = &args.json {
+        derived.write_json(BufWriter::new(File::create(path)?))?;
+        println!("Wrote JSON to {}", path);
+    }
+    if let Some(path) = &args.functions_csv {
+        derived.write_functions_csv(File::create(path)?)?;
+        println!("Wrote per-function CSV to {}", path);
+    }
+    if let Some(path) = &args.modules_csv {
+        derived.write_modules_csv(File::create(path)?)?;
+        println!("Wrote per-module CSV to {}", path);
+    }
+
+    println!();
+    println!("=== Per-module ratios ===");
+    println!(
+        "{:<50} {:>10} {:>12} {:>14}",
+        "module", "spec/body", "proof/exec", "lemmas/exec fn"
+    );
+    for module in &derived.modules {
+        println!(
+            "{:<50} {:>10} {:>12} {:>14}",
+            module.module,
+            format_ratio(module.spec_to_body_volume),
+            format_ratio(module.proof_to_exec_lines),
+            format_ratio(module.lemmas_per_exec_function)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+//! Same as `scip-callgraph dead-code`
+
+use clap::Parser;
+use metrics_cli::commands::{dead_code, CommonArgs};
+
+/// Find unreachable exec/proof functions and orphaned spec functions
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(flatten)]
+    args: dead_code::Args,
+
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    cli.common.init_logger();
+    dead_code::run(&cli.args)
+}
@@ -1,16 +1,23 @@
 use scip_core::scip_utils::generate_scip_json_index;
-use scip_core::{build_call_graph, parse_scip_json, write_call_graph_as_atoms_json, Atom};
+use scip_core::{
+    build_call_graph, parse_atoms_json, parse_scip_json, sarif_log, write_call_graph_as_atoms_json,
+    Atom, SarifFinding, SarifLevel, SarifRule,
+};
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
 use std::path::Path;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let sarif_path = match args.iter().position(|arg| arg == "--sarif") {
+        Some(i) if i + 1 < args.len() => Some(args.drain(i..i + 2).nth(1).unwrap()),
+        _ => None,
+    };
 
     if args.len() < 2 {
         eprintln!(
-            "Usage: {} <path_to_rust_project> [path_to_scip_json]",
+            "Usage: {} <path_to_rust_project> [path_to_scip_json] [--sarif <output.sarif>]",
             args[0]
         );
         eprintln!("\nExamples:");
@@ -20,6 +27,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             args[0]
         );
         eprintln!("\nIf SCIP JSON path is not provided, it will be generated automatically.");
+        eprintln!("With --sarif, the findings are also written as SARIF for code review tools.");
         std::process::exit(1);
     }
 
@@ -173,6 +181,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     save_results_to_json(&unused_specs, &unused_proofs, &results_file)?;
     println!("\nResults saved to: {}", results_file);
 
+    if let Some(sarif_path) = sarif_path {
+        save_results_to_sarif(&unused_specs, &unused_proofs, &sarif_path)?;
+        println!("SARIF results saved to: {}", sarif_path);
+    }
+
     Ok(())
 }
 
@@ -185,8 +198,7 @@ fn find_unused_functions<F>(
 where
     F: Fn(&Atom) -> bool,
 {
-    let contents = fs::read_to_string(atoms_json_path)?;
-    let atoms: Vec<Atom> = serde_json::from_str(&contents)?;
+    let atoms = parse_atoms_json(atoms_json_path)?.atoms;
 
     // Build a map: identifier -> Atom
     let atom_map: HashMap<String, &Atom> = atoms
@@ -231,6 +243,7 @@ where
                 full_path: atom.full_path.clone(),
                 body: atom.body.clone(),
                 visibility: extract_visibility(&atom.body),
+                line: atom.start_line,
             })
         })
         .collect();
@@ -290,6 +303,51 @@ fn extract_visibility(body: &str) -> String {
     }
 }
 
+/// Save results as a SARIF log (notes, since unused specs and proofs may be
+/// false positives)
+fn save_results_to_sarif(
+    unused_specs: &[UnusedSpec],
+    unused_proofs: &[UnusedSpec],
+    output_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let rules = [
+        SarifRule {
+            id: "unused_spec",
+            description: "Spec function no other function depends on",
+            default_level: SarifLevel::Note,
+        },
+        SarifRule {
+            id: "unused_proof",
+            description: "Proof function no other function depends on",
+            default_level: SarifLevel::Note,
+        },
+    ];
+    let mut findings = Vec::new();
+    for (rule_id, kind, functions) in [
+        ("unused_spec", "Spec", unused_specs),
+        ("unused_proof", "Proof", unused_proofs),
+    ] {
+        let mut functions: Vec<&UnusedSpec> = functions.iter().collect();
+        functions.sort_by_key(|f| &f.identifier);
+        findings.extend(functions.into_iter().map(|function| SarifFinding {
+            rule_id,
+            level: SarifLevel::Note,
+            message: format!(
+                "{} function `{}` ({}) is not used by any other function",
+                kind, function.display_name, function.visibility
+            ),
+            relative_path: function.relative_path.clone(),
+            line: function.line.map(|line| line as u32),
+            symbol: Some(function.identifier.clone()),
+        }));
+    }
+    fs::write(
+        output_path,
+        serde_json::to_string_pretty(&sarif_log(&rules, &findings))?,
+    )?;
+    Ok(())
+}
+
 /// Save results to a JSON file
 fn save_results_to_json(
     unused_specs: &[UnusedSpec],
@@ -453,4 +511,6 @@ struct UnusedSpec {
     full_path: String,
     body: String,
     visibility: String,
+    /// 1-based first line of the definition
+    line: Option<usize>,
 }
@@ -0,0 +1,88 @@
+use clap::{Parser, ValueEnum};
+use log::info;
+use scip_core::logging::init_logger;
+use scip_core::{
+    build_call_graph_with_options, dominators, gatekeepers, parse_scip_json, CallGraphOptions,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Markdown,
+}
+
+/// Find the lemmas that dominate the largest share of the proof graph, from
+/// the dominator tree of the call graph
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Input SCIP JSON file
+    input_scip_json: String,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: ReportFormat,
+
+    /// Entry point, by symbol or function name (repeatable; default: every
+    /// project function nothing calls)
+    #[arg(long = "entry", value_name = "FUNCTION")]
+    entry: Vec<String>,
+
+    /// Only report functions dominating at least this share (0 to 1) of
+    /// the reachable functions
+    #[arg(long, default_value_t = 0.01)]
+    min_share: f64,
+
+    /// Report exec and spec functions too, not only proof functions
+    #[arg(long)]
+    all_modes: bool,
+
+    /// Leave out source files whose relative path matches this glob (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Enable debug logging
+    #[arg(short, long)]
+    debug: bool,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    init_logger(args.debug);
+
+    info!("Parsing SCIP JSON from {}...", args.input_scip_json);
+    let scip_data = parse_scip_json(&args.input_scip_json)?;
+    let options = CallGraphOptions::default().with_exclude_globs(args.exclude.clone());
+    let call_graph = build_call_graph_with_options(&scip_data, &options);
+    info!("Call graph contains {} functions", call_graph.len());
+
+    let tree = dominators(&call_graph, &args.entry);
+    if tree.roots.is_empty() {
+        return Err("no entry point found".into());
+    }
+    let report = gatekeepers(&call_graph, &tree, args.min_share, !args.all_modes);
+    info!(
+        "{} functions reachable from {} entry points, {} gatekeepers",
+        report.reachable,
+        report.roots.len(),
+        report.gatekeepers.len()
+    );
+
+    let rendered = match args.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        ReportFormat::Markdown => report.to_markdown(),
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            info!("✓ Wrote gatekeeper report to {}", path);
+        }
+        None => println!("{}", rendered),
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use scip_core::{build_call_graph, parse_scip_json, FunctionNode};
+use scip_core::{build_call_graph, parse_scip_json, CallGraph, SymbolNormalizer};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -41,19 +41,33 @@ fn check_command_exists(cmd: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn symbol_to_path(symbol: &str, display_name: &str) -> String {
-    scip_core::symbol_to_path(symbol, display_name)
-}
+fn convert_to_atoms_with_lines(call_graph: &CallGraph) -> Vec<AtomWithLines> {
+    let normalizer = SymbolNormalizer::new(
+        call_graph
+            .values()
+            .map(|node| (node.symbol.as_str(), node.display_name.as_str())),
+    );
+    for collision in normalizer.collisions() {
+        println!(
+            "⚠ {} functions share the path {}; suffixed to keep them apart",
+            collision.symbols.len(),
+            collision.identifier
+        );
+    }
+    let symbol_to_path = |symbol: &str| {
+        normalizer
+            .identifier(symbol)
+            .unwrap_or_default()
+            .to_string()
+    };
 
-fn convert_to_atoms_with_lines(call_graph: &HashMap<String, FunctionNode>) -> Vec<AtomWithLines> {
     call_graph
         .values()
         .map(|node| {
             let mut dependencies = HashMap::new();
             for callee in &node.callees {
-                if let Some(callee_node) = call_graph.get(callee) {
-                    let dep_path = symbol_to_path(&callee_node.symbol, &callee_node.display_name);
-                    dependencies.insert(dep_path, DependencyInfo { visible: true });
+                if call_graph.contains_key(callee) {
+                    dependencies.insert(symbol_to_path(callee), DependencyInfo { visible: true });
                 }
             }
 
@@ -73,7 +87,7 @@ fn convert_to_atoms_with_lines(call_graph: &HashMap<String, FunctionNode>) -> Ve
                 visible: true,
                 dependencies,
                 code_path: node.relative_path.clone(),
-                code_function: symbol_to_path(&node.symbol, &node.display_name),
+                code_function: symbol_to_path(&node.symbol),
                 code_text: CodeTextInfo {
                     lines_start,
                     lines_end,
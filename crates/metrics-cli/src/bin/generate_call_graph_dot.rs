@@ -1,7 +1,12 @@
 use clap::Parser;
-use log::{debug, info};
+use log::{debug, error, info};
+use metrics_cli::commands::DEFAULT_HIGHLIGHT_PREFIXES;
 use scip_core::logging::init_logger;
-use scip_core::{build_call_graph, generate_call_graph_dot, parse_scip_json};
+use scip_core::{
+    build_call_graph_with_options, collapse_sccs, generate_call_graph_dot,
+    generate_edge_kind_subgraph_dot, generate_module_graph_dot, link_dot_file, parse_scip_json,
+    CallGraphOptions, EdgeKind, NodeFilters, RenderBackend, SourceLinks, TooltipContent,
+};
 
 /// Generate call graph DOT files from SCIP data
 #[derive(Parser, Debug)]
@@ -13,6 +18,63 @@ struct Args {
     /// Output DOT file path
     output_dot_file: String,
 
+    /// Only include calls of these kinds, comma-separated
+    /// (e.g. `proof_to_proof` for the proof-to-lemma subgraph)
+    #[arg(long, value_delimiter = ',')]
+    edge_kinds: Vec<EdgeKind>,
+
+    /// Leave out source files whose relative path matches this glob
+    /// (repeatable, e.g. `--exclude '**/benches/**'`)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Draw functions whose crate or source path starts with this prefix as
+    /// project sources (repeatable; default: the libsignal crates)
+    #[arg(
+        long = "highlight-prefix",
+        value_name = "PREFIX",
+        default_values = DEFAULT_HIGHLIGHT_PREFIXES
+    )]
+    highlight_prefix: Vec<String>,
+
+    /// Leave out functions whose source path starts with this prefix (repeatable)
+    #[arg(long = "skip-path", value_name = "PREFIX")]
+    skip_path: Vec<String>,
+
+    /// Draw one node per source file, with edges weighted by call counts,
+    /// instead of one node per function
+    #[arg(long, conflicts_with = "edge_kinds")]
+    modules: bool,
+
+    /// Collapse each recursion cycle into a single node labeled with its size
+    #[arg(long)]
+    collapse_sccs: bool,
+
+    /// Draw all calls from one source directory to another as a single edge
+    /// labeled with the number of calls, keeping calls within a directory;
+    /// much faster to render on large graphs
+    #[arg(long, conflicts_with = "modules")]
+    aggregate_cluster_edges: bool,
+
+    /// GitHub repository URL to link function nodes to their source
+    /// (e.g., https://github.com/user/repo), with --rev
+    #[arg(long, requires = "rev")]
+    github_url: Option<String>,
+
+    /// Revision (branch, tag or commit) of the source links
+    #[arg(long, requires = "github_url")]
+    rev: Option<String>,
+
+    /// What node tooltips show of each function: none, signature, lines:N
+    /// (the first N lines of the body) or full (the whole body)
+    #[arg(long, default_value_t = TooltipContent::Signature)]
+    tooltips: TooltipContent,
+
+    /// How to render SVG/PNG images next to the DOT file: auto (Graphviz if
+    /// installed, else in process or DOT only), graphviz, in-process or none
+    #[arg(long, default_value_t = RenderBackend::Auto)]
+    render: RenderBackend,
+
     /// Enable debug logging
     #[arg(short, long)]
     debug: bool,
@@ -23,32 +85,53 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize logger based on debug flag
     init_logger(args.debug);
+    let render = args.render.resolve().unwrap_or_else(|e| {
+        error!("{e}");
+        std::process::exit(1);
+    });
 
     debug!("Parsing SCIP JSON from {}...", args.input_scip_json);
     let scip_data = parse_scip_json(&args.input_scip_json)?;
 
     debug!("Building call graph...");
-    let call_graph = build_call_graph(&scip_data);
+    let options = CallGraphOptions::default().with_exclude_globs(args.exclude.clone());
+    let call_graph = build_call_graph_with_options(&scip_data, &options);
     info!("Call graph contains {} functions", call_graph.len());
+    let call_graph = if args.collapse_sccs {
+        collapse_sccs(&call_graph)
+    } else {
+        call_graph
+    };
 
     debug!("Generating DOT file at {}...", args.output_dot_file);
-    generate_call_graph_dot(&call_graph, &args.output_dot_file)?;
+    let filters = NodeFilters::from_prefixes(&args.highlight_prefix, &args.skip_path)
+        .render(render)
+        .aggregate_cluster_edges(args.aggregate_cluster_edges)
+        .tooltips(args.tooltips);
+    if args.modules {
+        generate_module_graph_dot(&call_graph, &args.output_dot_file, &filters)?;
+    } else if args.edge_kinds.is_empty() {
+        generate_call_graph_dot(&call_graph, &args.output_dot_file, &filters)?;
+    } else {
+        generate_edge_kind_subgraph_dot(
+            &call_graph,
+            &args.edge_kinds,
+            &args.output_dot_file,
+            &filters,
+        )?;
+    }
+    if let (Some(github_url), Some(rev), false) = (&args.github_url, &args.rev, args.modules) {
+        debug!("Linking functions to {github_url} at {rev}...");
+        let links = SourceLinks::new(github_url, rev);
+        link_dot_file(&args.output_dot_file, &call_graph, &links, render)?;
+    }
 
     // Show the actual filenames that were created
-    let svg_name = if let Some(stripped) = args.output_dot_file.strip_suffix(".dot") {
-        format!("{stripped}.svg")
-    } else {
-        format!("{}.svg", args.output_dot_file)
-    };
-    let png_name = if let Some(stripped) = args.output_dot_file.strip_suffix(".dot") {
-        format!("{stripped}.png")
-    } else {
-        format!("{}.png", args.output_dot_file)
-    };
     info!("✓ Generated files:");
     info!("  • {}", args.output_dot_file);
-    info!("  • {svg_name}");
-    info!("  • {png_name}");
+    for path in render.output_paths(&args.output_dot_file) {
+        info!("  • {path}");
+    }
 
     Ok(())
 }
@@ -1,9 +1,12 @@
 // filepath: /home/lacra/git_repos/baif/scip-callgraph/src/bin/generate_file_subgraph_dot.rs
 use log::{debug, error, info, warn};
 use scip_core::logging::{init_logger, should_enable_debug};
-use scip_core::{build_call_graph, parse_scip_json, FunctionNode};
+use scip_core::{
+    build_call_graph, collapse_sccs, collect_neighborhood, parse_scip_json, render_dot_file,
+    CallGraph, Error, FunctionNode, RenderBackend, TooltipContent,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::env;
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -21,7 +24,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
     if args.len() < 4 {
         eprintln!(
-            "Usage: {} <input-scip-json> <file-path> <output-dot-file> [verification-report.json] [--debug|-d]",
+            "Usage: {} <input-scip-json> <file-path> <output-dot-file> [verification-report.json] [--depth N] [--collapse-sccs] [--render auto|graphviz|in-process|none] [--tooltips none|signature|lines:N|full] [--debug|-d]",
             args[0]
         );
         eprintln!(
@@ -46,12 +49,68 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
+    // Number of caller/callee hops around the file (1st degree by default)
+    let depth = match args.iter().position(|arg| arg == "--depth") {
+        Some(i) => match args
+            .get(i + 1)
+            .and_then(|value| value.parse::<usize>().ok())
+        {
+            Some(depth) => depth,
+            None => {
+                eprintln!("--depth expects a number of calls");
+                std::process::exit(1);
+            }
+        },
+        None => 1,
+    };
+
+    // How to render the SVG and PNG next to the DOT file
+    let render = match args.iter().position(|arg| arg == "--render") {
+        Some(i) => match args.get(i + 1).map(|value| value.parse::<RenderBackend>()) {
+            Some(Ok(render)) => render,
+            Some(Err(e)) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            None => {
+                eprintln!("--render expects auto, graphviz, in-process or none");
+                std::process::exit(1);
+            }
+        },
+        None => RenderBackend::Auto,
+    };
+    let render = render.resolve().unwrap_or_else(|e| {
+        error!("{e}");
+        std::process::exit(1);
+    });
+
+    // What node tooltips show of each function (its signature by default)
+    let tooltips = match args.iter().position(|arg| arg == "--tooltips") {
+        Some(i) => match args.get(i + 1).map(|value| value.parse::<TooltipContent>()) {
+            Some(Ok(tooltips)) => tooltips,
+            Some(Err(e)) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            None => {
+                eprintln!("--tooltips expects none, signature, lines:N or full");
+                std::process::exit(1);
+            }
+        },
+        None => TooltipContent::Signature,
+    };
+
     debug!("Parsing SCIP JSON from {input_path}...");
     let scip_data = parse_scip_json(input_path)?;
 
     debug!("Building call graph...");
     let call_graph = build_call_graph(&scip_data);
     info!("Call graph contains {} functions", call_graph.len());
+    let call_graph = if args.iter().any(|arg| arg == "--collapse-sccs") {
+        collapse_sccs(&call_graph)
+    } else {
+        call_graph
+    };
 
     // Parse verification report if provided
     let verification_status = if let Some(report_path) = verification_report_path {
@@ -79,10 +138,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         &call_graph,
         file_path,
         output_path,
+        depth,
         &verification_status,
+        render,
+        tooltips,
     ) {
         Ok(_) => {
-            info!("File subgraph DOT and SVG files generated successfully!");
+            info!("✓ Generated files:");
+            info!("  • {output_path}");
+            for path in render.output_paths(output_path) {
+                info!("  • {path}");
+            }
         }
         Err(e) => {
             error!("Failed to generate file subgraph: {e}");
@@ -114,11 +180,14 @@ fn load_verification_report(path: &str) -> Result<VerificationResult, Box<dyn st
 }
 
 fn generate_file_subgraph_dot_with_verification(
-    call_graph: &HashMap<String, FunctionNode>,
+    call_graph: &CallGraph,
     file_path: &str,
     output_path: &str,
+    depth: usize,
     verification_status: &Option<VerificationResult>,
-) -> std::io::Result<()> {
+    render: RenderBackend,
+    tooltips: TooltipContent,
+) -> scip_core::Result<()> {
     use std::path::Path;
 
     let mut dot = String::from("digraph file_subgraph {\n");
@@ -155,26 +224,18 @@ fn generate_file_subgraph_dot_with_verification(
 
     if file_nodes.is_empty() {
         // List available paths that contain part of the requested path
-        let matching_paths: HashSet<_> = call_graph
+        let mut similar_paths: Vec<String> = call_graph
             .values()
             .filter(|node| node.file_path.contains(file_path))
-            .map(|node| &node.file_path)
+            .map(|node| node.file_path.clone())
             .collect();
+        similar_paths.sort();
+        similar_paths.dedup();
 
-        if !matching_paths.is_empty() {
-            let mut message = format!(
-                "No exact match for file path: {file_path}\n\nHere are some similar paths:\n"
-            );
-            for path in matching_paths {
-                message.push_str(&format!("  {path}\n"));
-            }
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, message));
-        }
-
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("No functions found in file path: {file_path}"),
-        ));
+        return Err(Error::FileNotFound {
+            paths: vec![file_path.to_string()],
+            similar_paths,
+        });
     }
 
     debug!("Found {} functions in file {}", file_nodes.len(), file_path);
@@ -185,32 +246,18 @@ fn generate_file_subgraph_dot_with_verification(
     // Get the symbols of nodes in the file
     let file_symbols: HashSet<String> = file_nodes.iter().map(|n| n.symbol.clone()).collect();
 
-    // Nodes that are called by or call into nodes from this file (1st degree connections)
-    let mut connected_symbols = HashSet::new();
-    for node in &file_nodes {
-        // Add callees
-        for callee in &node.callees {
-            connected_symbols.insert(callee.clone());
-        }
-        // Add callers
-        for caller in &node.callers {
-            connected_symbols.insert(caller.clone());
-        }
-    }
+    // Nodes within `depth` calls of nodes from this file, in either direction
+    let connected_symbols =
+        collect_neighborhood(call_graph, &file_symbols, true, true, Some(depth));
+    debug!(
+        "Found {} functions within {depth} call(s) of the file",
+        connected_symbols.len() - file_symbols.len()
+    );
 
     // Draw file nodes with verification-based colors
     for node in &file_nodes {
         let label = node.display_name.clone();
-        let tooltip = if let Some(body) = &node.body {
-            let plain = body.replace(['\n', '\r'], " ").replace('"', "' ");
-            if plain.len() > 200 {
-                format!("{}...", &plain[..200])
-            } else {
-                plain
-            }
-        } else {
-            "".to_string()
-        };
+        let tooltip = tooltips.dot_tooltip(node);
 
         // Determine color based on verification status
         let fillcolor = if verified_functions.contains(&node.display_name) {
@@ -252,20 +299,15 @@ fn generate_file_subgraph_dot_with_verification(
 
     dot.push('\n');
 
-    // Draw edges from file nodes to their callees
-    for node in &file_nodes {
-        for callee in &node.callees {
-            if file_symbols.contains(callee) || connected_symbols.contains(callee) {
-                dot.push_str(&format!("  \"{}\" -> \"{}\"\n", node.symbol, callee));
-            }
-        }
-    }
-
-    // Draw edges from callers to file nodes
-    for node in &file_nodes {
-        for caller in &node.callers {
-            if !file_symbols.contains(caller) && connected_symbols.contains(caller) {
-                dot.push_str(&format!("  \"{}\" -> \"{}\"\n", caller, node.symbol));
+    // Draw the calls between all drawn nodes
+    let mut drawn_symbols: Vec<&String> = connected_symbols.iter().collect();
+    drawn_symbols.sort();
+    for symbol in drawn_symbols {
+        if let Some(node) = call_graph.get(symbol) {
+            for callee in &node.callees {
+                if connected_symbols.contains(callee) {
+                    dot.push_str(&format!("  \"{}\" -> \"{}\"\n", node.symbol, callee));
+                }
             }
         }
     }
@@ -273,36 +315,6 @@ fn generate_file_subgraph_dot_with_verification(
     dot.push_str("}\n");
     // Write the DOT file
     std::fs::write(output_path, &dot)?;
-    // Generate SVG and PNG using Graphviz
-    let svg_path = if let Some(stripped) = output_path.strip_suffix(".dot") {
-        format!("{stripped}.svg")
-    } else {
-        format!("{output_path}.svg")
-    };
-    let png_path = if let Some(stripped) = output_path.strip_suffix(".dot") {
-        format!("{stripped}.png")
-    } else {
-        format!("{output_path}.png")
-    };
-
-    // Generate SVG
-    let svg_status = std::process::Command::new("dot")
-        .args(["-Tsvg", output_path, "-o", &svg_path])
-        .status()?;
-    if !svg_status.success() {
-        return Err(std::io::Error::other(format!(
-            "Failed to generate SVG: dot exited with {svg_status}"
-        )));
-    }
-
-    // Generate PNG
-    let png_status = std::process::Command::new("dot")
-        .args(["-Tpng", output_path, "-o", &png_path])
-        .status()?;
-    if !png_status.success() {
-        return Err(std::io::Error::other(format!(
-            "Failed to generate PNG: dot exited with {png_status}"
-        )));
-    }
-    Ok(())
+    // Generate SVG and PNG
+    render_dot_file(output_path, render)
 }
@@ -1,7 +1,11 @@
 use clap::Parser;
 use log::{debug, error, info};
+use metrics_cli::commands::DEFAULT_HIGHLIGHT_PREFIXES;
 use scip_core::logging::init_logger;
-use scip_core::{build_call_graph, generate_files_subgraph_dot, parse_scip_json};
+use scip_core::{
+    build_call_graph, collapse_sccs, generate_files_subgraph_dot, link_dot_file, parse_scip_json,
+    NodeFilters, RenderBackend, SourceLinks,
+};
 
 /// Generate files subgraph DOT files from SCIP data
 #[derive(Parser, Debug)]
@@ -17,6 +21,43 @@ struct Args {
     #[arg(required = true)]
     file_paths: Vec<String>,
 
+    /// Draw functions whose crate or source path starts with this prefix as
+    /// project sources (repeatable; default: the libsignal crates)
+    #[arg(
+        long = "highlight-prefix",
+        value_name = "PREFIX",
+        default_values = DEFAULT_HIGHLIGHT_PREFIXES
+    )]
+    highlight_prefix: Vec<String>,
+
+    /// Leave out functions whose source path starts with this prefix (repeatable)
+    #[arg(long = "skip-path", value_name = "PREFIX")]
+    skip_path: Vec<String>,
+
+    /// Collapse each recursion cycle into a single node labeled with its size
+    #[arg(long)]
+    collapse_sccs: bool,
+
+    /// Draw all calls from one file to another as a single edge labeled
+    /// with the number of calls, keeping calls within a file;
+    /// much faster to render on large graphs
+    #[arg(long)]
+    aggregate_cluster_edges: bool,
+
+    /// GitHub repository URL to link function nodes to their source
+    /// (e.g., https://github.com/user/repo), with --rev
+    #[arg(long, requires = "rev")]
+    github_url: Option<String>,
+
+    /// Revision (branch, tag or commit) of the source links
+    #[arg(long, requires = "github_url")]
+    rev: Option<String>,
+
+    /// How to render SVG/PNG images next to the DOT file: auto (Graphviz if
+    /// installed, else in process or DOT only), graphviz, in-process or none
+    #[arg(long, default_value_t = RenderBackend::Auto)]
+    render: RenderBackend,
+
     /// Enable debug logging
     #[arg(short, long)]
     debug: bool,
@@ -27,6 +68,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize logger based on debug flag
     init_logger(args.debug);
+    let render = args.render.resolve().unwrap_or_else(|e| {
+        error!("{e}");
+        std::process::exit(1);
+    });
 
     debug!("Parsing SCIP JSON from {}...", args.input_scip_json);
     let scip_data = parse_scip_json(&args.input_scip_json)?;
@@ -34,6 +79,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     debug!("Building call graph...");
     let call_graph = build_call_graph(&scip_data);
     info!("Call graph contains {} functions", call_graph.len());
+    let call_graph = if args.collapse_sccs {
+        collapse_sccs(&call_graph)
+    } else {
+        call_graph
+    };
 
     debug!(
         "Generating subgraph DOT file for {} files at {}...",
@@ -41,23 +91,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         args.output_dot_file
     );
 
-    match generate_files_subgraph_dot(&call_graph, &args.file_paths, &args.output_dot_file) {
+    let filters = NodeFilters::from_prefixes(&args.highlight_prefix, &args.skip_path)
+        .render(render)
+        .aggregate_cluster_edges(args.aggregate_cluster_edges);
+    match generate_files_subgraph_dot(
+        &call_graph,
+        &args.file_paths,
+        &args.output_dot_file,
+        &filters,
+    ) {
         Ok(_) => {
+            if let (Some(github_url), Some(rev)) = (&args.github_url, &args.rev) {
+                let links = SourceLinks::new(github_url, rev);
+                link_dot_file(&args.output_dot_file, &call_graph, &links, render)?;
+            }
+
             // Show the actual filenames that were created
-            let svg_name = if let Some(stripped) = args.output_dot_file.strip_suffix(".dot") {
-                format!("{stripped}.svg")
-            } else {
-                format!("{}.svg", args.output_dot_file)
-            };
-            let png_name = if let Some(stripped) = args.output_dot_file.strip_suffix(".dot") {
-                format!("{stripped}.png")
-            } else {
-                format!("{}.png", args.output_dot_file)
-            };
             info!("✓ Generated files:");
             info!("  • {}", args.output_dot_file);
-            info!("  • {svg_name}");
-            info!("  • {png_name}");
+            for path in render.output_paths(&args.output_dot_file) {
+                info!("  • {path}");
+            }
         }
         Err(e) => {
             error!("Failed to generate files subgraph: {e}");
@@ -1,7 +1,12 @@
 use clap::Parser;
 use log::{debug, error, info};
+use metrics_cli::commands::DEFAULT_HIGHLIGHT_PREFIXES;
 use scip_core::logging::init_logger;
-use scip_core::{build_call_graph, generate_function_subgraph_dot, parse_scip_json};
+use scip_core::{
+    build_call_graph, collapse_sccs, function_subgraph_output_path,
+    generate_function_subgraph_dot_with_paths, link_dot_file, parse_scip_json, NodeFilters,
+    RenderBackend, SourceLinks, TooltipContent,
+};
 
 /// Generate function subgraph DOT files from SCIP data
 #[derive(Parser, Debug)]
@@ -14,9 +19,10 @@ struct Args {
     #[arg(required = true)]
     function_names: Vec<String>,
 
-    /// Filter out non-libsignal sources
-    #[arg(long)]
-    filter_non_libsignal_sources: bool,
+    /// With --include-callers only, keep just the callers reachable from
+    /// highlighted sources (see --highlight-prefix)
+    #[arg(long, alias = "filter-non-libsignal-sources")]
+    highlighted_sources_only: bool,
 
     /// Include callees in the subgraph
     #[arg(long)]
@@ -30,6 +36,62 @@ struct Args {
     #[arg(long)]
     depth: Option<usize>,
 
+    /// Highlight call paths from the given functions to this function
+    #[arg(long, value_name = "FUNCTION")]
+    highlight_paths_to: Option<String>,
+
+    /// Maximum number of call paths to highlight
+    #[arg(long, default_value_t = 10)]
+    max_paths: usize,
+
+    /// Include the trust base of the given functions (assumes, admits,
+    /// external_body, axioms, unverified and external code they rely on
+    /// through proof and spec calls) and draw its functions in orange
+    #[arg(long)]
+    highlight_untrusted: bool,
+
+    /// Draw functions whose crate or source path starts with this prefix as
+    /// project sources (repeatable; default: the libsignal crates)
+    #[arg(
+        long = "highlight-prefix",
+        value_name = "PREFIX",
+        default_values = DEFAULT_HIGHLIGHT_PREFIXES
+    )]
+    highlight_prefix: Vec<String>,
+
+    /// Leave out functions whose source path starts with this prefix (repeatable)
+    #[arg(long = "skip-path", value_name = "PREFIX")]
+    skip_path: Vec<String>,
+
+    /// Draw at most this many nodes: beyond it, the functions farthest from
+    /// the given ones (and least central) are collapsed into one
+    /// "… N more in module X" node per module
+    #[arg(long, value_name = "N")]
+    max_nodes: Option<usize>,
+
+    /// Collapse each recursion cycle into a single node labeled with its size
+    #[arg(long)]
+    collapse_sccs: bool,
+
+    /// GitHub repository URL to link function nodes to their source
+    /// (e.g., https://github.com/user/repo), with --rev
+    #[arg(long, requires = "rev")]
+    github_url: Option<String>,
+
+    /// Revision (branch, tag or commit) of the source links
+    #[arg(long, requires = "github_url")]
+    rev: Option<String>,
+
+    /// What node tooltips show of each function: none, signature, lines:N
+    /// (the first N lines of the body) or full (the whole body)
+    #[arg(long, default_value_t = TooltipContent::Signature)]
+    tooltips: TooltipContent,
+
+    /// How to render SVG/PNG images next to the DOT file: auto (Graphviz if
+    /// installed, else in process or DOT only), graphviz, in-process or none
+    #[arg(long, default_value_t = RenderBackend::Auto)]
+    render: RenderBackend,
+
     /// Enable debug logging
     #[arg(short, long)]
     debug: bool,
@@ -40,6 +102,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize logger based on debug flag
     init_logger(args.debug);
+    let render = args.render.resolve().unwrap_or_else(|e| {
+        error!("{e}");
+        std::process::exit(1);
+    });
 
     // Generate output filename from function names and depth
     // Sanitize function names for use in filename
@@ -75,6 +141,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     debug!("Building call graph...");
     let call_graph = build_call_graph(&scip_data);
     info!("Call graph contains {} functions", call_graph.len());
+    let call_graph = if args.collapse_sccs {
+        collapse_sccs(&call_graph)
+    } else {
+        call_graph
+    };
 
     debug!(
         "Generating function subgraph DOT file for {} functions as {}...",
@@ -89,31 +160,43 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         debug!("No depth limit");
     }
 
-    match generate_function_subgraph_dot(
+    if let Some(target) = &args.highlight_paths_to {
+        debug!(
+            "Highlighting up to {} call paths to {target}",
+            args.max_paths
+        );
+    }
+
+    let filters = NodeFilters::from_prefixes(&args.highlight_prefix, &args.skip_path)
+        .render(render)
+        .tooltips(args.tooltips);
+    match generate_function_subgraph_dot_with_paths(
         &call_graph,
         &args.function_names,
         &output_dot_file,
         args.include_callees,
         args.include_callers,
         args.depth,
-        args.filter_non_libsignal_sources,
+        args.highlighted_sources_only,
+        args.highlight_paths_to.as_deref(),
+        args.max_paths,
+        args.highlight_untrusted,
+        args.max_nodes,
+        &filters,
     ) {
         Ok(_) => {
+            let output_dot_file = function_subgraph_output_path(&output_dot_file, args.depth);
+            if let (Some(github_url), Some(rev)) = (&args.github_url, &args.rev) {
+                let links = SourceLinks::new(github_url, rev);
+                link_dot_file(&output_dot_file, &call_graph, &links, render)?;
+            }
+
             // Show the actual filenames that were created
-            let svg_name = if let Some(stripped) = output_dot_file.strip_suffix(".dot") {
-                format!("{stripped}.svg")
-            } else {
-                format!("{output_dot_file}.svg")
-            };
-            let png_name = if let Some(stripped) = output_dot_file.strip_suffix(".dot") {
-                format!("{stripped}.png")
-            } else {
-                format!("{output_dot_file}.png")
-            };
             info!("✓ Generated files:");
             info!("  • {output_dot_file}");
-            info!("  • {svg_name}");
-            info!("  • {png_name}");
+            for path in render.output_paths(&output_dot_file) {
+                info!("  • {path}");
+            }
         }
         Err(e) => {
             error!("Failed to generate function subgraph: {e}");
@@ -1,24 +1,21 @@
-use scip_core::scip_utils::generate_scip_json_index;
-use std::env;
+//! Same as `scip-callgraph index`
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <path_to_folder>", args[0]);
-        eprintln!("Example: {} /path/to/rust/project", args[0]);
-        std::process::exit(1);
-    }
+use clap::Parser;
+use metrics_cli::commands::{index, CommonArgs};
+
+/// Generate a SCIP JSON index of a Rust project
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(flatten)]
+    args: index::Args,
 
-    let folder_path = &args[1];
+    #[command(flatten)]
+    common: CommonArgs,
+}
 
-    match generate_scip_json_index(folder_path) {
-        Ok(output_file) => {
-            println!("\nDone! Output file: {}", output_file);
-            Ok(())
-        }
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
-        }
-    }
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    cli.common.init_logger();
+    index::run(&cli.args)
 }
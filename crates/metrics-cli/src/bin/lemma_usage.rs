@@ -0,0 +1,71 @@
+use clap::Parser;
+use log::info;
+use scip_core::logging::init_logger;
+use scip_core::{build_call_graph_with_options, lemma_usage, parse_scip_json, CallGraphOptions};
+use std::path::Path;
+
+/// Report calls into external crates and the most used vstd lemmas per module
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Input SCIP JSON file
+    input_scip_json: String,
+
+    /// Output JSON file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Also write the per-module rankings as CSV
+    #[arg(long, value_name = "FILE")]
+    csv: Option<String>,
+
+    /// Maximum number of lemmas listed per module
+    #[arg(long, default_value_t = 20)]
+    top: usize,
+
+    /// Leave out source files whose relative path matches this glob (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Enable debug logging
+    #[arg(short, long)]
+    debug: bool,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    init_logger(args.debug);
+
+    info!("Parsing SCIP JSON from {}...", args.input_scip_json);
+    let scip_data = parse_scip_json(&args.input_scip_json)?;
+    let options = CallGraphOptions::default().with_exclude_globs(args.exclude.clone());
+    let call_graph = build_call_graph_with_options(&scip_data, &options);
+    info!("Call graph contains {} functions", call_graph.len());
+
+    let mut report = lemma_usage(&call_graph);
+    for usage in &report.crates {
+        info!(
+            "{}: {} calls to {} functions",
+            usage.crate_name, usage.uses, usage.functions
+        );
+    }
+
+    if let Some(path) = &args.csv {
+        report.write_csv(Path::new(path), args.top)?;
+        info!("✓ Wrote per-module lemma rankings to {}", path);
+    }
+
+    for module in &mut report.modules {
+        module.lemmas.truncate(args.top);
+    }
+    let rendered = serde_json::to_string_pretty(&report)?;
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            info!("✓ Wrote lemma usage report to {}", path);
+        }
+        None => println!("{}", rendered),
+    }
+
+    Ok(())
+}
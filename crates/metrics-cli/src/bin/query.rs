@@ -0,0 +1,239 @@
+//! Interactive call graph queries.
+//!
+//! Loads an atoms JSON file or a SCIP index once, then answers queries read
+//! from stdin, one per line:
+//!
+//! ```text
+//! callers foo [--depth N]
+//! callees foo [--depth N]
+//! path foo bar [--max-paths N]
+//! mode foo
+//! ```
+
+use clap::{Parser, Subcommand};
+use log::info;
+use scip_core::logging::init_logger;
+use scip_core::{
+    detect_decl_kind, find_call_paths, load_call_graph, symbol_to_path, CallGraph, FunctionNode,
+    SymbolResolver,
+};
+use std::collections::{HashSet, VecDeque};
+use std::io::{BufRead, IsTerminal, Write};
+
+/// Answer repeated call graph queries without re-parsing the index
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Atoms JSON (from write_atoms), SCIP JSON, or binary `index.scip` file
+    input: String,
+
+    /// Enable debug logging
+    #[arg(short, long)]
+    debug: bool,
+}
+
+/// Call graph queries, one per line
+#[derive(Parser, Debug)]
+#[command(
+    name = "query",
+    no_binary_name = true,
+    disable_version_flag = true,
+    override_usage = "<COMMAND> [ARGS]"
+)]
+struct QueryLine {
+    #[command(subcommand)]
+    query: Query,
+}
+
+#[derive(Subcommand, Debug)]
+enum Query {
+    /// Functions calling FUNCTION
+    Callers {
+        function: String,
+        /// Follow callers transitively up to this depth
+        #[arg(long, default_value_t = 1)]
+        depth: usize,
+    },
+    /// Functions called by FUNCTION
+    Callees {
+        function: String,
+        /// Follow callees transitively up to this depth
+        #[arg(long, default_value_t = 1)]
+        depth: usize,
+    },
+    /// Call chains from FROM to TO
+    Path {
+        from: String,
+        to: String,
+        /// Maximum number of paths to show
+        #[arg(long, default_value_t = 5)]
+        max_paths: usize,
+    },
+    /// Verus mode (exec/proof/spec) of FUNCTION
+    Mode { function: String },
+    /// Exit
+    #[command(alias = "exit")]
+    Quit,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    init_logger(args.debug);
+
+    info!("Loading {}...", args.input);
+    let call_graph = load_call_graph(&args.input)?;
+    let resolver = SymbolResolver::from_call_graph(&call_graph);
+    let edge_count: usize = call_graph.values().map(|node| node.callees.len()).sum();
+    eprintln!(
+        "Loaded {} functions, {} calls. Type `help` for commands, `quit` to exit.",
+        call_graph.len(),
+        edge_count
+    );
+
+    let interactive = std::io::stdin().is_terminal();
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            std::io::stdout().flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+
+        match QueryLine::try_parse_from(&words) {
+            Ok(QueryLine { query: Query::Quit }) => break,
+            Ok(QueryLine { query }) => run_query(&call_graph, &resolver, query),
+            Err(e) => {
+                // Help and usage errors are rendered by clap
+                let _ = e.print();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_query(call_graph: &CallGraph, resolver: &SymbolResolver, query: Query) {
+    match query {
+        Query::Callers { function, depth } => {
+            for node in resolve(call_graph, resolver, &function) {
+                println!("Callers of {}:", label(node));
+                print_reachable(call_graph, node, depth, |n| &n.callers);
+            }
+        }
+        Query::Callees { function, depth } => {
+            for node in resolve(call_graph, resolver, &function) {
+                println!("Callees of {}:", label(node));
+                print_reachable(call_graph, node, depth, |n| &n.callees);
+            }
+        }
+        Query::Path {
+            from,
+            to,
+            max_paths,
+        } => {
+            let sources = resolve(call_graph, resolver, &from);
+            let targets = resolve(call_graph, resolver, &to);
+            let mut found = 0;
+            for source in &sources {
+                for target in &targets {
+                    for path in
+                        find_call_paths(call_graph, &source.symbol, &target.symbol, max_paths, None)
+                    {
+                        let names: Vec<String> = path
+                            .iter()
+                            .map(|symbol| label(&call_graph[symbol]))
+                            .collect();
+                        println!("  {}", names.join(" -> "));
+                        found += 1;
+                    }
+                }
+            }
+            if found == 0 && !sources.is_empty() && !targets.is_empty() {
+                println!("No call path from '{from}' to '{to}'");
+            }
+        }
+        Query::Mode { function } => {
+            for node in resolve(call_graph, resolver, &function) {
+                match node.body.as_deref() {
+                    Some(body) => println!("{}: {}", label(node), detect_decl_kind(body).as_str()),
+                    None => println!("{}: unknown (no body)", label(node)),
+                }
+            }
+        }
+        Query::Quit => {}
+    }
+}
+
+/// Find the nodes matching a user-supplied name, reporting when there are
+/// none (with suggestions for typos)
+fn resolve<'a>(
+    call_graph: &'a CallGraph,
+    resolver: &SymbolResolver,
+    name: &str,
+) -> Vec<&'a FunctionNode> {
+    match resolver.resolve_all(name) {
+        Ok(symbols) => symbols
+            .into_iter()
+            .map(|symbol| &call_graph[symbol])
+            .collect(),
+        Err(e) => {
+            println!("{e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Breadth-first listing of nodes reachable via `next`, tagged with their depth
+fn print_reachable<F>(call_graph: &CallGraph, start: &FunctionNode, max_depth: usize, next: F)
+where
+    F: Fn(&FunctionNode) -> &HashSet<String>,
+{
+    let mut visited: HashSet<&str> = HashSet::from([start.symbol.as_str()]);
+    let mut queue: VecDeque<(&FunctionNode, usize)> = VecDeque::from([(start, 0)]);
+    let mut found = false;
+
+    while let Some((node, depth)) = queue.pop_front() {
+        if depth == max_depth {
+            continue;
+        }
+        let mut neighbors: Vec<&FunctionNode> = next(node)
+            .iter()
+            .filter_map(|symbol| call_graph.get(symbol))
+            .filter(|neighbor| visited.insert(neighbor.symbol.as_str()))
+            .collect();
+        neighbors.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+
+        for neighbor in neighbors {
+            found = true;
+            println!(
+                "  [{}] {} ({})",
+                depth + 1,
+                label(neighbor),
+                neighbor.relative_path
+            );
+            queue.push_back((neighbor, depth + 1));
+        }
+    }
+
+    if !found {
+        println!("  (none)");
+    }
+}
+
+/// Readable name for a node
+fn label(node: &FunctionNode) -> String {
+    if node.symbol.contains(' ') {
+        symbol_to_path(&node.symbol, &node.display_name)
+    } else {
+        // Atoms are already keyed by their readable identifier
+        node.symbol.clone()
+    }
+}
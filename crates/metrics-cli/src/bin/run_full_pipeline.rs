@@ -166,13 +166,19 @@ fn main() {
 
     // Step 5: Enrich CSV with all spec + proof metrics
     println!("───────────────────────────────────────────────────────────────");
-    println!("STEP 5: Enrich CSV with spec + proof metrics");
+    println!("STEP 5: Enrich CSV with proof difficulty and spec + proof metrics");
     println!("───────────────────────────────────────────────────────────────");
     let step5_start = Instant::now();
 
     let status = run_binary(
         "enrich_csv_complete",
-        &[&step3_proofs, &proof_csv, &step4_code, &final_csv],
+        &[
+            &step3_proofs,
+            &step4_code,
+            &final_csv,
+            "--proof-difficulty",
+            &proof_csv,
+        ],
     );
     if !status {
         eprintln!("❌ Step 5 failed!");
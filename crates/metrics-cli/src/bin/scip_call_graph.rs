@@ -1,7 +1,8 @@
 use clap::{Parser, Subcommand};
 use scip_core::{
-    build_call_graph, generate_call_graph_dot_string, generate_filtered_call_graph,
-    parse_scip_json, print_call_graph_summary,
+    build_call_graph, collapse_sccs, detect_decl_kind, find_sccs, generate_call_graph_dot_string,
+    generate_filtered_call_graph, parse_scip_json, print_call_graph_summary,
+    resolve_entry_profiles, DeclKind, EntryProfile, NodeFilters, SymbolResolver,
 };
 use std::fs::File;
 use std::io::Write;
@@ -22,17 +23,42 @@ enum Commands {
         scip_json_file: String,
         /// Output DOT file path (optional, prints to stdout if not provided)
         output_dot_file: Option<String>,
+        /// Collapse each recursion cycle into a single node labeled with its size
+        #[arg(long)]
+        collapse_sccs: bool,
     },
     /// Generate a filtered call graph starting from a specific function
+    /// or from the entry points of `--entry` profiles
     Filter {
         /// Input SCIP JSON file path
         scip_json_file: String,
         /// Function name to start filtering from
-        function_name: String,
+        #[arg(required_unless_present = "entry")]
+        function_name: Option<String>,
         /// Output DOT file path (optional, prints to stdout if not provided)
         output_dot_file: Option<String>,
         /// Maximum depth for traversal
         max_depth: Option<usize>,
+        /// Also start from every function of an entry-point profile: `main`,
+        /// `pub-api` (pub exec functions), `tests` or `proof-roots` (proof
+        /// functions no other proof calls); repeatable
+        #[arg(long, value_name = "PROFILE")]
+        entry: Vec<EntryProfile>,
+        /// Output DOT file path, for when no function name is given
+        #[arg(short, long, conflicts_with = "output_dot_file")]
+        output: Option<String>,
+        /// Collapse each recursion cycle into a single node labeled with its size
+        #[arg(long)]
+        collapse_sccs: bool,
+    },
+    /// Report recursion cycles (strongly connected components) involving proof functions
+    #[command(name = "detect-cycles", alias = "detect_cycles")]
+    DetectCycles {
+        /// Input SCIP JSON file path
+        scip_json_file: String,
+        /// Report all cycles, not just those involving proof functions
+        #[arg(long)]
+        all: bool,
     },
 }
 
@@ -43,19 +69,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Generate {
             scip_json_file,
             output_dot_file,
+            collapse_sccs: collapse,
         } => {
             // Parse SCIP JSON data
             let scip_data = parse_scip_json(&scip_json_file)?;
 
             // Build the call graph
             let call_graph = build_call_graph(&scip_data);
+            let call_graph = if collapse {
+                collapse_sccs(&call_graph)
+            } else {
+                call_graph
+            };
 
             // Print summary
             println!("Call graph generated from {scip_json_file}");
             print_call_graph_summary(&call_graph);
 
             // Generate DOT content
-            let dot_content = generate_call_graph_dot_string(&call_graph);
+            let dot_content = generate_call_graph_dot_string(&call_graph, &NodeFilters::default());
 
             if let Some(path) = output_dot_file {
                 let mut file = File::create(&path)?;
@@ -72,6 +104,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             function_name,
             output_dot_file,
             max_depth,
+            entry,
+            output,
+            collapse_sccs: collapse,
         } => {
             // Parse SCIP JSON data
             let scip_data = parse_scip_json(&scip_json_file)?;
@@ -79,48 +114,59 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Build the full call graph
             let full_graph = build_call_graph(&scip_data);
 
-            // Find possible matches for the entry point
-            let mut matching_entries = Vec::new();
-            for (symbol, node) in &full_graph {
-                if symbol.contains(&function_name) || node.display_name.contains(&function_name) {
-                    matching_entries.push(symbol.clone());
-                }
+            let mut entry_points = resolve_entry_profiles(&full_graph, &entry);
+            if !entry.is_empty() {
+                let profiles: Vec<&str> = entry.iter().map(|profile| profile.as_str()).collect();
+                println!(
+                    "{} entry points from profiles: {}",
+                    entry_points.len(),
+                    profiles.join(", ")
+                );
             }
 
-            if matching_entries.is_empty() {
-                println!("Error: No functions matching '{function_name}' found.");
-                return Ok(());
-            }
-
-            // If we have multiple matches, show them and let user be more specific
-            if matching_entries.len() > 1 {
-                println!("Multiple functions match '{function_name}'. Please be more specific:");
-                for (i, symbol) in matching_entries.iter().enumerate() {
-                    if let Some(node) = full_graph.get(symbol) {
-                        println!("  {}. {} ({})", i + 1, node.display_name, symbol);
+            if let Some(function_name) = &function_name {
+                // A single entry point; multiple matches are listed so the
+                // user can be more specific
+                match SymbolResolver::from_call_graph(&full_graph).resolve_one(function_name) {
+                    Ok(entry) => entry_points.push(entry.symbol.clone()),
+                    Err(e) => {
+                        println!("Error: {e}");
+                        return Ok(());
                     }
                 }
+            }
+
+            if entry_points.is_empty() {
+                println!("Error: No entry points found.");
                 return Ok(());
             }
 
             // Generate filtered graph
-            let filtered_graph = generate_filtered_call_graph(
-                &full_graph,
-                &[matching_entries[0].clone()],
-                max_depth,
-            );
+            let filtered_graph =
+                generate_filtered_call_graph(&full_graph, &entry_points, max_depth);
+            let filtered_graph = if collapse {
+                collapse_sccs(&filtered_graph)
+            } else {
+                filtered_graph
+            };
 
             // Print summary
-            println!("Filtered call graph starting from '{function_name}'");
+            match &function_name {
+                Some(function_name) => {
+                    println!("Filtered call graph starting from '{function_name}'")
+                }
+                None => println!("Filtered call graph starting from the entry-point profiles"),
+            }
             if let Some(depth) = max_depth {
                 println!("(limited to depth {depth})");
             }
             print_call_graph_summary(&filtered_graph);
 
             // Generate DOT content
-            let dot_content = generate_call_graph_dot_string(&filtered_graph);
+            let dot_content =
+                generate_call_graph_dot_string(&filtered_graph, &NodeFilters::default());
 
-            if let Some(path) = output_dot_file {
+            if let Some(path) = output_dot_file.or(output) {
                 let mut file = File::create(&path)?;
                 file.write_all(dot_content.as_bytes())?;
                 println!("\nFiltered DOT file written to: {path}");
@@ -130,6 +176,50 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("{dot_content}");
             }
         }
+        Commands::DetectCycles {
+            scip_json_file,
+            all,
+        } => {
+            let scip_data = parse_scip_json(&scip_json_file)?;
+            let call_graph = build_call_graph(&scip_data);
+
+            let is_proof = |symbol: &String| {
+                call_graph
+                    .get(symbol)
+                    .and_then(|node| node.body.as_deref())
+                    .map(detect_decl_kind)
+                    == Some(DeclKind::Proof)
+            };
+
+            let cycles: Vec<Vec<String>> = find_sccs(&call_graph)
+                .into_iter()
+                .filter(|component| component.len() > 1)
+                .filter(|component| all || component.iter().any(is_proof))
+                .collect();
+
+            let kind = if all {
+                ""
+            } else {
+                " involving proof functions"
+            };
+            if cycles.is_empty() {
+                println!("No cycles{kind} found in {scip_json_file}");
+                return Ok(());
+            }
+
+            println!("Found {} cycle(s){kind}:", cycles.len());
+            for (i, component) in cycles.iter().enumerate() {
+                println!("\n  Cycle {} ({} functions):", i + 1, component.len());
+                for symbol in component {
+                    let node = &call_graph[symbol];
+                    let marker = if is_proof(symbol) { " [proof]" } else { "" };
+                    println!(
+                        "    {}{} ({})",
+                        node.display_name, marker, node.relative_path
+                    );
+                }
+            }
+        }
     }
 
     Ok(())
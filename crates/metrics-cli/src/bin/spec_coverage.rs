@@ -0,0 +1,84 @@
+use clap::{Parser, ValueEnum};
+use log::info;
+use scip_core::logging::init_logger;
+use scip_core::{
+    build_call_graph_with_options, parse_scip_json, spec_coverage, CallGraphOptions, SarifReport,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Markdown,
+    /// SARIF 2.1.0 (one result per spec gap), for code review tools
+    Sarif,
+}
+
+/// Report which exec functions have requires/ensures, per module and crate
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Input SCIP JSON file
+    input_scip_json: String,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: ReportFormat,
+
+    /// Maximum number of spec gaps listed in the markdown report
+    #[arg(long, default_value_t = 50)]
+    max_gaps: usize,
+
+    /// Only report public exec functions as spec gaps
+    #[arg(long)]
+    public_only: bool,
+
+    /// Leave out source files whose relative path matches this glob (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Enable debug logging
+    #[arg(short, long)]
+    debug: bool,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    init_logger(args.debug);
+
+    info!("Parsing SCIP JSON from {}...", args.input_scip_json);
+    let scip_data = parse_scip_json(&args.input_scip_json)?;
+    let options = CallGraphOptions::default().with_exclude_globs(args.exclude.clone());
+    let call_graph = build_call_graph_with_options(&scip_data, &options);
+    info!("Call graph contains {} functions", call_graph.len());
+
+    let mut report = spec_coverage(&call_graph);
+    if args.public_only {
+        report.gaps.retain(|gap| gap.is_public);
+    }
+    info!(
+        "{} of {} exec functions have a spec ({:.1}%), {} public functions without spec",
+        report.total.with_spec,
+        report.total.exec_functions,
+        report.coverage * 100.0,
+        report.total.public_without_spec
+    );
+
+    let rendered = match args.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        ReportFormat::Markdown => report.to_markdown(args.max_gaps),
+        ReportFormat::Sarif => serde_json::to_string_pretty(&report.to_sarif())?,
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            info!("✓ Wrote spec coverage report to {}", path);
+        }
+        None => println!("{}", rendered),
+    }
+
+    Ok(())
+}
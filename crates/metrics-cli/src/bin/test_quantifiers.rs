@@ -31,6 +31,19 @@ fn test_spec(name: &str, spec: &str, expected_notes: &str) {
             println!("  Total operands (N2):      {}", metrics.n2_total_operands);
             println!();
 
+            println!("🔁 Quantifiers:");
+            println!("  Quantifiers:              {}", metrics.quantifier_count);
+            println!(
+                "  Max nesting depth:        {}",
+                metrics.max_quantifier_depth
+            );
+            println!(
+                "  Bound variables:          {}",
+                metrics.quantified_variables
+            );
+            println!("  Triggers:                 {}", metrics.trigger_count);
+            println!();
+
             println!("💡 Notes: {}", expected_notes);
         }
         Err(e) => {
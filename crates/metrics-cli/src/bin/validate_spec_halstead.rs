@@ -1,16 +1,22 @@
 //! Detailed validation: Show exactly what tokens are counted
 //!
-//! Usage: cargo run --bin validate_spec_halstead
+//! Usage: cargo run --bin validate_spec_halstead -- [--prose-patterns <file>] [--ratio-prose]
+//!        [<spec>...]
+//!
+//! Validates the built-in examples unless specs are given on the command line.
+//! `--prose-patterns` adds prose indicator phrases (one per line) to the
+//! default detector; `--ratio-prose` uses the operator-density detector instead.
 
-use verus_metrics::spec_halstead::analyze_spec;
+use verus_metrics::{analyze_spec_with, DefaultProseDetector, ProseDetector, RatioProseDetector};
 
-fn validate_spec(name: &str, spec: &str) {
+fn validate_spec(name: &str, spec: &str, detector: &dyn ProseDetector) {
     println!("\n{}", "=".repeat(80));
     println!("📋 Example: {}", name);
     println!("{}", "=".repeat(80));
-    println!("Spec: {}\n", spec);
+    println!("Spec: {}", spec);
+    println!("Prose confidence: {:.2}\n", detector.prose_confidence(spec));
 
-    match analyze_spec(spec) {
+    match analyze_spec_with(spec, detector) {
         Ok(metrics) => {
             println!("✅ Parse successful\n");
 
@@ -61,9 +67,61 @@ fn validate_spec(name: &str, spec: &str) {
     }
 }
 
+/// The detector selected on the command line
+fn prose_detector(ratio_prose: bool, prose_patterns: Option<&str>) -> Box<dyn ProseDetector> {
+    if ratio_prose {
+        return Box::new(RatioProseDetector::default());
+    }
+    let detector = DefaultProseDetector::default();
+    let Some(path) = prose_patterns else {
+        return Box::new(detector);
+    };
+    match detector.with_indicators_from_file(path) {
+        Ok(detector) => Box::new(detector),
+        Err(e) => {
+            eprintln!("Failed to read prose patterns from {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut prose_patterns: Option<String> = None;
+    let mut ratio_prose = false;
+    let mut specs: Vec<String> = Vec::new();
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--prose-patterns" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => prose_patterns = Some(path.clone()),
+                    None => {
+                        eprintln!("Error: --prose-patterns requires a file path");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--ratio-prose" => ratio_prose = true,
+            spec => specs.push(spec.to_string()),
+        }
+        i += 1;
+    }
+
+    let detector = prose_detector(ratio_prose, prose_patterns.as_deref());
+    let validate_spec = |name: &str, spec: &str| validate_spec(name, spec, detector.as_ref());
+
     println!("\n🔬 Spec Halstead Validation - Detailed Token Analysis");
 
+    if !specs.is_empty() {
+        for (i, spec) in specs.iter().enumerate() {
+            validate_spec(&format!("Spec {}", i + 1), spec);
+        }
+        return;
+    }
+
     // Example 1: Simplest possible spec
     validate_spec("Single Comparison", "x < 10");
 
@@ -1,4 +1,9 @@
-use scip_core::{build_call_graph, parse_scip_json, write_call_graph_as_atoms_json};
+use scip_core::logging::init_logger;
+use scip_core::{
+    add_graph_metrics_to_atoms, build_call_graph, call_graph_to_atoms, parse_scip_json,
+    write_atoms_json,
+};
+use verus_metrics::{add_signatures, add_spec_support};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -8,6 +13,8 @@ fn main() {
     }
     let input_path = &args[1];
     let output_path = &args[2];
+    // Shows the identifier collisions found while writing atoms
+    init_logger(false);
 
     let scip_index = match parse_scip_json(input_path) {
         Ok(idx) => idx,
@@ -17,7 +24,22 @@ fn main() {
         }
     };
     let call_graph = build_call_graph(&scip_index);
-    if let Err(e) = write_call_graph_as_atoms_json(&call_graph, output_path) {
+    let mut atoms = call_graph_to_atoms(&call_graph);
+    add_graph_metrics_to_atoms(&mut atoms);
+    let with_signature = add_signatures(&mut atoms);
+    println!(
+        "Parsed signatures of {} of {} functions",
+        with_signature,
+        atoms.len()
+    );
+    let unsupported = add_spec_support(&mut atoms);
+    if unsupported > 0 {
+        println!(
+            "{} ensures clause(s) have no supporting lemma among their callees",
+            unsupported
+        );
+    }
+    if let Err(e) = write_atoms_json(atoms, output_path) {
         eprintln!("Failed to write atoms JSON: {e}");
         std::process::exit(1);
     }
@@ -1,12 +1,14 @@
 use log::{error, info};
 use scip_core::logging::{init_logger, should_enable_debug};
-use scip_core::{build_call_graph, generate_call_graph_svg, parse_scip_json};
+use scip_core::{
+    build_call_graph, generate_call_graph_svg, parse_scip_json, NodeFilters, SvgLayoutOptions,
+};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 3 {
         eprintln!(
-            "Usage: {} <input_scip_json> <output_atoms_json> [--debug|-d]",
+            "Usage: {} <input_scip_json> <output_svg> [--width PX] [--height PX] [--iterations N] [--debug|-d]",
             args[0]
         );
         std::process::exit(1);
@@ -19,6 +21,25 @@ fn main() {
     let input_path = &args[1];
     let output_path = &args[2];
 
+    // Canvas size and layout iterations
+    let option = |name: &str| {
+        let i = args.iter().position(|arg| arg == name)?;
+        match args.get(i + 1).and_then(|value| value.parse::<f64>().ok()) {
+            Some(value) if value > 0.0 => Some(value),
+            _ => {
+                eprintln!("{name} expects a positive number");
+                std::process::exit(1);
+            }
+        }
+    };
+    let defaults = SvgLayoutOptions::default();
+    let options = SvgLayoutOptions::default()
+        .with_canvas(
+            option("--width").unwrap_or(defaults.width),
+            option("--height").unwrap_or(defaults.height),
+        )
+        .with_iterations(option("--iterations").map_or(defaults.iterations, |n| n as usize));
+
     let scip_index = match parse_scip_json(input_path) {
         Ok(idx) => idx,
         Err(e) => {
@@ -28,7 +49,9 @@ fn main() {
     };
     let call_graph = build_call_graph(&scip_index);
 
-    if let Err(e) = generate_call_graph_svg(&call_graph, output_path) {
+    if let Err(e) =
+        generate_call_graph_svg(&call_graph, output_path, &NodeFilters::default(), &options)
+    {
         error!("Failed to write atoms to SVG: {e}");
         std::process::exit(1);
     }
@@ -0,0 +1,80 @@
+//! Metrics rolled up by file, module, impl block or trait
+//! (`metrics aggregate`)
+//!
+//! Groups the functions of a metrics JSON and reports the sum, mean and
+//! maximum of spec effort and proof depth, and the verification failures,
+//! of each group, as CSV or JSON.
+
+use clap::ValueEnum;
+use log::info;
+use scip_core::progress;
+use scip_core::{add_verification_status_to_atoms, rollup_metrics, D3Graph, GroupBy};
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// Roll up per-function metrics by file, module, impl block or trait
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Atoms JSON with metrics and/or proof metrics
+    metrics_json: String,
+
+    /// What to group functions by: `file`, `module`, `impl` (impl block,
+    /// `module::Foo` or `<module::Foo as Bar>`) or `trait`
+    #[arg(long, value_name = "GROUP", default_value = "module")]
+    group_by: GroupBy,
+
+    /// D3 graph JSON from `graph` with verification results, for the
+    /// failure counts (matched by identifier)
+    #[arg(long, value_name = "JSON")]
+    graph: Option<String>,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "csv")]
+    format: ReportFormat,
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Reading metrics from {}...", args.metrics_json);
+    let mut atoms: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&args.metrics_json)?)?;
+    if let Some(path) = &args.graph {
+        let graph: D3Graph = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let matched = add_verification_status_to_atoms(&mut atoms, &graph);
+        info!(
+            "Verification status for {} of {} functions",
+            matched,
+            atoms.len()
+        );
+    }
+
+    let rollup = rollup_metrics(&atoms, args.group_by);
+    progress!(
+        "{} groups by {} ({} functions in none)",
+        rollup.groups.len(),
+        rollup.group_by,
+        rollup.ungrouped
+    );
+
+    let mut rendered = Vec::new();
+    match args.format {
+        ReportFormat::Csv => rollup.write_csv(&mut rendered)?,
+        ReportFormat::Json => serde_json::to_writer_pretty(&mut rendered, &rollup)?,
+    }
+    match &args.output {
+        Some(path) => {
+            fs::write(path, rendered)?;
+            info!("✓ Wrote metrics rollup to {}", path);
+        }
+        None => println!("{}", String::from_utf8(rendered)?.trim_end()),
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Delta report between the metrics of two revisions (`metrics compare`)
+//!
+//! Reports spec effort increases above a percentage, new functions without
+//! specs and proofs using more lemmas, as markdown for a PR comment or as
+//! JSON.
+
+use clap::ValueEnum;
+use log::info;
+use scip_core::compare_metrics;
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Markdown,
+}
+
+/// Compare the metrics of two revisions
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Metrics JSON of the base revision
+    #[arg(long, value_name = "JSON")]
+    baseline: String,
+
+    /// Metrics JSON of the current revision
+    #[arg(long, value_name = "JSON")]
+    current: String,
+
+    /// Report spec effort increases above this percentage
+    #[arg(long, value_name = "PCT", default_value_t = 10.0)]
+    effort_increase: f64,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: ReportFormat,
+}
+
+fn read_atoms(path: &str) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    info!("Reading metrics from {}...", path);
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let baseline = read_atoms(&args.baseline)?;
+    let current = read_atoms(&args.current)?;
+    let comparison = compare_metrics(&baseline, &current, args.effort_increase);
+    info!(
+        "{} spec effort increases, {} new functions without specs, {} proofs using more lemmas",
+        comparison.effort_increases.len(),
+        comparison.new_without_specs.len(),
+        comparison.lemma_growth.len()
+    );
+
+    let rendered = match args.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&comparison)?,
+        ReportFormat::Markdown => comparison.to_markdown(),
+    };
+    match &args.output {
+        Some(path) => {
+            fs::write(path, rendered)?;
+            info!("✓ Wrote metrics comparison to {}", path);
+        }
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+//! Report unreachable exec/proof functions and orphaned spec functions
+
+use crate::monitor::cli_monitor;
+use crate::pipeline::{load_scip_indices, parse_mode};
+use clap::ValueEnum;
+use log::info;
+use scip_core::{
+    build_call_graph_monitored, find_dead_code, CallGraphOptions, ReachabilityOptions, SarifReport,
+    Severity,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Markdown,
+    /// SARIF 2.1.0, for code review tools
+    Sarif,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MinSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+/// Find unreachable exec/proof functions and orphaned spec functions
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Input SCIP JSON file
+    input_scip_json: String,
+
+    /// SCIP JSON of another repository to merge into the graph, linking
+    /// calls between the repositories (repeatable)
+    #[arg(long = "scip", value_name = "JSON")]
+    scip: Vec<String>,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: ReportFormat,
+
+    /// Additional entry point, by symbol or function name (repeatable)
+    #[arg(long = "entry", value_name = "FUNCTION")]
+    entry: Vec<String>,
+
+    /// Don't treat `pub` functions as entry points (for binaries)
+    #[arg(long)]
+    no_public_entries: bool,
+
+    /// Only report findings of at least this severity
+    #[arg(long, value_enum, default_value = "low")]
+    min_severity: MinSeverity,
+
+    /// Leave out source files whose relative path matches this glob (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Fail on SCIP documents that don't match the schema instead of
+    /// skipping them with a warning
+    #[arg(long)]
+    strict: bool,
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Parsing SCIP JSON from {}...", args.input_scip_json);
+    let scip_data = load_scip_indices(&args.input_scip_json, &args.scip, parse_mode(args.strict))?;
+    let options = CallGraphOptions::default().with_exclude_globs(args.exclude.clone());
+    let call_graph = build_call_graph_monitored(&scip_data, &options, &cli_monitor())
+        .map_err(|_| "Cancelled before the call graph was built; nothing written")?;
+    info!("Call graph contains {} functions", call_graph.len());
+
+    let options = ReachabilityOptions::default()
+        .with_public_entry_points(!args.no_public_entries)
+        .with_entry_points(args.entry.clone());
+    let mut report = find_dead_code(&call_graph, &options);
+    let min_severity = match args.min_severity {
+        MinSeverity::Low => Severity::Low,
+        MinSeverity::Medium => Severity::Medium,
+        MinSeverity::High => Severity::High,
+    };
+    report
+        .findings
+        .retain(|finding| finding.severity >= min_severity);
+    info!(
+        "{} entry points, {} of {} functions reachable, {} findings",
+        report.summary.entry_points,
+        report.summary.reachable,
+        report.summary.functions,
+        report.findings.len()
+    );
+
+    let rendered = match args.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        ReportFormat::Markdown => report.to_markdown(),
+        ReportFormat::Sarif => serde_json::to_string_pretty(&report.to_sarif())?,
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            info!("✓ Wrote dead code report to {}", path);
+        }
+        None => println!("{}", rendered),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Enrich a functions CSV with spec/proof metrics from an atoms JSON
+//!
+//! Rows are matched to atoms by a key column and a list of matching
+//! strategies, and any metric fields can be appended (see
+//! `scip_core::csv_enrich`). With `--proof-difficulty`, the `has_proof` and
+//! `trivial_proof` columns of a proof difficulty CSV are joined in too.
+
+use log::info;
+use scip_core::{CsvEnricher, EnrichConfig, KeyStrategy, BUILTIN_FIELDS};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+
+/// Append spec Halstead metrics (requires, ensures, decreases), proof
+/// Halstead metrics (direct, transitive), lemma and proof step counts, or any
+/// other atom field, to the matching rows of a functions CSV
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Atoms JSON with spec and proof metrics
+    atoms_json: String,
+
+    /// CSV to enrich
+    input_csv: String,
+
+    /// Enriched CSV
+    output_csv: String,
+
+    /// CSV with function, has_proof and trivial_proof columns, matched to
+    /// the atoms like the input rows
+    #[arg(long, value_name = "CSV")]
+    proof_difficulty: Option<String>,
+
+    /// Settings TOML (key_column, module_column, strategies, fields); the
+    /// flags below override it
+    #[arg(long, value_name = "TOML")]
+    config: Option<String>,
+
+    /// Column with the function name [default: function]
+    #[arg(long, value_name = "COLUMN")]
+    key_column: Option<String>,
+
+    /// Column with the module path, for `module-name` matching and for
+    /// narrowing ambiguous names [default: module]
+    #[arg(long, value_name = "COLUMN")]
+    module_column: Option<String>,
+
+    /// Matching strategies to try in order: qualified-name, display-name,
+    /// module-name, fuzzy [default: qualified-name,display-name,module-name]
+    #[arg(long = "match", value_name = "STRATEGIES", value_delimiter = ',')]
+    strategies: Option<Vec<KeyStrategy>>,
+
+    /// Fields to append: built-in metric columns or dotted atom paths like
+    /// `metrics.function_mode` [default: all built-in columns]
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    fields: Option<Vec<String>>,
+
+    /// List the built-in fields and exit
+    #[arg(long)]
+    list_fields: bool,
+}
+
+impl Args {
+    fn enrich_config(&self) -> Result<EnrichConfig, String> {
+        let mut config = match &self.config {
+            Some(path) => {
+                EnrichConfig::load(path).map_err(|e| format!("Failed to load {}: {}", path, e))?
+            }
+            None => EnrichConfig::default(),
+        };
+        if let Some(column) = &self.key_column {
+            config.key_column = column.clone();
+        }
+        if let Some(column) = &self.module_column {
+            config.module_column = Some(column.clone()).filter(|c| !c.is_empty());
+        }
+        if let Some(strategies) = &self.strategies {
+            config.strategies = strategies.clone();
+        }
+        if let Some(fields) = &self.fields {
+            config.fields = fields.clone();
+        }
+        if config.strategies.is_empty() {
+            return Err("at least one matching strategy is required".to_string());
+        }
+        Ok(config)
+    }
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    if args.list_fields {
+        for field in BUILTIN_FIELDS {
+            println!("{}", field);
+        }
+        return Ok(());
+    }
+    let config = args.enrich_config()?;
+
+    info!("Loading atoms from {}...", args.atoms_json);
+    let atoms: Vec<serde_json::Value> =
+        serde_json::from_reader(std::io::BufReader::new(File::open(&args.atoms_json)?))?;
+    info!("  Loaded {} atoms", atoms.len());
+
+    info!(
+        "Enriching {} (key column '{}', matching by {})...",
+        args.input_csv,
+        config.key_column,
+        config
+            .strategies
+            .iter()
+            .map(KeyStrategy::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let mut enricher = CsvEnricher::new(&atoms, &config);
+    if let Some(path) = &args.proof_difficulty {
+        info!("Loading proof difficulty from {}...", path);
+        enricher = enricher.with_proof_difficulty(File::open(path)?)?;
+    }
+    let stats = enricher.enrich(
+        File::open(&args.input_csv)?,
+        BufWriter::new(File::create(&args.output_csv)?),
+    )?;
+
+    println!("✓ Wrote {}", args.output_csv);
+    println!();
+    println!("Rows: {}", stats.rows);
+    println!(
+        "Matched: {} ({:.1}%)",
+        stats.matched_total(),
+        stats.matched_total() as f64 / stats.rows.max(1) as f64 * 100.0
+    );
+    for (strategy, count) in &stats.matched {
+        println!("  by {}: {}", strategy, count);
+    }
+    println!("Ambiguous: {}", stats.ambiguous);
+    println!("Not found: {}", stats.not_found);
+    if args.proof_difficulty.is_some() {
+        println!("With proof difficulty: {}", stats.with_proof_difficulty);
+    }
+    println!("Fields: {}", config.fields.join(", "));
+
+    Ok(())
+}
@@ -0,0 +1,395 @@
+//! Export a call graph as D3.js graph JSON, node/edge tables, a SQLite
+//! database or a markdown dossier of one function
+
+use crate::commands::DEFAULT_HIGHLIGHT_PREFIXES;
+use crate::monitor::cli_monitor;
+use crate::pipeline::{load_scip_indices, parse_mode};
+use clap::ValueEnum;
+use log::{info, warn};
+use scip_core::{
+    add_graph_metrics_to_d3, build_call_graph_monitored, build_module_graph,
+    collapse_external_crates, collapse_sccs, count_calls_by_origin, export_call_graph_d3,
+    export_call_graph_d3_filtered, export_tabular, filter_by_edge_kinds, is_test_function,
+    module_graph_to_d3, render_dossiers, sample_call_graph, CallGraph, CallGraphOptions, EdgeKind,
+    FunctionNode, NodeFilters, NodeKind, NodeKindFilter, ReportMetrics, SourceLinks, TabularFormat,
+    TestFilter, TooltipContent,
+};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// D3.js graph JSON for the web viewer
+    D3,
+    /// nodes.csv and edges.csv
+    Csv,
+    /// nodes.parquet and edges.parquet (needs the `parquet` feature)
+    Parquet,
+    /// SQLite database with functions, edges, specs and proof_metrics tables
+    /// (needs the `sqlite` feature)
+    Sqlite,
+    /// Markdown dossier of the `--function` (signature, specs, lemmas,
+    /// callers/callees and a mermaid diagram)
+    Markdown,
+}
+
+/// Export call graph in D3.js force-directed graph format
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Input SCIP JSON file
+    input_scip_json: String,
+
+    /// SCIP JSON of another repository to merge into the graph, linking
+    /// calls between the repositories (repeatable)
+    #[arg(long = "scip", value_name = "JSON")]
+    scip: Vec<String>,
+
+    /// Output JSON file for D3.js visualization, the output directory for
+    /// `--format csv` / `--format parquet`, the database file for
+    /// `--format sqlite`, or the markdown file for `--format markdown`
+    /// (default: call_graph_d3.json; markdown is printed to stdout)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "d3")]
+    format: OutputFormat,
+
+    /// Atoms JSON with spec and proof metrics (from compute_metrics /
+    /// compute_proof_metrics) to store with `--format sqlite` or show with
+    /// `--format markdown`
+    #[arg(long, value_name = "JSON")]
+    metrics_json: Option<String>,
+
+    /// Function (symbol, display name or `Type#method`) to document with
+    /// `--format markdown`
+    #[arg(long, required_if_eq("format", "markdown"))]
+    function: Option<String>,
+
+    /// Graph JSON written by the pipeline, for the verification status
+    /// shown with `--format markdown`
+    #[arg(long, value_name = "JSON")]
+    graph_json: Option<String>,
+
+    /// Only include calls of these kinds, comma-separated
+    /// (e.g. `proof_to_proof` for the proof-to-lemma subgraph)
+    #[arg(long, value_delimiter = ',')]
+    edge_kinds: Vec<EdgeKind>,
+
+    /// Leave out source files whose relative path matches this glob
+    /// (repeatable, e.g. `--exclude '**/benches/**'`)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Draw functions whose crate or source path starts with this prefix as
+    /// project sources (repeatable; default: the libsignal crates)
+    #[arg(
+        long = "highlight-prefix",
+        value_name = "PREFIX",
+        default_values = DEFAULT_HIGHLIGHT_PREFIXES
+    )]
+    highlight_prefix: Vec<String>,
+
+    /// Leave out functions whose source path starts with this prefix (repeatable)
+    #[arg(long = "skip-path", value_name = "PREFIX")]
+    skip_path: Vec<String>,
+
+    /// Leave out test functions (`#[test]`, `#[cfg(test)]` or in test files)
+    #[arg(long, conflicts_with = "only_tests")]
+    exclude_tests: bool,
+
+    /// Export only test functions
+    #[arg(long)]
+    only_tests: bool,
+
+    /// Add structs, enums, type aliases, constants and statics as nodes of
+    /// kind `type`, with `references` edges from the functions using them
+    #[arg(long)]
+    include_types: bool,
+
+    /// Leave out nodes of this kind, `function` or `type` (repeatable)
+    #[arg(long = "skip-kind", value_name = "KIND")]
+    skip_kind: Vec<NodeKind>,
+
+    /// Add fan-in/fan-out, call depth and centrality (`graph_metrics`) to the
+    /// D3 graph nodes
+    #[arg(long)]
+    graph_metrics: bool,
+
+    /// Export one node per source file (listing its functions), with links
+    /// weighted by call counts, instead of one node per function
+    #[arg(long, conflicts_with = "edge_kinds")]
+    modules: bool,
+
+    /// Collapse each recursion cycle into a single node labeled with its size
+    #[arg(long)]
+    collapse_sccs: bool,
+
+    /// Roll the external functions of each crate up into one node
+    /// (`vstd (142 fns)`), whose tooltip lists the calls it replaces
+    #[arg(long)]
+    collapse_external_crates: bool,
+
+    /// Export at most this many D3 nodes: beyond it, the functions farthest
+    /// from highlighted ones (and least central) are collapsed into one
+    /// "… N more in module X" node per module
+    #[arg(long, value_name = "N")]
+    max_nodes: Option<usize>,
+
+    /// GitHub repository URL to link functions to their source
+    /// (e.g., https://github.com/user/repo), with --rev; sets `source_url`
+    /// on D3 graph nodes and links locations in markdown dossiers
+    #[arg(long, requires = "rev")]
+    github_url: Option<String>,
+
+    /// Revision (branch, tag or commit) of the source links
+    #[arg(long, requires = "github_url")]
+    rev: Option<String>,
+
+    /// What the D3 graph's bodies file (`<output>.bodies.json`, loaded by
+    /// viewers on demand) holds of each function: none (no file),
+    /// signature, lines:N (the first N lines of the body) or full
+    #[arg(long, default_value_t = TooltipContent::Signature)]
+    tooltips: TooltipContent,
+
+    /// Write nodes and edges in hash map order instead of sorted by symbol:
+    /// slightly faster on huge graphs, but the output changes between runs
+    #[arg(long)]
+    unsorted: bool,
+
+    /// Fail on SCIP documents that don't match the schema instead of
+    /// skipping them with a warning
+    #[arg(long)]
+    strict: bool,
+}
+
+impl Args {
+    fn source_links(&self) -> Option<SourceLinks> {
+        match (&self.github_url, &self.rev) {
+            (Some(github_url), Some(rev)) => Some(SourceLinks::new(github_url, rev)),
+            _ => None,
+        }
+    }
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Parsing SCIP JSON from {}...", args.input_scip_json);
+    let scip_data = load_scip_indices(&args.input_scip_json, &args.scip, parse_mode(args.strict))?;
+
+    info!("Building call graph...");
+    let options = CallGraphOptions::default()
+        .with_exclude_globs(args.exclude.clone())
+        .with_types(args.include_types);
+    let call_graph = build_call_graph_monitored(&scip_data, &options, &cli_monitor())
+        .map_err(|_| "Cancelled before the call graph was built; nothing written")?;
+    info!("Call graph contains {} functions", call_graph.len());
+    let call_graph = if args.collapse_sccs {
+        collapse_sccs(&call_graph)
+    } else {
+        call_graph
+    };
+    let call_graph = if args.collapse_external_crates {
+        let collapsed = collapse_external_crates(&call_graph);
+        info!(
+            "Rolled external functions up into crates: {} -> {} nodes",
+            call_graph.len(),
+            collapsed.len()
+        );
+        collapsed
+    } else {
+        call_graph
+    };
+
+    let mut filters = NodeFilters::from_prefixes(&args.highlight_prefix, &args.skip_path)
+        .deterministic(!args.unsorted)
+        .tooltips(args.tooltips);
+    if args.exclude_tests {
+        filters = filters.skip(TestFilter);
+    }
+    if args.only_tests {
+        filters = filters.skip(|node: &FunctionNode| !is_test_function(node));
+    }
+    for &kind in &args.skip_kind {
+        filters = filters.skip(NodeKindFilter(kind));
+    }
+    if args.format == OutputFormat::Markdown {
+        let call_graph = filters.apply(&call_graph);
+        return export_markdown(&call_graph, args);
+    }
+
+    let output = args.output.as_deref().unwrap_or("call_graph_d3.json");
+    let tabular_format = match args.format {
+        OutputFormat::D3 | OutputFormat::Sqlite | OutputFormat::Markdown => None,
+        OutputFormat::Csv => Some(TabularFormat::Csv),
+        OutputFormat::Parquet => Some(TabularFormat::Parquet),
+    };
+    if let Some(format) = tabular_format {
+        if !args.edge_kinds.is_empty() {
+            warn!("--edge-kinds is ignored for tabular output; filter on the edge_kind column");
+        }
+        info!("Exporting node and edge tables as {}...", format);
+        let call_graph = filters.apply(&call_graph);
+        export_tabular(&call_graph, &ReportMetrics::new(), output, format)
+            .map_err(|e| format!("Failed to export call graph tables: {}", e))?;
+        info!(
+            "✓ Wrote nodes.{ext} and edges.{ext} to {}",
+            output,
+            ext = format.extension()
+        );
+        return Ok(());
+    }
+
+    if args.format == OutputFormat::Sqlite {
+        let call_graph = filters.apply(&call_graph);
+        export_sqlite(&call_graph, args.metrics_json.as_deref(), output)
+            .map_err(|e| format!("Failed to export call graph database: {}", e))?;
+        info!("✓ Wrote call graph database to {}", output);
+        return Ok(());
+    }
+
+    if args.modules {
+        let module_graph = build_module_graph(&filters.apply(&call_graph));
+        let mut graph = module_graph_to_d3(&module_graph, &scip_data.metadata.project_root);
+        if args.graph_metrics {
+            add_graph_metrics_to_d3(&mut graph);
+        }
+        std::fs::write(output, serde_json::to_string_pretty(&graph)?)?;
+        info!("✓ Exported module graph to {}", output);
+        info!("  Modules: {}", graph.metadata.total_nodes);
+        info!("  Module edges: {}", graph.metadata.total_edges);
+        return Ok(());
+    }
+
+    let call_graph = match args.max_nodes {
+        Some(max_nodes) => sample_for_d3(&call_graph, args, &filters, max_nodes),
+        None => call_graph,
+    };
+
+    info!("Exporting call graph to D3.js format...");
+    let result = if args.edge_kinds.is_empty() {
+        export_call_graph_d3(&call_graph, &scip_data, output, &filters)
+    } else {
+        export_call_graph_d3_filtered(&call_graph, &scip_data, output, &args.edge_kinds, &filters)
+    };
+    match result {
+        Ok(mut graph) => {
+            if args.graph_metrics {
+                add_graph_metrics_to_d3(&mut graph);
+            }
+            if let Some(links) = args.source_links() {
+                let linked = links.link_d3_graph(&mut graph);
+                graph.metadata.github_url = args.github_url.clone();
+                info!("  Linked {} functions to their source", linked);
+            }
+            if args.graph_metrics || args.github_url.is_some() {
+                std::fs::write(output, serde_json::to_string_pretty(&graph)?)?;
+            }
+            info!("✓ Successfully exported call graph to {}", output);
+            info!("  Total nodes: {}", graph.metadata.total_nodes);
+            info!("  Total edges: {}", graph.metadata.total_edges);
+            if let Some(bodies_file) = &graph.metadata.bodies_file {
+                info!("  Function bodies ({}) in {}", args.tooltips, bodies_file);
+            }
+            let calls = count_calls_by_origin(&filters.apply(&call_graph));
+            info!("  Calls from production code: {}", calls.production);
+            info!("  Calls from tests: {}", calls.test);
+
+            if filters.has_highlights() {
+                let highlighted_count = call_graph
+                    .values()
+                    .filter(|node| filters.is_highlighted(node))
+                    .count();
+                info!("  Highlighted nodes: {}", highlighted_count);
+            }
+
+            info!("\nNext steps:");
+            info!("  1. Open the web viewer: open web/index.html");
+            info!("  2. Load the exported file: {}", output);
+        }
+        Err(e) => return Err(format!("Failed to export call graph: {}", e).into()),
+    }
+
+    Ok(())
+}
+
+/// Sample the graph as exported (edge kinds and filters applied) down to
+/// `max_nodes`, keeping the highlighted functions
+fn sample_for_d3(
+    call_graph: &CallGraph,
+    args: &Args,
+    filters: &NodeFilters,
+    max_nodes: usize,
+) -> CallGraph {
+    let call_graph = if args.edge_kinds.is_empty() {
+        filters.apply(call_graph)
+    } else {
+        filters.apply(&filter_by_edge_kinds(call_graph, &args.edge_kinds))
+    };
+    let pinned: HashSet<String> = call_graph
+        .values()
+        .filter(|node| filters.is_highlighted(node))
+        .map(|node| node.symbol.clone())
+        .collect();
+    let sampled = sample_call_graph(&call_graph, &pinned, max_nodes);
+    if sampled.len() < call_graph.len() {
+        info!(
+            "Sampled {} functions down to {} nodes",
+            call_graph.len(),
+            sampled.len()
+        );
+    }
+    sampled
+}
+
+fn export_markdown(
+    call_graph: &scip_core::CallGraph,
+    args: &Args,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let function = args
+        .function
+        .as_deref()
+        .ok_or("--format markdown needs --function")?;
+    let atoms: Vec<serde_json::Value> = match &args.metrics_json {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => Vec::new(),
+    };
+    let metrics = match &args.graph_json {
+        Some(path) => {
+            ReportMetrics::from_graph_json(&serde_json::from_str(&std::fs::read_to_string(path)?)?)
+        }
+        None => ReportMetrics::new(),
+    };
+
+    let links = args.source_links();
+    let dossier = render_dossiers(call_graph, function, &metrics, &atoms, links.as_ref())?;
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, dossier)?;
+            info!("✓ Wrote dossier of {} to {}", function, path);
+        }
+        None => print!("{}", dossier),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn export_sqlite(
+    call_graph: &scip_core::CallGraph,
+    metrics_json: Option<&str>,
+    output: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let atoms: Vec<serde_json::Value> = match metrics_json {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => Vec::new(),
+    };
+    info!("Exporting call graph to SQLite...");
+    scip_core::export_sqlite(call_graph, &ReportMetrics::new(), &atoms, output)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn export_sqlite(
+    _call_graph: &scip_core::CallGraph,
+    _metrics_json: Option<&str>,
+    _output: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("SQLite export requires building with the `sqlite` feature".into())
+}
@@ -0,0 +1,81 @@
+//! Fail CI when spec/proof complexity exceeds its budgets
+//!
+//! Checks a metrics JSON against a thresholds TOML (see `scip_core::gate`),
+//! writes the violations report and exits with status 1 if any budget is
+//! broken.
+
+use clap::ValueEnum;
+use log::info;
+use scip_core::{check_gate, GateThresholds};
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Markdown,
+}
+
+/// Check metrics against complexity budgets
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Atoms JSON with metrics and/or proof metrics
+    metrics_json: String,
+
+    /// Thresholds TOML (max_spec_effort, max_proof_depth, min_spec_coverage,
+    /// max_new_assumes)
+    #[arg(long, value_name = "TOML")]
+    config: String,
+
+    /// Metrics JSON of the base revision, for counting new assumes (without
+    /// it every assume counts as new)
+    #[arg(long, value_name = "JSON")]
+    baseline: Option<String>,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: ReportFormat,
+}
+
+fn read_atoms(path: &str) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    info!("Reading metrics from {}...", path);
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let thresholds = GateThresholds::load(&args.config)
+        .map_err(|e| format!("Failed to load {}: {}", args.config, e))?;
+    let atoms = read_atoms(&args.metrics_json)?;
+    let baseline = args.baseline.as_deref().map(read_atoms).transpose()?;
+
+    let report = check_gate(&atoms, baseline.as_deref(), &thresholds);
+    info!(
+        "{} functions checked, {} violations",
+        report.functions,
+        report.violations.len()
+    );
+
+    let rendered = match args.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        ReportFormat::Markdown => report.to_markdown(),
+    };
+    match &args.output {
+        Some(path) => {
+            fs::write(path, rendered)?;
+            info!("✓ Wrote gate report to {}", path);
+        }
+        None => println!("{}", rendered),
+    }
+
+    if !report.passed() {
+        eprintln!(
+            "Complexity gate failed: {} budget violations",
+            report.violations.len()
+        );
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Run the whole pipeline: SCIP index, call graph export, verification and
+//! similar-lemma enrichment (see [`crate::pipeline`])
+
+use crate::monitor::cli_monitor;
+use crate::pipeline::{self, PipelineConfig};
+use scip_core::progress;
+use std::path::PathBuf;
+
+/// Unified pipeline for generating enriched call graphs from Verus projects
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Path to the Verus project
+    project: PathBuf,
+
+    /// Output graph file
+    #[arg(short, long, default_value = "web/public/graph.json")]
+    output: PathBuf,
+
+    /// Skip verification status enrichment
+    #[arg(long)]
+    skip_verification: bool,
+
+    /// Skip similar lemmas enrichment
+    #[arg(long)]
+    skip_similar_lemmas: bool,
+
+    /// vstd lemma index JSON for similar lemmas (default: the
+    /// verus_lemma_finder submodule's index, then data/vstd_lemma_index.json)
+    #[arg(long, value_name = "FILE")]
+    lemma_index: Option<PathBuf>,
+
+    /// Use cached SCIP JSON if available (default: regenerate fresh)
+    #[arg(long)]
+    use_cached_scip: bool,
+
+    /// Verus package name (for workspaces)
+    #[arg(short, long)]
+    package: Option<String>,
+
+    /// GitHub repository URL for source code links in the web viewer
+    /// (e.g., https://github.com/user/repo)
+    #[arg(long)]
+    github_url: Option<String>,
+
+    /// Revision (branch, tag or commit) to link node sources to, with
+    /// --github-url
+    #[arg(long, requires = "github_url")]
+    rev: Option<String>,
+
+    /// Use rust-analyzer instead of verus-analyzer for SCIP generation
+    #[arg(long)]
+    use_rust_analyzer: bool,
+
+    /// Re-analyze only the SCIP documents changed since the last run,
+    /// patching the call graph cached next to the output (with document
+    /// fingerprints); reuse the previous graph when nothing changed
+    #[arg(long)]
+    incremental: bool,
+
+    /// Leave out source files whose relative path matches this glob
+    /// (repeatable, e.g. `--exclude 'vendor/**' --exclude '**/benches/**'`)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Also write a self-contained interactive HTML report of the final graph
+    /// (opens from disk, no dev server needed)
+    #[arg(long, value_name = "PATH")]
+    html_report: Option<PathBuf>,
+
+    /// Ask Verus for per-function verification times (`--time-expanded`)
+    /// and add `verification_time_ms` to graph nodes
+    #[arg(long)]
+    verification_times: bool,
+
+    /// Retry verification up to N times when it fails only on rlimit or
+    /// timeouts, doubling the rlimit and re-verifying just the affected modules
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    verify_retries: usize,
+
+    /// Verify module by module with N parallel workers (per-module logs go
+    /// to `verification-logs/` next to the output); each worker builds the
+    /// dependencies once in its own `target/verus-worker-<n>`
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
+    /// Verus release directory (or its cargo-verus binary) to verify with;
+    /// defaults to $VERUS_PATH, then `path` in the project's
+    /// verus-toolchain.toml, then cargo-verus on PATH
+    #[arg(long, value_name = "DIR")]
+    verus_path: Option<PathBuf>,
+
+    /// Cache verification results by function body hash (in
+    /// `<output>.verification-cache.json`) and only re-verify the modules of
+    /// functions that changed, or whose callees or spec functions changed
+    #[arg(long)]
+    verification_cache: bool,
+
+    /// Add fan-in/fan-out, call depth and centrality (`graph_metrics`) to
+    /// graph nodes
+    #[arg(long)]
+    graph_metrics: bool,
+
+    /// Fail on SCIP documents that don't match the schema instead of
+    /// skipping them with a warning
+    #[arg(long)]
+    strict: bool,
+}
+
+impl Args {
+    fn config(&self) -> PipelineConfig {
+        PipelineConfig {
+            skip_verification: self.skip_verification,
+            skip_similar_lemmas: self.skip_similar_lemmas,
+            lemma_index: self.lemma_index.clone(),
+            use_cached_scip: self.use_cached_scip,
+            package: self.package.clone(),
+            github_url: self.github_url.clone(),
+            rev: self.rev.clone(),
+            use_rust_analyzer: self.use_rust_analyzer,
+            incremental: self.incremental,
+            exclude: self.exclude.clone(),
+            html_report: self.html_report.clone(),
+            verification_times: self.verification_times,
+            verify_retries: self.verify_retries,
+            jobs: self.jobs,
+            verus_path: self.verus_path.clone(),
+            verification_cache: self.verification_cache,
+            graph_metrics: self.graph_metrics,
+            strict_scip: self.strict,
+            ..PipelineConfig::new(&self.project, &self.output)
+        }
+    }
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    progress!("════════════════════════════════════════════════════════════════");
+    progress!("  scip-callgraph Pipeline");
+    progress!("  Unified call graph generation with enrichments");
+    progress!("════════════════════════════════════════════════════════════════");
+    progress!();
+
+    let config = PipelineConfig {
+        monitor: cli_monitor(),
+        ..args.config()
+    };
+    let report = pipeline::run(&config)?;
+    if report.cancelled {
+        return Err(format!(
+            "Cancelled; {} holds the steps finished before",
+            args.output.display()
+        )
+        .into());
+    }
+
+    // Done!
+    progress!("════════════════════════════════════════════════════════════════");
+    progress!("  ✓ Pipeline Complete!");
+    progress!("════════════════════════════════════════════════════════════════");
+    progress!();
+    progress!("Output: {}", args.output.display());
+    if let Some(report_path) = &report.html_report {
+        progress!("Report: {}", report_path.display());
+    }
+    progress!();
+    progress!("Next steps:");
+    progress!("  cd web && npm install && npm run dev");
+    progress!("  Open http://localhost:3000");
+    progress!();
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Record per-commit metrics summaries and report them as a time series
+//!
+//! `history record` appends a summary of a metrics JSON (as written by
+//! `metrics` / `proof-metrics`) to a JSONL store; `history report` writes
+//! the store as CSV, one row per run (and module with `--modules`), for
+//! plotting trend lines.
+
+use clap::Subcommand;
+use log::info;
+use scip_core::progress;
+use scip_core::{append_run, read_history, summarize_run, write_report_csv};
+use std::fs;
+use std::process::Command;
+
+/// Track spec/proof metrics over commits
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    command: HistoryCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryCommand {
+    /// Append a summary of a metrics JSON to the history store
+    Record {
+        /// Atoms JSON with metrics and/or proof metrics
+        metrics_json: String,
+
+        /// History store (JSONL, one run per line)
+        #[arg(long, default_value = "metrics_history.jsonl")]
+        store: String,
+
+        /// Commit the metrics were computed on (default: `git rev-parse HEAD`
+        /// in the current directory)
+        #[arg(long)]
+        commit: Option<String>,
+    },
+    /// Write the history as time-series CSV
+    Report {
+        /// History store (JSONL, one run per line)
+        #[arg(long, default_value = "metrics_history.jsonl")]
+        store: String,
+
+        /// Output CSV file (prints to stdout if omitted)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Add a row per module and run after the totals
+        #[arg(long)]
+        modules: bool,
+    },
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    match &args.command {
+        HistoryCommand::Record {
+            metrics_json,
+            store,
+            commit,
+        } => {
+            let commit = match commit {
+                Some(commit) => commit.clone(),
+                None => head_commit()?,
+            };
+            info!("Reading metrics from {}...", metrics_json);
+            let atoms: Vec<serde_json::Value> =
+                serde_json::from_str(&fs::read_to_string(metrics_json)?)?;
+            let run = summarize_run(&atoms, &commit);
+            append_run(store, &run)?;
+            progress!(
+                "Recorded {} functions ({} modules) at {} in {}",
+                run.totals.functions,
+                run.modules.len(),
+                commit,
+                store
+            );
+        }
+        HistoryCommand::Report {
+            store,
+            output,
+            modules,
+        } => {
+            let runs = read_history(store)?;
+            info!("{} runs in {}", runs.len(), store);
+            match output {
+                Some(path) => {
+                    write_report_csv(&runs, *modules, fs::File::create(path)?)?;
+                    progress!("Wrote {} runs to {}", runs.len(), path);
+                }
+                None => write_report_csv(&runs, *modules, std::io::stdout().lock())?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Commit checked out in the current directory
+fn head_commit() -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new("git").args(["rev-parse", "HEAD"]).output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "git rev-parse HEAD failed (pass --commit): {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
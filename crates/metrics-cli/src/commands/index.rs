@@ -0,0 +1,16 @@
+//! Generate a SCIP JSON index of a Rust project
+
+use scip_core::scip_utils::generate_scip_json_index;
+
+/// Generate a SCIP JSON index of a Rust project
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Project folder, e.g. /path/to/rust/project
+    path_to_folder: String,
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let output_file = generate_scip_json_index(&args.path_to_folder)?;
+    println!("\nDone! Output file: {}", output_file);
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//! Lint the quantifier triggers of an atoms JSON
+//!
+//! Runs `verus_metrics::lint_triggers` on every atom's source and reports
+//! the findings (missing and arithmetic triggers, matching loops, deep
+//! `forall`/`exists` alternation) with their file and line, as JSON,
+//! markdown or SARIF for code review tools.
+
+use clap::ValueEnum;
+use log::{info, warn};
+use scip_core::parser::parse_atoms_json_str;
+use scip_core::{sarif_log, SarifFinding, SarifLevel, SarifRule};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use verus_metrics::{lint_triggers, TriggerLintKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Markdown,
+    /// SARIF 2.1.0, for code review tools
+    Sarif,
+}
+
+/// Lint quantifier triggers
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Atoms JSON (envelope or bare array)
+    atoms_json: String,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: ReportFormat,
+}
+
+/// One lint finding in a function
+#[derive(Debug, Serialize)]
+struct Finding {
+    kind: TriggerLintKind,
+    identifier: String,
+    display_name: String,
+    relative_path: String,
+    /// 1-based line in the source file (in the function if the atom has no
+    /// start line)
+    line: usize,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct LintReport {
+    /// Number of findings by kind
+    counts: BTreeMap<&'static str, usize>,
+    functions_linted: usize,
+    /// Functions whose source could not be parsed
+    parse_failures: usize,
+    /// Sorted by path and line
+    findings: Vec<Finding>,
+}
+
+fn lint(atoms: &[scip_core::Atom]) -> LintReport {
+    let mut counts: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut parse_failures = 0;
+    let mut findings = Vec::new();
+    for atom in atoms {
+        let lints = match lint_triggers(&atom.body) {
+            Ok(lints) => lints,
+            Err(e) => {
+                warn!("Skipping {}: {}", atom.display_name, e);
+                parse_failures += 1;
+                continue;
+            }
+        };
+        for lint in lints {
+            *counts.entry(lint.kind.as_str()).or_default() += 1;
+            findings.push(Finding {
+                kind: lint.kind,
+                identifier: atom.identifier.clone(),
+                display_name: atom.display_name.clone(),
+                relative_path: atom.relative_path.clone(),
+                line: atom
+                    .start_line
+                    .map_or(lint.line, |start| start + lint.line - 1),
+                message: lint.message,
+            });
+        }
+    }
+    findings.sort_by(|a, b| {
+        a.relative_path
+            .cmp(&b.relative_path)
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    LintReport {
+        counts,
+        functions_linted: atoms.len() - parse_failures,
+        parse_failures,
+        findings,
+    }
+}
+
+fn to_markdown(report: &LintReport) -> String {
+    let mut out = String::from("# Trigger lints\n\n");
+    let _ = writeln!(out, "| | Count |\n|---|---:|");
+    for (kind, count) in &report.counts {
+        let _ = writeln!(out, "| `{}` | {} |", kind, count);
+    }
+    let _ = writeln!(out, "| Functions linted | {} |", report.functions_linted);
+    let _ = writeln!(out, "| Parse failures | {} |", report.parse_failures);
+
+    if report.findings.is_empty() {
+        out.push_str("\nNo findings.\n");
+        return out;
+    }
+
+    out.push_str("\n## Findings\n\n");
+    out.push_str("| Kind | Function | Location | Message |\n");
+    out.push_str("|---|---|---|---|\n");
+    for finding in &report.findings {
+        let _ = writeln!(
+            out,
+            "| {} | `{}` | {}:{} | {} |",
+            finding.kind.as_str(),
+            finding.display_name,
+            finding.relative_path,
+            finding.line,
+            finding.message.replace('|', "\\|")
+        );
+    }
+    out
+}
+
+/// The findings as a SARIF log: missing triggers are notes (Verus picks
+/// one), the others warnings
+fn to_sarif(report: &LintReport) -> serde_json::Value {
+    let level = |kind: TriggerLintKind| match kind {
+        TriggerLintKind::MissingTrigger => SarifLevel::Note,
+        _ => SarifLevel::Warning,
+    };
+    let rules: Vec<SarifRule> = TriggerLintKind::ALL
+        .into_iter()
+        .map(|kind| SarifRule {
+            id: kind.as_str(),
+            description: kind.description(),
+            default_level: level(kind),
+        })
+        .collect();
+    let findings: Vec<SarifFinding> = report
+        .findings
+        .iter()
+        .map(|finding| SarifFinding {
+            rule_id: finding.kind.as_str(),
+            level: level(finding.kind),
+            message: format!("{} in `{}`", finding.message, finding.display_name),
+            relative_path: finding.relative_path.clone(),
+            line: Some(finding.line as u32),
+            symbol: Some(finding.identifier.clone()),
+        })
+        .collect();
+    sarif_log(&rules, &findings)
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Reading atoms from {}...", args.atoms_json);
+    let atoms = parse_atoms_json_str(&fs::read_to_string(&args.atoms_json)?)?.atoms;
+
+    let report = lint(&atoms);
+    info!(
+        "{} functions linted, {} findings",
+        report.functions_linted,
+        report.findings.len()
+    );
+
+    let rendered = match args.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        ReportFormat::Markdown => to_markdown(&report),
+        ReportFormat::Sarif => serde_json::to_string_pretty(&to_sarif(&report))?,
+    };
+    match &args.output {
+        Some(path) => {
+            fs::write(path, rendered)?;
+            info!("✓ Wrote trigger lints to {}", path);
+        }
+        None => println!("{}", rendered.trim_end()),
+    }
+    Ok(())
+}
@@ -0,0 +1,637 @@
+//! Long-running call graph query server (`scip-callgraph lsp`)
+//!
+//! Loads the call graph once and answers JSON-RPC 2.0 requests on stdio,
+//! framed like the Language Server Protocol (`Content-Length` headers), so
+//! editors and the web viewer backend get answers without re-parsing the
+//! index. Methods:
+//!
+//! - `initialize`, `shutdown` and the `exit` notification
+//! - `textDocument/definition`: definition of the function called (or
+//!   defined) at a position
+//! - `textDocument/references`: call sites of the function at a position
+//! - `callgraph/resolve` `{name}`: functions matching a name
+//! - `callgraph/callers`, `callgraph/callees` `{function, depth?}`:
+//!   transitive callers/callees with their depth
+//! - `callgraph/subgraph` `{functions, depth?, callers?, callees?}`: the
+//!   neighborhood of functions as D3 graph JSON, as read by the web viewer
+//!
+//! Functions are named as in the other tools (symbol, display name,
+//! `Type::method`, ...). Positions need call sites with columns, so
+//! `definition`/`references` only work on graphs built from SCIP.
+
+use log::info;
+use scip_core::{
+    call_graph_to_d3, collect_neighborhood, load_call_graph, neighborhood_by_depth, CallGraph,
+    FunctionNode, NodeFilters, SymbolResolver,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Serve call graph queries over stdio
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Atoms JSON, SCIP JSON, or binary `index.scip` file
+    input: String,
+
+    /// Directory relative source paths are resolved against for file URIs
+    /// (defaults to the current directory)
+    #[arg(long)]
+    project_root: Option<String>,
+}
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// A JSON-RPC error response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: impl ToString) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.to_string(),
+        }
+    }
+
+    /// A failure of the server rather than of the request
+    fn internal(message: impl ToString) -> Self {
+        Self {
+            code: INTERNAL_ERROR,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ResolveParams {
+    name: String,
+}
+
+fn default_depth() -> usize {
+    1
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct NeighborParams {
+    function: String,
+    #[serde(default = "default_depth")]
+    depth: usize,
+}
+
+#[derive(Deserialize)]
+struct SubgraphParams {
+    functions: Vec<String>,
+    depth: Option<usize>,
+    #[serde(default)]
+    callers: bool,
+    #[serde(default = "default_true")]
+    callees: bool,
+}
+
+#[derive(Deserialize)]
+struct TextDocument {
+    uri: String,
+}
+
+#[derive(Deserialize)]
+struct Position {
+    line: i32,
+    character: i32,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ReferenceContext {
+    include_declaration: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionParams {
+    text_document: TextDocument,
+    position: Position,
+    #[serde(default)]
+    context: ReferenceContext,
+}
+
+fn parse_params<T: DeserializeOwned>(params: &Value) -> Result<T, RpcError> {
+    serde_json::from_value(params.clone()).map_err(RpcError::invalid_params)
+}
+
+/// `file://` URI of a path, percent-encoding everything but unreserved
+/// characters and `/`
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let hex = encoded
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (encoded[i], hex) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn lsp_range(line: i32, start: i32, end: i32) -> Value {
+    json!({
+        "start": { "line": line, "character": start },
+        "end": { "line": line, "character": end },
+    })
+}
+
+/// The loaded call graph and its indexes
+pub struct Server {
+    call_graph: CallGraph,
+    resolver: SymbolResolver,
+    project_root: PathBuf,
+    /// Project functions by the path of their file
+    by_file: HashMap<PathBuf, Vec<String>>,
+}
+
+impl Server {
+    pub fn new(call_graph: CallGraph, project_root: PathBuf) -> Self {
+        let resolver = SymbolResolver::from_call_graph(&call_graph);
+        let mut server = Self {
+            call_graph,
+            resolver,
+            project_root,
+            by_file: HashMap::new(),
+        };
+        let mut by_file: HashMap<PathBuf, Vec<String>> = HashMap::new();
+        for node in server.call_graph.values() {
+            if let Some(path) = server.file_of(node) {
+                by_file.entry(path).or_default().push(node.symbol.clone());
+            }
+        }
+        server.by_file = by_file;
+        server
+    }
+
+    /// Path of the function's file; `None` for external functions
+    fn file_of(&self, node: &FunctionNode) -> Option<PathBuf> {
+        if node.file_path.starts_with("external:") {
+            return None;
+        }
+        let path = Path::new(&node.file_path);
+        if path.is_absolute() {
+            return Some(path.to_path_buf());
+        }
+        Some(
+            self.project_root
+                .join(node.relative_path.trim_start_matches("./")),
+        )
+    }
+
+    /// Answer one request
+    pub fn handle(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                },
+                "serverInfo": {
+                    "name": "scip-callgraph",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/definition" => self.definition(&parse_params(params)?),
+            "textDocument/references" => self.references(&parse_params(params)?),
+            "callgraph/resolve" => {
+                let params: ResolveParams = parse_params(params)?;
+                let functions: Vec<Value> = self
+                    .resolver
+                    .resolve(&params.name)
+                    .iter()
+                    .map(|m| self.function_json(&self.call_graph[m.entry.symbol.as_str()]))
+                    .collect();
+                Ok(json!(functions))
+            }
+            "callgraph/callers" => self.neighbors(&parse_params(params)?, false, true),
+            "callgraph/callees" => self.neighbors(&parse_params(params)?, true, false),
+            "callgraph/subgraph" => self.subgraph(&parse_params(params)?),
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Unknown method: {method}"),
+            }),
+        }
+    }
+
+    fn function_json(&self, node: &FunctionNode) -> Value {
+        json!({
+            "symbol": node.symbol,
+            "display_name": node.display_name,
+            "relative_path": node.relative_path,
+            "line": node.start_line,
+            "uri": self.file_of(node).map(|path| file_uri(&path)),
+        })
+    }
+
+    fn resolve_all(&self, name: &str) -> Result<Vec<&FunctionNode>, RpcError> {
+        let symbols = self
+            .resolver
+            .resolve_all(name)
+            .map_err(RpcError::invalid_params)?;
+        Ok(symbols
+            .into_iter()
+            .map(|symbol| &self.call_graph[symbol])
+            .collect())
+    }
+
+    /// Callees or callers within `params.depth` calls, closest first
+    fn neighbors(
+        &self,
+        params: &NeighborParams,
+        callees: bool,
+        callers: bool,
+    ) -> Result<Value, RpcError> {
+        let starts: Vec<&str> = self
+            .resolve_all(&params.function)?
+            .into_iter()
+            .map(|node| node.symbol.as_str())
+            .collect();
+        let found: Vec<Value> = neighborhood_by_depth(
+            &self.call_graph,
+            &starts,
+            callees,
+            callers,
+            Some(params.depth),
+        )
+        .into_iter()
+        .map(|(node, depth)| {
+            let mut entry = self.function_json(node);
+            entry["depth"] = json!(depth);
+            entry
+        })
+        .collect();
+        Ok(json!(found))
+    }
+
+    fn subgraph(&self, params: &SubgraphParams) -> Result<Value, RpcError> {
+        let mut seeds = HashSet::new();
+        for name in &params.functions {
+            seeds.extend(
+                self.resolve_all(name)?
+                    .into_iter()
+                    .map(|node| node.symbol.clone()),
+            );
+        }
+        let included = collect_neighborhood(
+            &self.call_graph,
+            &seeds,
+            params.callees,
+            params.callers,
+            params.depth,
+        );
+        let graph = call_graph_to_d3(
+            &self.call_graph.subgraph(&included),
+            &self.project_root.to_string_lossy(),
+            &NodeFilters::default(),
+        );
+        serde_json::to_value(graph).map_err(RpcError::internal)
+    }
+
+    fn functions_in(&self, uri: &str) -> impl Iterator<Item = &FunctionNode> {
+        uri_path(uri)
+            .and_then(|path| self.by_file.get(&path))
+            .into_iter()
+            .flatten()
+            .map(|symbol| &self.call_graph[symbol.as_str()])
+    }
+
+    /// The function called at a position
+    fn callee_at(&self, params: &PositionParams) -> Option<&FunctionNode> {
+        let Position { line, character } = params.position;
+        self.functions_in(&params.text_document.uri)
+            .flat_map(|node| &node.callee_occurrences)
+            .filter(|occurrence| occurrence.line == line)
+            .filter_map(|occurrence| {
+                let callee = self.call_graph.get(&occurrence.symbol)?;
+                let column = occurrence.column?;
+                let end = column + callee.display_name.chars().count() as i32;
+                (column <= character && character <= end).then_some(callee)
+            })
+            .next()
+    }
+
+    /// The function whose name is defined at a position
+    fn defined_at(&self, params: &PositionParams) -> Option<&FunctionNode> {
+        let Position { line, character } = params.position;
+        self.functions_in(&params.text_document.uri)
+            .find(|node| match node.range[..] {
+                [start_line, start, end] | [start_line, start, _, end] => {
+                    start_line == line && start <= character && character <= end
+                }
+                _ => false,
+            })
+    }
+
+    /// Location of a function's definition (its name when the range is known)
+    fn location_of(&self, node: &FunctionNode) -> Option<Value> {
+        let uri = file_uri(&self.file_of(node)?);
+        let range = match node.range[..] {
+            [line, start, end] => lsp_range(line, start, end),
+            [start_line, start, end_line, end] => json!({
+                "start": { "line": start_line, "character": start },
+                "end": { "line": end_line, "character": end },
+            }),
+            _ => {
+                let line = node.start_line? as i32 - 1;
+                lsp_range(line, 0, 0)
+            }
+        };
+        Some(json!({ "uri": uri, "range": range }))
+    }
+
+    fn definition(&self, params: &PositionParams) -> Result<Value, RpcError> {
+        let target = self.callee_at(params).or_else(|| self.defined_at(params));
+        Ok(target
+            .and_then(|node| self.location_of(node))
+            .unwrap_or(Value::Null))
+    }
+
+    fn references(&self, params: &PositionParams) -> Result<Value, RpcError> {
+        let Some(target) = self.defined_at(params).or_else(|| self.callee_at(params)) else {
+            return Ok(Value::Null);
+        };
+        let length = target.display_name.chars().count() as i32;
+
+        let mut locations: Vec<(String, i32, i32)> = Vec::new();
+        for caller in target
+            .callers
+            .iter()
+            .filter_map(|symbol| self.call_graph.get(symbol))
+        {
+            let Some(path) = self.file_of(caller) else {
+                continue;
+            };
+            for occurrence in &caller.callee_occurrences {
+                if let (true, Some(column)) =
+                    (occurrence.symbol == target.symbol, occurrence.column)
+                {
+                    locations.push((file_uri(&path), occurrence.line, column));
+                }
+            }
+        }
+        locations.sort();
+        locations.dedup();
+
+        let mut references: Vec<Value> = Vec::new();
+        if params.context.include_declaration {
+            references.extend(self.location_of(target));
+        }
+        references.extend(locations.into_iter().map(|(uri, line, column)| {
+            json!({ "uri": uri, "range": lsp_range(line, column, column + length) })
+        }));
+        Ok(json!(references))
+    }
+}
+
+/// Read the body of one `Content-Length` framed message; `None` at the end
+/// of the input
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            let value = value
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            length = Some(value);
+        }
+    }
+    let mut body = vec![0; length.unwrap_or_default()];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+/// Answer framed requests from `reader` until the input ends or an `exit`
+/// notification arrives
+pub fn serve(server: &Server, mut reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+    while let Some(body) = read_message(&mut reader)? {
+        let message: Value = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(e) => {
+                let error = RpcError {
+                    code: PARSE_ERROR,
+                    message: e.to_string(),
+                };
+                write_message(&mut writer, &error_response(Value::Null, error))?;
+                continue;
+            }
+        };
+        let method = message["method"].as_str().unwrap_or_default();
+        // Notifications get no response
+        let Some(id) = message.get("id").cloned() else {
+            if method == "exit" {
+                break;
+            }
+            continue;
+        };
+        let response = match server.handle(method, &message["params"]) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        };
+        write_message(&mut writer, &response)?;
+    }
+    Ok(())
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Loading {}...", args.input);
+    let call_graph = load_call_graph(&args.input)?;
+    let project_root = match &args.project_root {
+        Some(root) => PathBuf::from(root),
+        None => std::env::current_dir()?,
+    };
+    info!(
+        "Serving queries on {} functions over stdio",
+        call_graph.len()
+    );
+    let server = Server::new(call_graph, project_root);
+    serve(&server, io::stdin().lock(), io::stdout().lock())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scip_core::CalleeOccurrence;
+
+    fn node(symbol: &str, range: Vec<i32>, calls: &[(&str, i32, i32)]) -> FunctionNode {
+        FunctionNode {
+            symbol: symbol.to_string(),
+            display_name: symbol.to_string(),
+            file_path: "/project/src/lib.rs".to_string(),
+            relative_path: "src/lib.rs".to_string(),
+            callees: calls
+                .iter()
+                .map(|(callee, _, _)| callee.to_string())
+                .collect(),
+            callee_occurrences: calls
+                .iter()
+                .map(|(callee, line, column)| CalleeOccurrence {
+                    symbol: callee.to_string(),
+                    line: *line,
+                    column: Some(*column),
+                    location: None,
+                    edge_kind: None,
+                })
+                .collect(),
+            range,
+            ..Default::default()
+        }
+    }
+
+    fn frame(message: Value) -> String {
+        let body = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    #[test]
+    fn test_lsp_server() {
+        // main (line 0) calls helper on lines 1 and 2; helper (line 5) calls leaf
+        let mut graph: HashMap<String, FunctionNode> = [
+            node("main", vec![0, 3, 7], &[("helper", 1, 4), ("helper", 2, 4)]),
+            node("helper", vec![5, 3, 9], &[("leaf", 6, 4)]),
+            node("leaf", vec![9, 3, 7], &[]),
+        ]
+        .into_iter()
+        .map(|node| (node.symbol.clone(), node))
+        .collect();
+        graph
+            .get_mut("helper")
+            .unwrap()
+            .callers
+            .insert("main".into());
+        graph
+            .get_mut("leaf")
+            .unwrap()
+            .callers
+            .insert("helper".into());
+        let server = Server::new(graph.into(), PathBuf::from("/project"));
+
+        let at = |line: i32, character: i32| {
+            json!({
+                "textDocument": { "uri": "file:///project/src/lib.rs" },
+                "position": { "line": line, "character": character },
+                "context": { "includeDeclaration": false },
+            })
+        };
+        let definition = server.handle("textDocument/definition", &at(1, 6)).unwrap();
+        assert_eq!(definition["range"]["start"]["line"], 5);
+        assert_eq!(definition["uri"], "file:///project/src/lib.rs");
+
+        let references = server.handle("textDocument/references", &at(5, 4)).unwrap();
+        let lines: Vec<&Value> = references
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| &r["range"]["start"]["line"])
+            .collect();
+        assert_eq!(lines, vec![1, 2]);
+
+        let callees = server
+            .handle(
+                "callgraph/callees",
+                &json!({ "function": "main", "depth": 2 }),
+            )
+            .unwrap();
+        assert_eq!(callees[1]["symbol"], "leaf");
+        assert_eq!(callees[1]["depth"], 2);
+        let error = server
+            .handle("callgraph/callers", &json!({ "function": "missing" }))
+            .unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS);
+
+        let input = [
+            frame(json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} })),
+            frame(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} })),
+            frame(
+                json!({ "jsonrpc": "2.0", "id": 2, "method": "callgraph/subgraph",
+                          "params": { "functions": ["helper"] } }),
+            ),
+            frame(json!({ "jsonrpc": "2.0", "id": 3, "method": "nope" })),
+            frame(json!({ "jsonrpc": "2.0", "method": "exit" })),
+        ]
+        .concat();
+        let mut output = Vec::new();
+        serve(&server, input.as_bytes(), &mut output).unwrap();
+
+        let mut reader = output.as_slice();
+        let mut responses = Vec::new();
+        while let Some(body) = read_message(&mut reader).unwrap() {
+            responses.push(serde_json::from_slice::<Value>(&body).unwrap());
+        }
+        assert_eq!(responses.len(), 3);
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["definitionProvider"],
+            true
+        );
+        assert_eq!(responses[1]["result"]["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(responses[2]["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
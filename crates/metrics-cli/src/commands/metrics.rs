@@ -0,0 +1,1030 @@
+//! Compute Verus specification metrics using verus_syn AST parsing
+//!
+//! Uses verus_syn to parse entire function items, extracting specs directly
+//! from the AST instead of using string manipulation.
+//!
+//! Benefits:
+//! - Robust parsing (no edge cases from string matching)
+//! - Function mode (exec/proof/spec) extracted automatically
+//! - Specs already parsed as expressions
+//! - Loop invariants and `assert(...) by` found in the body, not just the signature
+//! - Cyclomatic/cognitive complexity without an external rust-code-analysis run
+//! - Ghost/tracked variable counts, flagging exec functions with heavy ghost
+//!   state (`--heavy-ghost-threshold`)
+//! - Clauses of functions that do not parse are analyzed one by one, and
+//!   clauses that still fail are counted in the summary
+//! - `--expand-spec-fns` also reports Halstead metrics of `requires` and
+//!   `ensures` clauses with the spec functions they call inlined
+//! - `--log-format json` reports progress and the summary as JSON events on
+//!   stderr, for CI wrappers
+//! - A progress bar while computing; ctrl-c stops early and writes the
+//!   metrics of the functions done so far
+//! - Clean, maintainable code
+
+use super::{aggregate, compare, metrics_export};
+use crate::monitor::cli_monitor;
+use quote::ToTokens;
+use scip_core::logging::{event, Step};
+use scip_core::parse_function_sections;
+use scip_core::parser::parse_atoms_json_str;
+use scip_core::progress;
+use scip_core::{render_distribution_table, write_atomic, Distribution, MetricDistribution};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use verus_metrics::{
+    analyze_block_complexity, analyze_expr, analyze_fn_ghost_state, analyze_specs_with, BatchStats,
+    DefaultProseDetector, GhostStateMetrics, HalsteadCollector, ProseDetector, RatioProseDetector,
+    SpecFnTable, SpecInliner, SpecOutcome, DEFAULT_INLINE_DEPTH, HEAVY_GHOST_STATE,
+};
+use verus_syn::visit::Visit;
+use verus_syn::{
+    Assert, Block, Expr, ExprForLoop, ExprLoop, ExprWhile, ImplItem, Item, ItemFn, TraitItem,
+};
+
+// Output format with metrics
+#[derive(Debug, Serialize)]
+struct AtomWithMetrics {
+    identifier: String,
+    statement_type: String,
+    deps: Vec<String>,
+    body: String,
+    display_name: String,
+    full_path: String,
+    relative_path: String,
+    file_name: String,
+    parent_folder: String,
+    metrics: FunctionMetrics,
+}
+
+#[derive(Debug, Serialize)]
+struct SpecHalsteadMetrics {
+    text: String,
+    halstead_length: Option<usize>,
+    halstead_difficulty: Option<f64>,
+    halstead_effort: Option<f64>,
+    halstead_vocabulary: Option<usize>,
+    halstead_volume: Option<f64>,
+    unique_operators: Option<usize>,
+    total_operators: Option<usize>,
+    unique_operands: Option<usize>,
+    total_operands: Option<usize>,
+    quantifier_count: Option<usize>,
+    max_quantifier_depth: Option<usize>,
+    trigger_count: Option<usize>,
+    quantified_variables: Option<usize>,
+    /// The same clause with the spec functions it calls inlined
+    /// (`--expand-spec-fns`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expanded: Option<ExpandedHalsteadMetrics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExpandedHalsteadMetrics {
+    /// Spec function calls inlined, nested ones included
+    inlined_calls: usize,
+    halstead_length: usize,
+    halstead_difficulty: f64,
+    halstead_effort: f64,
+    halstead_volume: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionMetrics {
+    /// Function mode: exec, proof, or spec
+    function_mode: String,
+    requires_count: usize,
+    requires_lengths: Vec<usize>,
+    requires_specs: Vec<SpecHalsteadMetrics>,
+    ensures_count: usize,
+    ensures_lengths: Vec<usize>,
+    ensures_specs: Vec<SpecHalsteadMetrics>,
+    decreases_count: usize,
+    decreases_specs: Vec<SpecHalsteadMetrics>,
+    /// Loop `invariant` / `invariant_except_break` clauses in the body
+    invariant_count: usize,
+    invariant_specs: Vec<SpecHalsteadMetrics>,
+    /// Asserted expressions of `assert(...) by` in the body
+    assert_by_count: usize,
+    assert_by_specs: Vec<SpecHalsteadMetrics>,
+    body_length: usize,
+    /// Cyclomatic complexity of the body (None if there is no body)
+    cyclomatic: Option<usize>,
+    /// Cognitive complexity of the body (None if there is no body)
+    cognitive: Option<usize>,
+    /// Deepest nesting of control flow in the body
+    max_nesting: Option<usize>,
+    /// Ghost/tracked declarations and uses (None if the function does not
+    /// parse)
+    ghost_state: Option<GhostStateMetrics>,
+    /// Exec function with at least `--heavy-ghost-threshold` pieces of
+    /// ghost state
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    heavy_ghost_state: bool,
+    operators: HashMap<String, usize>,
+    /// Outcomes of the clauses analyzed from text (set when the function
+    /// itself does not parse)
+    #[serde(skip)]
+    text_clause_stats: Option<BatchStats>,
+}
+
+impl Default for FunctionMetrics {
+    fn default() -> Self {
+        Self {
+            function_mode: "exec".to_string(),
+            requires_count: 0,
+            requires_lengths: Vec::new(),
+            requires_specs: Vec::new(),
+            ensures_count: 0,
+            ensures_lengths: Vec::new(),
+            ensures_specs: Vec::new(),
+            decreases_count: 0,
+            decreases_specs: Vec::new(),
+            invariant_count: 0,
+            invariant_specs: Vec::new(),
+            assert_by_count: 0,
+            assert_by_specs: Vec::new(),
+            body_length: 0,
+            cyclomatic: None,
+            cognitive: None,
+            max_nesting: None,
+            ghost_state: None,
+            heavy_ghost_state: false,
+            operators: HashMap::new(),
+            text_clause_stats: None,
+        }
+    }
+}
+
+// ============================================================================
+// Halstead Metrics Computation (from verus_syn Expr)
+// ============================================================================
+
+/// Compute Halstead metrics from a verus_syn Expr
+fn compute_halstead_from_expr(expr: &Expr) -> SpecHalsteadMetrics {
+    halstead_entry(expr.to_token_stream().to_string(), &analyze_expr(expr))
+}
+
+/// Halstead metrics of a `requires`/`ensures` clause, with the expanded
+/// values next to the syntactic ones when inlining spec functions
+fn compute_clause_halstead(expr: &Expr, inliner: Option<&SpecInliner>) -> SpecHalsteadMetrics {
+    let mut entry = compute_halstead_from_expr(expr);
+    if let Some(inliner) = inliner {
+        let (metrics, inlined_calls) = inliner.analyze(expr);
+        entry.expanded = Some(ExpandedHalsteadMetrics {
+            inlined_calls,
+            halstead_length: metrics.halstead_length,
+            halstead_difficulty: metrics.difficulty,
+            halstead_effort: metrics.effort,
+            halstead_volume: metrics.volume,
+        });
+    }
+    entry
+}
+
+fn halstead_entry(
+    text: String,
+    metrics: &verus_metrics::SpecHalsteadMetrics,
+) -> SpecHalsteadMetrics {
+    SpecHalsteadMetrics {
+        text,
+        halstead_length: Some(metrics.halstead_length),
+        halstead_difficulty: Some(metrics.difficulty),
+        halstead_effort: Some(metrics.effort),
+        halstead_vocabulary: Some(metrics.vocabulary),
+        halstead_volume: Some(metrics.volume),
+        unique_operators: Some(metrics.n1_unique_operators),
+        total_operators: Some(metrics.n1_total_operators),
+        unique_operands: Some(metrics.n2_unique_operands),
+        total_operands: Some(metrics.n2_total_operands),
+        quantifier_count: Some(metrics.quantifier_count),
+        max_quantifier_depth: Some(metrics.max_quantifier_depth),
+        trigger_count: Some(metrics.trigger_count),
+        quantified_variables: Some(metrics.quantified_variables),
+        expanded: None,
+        parse_error: None,
+    }
+}
+
+/// Entry for a clause analyzed from text, with the reason if it has no metrics
+fn halstead_entry_from_outcome(text: &str, outcome: &SpecOutcome) -> SpecHalsteadMetrics {
+    let parse_error = match outcome {
+        SpecOutcome::Ok(metrics) => return halstead_entry(text.to_string(), metrics),
+        SpecOutcome::SkippedProse { confidence } => {
+            format!("skipped prose (confidence {:.2})", confidence)
+        }
+        SpecOutcome::ParseError { message, .. } => message.clone(),
+    };
+    SpecHalsteadMetrics {
+        text: text.to_string(),
+        halstead_length: None,
+        halstead_difficulty: None,
+        halstead_effort: None,
+        halstead_vocabulary: None,
+        halstead_volume: None,
+        unique_operators: None,
+        total_operators: None,
+        unique_operands: None,
+        total_operands: None,
+        quantifier_count: None,
+        max_quantifier_depth: None,
+        trigger_count: None,
+        quantified_variables: None,
+        expanded: None,
+        parse_error: Some(parse_error),
+    }
+}
+
+/// Split a `requires`/`ensures` section (inclusive 0-based line range) into
+/// clause texts: groups of lines ending in a comma outside brackets
+fn split_clauses(lines: &[&str], range: Option<(i32, i32)>, keyword: &str) -> Vec<String> {
+    let Some((start, end)) = range else {
+        return Vec::new();
+    };
+    let (start, end) = (start.max(0) as usize, end.max(0) as usize);
+
+    let mut clauses = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    for (i, line) in lines.iter().enumerate().take(end + 1).skip(start) {
+        let mut text = line.trim();
+        if i == start {
+            text = text.strip_prefix(keyword).unwrap_or(text).trim_start();
+        } else if depth == 0 && text.starts_with("decreases") {
+            break;
+        }
+        for ch in text.chars() {
+            match ch {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        current.push_str(text);
+        current.push('\n');
+        if depth <= 0 && text.ends_with(',') {
+            clauses.push(current.trim().trim_end_matches(',').to_string());
+            current.clear();
+            depth = 0;
+        }
+    }
+    if !current.trim().is_empty() {
+        clauses.push(current.trim().to_string());
+    }
+    clauses
+}
+
+/// Analyze each clause text of a section, recording outcomes in `stats`
+fn analyze_clause_texts(
+    clauses: &[String],
+    detector: &dyn ProseDetector,
+    stats: &mut BatchStats,
+) -> Vec<SpecHalsteadMetrics> {
+    let texts: Vec<&str> = clauses.iter().map(String::as_str).collect();
+    let batch = analyze_specs_with(&texts, detector);
+    stats.merge(&batch.stats);
+    texts
+        .iter()
+        .zip(&batch.outcomes)
+        .map(|(text, outcome)| halstead_entry_from_outcome(text, outcome))
+        .collect()
+}
+
+// ============================================================================
+// Body Clause Extraction (loop invariants, assert-by, complexity)
+// ============================================================================
+
+/// Collects loop invariants and `assert(...) by` expressions, including those
+/// in nested loops and blocks
+#[derive(Default)]
+struct BodyClauseVisitor<'ast> {
+    invariants: Vec<&'ast Expr>,
+    assert_bys: Vec<&'ast Expr>,
+}
+
+impl<'ast> Visit<'ast> for BodyClauseVisitor<'ast> {
+    fn visit_expr_while(&mut self, node: &'ast ExprWhile) {
+        if let Some(invariant) = &node.invariant_except_break {
+            self.invariants.extend(invariant.exprs.exprs.iter());
+        }
+        if let Some(invariant) = &node.invariant {
+            self.invariants.extend(invariant.exprs.exprs.iter());
+        }
+        verus_syn::visit::visit_expr_while(self, node);
+    }
+
+    fn visit_expr_loop(&mut self, node: &'ast ExprLoop) {
+        if let Some(invariant) = &node.invariant_except_break {
+            self.invariants.extend(invariant.exprs.exprs.iter());
+        }
+        if let Some(invariant) = &node.invariant {
+            self.invariants.extend(invariant.exprs.exprs.iter());
+        }
+        verus_syn::visit::visit_expr_loop(self, node);
+    }
+
+    fn visit_expr_for_loop(&mut self, node: &'ast ExprForLoop) {
+        if let Some(invariant) = &node.invariant {
+            self.invariants.extend(invariant.exprs.exprs.iter());
+        }
+        verus_syn::visit::visit_expr_for_loop(self, node);
+    }
+
+    fn visit_assert(&mut self, node: &'ast Assert) {
+        // Plain `assert(e);` is a single SMT query; only `by` carries a proof
+        if node.by_token.is_some() || node.body.is_some() {
+            self.assert_bys.push(&node.expr);
+        }
+        verus_syn::visit::visit_assert(self, node);
+    }
+}
+
+/// Fill in loop invariant, assert-by and complexity metrics from a function body
+fn extract_body_clauses(block: &Block, metrics: &mut FunctionMetrics) {
+    let complexity = analyze_block_complexity(block);
+    metrics.cyclomatic = Some(complexity.cyclomatic);
+    metrics.cognitive = Some(complexity.cognitive);
+    metrics.max_nesting = Some(complexity.max_nesting);
+
+    let mut visitor = BodyClauseVisitor::default();
+    visitor.visit_block(block);
+
+    metrics.invariant_count = visitor.invariants.len();
+    metrics.invariant_specs = visitor
+        .invariants
+        .iter()
+        .map(|e| compute_halstead_from_expr(e))
+        .collect();
+    metrics.assert_by_count = visitor.assert_bys.len();
+    metrics.assert_by_specs = visitor
+        .assert_bys
+        .iter()
+        .map(|e| compute_halstead_from_expr(e))
+        .collect();
+}
+
+// ============================================================================
+// Function Mode Extraction
+// ============================================================================
+
+fn fn_mode_to_string(mode: &verus_syn::FnMode) -> String {
+    match mode {
+        verus_syn::FnMode::Default => "exec".to_string(),
+        verus_syn::FnMode::Spec(_) => "spec".to_string(),
+        verus_syn::FnMode::SpecChecked(_) => "spec".to_string(),
+        verus_syn::FnMode::Proof(_) => "proof".to_string(),
+        verus_syn::FnMode::ProofAxiom(_) => "proof".to_string(),
+        verus_syn::FnMode::Exec(_) => "exec".to_string(),
+    }
+}
+
+// ============================================================================
+// Main Metrics Extraction from ItemFn
+// ============================================================================
+
+/// Extract metrics from an ItemFn using verus_syn's structured parsing
+fn extract_metrics_from_item_fn(
+    item_fn: &ItemFn,
+    inliner: Option<&SpecInliner>,
+) -> FunctionMetrics {
+    let mut metrics = FunctionMetrics {
+        function_mode: fn_mode_to_string(&item_fn.sig.mode),
+        ..Default::default()
+    };
+
+    // Extract requires clauses
+    if let Some(requires) = &item_fn.sig.spec.requires {
+        let exprs: Vec<&Expr> = requires.exprs.exprs.iter().collect();
+        metrics.requires_count = exprs.len();
+        metrics.requires_lengths = exprs
+            .iter()
+            .map(|e| e.to_token_stream().to_string().len())
+            .collect();
+        metrics.requires_specs = exprs
+            .iter()
+            .map(|e| compute_clause_halstead(e, inliner))
+            .collect();
+    }
+
+    // Extract ensures clauses
+    if let Some(ensures) = &item_fn.sig.spec.ensures {
+        let exprs: Vec<&Expr> = ensures.exprs.exprs.iter().collect();
+        metrics.ensures_count = exprs.len();
+        metrics.ensures_lengths = exprs
+            .iter()
+            .map(|e| e.to_token_stream().to_string().len())
+            .collect();
+        metrics.ensures_specs = exprs
+            .iter()
+            .map(|e| compute_clause_halstead(e, inliner))
+            .collect();
+    }
+
+    // Extract decreases clauses
+    if let Some(decreases) = &item_fn.sig.spec.decreases {
+        let exprs: Vec<&Expr> = decreases.decreases.exprs.exprs.iter().collect();
+        metrics.decreases_count = exprs.len();
+        metrics.decreases_specs = exprs
+            .iter()
+            .map(|e| compute_halstead_from_expr(e))
+            .collect();
+    }
+
+    // Compute body length (the actual function block)
+    metrics.body_length = item_fn.block.to_token_stream().to_string().len();
+    extract_body_clauses(&item_fn.block, &mut metrics);
+    metrics.ghost_state = Some(analyze_fn_ghost_state(&item_fn.sig, Some(&item_fn.block)));
+
+    // Count operators in body
+    let mut body_collector = HalsteadCollector::default();
+    for stmt in &item_fn.block.stmts {
+        body_collector.visit_stmt(stmt);
+    }
+    metrics.operators = body_collector.operator_frequencies();
+
+    metrics
+}
+
+/// Try to parse body as different Verus item types; with an `inliner`,
+/// `requires`/`ensures` clauses also get expanded Halstead metrics
+fn compute_function_metrics(
+    body: &str,
+    inliner: Option<&SpecInliner>,
+    detector: &dyn ProseDetector,
+) -> FunctionMetrics {
+    // Attempt 1: Parse as standalone ItemFn
+    if let Ok(item_fn) = verus_syn::parse_str::<ItemFn>(body) {
+        return extract_metrics_from_item_fn(&item_fn, inliner);
+    }
+
+    // Attempt 2: Parse as Item (covers more cases)
+    if let Ok(Item::Fn(item_fn)) = verus_syn::parse_str::<Item>(body) {
+        return extract_metrics_from_item_fn(&item_fn, inliner);
+    }
+
+    // Attempt 3: Parse as ImplItemFn (method inside impl block)
+    if let Ok(ImplItem::Fn(impl_fn)) = verus_syn::parse_str::<ImplItem>(body) {
+        let mut metrics = FunctionMetrics {
+            function_mode: fn_mode_to_string(&impl_fn.sig.mode),
+            ..Default::default()
+        };
+
+        if let Some(requires) = &impl_fn.sig.spec.requires {
+            let exprs: Vec<&Expr> = requires.exprs.exprs.iter().collect();
+            metrics.requires_count = exprs.len();
+            metrics.requires_lengths = exprs
+                .iter()
+                .map(|e| e.to_token_stream().to_string().len())
+                .collect();
+            metrics.requires_specs = exprs
+                .iter()
+                .map(|e| compute_clause_halstead(e, inliner))
+                .collect();
+        }
+
+        if let Some(ensures) = &impl_fn.sig.spec.ensures {
+            let exprs: Vec<&Expr> = ensures.exprs.exprs.iter().collect();
+            metrics.ensures_count = exprs.len();
+            metrics.ensures_lengths = exprs
+                .iter()
+                .map(|e| e.to_token_stream().to_string().len())
+                .collect();
+            metrics.ensures_specs = exprs
+                .iter()
+                .map(|e| compute_clause_halstead(e, inliner))
+                .collect();
+        }
+
+        if let Some(decreases) = &impl_fn.sig.spec.decreases {
+            let exprs: Vec<&Expr> = decreases.decreases.exprs.exprs.iter().collect();
+            metrics.decreases_count = exprs.len();
+            metrics.decreases_specs = exprs
+                .iter()
+                .map(|e| compute_halstead_from_expr(e))
+                .collect();
+        }
+
+        metrics.body_length = impl_fn.block.to_token_stream().to_string().len();
+        extract_body_clauses(&impl_fn.block, &mut metrics);
+        metrics.ghost_state = Some(analyze_fn_ghost_state(&impl_fn.sig, Some(&impl_fn.block)));
+        return metrics;
+    }
+
+    // Attempt 4: Parse as TraitItemFn
+    if let Ok(TraitItem::Fn(trait_fn)) = verus_syn::parse_str::<TraitItem>(body) {
+        let mut metrics = FunctionMetrics {
+            function_mode: fn_mode_to_string(&trait_fn.sig.mode),
+            ..Default::default()
+        };
+
+        if let Some(requires) = &trait_fn.sig.spec.requires {
+            let exprs: Vec<&Expr> = requires.exprs.exprs.iter().collect();
+            metrics.requires_count = exprs.len();
+            metrics.requires_lengths = exprs
+                .iter()
+                .map(|e| e.to_token_stream().to_string().len())
+                .collect();
+            metrics.requires_specs = exprs
+                .iter()
+                .map(|e| compute_clause_halstead(e, inliner))
+                .collect();
+        }
+
+        if let Some(ensures) = &trait_fn.sig.spec.ensures {
+            let exprs: Vec<&Expr> = ensures.exprs.exprs.iter().collect();
+            metrics.ensures_count = exprs.len();
+            metrics.ensures_lengths = exprs
+                .iter()
+                .map(|e| e.to_token_stream().to_string().len())
+                .collect();
+            metrics.ensures_specs = exprs
+                .iter()
+                .map(|e| compute_clause_halstead(e, inliner))
+                .collect();
+        }
+
+        if let Some(decreases) = &trait_fn.sig.spec.decreases {
+            let exprs: Vec<&Expr> = decreases.decreases.exprs.exprs.iter().collect();
+            metrics.decreases_count = exprs.len();
+            metrics.decreases_specs = exprs
+                .iter()
+                .map(|e| compute_halstead_from_expr(e))
+                .collect();
+        }
+
+        if let Some(block) = &trait_fn.default {
+            metrics.body_length = block.to_token_stream().to_string().len();
+            extract_body_clauses(block, &mut metrics);
+        }
+        metrics.ghost_state = Some(analyze_fn_ghost_state(
+            &trait_fn.sig,
+            trait_fn.default.as_ref(),
+        ));
+        return metrics;
+    }
+
+    // Fallback: the function does not parse (non-function bodies or
+    // incomplete fragments), so find its clauses by line and analyze them
+    // one by one
+    let sections = parse_function_sections(body, 0);
+    let lines: Vec<&str> = body.lines().collect();
+    let requires = split_clauses(&lines, sections.requires_range, "requires");
+    let ensures = split_clauses(&lines, sections.ensures_range, "ensures");
+
+    let mut stats = BatchStats::default();
+    FunctionMetrics {
+        function_mode: "unknown".to_string(),
+        requires_count: requires.len(),
+        requires_lengths: requires.iter().map(String::len).collect(),
+        requires_specs: analyze_clause_texts(&requires, detector, &mut stats),
+        ensures_count: ensures.len(),
+        ensures_lengths: ensures.iter().map(String::len).collect(),
+        ensures_specs: analyze_clause_texts(&ensures, detector, &mut stats),
+        body_length: body.len(),
+        text_clause_stats: Some(stats),
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// Main
+// ============================================================================
+
+/// Compute Verus specification metrics of an atoms JSON
+#[derive(clap::Args, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<MetricsCommand>,
+
+    /// Atoms JSON, e.g. from write_atoms
+    #[arg(required = true)]
+    input_atoms_json: Option<String>,
+
+    /// Output atoms JSON with metrics
+    #[arg(required = true)]
+    output_metrics_json: Option<String>,
+
+    /// Flag exec functions with at least N ghost/tracked declarations,
+    /// uses and wrappers as `heavy_ghost_state`
+    #[arg(long, value_name = "N", default_value_t = HEAVY_GHOST_STATE)]
+    heavy_ghost_threshold: usize,
+
+    /// Also compute Halstead metrics of requires/ensures clauses with the
+    /// spec functions they call (per the atoms' deps) inlined, reported as
+    /// `expanded` next to the syntactic values
+    #[arg(long)]
+    expand_spec_fns: bool,
+
+    /// How deep to inline spec functions called by inlined spec functions
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_INLINE_DEPTH, requires = "expand_spec_fns")]
+    inline_depth: usize,
+
+    /// Prose indicator phrases (one per line) added to the default prose
+    /// detector, for clauses that don't parse
+    #[arg(long, value_name = "FILE")]
+    prose_patterns: Option<String>,
+
+    /// Detect prose clauses by operator density instead of indicator phrases
+    #[arg(long, conflicts_with = "prose_patterns")]
+    ratio_prose: bool,
+}
+
+/// The prose detector selected by `--prose-patterns` / `--ratio-prose`
+fn prose_detector(args: &Args) -> Result<Box<dyn ProseDetector>, String> {
+    if args.ratio_prose {
+        return Ok(Box::new(RatioProseDetector::default()));
+    }
+    let detector = DefaultProseDetector::default();
+    match &args.prose_patterns {
+        Some(path) => detector
+            .with_indicators_from_file(path)
+            .map(|detector| Box::new(detector) as Box<dyn ProseDetector>)
+            .map_err(|e| format!("Failed to read prose patterns from {}: {}", path, e)),
+        None => Ok(Box::new(detector)),
+    }
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum MetricsCommand {
+    /// Delta report between the metrics of two revisions (spec effort
+    /// increases, new functions without specs, proofs using more lemmas)
+    Compare(compare::Args),
+    /// Per-file, module, impl block or trait rollups (sum/mean/max) of spec
+    /// effort, proof depth and verification failures
+    Aggregate(aggregate::Args),
+    /// Export functions, module rollups, budget violations and trends as a
+    /// spreadsheet (`--format xlsx`), or verification health as Prometheus
+    /// gauges (`--format prometheus`, optionally served with `--serve`)
+    Export(metrics_export::Args),
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    match &args.command {
+        Some(MetricsCommand::Compare(args)) => return compare::run(args),
+        Some(MetricsCommand::Aggregate(args)) => return aggregate::run(args),
+        Some(MetricsCommand::Export(args)) => return metrics_export::run(args),
+        None => {}
+    }
+    let (Some(input_path), Some(output_path)) = (&args.input_atoms_json, &args.output_metrics_json)
+    else {
+        return Err("expected an input atoms JSON and an output metrics JSON".into());
+    };
+
+    progress!("Loading atoms from {}...", input_path);
+    let step = Step::start("load_atoms");
+    let content =
+        fs::read_to_string(input_path).map_err(|e| format!("Failed to read input file: {}", e))?;
+
+    // Input format from write_atoms (versioned envelope or legacy array)
+    let atoms = parse_atoms_json_str(&content)
+        .map_err(|e| format!("Failed to parse input JSON: {}", e))?
+        .atoms;
+
+    progress!("  Loaded {} functions", atoms.len());
+    step.finish(json!({ "functions": atoms.len() }));
+
+    let detector = prose_detector(args)?;
+    let spec_fns = args
+        .expand_spec_fns
+        .then(|| SpecFnTable::from_atoms(&atoms));
+    if let Some(spec_fns) = &spec_fns {
+        progress!(
+            "  Inlining {} spec functions (depth {})",
+            spec_fns.len(),
+            args.inline_depth
+        );
+    }
+
+    progress!("Computing metrics (using verus_syn AST parsing)...");
+    let step = Step::start("compute_metrics");
+    let monitor = cli_monitor();
+    monitor
+        .progress()
+        .start("Computing metrics", Some(atoms.len() as u64));
+    let atoms_with_metrics: Vec<AtomWithMetrics> = atoms
+        .iter()
+        .take_while(|_| !monitor.is_cancelled())
+        .map(|atom| {
+            let inliner = spec_fns
+                .as_ref()
+                .map(|table| SpecInliner::new(table, &atom.deps, args.inline_depth));
+            let mut metrics =
+                compute_function_metrics(&atom.body, inliner.as_ref(), detector.as_ref());
+            metrics.heavy_ghost_state = metrics.function_mode == "exec"
+                && metrics
+                    .ghost_state
+                    .as_ref()
+                    .is_some_and(|ghost| ghost.is_heavy(args.heavy_ghost_threshold));
+            monitor.progress().advance(1);
+            AtomWithMetrics {
+                identifier: atom.identifier.clone(),
+                statement_type: atom.statement_type.clone(),
+                deps: atom.deps.clone(),
+                body: atom.body.clone(),
+                display_name: atom.display_name.clone(),
+                full_path: atom.full_path.clone(),
+                relative_path: atom.relative_path.clone(),
+                file_name: atom.file_name.clone(),
+                parent_folder: atom.parent_folder.clone(),
+                metrics,
+            }
+        })
+        .collect();
+    monitor.progress().finish();
+    step.finish(json!({ "functions": atoms_with_metrics.len() }));
+
+    progress!("Writing output to {}...", output_path);
+    let step = Step::start("write_output");
+    let output_json = serde_json::to_string_pretty(&atoms_with_metrics)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+
+    write_atomic(Path::new(output_path), output_json)
+        .map_err(|e| format!("Failed to write output file: {}", e))?;
+
+    step.finish(serde_json::Value::Null);
+    if monitor.is_cancelled() {
+        return Err(format!(
+            "Cancelled after {} of {} functions; {} holds their metrics",
+            atoms_with_metrics.len(),
+            atoms.len(),
+            output_path
+        )
+        .into());
+    }
+    progress!("✓ Done!");
+
+    // Print summary statistics
+    let with_requires: usize = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.requires_count > 0)
+        .count();
+    let with_ensures: usize = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.ensures_count > 0)
+        .count();
+    let with_decreases: usize = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.decreases_count > 0)
+        .count();
+    let with_invariants: usize = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.invariant_count > 0)
+        .count();
+    let with_assert_bys: usize = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.assert_by_count > 0)
+        .count();
+    let with_ghost_state: usize = atoms_with_metrics
+        .iter()
+        .filter(|a| {
+            a.metrics
+                .ghost_state
+                .as_ref()
+                .is_some_and(|g| g.total() > 0)
+        })
+        .count();
+    let mut heavy_ghost: Vec<&AtomWithMetrics> = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.heavy_ghost_state)
+        .collect();
+    heavy_ghost.sort_by_key(|a| {
+        std::cmp::Reverse(
+            a.metrics
+                .ghost_state
+                .as_ref()
+                .map_or(0, GhostStateMetrics::total),
+        )
+    });
+
+    // Count by function mode
+    let exec_count = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.function_mode == "exec")
+        .count();
+    let proof_count = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.function_mode == "proof")
+        .count();
+    let spec_count = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.function_mode == "spec")
+        .count();
+    let unknown_count = atoms_with_metrics
+        .iter()
+        .filter(|a| a.metrics.function_mode == "unknown")
+        .count();
+
+    // Clauses of parsed functions are expressions already; the others were
+    // analyzed from text
+    let mut clause_stats = BatchStats::default();
+    for atom in &atoms_with_metrics {
+        match &atom.metrics.text_clause_stats {
+            Some(stats) => clause_stats.merge(stats),
+            None => {
+                let m = &atom.metrics;
+                let parsed = m.requires_count
+                    + m.ensures_count
+                    + m.decreases_count
+                    + m.invariant_count
+                    + m.assert_by_count;
+                clause_stats.total += parsed;
+                clause_stats.parsed += parsed;
+            }
+        }
+    }
+
+    // Spec effort per function (requires and ensures), syntactic or with
+    // spec functions inlined
+    let spec_effort = |expanded: bool| -> Vec<f64> {
+        atoms_with_metrics
+            .iter()
+            .filter_map(|a| {
+                let efforts: Vec<f64> = a
+                    .metrics
+                    .requires_specs
+                    .iter()
+                    .chain(&a.metrics.ensures_specs)
+                    .filter_map(|spec| {
+                        if expanded {
+                            Some(spec.expanded.as_ref()?.halstead_effort)
+                        } else {
+                            spec.halstead_effort
+                        }
+                    })
+                    .collect();
+                (!efforts.is_empty()).then(|| efforts.iter().sum())
+            })
+            .collect()
+    };
+    let inlined_calls: usize = atoms_with_metrics
+        .iter()
+        .flat_map(|a| {
+            a.metrics
+                .requires_specs
+                .iter()
+                .chain(&a.metrics.ensures_specs)
+        })
+        .filter_map(|spec| Some(spec.expanded.as_ref()?.inlined_calls))
+        .sum();
+
+    // Spread of the metrics, to spot outliers
+    let distributions: Vec<MetricDistribution> = [
+        (
+            "spec_length",
+            Distribution::of_counts(
+                atoms_with_metrics
+                    .iter()
+                    .map(|a| {
+                        a.metrics.requires_lengths.iter().sum::<usize>()
+                            + a.metrics.ensures_lengths.iter().sum::<usize>()
+                    })
+                    .filter(|&length| length > 0),
+            ),
+        ),
+        (
+            "fan_in",
+            Distribution::of_counts(
+                atoms
+                    .iter()
+                    .filter_map(|a| Some(a.graph_metrics.as_ref()?.fan_in)),
+            ),
+        ),
+        (
+            "spec_effort",
+            args.expand_spec_fns
+                .then(|| Distribution::of(&spec_effort(false)))
+                .flatten(),
+        ),
+        (
+            "spec_effort_expanded",
+            args.expand_spec_fns
+                .then(|| Distribution::of(&spec_effort(true)))
+                .flatten(),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(metric, distribution)| {
+        Some(MetricDistribution {
+            metric: metric.to_string(),
+            distribution: distribution?,
+        })
+    })
+    .collect();
+
+    event(
+        "summary",
+        json!({
+            "functions": atoms_with_metrics.len(),
+            "modes": {
+                "exec": exec_count,
+                "proof": proof_count,
+                "spec": spec_count,
+                "unknown": unknown_count,
+            },
+            "with_requires": with_requires,
+            "with_ensures": with_ensures,
+            "with_decreases": with_decreases,
+            "with_invariants": with_invariants,
+            "with_assert_by": with_assert_bys,
+            "with_ghost_state": with_ghost_state,
+            "heavy_ghost_state": heavy_ghost.len(),
+            "clauses": clause_stats.total,
+            "clauses_parsed": clause_stats.parsed,
+            "clauses_skipped_prose": clause_stats.skipped_prose,
+            "clause_parse_errors": clause_stats.parse_errors,
+            "inlined_spec_fn_calls": inlined_calls,
+            "distributions": distributions,
+        }),
+    );
+
+    progress!("\nSummary:");
+    progress!("  Total functions: {}", atoms_with_metrics.len());
+    progress!("  Function modes:");
+    progress!("    - exec: {}", exec_count);
+    progress!("    - proof: {}", proof_count);
+    progress!("    - spec: {}", spec_count);
+    progress!("    - unknown (parse failed): {}", unknown_count);
+    progress!("  Specs found:");
+    progress!("    - With requires: {}", with_requires);
+    progress!("    - With ensures: {}", with_ensures);
+    progress!("    - With decreases: {}", with_decreases);
+    progress!("    - With loop invariants: {}", with_invariants);
+    progress!("    - With assert-by: {}", with_assert_bys);
+    progress!("  Ghost state:");
+    progress!("    - With ghost/tracked variables: {}", with_ghost_state);
+    progress!(
+        "    - Exec functions with heavy ghost state (>= {}): {}",
+        args.heavy_ghost_threshold,
+        heavy_ghost.len()
+    );
+    for atom in heavy_ghost.iter().take(10) {
+        let total = atom
+            .metrics
+            .ghost_state
+            .as_ref()
+            .map_or(0, GhostStateMetrics::total);
+        progress!("        {} ({})", atom.display_name, total);
+    }
+    progress!("  Spec clauses: {}", clause_stats.total);
+    progress!("    - Parsed: {}", clause_stats.parsed);
+    progress!("    - Skipped as prose: {}", clause_stats.skipped_prose);
+    progress!("    - Failed to parse: {}", clause_stats.parse_errors);
+    progress!(
+        "    - Parse success rate: {:.1}%",
+        clause_stats.parse_success_rate() * 100.0
+    );
+    if args.expand_spec_fns {
+        progress!("    - Spec function calls inlined: {}", inlined_calls);
+    }
+    if !distributions.is_empty() {
+        progress!("\nDistributions (spec length in characters, over functions with specs):");
+        progress!("{}", render_distribution_table(&distributions).trim_end());
+    }
+
+    if let Some(example) = atoms_with_metrics
+        .iter()
+        .find(|a| a.metrics.requires_count > 0 || a.metrics.ensures_count > 0)
+    {
+        progress!("\nExample function with specs:");
+        progress!("  Name: {}", example.display_name);
+        progress!("  Mode: {}", example.metrics.function_mode);
+        progress!("  Requires: {}", example.metrics.requires_count);
+        progress!("  Ensures: {}", example.metrics.ensures_count);
+        progress!("  Body length: {}", example.metrics.body_length);
+        if let (Some(cyclomatic), Some(cognitive)) =
+            (example.metrics.cyclomatic, example.metrics.cognitive)
+        {
+            progress!("  Cyclomatic: {}, cognitive: {}", cyclomatic, cognitive);
+        }
+
+        if !example.metrics.requires_specs.is_empty() {
+            if let Some(first_req) = example.metrics.requires_specs.first() {
+                progress!("\n  First requires clause:");
+                progress!("    Text: {}", first_req.text);
+                if let Some(len) = first_req.halstead_length {
+                    progress!("    Halstead length: {}", len);
+                }
+                if let Some(diff) = first_req.halstead_difficulty {
+                    progress!("    Halstead difficulty: {:.2}", diff);
+                }
+                if let Some(effort) = first_req.halstead_effort {
+                    progress!("    Halstead effort: {:.2}", effort);
+                }
+                if let Some(expanded) = &first_req.expanded {
+                    progress!(
+                        "    Expanded Halstead effort: {:.2} ({} spec function calls inlined)",
+                        expanded.halstead_effort,
+                        expanded.inlined_calls
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+//! Metrics reports for consumers outside the toolchain (`metrics export`)
+//!
+//! `--format xlsx` writes an Excel workbook with function, module rollup,
+//! budget violation and trend sheets (see `scip_core::export_xlsx`; needs
+//! the `xlsx` feature). `--format prometheus` writes verification health
+//! gauges for node_exporter's textfile collector, or serves them over HTTP
+//! with `--serve`.
+
+use clap::ValueEnum;
+use log::{info, warn};
+use scip_core::{
+    add_verification_status_to_atoms, check_gate, read_history, render_prometheus, rollup_metrics,
+    write_atomic, D3Graph, GateThresholds, GroupBy,
+};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    /// Excel workbook (functions, modules, violations, trends)
+    Xlsx,
+    /// Prometheus text format gauges (verified/failed functions, spec
+    /// coverage, mean proof depth; totals and per module)
+    Prometheus,
+}
+
+/// Export a metrics JSON as a spreadsheet or as Prometheus gauges
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Atoms JSON with metrics and/or proof metrics
+    metrics_json: String,
+
+    /// Output file (required for xlsx; prometheus prints to stdout if
+    /// omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "xlsx")]
+    format: ExportFormat,
+
+    /// What to group functions by in the modules sheet: `file`, `module`,
+    /// `impl` or `trait`
+    #[arg(long, value_name = "GROUP", default_value = "module")]
+    group_by: GroupBy,
+
+    /// D3 graph JSON from `graph` with verification results, for the
+    /// failure counts
+    #[arg(long, value_name = "JSON")]
+    graph: Option<String>,
+
+    /// Thresholds TOML (as for `gate`) for the violations sheet
+    #[arg(long, value_name = "TOML")]
+    gate_config: Option<String>,
+
+    /// Metrics JSON of the base revision, for counting new assumes
+    #[arg(long, value_name = "JSON", requires = "gate_config")]
+    baseline: Option<String>,
+
+    /// History store from `history record`, for the trends sheet
+    #[arg(long, value_name = "JSONL")]
+    history: Option<String>,
+
+    /// Serve the prometheus gauges at http://ADDR/metrics instead of writing
+    /// them, re-reading the metrics (and graph) JSON on every scrape
+    #[arg(long, value_name = "ADDR", conflicts_with = "output")]
+    serve: Option<String>,
+}
+
+fn read_atoms(path: &str) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    info!("Reading metrics from {}...", path);
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Atoms of the metrics JSON, with verification status from the graph if given
+fn load_atoms(args: &Args) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    let mut atoms = read_atoms(&args.metrics_json)?;
+    if let Some(path) = &args.graph {
+        let graph: D3Graph = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let matched = add_verification_status_to_atoms(&mut atoms, &graph);
+        info!(
+            "Verification status for {} of {} functions",
+            matched,
+            atoms.len()
+        );
+    }
+    Ok(atoms)
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    match (args.format, &args.serve) {
+        (ExportFormat::Prometheus, Some(addr)) => return serve_prometheus(args, addr),
+        (ExportFormat::Prometheus, None) => return export_prometheus(args),
+        (ExportFormat::Xlsx, Some(_)) => return Err("--serve needs --format prometheus".into()),
+        (ExportFormat::Xlsx, None) => {}
+    }
+    let Some(output) = &args.output else {
+        return Err("--format xlsx needs an output file (-o)".into());
+    };
+
+    let atoms = load_atoms(args)?;
+    let rollup = rollup_metrics(&atoms, args.group_by);
+    let violations = match &args.gate_config {
+        Some(config) => {
+            let thresholds = GateThresholds::load(config)
+                .map_err(|e| format!("Failed to load {}: {}", config, e))?;
+            let baseline = args.baseline.as_deref().map(read_atoms).transpose()?;
+            check_gate(&atoms, baseline.as_deref(), &thresholds).violations
+        }
+        None => Vec::new(),
+    };
+    let history = match &args.history {
+        Some(path) => read_history(path)?,
+        None => Vec::new(),
+    };
+
+    export_xlsx(&atoms, &rollup, &violations, &history, output)?;
+    info!(
+        "✓ Wrote {} functions, {} groups, {} violations and {} runs to {}",
+        atoms.len(),
+        rollup.groups.len(),
+        violations.len(),
+        history.len(),
+        output
+    );
+    Ok(())
+}
+
+/// Write the gauges to the output file (atomically, so the textfile
+/// collector never reads a partial file) or stdout
+fn export_prometheus(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let text = render_prometheus(&load_atoms(args)?);
+    match &args.output {
+        Some(path) => {
+            write_atomic(Path::new(path), text)?;
+            info!("✓ Wrote Prometheus metrics to {}", path);
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Longest request (request line and headers) read before answering 431
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// How long a client may take to send its request (or to read the
+/// response) before it is dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Answer `GET /metrics` with freshly computed gauges until killed
+fn serve_prometheus(args: &Args, addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(addr)?;
+    info!(
+        "Serving Prometheus metrics at http://{}/metrics",
+        listener.local_addr()?
+    );
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let timeouts = stream
+            .set_read_timeout(Some(REQUEST_TIMEOUT))
+            .and_then(|()| stream.set_write_timeout(Some(REQUEST_TIMEOUT)));
+        if let Err(e) = timeouts {
+            warn!("Failed to set the request timeouts: {}", e);
+            continue;
+        }
+        let metrics = || {
+            load_atoms(args)
+                .map(|atoms| render_prometheus(&atoms))
+                .map_err(|e| e.to_string())
+        };
+        if let Err(e) = handle_request(&stream, &stream, metrics) {
+            warn!("Failed to answer metrics request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Read one HTTP request from `request` (at most [`MAX_REQUEST_BYTES`]) and
+/// answer it on `response`, with the gauges of `metrics` for `GET /metrics`
+fn handle_request(
+    request: impl Read,
+    mut response: impl Write,
+    metrics: impl FnOnce() -> Result<String, String>,
+) -> std::io::Result<()> {
+    // Request line, then headers up to the blank line
+    let mut reader = BufReader::new(request.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut complete = false;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 {
+        if header.trim().is_empty() {
+            complete = true;
+            break;
+        }
+        header.clear();
+    }
+
+    let mut request = request_line.split_whitespace();
+    let (status, body) = match (request.next(), request.next()) {
+        _ if !complete && reader.get_ref().limit() == 0 => (
+            "431 Request Header Fields Too Large",
+            "Request too large\n".to_string(),
+        ),
+        _ if !complete => ("400 Bad Request", "Incomplete request\n".to_string()),
+        (Some("GET"), Some("/metrics")) => match metrics() {
+            Ok(body) => ("200 OK", body),
+            Err(e) => {
+                warn!("Failed to compute metrics: {}", e);
+                ("500 Internal Server Error", format!("{}\n", e))
+            }
+        },
+        _ => (
+            "404 Not Found",
+            "Not found; metrics are at /metrics\n".to_string(),
+        ),
+    };
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    );
+    response.write_all(head.as_bytes())?;
+    response.write_all(body.as_bytes())
+}
+
+#[cfg(feature = "xlsx")]
+fn export_xlsx(
+    atoms: &[serde_json::Value],
+    rollup: &scip_core::MetricsRollup,
+    violations: &[scip_core::GateViolation],
+    history: &[scip_core::RunSummary],
+    output: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let workbook = scip_core::MetricsWorkbook {
+        atoms,
+        rollup,
+        violations,
+        history,
+    };
+    Ok(workbook.save(output)?)
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn export_xlsx(
+    _atoms: &[serde_json::Value],
+    _rollup: &scip_core::MetricsRollup,
+    _violations: &[scip_core::GateViolation],
+    _history: &[scip_core::RunSummary],
+    _output: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("Excel export requires building with the `xlsx` feature".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Status line and body of the response to `request`
+    fn respond(request: &[u8]) -> (String, String) {
+        let mut response = Vec::new();
+        handle_request(request, &mut response, || {
+            Ok("verus_functions 3\n".to_string())
+        })
+        .unwrap();
+        let response = String::from_utf8(response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let (status, body) = respond(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body, "verus_functions 3\n");
+
+        let (status, _) = respond(b"GET / HTTP/1.1\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+
+        // Metrics are not computed for requests cut short or too large
+        let mut response = Vec::new();
+        handle_request(&b"GET /metrics HTTP/1.1\r\nHost"[..], &mut response, || {
+            panic!("metrics computed for an incomplete request")
+        })
+        .unwrap();
+        assert!(response.starts_with(b"HTTP/1.1 400 Bad Request"));
+
+        let mut huge = b"GET /metrics HTTP/1.1\r\n".to_vec();
+        huge.extend(b"X-Padding: 0\r\n".repeat(1000));
+        huge.extend(b"\r\n");
+        let (status, _) = respond(&huge);
+        assert_eq!(status, "HTTP/1.1 431 Request Header Fields Too Large");
+
+        let mut response = Vec::new();
+        handle_request(&b"GET /metrics HTTP/1.1\r\n\r\n"[..], &mut response, || {
+            Err("no metrics JSON".to_string())
+        })
+        .unwrap();
+        assert!(response.starts_with(b"HTTP/1.1 500 Internal Server Error"));
+    }
+}
@@ -0,0 +1,53 @@
+//! Subcommands of the `scip-callgraph` CLI
+//!
+//! Each module holds the arguments and `run` function of one subcommand. The
+//! single-purpose binaries (`pipeline`, `compute_metrics`,
+//! `detect_dead_code`, ...) are thin wrappers parsing the same arguments, so
+//! both entry points stay in sync.
+
+pub mod aggregate;
+pub mod compare;
+pub mod dead_code;
+pub mod enrich_csv;
+pub mod export;
+pub mod gate;
+pub mod graph;
+pub mod history;
+pub mod index;
+pub mod lint;
+pub mod lsp;
+pub mod metrics;
+pub mod metrics_export;
+pub mod proof_metrics;
+pub mod validate;
+
+use scip_core::logging::{init_logger_with_format, LogFormat};
+
+/// Default `--highlight-prefix` values: the libsignal crates, which the
+/// exporters drew as project sources before the prefix was configurable
+pub const DEFAULT_HIGHLIGHT_PREFIXES: [&str; 5] = [
+    "libsignal",
+    "zkgroup",
+    "poksho",
+    "zkcredential",
+    "usernames",
+];
+
+/// Options shared by every command
+#[derive(clap::Args, Debug, Clone)]
+pub struct CommonArgs {
+    /// Enable debug logging
+    #[arg(short, long, global = true)]
+    pub debug: bool,
+
+    /// Log format: `text`, or `json` for one JSON object per line on stderr,
+    /// with step started/finished events instead of the progress output
+    #[arg(long, value_name = "FORMAT", default_value = "text", global = true)]
+    pub log_format: LogFormat,
+}
+
+impl CommonArgs {
+    pub fn init_logger(&self) {
+        init_logger_with_format(self.debug, self.log_format);
+    }
+}
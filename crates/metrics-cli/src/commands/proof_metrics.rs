@@ -0,0 +1,537 @@
+//! Proof metrics: Halstead counts of proof blocks, direct and through the
+//! lemmas they call, structural proof step counts, plus a summary of
+//! top-level theorems
+
+use scip_core::{render_distribution_table, MetricDistribution};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+use verus_metrics::{
+    analyze_proof_steps, extract_proof_blocks, HalsteadCollector, ProofStepMetrics,
+};
+use verus_syn::parse_file;
+use verus_syn::visit::Visit;
+
+#[derive(Debug, Deserialize, Clone)]
+struct AtomWithMetrics {
+    identifier: String,
+    statement_type: String,
+    deps: Vec<String>,
+    body: String,
+    display_name: String,
+    full_path: String,
+    #[allow(dead_code)]
+    relative_path: String,
+    #[allow(dead_code)]
+    file_name: String,
+    #[allow(dead_code)]
+    parent_folder: String,
+    #[allow(dead_code)]
+    metrics: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct AtomWithProofMetrics {
+    identifier: String,
+    statement_type: String,
+    deps: Vec<String>,
+    body: String,
+    display_name: String,
+    full_path: String,
+    relative_path: String,
+    file_name: String,
+    parent_folder: String,
+    metrics: serde_json::Value,
+    proof_metrics: Option<ProofMetrics>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ProofMetrics {
+    /// Direct Halstead metrics for proof block only
+    direct_proof_halstead: HalsteadCounts,
+    /// Transitive Halstead metrics (proof + all called lemmas)
+    transitive_proof_halstead: HalsteadCounts,
+    /// Proof fns called directly (proof-mode callees in the call graph)
+    direct_lemmas: Vec<String>,
+    /// All proof fns reached transitively through proof-mode callees
+    transitive_lemmas: Vec<String>,
+    /// Maximum depth of lemma call chain
+    proof_depth: usize,
+    /// Asserts, `by` blocks, `calc!` steps, reveals and solver switches in
+    /// the body
+    proof_steps: ProofStepMetrics,
+    /// Parse errors if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_error: Option<String>,
+}
+
+/// Summary record for a top-level theorem (a proof fn with no proof callers)
+#[derive(Debug, Serialize)]
+struct TheoremSummary {
+    identifier: String,
+    display_name: String,
+    relative_path: String,
+    /// Pretty-printed `ensures` clauses of the theorem
+    ensures: Vec<String>,
+    /// Number of distinct proof fns in the transitive closure (excluding the theorem)
+    supporting_lemmas: usize,
+    /// Halstead effort of the theorem body plus all supporting lemma bodies
+    transitive_proof_effort: f64,
+    /// Longest chain of proof fn calls starting at the theorem
+    max_depth: usize,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+struct HalsteadCounts {
+    /// n1: Unique operators
+    n1: usize,
+    /// N1: Total operators
+    n1_total: usize,
+    /// n2: Unique operands
+    n2: usize,
+    /// N2: Total operands
+    n2_total: usize,
+    /// N: Total tokens (N1 + N2)
+    length: usize,
+    /// Difficulty: (n1/2) * (N2/n2)
+    difficulty: f64,
+    /// Volume: N * log2(n)
+    volume: f64,
+    /// Effort: difficulty * volume
+    effort: f64,
+}
+
+impl HalsteadCounts {
+    /// Aggregate counts by summing totals and taking union of unique elements
+    fn aggregate(collectors: &[HalsteadCollector]) -> Self {
+        let mut all = HalsteadCollector::default();
+        for collector in collectors {
+            all.merge(collector);
+        }
+        let metrics = all.metrics();
+
+        Self {
+            n1: metrics.n1_unique_operators,
+            n1_total: metrics.n1_total_operators,
+            n2: metrics.n2_unique_operands,
+            n2_total: metrics.n2_total_operands,
+            length: metrics.halstead_length,
+            difficulty: metrics.difficulty,
+            volume: metrics.volume,
+            effort: metrics.effort,
+        }
+    }
+}
+
+/// Halstead collectors for each proof block in a function body, or the
+/// error if the body does not parse
+fn proof_block_collectors(body: &str) -> Result<Vec<HalsteadCollector>, String> {
+    Ok(extract_proof_blocks(body)?
+        .iter()
+        .map(|block| {
+            let mut visitor = HalsteadCollector::default();
+            visitor.visit_block(&block.block);
+            visitor
+        })
+        .collect())
+}
+
+/// Proof fns called directly by an atom, taken from its call-graph deps.
+///
+/// A proof fn can only be called from ghost code, so these are exactly the
+/// lemmas invoked by the atom's proof blocks (or, for a proof fn, its body).
+fn proof_callees<'a>(
+    atom: &AtomWithMetrics,
+    atoms_map: &'a HashMap<String, AtomWithMetrics>,
+) -> Vec<&'a AtomWithMetrics> {
+    let mut callees: Vec<&AtomWithMetrics> = atom
+        .deps
+        .iter()
+        .filter(|dep| **dep != atom.identifier)
+        .filter_map(|dep| atoms_map.get(dep))
+        .filter(|callee| is_proof_fn(callee))
+        .collect();
+    callees.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+    callees.dedup_by(|a, b| a.identifier == b.identifier);
+    callees
+}
+
+/// Display names for lemma identifiers, deduplicated by identifier
+fn lemma_names(
+    mut identifiers: Vec<String>,
+    atoms_map: &HashMap<String, AtomWithMetrics>,
+) -> Vec<String> {
+    identifiers.sort();
+    identifiers.dedup();
+    identifiers
+        .iter()
+        .map(|id| atoms_map.get(id).map_or(id, |a| &a.display_name).clone())
+        .collect()
+}
+
+/// Compute transitive proof metrics: Halstead collectors for the proof blocks
+/// of the atom and every lemma it reaches, the identifiers of those lemmas,
+/// and the depth of the longest lemma chain
+fn compute_transitive_metrics(
+    atom: &AtomWithMetrics,
+    atoms_map: &HashMap<String, AtomWithMetrics>,
+    visited: &mut HashSet<String>,
+    depth: usize,
+    max_depth: usize,
+) -> (Vec<HalsteadCollector>, Vec<String>, usize) {
+    if depth > max_depth || !visited.insert(atom.identifier.clone()) {
+        return (Vec::new(), Vec::new(), depth);
+    }
+
+    let mut all_visitors = proof_block_collectors(&atom.body).unwrap_or_default();
+    let mut all_lemmas = Vec::new();
+    let mut max_observed_depth = depth;
+
+    // Recurse into each called lemma
+    for callee in proof_callees(atom, atoms_map) {
+        all_lemmas.push(callee.identifier.clone());
+
+        let (callee_visitors, callee_lemmas, callee_depth) =
+            compute_transitive_metrics(callee, atoms_map, visited, depth + 1, max_depth);
+
+        all_visitors.extend(callee_visitors);
+        all_lemmas.extend(callee_lemmas);
+        max_observed_depth = max_observed_depth.max(callee_depth);
+    }
+
+    (all_visitors, all_lemmas, max_observed_depth)
+}
+
+/// Check whether an atom is a proof fn, using the mode computed by compute_metrics
+fn is_proof_fn(atom: &AtomWithMetrics) -> bool {
+    match atom.metrics.get("function_mode").and_then(|m| m.as_str()) {
+        Some(mode) => mode == "proof",
+        None => atom.statement_type.contains("proof"),
+    }
+}
+
+/// Tidy up a token-stream rendering of a clause (e.g. `f (x) . 0 < y` -> `f(x).0 < y`)
+fn pretty_print_clause(text: &str) -> String {
+    let mut result = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    for (from, to) in [
+        (" (", "("),
+        ("( ", "("),
+        (" )", ")"),
+        (" [", "["),
+        ("[ ", "["),
+        (" ]", "]"),
+        (" ,", ","),
+        (" . ", "."),
+        (" @", "@"),
+        ("! ", "!"),
+        (" :: ", "::"),
+        (" : ", ": "),
+    ] {
+        result = result.replace(from, to);
+    }
+    // Quantifier binders: `forall | i: int | body` -> `forall|i: int| body`
+    let binder = regex::Regex::new(r"\b(forall|exists|choose) \| ([^|]*?) \|").unwrap();
+    result = binder.replace_all(&result, "$1|$2|").to_string();
+    // Keep a space between binary operators and an opening paren
+    for op in [
+        "&&", "||", "==>", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%",
+    ] {
+        result = result.replace(&format!("{op}("), &format!("{op} ("));
+    }
+    result
+}
+
+/// Halstead counts for a whole function body (used for lemma bodies)
+fn body_halstead_visitor(body: &str) -> Option<HalsteadCollector> {
+    let file = parse_file(body).ok()?;
+    let mut visitor = HalsteadCollector::default();
+    for item in &file.items {
+        verus_syn::visit::visit_item(&mut visitor, item);
+    }
+    Some(visitor)
+}
+
+/// Longest chain of proof-fn calls starting at `identifier` (cycle-safe, memoized)
+fn proof_call_depth(
+    identifier: &str,
+    atoms_map: &HashMap<String, AtomWithMetrics>,
+    on_stack: &mut HashSet<String>,
+    memo: &mut HashMap<String, usize>,
+) -> usize {
+    if let Some(&depth) = memo.get(identifier) {
+        return depth;
+    }
+    let Some(atom) = atoms_map.get(identifier) else {
+        return 0;
+    };
+
+    on_stack.insert(identifier.to_string());
+    let mut depth = 0;
+    for dep in &atom.deps {
+        if on_stack.contains(dep) {
+            continue;
+        }
+        if let Some(callee) = atoms_map.get(dep) {
+            if is_proof_fn(callee) {
+                depth = depth.max(1 + proof_call_depth(dep, atoms_map, on_stack, memo));
+            }
+        }
+    }
+    on_stack.remove(identifier);
+
+    memo.insert(identifier.to_string(), depth);
+    depth
+}
+
+/// Summarize every entry-point proof fn (no proof-mode callers)
+fn summarize_theorems(
+    atoms: &[AtomWithMetrics],
+    atoms_map: &HashMap<String, AtomWithMetrics>,
+) -> Vec<TheoremSummary> {
+    let mut has_proof_caller: HashSet<&str> = HashSet::new();
+    for atom in atoms.iter().filter(|a| is_proof_fn(a)) {
+        for dep in &atom.deps {
+            if dep != &atom.identifier {
+                has_proof_caller.insert(dep.as_str());
+            }
+        }
+    }
+
+    let mut depth_memo = HashMap::new();
+    let mut summaries: Vec<TheoremSummary> = atoms
+        .iter()
+        .filter(|a| is_proof_fn(a) && !has_proof_caller.contains(a.identifier.as_str()))
+        .map(|theorem| {
+            // Collect the transitive closure of proof-mode callees
+            let mut lemmas: HashSet<String> = HashSet::new();
+            let mut stack: Vec<&str> = theorem.deps.iter().map(|d| d.as_str()).collect();
+            while let Some(dep) = stack.pop() {
+                if dep == theorem.identifier || lemmas.contains(dep) {
+                    continue;
+                }
+                if let Some(callee) = atoms_map.get(dep) {
+                    if is_proof_fn(callee) {
+                        lemmas.insert(dep.to_string());
+                        stack.extend(callee.deps.iter().map(|d| d.as_str()));
+                    }
+                }
+            }
+
+            let visitors: Vec<HalsteadCollector> = std::iter::once(theorem.body.as_str())
+                .chain(
+                    lemmas
+                        .iter()
+                        .filter_map(|l| atoms_map.get(l))
+                        .map(|a| a.body.as_str()),
+                )
+                .filter_map(body_halstead_visitor)
+                .collect();
+
+            let ensures = theorem
+                .metrics
+                .get("ensures_specs")
+                .and_then(|specs| specs.as_array())
+                .map(|specs| {
+                    specs
+                        .iter()
+                        .filter_map(|spec| spec.get("text").and_then(|t| t.as_str()))
+                        .map(pretty_print_clause)
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            TheoremSummary {
+                identifier: theorem.identifier.clone(),
+                display_name: theorem.display_name.clone(),
+                relative_path: theorem.relative_path.clone(),
+                ensures,
+                supporting_lemmas: lemmas.len(),
+                transitive_proof_effort: HalsteadCounts::aggregate(&visitors).effort,
+                max_depth: proof_call_depth(
+                    &theorem.identifier,
+                    atoms_map,
+                    &mut HashSet::new(),
+                    &mut depth_memo,
+                ),
+            }
+        })
+        .collect();
+
+    summaries.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+    summaries
+}
+
+/// Compute Halstead metrics for proof blocks, including transitive lemma
+/// dependencies, and summarize the top-level theorems
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Atoms JSON with spec metrics (from `metrics`)
+    input_atoms_json: String,
+
+    /// Output atoms JSON with proof metrics
+    output_atoms_json: String,
+
+    /// Theorem summary output (default: theorems.json next to the output)
+    theorems_json: Option<String>,
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let input_path = &args.input_atoms_json;
+    let output_path = &args.output_atoms_json;
+    let theorems_path = match &args.theorems_json {
+        Some(path) => Path::new(path).to_path_buf(),
+        None => Path::new(output_path)
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join("theorems.json"),
+    };
+
+    println!("Loading atoms from {}...", input_path);
+    let file = File::open(input_path)?;
+    let atoms: Vec<AtomWithMetrics> = serde_json::from_reader(file)?;
+    println!("  Loaded {} functions", atoms.len());
+
+    // Build lookup map
+    let mut atoms_map: HashMap<String, AtomWithMetrics> = HashMap::new();
+    for atom in &atoms {
+        atoms_map.insert(atom.identifier.clone(), atom.clone());
+    }
+
+    println!("Computing proof metrics...");
+    let mut atoms_with_proof: Vec<AtomWithProofMetrics> = Vec::new();
+    let mut processed = 0;
+    let mut with_proofs = 0;
+    let max_depth = 10; // Limit recursion depth
+
+    for atom in &atoms {
+        processed += 1;
+        if processed % 100 == 0 {
+            print!("\r  Processed {}/{} functions...", processed, atoms.len());
+        }
+
+        // Try to extract and compute proof metrics; proof fns get them even
+        // without proof blocks, for their step counts, and bodies that do not
+        // parse get them to record the error
+        let (direct_visitors, parse_error) = match proof_block_collectors(&atom.body) {
+            Ok(visitors) => (visitors, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        let has_metrics = !direct_visitors.is_empty() || is_proof_fn(atom) || parse_error.is_some();
+        let proof_metrics = if has_metrics {
+            if !direct_visitors.is_empty() {
+                with_proofs += 1;
+            }
+
+            // Compute direct proof metrics
+            let direct_halstead = HalsteadCounts::aggregate(&direct_visitors);
+            let direct_lemmas = proof_callees(atom, &atoms_map)
+                .iter()
+                .map(|callee| callee.identifier.clone())
+                .collect();
+
+            // Compute transitive metrics
+            let mut visited = HashSet::new();
+            let (transitive_visitors, transitive_lemmas, proof_depth) =
+                compute_transitive_metrics(atom, &atoms_map, &mut visited, 0, max_depth);
+
+            let transitive_halstead = HalsteadCounts::aggregate(&transitive_visitors);
+            let proof_steps = analyze_proof_steps(&atom.body).unwrap_or_default();
+
+            Some(ProofMetrics {
+                direct_proof_halstead: direct_halstead,
+                transitive_proof_halstead: transitive_halstead,
+                direct_lemmas: lemma_names(direct_lemmas, &atoms_map),
+                transitive_lemmas: lemma_names(transitive_lemmas, &atoms_map),
+                proof_depth,
+                proof_steps,
+                parse_error,
+            })
+        } else {
+            None
+        };
+
+        atoms_with_proof.push(AtomWithProofMetrics {
+            identifier: atom.identifier.clone(),
+            statement_type: atom.statement_type.clone(),
+            deps: atom.deps.clone(),
+            body: atom.body.clone(),
+            display_name: atom.display_name.clone(),
+            full_path: atom.full_path.clone(),
+            relative_path: atom.relative_path.clone(),
+            file_name: atom.file_name.clone(),
+            parent_folder: atom.parent_folder.clone(),
+            metrics: atom.metrics.clone(),
+            proof_metrics,
+        });
+    }
+
+    println!("\r  Processed {}/{} functions    ", processed, atoms.len());
+
+    println!("Writing output to {}...", output_path);
+    let output_file = File::create(output_path)?;
+    serde_json::to_writer_pretty(output_file, &atoms_with_proof)?;
+
+    println!("Summarizing top-level theorems...");
+    let theorems = summarize_theorems(&atoms, &atoms_map);
+    println!("Writing theorem summary to {}...", theorems_path.display());
+    let theorems_file = File::create(&theorems_path)?;
+    serde_json::to_writer_pretty(theorems_file, &theorems)?;
+
+    println!("✓ Done!");
+    println!();
+    println!("Summary:");
+    println!("  Total functions: {}", atoms_with_proof.len());
+    println!("  With proof blocks: {}", with_proofs);
+    println!(
+        "  With transitive proof metrics: {}",
+        atoms_with_proof
+            .iter()
+            .filter(|a| a.proof_metrics.is_some())
+            .count()
+    );
+    println!("  Top-level theorems: {}", theorems.len());
+    println!(
+        "  Bodies that failed to parse: {}",
+        atoms_with_proof
+            .iter()
+            .filter_map(|a| a.proof_metrics.as_ref())
+            .filter(|pm| pm.parse_error.is_some())
+            .count()
+    );
+
+    let mut steps = ProofStepMetrics::default();
+    for pm in atoms_with_proof
+        .iter()
+        .filter_map(|a| a.proof_metrics.as_ref())
+    {
+        steps.asserts += pm.proof_steps.asserts;
+        steps.assert_by_blocks += pm.proof_steps.assert_by_blocks;
+        steps.calc_steps += pm.proof_steps.calc_steps;
+        steps.reveals += pm.proof_steps.reveals;
+        steps.bit_vector += pm.proof_steps.bit_vector;
+        steps.nonlinear_arith += pm.proof_steps.nonlinear_arith;
+    }
+    println!(
+        "  Proof steps: {} asserts ({} by blocks, {} bit_vector, {} nonlinear_arith), {} calc steps, {} reveals",
+        steps.asserts,
+        steps.assert_by_blocks,
+        steps.bit_vector,
+        steps.nonlinear_arith,
+        steps.calc_steps,
+        steps.reveals
+    );
+
+    let proof_depths: Vec<f64> = atoms_with_proof
+        .iter()
+        .filter_map(|a| Some(a.proof_metrics.as_ref()?.proof_depth as f64))
+        .collect();
+    if let Some(distribution) = MetricDistribution::of("proof_depth", &proof_depths) {
+        println!();
+        println!("Distributions (over functions with proof metrics):");
+        println!("{}", render_distribution_table(&[distribution]).trim_end());
+    }
+
+    Ok(())
+}
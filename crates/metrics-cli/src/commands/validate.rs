@@ -0,0 +1,82 @@
+//! Check an atoms or metrics JSON before using it
+//!
+//! Validates the format version, the fields of every atom, identifier
+//! uniqueness and deps (see `scip_core::validate`), prints the issues and
+//! exits with status 1 if there are any.
+
+use clap::ValueEnum;
+use log::info;
+use scip_core::{
+    validate_atoms_json, FormatVersion, ValidationIssue, ValidationReport, ValidationRule,
+};
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Text,
+}
+
+/// Validate an atoms or metrics JSON
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Atoms JSON (envelope or bare array), with or without metrics
+    atoms_json: String,
+
+    /// Report files in another format version as stale (1 for bare
+    /// arrays, 2 for the `format_version` envelope)
+    #[arg(long, value_name = "N", value_parser = parse_format_version)]
+    format_version: Option<FormatVersion>,
+
+    /// Output file (prints to stdout if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "json")]
+    format: ReportFormat,
+}
+
+fn parse_format_version(s: &str) -> Result<FormatVersion, String> {
+    let version: u32 = s.parse().map_err(|e| format!("{s}: {e}"))?;
+    FormatVersion::try_from(version)
+}
+
+pub fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Validating {}...", args.atoms_json);
+    let contents = fs::read_to_string(&args.atoms_json)?;
+    let report = match serde_json::from_str::<serde_json::Value>(&contents) {
+        Ok(value) => validate_atoms_json(&value, args.format_version),
+        Err(e) => ValidationReport {
+            issues: vec![ValidationIssue {
+                rule: ValidationRule::Structure,
+                index: None,
+                identifier: None,
+                field: None,
+                message: format!("not valid JSON: {e}"),
+            }],
+            ..ValidationReport::default()
+        },
+    };
+    let rendered = match args.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        ReportFormat::Text => report.to_text(),
+    };
+    match &args.output {
+        Some(path) => {
+            fs::write(path, rendered)?;
+            info!("✓ Wrote validation report to {}", path);
+        }
+        None => println!("{}", rendered.trim_end()),
+    }
+
+    if !report.is_valid() {
+        eprintln!(
+            "{} is invalid: {} issues",
+            args.atoms_json,
+            report.issues.len()
+        );
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -8,3 +8,14 @@
 //! - Enriching CSV files with complexity metrics
 //!
 //! See the individual binaries for usage information.
+//!
+//! The `scip-callgraph` binary bundles the main tools as subcommands
+//! (`index`, `graph`, `metrics`, `proof-metrics`, `enrich-csv`, `dead-code`,
+//! `export`), implemented in [`commands`]. The [`pipeline`] module runs the
+//! whole SCIP-to-enriched-graph flow of `scip-callgraph graph` as a library
+//! call. Long operations report progress bars and stop cleanly on ctrl-c
+//! via [`monitor::cli_monitor`].
+
+pub mod commands;
+pub mod monitor;
+pub mod pipeline;
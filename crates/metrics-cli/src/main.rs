@@ -0,0 +1,87 @@
+//! `scip-callgraph`: one command-line entry point for the call graph and
+//! Verus metrics tools
+//!
+//! Usage:
+//!     scip-callgraph graph /path/to/verus-project
+//!     scip-callgraph metrics atoms.json atoms_with_metrics.json
+//!
+//! Each subcommand takes the same arguments as the single-purpose binary it
+//! replaces (`pipeline`, `compute_metrics`, ...), which are kept for
+//! compatibility.
+
+use clap::{Parser, Subcommand};
+use metrics_cli::commands::{
+    dead_code, enrich_csv, export, gate, graph, history, index, lint, lsp, metrics, proof_metrics,
+    validate, CommonArgs,
+};
+
+/// Call graphs and metrics for Rust and Verus projects
+#[derive(Parser, Debug)]
+#[command(name = "scip-callgraph")]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate a SCIP JSON index of a Rust project (generate_index_scip_json)
+    Index(index::Args),
+    /// Build the enriched call graph for the web viewer: SCIP index,
+    /// export, verification status, similar lemmas (pipeline)
+    Graph(graph::Args),
+    /// Compute spec metrics of an atoms JSON (compute_metrics), compare the
+    /// metrics of two revisions (`metrics compare`), roll them up by file,
+    /// module, impl or trait (`metrics aggregate`) or export them as a
+    /// spreadsheet or Prometheus gauges (`metrics export`)
+    Metrics(metrics::Args),
+    /// Compute proof metrics and a theorem summary (compute_proof_metrics)
+    ProofMetrics(proof_metrics::Args),
+    /// Add spec/proof metrics to a functions CSV, matching rows by
+    /// configurable key columns and strategies (enrich_csv_complete)
+    EnrichCsv(enrich_csv::Args),
+    /// Report unreachable functions and orphaned specs (detect_dead_code)
+    DeadCode(dead_code::Args),
+    /// Export a call graph as D3 JSON, CSV/Parquet tables, SQLite or a
+    /// markdown function dossier (export_call_graph_d3)
+    Export(export::Args),
+    /// Record metrics summaries per commit and report them as time-series
+    /// CSV
+    History(history::Args),
+    /// Check metrics against complexity budgets; exits non-zero on
+    /// violations
+    Gate(gate::Args),
+    /// Check an atoms or metrics JSON (format version, fields, duplicate
+    /// identifiers, dangling deps); exits non-zero on issues
+    Validate(validate::Args),
+    /// Load a call graph once and serve definition, references, callers,
+    /// callees and subgraph queries over stdio (JSON-RPC, LSP framing)
+    Lsp(lsp::Args),
+    /// Lint quantifier triggers: missing or arithmetic triggers, matching
+    /// loops, deep forall/exists alternation (JSON, markdown or SARIF)
+    Lint(lint::Args),
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    cli.common.init_logger();
+
+    match &cli.command {
+        Command::Index(args) => index::run(args),
+        Command::Graph(args) => graph::run(args),
+        Command::Metrics(args) => metrics::run(args),
+        Command::ProofMetrics(args) => proof_metrics::run(args),
+        Command::EnrichCsv(args) => enrich_csv::run(args),
+        Command::DeadCode(args) => dead_code::run(args),
+        Command::Export(args) => export::run(args),
+        Command::History(args) => history::run(args),
+        Command::Gate(args) => gate::run(args),
+        Command::Validate(args) => validate::run(args),
+        Command::Lsp(args) => lsp::run(args),
+        Command::Lint(args) => lint::run(args),
+    }
+}
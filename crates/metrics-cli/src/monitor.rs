@@ -0,0 +1,97 @@
+//! Progress bars and ctrl-c handling for the command-line tools
+//!
+//! [`cli_monitor`] returns the [`Monitor`] the commands pass to long
+//! operations: phases are drawn as indicatif progress bars on stderr (hidden
+//! when stderr is not a terminal or logs are JSON), and the first ctrl-c
+//! cancels the operation so the command can write what it finished. A
+//! second ctrl-c exits immediately.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use scip_core::logging::json_logs;
+use scip_core::{CancellationToken, Monitor, Progress};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Exit code of a process interrupted by SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+const SPINNER_TICK: Duration = Duration::from_millis(120);
+
+/// Draws one progress bar (or spinner, without a total) per phase
+pub struct IndicatifProgress {
+    bar: Mutex<Option<ProgressBar>>,
+    hidden: bool,
+}
+
+impl IndicatifProgress {
+    /// Progress bars on stderr, or nothing with `hidden`
+    pub fn new(hidden: bool) -> Self {
+        Self {
+            bar: Mutex::new(None),
+            hidden,
+        }
+    }
+}
+
+impl Progress for IndicatifProgress {
+    fn start(&self, phase: &str, total: Option<u64>) {
+        let target = if self.hidden {
+            ProgressDrawTarget::hidden()
+        } else {
+            ProgressDrawTarget::stderr()
+        };
+        let bar = ProgressBar::with_draw_target(total, target);
+        match total {
+            Some(_) => bar.set_style(
+                ProgressStyle::with_template("{msg:>20} [{bar:40}] {pos}/{len} ({eta})")
+                    .expect("valid template")
+                    .progress_chars("=> "),
+            ),
+            None => bar.enable_steady_tick(SPINNER_TICK),
+        }
+        bar.set_message(phase.to_string());
+        if let Some(previous) = self.bar.lock().unwrap().replace(bar) {
+            previous.finish_and_clear();
+        }
+    }
+
+    fn advance(&self, delta: u64) {
+        if let Some(bar) = self.bar.lock().unwrap().as_ref() {
+            bar.inc(delta);
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(bar) = self.bar.lock().unwrap().take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// The process-wide cancellation token, set by the ctrl-c handler
+fn interrupt_token() -> &'static CancellationToken {
+    static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+    TOKEN.get_or_init(|| {
+        let token = CancellationToken::new();
+        let handler_token = token.clone();
+        let installed = ctrlc::set_handler(move || {
+            if handler_token.is_cancelled() {
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+            handler_token.cancel();
+            eprintln!("\nInterrupted: finishing up (press ctrl-c again to quit immediately)");
+        });
+        if let Err(e) = installed {
+            log::warn!("Could not install the ctrl-c handler: {}", e);
+        }
+        token
+    })
+}
+
+/// Progress bars plus ctrl-c cancellation, for the commands' long operations
+pub fn cli_monitor() -> Monitor {
+    Monitor::new(
+        Arc::new(IndicatifProgress::new(json_logs())),
+        interrupt_token().clone(),
+    )
+}
@@ -0,0 +1,2071 @@
+//! Unified pipeline for generating enriched call graphs from Verus projects
+//!
+//! [`run`] combines multiple steps into a single call:
+//! 1. Generate SCIP index (if needed)
+//! 2. Export call graph to D3 format
+//! 3. Run verification and enrich with verification status
+//! 4. (Optional) Enrich failed and unverified functions with similar vstd lemmas
+//!
+//! and writes a fully enriched graph.json ready for the web viewer. The
+//! `pipeline` binary is a command-line front end to it:
+//!
+//! ```ignore
+//! use metrics_cli::pipeline::{self, PipelineConfig};
+//!
+//! let config = PipelineConfig {
+//!     skip_verification: true,
+//!     ..PipelineConfig::new("/path/to/verus-project", "graph.json")
+//! };
+//! let report = pipeline::run(&config)?;
+//! println!("{} nodes", report.graph.nodes.len());
+//! ```
+//!
+//! Fatal problems (no project, no SCIP index, a failed export) are returned
+//! as errors; steps that fail otherwise are skipped and listed in
+//! [`PipelineReport::warnings`].
+//!
+//! [`PipelineConfig::monitor`] shows the progress of the long steps. Once
+//! it is cancelled, the remaining steps are skipped and the graph built so
+//! far is written ([`PipelineReport::cancelled`]); outputs are replaced
+//! atomically, so an interrupted run never leaves a truncated graph.
+//!
+//! SCIP JSON is parsed leniently unless [`PipelineConfig::strict_scip`] is
+//! set: documents that don't match the schema are dropped and summarized in
+//! the warnings (see [`load_scip_json`]).
+
+use log::{info, warn};
+use probe_verus::verification::{
+    AnalysisResult, AnalysisStatus, VerificationAnalyzer, VerusRunner,
+};
+use probe_verus::{
+    add_external_stubs, build_call_graph, convert_to_atoms_with_parsed_spans, parse_scip_json,
+};
+use scip_core::atoms_to_d3::atoms_to_d3_graph;
+use scip_core::lemma_similarity::{self, annotate_similar_lemmas, LemmaIndex};
+use scip_core::logging::Step;
+use scip_core::progress;
+use scip_core::scip_utils::{changed_documents, document_fingerprints};
+use scip_core::{
+    add_graph_metrics_to_d3, add_spec_texts_to_d3, body_hashes, build_call_graph_with_options,
+    call_graph_to_d3, merge_scip_indices, modules_from_paths, parse_scip_json_with_mode,
+    render_interactive_report, run_cargo_verus, run_cargo_verus_by_module,
+    run_cargo_verus_with_retry, update_call_graph, write_atomic, CallGraph, CallGraphOptions,
+    CrateSummary, D3Graph, Document, Monitor, NodeFilters, ParseMode, ParseWarning,
+    ParseWarningKind, ParsedScipIndex, ReportMetrics, RetryPolicy, ScipIndex, SourceLinks,
+    VerificationAttempt, VerificationCache, VerificationStatus, VerificationTimings,
+    VerusToolchain, Workspace,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// What to run; see the `pipeline` binary's options for details
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    /// Path to the Verus project
+    pub project: PathBuf,
+    /// Output graph file
+    pub output: PathBuf,
+    pub skip_verification: bool,
+    pub skip_similar_lemmas: bool,
+    /// vstd lemma index JSON (default: the verus_lemma_finder submodule's
+    /// index, then data/vstd_lemma_index.json)
+    pub lemma_index: Option<PathBuf>,
+    /// Use cached SCIP JSON if available (default: regenerate fresh)
+    pub use_cached_scip: bool,
+    /// Verus package name (for workspaces)
+    pub package: Option<String>,
+    /// GitHub repository URL for source code links in the web viewer
+    pub github_url: Option<String>,
+    /// Revision (branch, tag or commit) of the source links; with
+    /// `github_url`, every node gets a `source_url`
+    pub rev: Option<String>,
+    /// Use rust-analyzer instead of verus-analyzer for SCIP generation
+    pub use_rust_analyzer: bool,
+    /// Patch the call graph of the previous run with the changed SCIP
+    /// documents, and reuse the previous graph when none changed
+    pub incremental: bool,
+    /// Globs of relative source paths to leave out
+    pub exclude: Vec<String>,
+    /// Also write a self-contained interactive HTML report
+    pub html_report: Option<PathBuf>,
+    /// Add per-function verification times to graph nodes
+    pub verification_times: bool,
+    /// Retries of verification failing only on rlimit or timeouts
+    pub verify_retries: usize,
+    /// Parallel verification workers (per module above 1)
+    pub jobs: usize,
+    /// Verus release directory, overriding `VERUS_PATH` and the project's
+    /// `verus-toolchain.toml` (see [`VerusToolchain::discover`])
+    pub verus_path: Option<PathBuf>,
+    /// Cache verification results by function body hash, and only
+    /// re-verify the modules of changed functions
+    pub verification_cache: bool,
+    /// Add fan-in/fan-out, call depth and centrality to graph nodes
+    pub graph_metrics: bool,
+    /// Progress reporting and cancellation (progress bars and ctrl-c in the
+    /// CLI)
+    pub monitor: Monitor,
+    /// Fail on SCIP documents that don't match the schema instead of
+    /// dropping them with a warning
+    pub strict_scip: bool,
+}
+
+impl PipelineConfig {
+    /// Run every step on `project`, writing the graph to `output`
+    pub fn new(project: impl Into<PathBuf>, output: impl Into<PathBuf>) -> Self {
+        Self {
+            project: project.into(),
+            output: output.into(),
+            skip_verification: false,
+            skip_similar_lemmas: false,
+            lemma_index: None,
+            use_cached_scip: false,
+            package: None,
+            github_url: None,
+            rev: None,
+            use_rust_analyzer: false,
+            incremental: false,
+            exclude: Vec::new(),
+            html_report: None,
+            verification_times: false,
+            verify_retries: 0,
+            jobs: 1,
+            verus_path: None,
+            verification_cache: false,
+            graph_metrics: false,
+            monitor: Monitor::default(),
+            strict_scip: false,
+        }
+    }
+}
+
+/// Result of [`run`]
+#[derive(Debug, Clone)]
+pub struct PipelineReport {
+    /// The enriched graph, as written to the output file
+    pub graph: D3Graph,
+    /// SCIP JSON the graph was built from
+    pub scip_json: PathBuf,
+    /// No document changed in incremental mode, so the previous graph was reused
+    pub reused_cached_graph: bool,
+    /// Nodes given a verification status (`None` if verification was skipped
+    /// or failed)
+    pub verified_nodes: Option<usize>,
+    /// Nodes annotated with similar lemmas (`None` if skipped)
+    pub similar_lemma_nodes: Option<usize>,
+    /// HTML report written, if requested and successful
+    pub html_report: Option<PathBuf>,
+    /// Problems that made the pipeline skip a step or an output
+    pub warnings: Vec<String>,
+    /// The monitor was cancelled; the steps after that were skipped
+    pub cancelled: bool,
+}
+
+/// Warnings listed one by one before [`load_scip_json`] only counts the rest
+const LISTED_PARSE_WARNINGS: usize = 5;
+
+/// One-line summary of the warnings of lenient SCIP parsing
+pub fn parse_warnings_summary(warnings: &[ParseWarning]) -> String {
+    let skipped = warnings
+        .iter()
+        .filter(|warning| warning.kind == ParseWarningKind::SkippedDocument)
+        .count();
+    format!(
+        "SCIP JSON did not match the schema in {} place(s); {} document(s) skipped",
+        warnings.len(),
+        skipped
+    )
+}
+
+/// [`ParseMode::Strict`] for `--strict`, else lenient
+pub fn parse_mode(strict: bool) -> ParseMode {
+    if strict {
+        ParseMode::Strict
+    } else {
+        ParseMode::Lenient
+    }
+}
+
+/// Parse SCIP JSON in `mode`, logging a summary of what lenient parsing
+/// dropped
+pub fn load_scip_json(
+    path: &str,
+    mode: ParseMode,
+) -> Result<ParsedScipIndex, Box<dyn std::error::Error>> {
+    let parsed = parse_scip_json_with_mode(path, mode)?;
+    if !parsed.warnings.is_empty() {
+        warn!("{}", parse_warnings_summary(&parsed.warnings));
+        for warning in parsed.warnings.iter().take(LISTED_PARSE_WARNINGS) {
+            warn!("  {}", warning);
+        }
+        if parsed.warnings.len() > LISTED_PARSE_WARNINGS {
+            warn!(
+                "  ... and {} more (use --strict to fail instead)",
+                parsed.warnings.len() - LISTED_PARSE_WARNINGS
+            );
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parse the SCIP JSON at `path` and merge the indices of `others` (e.g.
+/// of other repositories) into it, unifying cross-index calls
+pub fn load_scip_indices(
+    path: &str,
+    others: &[String],
+    mode: ParseMode,
+) -> Result<ScipIndex, Box<dyn std::error::Error>> {
+    let index = load_scip_json(path, mode)?.index;
+    if others.is_empty() {
+        return Ok(index);
+    }
+    let mut indices = vec![index];
+    for other in others {
+        info!("Parsing SCIP JSON from {}...", other);
+        indices.push(load_scip_json(other, mode)?.index);
+    }
+    Ok(merge_scip_indices(indices))
+}
+
+/// Parse SCIP JSON leniently and return what parsed as JSON, for
+/// probe-verus to deserialize in memory
+fn sanitize_scip_json(
+    scip_json: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<serde_json::Value, String> {
+    let parsed = load_scip_json(scip_json.to_str().unwrap(), ParseMode::Lenient)
+        .map_err(|e| format!("Failed to parse SCIP JSON: {}", e))?;
+    if !parsed.warnings.is_empty() {
+        warnings.push(parse_warnings_summary(&parsed.warnings));
+    }
+    serde_json::to_value(&parsed.index).map_err(|e| format!("Failed to serialize SCIP JSON: {}", e))
+}
+
+fn check_command_exists(cmd: &str) -> bool {
+    which::which(cmd).is_ok()
+}
+
+/// Run verus-analyzer or rust-analyzer to generate a new SCIP binary
+fn generate_new_scip(project: &Path, use_rust_analyzer: bool) -> Result<PathBuf, String> {
+    let analyzer = if use_rust_analyzer {
+        "rust-analyzer"
+    } else {
+        "verus-analyzer"
+    };
+
+    // Check prerequisites
+    if !check_command_exists(analyzer) {
+        let install_hint = if use_rust_analyzer {
+            "Install with: rustup component add rust-analyzer"
+        } else {
+            "Install verus-analyzer from https://github.com/verus-lang/verus-analyzer/releases"
+        };
+        return Err(format!("{} not found in PATH. {}", analyzer, install_hint));
+    }
+
+    info!(
+        "Generating SCIP index using {} for {}...",
+        analyzer,
+        project.display()
+    );
+    info!("  (This may take a while for large projects)");
+
+    // Run analyzer scip command
+    let scip_status = Command::new(analyzer)
+        .args(["scip", "."])
+        .current_dir(project)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", analyzer, e))?;
+
+    if !scip_status.success() {
+        return Err(format!(
+            "{} scip failed with status: {}",
+            analyzer, scip_status
+        ));
+    }
+
+    let generated_scip_path = project.join("index.scip");
+    if !generated_scip_path.exists() {
+        return Err(format!(
+            "index.scip not found after running {} scip",
+            analyzer
+        ));
+    }
+
+    info!("✓ SCIP index generated using {}", analyzer);
+    Ok(generated_scip_path)
+}
+
+/// Generate SCIP index and JSON for a project
+///
+/// By default, always regenerates fresh SCIP data.
+/// If `use_cached` is true, uses existing index.scip.json if available.
+fn generate_scip(
+    project: &Path,
+    use_cached: bool,
+    use_rust_analyzer: bool,
+) -> Result<PathBuf, String> {
+    let root_json_path = project.join("index.scip.json");
+
+    // If caching is enabled, check for existing JSON
+    if use_cached {
+        if root_json_path.exists() {
+            info!("Using cached SCIP JSON: {}", root_json_path.display());
+            return Ok(root_json_path);
+        } else {
+            info!("No cached SCIP JSON found, generating fresh...");
+        }
+    }
+
+    // Generate fresh SCIP binary
+    let scip_path = generate_new_scip(project, use_rust_analyzer)?;
+
+    // Convert SCIP to JSON
+    if !check_command_exists("scip") {
+        return Err("scip not found in PATH. Install with: cargo install scip-cli".to_string());
+    }
+    info!("Converting SCIP to JSON...");
+    let scip_output = Command::new("scip")
+        .args(["print", "--json", scip_path.to_str().unwrap()])
+        .output()
+        .map_err(|e| format!("Failed to run scip: {}", e))?;
+
+    if !scip_output.status.success() {
+        return Err(format!(
+            "scip print failed: {}",
+            String::from_utf8_lossy(&scip_output.stderr)
+        ));
+    }
+
+    // Write JSON to project root
+    std::fs::write(&root_json_path, &scip_output.stdout)
+        .map_err(|e| format!("Failed to write SCIP JSON: {}", e))?;
+
+    info!("✓ SCIP JSON saved to: {}", root_json_path.display());
+    Ok(root_json_path)
+}
+
+/// Build the D3 graph using probe-verus' unique name resolution
+///
+/// Documents matching the exclude globs of `options` are dropped before the
+/// graph is built; with [`ParseMode::Lenient`], so are the parts of the index
+/// that don't match the schema (summarized in `warnings`). The graph is written by [`write_graph`] once all
+/// enrichment steps ran.
+fn export_call_graph(
+    scip_json: &Path,
+    project_root: &Path,
+    github_url: Option<String>,
+    workspace: Option<&Workspace>,
+    options: &CallGraphOptions,
+    parse_mode: ParseMode,
+    warnings: &mut Vec<String>,
+) -> Result<D3Graph, String> {
+    info!("Building call graph from SCIP data (using probe-verus)...");
+
+    let mut scip_data = match parse_scip_json(scip_json.to_str().unwrap()) {
+        Ok(scip_data) => scip_data,
+        Err(e) if parse_mode == ParseMode::Lenient => {
+            warn!(
+                "SCIP JSON does not match the schema ({}), parsing leniently",
+                e
+            );
+            serde_json::from_value(sanitize_scip_json(scip_json, warnings)?)
+                .map_err(|e| format!("Failed to parse sanitized SCIP JSON: {}", e))?
+        }
+        Err(e) => return Err(format!("Failed to parse SCIP JSON: {}", e)),
+    };
+    let document_count = scip_data.documents.len();
+    scip_data
+        .documents
+        .retain(|doc| !options.is_excluded(&doc.relative_path));
+    if scip_data.documents.len() < document_count {
+        info!(
+            "  Excluded {} of {} documents",
+            document_count - scip_data.documents.len(),
+            document_count
+        );
+    }
+
+    let (call_graph, symbol_to_display_name) = build_call_graph(&scip_data);
+    info!("  Call graph contains {} functions", call_graph.len());
+
+    info!("Converting to atoms with unique scip_names and accurate line spans...");
+    info!("  Parsing source files with verus_syn for accurate function body spans...");
+    // Pass with_locations=true to get call location tracking (precondition/postcondition/inner)
+    let atoms = convert_to_atoms_with_parsed_spans(
+        &call_graph,
+        &symbol_to_display_name,
+        project_root,
+        true, // with_locations - enables requires/ensures tracking
+    );
+
+    // Convert to BTreeMap keyed by code_name for the D3 converter
+    let mut atoms_map: BTreeMap<String, _> = atoms
+        .into_iter()
+        .map(|atom| (atom.code_name.clone(), atom))
+        .collect();
+    info!("  Generated {} atoms with unique names", atoms_map.len());
+
+    // Add stub atoms for external function dependencies
+    let stub_count = add_external_stubs(&mut atoms_map);
+    if stub_count > 0 {
+        info!("  Added {} external function stub(s)", stub_count);
+    }
+
+    info!("Exporting to D3 format...");
+    let project_root_str = project_root.to_string_lossy().to_string();
+    let mut d3_graph = atoms_to_d3_graph(&atoms_map, &call_graph, &project_root_str, github_url);
+    let with_specs = add_spec_texts_to_d3(&mut d3_graph, project_root);
+    info!("  Added requires/ensures texts to {} nodes", with_specs);
+
+    if let Some(workspace) = workspace {
+        workspace.assign_crate_names(&mut d3_graph);
+        print_crate_summary(&d3_graph.metadata.crates);
+    }
+
+    info!(
+        "✓ Call graph exported: {} nodes, {} edges",
+        d3_graph.nodes.len(),
+        d3_graph.links.len()
+    );
+    Ok(d3_graph)
+}
+
+/// Read a graph written by a previous run
+fn read_graph(path: &Path) -> Result<D3Graph, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read graph: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse graph: {}", e))
+}
+
+/// Serialize the (enriched) graph
+fn write_graph(graph: &D3Graph, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(graph)
+        .map_err(|e| format!("Failed to serialize graph: {}", e))?;
+    write_atomic(path, json).map_err(|e| format!("Failed to write graph: {}", e))
+}
+
+/// Log the per-crate breakdown of a workspace graph
+fn print_crate_summary(crates: &[CrateSummary]) {
+    info!("Per-crate breakdown:");
+    info!(
+        "  {:<30} {:>6} {:>6} {:>6} {:>6} {:>8} {:>10}",
+        "crate", "fns", "exec", "proof", "spec", "internal", "cross-out"
+    );
+    for summary in crates {
+        info!(
+            "  {:<30} {:>6} {:>6} {:>6} {:>6} {:>8} {:>10}",
+            summary.crate_name,
+            summary.functions,
+            summary.exec_functions,
+            summary.proof_functions,
+            summary.spec_functions,
+            summary.internal_calls,
+            summary.outgoing_cross_crate_calls
+        );
+    }
+}
+
+/// Path of the document fingerprint cache used by `--incremental`
+fn fingerprint_cache_path(output: &Path) -> PathBuf {
+    output.with_extension("fingerprints.json")
+}
+
+/// Path of the verification cache used by `--verification-cache`
+fn verification_cache_path(output: &Path) -> PathBuf {
+    output.with_extension("verification-cache.json")
+}
+
+/// The fingerprint cache: the document fingerprints of the previous run and
+/// the [`CallGraphOptions::fingerprint`] of the options it was built with
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FingerprintCache {
+    options: u64,
+    documents: BTreeMap<String, u64>,
+}
+
+/// Load previously cached document fingerprints (empty if missing,
+/// unreadable or cached with other call graph options)
+fn load_fingerprints(path: &Path, options: u64) -> BTreeMap<String, u64> {
+    let cache: FingerprintCache = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    if cache.options != options && !cache.documents.is_empty() {
+        info!("Call graph options changed since the last run; rebuilding");
+        return BTreeMap::new();
+    }
+    cache.documents
+}
+
+/// The SCIP documents compared against the cached fingerprints
+struct DocumentChanges {
+    /// The parsed index
+    index: ScipIndex,
+    fingerprints: BTreeMap<String, u64>,
+    /// Relative paths of the added, modified and removed documents
+    changed: Vec<String>,
+}
+
+/// Compare the SCIP documents against the fingerprints cached with the same
+/// call graph `options` (see [`CallGraphOptions::fingerprint`])
+fn detect_changed_documents(
+    scip_json: &Path,
+    cache_path: &Path,
+    options: u64,
+) -> Result<DocumentChanges, String> {
+    let index = parse_scip_json_with_mode(scip_json.to_str().unwrap(), ParseMode::Lenient)
+        .map_err(|e| format!("Failed to parse SCIP JSON: {}", e))?
+        .index;
+    let fingerprints = document_fingerprints(&index);
+    let previous = load_fingerprints(cache_path, options);
+    let changed = changed_documents(&previous, &fingerprints);
+    Ok(DocumentChanges {
+        index,
+        fingerprints,
+        changed,
+    })
+}
+
+/// Path of the call graph cache used by `--incremental`
+fn call_graph_cache_path(output: &Path) -> PathBuf {
+    output.with_extension("callgraph.json")
+}
+
+/// The call graph cache: the call graph of the previous incremental run and
+/// the [`CallGraphOptions::fingerprint`] of the options it was built with
+#[derive(Serialize, Deserialize)]
+struct CallGraphCache {
+    options: u64,
+    call_graph: CallGraph,
+}
+
+/// Load the call graph cached by a previous incremental run (`None` if
+/// missing, unreadable or built with other call graph options)
+fn load_call_graph_cache(path: &Path, options: u64) -> Option<CallGraph> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str::<CallGraphCache>(&contents) {
+        Ok(cache) if cache.options == options => Some(cache.call_graph),
+        Ok(_) => {
+            info!("Ignoring call graph cache built with other options");
+            None
+        }
+        Err(e) => {
+            warn!(
+                "Ignoring unreadable call graph cache {}: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// The call graph of `index`: the `cached` graph of the previous run with
+/// the `changed` documents re-analyzed (see [`update_call_graph`]), or a
+/// full build when there is no cache.
+///
+/// Nodes of unchanged documents are taken from the cache as they are.
+/// Changed documents that are no longer in the index, or that match the
+/// exclude globs of `options`, are treated as deleted.
+fn incremental_call_graph(
+    index: ScipIndex,
+    changed: &[String],
+    cached: Option<CallGraph>,
+    options: &CallGraphOptions,
+) -> CallGraph {
+    let Some(mut call_graph) = cached else {
+        info!(
+            "No cached call graph; analyzing all {} documents",
+            index.documents.len()
+        );
+        return build_call_graph_with_options(&index, options);
+    };
+
+    let project_root = index.metadata.project_root;
+    let changed: BTreeSet<&str> = changed.iter().map(String::as_str).collect();
+    let mut changed_docs: Vec<Document> = index
+        .documents
+        .into_iter()
+        .filter(|doc| changed.contains(doc.relative_path.trim_start_matches('/')))
+        .map(|doc| {
+            if options.is_excluded(&doc.relative_path) {
+                Document {
+                    occurrences: Vec::new(),
+                    symbols: Vec::new(),
+                    ..doc
+                }
+            } else {
+                doc
+            }
+        })
+        .collect();
+    let present: HashSet<String> = changed_docs
+        .iter()
+        .map(|doc| doc.relative_path.trim_start_matches('/').to_string())
+        .collect();
+    for deleted in changed.iter().filter(|path| !present.contains(**path)) {
+        changed_docs.push(Document {
+            language: String::new(),
+            relative_path: deleted.to_string(),
+            occurrences: Vec::new(),
+            symbols: Vec::new(),
+            position_encoding: 0,
+        });
+    }
+
+    info!(
+        "Re-analyzing {} changed document(s) of the cached graph ({} nodes)",
+        changed_docs.len(),
+        call_graph.len()
+    );
+    update_call_graph(&mut call_graph, &project_root, &changed_docs, options);
+    call_graph
+}
+
+/// Build the D3 graph from the call graph of an incremental run (see
+/// [`incremental_call_graph`]) and cache the call graph for the next run.
+///
+/// Unlike [`export_call_graph`], nodes are keyed by SCIP symbol rather than
+/// probe-verus' unique names: those are resolved across the whole index, so
+/// they can't be patched per document.
+fn export_incremental_call_graph(
+    changes: DocumentChanges,
+    project_root: &Path,
+    cache_path: &Path,
+    github_url: Option<String>,
+    workspace: Option<&Workspace>,
+    options: &CallGraphOptions,
+    warnings: &mut Vec<String>,
+) -> Result<D3Graph, String> {
+    info!("Building call graph from SCIP data (incrementally)...");
+    let cached = load_call_graph_cache(cache_path, options.fingerprint());
+    let call_graph = incremental_call_graph(changes.index, &changes.changed, cached, options);
+    info!("  Call graph contains {} functions", call_graph.len());
+
+    let cache = CallGraphCache {
+        options: options.fingerprint(),
+        call_graph,
+    };
+    let json = serde_json::to_string(&cache)
+        .map_err(|e| format!("Failed to serialize call graph: {}", e))?;
+    if let Err(e) = write_atomic(cache_path, json) {
+        warn!("Failed to write call graph cache: {}", e);
+        warnings.push(format!("Failed to write call graph cache: {}", e));
+    }
+    let call_graph = cache.call_graph;
+
+    let project_root_str = project_root.to_string_lossy().to_string();
+    let mut d3_graph = call_graph_to_d3(&call_graph, &project_root_str, &NodeFilters::default());
+    d3_graph.metadata.github_url = github_url;
+    let with_specs = add_spec_texts_to_d3(&mut d3_graph, project_root);
+    info!("  Added requires/ensures texts to {} nodes", with_specs);
+
+    if let Some(workspace) = workspace {
+        workspace.assign_crate_names(&mut d3_graph);
+        print_crate_summary(&d3_graph.metadata.crates);
+    }
+
+    info!(
+        "✓ Call graph exported: {} nodes, {} edges",
+        d3_graph.nodes.len(),
+        d3_graph.links.len()
+    );
+    Ok(d3_graph)
+}
+
+/// Write the enriched graph as a self-contained HTML report
+fn write_html_report(graph_path: &Path, report_path: &Path, title: &str) -> Result<(), String> {
+    // Read back, so fields added by the Python enrichment are included
+    let graph = read_graph(graph_path)?;
+    let metrics = ReportMetrics::from_graph(&graph);
+
+    let html = render_interactive_report(&graph, &metrics, title)
+        .map_err(|e| format!("Failed to render report: {}", e))?;
+    std::fs::write(report_path, html).map_err(|e| format!("Failed to write report: {}", e))
+}
+
+/// Result of the verification step
+struct VerificationOutcome {
+    result: AnalysisResult,
+    /// Per-function times (empty unless requested)
+    timings: VerificationTimings,
+    /// Attempts made under the retry policy (empty when probe-verus ran it)
+    attempts: Vec<VerificationAttempt>,
+    /// `verus --version` of the toolchain used
+    verus_version: Option<String>,
+}
+
+/// How to run the verification step
+struct VerifyOptions<'a> {
+    package: Option<&'a str>,
+    /// `--verus-path`, for [`VerusToolchain::discover`]
+    verus_path: Option<&'a Path>,
+    /// Ask Verus for per-function times
+    with_timings: bool,
+    retry: RetryPolicy,
+    /// Number of parallel workers
+    jobs: usize,
+    /// Modules to verify separately (all modules with several workers, the
+    /// modules of stale functions with the verification cache); empty
+    /// verifies the crate in one run
+    modules: Vec<String>,
+    /// Directory for per-module verification logs
+    log_dir: PathBuf,
+    monitor: &'a Monitor,
+}
+
+/// `verus --version` of the toolchain the verification step runs, if it
+/// can be found
+fn toolchain_version(project: &Path, verus_path: Option<&Path>) -> Option<String> {
+    VerusToolchain::discover(project, verus_path)
+        .ok()?
+        .check_version()
+        .ok()?
+}
+
+/// Verus arguments of the verification step as recorded in the
+/// verification cache: the timing flags and the rlimit of the first attempt
+fn cached_run_args(with_timings: bool, retry: &RetryPolicy) -> Vec<String> {
+    let timing_args: &[&str] = if with_timings {
+        scip_core::verification::TIMING_ARGS
+    } else {
+        &[]
+    };
+    let mut args: Vec<String> = timing_args.iter().map(|arg| arg.to_string()).collect();
+    args.extend(["--rlimit".to_string(), retry.rlimit.to_string()]);
+    args
+}
+
+/// Run verification and return the analysis result, plus per-function
+/// times when requested
+fn run_verification(
+    project: &Path,
+    options: &VerifyOptions,
+) -> Result<VerificationOutcome, String> {
+    let toolchain = VerusToolchain::discover(project, options.verus_path)
+        .map_err(|e| format!("Failed to find Verus: {}", e))?;
+    let verus_version = toolchain.check_version().map_err(|e| e.to_string())?;
+    info!(
+        "Running Verus {} (from {})...",
+        verus_version.as_deref().unwrap_or("(unknown version)"),
+        toolchain.source
+    );
+    info!("  (This may take a while)");
+
+    let verus_args: &[&str] = if options.with_timings {
+        scip_core::verification::TIMING_ARGS
+    } else {
+        &[]
+    };
+    let mut attempts = Vec::new();
+    let (output, exit_code) = if !options.modules.is_empty() {
+        info!(
+            "  Verifying {} modules with {} workers (logs in {})",
+            options.modules.len(),
+            options.jobs,
+            options.log_dir.display()
+        );
+        let run = run_cargo_verus_by_module(
+            project,
+            &toolchain,
+            options.package,
+            &options.modules,
+            options.jobs,
+            verus_args,
+            &options.retry,
+            Some(&options.log_dir),
+            options.monitor,
+        )
+        .map_err(|e| format!("Failed to run verification: {}", e))?;
+        for module in run.modules.iter().filter(|m| m.exit_code != 0) {
+            let name = if module.module.is_empty() {
+                "(crate root)"
+            } else {
+                module.module.as_str()
+            };
+            warn!("  Module {} failed (exit code {})", name, module.exit_code);
+        }
+        (run.output, run.exit_code)
+    } else if options.with_timings || options.retry.max_retries > 0 {
+        options.monitor.progress().start("Verifying", None);
+        let run = run_cargo_verus_with_retry(
+            project,
+            &toolchain,
+            options.package,
+            verus_args,
+            &options.retry,
+        )
+        .map_err(|e| format!("Failed to run verification: {}", e))?;
+        for attempt in run.verification_attempts.iter().skip(1) {
+            info!(
+                "  Retry {} (rlimit {}, modules: {}): exit code {}",
+                attempt.attempt - 1,
+                attempt.rlimit.unwrap_or_default(),
+                if attempt.modules.is_empty() {
+                    "all".to_string()
+                } else {
+                    attempt.modules.join(", ")
+                },
+                attempt.exit_code
+            );
+        }
+        attempts = run.verification_attempts;
+        (run.output, run.exit_code)
+    } else if toolchain.bin_dir.is_some() {
+        options.monitor.progress().start("Verifying", None);
+        run_cargo_verus(project, &toolchain, options.package, verus_args)
+            .map_err(|e| format!("Failed to run verification: {}", e))?
+    } else {
+        options.monitor.progress().start("Verifying", None);
+        VerusRunner::new()
+            .run_verification(project, options.package, None, None, None)
+            .map_err(|e| format!("Failed to run verification: {}", e))?
+    };
+    options.monitor.progress().finish();
+    // Verus got the same interrupt, so its output is incomplete
+    if options.monitor.is_cancelled() {
+        return Err("interrupted before it finished".to_string());
+    }
+
+    info!("  Verification completed with exit code: {}", exit_code);
+
+    let timings = if options.with_timings {
+        let timings = VerificationTimings::parse(&output);
+        if timings.is_empty() {
+            warn!("  Verus reported no per-function times");
+        } else {
+            info!("  Timed {} functions", timings.functions.len());
+        }
+        timings
+    } else {
+        VerificationTimings::default()
+    };
+
+    let analyzer = VerificationAnalyzer::new();
+    let result = analyzer.analyze_output(project, &output, Some(exit_code), None, None);
+
+    match result.status {
+        AnalysisStatus::Success => info!("✓ Verification succeeded!"),
+        AnalysisStatus::VerificationFailed => {
+            warn!(
+                "⚠ Verification completed with {} errors",
+                result.summary.failed_functions
+            );
+        }
+        AnalysisStatus::CompilationFailed => {
+            warn!("⚠ Compilation failed");
+            // Show compilation errors for debugging
+            if !result.compilation.errors.is_empty() {
+                warn!(
+                    "  Compilation errors ({}):",
+                    result.compilation.errors.len()
+                );
+                for (i, err) in result.compilation.errors.iter().enumerate() {
+                    if let (Some(file), Some(line)) = (&err.file, err.line) {
+                        warn!("  {}. {}:{} - {}", i + 1, file, line, err.message);
+                    } else {
+                        warn!("  {}. {}", i + 1, err.message);
+                    }
+                    // Show full message for first few errors
+                    if i < 3 {
+                        for msg_line in &err.full_message {
+                            warn!("     {}", msg_line);
+                        }
+                    }
+                }
+                if result.compilation.errors.len() > 3 {
+                    warn!(
+                        "  ... and {} more errors",
+                        result.compilation.errors.len() - 3
+                    );
+                }
+            } else {
+                // No parsed errors - might be a different issue
+                warn!("  No specific compilation errors parsed.");
+                warn!("  This could indicate:");
+                warn!("    - cargo verus is not installed");
+                warn!("    - Project doesn't have Verus configured");
+                warn!("    - Other build issues");
+                // Show raw output snippet for debugging
+                warn!("  Raw output (first 50 lines):");
+                for line in output.lines().take(50) {
+                    warn!("    {}", line);
+                }
+            }
+        }
+        AnalysisStatus::FunctionsOnly => {
+            info!("  Functions parsed (no verification run)");
+        }
+    }
+
+    Ok(VerificationOutcome {
+        result,
+        timings,
+        attempts,
+        verus_version,
+    })
+}
+
+/// Normalize a file path for comparison
+fn normalize_path(path: &str) -> String {
+    let path = path.replace("file://", "");
+    if let Some(src_idx) = path.find("/src/") {
+        return path[src_idx + 1..].to_string();
+    }
+    path
+}
+
+/// Enrich graph nodes with verification status
+fn enrich_with_verification_status(graph: &mut D3Graph, verification: &AnalysisResult) -> usize {
+    info!("Enriching graph with verification status...");
+
+    // Build lookup: (display_name, normalized_path) -> status
+    let mut lookup: HashMap<(String, String), VerificationStatus> = HashMap::new();
+    let mut by_name: HashMap<String, Vec<VerificationStatus>> = HashMap::new();
+
+    for (functions, status) in [
+        (
+            &verification.verification.verified_functions,
+            VerificationStatus::Verified,
+        ),
+        (
+            &verification.verification.failed_functions,
+            VerificationStatus::Failed,
+        ),
+        (
+            &verification.verification.unverified_functions,
+            VerificationStatus::Unverified,
+        ),
+    ] {
+        for func in functions {
+            let norm_path = normalize_path(&func.code_path);
+            lookup.insert((func.display_name.clone(), norm_path), status);
+            by_name
+                .entry(func.display_name.clone())
+                .or_default()
+                .push(status);
+        }
+    }
+
+    // Enrich nodes
+    let mut enriched_count = 0;
+    for node in &mut graph.nodes {
+        let display_name = node.display_name.as_str();
+        let paths = [node.relative_path.as_str(), node.full_path.as_str()];
+
+        // Strategy 1: Match by (name, path)
+        let find_by_path = |name: &str| {
+            paths
+                .iter()
+                .filter(|path| !path.is_empty())
+                .find_map(|path| lookup.get(&(name.to_string(), normalize_path(path))))
+                .copied()
+        };
+        // Strategy 3: Match by name only if unique status
+        let find_by_name = |name: &str| {
+            by_name.get(name).and_then(|statuses| {
+                let unique: std::collections::HashSet<_> = statuses.iter().collect();
+                (unique.len() == 1).then_some(statuses[0])
+            })
+        };
+        // Graph nodes use "Type::method" but verification uses bare "method"
+        let bare_name = display_name
+            .rsplit("::")
+            .next()
+            .filter(|bare| *bare != display_name);
+
+        // Strategies 2 and 4: same as 1 and 3 with the bare method name
+        let status = find_by_path(display_name)
+            .or_else(|| bare_name.and_then(find_by_path))
+            .or_else(|| find_by_name(display_name))
+            .or_else(|| bare_name.and_then(find_by_name));
+
+        if let Some(status) = status {
+            node.verification_status = Some(status);
+            enriched_count += 1;
+        }
+    }
+
+    info!(
+        "✓ Enriched {} nodes with verification status",
+        enriched_count
+    );
+    info!("  Verified: {}", verification.summary.verified_functions);
+    info!("  Failed: {}", verification.summary.failed_functions);
+    info!(
+        "  Unverified (assume/admit): {}",
+        verification.summary.unverified_functions
+    );
+
+    enriched_count
+}
+
+/// Modules of the exported graph's source files, for per-module
+/// verification (limited to the `package` member crate when given, and to
+/// the modules of the `only` functions)
+fn verification_modules(
+    graph: &D3Graph,
+    package: Option<&str>,
+    only: Option<&BTreeSet<String>>,
+) -> Vec<String> {
+    let paths = graph
+        .nodes
+        .iter()
+        .filter(|node| match (package, node.crate_name.as_deref()) {
+            (Some(package), Some(crate_name)) => crate_name == package,
+            _ => true,
+        })
+        .filter(|node| only.is_none_or(|only| only.contains(&node.id)))
+        .map(|node| node.relative_path.as_str());
+    modules_from_paths(paths)
+}
+
+/// Set `verification_time_ms` (and `verification_rlimit` when reported) on
+/// graph nodes
+fn enrich_with_verification_times(graph: &mut D3Graph, timings: &VerificationTimings) -> usize {
+    let mut enriched_count = 0;
+    for node in &mut graph.nodes {
+        let Some(timing) = timings.time_for(&node.display_name, &node.relative_path) else {
+            continue;
+        };
+        node.verification_time_ms = Some(timing.time_ms);
+        if let Some(rlimit) = timing.rlimit {
+            node.verification_rlimit = Some(rlimit);
+        }
+        enriched_count += 1;
+    }
+
+    info!(
+        "✓ Enriched {} nodes with verification times",
+        enriched_count
+    );
+    let mut slowest: Vec<_> = timings.functions.values().collect();
+    slowest.sort_by(|a, b| b.time_ms.total_cmp(&a.time_ms));
+    for timing in slowest.iter().take(5) {
+        info!("  {:>8.0} ms  {}", timing.time_ms, timing.function);
+    }
+
+    enriched_count
+}
+
+/// Annotate failed and unverified nodes with similar vstd lemmas
+fn enrich_with_similar_lemmas(graph: &mut D3Graph, index: Option<&Path>) -> Result<usize, String> {
+    let index_path = match index {
+        Some(path) => path.to_path_buf(),
+        None => {
+            // Prefer the submodule's index, then data/
+            let repo_root = std::env::current_dir().unwrap_or_default();
+            [
+                repo_root.join("external/verus_lemma_finder/data/vstd_lemma_index.json"),
+                repo_root.join("data/vstd_lemma_index.json"),
+            ]
+            .into_iter()
+            .find(|p| p.exists())
+            .ok_or_else(|| {
+                "vstd_lemma_index.json not found. Check external/verus_lemma_finder submodule."
+                    .to_string()
+            })?
+        }
+    };
+
+    info!("Loading lemma index from {}...", index_path.display());
+    let index = LemmaIndex::load(&index_path)
+        .map_err(|e| format!("Failed to read {}: {}", index_path.display(), e))?;
+    let annotated = annotate_similar_lemmas(graph, &index, lemma_similarity::DEFAULT_TOP_K);
+    info!(
+        "✓ Added similar lemmas from {} indexed lemmas to {} nodes",
+        index.len(),
+        annotated
+    );
+    Ok(annotated)
+}
+
+/// Number of nodes per verification status, for the `verification` step event
+fn verification_counts(graph: &D3Graph) -> serde_json::Value {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for node in &graph.nodes {
+        if let Some(status) = node.verification_status {
+            *counts.entry(status.as_str()).or_default() += 1;
+        }
+    }
+    json!(counts)
+}
+
+/// Run the pipeline: generate SCIP, build and enrich the graph, and write
+/// the outputs
+pub fn run(config: &PipelineConfig) -> Result<PipelineReport, String> {
+    let pipeline = Step::start("pipeline");
+
+    // Validate project path
+    if !config.project.exists() {
+        return Err(format!(
+            "Project path does not exist: {}",
+            config.project.display()
+        ));
+    }
+
+    let cargo_toml = config.project.join("Cargo.toml");
+    if !cargo_toml.exists() {
+        return Err(format!(
+            "Not a valid Rust project (Cargo.toml not found): {}",
+            config.project.display()
+        ));
+    }
+
+    info!("Project: {}", config.project.display());
+    info!("Output: {}", config.output.display());
+
+    // Workspaces are indexed from the root; nodes are attributed to member crates
+    let mut warnings = Vec::new();
+    let workspace = match Workspace::detect(&config.project) {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            warn!("Could not read workspace members: {}", e);
+            warnings.push(format!("Could not read workspace members: {}", e));
+            None
+        }
+    };
+    if let Some(workspace) = &workspace {
+        info!(
+            "Workspace with {} member crate(s):",
+            workspace.members.len()
+        );
+        for member in &workspace.members {
+            info!("  {} ({})", member.name, member.path);
+        }
+    }
+    progress!();
+
+    // Step 1: Generate SCIP
+    progress!("─── Step 1: Generate SCIP Index ───────────────────────────────");
+    let step = Step::start("generate_scip");
+    if config.use_rust_analyzer {
+        info!("Using rust-analyzer (non-Verus mode)");
+    }
+    let monitor = &config.monitor;
+    monitor.progress().start("Indexing", None);
+    let scip_json = generate_scip(
+        &config.project,
+        config.use_cached_scip,
+        config.use_rust_analyzer,
+    );
+    monitor.progress().finish();
+    let scip_json = match (scip_json, monitor.check()) {
+        (Ok(path), Ok(())) => path,
+        (Err(e), _) => {
+            step.fail(&e);
+            return Err(format!("Failed to generate SCIP: {}", e));
+        }
+        (_, Err(cancelled)) => {
+            step.fail(&cancelled.to_string());
+            return Err("Cancelled before the call graph was built".to_string());
+        }
+    };
+    step.finish(serde_json::Value::Null);
+    progress!();
+
+    // Step 2: Export call graph
+    progress!("─── Step 2: Export Call Graph ─────────────────────────────────");
+    let step = Step::start("export_call_graph");
+
+    // Ensure output directory exists
+    if let Some(parent) = config.output.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create output directory: {}", e))?;
+    }
+
+    // In incremental mode, unchanged projects reuse the cached graph, and
+    // otherwise only the changed documents are re-analyzed. Both caches are
+    // discarded when the call graph options changed.
+    let options = CallGraphOptions::default().with_exclude_globs(config.exclude.clone());
+    let cache_path = fingerprint_cache_path(&config.output);
+    let mut fingerprints = None;
+    let mut changes = None;
+    let mut reuse_cached_graph = false;
+    if config.incremental {
+        match detect_changed_documents(&scip_json, &cache_path, options.fingerprint()) {
+            Ok(detected) => {
+                let changed = &detected.changed;
+                if changed.is_empty() && config.output.exists() {
+                    info!(
+                        "No documents changed since last run; reusing cached graph: {}",
+                        config.output.display()
+                    );
+                    reuse_cached_graph = true;
+                } else {
+                    info!("{} document(s) changed since last run:", changed.len());
+                    for path in changed.iter().take(20) {
+                        info!("  {}", path);
+                    }
+                    if changed.len() > 20 {
+                        info!("  ... and {} more", changed.len() - 20);
+                    }
+                }
+                fingerprints = Some(detected.fingerprints.clone());
+                changes = Some(detected);
+            }
+            Err(e) => {
+                warn!("Incremental check failed, doing a full export: {}", e);
+                warnings.push(format!("Incremental check failed: {}", e));
+            }
+        }
+    }
+
+    let graph = if reuse_cached_graph {
+        read_graph(&config.output)
+    } else {
+        monitor.progress().start("Exporting call graph", None);
+        let graph = match changes {
+            Some(changes) => export_incremental_call_graph(
+                changes,
+                &config.project,
+                &call_graph_cache_path(&config.output),
+                config.github_url.clone(),
+                workspace.as_ref(),
+                &options,
+                &mut warnings,
+            ),
+            None => export_call_graph(
+                &scip_json,
+                &config.project,
+                config.github_url.clone(),
+                workspace.as_ref(),
+                &options,
+                parse_mode(config.strict_scip),
+                &mut warnings,
+            ),
+        };
+        monitor.progress().finish();
+        graph
+    };
+    let mut graph = match graph {
+        Ok(graph) => graph,
+        Err(e) => {
+            step.fail(&e);
+            return Err(format!("Failed to export call graph: {}", e));
+        }
+    };
+    if let (Some(github_url), Some(rev)) = (&config.github_url, &config.rev) {
+        let linked = SourceLinks::new(github_url, rev).link_d3_graph(&mut graph);
+        info!("  Linked {} functions to {}", linked, github_url);
+    }
+    if !reuse_cached_graph {
+        if let Some(documents) = fingerprints {
+            let cache = FingerprintCache {
+                options: options.fingerprint(),
+                documents,
+            };
+            let json = serde_json::to_string_pretty(&cache)
+                .map_err(|e| format!("Failed to serialize fingerprints: {}", e))?;
+            if let Err(e) = std::fs::write(&cache_path, json) {
+                warn!("Failed to write fingerprint cache: {}", e);
+                warnings.push(format!("Failed to write fingerprint cache: {}", e));
+            }
+        }
+    }
+    step.finish(json!({
+        "nodes": graph.nodes.len(),
+        "links": graph.links.len(),
+        "reused_cached_graph": reuse_cached_graph,
+    }));
+    progress!();
+
+    // Step 3: Run verification and enrich (unless skipped)
+    let step = Step::start("verification");
+    let mut verified_nodes = None;
+    if monitor.is_cancelled() {
+        step.skip("cancelled");
+    } else if !config.skip_verification {
+        progress!("─── Step 3: Verification Status ─────────────────────────────────");
+        let package = config.package.as_deref();
+        let cache_path = verification_cache_path(&config.output);
+        let retry = RetryPolicy::default().with_max_retries(config.verify_retries);
+        let cache = config.verification_cache.then(|| {
+            let recorded = VerificationCache::load(&cache_path);
+            let had_results = !recorded.functions.is_empty();
+            let cache = recorded.for_run(
+                toolchain_version(&config.project, config.verus_path.as_deref()).as_deref(),
+                &cached_run_args(config.verification_times, &retry),
+            );
+            if had_results && cache.functions.is_empty() {
+                info!("Verus version or arguments changed since the cached run");
+            }
+            let hashes = body_hashes(&graph, &config.project);
+            let stale = cache.stale_functions(&graph, &hashes);
+            info!(
+                "{} of {} hashed functions changed or depend on changes since the cached run",
+                stale.len(),
+                hashes.len()
+            );
+            (cache, hashes, stale)
+        });
+        let all_modules = verification_modules(&graph, package, None);
+        let modules = match &cache {
+            Some((cache, _, stale)) if !cache.functions.is_empty() => {
+                verification_modules(&graph, package, Some(stale))
+            }
+            _ if config.jobs > 1 => all_modules.clone(),
+            _ => Vec::new(),
+        };
+        // Verifying every module one by one only pays off with several workers
+        let modules = if config.jobs == 1 && modules.len() == all_modules.len() {
+            Vec::new()
+        } else {
+            modules
+        };
+        let nothing_stale = cache
+            .as_ref()
+            .is_some_and(|(cache, _, stale)| !cache.functions.is_empty() && stale.is_empty());
+        let options = VerifyOptions {
+            package: config.package.as_deref(),
+            verus_path: config.verus_path.as_deref(),
+            with_timings: config.verification_times,
+            retry,
+            jobs: config.jobs,
+            modules,
+            log_dir: config
+                .output
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join("verification-logs"),
+            monitor,
+        };
+        let outcome = if nothing_stale {
+            info!("No function changed since the cached run; skipping verification");
+            Ok(None)
+        } else {
+            run_verification(&config.project, &options).map(Some)
+        };
+        match outcome {
+            Ok(outcome) => {
+                let mut enriched = 0;
+                if let Some(outcome) = outcome {
+                    enriched = enrich_with_verification_status(&mut graph, &outcome.result);
+                    if !outcome.timings.is_empty() {
+                        enrich_with_verification_times(&mut graph, &outcome.timings);
+                    }
+                    graph.metadata.verification_attempts = outcome.attempts;
+                    graph.metadata.verus_version = outcome.verus_version;
+                }
+                if let Some((mut cache, hashes, stale)) = cache {
+                    if graph.metadata.verus_version.is_none() {
+                        graph.metadata.verus_version = cache.verus_version.clone();
+                    }
+                    let cached = cache.fill_graph(&mut graph, &stale);
+                    info!("✓ Reused cached verification results of {} nodes", cached);
+                    enriched += cached;
+                    cache.record(&graph, &hashes);
+                    if let Err(e) = cache.save(&cache_path) {
+                        warn!("Failed to write verification cache: {}", e);
+                        warnings.push(format!("Failed to write verification cache: {}", e));
+                    }
+                }
+                verified_nodes = Some(enriched);
+                step.finish(verification_counts(&graph));
+            }
+            Err(e) => {
+                warn!("Verification failed: {}", e);
+                warn!("Continuing without verification status enrichment.");
+                warnings.push(format!("Verification failed: {}", e));
+                step.fail(&e);
+            }
+        }
+        progress!();
+    } else {
+        info!("Skipping verification status enrichment (--skip-verification)");
+        step.skip("--skip-verification");
+        progress!();
+    }
+
+    if config.graph_metrics {
+        add_graph_metrics_to_d3(&mut graph);
+        info!("Added graph metrics to {} nodes", graph.nodes.len());
+    }
+
+    // Step 4: Enrich with similar lemmas (unless skipped)
+    let step = Step::start("similar_lemmas");
+    let mut similar_lemma_nodes = None;
+    if monitor.is_cancelled() {
+        step.skip("cancelled");
+    } else if !config.skip_similar_lemmas {
+        progress!("─── Step 4: Similar Lemmas ──────────────────────────────────────");
+        match enrich_with_similar_lemmas(&mut graph, config.lemma_index.as_deref()) {
+            Ok(annotated) => {
+                similar_lemma_nodes = Some(annotated);
+                step.finish(json!({ "annotated_nodes": annotated }));
+            }
+            Err(e) => {
+                warn!("Similar lemmas enrichment skipped: {}", e);
+                warnings.push(format!("Similar lemmas enrichment skipped: {}", e));
+                info!("To enable: git submodule update --init, or pass --lemma-index <FILE>");
+                step.skip(&e);
+            }
+        }
+        progress!();
+    } else {
+        info!("Skipping similar lemmas enrichment (--skip-similar-lemmas)");
+        step.skip("--skip-similar-lemmas");
+        progress!();
+    }
+
+    let cancelled = monitor.is_cancelled();
+    if cancelled {
+        warn!("Cancelled: writing the graph without the remaining steps");
+        warnings.push("Cancelled: remaining steps skipped".to_string());
+    }
+    write_graph(&graph, &config.output)?;
+    info!("✓ Graph written to {}", config.output.display());
+
+    let mut html_report = None;
+    if let Some(report_path) = &config.html_report {
+        let title = config
+            .project
+            .file_name()
+            .map(|name| format!("{} call graph", name.to_string_lossy()))
+            .unwrap_or_else(|| "Call graph".to_string());
+        match write_html_report(&config.output, report_path, &title) {
+            Ok(()) => {
+                info!("✓ HTML report written to {}", report_path.display());
+                html_report = Some(report_path.clone());
+            }
+            Err(e) => {
+                warn!("Failed to write HTML report: {}", e);
+                warnings.push(format!("Failed to write HTML report: {}", e));
+            }
+        }
+    }
+
+    pipeline.finish(json!({ "nodes": graph.nodes.len() }));
+
+    Ok(PipelineReport {
+        graph,
+        scip_json,
+        reused_cached_graph: reuse_cached_graph,
+        verified_nodes,
+        similar_lemma_nodes,
+        html_report,
+        warnings,
+        cancelled,
+    })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scip_core::D3Node;
+    use std::fs;
+    use tempfile::TempDir;
+
+    // =========================================================================
+    // normalize_path tests
+    // =========================================================================
+
+    #[test]
+    fn test_parse_warnings_summary_counts_skipped_documents() {
+        let warning = |kind, message: &str| ParseWarning {
+            kind,
+            document: Some("src/lib.rs".to_string()),
+            message: message.to_string(),
+        };
+        let warnings = [
+            warning(
+                ParseWarningKind::SkippedDocument,
+                "missing field `occurrences`",
+            ),
+            warning(ParseWarningKind::PartialDocument, "skipped-looking message"),
+        ];
+        assert_eq!(
+            parse_warnings_summary(&warnings),
+            "SCIP JSON did not match the schema in 2 place(s); 1 document(s) skipped"
+        );
+    }
+
+    #[test]
+    fn test_normalize_path_strips_file_protocol() {
+        // Path with /src/ gets normalized to start from src/
+        let path = "file:///home/user/project/src/lib.rs";
+        assert_eq!(normalize_path(path), "src/lib.rs");
+    }
+
+    #[test]
+    fn test_normalize_path_strips_file_protocol_no_src() {
+        // Path without /src/ just strips the file:// protocol
+        let path = "file:///home/user/build.rs";
+        assert_eq!(normalize_path(path), "/home/user/build.rs");
+    }
+
+    #[test]
+    fn test_normalize_path_extracts_from_src() {
+        let path = "/home/user/project/src/backend/scalar.rs";
+        assert_eq!(normalize_path(path), "src/backend/scalar.rs");
+    }
+
+    #[test]
+    fn test_normalize_path_with_file_protocol_and_src() {
+        let path = "file:///home/user/my-project/src/lib.rs";
+        assert_eq!(normalize_path(path), "src/lib.rs");
+    }
+
+    #[test]
+    fn test_normalize_path_no_src_returns_as_is() {
+        let path = "/home/user/project/lib.rs";
+        assert_eq!(normalize_path(path), "/home/user/project/lib.rs");
+    }
+
+    #[test]
+    fn test_normalize_path_nested_src() {
+        // Should match the first /src/
+        let path = "/project/src/nested/src/file.rs";
+        assert_eq!(normalize_path(path), "src/nested/src/file.rs");
+    }
+
+    /// Typed graph from JSON nodes, the fields they leave out set to defaults
+    fn test_graph(json: serde_json::Value) -> D3Graph {
+        let nodes = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|fields| {
+                let mut node = serde_json::to_value(D3Node::default()).unwrap();
+                for (key, value) in fields.as_object().unwrap() {
+                    node[key] = value.clone();
+                }
+                serde_json::from_value(node).unwrap()
+            })
+            .collect();
+        D3Graph {
+            nodes,
+            links: Vec::new(),
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_enrich_with_verification_status_basic() {
+        use probe_verus::verification::{
+            AnalysisSummary, CompilationResult, FunctionLocation, VerificationResult,
+        };
+        use probe_verus::CodeTextInfo;
+
+        // Create a minimal graph with a node
+        let mut graph = test_graph(serde_json::json!({
+            "nodes": [
+                {
+                    "id": "test::my_function",
+                    "display_name": "my_function",
+                    "relative_path": "src/lib.rs",
+                    "full_path": "/project/src/lib.rs"
+                },
+                {
+                    "id": "test::other_function",
+                    "display_name": "other_function",
+                    "relative_path": "src/other.rs",
+                    "full_path": "/project/src/other.rs"
+                }
+            ],
+            "links": []
+        }));
+
+        // Create a mock verification result
+        let result = AnalysisResult {
+            status: AnalysisStatus::Success,
+            summary: AnalysisSummary {
+                total_functions: 2,
+                verified_functions: 1,
+                failed_functions: 0,
+                unverified_functions: 1,
+                verification_errors: 0,
+                compilation_errors: 0,
+                compilation_warnings: 0,
+            },
+            verification: VerificationResult {
+                verified_functions: vec![FunctionLocation {
+                    display_name: "my_function".to_string(),
+                    code_name: None,
+                    code_path: "src/lib.rs".to_string(),
+                    code_text: CodeTextInfo {
+                        lines_start: 1,
+                        lines_end: 10,
+                    },
+                }],
+                failed_functions: vec![],
+                unverified_functions: vec![FunctionLocation {
+                    display_name: "other_function".to_string(),
+                    code_name: None,
+                    code_path: "src/other.rs".to_string(),
+                    code_text: CodeTextInfo {
+                        lines_start: 1,
+                        lines_end: 5,
+                    },
+                }],
+                errors: vec![],
+            },
+            compilation: CompilationResult {
+                errors: vec![],
+                warnings: vec![],
+            },
+        };
+
+        // Run enrichment
+        let enriched_count = enrich_with_verification_status(&mut graph, &result);
+        assert_eq!(enriched_count, 2);
+
+        // Verify the graph was enriched
+        let enriched_graph = serde_json::to_value(&graph).unwrap();
+        let nodes = enriched_graph["nodes"].as_array().unwrap();
+
+        let my_func = nodes
+            .iter()
+            .find(|n| n["display_name"] == "my_function")
+            .unwrap();
+        assert_eq!(my_func["verification_status"], "verified");
+
+        let other_func = nodes
+            .iter()
+            .find(|n| n["display_name"] == "other_function")
+            .unwrap();
+        assert_eq!(other_func["verification_status"], "unverified");
+    }
+
+    #[test]
+    fn test_enrich_with_verification_status_handles_failed() {
+        use probe_verus::verification::{
+            AnalysisSummary, CompilationResult, FunctionLocation, VerificationResult,
+        };
+        use probe_verus::CodeTextInfo;
+
+        let mut graph = test_graph(serde_json::json!({
+            "nodes": [
+                {
+                    "id": "test::failing_proof",
+                    "display_name": "failing_proof",
+                    "relative_path": "src/proofs.rs",
+                    "full_path": "/project/src/proofs.rs"
+                }
+            ],
+            "links": []
+        }));
+
+        let result = AnalysisResult {
+            status: AnalysisStatus::VerificationFailed,
+            summary: AnalysisSummary {
+                total_functions: 1,
+                verified_functions: 0,
+                failed_functions: 1,
+                unverified_functions: 0,
+                verification_errors: 1,
+                compilation_errors: 0,
+                compilation_warnings: 0,
+            },
+            verification: VerificationResult {
+                verified_functions: vec![],
+                failed_functions: vec![FunctionLocation {
+                    display_name: "failing_proof".to_string(),
+                    code_name: None,
+                    code_path: "src/proofs.rs".to_string(),
+                    code_text: CodeTextInfo {
+                        lines_start: 1,
+                        lines_end: 20,
+                    },
+                }],
+                unverified_functions: vec![],
+                errors: vec![],
+            },
+            compilation: CompilationResult {
+                errors: vec![],
+                warnings: vec![],
+            },
+        };
+
+        let enriched_count = enrich_with_verification_status(&mut graph, &result);
+        assert_eq!(enriched_count, 1);
+
+        let enriched_graph = serde_json::to_value(&graph).unwrap();
+        let node = &enriched_graph["nodes"][0];
+        assert_eq!(node["verification_status"], "failed");
+    }
+
+    #[test]
+    fn test_enrich_with_verification_times() {
+        let mut graph = test_graph(serde_json::json!({
+            "nodes": [
+                {"id": "a", "display_name": "lemma_slow", "relative_path": "src/lemmas.rs"},
+                {"id": "b", "display_name": "untimed", "relative_path": "src/lib.rs"}
+            ],
+            "links": []
+        }));
+
+        let output = r#"{"times-ms": {"smt": {"smt-run-module-times": [{"module": "lemmas",
+            "function-breakdown": [{"function": "demo::lemmas::lemma_slow", "time": 4200, "rlimit": 90}]}]}}}"#;
+        let timings = VerificationTimings::parse(output);
+        let enriched_count = enrich_with_verification_times(&mut graph, &timings);
+        assert_eq!(enriched_count, 1);
+
+        let enriched_graph = serde_json::to_value(&graph).unwrap();
+        assert_eq!(enriched_graph["nodes"][0]["verification_time_ms"], 4200.0);
+        assert_eq!(enriched_graph["nodes"][0]["verification_rlimit"], 90);
+        assert!(enriched_graph["nodes"][1]
+            .get("verification_time_ms")
+            .is_none());
+    }
+
+    // =========================================================================
+    // Incremental mode tests
+    // =========================================================================
+
+    #[test]
+    fn test_fingerprint_cache_path() {
+        assert_eq!(
+            fingerprint_cache_path(Path::new("web/public/graph.json")),
+            PathBuf::from("web/public/graph.fingerprints.json")
+        );
+    }
+
+    #[test]
+    fn test_verification_cache_path() {
+        assert_eq!(
+            verification_cache_path(Path::new("web/public/graph.json")),
+            PathBuf::from("web/public/graph.verification-cache.json")
+        );
+    }
+
+    #[test]
+    fn test_detect_changed_documents_against_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let scip_path = temp_dir.path().join("index.scip.json");
+        let cache_path = temp_dir.path().join("graph.fingerprints.json");
+        fs::write(&scip_path, create_mock_scip_json().to_string()).unwrap();
+
+        let options = CallGraphOptions::default().fingerprint();
+
+        // No cache yet: every document counts as changed
+        let changes = detect_changed_documents(&scip_path, &cache_path, options).unwrap();
+        assert!(!changes.changed.is_empty());
+        assert_eq!(changes.changed.len(), changes.fingerprints.len());
+
+        // After caching, nothing changed
+        let cache = FingerprintCache {
+            options,
+            documents: changes.fingerprints.clone(),
+        };
+        fs::write(&cache_path, serde_json::to_string(&cache).unwrap()).unwrap();
+        let changes = detect_changed_documents(&scip_path, &cache_path, options).unwrap();
+        assert!(changes.changed.is_empty());
+
+        // With other options, every document counts as changed again
+        let other = CallGraphOptions::default()
+            .with_exclude_globs(["vendor/**".to_string()])
+            .fingerprint();
+        let changes = detect_changed_documents(&scip_path, &cache_path, other).unwrap();
+        assert_eq!(changes.changed.len(), changes.fingerprints.len());
+    }
+
+    #[test]
+    fn test_call_graph_cache_discarded_for_other_options() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_path = temp_dir.path().join("graph.callgraph.json");
+        let options = CallGraphOptions::default();
+        let cache = CallGraphCache {
+            options: options.fingerprint(),
+            call_graph: incremental_call_graph(two_document_index(3), &[], None, &options),
+        };
+        fs::write(&cache_path, serde_json::to_string(&cache).unwrap()).unwrap();
+
+        let cached = load_call_graph_cache(&cache_path, options.fingerprint());
+        assert_eq!(cached.map(|graph| graph.len()), Some(2));
+
+        let other = options.with_types(true).fingerprint();
+        assert!(load_call_graph_cache(&cache_path, other).is_none());
+    }
+
+    const MAIN: &str = "rust-analyzer cargo demo 0.1.0 app/main().";
+    const HELPER: &str = "rust-analyzer cargo demo 0.1.0 util/helper().";
+
+    /// `main()` in `src/app.rs` calling `helper()` in `src/util.rs` on line
+    /// `call_line`
+    fn two_document_index(call_line: i32) -> ScipIndex {
+        serde_json::from_value(json!({
+            "metadata": { "project_root": "/nonexistent" },
+            "documents": [
+                {
+                    "relative_path": "src/app.rs",
+                    "occurrences": [
+                        { "range": [1, 0, 4], "symbol": MAIN, "symbol_roles": 1 },
+                        { "range": [call_line, 4, 10], "symbol": HELPER }
+                    ],
+                    "symbols": [{ "symbol": MAIN, "kind": 12, "display_name": "main" }]
+                },
+                {
+                    "relative_path": "src/util.rs",
+                    "occurrences": [
+                        { "range": [0, 0, 6], "symbol": HELPER, "symbol_roles": 1 }
+                    ],
+                    "symbols": [{ "symbol": HELPER, "kind": 12, "display_name": "helper" }]
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_incremental_call_graph_reuses_unchanged_nodes() {
+        let options = CallGraphOptions::default();
+        let mut cached = incremental_call_graph(two_document_index(3), &[], None, &options);
+        assert_eq!(cached.len(), 2);
+
+        // Tag the node of the unchanged document: a rebuild would lose it
+        let mut helper = cached[HELPER].clone();
+        helper.doc = Some("from the cache".to_string());
+        cached.add_node(helper);
+
+        let changed = vec!["src/app.rs".to_string()];
+        let graph = incremental_call_graph(two_document_index(7), &changed, Some(cached), &options);
+
+        assert_eq!(graph[HELPER].doc.as_deref(), Some("from the cache"));
+        assert_eq!(graph[MAIN].call_sites(HELPER)[0].line, 7);
+        assert!(graph[HELPER].callers.contains(MAIN));
+    }
+
+    #[test]
+    fn test_incremental_call_graph_drops_deleted_and_excluded_documents() {
+        let options = CallGraphOptions::default().with_exclude_globs(vec!["src/app.rs".into()]);
+        let cached = incremental_call_graph(
+            two_document_index(3),
+            &[],
+            None,
+            &CallGraphOptions::default(),
+        );
+
+        let mut index = two_document_index(3);
+        index
+            .documents
+            .retain(|doc| doc.relative_path != "src/util.rs");
+        let changed = vec!["src/app.rs".to_string(), "src/util.rs".to_string()];
+        let graph = incremental_call_graph(index, &changed, Some(cached), &options);
+
+        assert!(graph.is_empty());
+    }
+
+    // =========================================================================
+    // Integration test: export_call_graph with mock SCIP data
+    // =========================================================================
+
+    /// Creates a minimal mock SCIP JSON for testing
+    fn create_mock_scip_json() -> serde_json::Value {
+        serde_json::json!({
+            "metadata": {
+                "version": 1,
+                "tool_info": {
+                    "name": "rust-analyzer",
+                    "version": "test"
+                },
+                "project_root": "file:///mock/project",
+                "text_document_encoding": 1
+            },
+            "documents": [
+                {
+                    "relative_path": "src/lib.rs",
+                    "language": "rust",
+                    "position_encoding": 1,
+                    "occurrences": [
+                        {
+                            "range": [10, 0, 10, 10],
+                            "symbol": "rust-analyzer cargo mock 0.1.0 lib/foo().",
+                            "symbol_roles": 1
+                        },
+                        {
+                            "range": [15, 4, 15, 7],
+                            "symbol": "rust-analyzer cargo mock 0.1.0 lib/bar().",
+                            "symbol_roles": 0
+                        }
+                    ],
+                    "symbols": [
+                        {
+                            "symbol": "rust-analyzer cargo mock 0.1.0 lib/foo().",
+                            "kind": 12,
+                            "display_name": "foo",
+                            "signature_documentation": {
+                                "language": "rust",
+                                "text": "fn foo()",
+                                "position_encoding": 1
+                            }
+                        },
+                        {
+                            "symbol": "rust-analyzer cargo mock 0.1.0 lib/bar().",
+                            "kind": 12,
+                            "display_name": "bar",
+                            "signature_documentation": {
+                                "language": "rust",
+                                "text": "fn bar()",
+                                "position_encoding": 1
+                            }
+                        }
+                    ]
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_export_call_graph_with_mock_scip() {
+        let temp_dir = TempDir::new().unwrap();
+        let scip_json_path = temp_dir.path().join("index.scip.json");
+        let output_path = temp_dir.path().join("graph.json");
+
+        // Write mock SCIP JSON
+        let mock_scip = create_mock_scip_json();
+        fs::write(
+            &scip_json_path,
+            serde_json::to_string_pretty(&mock_scip).unwrap(),
+        )
+        .unwrap();
+
+        // Run export
+        let result = export_call_graph(
+            &scip_json_path,
+            Path::new("/mock/project"),
+            None,
+            None,
+            &CallGraphOptions::default(),
+            ParseMode::Strict,
+            &mut Vec::new(),
+        );
+        assert!(
+            result.is_ok(),
+            "export_call_graph should succeed: {:?}",
+            result
+        );
+        write_graph(&result.unwrap(), &output_path).unwrap();
+
+        // Verify output exists and is valid JSON
+        assert!(output_path.exists());
+        let graph_content = fs::read_to_string(&output_path).unwrap();
+        let graph: serde_json::Value = serde_json::from_str(&graph_content).unwrap();
+
+        // Verify structure - the graph should have nodes and links arrays
+        assert!(graph["nodes"].is_array(), "Graph should have 'nodes' array");
+        assert!(graph["links"].is_array(), "Graph should have 'links' array");
+
+        let nodes = graph["nodes"].as_array().unwrap();
+        let links = graph["links"].as_array().unwrap();
+
+        // The minimal mock may not produce nodes (depends on function-like kind detection)
+        // but the output structure should be valid
+        eprintln!(
+            "✓ Mock SCIP test: {} nodes, {} links",
+            nodes.len(),
+            links.len()
+        );
+
+        // If there are nodes, verify their structure
+        if !nodes.is_empty() {
+            let first_node = &nodes[0];
+            assert!(first_node["id"].is_string());
+            assert!(first_node["display_name"].is_string());
+        }
+    }
+
+    #[test]
+    fn test_export_call_graph_with_github_url() {
+        let temp_dir = TempDir::new().unwrap();
+        let scip_json_path = temp_dir.path().join("index.scip.json");
+        let output_path = temp_dir.path().join("graph.json");
+
+        // Write mock SCIP JSON
+        let mock_scip = create_mock_scip_json();
+        fs::write(
+            &scip_json_path,
+            serde_json::to_string_pretty(&mock_scip).unwrap(),
+        )
+        .unwrap();
+
+        // Run export with GitHub URL
+        let github_url = Some("https://github.com/test/repo".to_string());
+        let result = export_call_graph(
+            &scip_json_path,
+            Path::new("/mock/project"),
+            github_url,
+            None,
+            &CallGraphOptions::default(),
+            ParseMode::Strict,
+            &mut Vec::new(),
+        );
+        assert!(result.is_ok());
+        write_graph(&result.unwrap(), &output_path).unwrap();
+
+        // Verify output
+        let graph: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+
+        // Graph should be valid
+        assert!(graph["nodes"].is_array());
+    }
+
+    // =========================================================================
+    // generate_scip tests (uses mock cached SCIP)
+    // =========================================================================
+
+    #[test]
+    fn test_run_rejects_non_cargo_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("graph.json");
+
+        let missing = PipelineConfig::new(temp_dir.path().join("missing"), &output);
+        let err = run(&missing).unwrap_err();
+        assert!(err.contains("does not exist"), "{}", err);
+
+        let err = run(&PipelineConfig::new(temp_dir.path(), &output)).unwrap_err();
+        assert!(err.contains("Cargo.toml not found"), "{}", err);
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_generate_scip_uses_cached_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().to_path_buf();
+
+        // Create a mock cached SCIP JSON
+        let cached_json = project_dir.join("index.scip.json");
+        fs::write(&cached_json, r#"{"metadata": {}, "documents": []}"#).unwrap();
+
+        // Should return the cached path when use_cached=true (use_rust_analyzer=false is default)
+        let result = generate_scip(&project_dir, true, false);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), cached_json);
+    }
+
+    #[test]
+    fn test_generate_scip_returns_error_without_cache_or_tools() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().to_path_buf();
+
+        // No cached JSON, and verus-analyzer likely not installed
+        // This should either use cache (if exists) or fail gracefully
+        let result = generate_scip(&project_dir, true, false);
+
+        // With use_cached=true but no cache, it should try to regenerate
+        // and likely fail (verus-analyzer not available)
+        // We just verify it doesn't panic
+        if let Err(err) = result {
+            // Should mention verus-analyzer or rust-analyzer or scip
+            assert!(
+                err.contains("verus-analyzer")
+                    || err.contains("rust-analyzer")
+                    || err.contains("scip")
+                    || err.contains("not found"),
+                "Error should mention missing tool: {}",
+                err
+            );
+        }
+    }
+}
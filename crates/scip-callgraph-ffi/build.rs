@@ -0,0 +1,25 @@
+//! Generates `scip_callgraph.h` from the `extern "C"` API into `OUT_DIR`;
+//! the `test_header_is_fresh` test checks `include/scip_callgraph.h`
+//! against it
+
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+        .expect("invalid cbindgen.toml");
+    let header = format!("{out_dir}/scip_callgraph.h");
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(header);
+        }
+        // The compiler reports the actual error when the source does not
+        // parse; the freshness test then fails on the empty header
+        Err(e) => {
+            println!("cargo:warning=Header not generated: {e}");
+            std::fs::write(header, "").unwrap();
+        }
+    }
+}
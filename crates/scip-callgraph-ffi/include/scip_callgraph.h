@@ -0,0 +1,132 @@
+#ifndef SCIP_CALLGRAPH_H
+#define SCIP_CALLGRAPH_H
+
+/* Generated by cbindgen from crates/scip-callgraph-ffi; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A loaded call graph (opaque)
+ */
+typedef struct ScgGraph ScgGraph;
+
+/**
+ * A function of the graph
+ */
+typedef struct ScgNode {
+  /**
+   * SCIP symbol (or atom identifier), unique in the graph
+   */
+  const char *symbol;
+  const char *display_name;
+  /**
+   * Source file relative to the project root
+   */
+  const char *relative_path;
+  /**
+   * 1-based first line, 0 when unknown
+   */
+  uint32_t start_line;
+  /**
+   * 1-based last line, 0 when unknown
+   */
+  uint32_t end_line;
+  /**
+   * Defined outside the indexed project
+   */
+  bool is_external;
+} ScgNode;
+
+/**
+ * A call from one function of the graph to another
+ */
+typedef struct ScgEdge {
+  /**
+   * Node index of the calling function
+   */
+  size_t caller;
+  /**
+   * Node index of the called function
+   */
+  size_t callee;
+} ScgEdge;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Load a call graph from SCIP JSON, a binary `.scip` index or atoms JSON.
+ *
+ * Returns NULL on failure; [`scg_last_error`] then describes the error.
+ * Free the graph with [`scg_graph_free`].
+ *
+ * # Safety
+ *
+ * `path` must be a valid NUL-terminated string.
+ */
+struct ScgGraph *scg_graph_load(const char *path);
+
+/**
+ * Free a graph returned by [`scg_graph_load`]; NULL is ignored.
+ *
+ * # Safety
+ *
+ * `graph` must come from [`scg_graph_load`] and not be used afterwards.
+ */
+void scg_graph_free(struct ScgGraph *graph);
+
+/**
+ * Number of functions in the graph (0 for NULL)
+ *
+ * # Safety
+ *
+ * `graph` must be NULL or a live graph from [`scg_graph_load`].
+ */
+size_t scg_graph_node_count(const struct ScgGraph *graph);
+
+/**
+ * Number of calls between functions of the graph (0 for NULL)
+ *
+ * # Safety
+ *
+ * `graph` must be NULL or a live graph from [`scg_graph_load`].
+ */
+size_t scg_graph_edge_count(const struct ScgGraph *graph);
+
+/**
+ * Fill `out` with node `index`; returns false if it is out of range.
+ *
+ * # Safety
+ *
+ * `graph` must be NULL or a live graph from [`scg_graph_load`], and `out`
+ * NULL or valid for writes.
+ */
+bool scg_graph_node(const struct ScgGraph *graph, size_t index, struct ScgNode *out);
+
+/**
+ * Fill `out` with edge `index`; returns false if it is out of range.
+ *
+ * # Safety
+ *
+ * `graph` must be NULL or a live graph from [`scg_graph_load`], and `out`
+ * NULL or valid for writes.
+ */
+bool scg_graph_edge(const struct ScgGraph *graph, size_t index, struct ScgEdge *out);
+
+/**
+ * Message of the last error on this thread, or NULL.
+ *
+ * The string stays valid until the next failing call on the same thread.
+ */
+const char *scg_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SCIP_CALLGRAPH_H */
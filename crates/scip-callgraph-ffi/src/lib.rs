@@ -0,0 +1,337 @@
+//! C API for the call graph, for embedding in non-Rust tooling
+//!
+//! Built as `libscip_callgraph_c.{so,dylib,a}`; the checked-in header
+//! `include/scip_callgraph.h` is generated by cbindgen, and a test fails
+//! when it is stale (`UPDATE_HEADER=1 cargo test -p scip-callgraph-ffi`
+//! rewrites it).
+//!
+//! ```c
+//! ScgGraph *graph = scg_graph_load("index_scip.json");
+//! if (!graph) { fprintf(stderr, "%s\n", scg_last_error()); return 1; }
+//! for (size_t i = 0; i < scg_graph_edge_count(graph); i++) {
+//!     ScgEdge edge;
+//!     ScgNode caller, callee;
+//!     scg_graph_edge(graph, i, &edge);
+//!     scg_graph_node(graph, edge.caller, &caller);
+//!     scg_graph_node(graph, edge.callee, &callee);
+//!     printf("%s -> %s\n", caller.display_name, callee.display_name);
+//! }
+//! scg_graph_free(graph);
+//! ```
+//!
+//! Nodes are numbered `0..scg_graph_node_count` in symbol order and edges
+//! `0..scg_graph_edge_count` in (caller, callee) order, so iteration is
+//! deterministic. Strings are NUL-terminated UTF-8 owned by the graph and
+//! valid until [`scg_graph_free`]. `ScgNode` and `ScgEdge` are `repr(C)`;
+//! fields are only ever appended.
+
+use scip_core::{load_call_graph, FunctionNode};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<Vec<u8>>) {
+    let mut message = message.into();
+    message.retain(|&byte| byte != 0);
+    let message = CString::new(message).expect("NUL bytes were removed");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Strings of one function, kept alive for the C side
+struct NodeData {
+    symbol: CString,
+    display_name: CString,
+    relative_path: CString,
+    start_line: u32,
+    end_line: u32,
+    is_external: bool,
+}
+
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).expect("NUL bytes were removed")
+}
+
+impl From<&FunctionNode> for NodeData {
+    fn from(node: &FunctionNode) -> Self {
+        let line = |line: Option<usize>| line.map_or(0, |line| line as u32);
+        NodeData {
+            symbol: c_string(&node.symbol),
+            display_name: c_string(&node.display_name),
+            relative_path: c_string(&node.relative_path),
+            start_line: line(node.start_line),
+            end_line: line(node.end_line),
+            is_external: node.file_path.starts_with("external:"),
+        }
+    }
+}
+
+/// A loaded call graph (opaque)
+pub struct ScgGraph {
+    nodes: Vec<NodeData>,
+    /// (caller, callee) node indices, sorted
+    edges: Vec<(usize, usize)>,
+}
+
+impl ScgGraph {
+    fn new(call_graph: &scip_core::CallGraph) -> Self {
+        let mut functions: Vec<&FunctionNode> = call_graph.values().collect();
+        functions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        let index: std::collections::HashMap<&str, usize> = functions
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.symbol.as_str(), i))
+            .collect();
+
+        // Calls to functions outside the graph have no node to point to
+        let mut edges: Vec<(usize, usize)> = functions
+            .iter()
+            .enumerate()
+            .flat_map(|(caller, node)| {
+                node.callees
+                    .iter()
+                    .filter_map(|callee| index.get(callee.as_str()))
+                    .map(move |&callee| (caller, callee))
+            })
+            .collect();
+        edges.sort_unstable();
+
+        ScgGraph {
+            nodes: functions.into_iter().map(NodeData::from).collect(),
+            edges,
+        }
+    }
+}
+
+/// A function of the graph
+#[repr(C)]
+pub struct ScgNode {
+    /// SCIP symbol (or atom identifier), unique in the graph
+    pub symbol: *const c_char,
+    pub display_name: *const c_char,
+    /// Source file relative to the project root
+    pub relative_path: *const c_char,
+    /// 1-based first line, 0 when unknown
+    pub start_line: u32,
+    /// 1-based last line, 0 when unknown
+    pub end_line: u32,
+    /// Defined outside the indexed project
+    pub is_external: bool,
+}
+
+/// A call from one function of the graph to another
+#[repr(C)]
+pub struct ScgEdge {
+    /// Node index of the calling function
+    pub caller: usize,
+    /// Node index of the called function
+    pub callee: usize,
+}
+
+/// Load a call graph from SCIP JSON, a binary `.scip` index or atoms JSON.
+///
+/// Returns NULL on failure; [`scg_last_error`] then describes the error.
+/// Free the graph with [`scg_graph_free`].
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn scg_graph_load(path: *const c_char) -> *mut ScgGraph {
+    if path.is_null() {
+        set_last_error("path is NULL");
+        return ptr::null_mut();
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        set_last_error("path is not valid UTF-8");
+        return ptr::null_mut();
+    };
+    let loaded = catch_unwind(AssertUnwindSafe(|| {
+        load_call_graph(path)
+            .map(|call_graph| ScgGraph::new(&call_graph))
+            .map_err(|e| format!("Failed to load {}: {}", path, e))
+    }));
+    match loaded {
+        Ok(Ok(graph)) => Box::into_raw(Box::new(graph)),
+        Ok(Err(message)) => {
+            set_last_error(message);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error(format!("Failed to load {}: internal error", path));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Free a graph returned by [`scg_graph_load`]; NULL is ignored.
+///
+/// # Safety
+///
+/// `graph` must come from [`scg_graph_load`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn scg_graph_free(graph: *mut ScgGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Number of functions in the graph (0 for NULL)
+///
+/// # Safety
+///
+/// `graph` must be NULL or a live graph from [`scg_graph_load`].
+#[no_mangle]
+pub unsafe extern "C" fn scg_graph_node_count(graph: *const ScgGraph) -> usize {
+    graph.as_ref().map_or(0, |graph| graph.nodes.len())
+}
+
+/// Number of calls between functions of the graph (0 for NULL)
+///
+/// # Safety
+///
+/// `graph` must be NULL or a live graph from [`scg_graph_load`].
+#[no_mangle]
+pub unsafe extern "C" fn scg_graph_edge_count(graph: *const ScgGraph) -> usize {
+    graph.as_ref().map_or(0, |graph| graph.edges.len())
+}
+
+/// Fill `out` with node `index`; returns false if it is out of range.
+///
+/// # Safety
+///
+/// `graph` must be NULL or a live graph from [`scg_graph_load`], and `out`
+/// NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn scg_graph_node(
+    graph: *const ScgGraph,
+    index: usize,
+    out: *mut ScgNode,
+) -> bool {
+    let (Some(graph), false) = (graph.as_ref(), out.is_null()) else {
+        return false;
+    };
+    let Some(node) = graph.nodes.get(index) else {
+        return false;
+    };
+    out.write(ScgNode {
+        symbol: node.symbol.as_ptr(),
+        display_name: node.display_name.as_ptr(),
+        relative_path: node.relative_path.as_ptr(),
+        start_line: node.start_line,
+        end_line: node.end_line,
+        is_external: node.is_external,
+    });
+    true
+}
+
+/// Fill `out` with edge `index`; returns false if it is out of range.
+///
+/// # Safety
+///
+/// `graph` must be NULL or a live graph from [`scg_graph_load`], and `out`
+/// NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn scg_graph_edge(
+    graph: *const ScgGraph,
+    index: usize,
+    out: *mut ScgEdge,
+) -> bool {
+    let (Some(graph), false) = (graph.as_ref(), out.is_null()) else {
+        return false;
+    };
+    let Some(&(caller, callee)) = graph.edges.get(index) else {
+        return false;
+    };
+    out.write(ScgEdge { caller, callee });
+    true
+}
+
+/// Message of the last error on this thread, or NULL.
+///
+/// The string stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn scg_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_is_fresh() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/scip_callgraph.h"));
+        let checked_in = concat!(env!("CARGO_MANIFEST_DIR"), "/include/scip_callgraph.h");
+        assert!(
+            !generated.is_empty(),
+            "cbindgen failed, see the build warnings"
+        );
+        if std::env::var_os("UPDATE_HEADER").is_some() {
+            std::fs::write(checked_in, generated).unwrap();
+        }
+        assert!(
+            std::fs::read_to_string(checked_in).unwrap() == generated,
+            "{checked_in} is stale; run `UPDATE_HEADER=1 cargo test -p scip-callgraph-ffi`"
+        );
+    }
+
+    #[test]
+    fn test_c_api() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("atoms.json");
+        let atom = |id: &str, deps: &[&str]| {
+            format!(
+                r#"{{"identifier": "{id}", "statement_type": "function", "deps": {deps:?},
+                    "body": "", "display_name": "{id}", "full_path": "/p/src/lib.rs",
+                    "relative_path": "src/lib.rs", "file_name": "lib.rs", "parent_folder": "src",
+                    "start_line": 3}}"#
+            )
+        };
+        let atoms = format!(
+            "[{}, {}, {}]",
+            atom("main", &["helper", "std_fn"]),
+            atom("helper", &["leaf"]),
+            atom("leaf", &[])
+        );
+        std::fs::write(&path, atoms).unwrap();
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let graph = scg_graph_load(c_path.as_ptr());
+            assert!(!graph.is_null());
+            assert_eq!(scg_graph_node_count(graph), 3);
+            // The call to std_fn has no node
+            assert_eq!(scg_graph_edge_count(graph), 2);
+
+            let mut edge = ScgEdge {
+                caller: 0,
+                callee: 0,
+            };
+            assert!(scg_graph_edge(graph, 0, &mut edge));
+            let mut node = std::mem::zeroed::<ScgNode>();
+            assert!(scg_graph_node(graph, edge.caller, &mut node));
+            // Nodes in symbol order: helper, leaf, main
+            assert_eq!(CStr::from_ptr(node.symbol).to_str().unwrap(), "helper");
+            assert_eq!(node.start_line, 3);
+            assert!(scg_graph_node(graph, edge.callee, &mut node));
+            assert_eq!(CStr::from_ptr(node.display_name).to_str().unwrap(), "leaf");
+            assert!(!scg_graph_edge(graph, 2, &mut edge));
+            assert!(!scg_graph_node(graph, 3, &mut node));
+            scg_graph_free(graph);
+
+            let missing = CString::new("/nonexistent/index.json").unwrap();
+            assert!(scg_graph_load(missing.as_ptr()).is_null());
+            let error = CStr::from_ptr(scg_last_error()).to_str().unwrap();
+            assert!(error.starts_with("Failed to load /nonexistent/index.json"));
+        }
+    }
+}
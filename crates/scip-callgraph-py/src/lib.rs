@@ -0,0 +1,308 @@
+//! Python bindings for the call graph API (the `scip_callgraph` module)
+//!
+//! Build and install into the current virtualenv with
+//! `maturin develop -m crates/scip-callgraph-py/Cargo.toml`, then:
+//!
+//! ```python
+//! import networkx as nx
+//! import scip_callgraph
+//!
+//! graph = scip_callgraph.load_scip("index_scip.json").build_graph()
+//! graph.callers("lemma_foo", depth=2)  # [{"symbol": ..., "depth": 1, ...}, ...]
+//! graph.metrics("lemma_foo")           # {"fan_in": 3, "pagerank": 0.01, ...}
+//! g = nx.node_link_graph(graph.to_networkx_dicts(), edges="links")
+//! ```
+//!
+//! Results are built as Python dicts and lists directly, skipping the JSON
+//! round-trip. Functions are named as in the CLIs: display name,
+//! `Type::method`, path suffix or full symbol (see [`SymbolResolver`]).
+
+use pyo3::exceptions::{PyIOError, PyKeyError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use scip_core::{
+    build_call_graph, compute_graph_metrics, neighborhood_by_depth, parse_scip_json,
+    parse_scip_protobuf, FunctionNode, GraphMetrics, SymbolResolver,
+};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+fn load_error(path: &str, message: String) -> PyErr {
+    PyIOError::new_err(format!("Failed to load {}: {}", path, message))
+}
+
+/// A parsed SCIP index
+#[pyclass(module = "scip_callgraph", frozen)]
+struct ScipIndex {
+    index: scip_core::ScipIndex,
+}
+
+#[pymethods]
+impl ScipIndex {
+    /// Number of indexed source files
+    #[getter]
+    fn documents(&self) -> usize {
+        self.index.documents.len()
+    }
+
+    /// Build the call graph of the index
+    fn build_graph(&self, py: Python<'_>) -> CallGraph {
+        CallGraph::new(py.allow_threads(|| build_call_graph(&self.index)))
+    }
+}
+
+/// A call graph, with its functions indexed by name
+#[pyclass(module = "scip_callgraph", frozen)]
+struct CallGraph {
+    graph: scip_core::CallGraph,
+    resolver: SymbolResolver,
+    /// Computed on the first `metrics` call
+    metrics: OnceLock<HashMap<String, GraphMetrics>>,
+}
+
+fn node_dict<'py>(py: Python<'py>, node: &FunctionNode) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("symbol", node.symbol.as_str())?;
+    dict.set_item("display_name", node.display_name.as_str())?;
+    dict.set_item("relative_path", node.relative_path.as_str())?;
+    dict.set_item("start_line", node.start_line)?;
+    dict.set_item("end_line", node.end_line)?;
+    dict.set_item("external", node.file_path.starts_with("external:"))?;
+    Ok(dict)
+}
+
+impl CallGraph {
+    fn new(graph: scip_core::CallGraph) -> Self {
+        let resolver = SymbolResolver::from_call_graph(&graph);
+        Self {
+            graph,
+            resolver,
+            metrics: OnceLock::new(),
+        }
+    }
+
+    fn resolve_all(&self, name: &str) -> PyResult<Vec<&FunctionNode>> {
+        let symbols = self
+            .resolver
+            .resolve_all(name)
+            .map_err(|e| PyKeyError::new_err(e.to_string()))?;
+        Ok(symbols
+            .into_iter()
+            .map(|symbol| &self.graph[symbol])
+            .collect())
+    }
+
+    /// Callees or callers of `name` within `depth` calls, closest first
+    fn reachable_dicts<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        depth: usize,
+        callees: bool,
+        callers: bool,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let starts: Vec<&str> = self
+            .resolve_all(name)?
+            .into_iter()
+            .map(|node| node.symbol.as_str())
+            .collect();
+        neighborhood_by_depth(&self.graph, &starts, callees, callers, Some(depth))
+            .into_iter()
+            .map(|(node, depth)| {
+                let dict = node_dict(py, node)?;
+                dict.set_item("depth", depth)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+}
+
+#[pymethods]
+impl CallGraph {
+    fn __len__(&self) -> usize {
+        self.graph.len()
+    }
+
+    /// Functions calling `name`, transitively up to `depth` calls away
+    #[pyo3(signature = (name, depth = 1))]
+    fn callers<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        depth: usize,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.reachable_dicts(py, name, depth, false, true)
+    }
+
+    /// Functions called by `name`, transitively up to `depth` calls away
+    #[pyo3(signature = (name, depth = 1))]
+    fn callees<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        depth: usize,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.reachable_dicts(py, name, depth, true, false)
+    }
+
+    /// Graph metrics of one function: fan-in/out, max call depth,
+    /// transitive callees, betweenness and PageRank. Raises `KeyError` for
+    /// names that are not functions, e.g. type nodes
+    fn metrics<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyDict>> {
+        let symbol = self
+            .resolver
+            .resolve_one(name)
+            .map_err(|e| PyKeyError::new_err(e.to_string()))?
+            .symbol
+            .clone();
+        let all = py.allow_threads(|| {
+            self.metrics
+                .get_or_init(|| compute_graph_metrics(&self.graph))
+        });
+        // Type nodes are resolvable but have no graph metrics
+        let metrics = all
+            .get(&symbol)
+            .ok_or_else(|| PyKeyError::new_err(format!("no graph metrics for {symbol}")))?;
+        let dict = PyDict::new(py);
+        dict.set_item("symbol", symbol)?;
+        dict.set_item("fan_in", metrics.fan_in)?;
+        dict.set_item("fan_out", metrics.fan_out)?;
+        dict.set_item("max_depth", metrics.max_depth)?;
+        dict.set_item("transitive_callees", metrics.transitive_callees)?;
+        dict.set_item("betweenness", metrics.betweenness)?;
+        dict.set_item("pagerank", metrics.pagerank)?;
+        Ok(dict)
+    }
+
+    /// The graph in networkx node-link format, for
+    /// `networkx.node_link_graph` (pass `edges="links"` on networkx 3.4+).
+    /// Nodes are keyed by symbol; links are calls between functions of the
+    /// graph.
+    fn to_networkx_dicts<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let mut nodes: Vec<&FunctionNode> = self.graph.values().collect();
+        nodes.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        let mut node_dicts = Vec::with_capacity(nodes.len());
+        let mut links = Vec::new();
+        for node in nodes {
+            let dict = node_dict(py, node)?;
+            dict.set_item("id", node.symbol.as_str())?;
+            node_dicts.push(dict);
+
+            let mut callees: Vec<&String> = node
+                .callees
+                .iter()
+                .filter(|callee| self.graph.contains_key(*callee))
+                .collect();
+            callees.sort();
+            for callee in callees {
+                let link = PyDict::new(py);
+                link.set_item("source", node.symbol.as_str())?;
+                link.set_item("target", callee.as_str())?;
+                links.push(link);
+            }
+        }
+
+        let data = PyDict::new(py);
+        data.set_item("directed", true)?;
+        data.set_item("multigraph", false)?;
+        data.set_item("graph", PyDict::new(py))?;
+        data.set_item("nodes", node_dicts)?;
+        data.set_item("links", links)?;
+        Ok(data)
+    }
+}
+
+/// Parse a SCIP index: binary `.scip`, or SCIP JSON (`scip print --json`)
+#[pyfunction]
+fn load_scip(py: Python<'_>, path: &str) -> PyResult<ScipIndex> {
+    let index = py.allow_threads(|| {
+        let parsed = if path.ends_with(".scip") {
+            parse_scip_protobuf(path)
+        } else {
+            parse_scip_json(path)
+        };
+        parsed.map_err(|e| e.to_string())
+    });
+    index
+        .map(|index| ScipIndex { index })
+        .map_err(|e| load_error(path, e))
+}
+
+/// Load a call graph from atoms JSON, SCIP JSON or a binary `.scip` index
+#[pyfunction]
+fn load_call_graph(py: Python<'_>, path: &str) -> PyResult<CallGraph> {
+    let graph = py.allow_threads(|| scip_core::load_call_graph(path).map_err(|e| e.to_string()));
+    graph.map(CallGraph::new).map_err(|e| load_error(path, e))
+}
+
+#[pymodule]
+fn scip_callgraph(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<ScipIndex>()?;
+    m.add_class::<CallGraph>()?;
+    m.add_function(wrap_pyfunction!(load_scip, m)?)?;
+    m.add_function(wrap_pyfunction!(load_call_graph, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(symbol: &str, callees: &[&str]) -> FunctionNode {
+        FunctionNode {
+            symbol: symbol.to_string(),
+            display_name: symbol.to_string(),
+            file_path: "/project/src/lib.rs".to_string(),
+            relative_path: "src/lib.rs".to_string(),
+            callees: callees.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_call_graph_bindings() {
+        // main -> helper -> leaf, main -> leaf; helper calls an external function
+        let mut nodes: HashMap<String, FunctionNode> = [
+            node("main", &["helper", "leaf"]),
+            node("helper", &["leaf", "external_fn"]),
+            node("leaf", &[]),
+        ]
+        .into_iter()
+        .map(|node| (node.symbol.clone(), node))
+        .collect();
+        nodes
+            .get_mut("helper")
+            .unwrap()
+            .callers
+            .insert("main".into());
+        for caller in ["main", "helper"] {
+            nodes.get_mut("leaf").unwrap().callers.insert(caller.into());
+        }
+        let graph = CallGraph::new(nodes.into());
+
+        fn get<'py, T: FromPyObject<'py>>(dict: &Bound<'py, PyDict>, key: &str) -> T {
+            dict.get_item(key).unwrap().unwrap().extract().unwrap()
+        }
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let callers = graph.callers(py, "leaf", 1).unwrap();
+            let names: Vec<String> = callers.iter().map(|c| get(c, "symbol")).collect();
+            assert_eq!(names, vec!["helper", "main"]);
+
+            let callees = graph.callees(py, "main", 2).unwrap();
+            assert_eq!(callees.len(), 2);
+            assert_eq!(get::<usize>(&callees[0], "depth"), 1);
+            assert!(graph.callees(py, "missing", 1).is_err());
+
+            let metrics = graph.metrics(py, "leaf").unwrap();
+            assert_eq!(get::<usize>(&metrics, "fan_in"), 2);
+
+            // The call to the external function is not a link
+            let data = graph.to_networkx_dicts(py).unwrap();
+            assert_eq!(data.get_item("nodes").unwrap().unwrap().len().unwrap(), 3);
+            assert_eq!(data.get_item("links").unwrap().unwrap().len().unwrap(), 3);
+        });
+    }
+}
@@ -0,0 +1,672 @@
+//! Per-function call graph metrics.
+//!
+//! [`compute_graph_metrics`] measures where each function sits in the call
+//! graph: how many functions call it and it calls (fan-in, fan-out), the
+//! longest call chain below it, how many functions it reaches, and its
+//! betweenness and PageRank centrality. Lemmas with high fan-in and
+//! centrality are the ones whose refactoring pays off most.
+//!
+//! The same metrics can be added to atoms ([`add_graph_metrics_to_atoms`],
+//! computed over their `deps`) and to D3 graphs ([`add_graph_metrics_to_d3`],
+//! computed over node `dependencies`). Calls to functions outside the graph
+//! and self-calls are ignored, and so are type nodes and the references to
+//! them: they get no metrics and count toward no function's fan-out.
+//!
+//! [`dominators`] computes the dominator tree from chosen entry points: a
+//! function dominates another when every call chain from the entry points
+//! to it goes through it. [`gatekeepers`] lists the lemmas dominating the
+//! largest share of the reachable graph, the ones whose specs matter most.
+
+use crate::call_graph::detect_decl_kind;
+use crate::types::{Atom, CallGraph, D3Graph, DeclKind, FunctionNode, GraphMetrics, NodeKind};
+use petgraph::algo::{dominators::simple_fast, tarjan_scc};
+use petgraph::graph::{DiGraph, NodeIndex};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+
+/// PageRank damping factor
+const DAMPING: f64 = 0.85;
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-10;
+
+/// Call edges between functions numbered `0..n`
+struct Adjacency {
+    callees: Vec<Vec<usize>>,
+    callers: Vec<Vec<usize>>,
+}
+
+impl Adjacency {
+    /// Edges from each of `ids` to its callees, keeping only callees among
+    /// `ids` and dropping self-calls and duplicates
+    fn new<'a, C>(ids: &[&'a str], callees_of: impl Fn(usize) -> C) -> Self
+    where
+        C: IntoIterator<Item = &'a str>,
+    {
+        let index: HashMap<&str, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut callees: Vec<Vec<usize>> = vec![Vec::new(); ids.len()];
+        let mut callers: Vec<Vec<usize>> = vec![Vec::new(); ids.len()];
+        for (caller, targets) in callees.iter_mut().enumerate() {
+            targets.extend(
+                callees_of(caller)
+                    .into_iter()
+                    .filter_map(|callee| index.get(callee).copied())
+                    .filter(|&callee| callee != caller),
+            );
+            targets.sort_unstable();
+            targets.dedup();
+            for &callee in targets.iter() {
+                callers[callee].push(caller);
+            }
+        }
+        Self { callees, callers }
+    }
+
+    fn len(&self) -> usize {
+        self.callees.len()
+    }
+
+    fn metrics(&self) -> Vec<GraphMetrics> {
+        let depths = self.max_depths();
+        let betweenness = self.betweenness();
+        let pagerank = self.pagerank();
+        (0..self.len())
+            .map(|i| GraphMetrics {
+                fan_in: self.callers[i].len(),
+                fan_out: self.callees[i].len(),
+                max_depth: depths[i],
+                transitive_callees: self.reachable_count(i),
+                betweenness: betweenness[i],
+                pagerank: pagerank[i],
+            })
+            .collect()
+    }
+
+    /// Longest call chain below each function, over the graph with each
+    /// recursion cycle collapsed into a single node
+    fn max_depths(&self) -> Vec<usize> {
+        let mut graph: DiGraph<(), ()> = DiGraph::with_capacity(self.len(), 0);
+        for _ in 0..self.len() {
+            graph.add_node(());
+        }
+        for (caller, callees) in self.callees.iter().enumerate() {
+            for &callee in callees {
+                graph.add_edge(NodeIndex::new(caller), NodeIndex::new(callee), ());
+            }
+        }
+
+        // Tarjan yields components callees-first, so every call out of a
+        // component leads to one whose depth is already known
+        let components = tarjan_scc(&graph);
+        let mut component_of = vec![0; self.len()];
+        for (component, members) in components.iter().enumerate() {
+            for member in members {
+                component_of[member.index()] = component;
+            }
+        }
+        let mut component_depth = vec![0; components.len()];
+        for (component, members) in components.iter().enumerate() {
+            component_depth[component] = members
+                .iter()
+                .flat_map(|member| &self.callees[member.index()])
+                .filter(|&&callee| component_of[callee] != component)
+                .map(|&callee| component_depth[component_of[callee]] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        component_of
+            .into_iter()
+            .map(|component| component_depth[component])
+            .collect()
+    }
+
+    /// Number of other functions reachable from `start`
+    fn reachable_count(&self, start: usize) -> usize {
+        let mut seen = vec![false; self.len()];
+        seen[start] = true;
+        let mut queue = VecDeque::from([start]);
+        let mut count = 0;
+        while let Some(node) = queue.pop_front() {
+            for &callee in &self.callees[node] {
+                if !seen[callee] {
+                    seen[callee] = true;
+                    count += 1;
+                    queue.push_back(callee);
+                }
+            }
+        }
+        count
+    }
+
+    /// Normalized betweenness centrality (Brandes' algorithm)
+    fn betweenness(&self) -> Vec<f64> {
+        let n = self.len();
+        let mut centrality = vec![0.0; n];
+        for source in 0..n {
+            let mut order = Vec::new();
+            let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+            let mut paths = vec![0.0; n];
+            let mut distance: Vec<Option<usize>> = vec![None; n];
+            paths[source] = 1.0;
+            distance[source] = Some(0);
+
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                order.push(node);
+                let next = distance[node].map(|d| d + 1);
+                for &callee in &self.callees[node] {
+                    if distance[callee].is_none() {
+                        distance[callee] = next;
+                        queue.push_back(callee);
+                    }
+                    if distance[callee] == next {
+                        paths[callee] += paths[node];
+                        predecessors[callee].push(node);
+                    }
+                }
+            }
+
+            let mut dependency = vec![0.0; n];
+            while let Some(node) = order.pop() {
+                for &predecessor in &predecessors[node] {
+                    dependency[predecessor] +=
+                        paths[predecessor] / paths[node] * (1.0 + dependency[node]);
+                }
+                if node != source {
+                    centrality[node] += dependency[node];
+                }
+            }
+        }
+
+        if n > 2 {
+            let pairs = ((n - 1) * (n - 2)) as f64;
+            for value in &mut centrality {
+                *value /= pairs;
+            }
+        }
+        centrality
+    }
+
+    /// PageRank along call edges; functions calling nothing spread their
+    /// rank evenly over the graph
+    fn pagerank(&self) -> Vec<f64> {
+        let n = self.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let uniform = 1.0 / n as f64;
+        let mut rank = vec![uniform; n];
+        for _ in 0..PAGERANK_MAX_ITERATIONS {
+            let dangling: f64 = (0..n)
+                .filter(|&i| self.callees[i].is_empty())
+                .map(|i| rank[i])
+                .sum();
+            let base = (1.0 - DAMPING) * uniform + DAMPING * dangling * uniform;
+            let next: Vec<f64> = (0..n)
+                .map(|i| {
+                    base + DAMPING
+                        * self.callers[i]
+                            .iter()
+                            .map(|&caller| rank[caller] / self.callees[caller].len() as f64)
+                            .sum::<f64>()
+                })
+                .collect();
+            let change: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+            rank = next;
+            if change < PAGERANK_TOLERANCE {
+                break;
+            }
+        }
+        rank
+    }
+}
+
+/// Function nodes of the call graph: type nodes (`CallGraphOptions::with_types`)
+/// are referenced rather than called, so they take no part in the metrics
+fn function_nodes(call_graph: &CallGraph) -> Vec<&FunctionNode> {
+    call_graph.values().filter(|node| !node.is_type()).collect()
+}
+
+/// Graph metrics of every function in the call graph, keyed by symbol
+pub fn compute_graph_metrics(call_graph: &CallGraph) -> HashMap<String, GraphMetrics> {
+    let nodes = function_nodes(call_graph);
+    let ids: Vec<&str> = nodes.iter().map(|node| node.symbol.as_str()).collect();
+    let adjacency = Adjacency::new(&ids, |i| nodes[i].callees.iter().map(String::as_str));
+    ids.into_iter()
+        .map(String::from)
+        .zip(adjacency.metrics())
+        .collect()
+}
+
+/// PageRank of every function, without the costlier metrics of
+/// [`compute_graph_metrics`]
+pub(crate) fn pagerank(call_graph: &CallGraph) -> HashMap<String, f64> {
+    let nodes = function_nodes(call_graph);
+    let ids: Vec<&str> = nodes.iter().map(|node| node.symbol.as_str()).collect();
+    let adjacency = Adjacency::new(&ids, |i| nodes[i].callees.iter().map(String::as_str));
+    ids.into_iter()
+        .map(String::from)
+        .zip(adjacency.pagerank())
+        .collect()
+}
+
+/// Set the graph metrics of every function atom, computed over the atoms'
+/// `deps`
+pub fn add_graph_metrics_to_atoms(atoms: &mut [Atom]) {
+    let functions: Vec<usize> = (0..atoms.len())
+        .filter(|&i| atoms[i].statement_type != NodeKind::Type.as_str())
+        .collect();
+    let ids: Vec<&str> = functions
+        .iter()
+        .map(|&i| atoms[i].identifier.as_str())
+        .collect();
+    let metrics = Adjacency::new(&ids, |i| {
+        atoms[functions[i]].deps.iter().map(String::as_str)
+    })
+    .metrics();
+    for (i, metrics) in functions.into_iter().zip(metrics) {
+        atoms[i].graph_metrics = Some(metrics);
+    }
+}
+
+/// Set the graph metrics of every function node, computed over the nodes'
+/// `dependencies`
+pub fn add_graph_metrics_to_d3(graph: &mut D3Graph) {
+    let functions: Vec<usize> = (0..graph.nodes.len())
+        .filter(|&i| graph.nodes[i].node_kind != NodeKind::Type)
+        .collect();
+    let ids: Vec<&str> = functions
+        .iter()
+        .map(|&i| graph.nodes[i].id.as_str())
+        .collect();
+    let metrics = Adjacency::new(&ids, |i| {
+        graph.nodes[functions[i]]
+            .dependencies
+            .iter()
+            .map(String::as_str)
+    })
+    .metrics();
+    for (i, metrics) in functions.into_iter().zip(metrics) {
+        graph.nodes[i].graph_metrics = Some(metrics);
+    }
+}
+
+/// Dominator tree of the functions reachable from a set of entry points
+#[derive(Debug, Clone, Default)]
+pub struct DominatorTree {
+    /// Entry points, sorted by symbol
+    pub roots: Vec<String>,
+    /// Immediate dominator of every reachable function; `None` for the
+    /// entry points and for functions only dominated by several of them
+    idom: HashMap<String, Option<String>>,
+    /// Functions dominated by each function, itself excluded
+    dominated: HashMap<String, usize>,
+}
+
+impl DominatorTree {
+    /// Number of functions reachable from the entry points, entry points
+    /// included
+    pub fn reachable(&self) -> usize {
+        self.idom.len()
+    }
+
+    /// Whether `symbol` is reachable from the entry points
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.idom.contains_key(symbol)
+    }
+
+    /// The closest function every call chain to `symbol` goes through
+    pub fn immediate_dominator(&self, symbol: &str) -> Option<&str> {
+        self.idom.get(symbol)?.as_deref()
+    }
+
+    /// Dominators of `symbol`, closest first
+    pub fn dominators_of(&self, symbol: &str) -> Vec<&str> {
+        let mut chain = Vec::new();
+        let mut current = self.immediate_dominator(symbol);
+        while let Some(dominator) = current {
+            chain.push(dominator);
+            current = self.immediate_dominator(dominator);
+        }
+        chain
+    }
+
+    /// Number of functions `symbol` dominates, itself excluded
+    pub fn dominated_count(&self, symbol: &str) -> usize {
+        self.dominated.get(symbol).copied().unwrap_or_default()
+    }
+}
+
+/// Dominator tree of the call graph from `roots` (symbols or display
+/// names); without roots, from every project function nothing calls
+pub fn dominators(call_graph: &CallGraph, roots: &[String]) -> DominatorTree {
+    let mut nodes = function_nodes(call_graph);
+    nodes.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    let ids: Vec<&str> = nodes.iter().map(|node| node.symbol.as_str()).collect();
+    let adjacency = Adjacency::new(&ids, |i| nodes[i].callees.iter().map(String::as_str));
+
+    let root_indices: Vec<usize> = (0..nodes.len())
+        .filter(|&i| {
+            let node = nodes[i];
+            if roots.is_empty() {
+                !node.file_path.starts_with("external:") && adjacency.callers[i].is_empty()
+            } else {
+                roots
+                    .iter()
+                    .any(|root| *root == node.symbol || *root == node.display_name)
+            }
+        })
+        .collect();
+
+    // A virtual entry (the last node) calls every root, so that several
+    // entry points share one tree
+    let entry = nodes.len();
+    let mut graph: DiGraph<(), ()> = DiGraph::with_capacity(entry + 1, 0);
+    for _ in 0..=entry {
+        graph.add_node(());
+    }
+    for (caller, callees) in adjacency.callees.iter().enumerate() {
+        for &callee in callees {
+            graph.add_edge(NodeIndex::new(caller), NodeIndex::new(callee), ());
+        }
+    }
+    for &root in &root_indices {
+        graph.add_edge(NodeIndex::new(entry), NodeIndex::new(root), ());
+    }
+    let tree = simple_fast(&graph, NodeIndex::new(entry));
+
+    let mut idom: HashMap<String, Option<String>> = HashMap::new();
+    let mut parent: Vec<Option<usize>> = vec![None; entry];
+    for i in 0..entry {
+        let Some(dominator) = tree.immediate_dominator(NodeIndex::new(i)) else {
+            continue;
+        };
+        let dominator = (dominator.index() != entry).then_some(dominator.index());
+        parent[i] = dominator;
+        idom.insert(ids[i].to_string(), dominator.map(|d| ids[d].to_string()));
+    }
+
+    // Every reachable function counts once for each of its dominators
+    let mut dominated = vec![0; entry];
+    for i in 0..entry {
+        let mut current = parent[i];
+        while let Some(dominator) = current {
+            dominated[dominator] += 1;
+            current = parent[dominator];
+        }
+    }
+
+    DominatorTree {
+        roots: root_indices.iter().map(|&i| ids[i].to_string()).collect(),
+        dominated: (0..entry)
+            .filter(|i| idom.contains_key(ids[*i]))
+            .map(|i| (ids[i].to_string(), dominated[i]))
+            .collect(),
+        idom,
+    }
+}
+
+/// A function that a large share of the reachable graph depends on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Gatekeeper {
+    pub symbol: String,
+    pub display_name: String,
+    pub relative_path: String,
+    /// Functions only reachable through this one
+    pub dominated: usize,
+    /// `dominated` over the functions reachable from the entry points
+    pub share: f64,
+    /// Immediate dominator, `None` below an entry point
+    pub immediate_dominator: Option<String>,
+}
+
+/// Result of [`gatekeepers`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GatekeeperReport {
+    pub roots: Vec<String>,
+    pub reachable: usize,
+    /// Sorted by dominated count (highest first), then symbol
+    pub gatekeepers: Vec<Gatekeeper>,
+}
+
+/// Proof functions dominating at least `min_share` of the functions
+/// reachable from the tree's entry points (all modes with `proof_only`
+/// false); entry points are left out
+pub fn gatekeepers(
+    call_graph: &CallGraph,
+    tree: &DominatorTree,
+    min_share: f64,
+    proof_only: bool,
+) -> GatekeeperReport {
+    let reachable = tree.reachable();
+    let mut gatekeepers: Vec<Gatekeeper> = call_graph
+        .values()
+        .filter(|node| tree.contains(&node.symbol) && !tree.roots.contains(&node.symbol))
+        .filter(|node| {
+            !proof_only
+                || detect_decl_kind(node.body.as_deref().unwrap_or_default()) == DeclKind::Proof
+        })
+        .filter_map(|node| {
+            let dominated = tree.dominated_count(&node.symbol);
+            let share = dominated as f64 / reachable.max(1) as f64;
+            (dominated > 0 && share >= min_share).then(|| Gatekeeper {
+                symbol: node.symbol.clone(),
+                display_name: node.display_name.clone(),
+                relative_path: node.relative_path.clone(),
+                dominated,
+                share,
+                immediate_dominator: tree.immediate_dominator(&node.symbol).map(str::to_string),
+            })
+        })
+        .collect();
+    gatekeepers.sort_by(|a, b| {
+        b.dominated
+            .cmp(&a.dominated)
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    GatekeeperReport {
+        roots: tree.roots.clone(),
+        reachable,
+        gatekeepers,
+    }
+}
+
+impl GatekeeperReport {
+    /// Render the report as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Gatekeeper lemmas\n\n");
+        let _ = writeln!(out, "| | Count |\n|---|---:|");
+        let _ = writeln!(out, "| Entry points | {} |", self.roots.len());
+        let _ = writeln!(out, "| Reachable functions | {} |", self.reachable);
+        let _ = writeln!(out, "| Gatekeepers | {} |", self.gatekeepers.len());
+
+        if self.gatekeepers.is_empty() {
+            out.push_str("\nNo gatekeepers found.\n");
+            return out;
+        }
+
+        out.push_str("\n## Gatekeepers\n\n");
+        out.push_str("| Function | Location | Dominated | Share | Immediate dominator |\n");
+        out.push_str("|---|---|---:|---:|---|\n");
+        for gatekeeper in &self.gatekeepers {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {:.1}% | {} |",
+                gatekeeper.display_name,
+                gatekeeper.relative_path,
+                gatekeeper.dominated,
+                gatekeeper.share * 100.0,
+                gatekeeper
+                    .immediate_dominator
+                    .as_deref()
+                    .map_or("-".to_string(), |d| format!("`{}`", d))
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::node;
+    use crate::types::{D3GraphMetadata, D3Node};
+
+    /// main -> {left, right} -> lemma <-> helper (a recursion cycle),
+    /// plus a call to an external function
+    fn diamond() -> CallGraph {
+        [
+            node("main", &["left", "right", "external"]),
+            node("left", &["lemma"]),
+            node("right", &["lemma"]),
+            node("lemma", &["helper", "lemma"]),
+            node("helper", &["lemma"]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_compute_graph_metrics() {
+        let metrics = compute_graph_metrics(&diamond());
+
+        let main = &metrics["main"];
+        assert_eq!((main.fan_in, main.fan_out), (0, 2));
+        assert_eq!(main.max_depth, 2);
+        assert_eq!(main.transitive_callees, 4);
+        assert_eq!(main.betweenness, 0.0);
+
+        let lemma = &metrics["lemma"];
+        assert_eq!((lemma.fan_in, lemma.fan_out), (3, 1));
+        assert_eq!(lemma.max_depth, 0);
+        assert_eq!(lemma.transitive_callees, 1);
+        assert_eq!(metrics["left"].max_depth, 1);
+
+        // lemma sits on every path into helper, left on half of main's
+        assert!(lemma.betweenness > metrics["left"].betweenness);
+        assert!(metrics["left"].betweenness > 0.0);
+
+        let total: f64 = metrics.values().map(|m| m.pagerank).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert!(lemma.pagerank > metrics["left"].pagerank);
+        assert!(metrics["left"].pagerank > main.pagerank);
+    }
+
+    #[test]
+    fn test_add_graph_metrics_to_atoms_and_d3() {
+        let call_graph = diamond();
+        let expected = compute_graph_metrics(&call_graph);
+
+        let mut atoms = crate::export_d3::call_graph_to_atoms(&call_graph);
+        add_graph_metrics_to_atoms(&mut atoms);
+        let main = atoms
+            .iter()
+            .find(|atom| atom.display_name == "main")
+            .unwrap();
+        assert_eq!(main.graph_metrics.as_ref(), Some(&expected["main"]));
+
+        let json = serde_json::to_value(main).unwrap();
+        assert_eq!(json["fan_out"], 2);
+        assert_eq!(json["max_depth"], 2);
+        let parsed: Atom = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.graph_metrics, main.graph_metrics);
+
+        let mut graph = D3Graph {
+            nodes: call_graph
+                .values()
+                .map(|node| D3Node {
+                    id: node.symbol.clone(),
+                    dependencies: node.callees.iter().cloned().collect(),
+                    ..Default::default()
+                })
+                .collect(),
+            links: Vec::new(),
+            metadata: D3GraphMetadata::default(),
+        };
+        add_graph_metrics_to_d3(&mut graph);
+        for node in &graph.nodes {
+            assert_eq!(node.graph_metrics.as_ref(), Some(&expected[&node.id]));
+        }
+    }
+
+    #[test]
+    fn test_type_nodes_take_no_part_in_metrics() {
+        // A constant calling back into main would close a cycle, and the
+        // struct would add to both functions' fan-out
+        let call_graph: CallGraph = [
+            node("main", &["helper", "Point", "LIMIT"]),
+            node("helper", &["Point"]),
+            node("Point", &[]).as_type(),
+            node("LIMIT", &["main"]).as_type(),
+        ]
+        .into_iter()
+        .collect();
+
+        let metrics = compute_graph_metrics(&call_graph);
+        assert_eq!(metrics.len(), 2);
+        assert_eq!((metrics["main"].fan_in, metrics["main"].fan_out), (0, 1));
+        assert_eq!(metrics["helper"].fan_out, 0);
+        assert_eq!(pagerank(&call_graph).len(), 2);
+        assert!(!dominators(&call_graph, &[]).contains("Point"));
+
+        let mut atoms = crate::export_d3::call_graph_to_atoms(&call_graph);
+        add_graph_metrics_to_atoms(&mut atoms);
+        for atom in &atoms {
+            let is_function = atom.statement_type == "function";
+            assert_eq!(
+                atom.graph_metrics.is_some(),
+                is_function,
+                "{}",
+                atom.identifier
+            );
+        }
+    }
+
+    #[test]
+    fn test_dominators() {
+        // The diamond with proof bodies: lemma dominates helper, main
+        // everything
+        let call_graph: CallGraph = [
+            node("main", &["left", "right", "external"]),
+            node("left", &["lemma"]),
+            node("right", &["lemma"]),
+            node("lemma", &["helper", "lemma"]),
+            node("helper", &["lemma"]),
+        ]
+        .into_iter()
+        .map(|mut node| {
+            node.body = Some(format!("proof fn {}() {{}}", node.symbol));
+            node
+        })
+        .collect();
+
+        let tree = dominators(&call_graph, &[]);
+        assert_eq!(tree.roots, vec!["main"]);
+        assert_eq!(tree.reachable(), 5);
+        assert_eq!(tree.immediate_dominator("main"), None);
+        assert_eq!(tree.immediate_dominator("left"), Some("main"));
+        assert_eq!(tree.immediate_dominator("lemma"), Some("main"));
+        assert_eq!(tree.dominators_of("helper"), vec!["lemma", "main"]);
+        assert_eq!(tree.dominated_count("main"), 4);
+        assert_eq!(tree.dominated_count("lemma"), 1);
+        assert_eq!(tree.dominated_count("left"), 0);
+
+        let report = gatekeepers(&call_graph, &tree, 0.1, true);
+        let names: Vec<&str> = report
+            .gatekeepers
+            .iter()
+            .map(|g| g.symbol.as_str())
+            .collect();
+        assert_eq!(names, vec!["lemma"]);
+        assert_eq!(report.gatekeepers[0].share, 0.2);
+        assert!(report
+            .to_markdown()
+            .contains("| `lemma` | src/lib.rs | 1 | 20.0% | `main` |"));
+
+        // From two entry points, neither dominates the shared lemma
+        let tree = dominators(&call_graph, &["left".to_string(), "right".to_string()]);
+        assert_eq!(tree.reachable(), 4);
+        assert_eq!(tree.immediate_dominator("lemma"), None);
+        assert_eq!(tree.dominated_count("lemma"), 1);
+    }
+}
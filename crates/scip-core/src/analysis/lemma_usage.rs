@@ -0,0 +1,308 @@
+//! Calls from the project into external crates, and vstd lemma usage.
+//!
+//! External callees (placeholder nodes for functions defined outside the
+//! project) are grouped by crate (`vstd`, `core`, `std`, ...) from their SCIP
+//! symbol package. Calls into `vstd` are also counted per function and per
+//! calling module, giving a ranking of the most used vstd lemmas in each
+//! module.
+//!
+//! A use is a call site: calls in `requires`/`ensures` clauses are spec
+//! references, not uses. Modules come from the source path
+//! (`src/field/mod.rs` -> `field`).
+
+use super::crate_name;
+use crate::error::Result;
+use crate::types::{CallGraph, CallLocation, FunctionNode};
+use crate::verification::module_path;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Crate whose functions are ranked per module
+pub const VSTD_CRATE: &str = "vstd";
+
+/// Uses of one external function
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExternalFunctionUsage {
+    pub symbol: String,
+    pub display_name: String,
+    pub crate_name: String,
+    /// Number of call sites
+    pub uses: usize,
+    /// Number of distinct calling functions
+    pub callers: usize,
+}
+
+/// Uses of the functions of one external crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExternalCrateUsage {
+    pub crate_name: String,
+    /// Number of distinct functions called
+    pub functions: usize,
+    /// Number of call sites
+    pub uses: usize,
+}
+
+/// vstd functions called from one project module, most used first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleLemmaUsage {
+    pub crate_name: String,
+    /// Module path within the crate (`""` for the crate root)
+    pub module: String,
+    /// Number of vstd call sites in the module
+    pub uses: usize,
+    pub lemmas: Vec<ExternalFunctionUsage>,
+}
+
+/// Result of [`lemma_usage`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LemmaUsageReport {
+    /// Sorted by uses (highest first), then crate name
+    pub crates: Vec<ExternalCrateUsage>,
+    /// vstd functions over the whole project, most used first
+    pub lemmas: Vec<ExternalFunctionUsage>,
+    /// Sorted by crate name, then module
+    pub modules: Vec<ModuleLemmaUsage>,
+}
+
+/// One row of the CSV report
+#[derive(Serialize)]
+struct UsageRow<'a> {
+    crate_name: &'a str,
+    module: &'a str,
+    rank: usize,
+    lemma: &'a str,
+    symbol: &'a str,
+    uses: usize,
+    callers: usize,
+}
+
+/// Call sites of `callee` in `node`; at least one, since graphs built from
+/// atoms have no occurrences
+fn call_sites(node: &FunctionNode, callee: &str) -> usize {
+    let sites = node
+        .callee_occurrences
+        .iter()
+        .filter(|occurrence| occurrence.symbol == callee)
+        .filter(|occurrence| {
+            !matches!(
+                occurrence.location,
+                Some(CallLocation::Precondition | CallLocation::Postcondition)
+            )
+        })
+        .count();
+    sites.max(1)
+}
+
+/// Uses and callers of each external function, from a set of calls
+#[derive(Default)]
+struct Tally {
+    uses: HashMap<String, usize>,
+    callers: HashMap<String, HashSet<String>>,
+}
+
+impl Tally {
+    fn add(&mut self, callee: &str, caller: &str, uses: usize) {
+        *self.uses.entry(callee.to_string()).or_default() += uses;
+        self.callers
+            .entry(callee.to_string())
+            .or_default()
+            .insert(caller.to_string());
+    }
+
+    /// Ranked usage of the tallied functions
+    fn ranked(&self, call_graph: &CallGraph) -> Vec<ExternalFunctionUsage> {
+        let mut usage: Vec<ExternalFunctionUsage> = self
+            .uses
+            .iter()
+            .map(|(symbol, &uses)| {
+                let node = &call_graph[symbol];
+                ExternalFunctionUsage {
+                    symbol: symbol.clone(),
+                    display_name: node.display_name.clone(),
+                    crate_name: crate_name(node),
+                    uses,
+                    callers: self.callers[symbol].len(),
+                }
+            })
+            .collect();
+        usage.sort_by(|a, b| {
+            b.uses
+                .cmp(&a.uses)
+                .then_with(|| a.display_name.cmp(&b.display_name))
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+        usage
+    }
+}
+
+/// Count the calls from project functions into external crates
+pub fn lemma_usage(call_graph: &CallGraph) -> LemmaUsageReport {
+    let mut crates: BTreeMap<String, (HashSet<&str>, usize)> = BTreeMap::new();
+    let mut vstd = Tally::default();
+    let mut modules: BTreeMap<(String, String), Tally> = BTreeMap::new();
+
+    for node in call_graph.values() {
+        if node.file_path.starts_with("external:") {
+            continue;
+        }
+        let module_key = (crate_name(node), module_path(&node.relative_path));
+
+        for callee in node.calls() {
+            let Some(callee_node) = call_graph.get(callee) else {
+                continue;
+            };
+            if !callee_node.file_path.starts_with("external:") {
+                continue;
+            }
+            let uses = call_sites(node, callee);
+            let callee_crate = crate_name(callee_node);
+
+            let entry = crates.entry(callee_crate.clone()).or_default();
+            entry.0.insert(callee.as_str());
+            entry.1 += uses;
+
+            if callee_crate == VSTD_CRATE {
+                vstd.add(callee, &node.symbol, uses);
+                modules
+                    .entry(module_key.clone())
+                    .or_default()
+                    .add(callee, &node.symbol, uses);
+            }
+        }
+    }
+
+    let mut crates: Vec<ExternalCrateUsage> = crates
+        .into_iter()
+        .map(|(crate_name, (functions, uses))| ExternalCrateUsage {
+            crate_name,
+            functions: functions.len(),
+            uses,
+        })
+        .collect();
+    crates.sort_by(|a, b| {
+        b.uses
+            .cmp(&a.uses)
+            .then_with(|| a.crate_name.cmp(&b.crate_name))
+    });
+
+    LemmaUsageReport {
+        crates,
+        lemmas: vstd.ranked(call_graph),
+        modules: modules
+            .into_iter()
+            .map(|((crate_name, module), tally)| ModuleLemmaUsage {
+                crate_name,
+                module,
+                uses: tally.uses.values().sum(),
+                lemmas: tally.ranked(call_graph),
+            })
+            .collect(),
+    }
+}
+
+impl LemmaUsageReport {
+    /// Write the per-module rankings as CSV, one row per module and lemma
+    /// (`crate_name,module,rank,lemma,symbol,uses,callers`), keeping at
+    /// most `top` lemmas per module
+    pub fn write_csv(&self, path: &Path, top: usize) -> Result<()> {
+        let mut writer = csv::Writer::from_path(path)?;
+        for module in &self.modules {
+            for (i, lemma) in module.lemmas.iter().take(top).enumerate() {
+                writer.serialize(UsageRow {
+                    crate_name: &module.crate_name,
+                    module: &module.module,
+                    rank: i + 1,
+                    lemma: &lemma.display_name,
+                    symbol: &lemma.symbol,
+                    uses: lemma.uses,
+                    callers: lemma.callers,
+                })?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::types::CalleeOccurrence;
+
+    fn node(symbol: &str, path: &str, callees: &[&str]) -> FunctionNode {
+        let name = symbol.rsplit('/').next().unwrap().trim_end_matches("().");
+        test_support::node(symbol, callees)
+            .with_display_name(name)
+            .in_file(path)
+    }
+
+    #[test]
+    fn test_lemma_usage() {
+        let mul = "rust-analyzer cargo vstd 0.1.0 arithmetic/lemma_mul().";
+        let pow = "rust-analyzer cargo vstd 0.1.0 arithmetic/lemma_pow().";
+        let len = "rust-analyzer cargo core 1.0.0 slice/len().";
+        let add = "rust-analyzer cargo my-crate 0.1.0 field/add().";
+        let neg = "rust-analyzer cargo my-crate 0.1.0 field/neg().";
+        let open = "rust-analyzer cargo my-crate 0.1.0 scalar/open().";
+
+        let mut add_node = node(add, "src/field/mod.rs", &[mul, len, neg]);
+        add_node.callee_occurrences = [(mul, None), (mul, None), (len, None)]
+            .into_iter()
+            .chain([(pow, Some(CallLocation::Postcondition))])
+            .map(|(symbol, location)| CalleeOccurrence {
+                symbol: symbol.to_string(),
+                line: 0,
+                column: None,
+                location,
+                edge_kind: None,
+            })
+            .collect();
+        add_node.callees.insert(pow.to_string());
+        add_node.spec_refs.insert(pow.to_string());
+
+        let call_graph: CallGraph = [
+            add_node,
+            node(neg, "src/field/mod.rs", &[mul]),
+            node(open, "src/scalar.rs", &[pow]),
+            node(mul, "", &[]),
+            node(pow, "", &[]),
+            node(len, "", &[]),
+        ]
+        .into_iter()
+        .collect();
+
+        let report = lemma_usage(&call_graph);
+
+        assert_eq!(
+            report.crates,
+            vec![
+                ExternalCrateUsage {
+                    crate_name: "vstd".to_string(),
+                    functions: 2,
+                    uses: 4,
+                },
+                ExternalCrateUsage {
+                    crate_name: "core".to_string(),
+                    functions: 1,
+                    uses: 1,
+                },
+            ]
+        );
+
+        let ranking: Vec<(&str, usize, usize)> = report
+            .lemmas
+            .iter()
+            .map(|l| (l.display_name.as_str(), l.uses, l.callers))
+            .collect();
+        assert_eq!(ranking, vec![("lemma_mul", 3, 2), ("lemma_pow", 1, 1)]);
+
+        let modules: Vec<(&str, usize, usize)> = report
+            .modules
+            .iter()
+            .map(|m| (m.module.as_str(), m.uses, m.lemmas.len()))
+            .collect();
+        assert_eq!(modules, vec![("field", 3, 1), ("scalar", 1, 1)]);
+    }
+}
@@ -0,0 +1,64 @@
+//! Whole-graph analyses built on the call graph
+//!
+//! - [`graph_metrics`]: Fan-in/fan-out, call depth and centrality per function, dominator tree
+//!   and gatekeeper lemmas
+//! - [`lemma_usage`]: Calls into external crates, and the most used vstd lemmas per module
+//! - [`reachability`]: Dead-code detection from entry points
+//! - [`spec_coverage`]: Share of exec functions with `requires`/`ensures`
+//! - [`trust`]: Assumptions, axioms and external code a proof relies on
+
+pub mod graph_metrics;
+pub mod lemma_usage;
+pub mod reachability;
+pub mod spec_coverage;
+pub mod trust;
+
+use crate::symbol_scheme::parse_symbol;
+use crate::types::FunctionNode;
+
+/// Crate of a function: the SCIP symbol package, else the directory
+/// holding `src/`
+pub(crate) fn crate_name(node: &FunctionNode) -> String {
+    if let Some(parsed) = parse_symbol(&node.symbol) {
+        if !parsed.package.is_empty() && parsed.package != "." {
+            return parsed.package;
+        }
+    }
+    node.relative_path
+        .rsplit_once("src/")
+        .and_then(|(dir, _)| dir.trim_end_matches('/').rsplit('/').next())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// First signature line of a function body, skipping blank lines, comments
+/// and any attribute lines (attributes belong in
+/// [`FunctionNode::attributes`])
+pub(crate) fn signature_line(body: &str) -> &str {
+    body.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with("#["))
+        .unwrap_or_default()
+}
+
+/// Whether a signature line declares a `pub` item visible outside its crate
+pub(crate) fn is_public_signature(signature: &str) -> bool {
+    let restricted = ["pub(crate)", "pub(super)", "pub(self)", "pub(in "];
+    signature.starts_with("pub") && !restricted.iter().any(|r| signature.starts_with(r))
+}
+
+/// Whether attribute lines mark a test function (`#[test]`,
+/// `#[tokio::test]`, `#[rstest]` or `#[cfg(test)]`)
+pub(crate) fn is_test_attribute(attributes: &[&str]) -> bool {
+    attributes.iter().any(|attribute| {
+        let inner: String = attribute
+            .trim()
+            .trim_start_matches("#[")
+            .trim_end_matches(']')
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let name = inner.split('(').next().unwrap_or_default();
+        name == "test" || name.ends_with("::test") || name == "rstest" || inner == "cfg(test)"
+    })
+}
@@ -0,0 +1,555 @@
+//! Dead-code and unreachable-function detection.
+//!
+//! Starting from the entry points of a project (`main`, `#[test]` functions
+//! and, by default, `pub` functions), walk the call graph and report every
+//! project function that is never reached:
+//!
+//! - unreachable exec functions
+//! - unreachable proof functions (lemmas no proof depends on)
+//! - orphaned spec functions (specs no signature or proof refers to)
+//!
+//! Calls from `requires`/`ensures` clauses are call graph edges as well, so a
+//! spec fn used only in a reachable contract counts as reachable.
+//!
+//! Entry points are detected from attributes and signatures, so trait
+//! methods called through dynamic dispatch and functions used only from
+//! macros may show up as false positives; findings carry a [`Severity`] to
+//! help triage.
+
+use super::{is_public_signature, signature_line};
+use crate::call_graph::detect_decl_kind;
+use crate::node_filter::is_test_function;
+use crate::types::{CallGraph, DeclKind, FunctionNode};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write as _;
+
+/// Why a function is treated as an entry point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryPointKind {
+    Main,
+    Test,
+    Public,
+    /// Named explicitly via [`ReachabilityOptions::with_entry_points`]
+    Explicit,
+}
+
+/// What kind of dead code a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadCodeCategory {
+    UnreachableExec,
+    UnreachableProof,
+    OrphanedSpec,
+}
+
+impl DeadCodeCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadCodeCategory::UnreachableExec => "unreachable_exec",
+            DeadCodeCategory::UnreachableProof => "unreachable_proof",
+            DeadCodeCategory::OrphanedSpec => "orphaned_spec",
+        }
+    }
+}
+
+/// How likely a finding is to be removable dead code.
+///
+/// - `High`: exec function with no callers at all
+/// - `Medium`: exec function only called from other dead code, or proof
+///   function with no callers at all
+/// - `Low`: proof function only called from dead code, and orphaned specs
+///   (specs are often kept for documentation or external clients)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        }
+    }
+}
+
+/// Options for [`find_dead_code`]
+#[derive(Debug, Clone)]
+pub struct ReachabilityOptions {
+    /// Treat `pub` functions as entry points (true for libraries)
+    pub public_entry_points: bool,
+    /// Additional entry points, by symbol or display name
+    pub entry_points: Vec<String>,
+}
+
+impl Default for ReachabilityOptions {
+    fn default() -> Self {
+        Self {
+            public_entry_points: true,
+            entry_points: Vec::new(),
+        }
+    }
+}
+
+impl ReachabilityOptions {
+    pub fn with_public_entry_points(mut self, enabled: bool) -> Self {
+        self.public_entry_points = enabled;
+        self
+    }
+
+    pub fn with_entry_points(mut self, entry_points: impl IntoIterator<Item = String>) -> Self {
+        self.entry_points.extend(entry_points);
+        self
+    }
+}
+
+/// A function that is never reached from an entry point
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeadCodeFinding {
+    pub symbol: String,
+    pub display_name: String,
+    pub relative_path: String,
+    /// 1-based first line of the definition
+    pub line: Option<u32>,
+    pub category: DeadCodeCategory,
+    pub severity: Severity,
+    /// Number of (unreachable) callers
+    pub callers: usize,
+}
+
+/// Counts of a [`DeadCodeReport`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DeadCodeSummary {
+    /// Project functions analyzed (external stubs are left out)
+    pub functions: usize,
+    pub entry_points: usize,
+    pub reachable: usize,
+    pub unreachable_exec: usize,
+    pub unreachable_proof: usize,
+    pub orphaned_specs: usize,
+}
+
+/// Result of [`find_dead_code`]
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadCodeReport {
+    pub summary: DeadCodeSummary,
+    /// Entry points with the reason they were chosen, sorted by symbol
+    pub entry_points: Vec<(String, EntryPointKind)>,
+    /// Sorted by severity (highest first), then path and line
+    pub findings: Vec<DeadCodeFinding>,
+}
+
+/// Classify a function as an entry point from its attributes, signature
+/// and name
+pub fn entry_point_kind(node: &FunctionNode) -> Option<EntryPointKind> {
+    if is_test_function(node) {
+        return Some(EntryPointKind::Test);
+    }
+    let signature = signature_line(node.body.as_deref().unwrap_or_default());
+    if node.display_name == "main" && signature.contains("fn main(") {
+        return Some(EntryPointKind::Main);
+    }
+    if is_public_signature(signature) {
+        return Some(EntryPointKind::Public);
+    }
+    None
+}
+
+/// A named set of entry points, for callers that don't know symbols offhand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryProfile {
+    /// `fn main()`
+    Main,
+    /// `pub` exec functions
+    PubApi,
+    /// `#[test]` functions
+    Tests,
+    /// Proof functions no other proof function calls
+    ProofRoots,
+}
+
+impl EntryProfile {
+    pub const ALL: [EntryProfile; 4] = [
+        EntryProfile::Main,
+        EntryProfile::PubApi,
+        EntryProfile::Tests,
+        EntryProfile::ProofRoots,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryProfile::Main => "main",
+            EntryProfile::PubApi => "pub-api",
+            EntryProfile::Tests => "tests",
+            EntryProfile::ProofRoots => "proof-roots",
+        }
+    }
+
+    /// Whether a project function is an entry point of this profile
+    pub fn matches(&self, call_graph: &CallGraph, node: &FunctionNode) -> bool {
+        let decl_kind = |node: &FunctionNode| node.body.as_deref().map(detect_decl_kind);
+        match self {
+            EntryProfile::Main => entry_point_kind(node) == Some(EntryPointKind::Main),
+            EntryProfile::PubApi => {
+                entry_point_kind(node) == Some(EntryPointKind::Public)
+                    && decl_kind(node) == Some(DeclKind::Exec)
+            }
+            EntryProfile::Tests => entry_point_kind(node) == Some(EntryPointKind::Test),
+            EntryProfile::ProofRoots => {
+                decl_kind(node) == Some(DeclKind::Proof)
+                    && !node.callers.iter().any(|caller| {
+                        caller != &node.symbol
+                            && call_graph
+                                .get(caller)
+                                .is_some_and(|caller| decl_kind(caller) == Some(DeclKind::Proof))
+                    })
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for EntryProfile {
+    type Err = String;
+
+    /// Parse `main`, `pub-api`, `tests` or `proof-roots` (`_` works too)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase().replace('_', "-");
+        EntryProfile::ALL
+            .into_iter()
+            .find(|profile| profile.as_str() == normalized)
+            .ok_or_else(|| {
+                let known: Vec<&str> = EntryProfile::ALL.iter().map(|p| p.as_str()).collect();
+                format!("unknown entry profile: {s} (expected {})", known.join(", "))
+            })
+    }
+}
+
+/// Symbols of the project functions matching any of the profiles, sorted
+pub fn resolve_entry_profiles(call_graph: &CallGraph, profiles: &[EntryProfile]) -> Vec<String> {
+    let mut symbols: Vec<String> = call_graph
+        .values()
+        .filter(|node| !node.file_path.starts_with("external:"))
+        .filter(|node| profiles.iter().any(|p| p.matches(call_graph, node)))
+        .map(|node| node.symbol.clone())
+        .collect();
+    symbols.sort();
+    symbols
+}
+
+/// Find unreachable exec/proof functions and orphaned specs
+pub fn find_dead_code(call_graph: &CallGraph, options: &ReachabilityOptions) -> DeadCodeReport {
+    let is_project = |node: &FunctionNode| !node.file_path.starts_with("external:");
+
+    let mut entry_points: Vec<(String, EntryPointKind)> = Vec::new();
+    for node in call_graph.values().filter(|node| is_project(node)) {
+        let explicit = options
+            .entry_points
+            .iter()
+            .any(|entry| *entry == node.symbol || *entry == node.display_name);
+        let kind = if explicit {
+            Some(EntryPointKind::Explicit)
+        } else {
+            entry_point_kind(node)
+                .filter(|kind| options.public_entry_points || *kind != EntryPointKind::Public)
+        };
+        if let Some(kind) = kind {
+            entry_points.push((node.symbol.clone(), kind));
+        }
+    }
+    entry_points.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut reachable: HashSet<&str> = HashSet::new();
+    let mut queue: VecDeque<&str> = entry_points.iter().map(|(s, _)| s.as_str()).collect();
+    while let Some(symbol) = queue.pop_front() {
+        if !reachable.insert(symbol) {
+            continue;
+        }
+        if let Some(node) = call_graph.get(symbol) {
+            queue.extend(node.callees.iter().map(String::as_str));
+        }
+    }
+
+    let mut summary = DeadCodeSummary {
+        entry_points: entry_points.len(),
+        ..DeadCodeSummary::default()
+    };
+    let mut findings = Vec::new();
+    for node in call_graph.values().filter(|node| is_project(node)) {
+        summary.functions += 1;
+        if reachable.contains(node.symbol.as_str()) {
+            summary.reachable += 1;
+            continue;
+        }
+
+        let mode = node
+            .body
+            .as_deref()
+            .map(detect_decl_kind)
+            .unwrap_or(DeclKind::Exec);
+        let never_called = node.callers.is_empty();
+        let (category, severity) = match mode {
+            DeclKind::Exec => {
+                summary.unreachable_exec += 1;
+                let severity = if never_called {
+                    Severity::High
+                } else {
+                    Severity::Medium
+                };
+                (DeadCodeCategory::UnreachableExec, severity)
+            }
+            DeclKind::Proof => {
+                summary.unreachable_proof += 1;
+                let severity = if never_called {
+                    Severity::Medium
+                } else {
+                    Severity::Low
+                };
+                (DeadCodeCategory::UnreachableProof, severity)
+            }
+            DeclKind::Spec => {
+                summary.orphaned_specs += 1;
+                (DeadCodeCategory::OrphanedSpec, Severity::Low)
+            }
+        };
+        findings.push(DeadCodeFinding {
+            symbol: node.symbol.clone(),
+            display_name: node.display_name.clone(),
+            relative_path: node.relative_path.clone(),
+            line: node.range.first().map(|line| *line as u32 + 1),
+            category,
+            severity,
+            callers: node.callers.len(),
+        });
+    }
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+
+    DeadCodeReport {
+        summary,
+        entry_points,
+        findings,
+    }
+}
+
+impl DeadCodeReport {
+    /// Render the report as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let s = &self.summary;
+        let mut out = String::from("# Dead code report\n\n");
+        let _ = writeln!(out, "| | Count |\n|---|---:|");
+        let _ = writeln!(out, "| Functions analyzed | {} |", s.functions);
+        let _ = writeln!(out, "| Entry points | {} |", s.entry_points);
+        let _ = writeln!(out, "| Reachable | {} |", s.reachable);
+        let _ = writeln!(
+            out,
+            "| Unreachable exec functions | {} |",
+            s.unreachable_exec
+        );
+        let _ = writeln!(
+            out,
+            "| Unreachable proof functions | {} |",
+            s.unreachable_proof
+        );
+        let _ = writeln!(out, "| Orphaned spec functions | {} |", s.orphaned_specs);
+
+        if self.findings.is_empty() {
+            out.push_str("\nNo dead code found.\n");
+            return out;
+        }
+
+        out.push_str("\n## Findings\n\n");
+        out.push_str("| Severity | Category | Function | Location | Callers |\n");
+        out.push_str("|---|---|---|---|---:|\n");
+        for finding in &self.findings {
+            let location = match finding.line {
+                Some(line) => format!("{}:{}", finding.relative_path, line),
+                None => finding.relative_path.clone(),
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {} | `{}` | {} | {} |",
+                finding.severity.as_str(),
+                finding.category.as_str(),
+                finding.display_name,
+                location,
+                finding.callers
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn node(symbol: &str, body: &str, callees: &[&str], callers: &[&str]) -> FunctionNode {
+        FunctionNode {
+            range: vec![0, 0, 1],
+            ..test_support::node(symbol, callees)
+                .with_callers(callers)
+                .with_body(body)
+        }
+    }
+
+    fn sample_graph() -> CallGraph {
+        [
+            node("main", "fn main() {\n    helper();\n}", &["helper"], &[]),
+            node(
+                "helper",
+                "fn helper()\n    ensures is_ok()\n{}",
+                &["is_ok"],
+                &["main"],
+            ),
+            node(
+                "is_ok",
+                "spec fn is_ok() -> bool { true }",
+                &[],
+                &["helper"],
+            ),
+            node("api", "pub fn api() {}", &[], &[]),
+            node("unused", "fn unused() {\n    old();\n}", &["old"], &[]),
+            node("old", "fn old() {}", &[], &["unused"]),
+            node("lemma_unused", "proof fn lemma_unused() {}", &[], &[]),
+            node("orphan", "spec fn orphan() -> int { 0 }", &[], &[]),
+            node("a_test", "fn a_test() {}", &[], &[]).with_attributes(&["#[test]"]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_entry_point_kind() {
+        let graph = sample_graph();
+        assert_eq!(entry_point_kind(&graph["main"]), Some(EntryPointKind::Main));
+        assert_eq!(
+            entry_point_kind(&graph["api"]),
+            Some(EntryPointKind::Public)
+        );
+        assert_eq!(
+            entry_point_kind(&graph["a_test"]),
+            Some(EntryPointKind::Test)
+        );
+        assert_eq!(entry_point_kind(&graph["helper"]), None);
+        let restricted = node("r", "pub(crate) fn r() {}", &[], &[]);
+        assert_eq!(entry_point_kind(&restricted), None);
+    }
+
+    #[test]
+    fn test_entry_point_kind_reads_captured_attributes() {
+        // The extractor keeps attributes out of the body, so a `pub` test
+        // is only recognized as a test through `attributes`
+        let public_test = node("t", "pub fn t() {}", &[], &[]).with_attributes(&["#[tokio::test]"]);
+        assert_eq!(entry_point_kind(&public_test), Some(EntryPointKind::Test));
+        let bare = node("t", "pub fn t() {}", &[], &[]);
+        assert_eq!(entry_point_kind(&bare), Some(EntryPointKind::Public));
+        let test_file = node("t", "fn t() {}", &[], &[]).in_file("tests/api.rs");
+        assert_eq!(entry_point_kind(&test_file), Some(EntryPointKind::Test));
+    }
+
+    #[test]
+    fn test_resolve_entry_profiles() {
+        let extra = [
+            node(
+                "lemma_root",
+                "proof fn lemma_root() {\n    lemma_step();\n}",
+                &["lemma_step"],
+                &["api"],
+            ),
+            node(
+                "lemma_step",
+                "proof fn lemma_step() {}",
+                &[],
+                &["lemma_root"],
+            ),
+            node(
+                "pub_spec",
+                "pub open spec fn pub_spec() -> bool { true }",
+                &[],
+                &[],
+            ),
+        ];
+        let graph: CallGraph = sample_graph().values().cloned().chain(extra).collect();
+
+        let resolve = |profiles: &[&str]| {
+            let profiles: Vec<EntryProfile> = profiles.iter().map(|p| p.parse().unwrap()).collect();
+            resolve_entry_profiles(&graph, &profiles)
+        };
+        assert_eq!(resolve(&["main"]), vec!["main"]);
+        assert_eq!(resolve(&["pub-api"]), vec!["api"]);
+        assert_eq!(resolve(&["tests"]), vec!["a_test"]);
+        assert_eq!(
+            resolve(&["proof_roots"]),
+            vec!["lemma_root", "lemma_unused"]
+        );
+        assert_eq!(resolve(&["main", "tests"]), vec!["a_test", "main"]);
+
+        // Test helpers under `tests/` count as test entry points too
+        let graph: CallGraph = graph
+            .values()
+            .cloned()
+            .chain([node("setup", "fn setup() {}", &[], &[]).in_file("tests/common.rs")])
+            .collect();
+        let profiles = [EntryProfile::Tests];
+        assert_eq!(
+            resolve_entry_profiles(&graph, &profiles),
+            vec!["a_test", "setup"]
+        );
+        assert!("everything".parse::<EntryProfile>().is_err());
+    }
+
+    #[test]
+    fn test_find_dead_code() {
+        let report = find_dead_code(&sample_graph(), &ReachabilityOptions::default());
+
+        assert_eq!(report.summary.functions, 9);
+        assert_eq!(report.summary.entry_points, 3);
+        assert_eq!(report.summary.reachable, 5);
+        let findings: Vec<(&str, DeadCodeCategory, Severity)> = report
+            .findings
+            .iter()
+            .map(|f| (f.symbol.as_str(), f.category, f.severity))
+            .collect();
+        assert_eq!(
+            findings,
+            vec![
+                ("unused", DeadCodeCategory::UnreachableExec, Severity::High),
+                (
+                    "lemma_unused",
+                    DeadCodeCategory::UnreachableProof,
+                    Severity::Medium
+                ),
+                ("old", DeadCodeCategory::UnreachableExec, Severity::Medium),
+                ("orphan", DeadCodeCategory::OrphanedSpec, Severity::Low),
+            ]
+        );
+        assert!(report
+            .to_markdown()
+            .contains("| high | unreachable_exec | `unused` |"));
+    }
+
+    #[test]
+    fn test_explicit_entry_points_without_public() {
+        let options = ReachabilityOptions::default()
+            .with_public_entry_points(false)
+            .with_entry_points(["unused".to_string()]);
+        let report = find_dead_code(&sample_graph(), &options);
+
+        let dead: HashSet<&str> = report.findings.iter().map(|f| f.symbol.as_str()).collect();
+        assert!(dead.contains("api"));
+        assert!(!dead.contains("unused"));
+        assert!(!dead.contains("old"));
+    }
+}
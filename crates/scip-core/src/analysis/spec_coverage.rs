@@ -0,0 +1,366 @@
+//! Spec coverage of exec functions.
+//!
+//! For every project exec function, check whether its signature carries a
+//! `requires` and/or `ensures` clause, and aggregate per module and per
+//! crate:
+//!
+//! - fraction of exec functions with any spec
+//! - public exec functions with no spec at all
+//! - spec gaps (exec functions without a spec), sorted by caller count so
+//!   the most widely used unspecified functions come first
+//!
+//! Test functions (`#[test]` and friends, and anything under `tests/`) are
+//! left out. Modules come from the source path
+//! (`src/field/mod.rs` -> `field`), crates from the SCIP symbol package.
+
+use super::{crate_name, is_public_signature, signature_line};
+use crate::call_graph::{detect_decl_kind, parse_function_sections};
+use crate::node_filter::is_test_function;
+use crate::types::{CallGraph, DeclKind};
+use crate::verification::module_path;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// Spec counts for a group of exec functions
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CoverageStats {
+    pub exec_functions: usize,
+    pub with_requires: usize,
+    pub with_ensures: usize,
+    /// Functions with a `requires` or an `ensures` clause
+    pub with_spec: usize,
+    pub public_functions: usize,
+    /// Public functions with neither `requires` nor `ensures`
+    pub public_without_spec: usize,
+}
+
+impl CoverageStats {
+    /// Fraction of exec functions with any spec (1.0 for an empty group)
+    pub fn coverage(&self) -> f64 {
+        if self.exec_functions == 0 {
+            1.0
+        } else {
+            self.with_spec as f64 / self.exec_functions as f64
+        }
+    }
+
+    fn add(&mut self, has_requires: bool, has_ensures: bool, is_public: bool) {
+        self.exec_functions += 1;
+        self.with_requires += usize::from(has_requires);
+        self.with_ensures += usize::from(has_ensures);
+        let has_spec = has_requires || has_ensures;
+        self.with_spec += usize::from(has_spec);
+        self.public_functions += usize::from(is_public);
+        self.public_without_spec += usize::from(is_public && !has_spec);
+    }
+}
+
+/// Coverage of one crate or module
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupCoverage {
+    pub crate_name: String,
+    /// Module path within the crate (`""` for the crate root); `None` for
+    /// per-crate entries
+    pub module: Option<String>,
+    #[serde(flatten)]
+    pub stats: CoverageStats,
+    pub coverage: f64,
+}
+
+/// An exec function with neither `requires` nor `ensures`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpecGap {
+    pub symbol: String,
+    pub display_name: String,
+    pub relative_path: String,
+    /// 1-based first line of the definition
+    pub line: Option<u32>,
+    pub crate_name: String,
+    pub module: String,
+    pub is_public: bool,
+    pub callers: usize,
+}
+
+/// Result of [`spec_coverage`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SpecCoverageReport {
+    pub total: CoverageStats,
+    pub coverage: f64,
+    /// Sorted by crate name
+    pub crates: Vec<GroupCoverage>,
+    /// Sorted by crate name, then module
+    pub modules: Vec<GroupCoverage>,
+    /// Sorted by caller count (highest first), then path and line
+    pub gaps: Vec<SpecGap>,
+}
+
+fn group_coverage(crate_name: &str, module: Option<&str>, stats: &CoverageStats) -> GroupCoverage {
+    GroupCoverage {
+        crate_name: crate_name.to_string(),
+        module: module.map(str::to_string),
+        stats: stats.clone(),
+        coverage: stats.coverage(),
+    }
+}
+
+/// Compute spec coverage of the project exec functions in `call_graph`
+pub fn spec_coverage(call_graph: &CallGraph) -> SpecCoverageReport {
+    let mut total = CoverageStats::default();
+    let mut crates: BTreeMap<String, CoverageStats> = BTreeMap::new();
+    let mut modules: BTreeMap<(String, String), CoverageStats> = BTreeMap::new();
+    let mut gaps = Vec::new();
+
+    for node in call_graph.values() {
+        if node.file_path.starts_with("external:") {
+            continue;
+        }
+        let Some(body) = node.body.as_deref() else {
+            continue;
+        };
+        if detect_decl_kind(body) != DeclKind::Exec || is_test_function(node) {
+            continue;
+        }
+        let signature = signature_line(body);
+
+        let sections = parse_function_sections(body, 0);
+        let has_requires = sections.requires_range.is_some();
+        let has_ensures = sections.ensures_range.is_some();
+        let is_public = is_public_signature(signature);
+        let crate_name = crate_name(node);
+        let module = module_path(&node.relative_path);
+
+        total.add(has_requires, has_ensures, is_public);
+        crates
+            .entry(crate_name.clone())
+            .or_default()
+            .add(has_requires, has_ensures, is_public);
+        modules
+            .entry((crate_name.clone(), module.clone()))
+            .or_default()
+            .add(has_requires, has_ensures, is_public);
+
+        if !has_requires && !has_ensures {
+            gaps.push(SpecGap {
+                symbol: node.symbol.clone(),
+                display_name: node.display_name.clone(),
+                relative_path: node.relative_path.clone(),
+                line: node.range.first().map(|line| *line as u32 + 1),
+                crate_name,
+                module,
+                is_public,
+                callers: node.callers.len(),
+            });
+        }
+    }
+    gaps.sort_by(|a, b| {
+        b.callers
+            .cmp(&a.callers)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+
+    SpecCoverageReport {
+        coverage: total.coverage(),
+        total,
+        crates: crates
+            .iter()
+            .map(|(name, stats)| group_coverage(name, None, stats))
+            .collect(),
+        modules: modules
+            .iter()
+            .map(|((name, module), stats)| group_coverage(name, Some(module), stats))
+            .collect(),
+        gaps,
+    }
+}
+
+impl SpecCoverageReport {
+    /// Render the report as a markdown document, listing at most `max_gaps`
+    /// spec gaps
+    pub fn to_markdown(&self, max_gaps: usize) -> String {
+        let t = &self.total;
+        let mut out = String::from("# Spec coverage report\n\n");
+        let _ = writeln!(out, "| | Count |\n|---|---:|");
+        let _ = writeln!(out, "| Exec functions | {} |", t.exec_functions);
+        let _ = writeln!(out, "| With `requires` | {} |", t.with_requires);
+        let _ = writeln!(out, "| With `ensures` | {} |", t.with_ensures);
+        let _ = writeln!(
+            out,
+            "| With any spec | {} ({:.1}%) |",
+            t.with_spec,
+            self.coverage * 100.0
+        );
+        let _ = writeln!(
+            out,
+            "| Public functions without spec | {} / {} |",
+            t.public_without_spec, t.public_functions
+        );
+
+        let table_header = |out: &mut String, first: &str| {
+            let _ = writeln!(
+                out,
+                "| {first} | Exec | Requires | Ensures | Any spec | Coverage | Public without spec |"
+            );
+            out.push_str("|---|---:|---:|---:|---:|---:|---:|\n");
+        };
+        let table_row = |out: &mut String, name: &str, group: &GroupCoverage| {
+            let s = &group.stats;
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} | {} | {:.1}% | {} |",
+                name,
+                s.exec_functions,
+                s.with_requires,
+                s.with_ensures,
+                s.with_spec,
+                group.coverage * 100.0,
+                s.public_without_spec
+            );
+        };
+
+        out.push_str("\n## Crates\n\n");
+        table_header(&mut out, "Crate");
+        for group in &self.crates {
+            table_row(&mut out, &group.crate_name, group);
+        }
+
+        out.push_str("\n## Modules\n\n");
+        table_header(&mut out, "Module");
+        for group in &self.modules {
+            let module = group.module.as_deref().unwrap_or_default();
+            let name = if module.is_empty() {
+                group.crate_name.clone()
+            } else {
+                format!("{}::{}", group.crate_name, module)
+            };
+            table_row(&mut out, &name, group);
+        }
+
+        if self.gaps.is_empty() {
+            out.push_str("\nEvery exec function has a spec.\n");
+            return out;
+        }
+
+        let _ = writeln!(
+            out,
+            "\n## Spec gaps ({} of {})\n",
+            self.gaps.len().min(max_gaps),
+            self.gaps.len()
+        );
+        out.push_str("| Function | Location | Public | Callers |\n");
+        out.push_str("|---|---|---|---:|\n");
+        for gap in self.gaps.iter().take(max_gaps) {
+            let location = match gap.line {
+                Some(line) => format!("{}:{}", gap.relative_path, line),
+                None => gap.relative_path.clone(),
+            };
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} |",
+                gap.display_name,
+                location,
+                if gap.is_public { "yes" } else { "no" },
+                gap.callers
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::types::FunctionNode;
+
+    fn node(symbol: &str, path: &str, body: &str, callers: &[&str]) -> FunctionNode {
+        let name = symbol.rsplit('/').next().unwrap().trim_end_matches("().");
+        FunctionNode {
+            range: vec![4, 0, 1],
+            ..test_support::node(&format!("rust-analyzer cargo my-crate 0.1.0 {symbol}"), &[])
+                .with_display_name(name)
+                .in_file(path)
+                .with_callers(callers)
+                .with_body(body)
+        }
+    }
+
+    fn sample_graph() -> CallGraph {
+        [
+            node(
+                "field/add().",
+                "src/field/mod.rs",
+                "pub fn add(a: u8, b: u8) -> (r: u8)\n    requires a + b < 256,\n    ensures r == a + b,\n{ a + b }",
+                &["x"],
+            ),
+            node(
+                "field/neg().",
+                "src/field/mod.rs",
+                "fn neg(a: u8) -> (r: u8)\n    ensures r == 0 - a,\n{ 0 - a }",
+                &[],
+            ),
+            node(
+                "field/raw().",
+                "src/field/mod.rs",
+                "pub fn raw() -> u8 { 0 }",
+                &["a", "b", "c"],
+            ),
+            node("helper().", "src/lib.rs", "fn helper() {}", &["a"]),
+            node("lemma().", "src/lib.rs", "proof fn lemma() {}", &[]),
+            node("a_test().", "src/lib.rs", "pub fn a_test() {}", &[])
+                .with_attributes(&["#[test]"]),
+            node("it().", "tests/it.rs", "fn it() {}", &[]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_spec_coverage() {
+        let report = spec_coverage(&sample_graph());
+
+        assert_eq!(
+            report.total,
+            CoverageStats {
+                exec_functions: 4,
+                with_requires: 1,
+                with_ensures: 2,
+                with_spec: 2,
+                public_functions: 2,
+                public_without_spec: 1,
+            }
+        );
+        assert_eq!(report.coverage, 0.5);
+        assert_eq!(report.crates.len(), 1);
+        assert_eq!(report.crates[0].crate_name, "my-crate");
+
+        let modules: Vec<(Option<&str>, usize, f64)> = report
+            .modules
+            .iter()
+            .map(|m| (m.module.as_deref(), m.stats.exec_functions, m.coverage))
+            .collect();
+        assert_eq!(
+            modules,
+            vec![(Some(""), 1, 0.0), (Some("field"), 3, 2.0 / 3.0)]
+        );
+
+        let gaps: Vec<(&str, bool, usize)> = report
+            .gaps
+            .iter()
+            .map(|g| (g.display_name.as_str(), g.is_public, g.callers))
+            .collect();
+        assert_eq!(gaps, vec![("raw", true, 3), ("helper", false, 1)]);
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = spec_coverage(&sample_graph()).to_markdown(1);
+        assert!(markdown.contains("| With any spec | 2 (50.0%) |"));
+        assert!(markdown.contains("| `my-crate::field` | 3 | 1 | 2 | 2 | 66.7% | 1 |"));
+        assert!(markdown.contains("## Spec gaps (1 of 2)"));
+        assert!(markdown.contains("| `raw` | src/field/mod.rs:5 | yes | 3 |"));
+        assert!(!markdown.contains("`helper`"));
+    }
+}
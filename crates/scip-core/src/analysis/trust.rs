@@ -0,0 +1,399 @@
+//! Transitive trust base of a proof.
+//!
+//! A lemma is only as sound as everything it relies on. [`trust_base`] walks
+//! the proof and spec callees of a target function and collects every
+//! function along the way that is trusted rather than proven:
+//!
+//! - bodies containing `assume(...)` or `admit()`
+//! - `#[verifier::external_body]` and `assume_specification` items
+//! - axioms (`proof fn axiom_...`, the vstd naming convention)
+//! - external functions, whose source (and proofs) are outside the project
+//! - unverified functions: `#[verifier::external]` ones, which Verus skips,
+//!   and, with [`trust_base_with_status`], those whose verification failed
+//!   or never ran
+//!
+//! Detection is textual: attributes come from [`FunctionNode::attributes`]
+//! (SCIP bodies start at the `fn` line), everything else from the function
+//! bodies in the call graph.
+
+use crate::call_graph::detect_decl_kind;
+use crate::export_html::ReportMetrics;
+use crate::types::{CallGraph, DeclKind, FunctionNode, VerificationStatus};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Why a function is trusted instead of proven
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustReason {
+    Assume,
+    Admit,
+    ExternalBody,
+    AssumeSpecification,
+    Axiom,
+    /// Defined outside the project (no body to check)
+    External,
+    /// Not verified: `#[verifier::external]`, or a failed or missing
+    /// verification result
+    Unverified,
+}
+
+impl TrustReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrustReason::Assume => "assume",
+            TrustReason::Admit => "admit",
+            TrustReason::ExternalBody => "external_body",
+            TrustReason::AssumeSpecification => "assume_specification",
+            TrustReason::Axiom => "axiom",
+            TrustReason::External => "external",
+            TrustReason::Unverified => "unverified",
+        }
+    }
+}
+
+/// A trusted function in the trust base of a target
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrustedFunction {
+    pub symbol: String,
+    pub display_name: String,
+    pub relative_path: String,
+    /// 1-based first line of the definition
+    pub line: Option<u32>,
+    pub reasons: Vec<TrustReason>,
+    /// Call distance from the target (0 for the target itself)
+    pub depth: usize,
+    /// Display names of a shortest call chain from the target to this function
+    pub path: Vec<String>,
+}
+
+/// Result of [`trust_base`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TrustReport {
+    pub target: String,
+    /// Proof and spec functions the target transitively depends on,
+    /// excluding the target itself
+    pub dependencies: usize,
+    /// Sorted by depth, then path and line
+    pub trusted: Vec<TrustedFunction>,
+}
+
+impl TrustReport {
+    /// Number of trusted functions per reason
+    pub fn counts(&self) -> BTreeMap<TrustReason, usize> {
+        let mut counts = BTreeMap::new();
+        for function in &self.trusted {
+            for reason in &function.reasons {
+                *counts.entry(*reason).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Whether the target depends on nothing trusted
+    pub fn is_fully_proven(&self) -> bool {
+        self.trusted.is_empty()
+    }
+}
+
+/// Number of calls to `name` in `code`, i.e. of `name` as a whole word
+/// followed by `(`
+pub(crate) fn count_calls(code: &str, name: &str) -> usize {
+    code.match_indices(name)
+        .filter(|(start, _)| {
+            let before = code[..*start].chars().next_back();
+            let after = code[start + name.len()..].trim_start().chars().next();
+            !before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.')
+                && after == Some('(')
+        })
+        .count()
+}
+
+/// Whether `code` calls `name`
+fn calls(code: &str, name: &str) -> bool {
+    count_calls(code, name) > 0
+}
+
+/// `body` without its `//` comments
+pub(crate) fn strip_line_comments(body: &str) -> String {
+    body.lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Names of the `#[verifier::...]` / `#[verifier(...)]` attributes of a
+/// function, e.g. `external_body`
+fn verifier_attributes(node: &FunctionNode) -> Vec<String> {
+    node.attributes
+        .iter()
+        .filter_map(|attribute| {
+            let compact: String = attribute.chars().filter(|c| !c.is_whitespace()).collect();
+            let inner = compact
+                .strip_prefix("#[verifier::")
+                .or_else(|| compact.strip_prefix("#[verifier("))?;
+            let name = inner.split(|c| matches!(c, ']' | ')' | '(')).next()?;
+            Some(name.to_string())
+        })
+        .collect()
+}
+
+/// Trust reasons visible in a function's own attributes, body and name
+pub fn trust_reasons(node: &FunctionNode) -> Vec<TrustReason> {
+    if node.file_path.starts_with("external:") {
+        return vec![TrustReason::External];
+    }
+    let verifier = verifier_attributes(node);
+    let has_attribute = |name: &str| verifier.iter().any(|attribute| attribute == name);
+    let code = strip_line_comments(node.body.as_deref().unwrap_or_default());
+    let compact: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+
+    let mut reasons = Vec::new();
+    if calls(&code, "assume") {
+        reasons.push(TrustReason::Assume);
+    }
+    if calls(&code, "admit") {
+        reasons.push(TrustReason::Admit);
+    }
+    if has_attribute("external_body") {
+        reasons.push(TrustReason::ExternalBody);
+    }
+    // `assume_specification[...]` items, or the attribute form
+    if has_attribute("assume_specification") || compact.contains("assume_specification[") {
+        reasons.push(TrustReason::AssumeSpecification);
+    }
+    if has_attribute("external") {
+        reasons.push(TrustReason::Unverified);
+    }
+    let Some(body) = node.body.as_deref() else {
+        return reasons;
+    };
+    if node.display_name.starts_with("axiom") && detect_decl_kind(body) == DeclKind::Proof {
+        reasons.push(TrustReason::Axiom);
+    }
+    reasons
+}
+
+/// Collect everything trusted that the function `symbol` transitively relies
+/// on, following calls into proof and spec functions.
+///
+/// An unknown symbol gives an empty report.
+pub fn trust_base(call_graph: &CallGraph, symbol: &str) -> TrustReport {
+    trust_base_with_status(call_graph, symbol, &ReportMetrics::new())
+}
+
+/// Like [`trust_base`], but also counts the project dependencies whose
+/// `verification_status` in `metrics` is `failed` or `unverified` as
+/// [`TrustReason::Unverified`]
+pub fn trust_base_with_status(
+    call_graph: &CallGraph,
+    symbol: &str,
+    metrics: &ReportMetrics,
+) -> TrustReport {
+    let mut report = TrustReport {
+        target: symbol.to_string(),
+        ..TrustReport::default()
+    };
+    if !call_graph.contains_key(symbol) {
+        return report;
+    }
+
+    // Breadth-first, so `parent` records a shortest chain to every function
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    let mut depth: HashMap<&str, usize> = HashMap::from([(symbol, 0)]);
+    let mut queue: VecDeque<&str> = VecDeque::from([symbol]);
+    while let Some(current) = queue.pop_front() {
+        let Some(node) = call_graph.get(current) else {
+            continue;
+        };
+        let mut callees: Vec<&String> = node.callees.iter().collect();
+        callees.sort();
+        for callee in callees {
+            let Some(callee_node) = call_graph.get(callee) else {
+                continue;
+            };
+            if depth.contains_key(callee.as_str()) {
+                continue;
+            }
+            let is_ghost = callee_node.file_path.starts_with("external:")
+                || callee_node
+                    .body
+                    .as_deref()
+                    .is_some_and(|body| detect_decl_kind(body) != DeclKind::Exec);
+            if !is_ghost {
+                continue;
+            }
+            depth.insert(callee, depth[current] + 1);
+            parent.insert(callee, current);
+            queue.push_back(callee);
+        }
+    }
+    report.dependencies = depth.len() - 1;
+
+    for (&reached, &distance) in &depth {
+        let node = &call_graph[reached];
+        let mut reasons = trust_reasons(node);
+        let status = metrics.get(reached).and_then(|m| m.verification_status);
+        if matches!(
+            status,
+            Some(VerificationStatus::Failed | VerificationStatus::Unverified)
+        ) && !reasons.contains(&TrustReason::Unverified)
+        {
+            reasons.push(TrustReason::Unverified);
+        }
+        if reasons.is_empty() {
+            continue;
+        }
+        let mut path = vec![node.display_name.clone()];
+        let mut current = reached;
+        while let Some(&previous) = parent.get(current) {
+            path.push(call_graph[previous].display_name.clone());
+            current = previous;
+        }
+        path.reverse();
+        report.trusted.push(TrustedFunction {
+            symbol: node.symbol.clone(),
+            display_name: node.display_name.clone(),
+            relative_path: node.relative_path.clone(),
+            line: node.range.first().map(|line| *line as u32 + 1),
+            reasons,
+            depth: distance,
+            path,
+        });
+    }
+    report.trusted.sort_by(|a, b| {
+        a.depth
+            .cmp(&b.depth)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn node(symbol: &str, body: Option<&str>, callees: &[&str]) -> FunctionNode {
+        let node = FunctionNode {
+            range: vec![0, 0, 1],
+            ..test_support::node(symbol, callees)
+        };
+        match body {
+            Some(body) => node.with_body(body),
+            None => FunctionNode {
+                file_path: format!("external:vstd/{symbol}"),
+                ..node
+            },
+        }
+    }
+
+    fn sample_graph() -> CallGraph {
+        [
+            node(
+                "theorem",
+                Some("proof fn theorem()\n    ensures spec_ok(),\n{\n    lemma_a();\n}"),
+                &["lemma_a", "spec_ok", "helper"],
+            ),
+            node(
+                "lemma_a",
+                Some("proof fn lemma_a() {\n    // assume(false) is not real\n    lemma_b();\n    axiom_c();\n}"),
+                &["lemma_b", "axiom_c"],
+            ),
+            node("lemma_b", Some("proof fn lemma_b() {\n    admit();\n}"), &[]),
+            node("axiom_c", Some("proof fn axiom_c();"), &[]),
+            node("spec_ok", Some("spec fn spec_ok() -> bool { seq_len() > 0 }"), &["seq_len"]),
+            node("seq_len", None, &[]),
+            // Exec callees are not part of a proof's trust base
+            node("helper", Some("fn helper() {\n    assume(true);\n}"), &[]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_trust_reasons() {
+        let graph = sample_graph();
+        assert_eq!(trust_reasons(&graph["lemma_a"]), vec![]);
+        assert_eq!(trust_reasons(&graph["lemma_b"]), vec![TrustReason::Admit]);
+        assert_eq!(trust_reasons(&graph["axiom_c"]), vec![TrustReason::Axiom]);
+        assert_eq!(
+            trust_reasons(&graph["seq_len"]),
+            vec![TrustReason::External]
+        );
+        let external_body = node("raw", Some("fn raw() { x.assume(1); }"), &[])
+            .with_attributes(&["#[verifier::external_body]"]);
+        assert_eq!(
+            trust_reasons(&external_body),
+            vec![TrustReason::ExternalBody]
+        );
+        let skipped = node("skipped", Some("fn skipped() {}"), &[])
+            .with_attributes(&["#[inline]", "#[verifier(external)]"]);
+        assert_eq!(trust_reasons(&skipped), vec![TrustReason::Unverified]);
+        // Attributes are read from `attributes`, not from the body text
+        let quoted = node(
+            "quoted",
+            Some("proof fn quoted() {\n    let s = \"#[verifier::external_body]\";\n}"),
+            &[],
+        );
+        assert_eq!(trust_reasons(&quoted), vec![]);
+    }
+
+    #[test]
+    fn test_trust_base() {
+        let report = trust_base(&sample_graph(), "theorem");
+
+        assert_eq!(report.dependencies, 5);
+        let trusted: Vec<(&str, usize, Vec<&str>)> = report
+            .trusted
+            .iter()
+            .map(|t| {
+                (
+                    t.symbol.as_str(),
+                    t.depth,
+                    t.path.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            trusted,
+            vec![
+                ("axiom_c", 2, vec!["theorem", "lemma_a", "axiom_c"]),
+                ("lemma_b", 2, vec!["theorem", "lemma_a", "lemma_b"]),
+                ("seq_len", 2, vec!["theorem", "spec_ok", "seq_len"]),
+            ]
+        );
+        assert_eq!(report.counts()[&TrustReason::Admit], 1);
+        assert!(!report.is_fully_proven());
+
+        assert!(trust_base(&sample_graph(), "spec_ok").trusted.len() == 1);
+        assert_eq!(trust_base(&sample_graph(), "missing").dependencies, 0);
+    }
+
+    #[test]
+    fn test_trust_base_with_status() {
+        let mut metrics = ReportMetrics::new();
+        metrics.set_verification_status("lemma_a", VerificationStatus::Failed);
+        metrics.set_verification_status("lemma_b", VerificationStatus::Unverified);
+        metrics.set_verification_status("spec_ok", VerificationStatus::Verified);
+        let report = trust_base_with_status(&sample_graph(), "theorem", &metrics);
+
+        let trusted: Vec<(&str, Vec<TrustReason>)> = report
+            .trusted
+            .iter()
+            .map(|t| (t.symbol.as_str(), t.reasons.clone()))
+            .collect();
+        assert_eq!(
+            trusted,
+            vec![
+                ("lemma_a", vec![TrustReason::Unverified]),
+                ("axiom_c", vec![TrustReason::Axiom]),
+                ("lemma_b", vec![TrustReason::Admit, TrustReason::Unverified]),
+                ("seq_len", vec![TrustReason::External]),
+            ]
+        );
+        assert_eq!(report.counts()[&TrustReason::Unverified], 2);
+    }
+}
@@ -5,6 +5,7 @@
 //!
 //! This approach keeps probe-verus unchanged while extending its output for our needs.
 
+use crate::export_d3::assign_stable_ids;
 use crate::types::{D3Graph, D3GraphMetadata, D3Link, D3Node, DeclKind};
 use probe_verus::{AtomWithLines, CallLocation, FunctionNode};
 use std::collections::{BTreeMap, HashMap, HashSet};
@@ -94,6 +95,8 @@ pub fn atoms_to_d3_graph(
                 dependencies,
                 dependents,
                 kind,
+                crate_name: None,
+                ..Default::default()
             }
         })
         .collect();
@@ -128,6 +131,9 @@ pub fn atoms_to_d3_graph(
                                 source: atom.code_name.clone(),
                                 target: dep.code_name.clone(),
                                 link_type,
+                                edge_kind: None,
+                                weight: None,
+                                lines: Vec::new(),
                             })
                         } else {
                             None
@@ -148,13 +154,17 @@ pub fn atoms_to_d3_graph(
         project_root: project_root.to_string(),
         generated_at: now.to_rfc3339(),
         github_url,
+        crates: Vec::new(),
+        ..Default::default()
     };
 
-    D3Graph {
+    let mut graph = D3Graph {
         nodes,
         links,
         metadata,
-    }
+    };
+    assign_stable_ids(&mut graph);
+    graph
 }
 
 /// Convert probe-verus DeclKind to our local DeclKind enum.
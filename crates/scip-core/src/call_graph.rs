@@ -2,28 +2,56 @@
 //!
 //! This module provides the core functionality for building call graphs from SCIP data:
 //! - `build_call_graph` - Build a call graph from SCIP index
+//! - `build_call_graph_with_options` - Same, with configurable node kinds and parallelism
+//!   (`CallGraphOptions`)
+//! - `build_call_graph_monitored` - Same, with progress reporting and cancellation
+//! - `build_call_graph_streaming` - Build a call graph from a stream of documents
+//! - `update_call_graph` - Patch a call graph after some documents changed
 //! - `detect_decl_kind` - Detect Verus function mode (exec/proof/spec)
 //! - `parse_function_sections` - Parse requires/ensures/body sections
 //! - `classify_call_location` - Classify where calls occur (precondition/postcondition/inner)
+//! - `filter_by_edge_kinds` - Keep only exec/proof/spec edges of the given kinds
 //! - `generate_filtered_call_graph` - Create depth-limited subgraphs
+//! - `generate_reverse_filtered_call_graph` - Create depth-limited subgraphs of callers
+//! - `collect_neighborhood` - Collect functions within N calls of a set of functions
+//! - `neighborhood_by_depth` - The same functions, closest first, with their distance
+//! - `find_sccs` - Find strongly connected components (recursion cycles)
+//! - `collapse_sccs` - Replace each recursion cycle by a single super-node
+//! - `collapse_external_crates` - Replace the functions of each external crate by one node
+//! - `find_call_paths` - Find call chains from one function to another
+//! - `count_calls_by_origin` - Count calls made from production code and from tests
 //! - `print_call_graph_summary` - Print human-readable summary
 
+use crate::item_spans::{ItemEnd, ItemSpans};
+use crate::monitor::{Cancelled, Monitor};
+use crate::node_filter::is_test_function;
 use crate::parser::{extract_display_name_from_symbol, extract_path_info_from_symbol};
+use crate::spec_text::{signature_text, spec_clauses};
+use crate::symbol_scheme::{parse_symbol, scheme_for_symbol, RustAnalyzerScheme};
 use crate::types::{
-    CallLocation, CalleeOccurrence, DeclKind, FunctionNode, FunctionSections, ScipIndex,
+    CallGraph, CallLocation, CalleeOccurrence, DeclKind, Document, EdgeKind, FunctionNode,
+    FunctionSections, NodeKind, ScipIndex, SourceSpan,
 };
 use log::{debug, info};
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, NodeIndex};
+use rayon::prelude::*;
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
-use std::path::Path;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-/// Compiled regex for removing generic type parameters from paths
-fn generics_regex() -> &'static Regex {
-    static RE: OnceLock<Regex> = OnceLock::new();
-    RE.get_or_init(|| Regex::new(r"<[^>]*>").unwrap())
-}
+/// SCIP kind values treated as function-like by default (see [`is_function_like`])
+pub const DEFAULT_FUNCTION_KINDS: [i32; 4] = [6, 12, 17, 80];
+
+/// SCIP kind values for constants and statics (`Constant`, `StaticField`,
+/// `StaticVariable` in `scip.proto`)
+pub const CONSTANT_KINDS: [i32; 3] = [8, 79, 82];
+
+/// SCIP kind values for types (`Enum`, `Struct`, `TypeAlias`, `Union` in
+/// `scip.proto`)
+pub const TYPE_KINDS: [i32; 4] = [11, 49, 55, 59];
 
 /// Check if a symbol kind represents a function-like entity.
 ///
@@ -33,7 +61,151 @@ fn generics_regex() -> &'static Regex {
 /// - 17: Macro
 /// - 80: Method
 pub fn is_function_like(kind: i32) -> bool {
-    matches!(kind, 6 | 12 | 17 | 80)
+    DEFAULT_FUNCTION_KINDS.contains(&kind)
+}
+
+/// Options controlling which SCIP symbols become call graph nodes.
+///
+/// The defaults reproduce [`build_call_graph`]. Indexers that emit other kind
+/// values (e.g. newer scip-rust or scip-typescript releases) can be supported
+/// by supplying their own `function_kinds`.
+#[derive(Debug, Clone)]
+pub struct CallGraphOptions {
+    /// SCIP `SymbolInformation.kind` values treated as functions
+    pub function_kinds: HashSet<i32>,
+    /// Also create nodes for constants and statics ([`CONSTANT_KINDS`]), so
+    /// that references to them show up as edges
+    pub include_constants: bool,
+    /// Also create type nodes ([`NodeKind::Type`]) for structs, enums,
+    /// unions and type aliases ([`TYPE_KINDS`]), constants and statics, so
+    /// that functions get "references" edges to the types they use
+    pub include_types: bool,
+    /// Number of threads used to extract function bodies from source files:
+    /// `None` uses rayon's global pool (one thread per core), `Some(1)`
+    /// extracts sequentially
+    pub parallelism: Option<usize>,
+    /// Glob patterns matched against document relative paths; matching
+    /// documents (generated code, vendored crates, benches) are left out of
+    /// the graph. `*` and `?` match within one path component, `**` matches
+    /// any number of components.
+    pub exclude_globs: Vec<String>,
+}
+
+impl Default for CallGraphOptions {
+    fn default() -> Self {
+        CallGraphOptions {
+            function_kinds: DEFAULT_FUNCTION_KINDS.into_iter().collect(),
+            include_constants: false,
+            include_types: false,
+            parallelism: None,
+            exclude_globs: Vec::new(),
+        }
+    }
+}
+
+impl CallGraphOptions {
+    /// Use a custom whitelist of function-like kinds
+    pub fn with_function_kinds(mut self, kinds: impl IntoIterator<Item = i32>) -> Self {
+        self.function_kinds = kinds.into_iter().collect();
+        self
+    }
+
+    /// Include constants and statics as nodes
+    pub fn with_constants(mut self, include_constants: bool) -> Self {
+        self.include_constants = include_constants;
+        self
+    }
+
+    /// Include types, constants and statics as type nodes
+    pub fn with_types(mut self, include_types: bool) -> Self {
+        self.include_types = include_types;
+        self
+    }
+
+    /// Limit body extraction to `threads` threads (`1` disables parallelism)
+    pub fn with_parallelism(mut self, threads: usize) -> Self {
+        self.parallelism = Some(threads.max(1));
+        self
+    }
+
+    /// Leave out documents whose relative path matches one of `globs`
+    pub fn with_exclude_globs(mut self, globs: impl IntoIterator<Item = String>) -> Self {
+        self.exclude_globs = globs.into_iter().collect();
+        self
+    }
+
+    /// Stable hash of the options that shape the graph, for caches of
+    /// graphs built with them. [`Self::parallelism`] is left out: it doesn't
+    /// change the result.
+    pub fn fingerprint(&self) -> u64 {
+        let mut kinds: Vec<i32> = self.function_kinds.iter().copied().collect();
+        kinds.sort_unstable();
+        let key = format!(
+            "kinds={kinds:?};constants={};types={};exclude={:?}",
+            self.include_constants, self.include_types, self.exclude_globs
+        );
+        crate::scip_utils::fnv1a(key.into_bytes())
+    }
+
+    /// Check whether a document is excluded by [`Self::exclude_globs`]
+    pub fn is_excluded(&self, relative_path: &str) -> bool {
+        let path = relative_path
+            .trim_start_matches('/')
+            .trim_start_matches("./");
+        self.exclude_globs
+            .iter()
+            .any(|glob| glob_matches(glob.trim_start_matches("./"), path))
+    }
+
+    /// Check whether symbols of this kind become call graph nodes
+    pub fn is_node_kind(&self, kind: i32) -> bool {
+        self.function_kinds.contains(&kind)
+            || (self.include_constants && CONSTANT_KINDS.contains(&kind))
+            || self.node_kind(kind) == NodeKind::Type
+    }
+
+    /// Kind of the nodes made for symbols of this kind
+    pub fn node_kind(&self, kind: i32) -> NodeKind {
+        let is_type = TYPE_KINDS.contains(&kind) || CONSTANT_KINDS.contains(&kind);
+        if self.include_types && is_type {
+            NodeKind::Type
+        } else {
+            NodeKind::Function
+        }
+    }
+}
+
+/// Match a `/`-separated path against a glob pattern
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    glob_matches_components(&pattern, &path)
+}
+
+fn glob_matches_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => {
+            (0..=path.len()).any(|skip| glob_matches_components(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((component, path_rest)) => {
+                component_matches(first.as_bytes(), component.as_bytes())
+                    && glob_matches_components(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match one path component against a pattern component (`*` and `?`)
+fn component_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| component_matches(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && component_matches(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && component_matches(rest, &name[1..]),
+    }
 }
 
 /// Detect the Verus function mode from the function signature/body.
@@ -143,11 +315,169 @@ pub fn parse_function_sections(body: &str, func_start_line: i32) -> FunctionSect
         }
     }
 
+    if let Some((requires_span, ensures_span)) = clause_spans(body, func_start_line) {
+        sections.requires_span = requires_span;
+        sections.ensures_span = ensures_span;
+    }
+
     sections
 }
 
-/// Classify a call occurrence based on its line number and the function sections.
-pub fn classify_call_location(call_line: i32, sections: &FunctionSections) -> CallLocation {
+/// Exact spans of the `requires` and `ensures`/`decreases` clauses of a
+/// function, from the token positions of the lexer verus_syn parses with.
+///
+/// Clauses are the top-level tokens between their keyword and the next
+/// clause keyword or the body's opening brace, so string literals, comments
+/// and one-line functions do not confuse them. Returns `None` if the text
+/// does not tokenize or positions are unavailable.
+fn clause_spans(
+    body: &str,
+    func_start_line: i32,
+) -> Option<(Option<SourceSpan>, Option<SourceSpan>)> {
+    let tokens: proc_macro2::TokenStream = body.parse().ok()?;
+    let position = |at: proc_macro2::LineColumn| -> Option<(i32, i32)> {
+        (at.line > 0).then(|| (func_start_line + at.line as i32 - 1, at.column as i32))
+    };
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Clause {
+        Requires,
+        Ensures,
+    }
+    let mut requires: Option<SourceSpan> = None;
+    let mut ensures: Option<SourceSpan> = None;
+    let mut current: Option<(Clause, SourceSpan)> = None;
+    let mut close = |current: &mut Option<(Clause, SourceSpan)>| {
+        if let Some((clause, span)) = current.take() {
+            let merged = match clause {
+                Clause::Requires => &mut requires,
+                Clause::Ensures => &mut ensures,
+            };
+            *merged = Some(match *merged {
+                Some(previous) => SourceSpan {
+                    start: previous.start.min(span.start),
+                    end: previous.end.max(span.end),
+                },
+                None => span,
+            });
+        }
+    };
+
+    for token in tokens {
+        let span = token.span();
+        let (start, end) = (position(span.start())?, position(span.end())?);
+        let keyword = match &token {
+            proc_macro2::TokenTree::Ident(ident) if ident == "requires" => Some(Clause::Requires),
+            proc_macro2::TokenTree::Ident(ident) if ident == "ensures" || ident == "decreases" => {
+                Some(Clause::Ensures)
+            }
+            proc_macro2::TokenTree::Group(group)
+                if group.delimiter() == proc_macro2::Delimiter::Brace =>
+            {
+                // The function body
+                break;
+            }
+            _ => None,
+        };
+        match (keyword, current.as_mut()) {
+            (Some(clause), _) => {
+                close(&mut current);
+                current = Some((clause, SourceSpan { start, end }));
+            }
+            (None, Some((_, span))) => span.end = end,
+            (None, None) => {}
+        }
+    }
+    close(&mut current);
+
+    Some((requires, ensures))
+}
+
+/// Names of the functions starting a concurrent task with their argument
+const SPAWN_FUNCTIONS: [&str; 3] = ["spawn", "spawn_blocking", "spawn_local"];
+
+/// Spans of the code running in another task: the arguments of
+/// `spawn(...)` calls (`tokio::spawn`, `thread::spawn`, `spawn_blocking`,
+/// `spawn_local`) and `async` / `async move` blocks. Empty if the text does
+/// not tokenize.
+fn spawn_spans(body: &str, func_start_line: i32) -> Vec<SourceSpan> {
+    fn collect(
+        tokens: proc_macro2::TokenStream,
+        func_start_line: i32,
+        spans: &mut Vec<SourceSpan>,
+    ) {
+        use proc_macro2::{Delimiter, TokenTree};
+
+        let mut previous: Vec<String> = Vec::new();
+        for token in tokens {
+            match &token {
+                TokenTree::Group(group) => {
+                    let spawned = match group.delimiter() {
+                        Delimiter::Parenthesis => previous
+                            .last()
+                            .is_some_and(|name| SPAWN_FUNCTIONS.contains(&name.as_str())),
+                        Delimiter::Brace => match previous.as_slice() {
+                            [.., last] if last == "async" => true,
+                            [.., async_kw, last] => async_kw == "async" && last == "move",
+                            _ => false,
+                        },
+                        _ => false,
+                    };
+                    let (start, end) = (group.span().start(), group.span().end());
+                    if spawned && start.line > 0 {
+                        spans.push(SourceSpan {
+                            start: (func_start_line + start.line as i32 - 1, start.column as i32),
+                            end: (func_start_line + end.line as i32 - 1, end.column as i32),
+                        });
+                    } else {
+                        collect(group.stream(), func_start_line, spans);
+                    }
+                    previous.clear();
+                }
+                TokenTree::Ident(ident) => previous.push(ident.to_string()),
+                _ => previous.clear(),
+            }
+        }
+    }
+
+    let mut spans = Vec::new();
+    if let Ok(tokens) = body.parse::<proc_macro2::TokenStream>() {
+        collect(tokens, func_start_line, &mut spans);
+    }
+    spans
+}
+
+/// Whether a call at `line` (and `column`, if known) runs in a spawned task
+fn is_spawned_call(spans: &[SourceSpan], line: i32, column: Option<i32>) -> bool {
+    spans.iter().any(|span| match column {
+        Some(column) => span.contains(line, column),
+        None => span.start.0 <= line && line <= span.end.0,
+    })
+}
+
+/// Classify a call occurrence based on its position and the function sections.
+///
+/// When the call's column is known and the sections carry exact clause spans
+/// (see [`FunctionSections::requires_span`]), the call is classified by
+/// position, so calls sharing a line with the body's opening brace land in
+/// the right section. Otherwise the line ranges decide.
+pub fn classify_call_location(
+    call_line: i32,
+    call_column: Option<i32>,
+    sections: &FunctionSections,
+) -> CallLocation {
+    let has_spans = sections.requires_span.is_some() || sections.ensures_span.is_some();
+    if let Some(column) = call_column.filter(|_| has_spans) {
+        let within = |span: Option<SourceSpan>| span.is_some_and(|s| s.contains(call_line, column));
+        return if within(sections.requires_span) {
+            CallLocation::Precondition
+        } else if within(sections.ensures_span) {
+            CallLocation::Postcondition
+        } else {
+            CallLocation::Inner
+        };
+    }
+
     if let Some((start, end)) = sections.requires_range {
         if call_line >= start && call_line <= end {
             return CallLocation::Precondition;
@@ -164,15 +494,59 @@ pub fn classify_call_location(call_line: i32, sections: &FunctionSections) -> Ca
 }
 
 /// Build a call graph from SCIP JSON data
-pub fn build_call_graph(scip_data: &ScipIndex) -> HashMap<String, FunctionNode> {
+pub fn build_call_graph(scip_data: &ScipIndex) -> CallGraph {
+    build_call_graph_with_options(scip_data, &CallGraphOptions::default())
+}
+
+/// Build a call graph from SCIP JSON data, choosing node kinds via `options`
+///
+/// Calls whose source text lives in a macro definition (e.g. a helper macro
+/// used inside `verus! {}` or a test-generating macro) are attributed to the
+/// functions invoking the macro, at the line of the invocation.
+pub fn build_call_graph_with_options(
+    scip_data: &ScipIndex,
+    options: &CallGraphOptions,
+) -> CallGraph {
+    build_call_graph_monitored(scip_data, options, &Monitor::default())
+        .expect("the default monitor is never cancelled")
+}
+
+/// [`build_call_graph_with_options`], reporting the progress of the
+/// definition, call and body extraction passes to `monitor`.
+///
+/// Cancellation is checked between documents and source files; a cancelled
+/// build returns [`Cancelled`] since a partial graph would silently lack
+/// calls.
+pub fn build_call_graph_monitored(
+    scip_data: &ScipIndex,
+    options: &CallGraphOptions,
+    monitor: &Monitor,
+) -> Result<CallGraph, Cancelled> {
+    let progress = monitor.progress();
     let mut call_graph: HashMap<String, FunctionNode> = HashMap::new();
     let mut symbol_to_file: HashMap<String, String> = HashMap::new();
     let mut symbol_to_kind: HashMap<String, i32> = HashMap::new();
     let mut function_symbols: HashSet<String> = HashSet::new();
 
+    let documents: Vec<&Document> = scip_data
+        .documents
+        .iter()
+        .filter(|doc| !options.is_excluded(&doc.relative_path))
+        .collect();
+    if documents.len() < scip_data.documents.len() {
+        info!(
+            "Excluded {} of {} documents matching exclude globs",
+            scip_data.documents.len() - documents.len(),
+            scip_data.documents.len()
+        );
+    }
+
     // Pre-pass: Find where each symbol is DEFINED (symbol_roles == 1)
     let mut symbol_to_def_file: HashMap<String, (String, String)> = HashMap::new();
-    for doc in &scip_data.documents {
+    progress.start("Reading definitions", Some(documents.len() as u64));
+    for doc in &documents {
+        monitor.check()?;
+        progress.advance(1);
         let project_root = &scip_data.metadata.project_root;
         let rel_path = doc.relative_path.trim_start_matches('/');
         let abs_path = format!("{project_root}/{rel_path}");
@@ -187,15 +561,16 @@ pub fn build_call_graph(scip_data: &ScipIndex) -> HashMap<String, FunctionNode>
             }
         }
     }
+    progress.finish();
     debug!(
         "Pre-pass: Found {} symbol definitions",
         symbol_to_def_file.len()
     );
 
     // First pass: identify all LOCAL function symbols
-    for doc in &scip_data.documents {
+    for doc in &documents {
         for symbol in &doc.symbols {
-            if is_function_like(symbol.kind) {
+            if options.is_node_kind(symbol.kind) {
                 let (abs_path, rel_path) =
                     if let Some((def_abs, def_rel)) = symbol_to_def_file.get(&symbol.symbol) {
                         (def_abs.clone(), def_rel.clone())
@@ -217,11 +592,8 @@ pub fn build_call_graph(scip_data: &ScipIndex) -> HashMap<String, FunctionNode>
                             .unwrap_or_else(|| "unknown".to_string()),
                         file_path: abs_path,
                         relative_path: rel_path,
-                        callers: HashSet::new(),
-                        callees: HashSet::new(),
-                        callee_occurrences: Vec::new(),
-                        range: Vec::new(),
-                        body: None,
+                        node_kind: options.node_kind(symbol.kind),
+                        ..Default::default()
                     },
                 );
             }
@@ -236,19 +608,23 @@ pub fn build_call_graph(scip_data: &ScipIndex) -> HashMap<String, FunctionNode>
     // Pass 1.5: Identify external function symbols
     let mut external_function_symbols: HashSet<String> = HashSet::new();
     let mut external_display_names: HashMap<String, String> = HashMap::new();
+    let mut external_types: HashSet<String> = HashSet::new();
 
-    for doc in &scip_data.documents {
+    for doc in &documents {
         for symbol in &doc.symbols {
-            if is_function_like(symbol.kind) && !function_symbols.contains(&symbol.symbol) {
+            if options.is_node_kind(symbol.kind) && !function_symbols.contains(&symbol.symbol) {
                 external_function_symbols.insert(symbol.symbol.clone());
                 if let Some(name) = &symbol.display_name {
                     external_display_names.insert(symbol.symbol.clone(), name.clone());
                 }
+                if options.node_kind(symbol.kind) == NodeKind::Type {
+                    external_types.insert(symbol.symbol.clone());
+                }
             }
         }
     }
 
-    for doc in &scip_data.documents {
+    for doc in &documents {
         for occurrence in &doc.occurrences {
             let is_definition = occurrence.symbol_roles.unwrap_or(0) & 1 == 1;
             let symbol = &occurrence.symbol;
@@ -260,10 +636,7 @@ pub fn build_call_graph(scip_data: &ScipIndex) -> HashMap<String, FunctionNode>
                 continue;
             }
 
-            if (symbol.contains("()") || symbol.ends_with("."))
-                && (symbol.contains('#') || symbol.contains('/'))
-                && !symbol.contains("().(")
-            {
+            if looks_like_external_function(symbol) {
                 external_function_symbols.insert(symbol.clone());
             }
         }
@@ -276,27 +649,11 @@ pub fn build_call_graph(scip_data: &ScipIndex) -> HashMap<String, FunctionNode>
 
     // Create placeholder nodes for external functions
     for symbol in &external_function_symbols {
-        let display_name = external_display_names
-            .get(symbol)
-            .cloned()
-            .unwrap_or_else(|| extract_display_name_from_symbol(symbol));
-
-        let (relative_path, _file_name, _parent_folder) = extract_path_info_from_symbol(symbol);
-
-        call_graph.insert(
-            symbol.clone(),
-            FunctionNode {
-                symbol: symbol.clone(),
-                display_name,
-                file_path: format!("external:{}", symbol),
-                relative_path,
-                callers: HashSet::new(),
-                callees: HashSet::new(),
-                callee_occurrences: Vec::new(),
-                range: Vec::new(),
-                body: None,
-            },
-        );
+        let mut node = external_function_node(symbol, external_display_names.get(symbol).cloned());
+        if external_types.contains(symbol) {
+            node.node_kind = NodeKind::Type;
+        }
+        call_graph.insert(symbol.clone(), node);
     }
 
     let all_function_symbols: HashSet<String> = function_symbols
@@ -305,8 +662,12 @@ pub fn build_call_graph(scip_data: &ScipIndex) -> HashMap<String, FunctionNode>
         .collect();
 
     // Second pass: analyze occurrences to build the call graph
-    for doc in &scip_data.documents {
-        let mut current_function: Option<String> = None;
+    let mut macro_calls = MacroCalls::default();
+    progress.start("Resolving calls", Some(documents.len() as u64));
+    for doc in &documents {
+        monitor.check()?;
+        progress.advance(1);
+        let mut scope = CallerScope::default();
 
         let mut ordered_occurrences = doc.occurrences.clone();
         ordered_occurrences.sort_by(|a, b| {
@@ -317,310 +678,2328 @@ pub fn build_call_graph(scip_data: &ScipIndex) -> HashMap<String, FunctionNode>
 
         for occurrence in &ordered_occurrences {
             let is_definition = occurrence.symbol_roles.unwrap_or(0) & 1 == 1;
+            let symbol = &occurrence.symbol;
+            let line = occurrence.range.first().copied().unwrap_or(0);
 
-            if is_definition && function_symbols.contains(&occurrence.symbol) {
-                current_function = Some(occurrence.symbol.clone());
-                if let Some(node) = call_graph.get_mut(&occurrence.symbol) {
-                    node.range = occurrence.range.clone();
+            if is_definition {
+                if function_symbols.contains(symbol) {
+                    if let Some(node) = call_graph.get_mut(symbol) {
+                        node.range = occurrence.range.clone();
+                    }
                 }
+                if is_macro_symbol(symbol) {
+                    scope.define_macro(symbol);
+                } else if function_symbols.contains(symbol) {
+                    scope.define_function(symbol, line, &mut macro_calls);
+                }
+                continue;
             }
 
-            if !is_definition && all_function_symbols.contains(&occurrence.symbol) {
-                if let Some(caller) = &current_function {
-                    if caller != &occurrence.symbol {
-                        if let Some(caller_node) = call_graph.get_mut(caller) {
-                            caller_node.callees.insert(occurrence.symbol.clone());
-                            let call_line = occurrence.range.first().copied().unwrap_or(0);
-                            caller_node.callee_occurrences.push(CalleeOccurrence {
-                                symbol: occurrence.symbol.clone(),
-                                line: call_line,
-                                location: None,
-                            });
-                        }
-
-                        if let Some(callee_node) = call_graph.get_mut(&occurrence.symbol) {
-                            callee_node.callers.insert(caller.clone());
-                        }
-                    }
+            if is_macro_symbol(symbol) {
+                scope.invoke_macro(symbol, line, &mut macro_calls);
+            }
+            if all_function_symbols.contains(symbol) {
+                if let Some(caller) = scope.call(symbol, &mut macro_calls) {
+                    let column = occurrence.range.get(1).copied();
+                    add_call_edge(&mut call_graph, &caller, symbol, line, column);
                 }
             }
         }
     }
 
+    progress.finish();
+
+    // Calls written inside macro definitions belong to the invoking functions
+    for (caller, callee, line) in macro_calls.resolve() {
+        if all_function_symbols.contains(&callee) {
+            add_call_edge(&mut call_graph, &caller, &callee, line, None);
+        }
+    }
+
     // Third pass: extract function bodies from source files
-    for node in call_graph.values_mut() {
-        if !node.range.is_empty() {
-            let file_path = &node.file_path;
+    extract_function_bodies(&mut call_graph, options.parallelism, monitor);
+    monitor.check()?;
 
-            let clean_path = if file_path.starts_with("file://") {
-                file_path.trim_start_matches("file://")
-            } else {
-                file_path
-            };
+    // Fourth pass: Default unclassified callee occurrences to Inner
+    default_call_locations(&mut call_graph);
 
-            let abs_path = Path::new(clean_path);
-            debug!("Trying to read file: {clean_path}");
-
-            if let Ok(contents) = fs::read_to_string(abs_path) {
-                let lines: Vec<&str> = contents.lines().collect();
-
-                let display_name = &node.display_name;
-                let range = &node.range;
-                debug!("Function: {display_name}, Range: {range:?}");
-
-                if !node.range.is_empty() {
-                    let start_line = node.range[0] as usize;
-
-                    if start_line < lines.len() {
-                        let mut body_lines = Vec::new();
-                        let mut open_braces = 0;
-                        let mut found_first_brace = false;
-
-                        body_lines.push(lines[start_line]);
-
-                        for (line_idx, line) in lines.iter().enumerate().skip(start_line) {
-                            if line_idx == start_line {
-                                if line.contains('{') {
-                                    found_first_brace = true;
-                                    open_braces = line.matches('{').count();
-                                    open_braces =
-                                        open_braces.saturating_sub(line.matches('}').count());
-                                }
-                                continue;
-                            }
-
-                            if !found_first_brace {
-                                if line.contains('{') {
-                                    found_first_brace = true;
-                                    open_braces = line.matches('{').count();
-                                    open_braces =
-                                        open_braces.saturating_sub(line.matches('}').count());
-                                }
-                                body_lines.push(line);
-                            } else {
-                                open_braces += line.matches('{').count();
-                                open_braces = open_braces.saturating_sub(line.matches('}').count());
-                                body_lines.push(line);
-                                if open_braces == 0 {
-                                    break;
-                                }
-                            }
-                        }
+    // Fifth pass: Classify edges by caller/callee declaration kind
+    assign_edge_kinds(&mut call_graph);
 
-                        let full_body = body_lines.join("\n");
-                        let body_len = full_body.len();
-                        node.body = Some(full_body.clone());
-                        let display_name = &node.display_name;
-                        debug!("Extracted body for {display_name}, length: {body_len}");
+    Ok(call_graph.into())
+}
 
-                        let sections = parse_function_sections(&full_body, node.range[0]);
-                        for occurrence in &mut node.callee_occurrences {
-                            occurrence.location =
-                                Some(classify_call_location(occurrence.line, &sections));
-                        }
+/// Build a call graph from a stream of SCIP documents.
+///
+/// Produces the same graph as [`build_call_graph`], but consumes documents one
+/// at a time (e.g. from [`crate::scip_reader::stream_documents`]) so peak
+/// memory is bounded by a single document plus the graph itself. Only
+/// potential call references are retained between documents; they are
+/// resolved against the complete set of function symbols at the end.
+///
+/// As with the other passes, a definition only opens a function scope if its
+/// `SymbolInformation` has been seen in the same or an earlier document.
+pub fn build_call_graph_streaming<I, E>(
+    project_root: &str,
+    documents: I,
+    options: &CallGraphOptions,
+) -> Result<CallGraph, E>
+where
+    I: IntoIterator<Item = Result<Document, E>>,
+{
+    let mut builder = StreamingGraphBuilder::new(project_root, options);
+    for doc in documents {
+        builder.add_document(&doc?);
+    }
+    Ok(builder.finish().into())
+}
 
-                        debug!(
-                            "Classified {} callee occurrences for {display_name}: {:?}",
-                            node.callee_occurrences.len(),
-                            sections
-                        );
-                    }
+/// Incrementally update a call graph after some documents changed.
+///
+/// Every node defined in one of `changed_docs` (matched by relative path) is
+/// removed together with its outgoing edges, then the documents are
+/// re-analyzed and their functions re-added. Calls from unchanged documents
+/// into re-added functions are preserved; if a called function disappeared,
+/// its callers keep pointing at an external placeholder (as a full rebuild
+/// would produce) when the symbol still looks like a function.
+///
+/// Pass the new version of every changed document. To handle a deleted file,
+/// pass a document with its relative path and no occurrences.
+///
+/// `options` must be the ones the graph was built with, or the re-analyzed
+/// documents won't match the rest of the graph.
+pub fn update_call_graph(
+    call_graph: &mut CallGraph,
+    project_root: &str,
+    changed_docs: &[Document],
+    options: &CallGraphOptions,
+) {
+    let call_graph = call_graph.nodes_mut();
+    let changed_paths: HashSet<&str> = changed_docs
+        .iter()
+        .map(|doc| doc.relative_path.trim_start_matches('/'))
+        .collect();
+
+    let removed: HashSet<String> = call_graph
+        .values()
+        .filter(|node| {
+            !node.file_path.starts_with("external:")
+                && changed_paths.contains(node.relative_path.as_str())
+        })
+        .map(|node| node.symbol.clone())
+        .collect();
+
+    // Drop outgoing edges of removed nodes, remembering callers from
+    // unchanged documents so they can be re-attached.
+    let mut touched_externals: HashSet<String> = HashSet::new();
+    let mut orphan_callers: HashMap<String, HashSet<String>> = HashMap::new();
+    for symbol in &removed {
+        let Some(node) = call_graph.remove(symbol) else {
+            continue;
+        };
+        for callee in &node.callees {
+            if let Some(callee_node) = call_graph.get_mut(callee) {
+                callee_node.callers.remove(symbol);
+                if callee_node.file_path.starts_with("external:") {
+                    touched_externals.insert(callee.clone());
                 }
-            } else {
-                debug!("Failed to read file: {clean_path}");
             }
         }
+        let callers: HashSet<String> = node
+            .callers
+            .into_iter()
+            .filter(|caller| !removed.contains(caller))
+            .collect();
+        if !callers.is_empty() {
+            orphan_callers.insert(symbol.clone(), callers);
+        }
     }
 
-    // Fourth pass: Default unclassified callee occurrences to Inner
-    for node in call_graph.values_mut() {
-        for occurrence in &mut node.callee_occurrences {
-            if occurrence.location.is_none() {
-                occurrence.location = Some(CallLocation::Inner);
+    debug!(
+        "Incremental update: {} changed documents, {} nodes removed",
+        changed_paths.len(),
+        removed.len()
+    );
+
+    // Re-analyze the changed documents, seeded with the functions that are
+    // still defined elsewhere so calls into them are recognized.
+    let mut builder = StreamingGraphBuilder::new(project_root, options);
+    for node in call_graph.values() {
+        if !node.file_path.starts_with("external:") {
+            builder.seed_function(&node.symbol, &node.display_name);
+        }
+    }
+    for doc in changed_docs {
+        builder.add_document(doc);
+    }
+    let patch = builder.finish();
+
+    for (symbol, mut node) in patch {
+        let is_external = node.file_path.starts_with("external:");
+        if let Some(existing) = call_graph.get_mut(&symbol) {
+            // Already known from an unchanged document or as a placeholder
+            if is_external {
+                existing.callers.extend(node.callers);
+                continue;
             }
+            node.callers.extend(existing.callers.drain());
+        } else if is_external && node.callers.is_empty() && !orphan_callers.contains_key(&symbol) {
+            continue;
         }
+        if let Some(callers) = orphan_callers.remove(&symbol) {
+            node.callers.extend(callers);
+        }
+        call_graph.insert(symbol, node);
     }
 
-    call_graph
-}
-
-/// Convert a SCIP symbol to a clean path format with display name
-pub fn symbol_to_path(symbol: &str, display_name: &str) -> String {
-    let mut parts = symbol.split_whitespace();
-    let mut s = symbol;
-    if parts.next() == Some("rust-analyzer") && parts.next() == Some("cargo") {
-        if let Some(rest) = symbol.find("cargo ").and_then(|pos| symbol.get(pos + 6..)) {
-            s = rest;
+    // Functions that were removed but are still called from unchanged code
+    for (symbol, callers) in orphan_callers {
+        if looks_like_external_function(&symbol) {
+            let mut node = external_function_node(&symbol, None);
+            node.callers = callers;
+            call_graph.insert(symbol, node);
+        } else {
+            for caller in callers {
+                if let Some(caller_node) = call_graph.get_mut(&caller) {
+                    caller_node.callees.remove(&symbol);
+                    caller_node
+                        .callee_occurrences
+                        .retain(|occurrence| occurrence.symbol != symbol);
+                }
+            }
         }
     }
 
-    if let Some(pos) = s.find(|c: char| c.is_ascii_digit()) {
-        if let Some(space_pos) = s[pos..].find(' ') {
-            s = s[(pos + space_pos + 1)..].trim();
+    // External placeholders that were only called from changed code
+    for symbol in touched_externals {
+        if call_graph
+            .get(&symbol)
+            .is_some_and(|node| node.callers.is_empty())
+        {
+            call_graph.remove(&symbol);
         }
     }
 
-    let path = s
-        .replace(['/', '#'], "::")
-        .replace("impl#", "")
-        .replace('`', "");
+    // Calls from unchanged code may now target functions of a different kind
+    assign_edge_kinds(call_graph);
+}
 
-    let path = generics_regex().replace_all(&path, "");
-    let path = path.trim_end_matches('.').trim_end_matches("()");
+/// State for building a call graph one document at a time
+struct StreamingGraphBuilder {
+    project_root: String,
+    options: CallGraphOptions,
+    /// Function-like symbols seen so far: symbol -> display_name
+    function_infos: HashMap<String, Option<String>>,
+    /// Symbols of `function_infos` that are types (with `include_types`)
+    type_symbols: HashSet<String>,
+    /// Functions known to be defined outside the documents being added
+    seeded: HashSet<String>,
+    symbol_to_def_file: HashMap<String, (String, String)>,
+    def_ranges: HashMap<String, Vec<i32>>,
+    external_candidates: HashSet<String>,
+    /// (caller, callee, line) in document/occurrence order
+    pending_calls: Vec<(String, String, i32, Option<i32>)>,
+    macro_calls: MacroCalls,
+    document_count: usize,
+}
 
-    if path.ends_with(&format!("::{display_name}")) {
-        path.to_string()
-    } else {
-        format!("{path}::{display_name}")
+impl StreamingGraphBuilder {
+    fn new(project_root: &str, options: &CallGraphOptions) -> Self {
+        StreamingGraphBuilder {
+            project_root: project_root.to_string(),
+            options: options.clone(),
+            function_infos: HashMap::new(),
+            type_symbols: HashSet::new(),
+            seeded: HashSet::new(),
+            symbol_to_def_file: HashMap::new(),
+            def_ranges: HashMap::new(),
+            external_candidates: HashSet::new(),
+            pending_calls: Vec::new(),
+            macro_calls: MacroCalls::default(),
+            document_count: 0,
+        }
     }
-}
 
-/// Generate a filtered call graph starting from specific entry points
-pub fn generate_filtered_call_graph(
-    call_graph: &HashMap<String, FunctionNode>,
-    entry_points: &[String],
-    max_depth: Option<usize>,
-) -> HashMap<String, FunctionNode> {
-    let mut filtered_graph: HashMap<String, FunctionNode> = HashMap::new();
-    let mut visited: HashSet<String> = HashSet::new();
+    fn seed_function(&mut self, symbol: &str, display_name: &str) {
+        self.function_infos
+            .insert(symbol.to_string(), Some(display_name.to_string()));
+        self.seeded.insert(symbol.to_string());
+    }
 
-    for entry in entry_points {
-        if let Some(node) = call_graph.get(entry) {
-            traverse_graph(
-                call_graph,
-                node,
-                &mut filtered_graph,
-                &mut visited,
-                0,
-                max_depth,
-            );
+    fn node_kind(&self, symbol: &str) -> NodeKind {
+        if self.type_symbols.contains(symbol) {
+            NodeKind::Type
+        } else {
+            NodeKind::Function
         }
     }
 
-    filtered_graph
-}
+    fn add_document(&mut self, doc: &Document) {
+        if self.options.is_excluded(&doc.relative_path) {
+            debug!("Skipping excluded document {}", doc.relative_path);
+            return;
+        }
+        self.document_count += 1;
 
-/// Recursively traverse the call graph to build a filtered view
-fn traverse_graph(
-    full_graph: &HashMap<String, FunctionNode>,
-    current_node: &FunctionNode,
-    filtered_graph: &mut HashMap<String, FunctionNode>,
-    visited: &mut HashSet<String>,
-    depth: usize,
-    max_depth: Option<usize>,
-) {
-    if max_depth.is_some_and(|max| depth >= max) || visited.contains(&current_node.symbol) {
-        return;
-    }
+        let rel_path = doc.relative_path.trim_start_matches('/');
+        let abs_path = format!("{}/{rel_path}", self.project_root);
 
-    visited.insert(current_node.symbol.clone());
+        for symbol in &doc.symbols {
+            if self.options.is_node_kind(symbol.kind) {
+                self.function_infos
+                    .insert(symbol.symbol.clone(), symbol.display_name.clone());
+                self.seeded.remove(&symbol.symbol);
+                if self.options.node_kind(symbol.kind) == NodeKind::Type {
+                    self.type_symbols.insert(symbol.symbol.clone());
+                }
+            }
+        }
 
-    if !filtered_graph.contains_key(&current_node.symbol) {
-        filtered_graph.insert(current_node.symbol.clone(), current_node.clone());
-    }
+        let mut ordered_occurrences: Vec<_> = doc.occurrences.iter().collect();
+        ordered_occurrences.sort_by(|a, b| {
+            let a_start = (a.range[0], a.range[1]);
+            let b_start = (b.range[0], b.range[1]);
+            a_start.cmp(&b_start)
+        });
 
-    for callee_symbol in &current_node.callees {
-        if let Some(callee_node) = full_graph.get(callee_symbol) {
-            if let Some(filtered_current) = filtered_graph.get_mut(&current_node.symbol) {
-                filtered_current.callees.insert(callee_symbol.clone());
+        let mut scope = CallerScope::default();
+        for occurrence in ordered_occurrences {
+            let is_definition = occurrence.symbol_roles.unwrap_or(0) & 1 == 1;
+            let symbol = &occurrence.symbol;
+            let line = occurrence.range.first().copied().unwrap_or(0);
+
+            if is_definition {
+                self.symbol_to_def_file
+                    .insert(symbol.clone(), (abs_path.clone(), rel_path.to_string()));
+                let is_function = self.function_infos.contains_key(symbol);
+                if is_function {
+                    self.def_ranges
+                        .insert(symbol.clone(), occurrence.range.clone());
+                }
+                if is_macro_symbol(symbol) {
+                    scope.define_macro(symbol);
+                } else if is_function {
+                    scope.define_function(symbol, line, &mut self.macro_calls);
+                }
+                continue;
             }
-            if !filtered_graph.contains_key(callee_symbol) {
-                filtered_graph.insert(callee_symbol.clone(), callee_node.clone());
+
+            if is_macro_symbol(symbol) {
+                scope.invoke_macro(symbol, line, &mut self.macro_calls);
             }
-            if let Some(filtered_callee) = filtered_graph.get_mut(callee_symbol) {
-                filtered_callee.callers.insert(current_node.symbol.clone());
+
+            let is_external = looks_like_external_function(symbol);
+            if !is_external && !self.function_infos.contains_key(symbol) {
+                continue;
             }
 
-            traverse_graph(
-                full_graph,
-                callee_node,
-                filtered_graph,
-                visited,
-                depth + 1,
-                max_depth,
-            );
+            if let Some(caller) = scope.call(symbol, &mut self.macro_calls) {
+                let column = occurrence.range.get(1).copied();
+                self.pending_calls
+                    .push((caller, symbol.clone(), line, column));
+            }
+            if is_external {
+                self.external_candidates.insert(symbol.clone());
+            }
         }
     }
-}
 
-/// Print a human-readable call graph summary
-pub fn print_call_graph_summary(call_graph: &HashMap<String, FunctionNode>) {
-    info!("Call Graph Summary");
-    info!("=================");
-    info!("Total functions: {}", call_graph.len());
+    fn finish(mut self) -> HashMap<String, FunctionNode> {
+        debug!("Streamed {} documents", self.document_count);
 
-    let mut entry_points = 0;
-    let mut leaf_functions = 0;
-    let mut internal_functions = 0;
+        // Calls written inside macro definitions belong to the invoking functions
+        let macro_calls = self.macro_calls.resolve();
+        self.pending_calls.extend(
+            macro_calls
+                .into_iter()
+                .map(|(caller, callee, line)| (caller, callee, line, None)),
+        );
 
-    for node in call_graph.values() {
-        if node.callers.is_empty() && !node.callees.is_empty() {
-            entry_points += 1;
-        } else if !node.callers.is_empty() && node.callees.is_empty() {
-            leaf_functions += 1;
-        } else if !node.callers.is_empty() && !node.callees.is_empty() {
-            internal_functions += 1;
-        }
-    }
+        let mut call_graph: HashMap<String, FunctionNode> = HashMap::new();
 
-    info!("Entry points (functions not called by others): {entry_points}");
-    info!("Leaf functions (functions that don't call others): {leaf_functions}");
-    info!("Internal functions: {internal_functions}");
+        // Local functions: function-like symbols with a definition in the index
+        for (symbol, display_name) in &self.function_infos {
+            if let Some((abs_path, rel_path)) = self.symbol_to_def_file.get(symbol) {
+                call_graph.insert(
+                    symbol.clone(),
+                    FunctionNode {
+                        symbol: symbol.clone(),
+                        display_name: display_name
+                            .clone()
+                            .unwrap_or_else(|| "unknown".to_string()),
+                        file_path: abs_path.clone(),
+                        relative_path: rel_path.clone(),
+                        range: self.def_ranges.remove(symbol).unwrap_or_default(),
+                        node_kind: self.node_kind(symbol),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
 
-    let mut functions_by_caller_count: Vec<_> = call_graph.values().collect();
-    functions_by_caller_count.sort_by(|a, b| b.callers.len().cmp(&a.callers.len()));
+        // External functions: everything function-like that isn't local.
+        // Seeded functions only matter if something here calls them.
+        let called: HashSet<&str> = self
+            .pending_calls
+            .iter()
+            .map(|(_, callee, _, _)| callee.as_str())
+            .collect();
+        let externals: Vec<String> = self
+            .function_infos
+            .keys()
+            .filter(|symbol| !self.seeded.contains(*symbol) || called.contains(symbol.as_str()))
+            .chain(self.external_candidates.iter())
+            .filter(|symbol| !call_graph.contains_key(*symbol))
+            .cloned()
+            .collect();
+        for symbol in externals {
+            let display_name = self.function_infos.get(&symbol).cloned().flatten();
+            let node = FunctionNode {
+                node_kind: self.node_kind(&symbol),
+                ..external_function_node(&symbol, display_name)
+            };
+            call_graph.insert(symbol, node);
+        }
+
+        debug!(
+            "Streaming: {} function nodes, {} pending calls",
+            call_graph.len(),
+            self.pending_calls.len()
+        );
+
+        for (caller, callee, line, column) in self.pending_calls {
+            if call_graph.contains_key(&callee) {
+                add_call_edge(&mut call_graph, &caller, &callee, line, column);
+            }
+        }
+
+        extract_function_bodies(
+            &mut call_graph,
+            self.options.parallelism,
+            &Monitor::default(),
+        );
+        default_call_locations(&mut call_graph);
+        assign_edge_kinds(&mut call_graph);
+
+        call_graph
+    }
+}
+
+/// Record a call from `caller` to `callee` at `line` and, if known, `column`
+/// (self-calls are ignored)
+fn add_call_edge(
+    call_graph: &mut HashMap<String, FunctionNode>,
+    caller: &str,
+    callee: &str,
+    line: i32,
+    column: Option<i32>,
+) {
+    if caller == callee {
+        return;
+    }
+
+    if let Some(caller_node) = call_graph.get_mut(caller) {
+        caller_node.callees.insert(callee.to_string());
+        caller_node.callee_occurrences.push(CalleeOccurrence {
+            symbol: callee.to_string(),
+            line,
+            column,
+            location: None,
+            edge_kind: None,
+        });
+    }
+
+    if let Some(callee_node) = call_graph.get_mut(callee) {
+        callee_node.callers.insert(caller.to_string());
+    }
+}
+
+/// Whether a symbol names a macro (SCIP macro descriptors end with `!`)
+fn is_macro_symbol(symbol: &str) -> bool {
+    symbol.ends_with('!')
+}
+
+/// Tracks which function or macro definition the occurrences of a document
+/// belong to while they are scanned in source order
+#[derive(Debug, Default)]
+struct CallerScope {
+    current_function: Option<String>,
+    current_macro: Option<String>,
+    /// Macro invoked outside any function; a function defined on the same
+    /// line (e.g. `my_test!(name)` expanding to `fn name()`) is its caller
+    pending_invocation: Option<(String, i32)>,
+}
+
+impl CallerScope {
+    fn define_function(&mut self, symbol: &str, line: i32, macro_calls: &mut MacroCalls) {
+        self.current_function = Some(symbol.to_string());
+        self.current_macro = None;
+        if let Some((macro_symbol, invocation_line)) = self.pending_invocation.take() {
+            if invocation_line == line {
+                macro_calls.invoke(symbol, &macro_symbol, line);
+            }
+        }
+    }
+
+    fn define_macro(&mut self, symbol: &str) {
+        self.current_function = None;
+        self.current_macro = Some(symbol.to_string());
+        self.pending_invocation = None;
+    }
+
+    fn invoke_macro(&mut self, symbol: &str, line: i32, macro_calls: &mut MacroCalls) {
+        // Occurrences carry no end position, so a macro's definition is only
+        // known to end at its first invocation (recursive invocations add no
+        // calls of their own)
+        if self.current_macro.as_deref() == Some(symbol) {
+            self.current_macro = None;
+        }
+
+        if let Some(macro_symbol) = &self.current_macro {
+            macro_calls.add_body_call(macro_symbol, symbol);
+        } else if let Some(caller) = &self.current_function {
+            macro_calls.invoke(caller, symbol, line);
+        } else {
+            self.pending_invocation = Some((symbol.to_string(), line));
+        }
+    }
+
+    /// Caller to attribute a call to `callee` to, if it is made directly by a
+    /// function (calls inside a macro definition are recorded for later)
+    fn call(&self, callee: &str, macro_calls: &mut MacroCalls) -> Option<String> {
+        if let Some(macro_symbol) = &self.current_macro {
+            macro_calls.add_body_call(macro_symbol, callee);
+            return None;
+        }
+        self.current_function.clone()
+    }
+}
+
+/// Calls found inside macro definitions, and where those macros are invoked
+#[derive(Debug, Default)]
+struct MacroCalls {
+    /// macro -> callees referenced in its definition
+    body_calls: HashMap<String, Vec<String>>,
+    /// (caller, macro, line) for each macro invocation inside a function
+    invocations: Vec<(String, String, i32)>,
+}
+
+impl MacroCalls {
+    fn add_body_call(&mut self, macro_symbol: &str, callee: &str) {
+        let callees = self.body_calls.entry(macro_symbol.to_string()).or_default();
+        if !callees.iter().any(|existing| existing == callee) {
+            callees.push(callee.to_string());
+        }
+    }
+
+    fn invoke(&mut self, caller: &str, macro_symbol: &str, line: i32) {
+        self.invocations
+            .push((caller.to_string(), macro_symbol.to_string(), line));
+    }
+
+    /// (caller, callee, line) for every call made through a macro invocation,
+    /// following macros that invoke other macros. Calls are attributed to the
+    /// invoking function at the line of the invocation.
+    fn resolve(&self) -> Vec<(String, String, i32)> {
+        let mut calls = Vec::new();
+        for (caller, macro_symbol, line) in &self.invocations {
+            let mut visited: HashSet<&str> = HashSet::new();
+            let mut stack = vec![macro_symbol.as_str()];
+            while let Some(current) = stack.pop() {
+                if !visited.insert(current) {
+                    continue;
+                }
+                for callee in self.body_calls.get(current).into_iter().flatten() {
+                    if is_macro_symbol(callee) {
+                        stack.push(callee);
+                    }
+                    calls.push((caller.clone(), callee.clone(), *line));
+                }
+            }
+        }
+        calls
+    }
+}
+
+/// Heuristic for occurrences of functions that have no `SymbolInformation`
+/// in the index (typically calls into dependencies).
+fn looks_like_external_function(symbol: &str) -> bool {
+    (symbol.contains("()") || symbol.ends_with("."))
+        && (symbol.contains('#') || symbol.contains('/'))
+        && !symbol.contains("().(")
+}
+
+/// Create a placeholder node for a function defined outside the project
+///
+/// Symbols of other indexers take their name and module path (the relative
+/// path) from their [`SymbolScheme`](crate::symbol_scheme::SymbolScheme);
+/// rust-analyzer symbols keep the `crate::module::function` path.
+fn external_function_node(symbol: &str, display_name: Option<String>) -> FunctionNode {
+    let (default_name, relative_path) = match scheme_for_symbol(symbol) {
+        Some(scheme) if !RustAnalyzerScheme.matches(symbol) => {
+            (scheme.display_name(symbol), scheme.module_path(symbol))
+        }
+        _ => (None, extract_path_info_from_symbol(symbol).0),
+    };
+    let display_name = display_name
+        .or(default_name)
+        .unwrap_or_else(|| extract_display_name_from_symbol(symbol));
+
+    FunctionNode {
+        symbol: symbol.to_string(),
+        display_name,
+        file_path: format!("external:{}", symbol),
+        relative_path,
+        ..Default::default()
+    }
+}
+
+/// Read each node's source file and extract its body, classifying callee
+/// occurrences against the parsed requires/ensures sections.
+///
+/// Nodes are grouped by file so that each file is read only once, and files
+/// are processed in parallel according to `parallelism` (see
+/// [`CallGraphOptions::parallelism`]). Returns the number of files read.
+fn extract_function_bodies(
+    call_graph: &mut HashMap<String, FunctionNode>,
+    parallelism: Option<usize>,
+    monitor: &Monitor,
+) -> usize {
+    let mut nodes_by_file: HashMap<PathBuf, Vec<&mut FunctionNode>> = HashMap::new();
+    for node in call_graph.values_mut() {
+        if !node.range.is_empty() {
+            let clean_path = PathBuf::from(node.file_path.trim_start_matches("file://"));
+            nodes_by_file.entry(clean_path).or_default().push(node);
+        }
+    }
+
+    let files: Vec<(PathBuf, Vec<&mut FunctionNode>)> = nodes_by_file.into_iter().collect();
+    let file_count = files.len();
+    debug!("Extracting function bodies from {file_count} files");
+
+    monitor
+        .progress()
+        .start("Extracting bodies", Some(file_count as u64));
+    let reads = AtomicUsize::new(0);
+    let process = |(clean_path, nodes): (PathBuf, Vec<&mut FunctionNode>)| {
+        if monitor.is_cancelled() {
+            return;
+        }
+        monitor.progress().advance(1);
+        debug!("Trying to read file: {}", clean_path.display());
+        reads.fetch_add(1, Ordering::Relaxed);
+        let Ok(contents) = fs::read_to_string(&clean_path) else {
+            debug!("Failed to read file: {}", clean_path.display());
+            return;
+        };
+        let lines: Vec<String> = contents.lines().map(str::to_string).collect();
+        // Parsed on first use, only if some node lacks an end position
+        let item_spans = OnceCell::new();
+        for node in nodes {
+            extract_function_body(node, &lines, &item_spans);
+        }
+    };
+
+    match parallelism {
+        Some(1) => files.into_iter().for_each(process),
+        Some(threads) => match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+            Ok(pool) => pool.install(|| files.into_par_iter().for_each(process)),
+            Err(e) => {
+                debug!("Failed to build thread pool ({e}), extracting bodies sequentially");
+                files.into_iter().for_each(process);
+            }
+        },
+        None => files.into_par_iter().for_each(process),
+    }
+    monitor.progress().finish();
+
+    reads.into_inner()
+}
+
+/// Extract a node's body from the lines of its source file and classify its
+/// callee occurrences.
+///
+/// The body ends where the SCIP range ends if it has an end line (4
+/// elements), else where verus_syn finds the enclosing item ends (`item_spans`
+/// holds the file's parsed spans), and only if the file does not parse where
+/// the braces balance.
+fn extract_function_body(
+    node: &mut FunctionNode,
+    lines: &[String],
+    item_spans: &OnceCell<Option<ItemSpans>>,
+) {
+    let display_name = &node.display_name;
+    let range = &node.range;
+    debug!("Function: {display_name}, Range: {range:?}");
+
+    let start_line = node.range[0] as usize;
+    if start_line >= lines.len() {
+        return;
+    }
+
+    let end = match node.range[..] {
+        [_, _, end_line, end_column] if end_line as usize > start_line => Some(ItemEnd {
+            line: end_line as usize,
+            column: end_column as usize,
+        }),
+        _ => item_spans
+            .get_or_init(|| ItemSpans::parse(&lines.join("\n")))
+            .as_ref()
+            .and_then(|spans| spans.end_of_item_at(start_line)),
+    };
+    let full_body = match end {
+        Some(end) if end.line < lines.len() => body_until(lines, start_line, end),
+        _ => brace_counted_body(lines, start_line),
+    };
+
+    let body_len = full_body.len();
+    node.start_line = Some(start_line + 1);
+    node.end_line = Some(start_line + full_body.lines().count().max(1));
+    node.body = Some(full_body.clone());
+    let (doc, attributes) = leading_doc_and_attributes(lines, start_line);
+    node.doc = doc;
+    node.attributes = attributes;
+    (node.requires_text, node.ensures_text) = spec_clauses(&full_body);
+    node.signature = signature_text(&full_body);
+    let display_name = &node.display_name;
+    debug!("Extracted body for {display_name}, length: {body_len}");
+
+    let sections = parse_function_sections(&full_body, node.range[0]);
+    let spawned = spawn_spans(&full_body, node.range[0]);
+    for occurrence in &mut node.callee_occurrences {
+        occurrence.location = Some(classify_call_location(
+            occurrence.line,
+            occurrence.column,
+            &sections,
+        ));
+        if is_spawned_call(&spawned, occurrence.line, occurrence.column) {
+            occurrence.edge_kind = Some(EdgeKind::Spawn);
+        }
+    }
+    node.spec_refs = node
+        .callee_occurrences
+        .iter()
+        .filter(|occurrence| occurrence.location != Some(CallLocation::Inner))
+        .map(|occurrence| occurrence.symbol.clone())
+        .collect();
+
+    debug!(
+        "Classified {} callee occurrences for {display_name}: {:?}",
+        node.callee_occurrences.len(),
+        sections
+    );
+}
+
+/// Lines from `start_line` up to the character column of `end`
+fn body_until(lines: &[String], start_line: usize, end: ItemEnd) -> String {
+    let mut body_lines: Vec<&str> = lines[start_line..end.line]
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let last = &lines[end.line];
+    let cut = last
+        .char_indices()
+        .nth(end.column)
+        .map_or(last.len(), |(i, _)| i);
+    body_lines.push(&last[..cut]);
+    body_lines.join("\n")
+}
+
+/// Lines from `start_line` until the braces opened after it balance (or the
+/// declaration ends with `;`)
+fn brace_counted_body(lines: &[String], start_line: usize) -> String {
+    let mut body_lines = Vec::new();
+    let mut open_braces = 0;
+    let mut found_first_brace = false;
+
+    body_lines.push(lines[start_line].as_str());
+
+    for (line_idx, line) in lines.iter().enumerate().skip(start_line) {
+        if line_idx == start_line {
+            if line.contains('{') {
+                found_first_brace = true;
+                open_braces = line.matches('{').count();
+                open_braces = open_braces.saturating_sub(line.matches('}').count());
+            } else if line.trim_end().ends_with(';') {
+                // Bodiless item, e.g. a constant or trait method declaration
+                break;
+            }
+            continue;
+        }
+
+        if !found_first_brace {
+            if line.contains('{') {
+                found_first_brace = true;
+                open_braces = line.matches('{').count();
+                open_braces = open_braces.saturating_sub(line.matches('}').count());
+            }
+            body_lines.push(line);
+            if !found_first_brace && line.trim_end().ends_with(';') {
+                break;
+            }
+        } else {
+            open_braces += line.matches('{').count();
+            open_braces = open_braces.saturating_sub(line.matches('}').count());
+            body_lines.push(line);
+            if open_braces == 0 {
+                break;
+            }
+        }
+    }
+
+    body_lines.join("\n")
+}
+
+/// Doc comment and attributes directly above the line `start_line`.
+///
+/// Scans upward over `///` lines and (possibly multi-line) `#[...]`
+/// attributes, stopping at the first blank line, plain comment or code. The
+/// doc comment is returned without its slashes, attributes with whitespace
+/// collapsed, both in source order.
+pub(crate) fn leading_doc_and_attributes(
+    lines: &[String],
+    start_line: usize,
+) -> (Option<String>, Vec<String>) {
+    let mut doc_lines = Vec::new();
+    let mut attributes = Vec::new();
+    // Lines of an attribute spanning several lines, collected bottom-up
+    let mut pending: Vec<&str> = Vec::new();
+
+    for line in lines[..start_line.min(lines.len())].iter().rev() {
+        let trimmed = line.trim();
+        if !pending.is_empty() {
+            if trimmed.is_empty() {
+                break;
+            }
+            pending.push(trimmed);
+            if trimmed.starts_with("#[") {
+                let text = pending.drain(..).rev().collect::<Vec<_>>().join(" ");
+                attributes.push(text.split_whitespace().collect::<Vec<_>>().join(" "));
+            }
+            continue;
+        }
+        if let Some(text) = trimmed.strip_prefix("///") {
+            doc_lines.push(text.strip_prefix(' ').unwrap_or(text));
+        } else if trimmed.starts_with("#[") {
+            attributes.push(trimmed.to_string());
+        } else if trimmed.ends_with(']') && !trimmed.starts_with("//") {
+            // Last line of a multi-line attribute
+            pending.push(trimmed);
+        } else {
+            break;
+        }
+    }
+
+    doc_lines.reverse();
+    attributes.reverse();
+    let doc = (!doc_lines.is_empty()).then(|| doc_lines.join("\n"));
+    (doc, attributes)
+}
+
+/// Default unclassified callee occurrences to Inner
+fn default_call_locations(call_graph: &mut HashMap<String, FunctionNode>) {
+    for node in call_graph.values_mut() {
+        for occurrence in &mut node.callee_occurrences {
+            if occurrence.location.is_none() {
+                occurrence.location = Some(CallLocation::Inner);
+            }
+        }
+    }
+}
+
+/// Classify every callee occurrence by the declaration kinds of its caller
+/// and callee. Functions without an extracted body count as exec, as in the
+/// D3 export. Calls found in spawned tasks while extracting bodies stay
+/// [`EdgeKind::Spawn`].
+pub(crate) fn assign_edge_kinds(call_graph: &mut HashMap<String, FunctionNode>) {
+    // Type nodes have no declaration kind; references to and from them
+    // keep no edge kind
+    let decl_kinds: HashMap<String, Option<DeclKind>> = call_graph
+        .values()
+        .map(|node| {
+            let kind = (!node.is_type()).then(|| node_decl_kind(node));
+            (node.symbol.clone(), kind)
+        })
+        .collect();
+
+    for node in call_graph.values_mut() {
+        let caller_kind = decl_kinds[&node.symbol];
+        for occurrence in &mut node.callee_occurrences {
+            if occurrence.edge_kind == Some(EdgeKind::Spawn) {
+                continue;
+            }
+            let callee_kind = decl_kinds
+                .get(&occurrence.symbol)
+                .copied()
+                .unwrap_or(Some(DeclKind::Exec));
+            occurrence.edge_kind = caller_kind
+                .zip(callee_kind)
+                .map(|(caller, callee)| EdgeKind::new(caller, callee));
+        }
+    }
+}
+
+/// Declaration kind of a node, detected from its body (exec if unknown)
+fn node_decl_kind(node: &FunctionNode) -> DeclKind {
+    node.body
+        .as_deref()
+        .map(detect_decl_kind)
+        .unwrap_or(DeclKind::Exec)
+}
+
+/// Keep only the calls whose [`EdgeKind`] is in `edge_kinds`, and the
+/// functions taking part in at least one of them.
+///
+/// For example, `&[EdgeKind::ProofToProof]` yields the proof-to-lemma
+/// subgraph.
+pub fn filter_by_edge_kinds(call_graph: &CallGraph, edge_kinds: &[EdgeKind]) -> CallGraph {
+    let mut kept_edges: HashSet<(&str, &str)> = HashSet::new();
+    for node in call_graph.values() {
+        for occurrence in &node.callee_occurrences {
+            let matches = occurrence
+                .edge_kind
+                .is_some_and(|kind| edge_kinds.contains(&kind));
+            if matches && call_graph.contains_key(&occurrence.symbol) {
+                kept_edges.insert((node.symbol.as_str(), occurrence.symbol.as_str()));
+            }
+        }
+    }
+
+    let kept_nodes: HashSet<&str> = kept_edges
+        .iter()
+        .flat_map(|(caller, callee)| [*caller, *callee])
+        .collect();
+
+    kept_nodes
+        .iter()
+        .map(|symbol| {
+            let mut node = call_graph[*symbol].clone();
+            node.callees
+                .retain(|callee| kept_edges.contains(&(*symbol, callee.as_str())));
+            node.callers
+                .retain(|caller| kept_edges.contains(&(caller.as_str(), *symbol)));
+            node.callee_occurrences
+                .retain(|occurrence| kept_edges.contains(&(*symbol, occurrence.symbol.as_str())));
+            (symbol.to_string(), node)
+        })
+        .collect::<HashMap<_, _>>()
+        .into()
+}
+
+/// Convert a SCIP symbol to a clean path format with display name
+///
+/// The [`SymbolScheme`] matching the symbol's indexer decides the format;
+/// unknown schemes fall back to rust-analyzer style `::` paths. Paths are
+/// labels for humans; graph nodes are identified across runs by
+/// [`crate::export_d3::stable_node_id`].
+pub fn symbol_to_path(symbol: &str, display_name: &str) -> String {
+    scheme_for_symbol(symbol)
+        .unwrap_or(&RustAnalyzerScheme)
+        .to_path(symbol, display_name)
+}
+
+/// Generate a filtered call graph starting from specific entry points
+pub fn generate_filtered_call_graph(
+    call_graph: &CallGraph,
+    entry_points: &[String],
+    max_depth: Option<usize>,
+) -> CallGraph {
+    let mut filtered_graph: HashMap<String, FunctionNode> = HashMap::new();
+    let mut visited: HashSet<String> = HashSet::new();
+
+    for entry in entry_points {
+        if let Some(node) = call_graph.get(entry) {
+            traverse_graph(
+                call_graph,
+                node,
+                &mut filtered_graph,
+                &mut visited,
+                0,
+                max_depth,
+            );
+        }
+    }
+
+    filtered_graph.into()
+}
+
+/// Recursively traverse the call graph to build a filtered view
+fn traverse_graph(
+    full_graph: &CallGraph,
+    current_node: &FunctionNode,
+    filtered_graph: &mut HashMap<String, FunctionNode>,
+    visited: &mut HashSet<String>,
+    depth: usize,
+    max_depth: Option<usize>,
+) {
+    if max_depth.is_some_and(|max| depth >= max) || visited.contains(&current_node.symbol) {
+        return;
+    }
+
+    visited.insert(current_node.symbol.clone());
+
+    if !filtered_graph.contains_key(&current_node.symbol) {
+        filtered_graph.insert(current_node.symbol.clone(), current_node.clone());
+    }
+
+    for callee_symbol in &current_node.callees {
+        if let Some(callee_node) = full_graph.get(callee_symbol) {
+            if let Some(filtered_current) = filtered_graph.get_mut(&current_node.symbol) {
+                filtered_current.callees.insert(callee_symbol.clone());
+            }
+            if !filtered_graph.contains_key(callee_symbol) {
+                filtered_graph.insert(callee_symbol.clone(), callee_node.clone());
+            }
+            if let Some(filtered_callee) = filtered_graph.get_mut(callee_symbol) {
+                filtered_callee.callers.insert(current_node.symbol.clone());
+            }
+
+            traverse_graph(
+                full_graph,
+                callee_node,
+                filtered_graph,
+                visited,
+                depth + 1,
+                max_depth,
+            );
+        }
+    }
+}
+
+/// Generate a filtered call graph of everything that (transitively) calls the
+/// given targets, walking callers upward instead of callees downward.
+///
+/// This is the reverse of [`generate_filtered_call_graph`] and answers "what
+/// could break if I change this function". `max_depth` limits how many caller
+/// levels above the targets are included.
+pub fn generate_reverse_filtered_call_graph(
+    call_graph: &CallGraph,
+    targets: &[String],
+    max_depth: Option<usize>,
+) -> CallGraph {
+    let mut filtered_graph: HashMap<String, FunctionNode> = HashMap::new();
+    let mut visited: HashSet<String> = HashSet::new();
+
+    for target in targets {
+        if let Some(node) = call_graph.get(target) {
+            traverse_callers(
+                call_graph,
+                node,
+                &mut filtered_graph,
+                &mut visited,
+                0,
+                max_depth,
+            );
+        }
+    }
+
+    filtered_graph.into()
+}
+
+/// Recursively traverse callers to build a reverse filtered view
+fn traverse_callers(
+    full_graph: &CallGraph,
+    current_node: &FunctionNode,
+    filtered_graph: &mut HashMap<String, FunctionNode>,
+    visited: &mut HashSet<String>,
+    depth: usize,
+    max_depth: Option<usize>,
+) {
+    if max_depth.is_some_and(|max| depth >= max) || visited.contains(&current_node.symbol) {
+        return;
+    }
+
+    visited.insert(current_node.symbol.clone());
+
+    if !filtered_graph.contains_key(&current_node.symbol) {
+        filtered_graph.insert(current_node.symbol.clone(), current_node.clone());
+    }
+
+    for caller_symbol in &current_node.callers {
+        if let Some(caller_node) = full_graph.get(caller_symbol) {
+            if let Some(filtered_current) = filtered_graph.get_mut(&current_node.symbol) {
+                filtered_current.callers.insert(caller_symbol.clone());
+            }
+            if !filtered_graph.contains_key(caller_symbol) {
+                filtered_graph.insert(caller_symbol.clone(), caller_node.clone());
+            }
+            if let Some(filtered_caller) = filtered_graph.get_mut(caller_symbol) {
+                filtered_caller.callees.insert(current_node.symbol.clone());
+            }
+
+            traverse_callers(
+                full_graph,
+                caller_node,
+                filtered_graph,
+                visited,
+                depth + 1,
+                max_depth,
+            );
+        }
+    }
+}
+
+/// Symbols within `depth` calls of any of `seeds`, following callees,
+/// callers or both (breadth-first; `None` means no limit).
+///
+/// The seeds themselves are always included. With both directions a
+/// function is reached through any mix of calls and callers, so depth 2
+/// around a file includes the other callers of its callees.
+pub fn collect_neighborhood(
+    call_graph: &CallGraph,
+    seeds: &HashSet<String>,
+    include_callees: bool,
+    include_callers: bool,
+    depth: Option<usize>,
+) -> HashSet<String> {
+    let seed_symbols: Vec<&str> = seeds.iter().map(String::as_str).collect();
+    let neighborhood = neighborhood_by_depth(
+        call_graph,
+        &seed_symbols,
+        include_callees,
+        include_callers,
+        depth,
+    );
+    let mut included = seeds.clone();
+    included.extend(
+        neighborhood
+            .into_iter()
+            .map(|(node, _)| node.symbol.clone()),
+    );
+    included
+}
+
+/// Functions of the graph within `depth` calls of any of `seeds`, as
+/// [`collect_neighborhood`] finds them, but without the seeds and with
+/// their distance in calls.
+///
+/// They come in breadth-first order: closest first, and the new neighbors
+/// of each function by display name.
+pub fn neighborhood_by_depth<'a>(
+    call_graph: &'a CallGraph,
+    seeds: &[&str],
+    include_callees: bool,
+    include_callers: bool,
+    depth: Option<usize>,
+) -> Vec<(&'a FunctionNode, usize)> {
+    let mut visited: HashSet<&str> = seeds.iter().copied().collect();
+    let mut queue: VecDeque<(&FunctionNode, usize)> = seeds
+        .iter()
+        .filter_map(|symbol| call_graph.get(*symbol))
+        .map(|node| (node, 0))
+        .collect();
+    let mut found = Vec::new();
+
+    while let Some((node, current_depth)) = queue.pop_front() {
+        if depth.is_some_and(|max_depth| current_depth >= max_depth) {
+            continue;
+        }
+        let callees = node.callees.iter().filter(|_| include_callees);
+        let callers = node.callers.iter().filter(|_| include_callers);
+        let mut neighbors: Vec<&FunctionNode> = callees
+            .chain(callers)
+            .filter_map(|symbol| call_graph.get(symbol))
+            .filter(|neighbor| visited.insert(neighbor.symbol.as_str()))
+            .collect();
+        neighbors.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+        for neighbor in neighbors {
+            found.push((neighbor, current_depth + 1));
+            queue.push_back((neighbor, current_depth + 1));
+        }
+    }
+
+    found
+}
+
+/// Find distinct call chains from `from` to `to`.
+///
+/// Each path starts with `from`, ends with `to` and never visits a function
+/// twice. The `max_paths` shortest paths are returned, shortest first and
+/// sorted by symbol among equally long ones; `max_depth` limits the number of
+/// calls in a path. The search only descends into callees that can still
+/// reach `to` within the path length searched, so it stays cheap on large
+/// graphs.
+pub fn find_call_paths(
+    call_graph: &CallGraph,
+    from: &str,
+    to: &str,
+    max_paths: usize,
+    max_depth: Option<usize>,
+) -> Vec<Vec<String>> {
+    if max_paths == 0 || !call_graph.contains_key(from) || !call_graph.contains_key(to) {
+        return Vec::new();
+    }
+
+    // Distance (in calls) from each function to the target, walking callers
+    let mut distance_to_target: HashMap<&str, usize> = HashMap::new();
+    let mut queue = VecDeque::new();
+    distance_to_target.insert(to, 0);
+    queue.push_back(to);
+    while let Some(symbol) = queue.pop_front() {
+        let distance = distance_to_target[symbol];
+        for caller in &call_graph[symbol].callers {
+            if call_graph.contains_key(caller) && !distance_to_target.contains_key(caller.as_str())
+            {
+                distance_to_target.insert(caller, distance + 1);
+                queue.push_back(caller);
+            }
+        }
+    }
+
+    let Some(&shortest) = distance_to_target.get(from) else {
+        return Vec::new();
+    };
+    // A path never visits a function twice, and only functions that reach
+    // `to` can be on it
+    let longest = max_depth.map_or(distance_to_target.len() - 1, |max| {
+        max.min(distance_to_target.len() - 1)
+    });
+
+    // One search per path length, so the shortest paths are found first and
+    // the ones kept are the same as if all paths were sorted and truncated
+    let mut paths = Vec::new();
+    for calls in shortest..=longest {
+        let mut path = vec![from.to_string()];
+        let mut on_path: HashSet<String> = HashSet::from([from.to_string()]);
+        collect_call_paths(
+            call_graph,
+            to,
+            &distance_to_target,
+            calls,
+            max_paths,
+            &mut path,
+            &mut on_path,
+            &mut paths,
+        );
+        if paths.len() >= max_paths {
+            break;
+        }
+    }
+    paths
+}
+
+/// Depth-first search for [`find_call_paths`] of paths with exactly `calls`
+/// calls, visiting callees by symbol so paths come in sorted order
+#[allow(clippy::too_many_arguments)]
+fn collect_call_paths(
+    call_graph: &CallGraph,
+    to: &str,
+    distance_to_target: &HashMap<&str, usize>,
+    calls: usize,
+    max_paths: usize,
+    path: &mut Vec<String>,
+    on_path: &mut HashSet<String>,
+    paths: &mut Vec<Vec<String>>,
+) {
+    let current = path.last().expect("path always starts with `from`").clone();
+    if current == to {
+        if path.len() - 1 == calls {
+            paths.push(path.clone());
+        }
+        return;
+    }
+
+    let calls_so_far = path.len() - 1;
+    let mut next: Vec<&String> = call_graph[&current]
+        .callees
+        .iter()
+        .filter(|callee| !on_path.contains(*callee))
+        .filter(|callee| {
+            distance_to_target
+                .get(callee.as_str())
+                .is_some_and(|distance| calls_so_far + 1 + distance <= calls)
+        })
+        .collect();
+    next.sort();
+
+    for callee in next {
+        if paths.len() >= max_paths {
+            return;
+        }
+        path.push(callee.clone());
+        on_path.insert(callee.clone());
+        collect_call_paths(
+            call_graph,
+            to,
+            distance_to_target,
+            calls,
+            max_paths,
+            path,
+            on_path,
+            paths,
+        );
+        on_path.remove(callee);
+        path.pop();
+    }
+}
+
+/// Find the strongly connected components of a call graph.
+///
+/// Members of each component are sorted and components are returned largest
+/// first. Self-calls are not recorded as edges, so every component with more
+/// than one member is a cycle of mutually recursive functions. Type nodes
+/// (`CallGraphOptions::with_types`) are referenced, not called, and are left
+/// out.
+pub fn find_sccs(call_graph: &CallGraph) -> Vec<Vec<String>> {
+    // Sort symbols so the result doesn't depend on HashMap iteration order
+    let mut symbols: Vec<&str> = call_graph
+        .values()
+        .filter(|node| !node.is_type())
+        .map(|node| node.symbol.as_str())
+        .collect();
+    symbols.sort_unstable();
+
+    let mut graph: DiGraph<&str, ()> = DiGraph::new();
+    let indices: HashMap<&str, NodeIndex> = symbols
+        .iter()
+        .map(|symbol| (*symbol, graph.add_node(*symbol)))
+        .collect();
+
+    for symbol in &symbols {
+        for callee in &call_graph[*symbol].callees {
+            if let Some(&target) = indices.get(callee.as_str()) {
+                graph.add_edge(indices[symbol], target, ());
+            }
+        }
+    }
+
+    let mut sccs: Vec<Vec<String>> = tarjan_scc(&graph)
+        .into_iter()
+        .map(|component| {
+            let mut members: Vec<String> = component
+                .into_iter()
+                .map(|index| graph[index].to_string())
+                .collect();
+            members.sort();
+            members
+        })
+        .collect();
+    sccs.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    sccs
+}
+
+/// Collapse every recursion cycle (strongly connected component with more
+/// than one member, see [`find_sccs`]) into a single super-node.
+///
+/// The super-node stands in for its representative, the member called by
+/// most functions: it has the symbol `scc:<representative symbol>`, the
+/// display name `<representative> [N functions]`, the representative's
+/// location and body, and the member names as its doc comment. Calls into
+/// and out of the cycle are redirected to it; calls within it are dropped.
+/// Exporting the collapsed graph keeps cycles from exploding Graphviz
+/// layouts.
+pub fn collapse_sccs(call_graph: &CallGraph) -> CallGraph {
+    let sccs = find_sccs(call_graph);
+    let mut collapsed_into: HashMap<&str, String> = HashMap::new();
+    let mut super_nodes = Vec::new();
+    for members in sccs.iter().filter(|members| members.len() > 1) {
+        let representative = members
+            .iter()
+            .map(|member| &call_graph[member.as_str()])
+            .max_by(|a, b| {
+                a.callers
+                    .len()
+                    .cmp(&b.callers.len())
+                    .then_with(|| b.symbol.cmp(&a.symbol))
+            })
+            .expect("components are non-empty");
+        let symbol = format!("scc:{}", representative.symbol);
+        for member in members {
+            collapsed_into.insert(member.as_str(), symbol.clone());
+        }
+        let names: Vec<&str> = members
+            .iter()
+            .map(|member| call_graph[member.as_str()].display_name.as_str())
+            .collect();
+        super_nodes.push(FunctionNode {
+            symbol,
+            display_name: format!(
+                "{} [{} functions]",
+                representative.display_name,
+                members.len()
+            ),
+            file_path: representative.file_path.clone(),
+            relative_path: representative.relative_path.clone(),
+            range: representative.range.clone(),
+            body: representative.body.clone(),
+            doc: Some(format!("Recursion cycle of {}", names.join(", "))),
+            start_line: representative.start_line,
+            end_line: representative.end_line,
+            ..Default::default()
+        });
+    }
+    if super_nodes.is_empty() {
+        return call_graph.clone();
+    }
+    merge_nodes(call_graph, &collapsed_into, super_nodes)
+}
+
+/// Symbol prefix of the nodes made by [`collapse_external_crates`]
+pub const EXTERNAL_CRATE_PREFIX: &str = "external-crate:";
+
+/// Whether the node stands in for the functions of an external crate
+/// ([`collapse_external_crates`])
+pub fn is_external_crate_node(node: &FunctionNode) -> bool {
+    node.symbol.starts_with(EXTERNAL_CRATE_PREFIX)
+}
+
+/// Crate of an external function (a placeholder of a function outside the
+/// index), or `external` if its symbol names none; `None` for functions of
+/// the project
+pub(crate) fn external_crate_name(node: &FunctionNode) -> Option<String> {
+    let symbol = node.file_path.strip_prefix("external:")?;
+    let crate_name = parse_symbol(symbol)
+        .map(|parsed| parsed.package)
+        .filter(|package| !package.is_empty())
+        .unwrap_or_else(|| "external".to_string());
+    Some(crate_name)
+}
+
+/// Roll the external functions (placeholders of functions outside the
+/// index) up into one node per crate.
+///
+/// The node has the symbol `external-crate:<crate>` and the display name
+/// `<crate> (N fns)`; its doc comment lists the calls into the crate it
+/// replaces (`caller → callee`, shown in tooltips), so the detailed edges
+/// stay available. Graphs of projects calling into vstd or std shrink by
+/// thousands of nodes, and dependencies between crates become obvious.
+pub fn collapse_external_crates(call_graph: &CallGraph) -> CallGraph {
+    let mut crates: HashMap<String, Vec<&FunctionNode>> = HashMap::new();
+    for node in call_graph.values() {
+        if let Some(crate_name) = external_crate_name(node) {
+            crates.entry(crate_name).or_default().push(node);
+        }
+    }
+
+    let mut collapsed_into: HashMap<&str, String> = HashMap::new();
+    let mut crate_nodes = Vec::new();
+    for (crate_name, members) in crates {
+        let symbol = format!("{EXTERNAL_CRATE_PREFIX}{crate_name}");
+        for member in &members {
+            collapsed_into.insert(member.symbol.as_str(), symbol.clone());
+        }
+        let mut calls: Vec<String> = members
+            .iter()
+            .flat_map(|member| {
+                member.callers.iter().filter_map(|caller| {
+                    let caller = call_graph.get(caller)?;
+                    Some(format!("{} → {}", caller.display_name, member.display_name))
+                })
+            })
+            .collect();
+        calls.sort();
+        calls.dedup();
+        crate_nodes.push(FunctionNode {
+            display_name: format!("{} ({} fns)", crate_name, members.len()),
+            file_path: format!("external:{symbol}"),
+            relative_path: String::new(),
+            doc: Some(format!(
+                "External crate {}, calls:\n{}",
+                crate_name,
+                calls.join("\n")
+            )),
+            ..external_function_node(&symbol, None)
+        });
+    }
+    if crate_nodes.is_empty() {
+        return call_graph.clone();
+    }
+    merge_nodes(call_graph, &collapsed_into, crate_nodes)
+}
+
+/// Replace the nodes in `merged_into` by the super-nodes named there,
+/// redirecting their calls to the super-nodes; calls within a super-node
+/// are dropped
+pub(crate) fn merge_nodes(
+    call_graph: &CallGraph,
+    merged_into: &HashMap<&str, String>,
+    super_nodes: Vec<FunctionNode>,
+) -> CallGraph {
+    let target = |symbol: &str| {
+        merged_into
+            .get(symbol)
+            .cloned()
+            .unwrap_or_else(|| symbol.to_string())
+    };
+    let mut nodes: HashMap<String, FunctionNode> = super_nodes
+        .into_iter()
+        .map(|node| (node.symbol.clone(), node))
+        .collect();
+    for node in call_graph.values() {
+        let own = target(&node.symbol);
+        let callees: HashSet<String> = node
+            .callees
+            .iter()
+            .map(|callee| target(callee))
+            .filter(|callee| *callee != own)
+            .collect();
+        let callers: HashSet<String> = node
+            .callers
+            .iter()
+            .map(|caller| target(caller))
+            .filter(|caller| *caller != own)
+            .collect();
+        let spec_refs: HashSet<String> = node
+            .spec_refs
+            .iter()
+            .map(|callee| target(callee))
+            .filter(|callee| *callee != own)
+            .collect();
+        let occurrences = node
+            .callee_occurrences
+            .iter()
+            .map(|occurrence| CalleeOccurrence {
+                symbol: target(&occurrence.symbol),
+                ..occurrence.clone()
+            })
+            .filter(|occurrence| occurrence.symbol != own)
+            .collect::<Vec<_>>();
+
+        match nodes.get_mut(&own) {
+            Some(super_node) if own != node.symbol => {
+                super_node.callees.extend(callees);
+                super_node.callers.extend(callers);
+                super_node.spec_refs.extend(spec_refs);
+                super_node.callee_occurrences.extend(occurrences);
+            }
+            _ => {
+                nodes.insert(
+                    own,
+                    FunctionNode {
+                        callees,
+                        callers,
+                        spec_refs,
+                        callee_occurrences: occurrences,
+                        ..node.clone()
+                    },
+                );
+            }
+        }
+    }
+    nodes.into()
+}
+
+/// Calls in a graph, split by whether the caller is a test function
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallCounts {
+    pub production: usize,
+    pub test: usize,
+}
+
+/// Count the calls (distinct caller-callee pairs) made from production code
+/// and from test functions.
+///
+/// Functions only referenced from requires/ensures clauses (see
+/// [`FunctionNode::spec_refs`]) are not counted as called.
+pub fn count_calls_by_origin(call_graph: &CallGraph) -> CallCounts {
+    let mut counts = CallCounts::default();
+    for node in call_graph.values() {
+        let calls = node.calls().count();
+        if is_test_function(node) {
+            counts.test += calls;
+        } else {
+            counts.production += calls;
+        }
+    }
+    counts
+}
+
+/// Print a human-readable call graph summary
+///
+/// Callers are counted separately for production code and tests, so that
+/// heavily tested functions don't top the "most called" list. Functions only
+/// referenced from requires/ensures clauses are reported as spec references
+/// rather than calls.
+pub fn print_call_graph_summary(call_graph: &CallGraph) {
+    info!("Call Graph Summary");
+    info!("=================");
+    info!("Total functions: {}", call_graph.len());
+
+    let test_functions: HashSet<&str> = call_graph
+        .values()
+        .filter(|node| is_test_function(node))
+        .map(|node| node.symbol.as_str())
+        .collect();
+    info!("Test functions: {}", test_functions.len());
+
+    let mut entry_points = 0;
+    let mut leaf_functions = 0;
+    let mut internal_functions = 0;
+
+    for node in call_graph.values() {
+        let calls_any = node.calls().next().is_some();
+        if node.callers.is_empty() && calls_any {
+            entry_points += 1;
+        } else if !node.callers.is_empty() && !calls_any {
+            leaf_functions += 1;
+        } else if !node.callers.is_empty() && calls_any {
+            internal_functions += 1;
+        }
+    }
+
+    info!("Entry points (functions not called by others): {entry_points}");
+    info!("Leaf functions (functions that don't call others): {leaf_functions}");
+    info!("Internal functions: {internal_functions}");
+
+    let calls = count_calls_by_origin(call_graph);
+    info!("Calls from production code: {}", calls.production);
+    info!("Calls from tests: {}", calls.test);
+    let spec_refs: usize = call_graph.values().map(|node| node.spec_refs.len()).sum();
+    info!("Spec references (calls in requires/ensures): {spec_refs}");
+
+    let test_callers = |node: &FunctionNode| {
+        node.callers
+            .iter()
+            .filter(|caller| test_functions.contains(caller.as_str()))
+            .count()
+    };
+    let mut functions_by_caller_count: Vec<_> = call_graph
+        .values()
+        .map(|node| {
+            let tests = test_callers(node);
+            (node, node.callers.len() - tests, tests)
+        })
+        .collect();
+    functions_by_caller_count.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.2.cmp(&a.2)));
+
+    info!("\nMost called functions:");
+    for (node, production, tests) in functions_by_caller_count.iter().take(5) {
+        if !node.callers.is_empty() {
+            info!(
+                "  {} (called by {} production functions, {} tests)",
+                node.display_name, production, tests
+            );
+        }
+    }
+
+    let mut functions_by_callee_count: Vec<_> = call_graph
+        .values()
+        .map(|node| (node, node.calls().count()))
+        .collect();
+    functions_by_callee_count.sort_by(|a, b| b.1.cmp(&a.1));
+
+    info!("\nFunctions calling the most other functions:");
+    for (node, calls) in functions_by_callee_count.iter().take(5) {
+        if *calls > 0 {
+            info!("  {} (calls {} functions)", node.display_name, calls);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==========================================================================
+    // is_function_like tests - SCIP kind identification
+    // ==========================================================================
+
+    #[test]
+    fn test_is_function_like_method() {
+        assert!(is_function_like(6)); // Constructor
+        assert!(is_function_like(80)); // Method (Rust-specific)
+    }
+
+    #[test]
+    fn test_is_function_like_function() {
+        assert!(is_function_like(12)); // Function
+        assert!(is_function_like(17)); // Macro
+    }
+
+    #[test]
+    fn test_is_function_like_non_function() {
+        assert!(!is_function_like(0)); // Unknown
+        assert!(!is_function_like(26)); // TypeParameter
+        assert!(!is_function_like(100)); // Some other kind
+    }
+
+    // ==========================================================================
+    // CallGraphOptions tests
+    // ==========================================================================
+
+    #[test]
+    fn test_call_graph_options_default_matches_is_function_like() {
+        let options = CallGraphOptions::default();
+        for kind in 0..100 {
+            assert_eq!(options.is_node_kind(kind), is_function_like(kind), "{kind}");
+        }
+    }
+
+    #[test]
+    fn test_call_graph_options_custom_kinds() {
+        let options = CallGraphOptions::default().with_function_kinds([26]);
+        assert!(options.is_node_kind(26));
+        assert!(!options.is_node_kind(12));
+
+        // With kind 12 excluded, main() and helper() are no longer local nodes
+        let graph = build_call_graph_with_options(&streaming_test_index(), &options);
+        assert!(graph
+            .values()
+            .all(|node| node.file_path.starts_with("external:")));
+    }
+
+    #[test]
+    fn test_call_graph_options_include_constants() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(
+            project.path().join("lib.rs"),
+            "const LIMIT: u32 = 5;\nfn main() {\n    let x = LIMIT;\n}\n",
+        )
+        .unwrap();
+
+        let main = "rust-analyzer cargo demo 0.1.0 main().";
+        let limit = "rust-analyzer cargo demo 0.1.0 LIMIT.";
+        let mut constant = function_symbol(limit, "LIMIT");
+        constant.kind = CONSTANT_KINDS[0];
+        let index = ScipIndex {
+            metadata: crate::types::Metadata {
+                tool_info: crate::types::ToolInfo {
+                    name: "rust-analyzer".to_string(),
+                    version: "0.3".to_string(),
+                },
+                project_root: project.path().to_str().unwrap().to_string(),
+                text_document_encoding: 1,
+            },
+            documents: vec![Document {
+                language: "rust".to_string(),
+                relative_path: "lib.rs".to_string(),
+                occurrences: vec![
+                    occurrence(0, limit, Some(1)),
+                    occurrence(1, main, Some(1)),
+                    occurrence(2, limit, None),
+                ],
+                symbols: vec![constant, function_symbol(main, "main")],
+                position_encoding: 1,
+            }],
+        };
+
+        let without = build_call_graph(&index);
+        assert!(!without.contains_key(limit));
+
+        let options = CallGraphOptions::default().with_constants(true);
+        let with = build_call_graph_with_options(&index, &options);
+        assert!(with[main].callees.contains(limit));
+        assert_eq!(with[limit].body.as_deref(), Some("const LIMIT: u32 = 5;"));
+    }
+
+    #[test]
+    fn test_call_graph_options_include_types() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(
+            project.path().join("lib.rs"),
+            "struct Point {\n    x: u32,\n}\nfn main() {\n    let p = Point { x: 1 };\n}\n",
+        )
+        .unwrap();
+
+        let main = "rust-analyzer cargo demo 0.1.0 main().";
+        let point = "rust-analyzer cargo demo 0.1.0 Point#";
+        let mut structure = function_symbol(point, "Point");
+        structure.kind = 49;
+        let index = ScipIndex {
+            metadata: crate::types::Metadata {
+                project_root: project.path().to_str().unwrap().to_string(),
+                ..Default::default()
+            },
+            documents: vec![Document {
+                language: "rust".to_string(),
+                relative_path: "lib.rs".to_string(),
+                occurrences: vec![
+                    occurrence(0, point, Some(1)),
+                    occurrence(3, main, Some(1)),
+                    occurrence(4, point, None),
+                ],
+                symbols: vec![structure, function_symbol(main, "main")],
+                position_encoding: 1,
+            }],
+        };
+
+        assert!(!build_call_graph(&index).contains_key(point));
+
+        let options = CallGraphOptions::default().with_types(true);
+        let with = build_call_graph_with_options(&index, &options);
+        assert!(with[point].is_type());
+        assert_eq!(with[main].node_kind, NodeKind::Function);
+        assert!(with[main].callees.contains(point));
+        assert_eq!(with[main].callee_occurrences[0].edge_kind, None);
+
+        let d3 = crate::export_d3::call_graph_to_d3(
+            &with,
+            "",
+            &crate::node_filter::NodeFilters::default(),
+        );
+        assert_eq!(d3.links.len(), 1);
+        assert_eq!(
+            d3.links[0].link_type,
+            crate::export_d3::REFERENCES_LINK_TYPE
+        );
+        let without_types = crate::node_filter::NodeFilters::default()
+            .skip(crate::node_filter::NodeKindFilter(NodeKind::Type));
+        assert_eq!(without_types.apply(&with).len(), 1);
+    }
+
+    #[test]
+    fn test_call_graph_options_parallelism_same_bodies() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(
+            project.path().join("a.rs"),
+            "fn a() {\n    b();\n}\nfn c() {\n    b();\n}\n",
+        )
+        .unwrap();
+        std::fs::write(project.path().join("b.rs"), "fn b() {\n    42\n}\n").unwrap();
+
+        let symbol = |name: &str| format!("rust-analyzer cargo demo 0.1.0 {name}().");
+        let (a, b, c) = (symbol("a"), symbol("b"), symbol("c"));
+        let document = |path: &str, occurrences, symbols| Document {
+            language: "rust".to_string(),
+            relative_path: path.to_string(),
+            occurrences,
+            symbols,
+            position_encoding: 1,
+        };
+        let index = ScipIndex {
+            metadata: crate::types::Metadata {
+                tool_info: crate::types::ToolInfo {
+                    name: "rust-analyzer".to_string(),
+                    version: "0.3".to_string(),
+                },
+                project_root: project.path().to_str().unwrap().to_string(),
+                text_document_encoding: 1,
+            },
+            documents: vec![
+                document(
+                    "a.rs",
+                    vec![
+                        occurrence(0, &a, Some(1)),
+                        occurrence(1, &b, None),
+                        occurrence(3, &c, Some(1)),
+                        occurrence(4, &b, None),
+                    ],
+                    vec![function_symbol(&a, "a"), function_symbol(&c, "c")],
+                ),
+                document(
+                    "b.rs",
+                    vec![occurrence(0, &b, Some(1))],
+                    vec![function_symbol(&b, "b")],
+                ),
+            ],
+        };
+
+        let sequential =
+            build_call_graph_with_options(&index, &CallGraphOptions::default().with_parallelism(1));
+        let parallel =
+            build_call_graph_with_options(&index, &CallGraphOptions::default().with_parallelism(4));
+
+        assert_eq!(
+            sequential[&c].body.as_deref(),
+            Some("fn c() {\n    b();\n}")
+        );
+        assert_eq!(sequential[&b].body.as_deref(), Some("fn b() {\n    42\n}"));
+        for (symbol, node) in &sequential {
+            assert_eq!(node.body, parallel[symbol].body, "{symbol}");
+        }
+    }
+
+    #[test]
+    fn test_call_graph_options_exclude_globs_match_paths() {
+        let options = CallGraphOptions::default().with_exclude_globs([
+            "**/benches/**".to_string(),
+            "src/generated_*.rs".to_string(),
+            "vendor/**".to_string(),
+        ]);
+
+        assert!(options.is_excluded("rust/protocol/benches/bench.rs"));
+        assert!(options.is_excluded("benches/a/b.rs"));
+        assert!(options.is_excluded("/src/generated_tables.rs"));
+        assert!(options.is_excluded("vendor/dep/src/lib.rs"));
+        assert!(!options.is_excluded("src/generated/tables.rs"));
+        assert!(!options.is_excluded("src/lib.rs"));
+        assert!(!CallGraphOptions::default().is_excluded("vendor/dep/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_call_graph_options_fingerprint() {
+        let default = CallGraphOptions::default();
+        assert_eq!(
+            default.fingerprint(),
+            CallGraphOptions::default().fingerprint()
+        );
+        assert_eq!(
+            default.fingerprint(),
+            default.clone().with_parallelism(1).fingerprint()
+        );
+        for other in [
+            default.clone().with_types(true),
+            default.clone().with_constants(true),
+            default.clone().with_function_kinds([12]),
+            default
+                .clone()
+                .with_exclude_globs(["vendor/**".to_string()]),
+        ] {
+            assert_ne!(default.fingerprint(), other.fingerprint(), "{other:?}");
+        }
+    }
+
+    #[test]
+    fn test_call_graph_options_exclude_globs_drop_documents() {
+        let options = CallGraphOptions::default().with_exclude_globs(["src/util.?s".to_string()]);
+        let graph = build_call_graph_with_options(&streaming_test_index(), &options);
+
+        assert!(graph.contains_key("rust-analyzer cargo demo 0.1.0 app/main()."));
+        assert!(graph
+            .values()
+            .all(|node| node.relative_path != "src/util.rs"));
+    }
+
+    #[test]
+    fn test_build_call_graph_monitored_cancellation() {
+        let index = streaming_test_index();
+        let monitor = Monitor::default();
+        let graph = build_call_graph_monitored(&index, &CallGraphOptions::default(), &monitor);
+        assert_eq!(graph.unwrap().len(), build_call_graph(&index).len());
+
+        monitor.cancellation().cancel();
+        let graph = build_call_graph_monitored(&index, &CallGraphOptions::default(), &monitor);
+        assert_eq!(graph.err(), Some(Cancelled));
+    }
+
+    // ==========================================================================
+    // extract_function_bodies tests - body extraction I/O
+    // ==========================================================================
+
+    fn node_at(file_path: &str, name: &str, line: i32) -> FunctionNode {
+        FunctionNode {
+            symbol: format!("rust-analyzer cargo demo 0.1.0 {name}()."),
+            display_name: name.to_string(),
+            file_path: file_path.to_string(),
+            relative_path: "lib.rs".to_string(),
+            range: vec![line, 0, 5],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_extract_function_bodies_reads_each_file_once() {
+        let project = tempfile::tempdir().unwrap();
+        let file = project.path().join("lib.rs");
+        let source: String = (0..20)
+            .map(|i| format!("fn f{i}() {{\n    {i}\n}}\n"))
+            .collect();
+        std::fs::write(&file, source).unwrap();
+        let file_path = format!("file://{}", file.display());
+
+        let mut graph: HashMap<String, FunctionNode> = (0..20)
+            .map(|i| {
+                let node = node_at(&file_path, &format!("f{i}"), i * 3);
+                (node.symbol.clone(), node)
+            })
+            .collect();
+
+        let reads = extract_function_bodies(&mut graph, Some(1), &Monitor::default());
+        assert_eq!(reads, 1);
+        assert!(graph.values().all(|node| node.body.is_some()));
+        assert_eq!(
+            graph["rust-analyzer cargo demo 0.1.0 f7()."]
+                .body
+                .as_deref(),
+            Some("fn f7() {\n    7\n}")
+        );
+
+        // Parallel extraction reads the file once as well
+        assert_eq!(
+            extract_function_bodies(&mut graph, None, &Monitor::default()),
+            1
+        );
+    }
+
+    #[test]
+    fn test_extract_function_bodies_missing_file() {
+        let mut graph: HashMap<String, FunctionNode> = ["f0", "f1"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let node = node_at("file:///nonexistent/scip-core/missing.rs", name, i as i32);
+                (node.symbol.clone(), node)
+            })
+            .collect();
+
+        assert_eq!(
+            extract_function_bodies(&mut graph, Some(1), &Monitor::default()),
+            1
+        );
+        assert!(graph.values().all(|node| node.body.is_none()));
+    }
+
+    #[test]
+    fn test_leading_doc_and_attributes() {
+        let lines: Vec<String> = [
+            "use vstd::prelude::*;",
+            "",
+            "// Not part of the doc comment",
+            "/// Adds two numbers.",
+            "///",
+            "///  Never overflows.",
+            "#[inline]",
+            "#[verifier::rlimit(",
+            "    50",
+            ")]",
+            "pub fn add(a: u8, b: u8) -> u8 {",
+            "    a + b",
+            "}",
+            "fn bare() {}",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+        let (doc, attributes) = leading_doc_and_attributes(&lines, 10);
+        assert_eq!(
+            doc.as_deref(),
+            Some("Adds two numbers.\n\n Never overflows.")
+        );
+        assert_eq!(attributes, vec!["#[inline]", "#[verifier::rlimit( 50 )]"]);
+
+        assert_eq!(leading_doc_and_attributes(&lines, 13), (None, Vec::new()));
+        assert_eq!(leading_doc_and_attributes(&lines, 0), (None, Vec::new()));
+    }
+
+    #[test]
+    fn test_extract_function_body_uses_spans() {
+        let to_lines = |source: &str| -> Vec<String> { source.lines().map(String::from).collect() };
+        let mut node = FunctionNode {
+            symbol: "tricky().".to_string(),
+            display_name: "tricky".to_string(),
+            file_path: "src/lib.rs".to_string(),
+            relative_path: "src/lib.rs".to_string(),
+            range: vec![2, 3, 9],
+            ..Default::default()
+        };
+        let tricky = "fn tricky() -> &'static str {\n    // a stray } in a comment\n    let s = \"}{\";\n    s\n}";
+
+        // Parsed span, inside a verus! block
+        let lines = to_lines(&format!(
+            "verus! {{\n#[inline]\n{tricky}\n}}\nfn after() {{}}\n"
+        ));
+        extract_function_body(&mut node, &lines, &OnceCell::new());
+        assert_eq!(node.body.as_deref(), Some(tricky));
+        assert_eq!(node.attributes, vec!["#[inline]"]);
+        assert_eq!((node.start_line, node.end_line), (Some(3), Some(7)));
+
+        // End position from the SCIP range, even though the file does not parse
+        let lines = to_lines(&format!("fn broken( {{\n\n{tricky} fn after() {{}}\n"));
+        node.range = vec![2, 3, 6, 1];
+        extract_function_body(&mut node, &lines, &OnceCell::new());
+        assert_eq!(node.body.as_deref(), Some(tricky));
+
+        // Brace counting as the last resort
+        node.range = vec![2, 3, 9];
+        extract_function_body(&mut node, &lines, &OnceCell::new());
+        assert_eq!(
+            node.body.as_deref(),
+            Some("fn tricky() -> &'static str {\n    // a stray } in a comment")
+        );
+    }
+
+    #[test]
+    fn test_spawn_edges() {
+        let source = [
+            "async fn serve() {",
+            "    let handle = tokio::spawn(async move { work().await });",
+            "    helper();",
+            "    let fut = async { background() };",
+            "}",
+        ];
+        let mut serve = node_at("file:///p/src/lib.rs", "serve", 0);
+        let callees = ["spawn", "work", "helper", "background"].map(|name| node_at("", name, 9));
+        for callee in &callees {
+            let (line, text) = source
+                .iter()
+                .enumerate()
+                .find(|(_, text)| text.contains(&format!("{}(", callee.display_name)))
+                .unwrap();
+            let column = text.find(&format!("{}(", callee.display_name)).unwrap();
+            serve.callees.insert(callee.symbol.clone());
+            serve.callee_occurrences.push(CalleeOccurrence {
+                symbol: callee.symbol.clone(),
+                line: line as i32,
+                column: Some(column as i32),
+                location: None,
+                edge_kind: None,
+            });
+        }
+        let lines: Vec<String> = source.iter().map(|line| line.to_string()).collect();
+        extract_function_body(&mut serve, &lines, &OnceCell::new());
+
+        let mut graph: HashMap<String, FunctionNode> = [serve]
+            .into_iter()
+            .chain(callees)
+            .map(|node| (node.symbol.clone(), node))
+            .collect();
+        assign_edge_kinds(&mut graph);
+        let serve = &graph["rust-analyzer cargo demo 0.1.0 serve()."];
+        let kind_of = |name: &str| {
+            serve
+                .callee_occurrences
+                .iter()
+                .find(|occurrence| extract_display_name_from_symbol(&occurrence.symbol) == name)
+                .and_then(|occurrence| occurrence.edge_kind)
+        };
+        assert_eq!(kind_of("work"), Some(EdgeKind::Spawn));
+        assert_eq!(kind_of("background"), Some(EdgeKind::Spawn));
+        assert_eq!(kind_of("helper"), Some(EdgeKind::ExecToExec));
+        assert_eq!(kind_of("spawn"), Some(EdgeKind::ExecToExec));
+    }
+
+    #[test]
+    fn test_spec_refs_separate_from_calls() {
+        let source = "fn f(x: u8) -> u8 requires pre(x) ensures post(x) { bar(x); post(x) }";
+        let mut f = node_at("file:///p/src/lib.rs", "f", 0);
+        let [pre, post, bar] = ["pre", "post", "bar"].map(|name| node_at("", name, 1));
+        let mut call = |callee: &FunctionNode, needle: &str, nth: usize| {
+            let column = source.match_indices(needle).nth(nth).unwrap().0 as i32;
+            f.callees.insert(callee.symbol.clone());
+            f.callee_occurrences.push(CalleeOccurrence {
+                symbol: callee.symbol.clone(),
+                line: 0,
+                column: Some(column),
+                location: None,
+                edge_kind: None,
+            });
+        };
+        call(&pre, "pre(", 0);
+        call(&post, "post(", 0);
+        call(&bar, "bar(", 0);
+        call(&post, "post(", 1);
+        extract_function_body(&mut f, &[source.to_string()], &OnceCell::new());
+
+        let sorted = |symbols: Vec<&String>| {
+            let mut names: Vec<String> = symbols
+                .into_iter()
+                .map(|symbol| extract_display_name_from_symbol(symbol))
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(sorted(f.spec_refs.iter().collect()), ["post", "pre"]);
+        assert_eq!(sorted(f.calls().collect()), ["bar", "post"]);
+
+        let call_graph: CallGraph = [f, pre, post, bar].into_iter().collect();
+        assert_eq!(count_calls_by_origin(&call_graph).production, 2);
+
+        let dot = crate::export_dot::generate_call_graph_dot_string(
+            &call_graph,
+            &crate::node_filter::NodeFilters::default(),
+        );
+        let edge = |callee: &str| {
+            dot.lines()
+                .find(|line| {
+                    line.contains(&format!(
+                        "-> \"rust-analyzer cargo demo 0.1.0 {callee}().\""
+                    ))
+                })
+                .unwrap()
+                .to_string()
+        };
+        assert!(edge("pre").ends_with("[style=dashed]"));
+        assert!(!edge("post").contains("dashed"));
+    }
+
+    #[test]
+    fn test_count_calls_by_origin() {
+        let mut helper = node_at("file:///p/src/lib.rs", "helper", 0);
+        let mut main = node_at("file:///p/src/lib.rs", "main", 5);
+        let mut check = node_at("file:///p/src/lib.rs", "check", 10);
+        check.attributes = vec!["#[test]".to_string()];
+        main.callees.insert(helper.symbol.clone());
+        check.callees.insert(helper.symbol.clone());
+        check.callees.insert(main.symbol.clone());
+        helper.callers.insert(main.symbol.clone());
+        let call_graph: CallGraph = [helper, main, check].into_iter().collect();
+
+        assert_eq!(
+            count_calls_by_origin(&call_graph),
+            CallCounts {
+                production: 1,
+                test: 2
+            }
+        );
+    }
+
+    // ==========================================================================
+    // Edge kind tests
+    // ==========================================================================
 
-    info!("\nMost called functions:");
-    for node in functions_by_caller_count.iter().take(5) {
-        if !node.callers.is_empty() {
-            info!(
-                "  {} (called by {} functions)",
-                node.display_name,
-                node.callers.len()
-            );
+    fn create_mixed_mode_graph() -> CallGraph {
+        // main (exec) -> lemma_a (proof) -> lemma_b (proof)
+        //                        lemma_a -> spec_c (spec)
+        let mut graph = HashMap::new();
+        for (name, body) in [
+            ("main", "fn main() {}"),
+            ("lemma_a", "proof fn lemma_a() {}"),
+            ("lemma_b", "proof fn lemma_b() {}"),
+            ("spec_c", "spec fn spec_c() -> bool { true }"),
+        ] {
+            let mut node = node_at("lib.rs", name, 0);
+            node.symbol = name.to_string();
+            node.body = Some(body.to_string());
+            graph.insert(name.to_string(), node);
         }
+        add_call_edge(&mut graph, "main", "lemma_a", 1, None);
+        add_call_edge(&mut graph, "lemma_a", "lemma_b", 2, None);
+        add_call_edge(&mut graph, "lemma_a", "spec_c", 3, None);
+        assign_edge_kinds(&mut graph);
+        graph.into()
     }
 
-    let mut functions_by_callee_count: Vec<_> = call_graph.values().collect();
-    functions_by_callee_count.sort_by(|a, b| b.callees.len().cmp(&a.callees.len()));
+    #[test]
+    fn test_assign_edge_kinds() {
+        let graph = create_mixed_mode_graph();
+        let kind_of = |caller: &str, callee: &str| {
+            graph[caller]
+                .callee_occurrences
+                .iter()
+                .find(|occurrence| occurrence.symbol == callee)
+                .and_then(|occurrence| occurrence.edge_kind)
+        };
 
-    info!("\nFunctions calling the most other functions:");
-    for node in functions_by_callee_count.iter().take(5) {
-        if !node.callees.is_empty() {
-            info!(
-                "  {} (calls {} functions)",
-                node.display_name,
-                node.callees.len()
-            );
-        }
+        assert_eq!(kind_of("main", "lemma_a"), Some(EdgeKind::ExecToProof));
+        assert_eq!(kind_of("lemma_a", "lemma_b"), Some(EdgeKind::ProofToProof));
+        assert_eq!(kind_of("lemma_a", "spec_c"), Some(EdgeKind::ProofToSpec));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_filter_by_edge_kinds_proof_subgraph() {
+        let graph = create_mixed_mode_graph();
+        let filtered = filter_by_edge_kinds(&graph, &[EdgeKind::ProofToProof]);
+
+        let mut symbols: Vec<&String> = filtered.keys().collect();
+        symbols.sort();
+        assert_eq!(symbols, vec!["lemma_a", "lemma_b"]);
+        assert_eq!(
+            filtered["lemma_a"].callees,
+            HashSet::from(["lemma_b".to_string()])
+        );
+        assert_eq!(filtered["lemma_a"].callee_occurrences.len(), 1);
+        assert!(filtered["lemma_a"].callers.is_empty());
+        assert!(filter_by_edge_kinds(&graph, &[EdgeKind::SpecToSpec]).is_empty());
+    }
 
     // ==========================================================================
-    // is_function_like tests - SCIP kind identification
+    // Macro attribution tests
     // ==========================================================================
 
-    #[test]
-    fn test_is_function_like_method() {
-        assert!(is_function_like(6)); // Constructor
-        assert!(is_function_like(80)); // Method (Rust-specific)
+    const VERUS_MACRO_FIXTURE: &str = "verus! {
+macro_rules! check {
+    ($e:expr) => { lemma_helper($e) };
+}
+proof fn lemma_helper(x: int) {
+}
+fn main() {
+    check!(1);
+}
+} // verus!
+";
+
+    fn verus_macro_index(project_root: &str) -> ScipIndex {
+        let verus = "rust-analyzer cargo builtin_macros 0.1.0 verus!";
+        let check = "rust-analyzer cargo demo 0.1.0 check!";
+        let helper = "rust-analyzer cargo demo 0.1.0 lemma_helper().";
+        let main = "rust-analyzer cargo demo 0.1.0 main().";
+        let mut check_symbol = function_symbol(check, "check");
+        check_symbol.kind = 17;
+
+        ScipIndex {
+            metadata: crate::types::Metadata {
+                tool_info: crate::types::ToolInfo {
+                    name: "verus-analyzer".to_string(),
+                    version: "0.3".to_string(),
+                },
+                project_root: project_root.to_string(),
+                text_document_encoding: 1,
+            },
+            documents: vec![Document {
+                language: "rust".to_string(),
+                relative_path: "lib.rs".to_string(),
+                occurrences: vec![
+                    occurrence(0, verus, None),
+                    occurrence(1, check, Some(1)),
+                    occurrence(2, helper, None),
+                    occurrence(4, helper, Some(1)),
+                    occurrence(6, main, Some(1)),
+                    occurrence(7, check, None),
+                ],
+                symbols: vec![
+                    check_symbol,
+                    function_symbol(helper, "lemma_helper"),
+                    function_symbol(main, "main"),
+                ],
+                position_encoding: 1,
+            }],
+        }
     }
 
     #[test]
-    fn test_is_function_like_function() {
-        assert!(is_function_like(12)); // Function
-        assert!(is_function_like(17)); // Macro
+    fn test_macro_body_calls_attributed_to_invoking_function() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(project.path().join("lib.rs"), VERUS_MACRO_FIXTURE).unwrap();
+        let index = verus_macro_index(project.path().to_str().unwrap());
+
+        let main = "rust-analyzer cargo demo 0.1.0 main().";
+        let check = "rust-analyzer cargo demo 0.1.0 check!";
+        let helper = "rust-analyzer cargo demo 0.1.0 lemma_helper().";
+
+        let batch = build_call_graph(&index);
+        let streaming = build_call_graph_streaming(
+            &index.metadata.project_root,
+            index
+                .documents
+                .into_iter()
+                .map(Ok::<_, std::convert::Infallible>),
+            &CallGraphOptions::default(),
+        )
+        .unwrap();
+
+        for graph in [&batch, &streaming] {
+            assert!(graph[main].callees.contains(helper));
+            assert!(graph[main].callees.contains(check));
+            assert!(graph[check].callees.is_empty());
+            assert_eq!(graph[helper].callers, HashSet::from([main.to_string()]));
+
+            let occurrence = graph[main]
+                .callee_occurrences
+                .iter()
+                .find(|occurrence| occurrence.symbol == helper)
+                .unwrap();
+            assert_eq!(occurrence.line, 7);
+            assert_eq!(occurrence.edge_kind, Some(EdgeKind::ExecToProof));
+        }
     }
 
     #[test]
-    fn test_is_function_like_non_function() {
-        assert!(!is_function_like(0)); // Unknown
-        assert!(!is_function_like(26)); // TypeParameter
-        assert!(!is_function_like(100)); // Some other kind
+    fn test_macro_calls_nested_and_generated_functions() {
+        let mut macro_calls = MacroCalls::default();
+        let mut scope = CallerScope::default();
+
+        // macro_rules! inner { () => { a() } }
+        scope.define_macro("inner!");
+        assert_eq!(scope.call("a().", &mut macro_calls), None);
+        // macro_rules! outer { ($n:ident) => { fn $n() { inner!(); b() } } }
+        scope.define_macro("outer!");
+        scope.invoke_macro("inner!", 2, &mut macro_calls);
+        assert_eq!(scope.call("b().", &mut macro_calls), None);
+        // outer!(generated); at module level defines `generated` on line 5
+        scope.invoke_macro("outer!", 5, &mut macro_calls);
+        scope.define_function("generated().", 5, &mut macro_calls);
+
+        let mut callees: Vec<(String, String, i32)> = macro_calls.resolve();
+        callees.sort();
+        assert_eq!(
+            callees,
+            vec![
+                ("generated().".to_string(), "a().".to_string(), 5),
+                ("generated().".to_string(), "b().".to_string(), 5),
+                ("generated().".to_string(), "inner!".to_string(), 5),
+            ]
+        );
     }
 
     // ==========================================================================
@@ -737,6 +3116,42 @@ mod tests {
         assert!(sections.body_start_line.is_some());
     }
 
+    #[test]
+    fn test_parse_function_sections_one_liner_spans() {
+        let body = "fn f(x: u8) -> u8 requires pre(x) ensures post(x), decreases x { bar(x) }";
+        let column = |needle: &str| body.find(needle).unwrap() as i32;
+        let sections = parse_function_sections(body, 7);
+
+        let requires = sections.requires_span.unwrap();
+        assert_eq!(requires.start, (7, column("requires")));
+        assert_eq!(requires.end, (7, column(" ensures")));
+        let ensures = sections.ensures_span.unwrap();
+        assert_eq!(ensures.start, (7, column("ensures")));
+        assert_eq!(ensures.end, (7, column(" {")));
+
+        let classify = |needle| classify_call_location(7, Some(column(needle)), &sections);
+        assert_eq!(classify("pre("), CallLocation::Precondition);
+        assert_eq!(classify("post("), CallLocation::Postcondition);
+        assert_eq!(classify("bar("), CallLocation::Inner);
+
+        // A brace in a comment fools the line heuristic, not the spans
+        let body = "fn g() // {\n    requires pre()\n{ bar() }";
+        let sections = parse_function_sections(body, 0);
+        assert!(sections.requires_span.is_some());
+        assert_eq!(
+            classify_call_location(1, Some(13), &sections),
+            CallLocation::Precondition
+        );
+        let line_only = FunctionSections {
+            requires_span: None,
+            ..sections
+        };
+        assert_eq!(
+            classify_call_location(1, Some(13), &line_only),
+            CallLocation::Inner
+        );
+    }
+
     // ==========================================================================
     // classify_call_location tests
     // ==========================================================================
@@ -748,10 +3163,14 @@ mod tests {
             requires_range: Some((1, 2)),
             ensures_range: Some((3, 4)),
             body_start_line: Some(5),
+            ..Default::default()
         };
 
         // Line 6 is inside the body
-        assert_eq!(classify_call_location(6, &sections), CallLocation::Inner);
+        assert_eq!(
+            classify_call_location(6, None, &sections),
+            CallLocation::Inner
+        );
     }
 
     #[test]
@@ -761,14 +3180,15 @@ mod tests {
             requires_range: Some((1, 2)),
             ensures_range: Some((3, 4)),
             body_start_line: Some(5),
+            ..Default::default()
         };
 
         assert_eq!(
-            classify_call_location(1, &sections),
+            classify_call_location(1, None, &sections),
             CallLocation::Precondition
         );
         assert_eq!(
-            classify_call_location(2, &sections),
+            classify_call_location(2, None, &sections),
             CallLocation::Precondition
         );
     }
@@ -780,14 +3200,15 @@ mod tests {
             requires_range: Some((1, 2)),
             ensures_range: Some((3, 4)),
             body_start_line: Some(5),
+            ..Default::default()
         };
 
         assert_eq!(
-            classify_call_location(3, &sections),
+            classify_call_location(3, None, &sections),
             CallLocation::Postcondition
         );
         assert_eq!(
-            classify_call_location(4, &sections),
+            classify_call_location(4, None, &sections),
             CallLocation::Postcondition
         );
     }
@@ -799,11 +3220,18 @@ mod tests {
             requires_range: None,
             ensures_range: None,
             body_start_line: Some(1),
+            ..Default::default()
         };
 
         // Everything should be Inner when there are no specs
-        assert_eq!(classify_call_location(0, &sections), CallLocation::Inner);
-        assert_eq!(classify_call_location(5, &sections), CallLocation::Inner);
+        assert_eq!(
+            classify_call_location(0, None, &sections),
+            CallLocation::Inner
+        );
+        assert_eq!(
+            classify_call_location(5, None, &sections),
+            CallLocation::Inner
+        );
     }
 
     // ==========================================================================
@@ -846,7 +3274,7 @@ mod tests {
     // generate_filtered_call_graph tests
     // ==========================================================================
 
-    fn create_test_graph() -> HashMap<String, FunctionNode> {
+    fn create_test_graph() -> CallGraph {
         // Create a simple call graph: A -> B -> C -> D
         let mut graph = HashMap::new();
 
@@ -855,11 +3283,9 @@ mod tests {
             display_name: "func_a".to_string(),
             file_path: "test.rs".to_string(),
             relative_path: "test.rs".to_string(),
-            callers: HashSet::new(),
             callees: HashSet::from(["B".to_string()]),
-            callee_occurrences: Vec::new(),
             range: vec![0],
-            body: None,
+            ..Default::default()
         };
 
         let node_b = FunctionNode {
@@ -869,9 +3295,8 @@ mod tests {
             relative_path: "test.rs".to_string(),
             callers: HashSet::from(["A".to_string()]),
             callees: HashSet::from(["C".to_string()]),
-            callee_occurrences: Vec::new(),
             range: vec![10],
-            body: None,
+            ..Default::default()
         };
 
         let node_c = FunctionNode {
@@ -881,9 +3306,8 @@ mod tests {
             relative_path: "test.rs".to_string(),
             callers: HashSet::from(["B".to_string()]),
             callees: HashSet::from(["D".to_string()]),
-            callee_occurrences: Vec::new(),
             range: vec![20],
-            body: None,
+            ..Default::default()
         };
 
         let node_d = FunctionNode {
@@ -892,10 +3316,8 @@ mod tests {
             file_path: "test.rs".to_string(),
             relative_path: "test.rs".to_string(),
             callers: HashSet::from(["C".to_string()]),
-            callees: HashSet::new(),
-            callee_occurrences: Vec::new(),
             range: vec![30],
-            body: None,
+            ..Default::default()
         };
 
         graph.insert("A".to_string(), node_a);
@@ -903,7 +3325,7 @@ mod tests {
         graph.insert("C".to_string(), node_c);
         graph.insert("D".to_string(), node_d);
 
-        graph
+        graph.into()
     }
 
     #[test]
@@ -965,4 +3387,571 @@ mod tests {
         assert!(filtered.contains_key("C"));
         assert!(filtered.contains_key("D"));
     }
+
+    // ==========================================================================
+    // generate_reverse_filtered_call_graph tests
+    // ==========================================================================
+
+    #[test]
+    fn test_generate_reverse_filtered_call_graph_no_depth_limit() {
+        let graph = create_test_graph();
+        let filtered = generate_reverse_filtered_call_graph(&graph, &["C".to_string()], None);
+
+        // C is called by B, which is called by A; D is not a caller
+        assert_eq!(filtered.len(), 3);
+        assert!(filtered.contains_key("A"));
+        assert!(filtered.contains_key("B"));
+        assert!(filtered.contains_key("C"));
+        assert!(!filtered.contains_key("D"));
+    }
+
+    #[test]
+    fn test_generate_reverse_filtered_call_graph_with_depth_1() {
+        let graph = create_test_graph();
+        let filtered = generate_reverse_filtered_call_graph(&graph, &["D".to_string()], Some(1));
+
+        // Should only include D and its direct caller C
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered.contains_key("C"));
+        assert!(filtered.contains_key("D"));
+        assert!(filtered["C"].callees.contains("D"));
+    }
+
+    #[test]
+    fn test_generate_reverse_filtered_call_graph_nonexistent_target() {
+        let graph = create_test_graph();
+        let filtered =
+            generate_reverse_filtered_call_graph(&graph, &["NONEXISTENT".to_string()], None);
+
+        assert!(filtered.is_empty());
+    }
+
+    #[test]
+    fn test_collect_neighborhood() {
+        let graph = create_test_graph();
+        let seeds = HashSet::from(["B".to_string()]);
+        let sorted = |symbols: HashSet<String>| {
+            let mut symbols: Vec<String> = symbols.into_iter().collect();
+            symbols.sort();
+            symbols
+        };
+
+        assert_eq!(
+            sorted(collect_neighborhood(&graph, &seeds, true, true, Some(1))),
+            ["A", "B", "C"]
+        );
+        assert_eq!(
+            sorted(collect_neighborhood(&graph, &seeds, true, true, Some(2))),
+            ["A", "B", "C", "D"]
+        );
+        assert_eq!(
+            sorted(collect_neighborhood(&graph, &seeds, true, false, None)),
+            ["B", "C", "D"]
+        );
+        assert_eq!(
+            sorted(collect_neighborhood(&graph, &seeds, false, true, Some(0))),
+            ["B"]
+        );
+    }
+
+    #[test]
+    fn test_neighborhood_by_depth() {
+        let graph = create_test_graph();
+        let neighborhood = |seeds: &[&str], callees, callers, depth| {
+            neighborhood_by_depth(&graph, seeds, callees, callers, depth)
+                .into_iter()
+                .map(|(node, depth)| (node.symbol.as_str(), depth))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            neighborhood(&["A"], true, false, None),
+            [("B", 1), ("C", 2), ("D", 3)]
+        );
+        assert_eq!(
+            neighborhood(&["D"], false, true, Some(2)),
+            [("C", 1), ("B", 2)]
+        );
+        assert_eq!(
+            neighborhood(&["B", "C"], true, true, Some(1)),
+            [("A", 1), ("D", 1)]
+        );
+        assert!(neighborhood(&["unknown"], true, true, None).is_empty());
+    }
+
+    // ==========================================================================
+    // build_call_graph_streaming tests
+    // ==========================================================================
+
+    fn occurrence(line: i32, symbol: &str, roles: Option<i32>) -> crate::types::Occurrence {
+        crate::types::Occurrence {
+            range: vec![line, 0, 5],
+            symbol: symbol.to_string(),
+            symbol_roles: roles,
+        }
+    }
+
+    fn function_symbol(symbol: &str, name: &str) -> crate::types::Symbol {
+        crate::types::Symbol {
+            symbol: symbol.to_string(),
+            kind: 12,
+            display_name: Some(name.to_string()),
+            documentation: None,
+            signature_documentation: Default::default(),
+            enclosing_symbol: None,
+        }
+    }
+
+    fn streaming_test_index() -> ScipIndex {
+        let main = "rust-analyzer cargo demo 0.1.0 app/main().";
+        let helper = "rust-analyzer cargo demo 0.1.0 util/helper().";
+        let external = "rust-analyzer cargo std 1.0.0 vec/Vec#push().";
+
+        ScipIndex {
+            metadata: crate::types::Metadata {
+                tool_info: crate::types::ToolInfo {
+                    name: "rust-analyzer".to_string(),
+                    version: "0.3".to_string(),
+                },
+                project_root: "/nonexistent".to_string(),
+                text_document_encoding: 1,
+            },
+            documents: vec![
+                Document {
+                    language: "rust".to_string(),
+                    relative_path: "src/app.rs".to_string(),
+                    occurrences: vec![
+                        // Deliberately unsorted
+                        occurrence(3, helper, None),
+                        occurrence(1, main, Some(1)),
+                        occurrence(4, external, None),
+                    ],
+                    symbols: vec![function_symbol(main, "main")],
+                    position_encoding: 1,
+                },
+                Document {
+                    language: "rust".to_string(),
+                    relative_path: "src/util.rs".to_string(),
+                    occurrences: vec![occurrence(0, helper, Some(1))],
+                    symbols: vec![function_symbol(helper, "helper")],
+                    position_encoding: 1,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_build_call_graph_streaming_matches_batch() {
+        let index = streaming_test_index();
+        let batch = build_call_graph(&index);
+
+        let documents = index.documents.into_iter().map(Ok::<_, String>);
+        let streamed = build_call_graph_streaming(
+            &index.metadata.project_root,
+            documents,
+            &CallGraphOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(batch.len(), 3);
+        assert_eq!(
+            batch.keys().collect::<HashSet<_>>(),
+            streamed.keys().collect::<HashSet<_>>()
+        );
+        for (symbol, node) in &batch {
+            let other = &streamed[symbol];
+            assert_eq!(node.display_name, other.display_name, "{symbol}");
+            assert_eq!(node.file_path, other.file_path, "{symbol}");
+            assert_eq!(node.relative_path, other.relative_path, "{symbol}");
+            assert_eq!(node.callers, other.callers, "{symbol}");
+            assert_eq!(node.callees, other.callees, "{symbol}");
+            assert_eq!(node.range, other.range, "{symbol}");
+            let lines = |n: &FunctionNode| -> Vec<i32> {
+                n.callee_occurrences.iter().map(|o| o.line).collect()
+            };
+            assert_eq!(lines(node), lines(other), "{symbol}");
+        }
+
+        let main = &streamed["rust-analyzer cargo demo 0.1.0 app/main()."];
+        assert_eq!(main.callees.len(), 2);
+        assert!(main
+            .callee_occurrences
+            .iter()
+            .all(|o| o.location == Some(CallLocation::Inner)));
+    }
+
+    #[test]
+    fn test_build_call_graph_streaming_propagates_errors() {
+        let documents = vec![Err::<Document, _>("bad document".to_string())];
+        let result = build_call_graph_streaming("/root", documents, &CallGraphOptions::default());
+        assert_eq!(result.unwrap_err(), "bad document");
+    }
+
+    // ==========================================================================
+    // update_call_graph tests
+    // ==========================================================================
+
+    fn assert_same_graph(
+        expected: &HashMap<String, FunctionNode>,
+        actual: &HashMap<String, FunctionNode>,
+    ) {
+        assert_eq!(
+            expected.keys().collect::<HashSet<_>>(),
+            actual.keys().collect::<HashSet<_>>()
+        );
+        for (symbol, node) in expected {
+            let other = &actual[symbol];
+            assert_eq!(node.file_path, other.file_path, "{symbol}");
+            assert_eq!(node.callers, other.callers, "{symbol}");
+            assert_eq!(node.callees, other.callees, "{symbol}");
+            assert_eq!(node.range, other.range, "{symbol}");
+        }
+    }
+
+    #[test]
+    fn test_update_call_graph_matches_full_rebuild() {
+        let mut graph = build_call_graph(&streaming_test_index());
+
+        // main() now calls helper() on a different line and no longer calls push()
+        let main = "rust-analyzer cargo demo 0.1.0 app/main().";
+        let helper = "rust-analyzer cargo demo 0.1.0 util/helper().";
+        let new_app = || Document {
+            language: "rust".to_string(),
+            relative_path: "src/app.rs".to_string(),
+            occurrences: vec![occurrence(2, main, Some(1)), occurrence(6, helper, None)],
+            symbols: vec![function_symbol(main, "main")],
+            position_encoding: 1,
+        };
+
+        let mut expected_index = streaming_test_index();
+        expected_index.documents[0] = new_app();
+        let expected = build_call_graph(&expected_index);
+
+        update_call_graph(
+            &mut graph,
+            "/nonexistent",
+            &[new_app()],
+            &CallGraphOptions::default(),
+        );
+
+        assert_same_graph(&expected, &graph);
+        assert_eq!(graph[main].callee_occurrences.len(), 1);
+        assert_eq!(graph[main].callee_occurrences[0].line, 6);
+    }
+
+    #[test]
+    fn test_update_call_graph_with_options_matches_full_rebuild() {
+        let project = tempfile::tempdir().unwrap();
+        let project_root = project.path().to_str().unwrap();
+        std::fs::write(
+            project.path().join("lib.rs"),
+            "const LIMIT: u32 = 5;\nfn main() {\n    let x = LIMIT;\n}\n",
+        )
+        .unwrap();
+
+        let main = "rust-analyzer cargo demo 0.1.0 main().";
+        let limit = "rust-analyzer cargo demo 0.1.0 LIMIT.";
+        let lib = |uses_limit: bool| {
+            let mut constant = function_symbol(limit, "LIMIT");
+            constant.kind = CONSTANT_KINDS[0];
+            let mut occurrences = vec![occurrence(0, limit, Some(1)), occurrence(1, main, Some(1))];
+            if uses_limit {
+                occurrences.push(occurrence(2, limit, None));
+            }
+            Document {
+                language: "rust".to_string(),
+                relative_path: "lib.rs".to_string(),
+                occurrences,
+                symbols: vec![constant, function_symbol(main, "main")],
+                position_encoding: 1,
+            }
+        };
+        let index = |uses_limit: bool| ScipIndex {
+            metadata: crate::types::Metadata {
+                project_root: project_root.to_string(),
+                ..Default::default()
+            },
+            documents: vec![lib(uses_limit)],
+        };
+
+        let options = CallGraphOptions::default().with_constants(true);
+        let mut graph = build_call_graph_with_options(&index(false), &options);
+        let expected = build_call_graph_with_options(&index(true), &options);
+
+        update_call_graph(&mut graph, project_root, &[lib(true)], &options);
+
+        assert_same_graph(&expected, &graph);
+        assert!(graph[main].callees.contains(limit));
+    }
+
+    #[test]
+    fn test_update_call_graph_keeps_callers_from_unchanged_documents() {
+        let mut graph = build_call_graph(&streaming_test_index());
+        let main = "rust-analyzer cargo demo 0.1.0 app/main().";
+        let helper = "rust-analyzer cargo demo 0.1.0 util/helper().";
+
+        // Re-analyze util.rs with helper() moved down a few lines
+        let new_util = Document {
+            language: "rust".to_string(),
+            relative_path: "src/util.rs".to_string(),
+            occurrences: vec![occurrence(5, helper, Some(1))],
+            symbols: vec![function_symbol(helper, "helper")],
+            position_encoding: 1,
+        };
+        update_call_graph(
+            &mut graph,
+            "/nonexistent",
+            &[new_util],
+            &CallGraphOptions::default(),
+        );
+
+        assert_eq!(graph[helper].range[0], 5);
+        assert!(graph[helper].callers.contains(main));
+        assert!(graph[main].callees.contains(helper));
+    }
+
+    #[test]
+    fn test_update_call_graph_deleted_document() {
+        let mut graph = build_call_graph(&streaming_test_index());
+        let main = "rust-analyzer cargo demo 0.1.0 app/main().";
+        let helper = "rust-analyzer cargo demo 0.1.0 util/helper().";
+        let external = "rust-analyzer cargo std 1.0.0 vec/Vec#push().";
+
+        let deleted_app = Document {
+            language: "rust".to_string(),
+            relative_path: "src/app.rs".to_string(),
+            occurrences: vec![],
+            symbols: vec![],
+            position_encoding: 1,
+        };
+        update_call_graph(
+            &mut graph,
+            "/nonexistent",
+            &[deleted_app],
+            &CallGraphOptions::default(),
+        );
+
+        assert!(!graph.contains_key(main));
+        assert!(!graph.contains_key(external));
+        assert!(graph[helper].callers.is_empty());
+    }
+
+    // ==========================================================================
+    // find_call_paths tests
+    // ==========================================================================
+
+    /// A -> B -> C -> D plus a shortcut A -> C
+    fn create_diamond_graph() -> CallGraph {
+        let mut graph = create_test_graph();
+        graph.add_edge("A", "C");
+        graph
+    }
+
+    #[test]
+    fn test_find_call_paths_shortest_first() {
+        let graph = create_diamond_graph();
+        let paths = find_call_paths(&graph, "A", "D", 10, None);
+
+        assert_eq!(paths, vec![vec!["A", "C", "D"], vec!["A", "B", "C", "D"],]);
+    }
+
+    #[test]
+    fn test_find_call_paths_max_paths() {
+        let graph = create_diamond_graph();
+        let paths = find_call_paths(&graph, "A", "D", 1, None);
+
+        assert_eq!(paths, vec![vec!["A", "C", "D"]]);
+    }
+
+    #[test]
+    fn test_find_call_paths_keeps_shortest_when_truncating() {
+        // A -> B -> D is as short as A -> C -> D; A -> B -> C -> D is longer
+        // but found first when descending into B
+        let mut graph = create_diamond_graph();
+        graph.add_edge("B", "D");
+
+        assert_eq!(
+            find_call_paths(&graph, "A", "D", 2, None),
+            vec![vec!["A", "B", "D"], vec!["A", "C", "D"]]
+        );
+    }
+
+    #[test]
+    fn test_find_call_paths_max_depth() {
+        let graph = create_diamond_graph();
+
+        assert_eq!(find_call_paths(&graph, "A", "D", 10, Some(2)).len(), 1);
+        assert!(find_call_paths(&graph, "A", "D", 10, Some(1)).is_empty());
+    }
+
+    #[test]
+    fn test_find_call_paths_unreachable() {
+        let graph = create_test_graph();
+
+        assert!(find_call_paths(&graph, "D", "A", 10, None).is_empty());
+        assert!(find_call_paths(&graph, "A", "NONEXISTENT", 10, None).is_empty());
+    }
+
+    #[test]
+    fn test_find_call_paths_handles_cycles() {
+        // A -> B -> C -> D -> B
+        let mut graph = create_test_graph();
+        graph.add_edge("D", "B");
+
+        assert_eq!(
+            find_call_paths(&graph, "A", "D", 10, None),
+            vec![vec!["A", "B", "C", "D"]]
+        );
+    }
+
+    // ==========================================================================
+    // find_sccs tests
+    // ==========================================================================
+
+    #[test]
+    fn test_find_sccs_acyclic() {
+        let graph = create_test_graph();
+        let sccs = find_sccs(&graph);
+
+        assert_eq!(sccs.len(), 4);
+        assert!(sccs.iter().all(|component| component.len() == 1));
+    }
+
+    #[test]
+    fn test_find_sccs_detects_cycle() {
+        // A -> B -> C -> D -> B
+        let mut graph = create_test_graph();
+        graph.add_edge("D", "B");
+
+        let sccs = find_sccs(&graph);
+
+        assert_eq!(sccs[0], vec!["B", "C", "D"]);
+        assert_eq!(sccs[1], vec!["A"]);
+        assert_eq!(sccs.len(), 2);
+    }
+
+    #[test]
+    fn test_find_sccs_ignores_missing_callees() {
+        let mut graph = create_test_graph();
+        let mut node_d = graph["D"].clone();
+        node_d.callees.insert("not_in_graph".to_string());
+        graph.add_node(node_d);
+
+        assert_eq!(find_sccs(&graph).len(), 4);
+    }
+
+    #[test]
+    fn test_find_sccs_skips_type_nodes() {
+        // D -> LIMIT -> B would close a cycle if references were calls
+        let mut graph = create_test_graph();
+        graph.add_node(FunctionNode {
+            symbol: "LIMIT".to_string(),
+            node_kind: NodeKind::Type,
+            ..Default::default()
+        });
+        graph.add_edge("D", "LIMIT");
+        graph.add_edge("LIMIT", "B");
+
+        let sccs = find_sccs(&graph);
+        assert_eq!(sccs.len(), 4);
+        assert!(sccs.iter().flatten().all(|member| member != "LIMIT"));
+    }
+
+    #[test]
+    fn test_collapse_sccs() {
+        // A -> B -> C -> D -> B, D -> external
+        let mut graph = create_test_graph();
+        graph.add_edge("D", "B");
+        let mut node_d = graph["D"].clone();
+        node_d.callees.insert("external".to_string());
+        graph.add_node(node_d);
+
+        let collapsed = collapse_sccs(&graph);
+
+        let mut symbols: Vec<&str> = collapsed.keys().map(String::as_str).collect();
+        symbols.sort();
+        assert_eq!(symbols, ["A", "scc:B"]);
+
+        let cycle = &collapsed["scc:B"];
+        assert_eq!(
+            cycle.display_name,
+            format!("{} [3 functions]", graph["B"].display_name)
+        );
+        assert_eq!(cycle.callers, HashSet::from(["A".to_string()]));
+        assert_eq!(cycle.callees, HashSet::from(["external".to_string()]));
+        assert!(cycle
+            .doc
+            .as_deref()
+            .unwrap()
+            .starts_with("Recursion cycle of "));
+        assert_eq!(collapsed["A"].callees, HashSet::from(["scc:B".to_string()]));
+
+        let acyclic = create_test_graph();
+        assert_eq!(collapse_sccs(&acyclic).len(), acyclic.len());
+    }
+
+    #[test]
+    fn test_external_function_node_uses_symbol_scheme() {
+        let rust = external_function_node("rust-analyzer cargo std 1.0.0 vec/Vec#push().", None);
+        assert_eq!(rust.display_name, "Vec#push");
+        assert_eq!(rust.relative_path, "std::vec::Vec#push");
+
+        let typescript = external_function_node(
+            "scip-typescript npm lodash 4.17.21 `lodash.d.ts`/chunk().",
+            None,
+        );
+        assert_eq!(typescript.display_name, "chunk");
+        assert_eq!(typescript.relative_path, "lodash");
+
+        let python = external_function_node(
+            "scip-python python requests 2.31.0 `requests.api`/get().",
+            Some("requests.get".to_string()),
+        );
+        assert_eq!(python.display_name, "requests.get");
+        assert_eq!(python.relative_path, "requests.api");
+    }
+
+    #[test]
+    fn test_collapse_external_crates() {
+        // A -> B -> {vstd seq_lemma, vstd set_lemma}, A -> std push
+        let seq_lemma = "rust-analyzer cargo vstd 0.1.0 seq_lib/lemma_seq_len().";
+        let set_lemma = "rust-analyzer cargo vstd 0.1.0 set_lib/lemma_set_len().";
+        let push = "rust-analyzer cargo std 1.0.0 vec/Vec#push().";
+        let mut graph = create_test_graph();
+        for symbol in [seq_lemma, set_lemma, push] {
+            graph.add_node(external_function_node(symbol, None));
+        }
+        graph.add_edge("B", seq_lemma);
+        graph.add_edge("B", set_lemma);
+        graph.add_edge("A", push);
+        graph.add_edge("A", seq_lemma);
+
+        let collapsed = collapse_external_crates(&graph);
+
+        assert_eq!(collapsed.len(), graph.len() - 1);
+        let vstd = &collapsed["external-crate:vstd"];
+        assert!(is_external_crate_node(vstd));
+        assert_eq!(vstd.display_name, "vstd (2 fns)");
+        assert!(vstd.file_path.starts_with("external:"));
+        assert_eq!(
+            vstd.callers,
+            HashSet::from(["A".to_string(), "B".to_string()])
+        );
+        assert_eq!(
+            vstd.doc.as_deref(),
+            Some(
+                "External crate vstd, calls:\nfunc_a → lemma_seq_len\n\
+                 func_b → lemma_seq_len\nfunc_b → lemma_set_len"
+            )
+        );
+        assert_eq!(
+            collapsed["B"].callees,
+            HashSet::from(["C".to_string(), "external-crate:vstd".to_string()])
+        );
+        assert!(collapsed["A"].callees.contains("external-crate:std"));
+        assert!(collapsed.verify_integrity().is_empty());
+
+        let local = create_test_graph();
+        assert_eq!(collapse_external_crates(&local).len(), local.len());
+    }
 }
@@ -1,14 +1,204 @@
+//! In-process SVG drawing of a call graph, without Graphviz
+//!
+//! Functions are placed by a force-directed layout (Fruchterman-Reingold):
+//! every pair of functions pushes apart, every call pulls caller and callee
+//! together, and the moves shrink over a bounded number of iterations.
+//! Starting positions come from the order of the symbols, so the same graph
+//! always gives the same drawing. This keeps graphs of up to a few hundred
+//! functions readable; larger ones are better rendered from the DOT exports.
+
+use crate::node_filter::NodeFilters;
+use crate::types::{CallGraph, FunctionNode};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Write;
 
-pub struct FunctionNode {
-    symbol: String,
-    display_name: String,
-    body: Option<String>,
+/// Canvas and iteration budget of [`force_directed_layout`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgLayoutOptions {
+    pub width: f64,
+    pub height: f64,
+    /// Layout iterations; each costs time quadratic in the number of nodes
+    pub iterations: usize,
+    pub node_radius: f64,
+}
+
+impl Default for SvgLayoutOptions {
+    fn default() -> Self {
+        Self {
+            width: 1200.0,
+            height: 800.0,
+            iterations: 300,
+            node_radius: 18.0,
+        }
+    }
 }
 
-fn escape_html(input: &str) -> String {
+impl SvgLayoutOptions {
+    /// Draw on a `width` x `height` canvas
+    pub fn with_canvas(mut self, width: f64, height: f64) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+}
+
+/// Positions of `nodes` (centers, within the canvas of `options`), indexed
+/// like `nodes`; `edges` are pairs of indices into `nodes`.
+///
+/// The layout is computed unbounded and stretched to fill the canvas;
+/// nodes closer than their diameter (plus a gap) are then pushed apart.
+pub fn force_directed_layout(
+    nodes: usize,
+    edges: &[(usize, usize)],
+    options: &SvgLayoutOptions,
+) -> Vec<(f64, f64)> {
+    if nodes == 0 {
+        return Vec::new();
+    }
+    let margin = options.node_radius * 2.0;
+    let canvas_width = (options.width - 2.0 * margin).max(1.0);
+    let canvas_height = (options.height - 2.0 * margin).max(1.0);
+
+    // Start on a golden-angle spiral: spread out, and the same every run
+    let spread = canvas_width.min(canvas_height) / 2.0;
+    let golden_angle = std::f64::consts::PI * (3.0 - 5f64.sqrt());
+    let mut positions: Vec<(f64, f64)> = (0..nodes)
+        .map(|i| {
+            let radius = spread * ((i as f64 + 0.5) / nodes as f64).sqrt();
+            let angle = i as f64 * golden_angle;
+            (radius * angle.cos(), radius * angle.sin())
+        })
+        .collect();
+
+    // Ideal distance between nodes
+    let k = (canvas_width * canvas_height / nodes as f64).sqrt();
+    let initial_temperature = canvas_width.max(canvas_height) / 10.0;
+    for iteration in 0..options.iterations {
+        let mut displacement = vec![(0.0, 0.0); nodes];
+        for i in 0..nodes {
+            for j in (i + 1)..nodes {
+                let (dx, dy, distance) = offset(positions[i], positions[j], i + j);
+                let force = k * k / distance;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                displacement[i].0 += fx;
+                displacement[i].1 += fy;
+                displacement[j].0 -= fx;
+                displacement[j].1 -= fy;
+            }
+        }
+        for &(from, to) in edges {
+            if from == to {
+                continue;
+            }
+            let (dx, dy, distance) = offset(positions[from], positions[to], from + to);
+            let force = distance * distance / k;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            displacement[from].0 -= fx;
+            displacement[from].1 -= fy;
+            displacement[to].0 += fx;
+            displacement[to].1 += fy;
+        }
+
+        // Linear cooling: large moves first, fine adjustments at the end
+        let temperature =
+            initial_temperature * (1.0 - iteration as f64 / options.iterations as f64);
+        for (position, (dx, dy)) in positions.iter_mut().zip(displacement) {
+            // Weak gravity keeps disconnected parts from drifting apart
+            let (dx, dy) = (dx - position.0 * 0.1, dy - position.1 * 0.1);
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.0 += dx / length * step;
+                position.1 += dy / length * step;
+            }
+        }
+    }
+
+    // Stretch to fill the canvas (the layout comes out round, the canvas
+    // usually isn't), then push overlapping nodes apart and shrink back
+    // into the canvas if that pushed some out
+    fit(&mut positions, options, true);
+    for _ in 0..3 {
+        remove_overlaps(&mut positions, options.node_radius * 2.5);
+        fit(&mut positions, options, false);
+    }
+    positions
+}
+
+/// Scale `positions` into the canvas, leaving a margin for labels; only
+/// shrink unless `stretch`
+fn fit(positions: &mut [(f64, f64)], options: &SvgLayoutOptions, stretch: bool) {
+    let margin = options.node_radius * 2.0;
+    let width = (options.width - 2.0 * margin).max(1.0);
+    let height = (options.height - 2.0 * margin).max(1.0);
+    let (min_x, max_x) = bounds(positions.iter().map(|p| p.0));
+    let (min_y, max_y) = bounds(positions.iter().map(|p| p.1));
+    let scale = |extent: f64, available: f64| {
+        let scale = available / extent.max(1e-9);
+        if stretch {
+            scale
+        } else {
+            scale.min(1.0)
+        }
+    };
+    let (scale_x, scale_y) = (scale(max_x - min_x, width), scale(max_y - min_y, height));
+    let offset_x = margin + (width - (max_x - min_x) * scale_x) / 2.0;
+    let offset_y = margin + (height - (max_y - min_y) * scale_y) / 2.0;
+    for position in positions {
+        position.0 = offset_x + (position.0 - min_x) * scale_x;
+        position.1 = offset_y + (position.1 - min_y) * scale_y;
+    }
+}
+
+/// Push apart nodes closer than `min_distance`, for a bounded number of
+/// passes
+fn remove_overlaps(positions: &mut [(f64, f64)], min_distance: f64) {
+    for _ in 0..50 {
+        let mut moved = false;
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let (dx, dy, distance) = offset(positions[i], positions[j], i + j);
+                if distance >= min_distance {
+                    continue;
+                }
+                let push = (min_distance - distance) / 2.0 + 0.01;
+                let (px, py) = (dx / distance * push, dy / distance * push);
+                positions[i].0 += px;
+                positions[i].1 += py;
+                positions[j].0 -= px;
+                positions[j].1 -= py;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+}
+
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
+
+/// Vector from `b` to `a` and its length, nudged apart (deterministically,
+/// by `salt`) when the two points coincide
+fn offset(a: (f64, f64), b: (f64, f64), salt: usize) -> (f64, f64, f64) {
+    let (mut dx, mut dy) = (a.0 - b.0, a.1 - b.1);
+    let mut distance = (dx * dx + dy * dy).sqrt();
+    if distance < 0.01 {
+        let angle = salt as f64;
+        (dx, dy, distance) = (0.01 * angle.cos(), 0.01 * angle.sin(), 0.01);
+    }
+    (dx, dy, distance)
+}
+
+fn escape_xml(input: &str) -> String {
     input
         .replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -17,49 +207,117 @@ fn escape_html(input: &str) -> String {
         .replace('\'', "&#39;")
 }
 
-pub fn generate_call_graph_svg(
-    call_graph: &HashMap<String, FunctionNode>,
-    output_path: &str,
-) -> std::io::Result<()> {
-    let mut svg = String::new();
-    let node_radius = 20;
-    let positions = calculate_positions(call_graph);
-
-    svg.push_str("<svg xmlns='http://www.w3.org/2000/svg' version='1.1'>\n");
-
-    for node in call_graph.values() {
-        let (x, y) = positions[&node.symbol];
-        let body = node
-            .body
-            .as_ref()
-            .map(|b| escape_html(b))
-            .unwrap_or_default();
+/// Draw the call graph as an SVG document.
+///
+/// Skipped functions are left out, highlighted ones are drawn green, and the
+/// full symbol of each function is shown as its tooltip.
+pub fn render_call_graph_svg(
+    call_graph: &CallGraph,
+    filters: &NodeFilters,
+    options: &SvgLayoutOptions,
+) -> String {
+    let call_graph = &filters.apply(call_graph);
+    let nodes: Vec<&FunctionNode> = filters.order_nodes(call_graph.values());
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.symbol.as_str(), i))
+        .collect();
+    let edges: Vec<(usize, usize)> = nodes
+        .iter()
+        .enumerate()
+        .flat_map(|(i, node)| {
+            filters
+                .order(&node.callees)
+                .into_iter()
+                .filter_map(|callee| index.get(callee.as_str()))
+                .map(move |&j| (i, j))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let positions = force_directed_layout(nodes.len(), &edges, options);
+
+    let (width, height, radius) = (options.width, options.height, options.node_radius);
+    let mut svg = format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' width='{width}' height='{height}' \
+         viewBox='0 0 {width} {height}' style='background:#fff;font-family:sans-serif'>\n"
+    );
+    svg.push_str("<defs><marker id='arrow' markerWidth='10' markerHeight='10' refX='10' refY='5' orient='auto' markerUnits='strokeWidth'><path d='M0,0 L10,5 L0,10 z' fill='#888'/></marker></defs>\n");
+
+    // Edges end at the border of the callee's circle so the arrow shows
+    for &(from, to) in &edges {
+        if from == to {
+            continue;
+        }
+        let ((x1, y1), (x2, y2)) = (positions[from], positions[to]);
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= 2.0 * radius {
+            continue;
+        }
+        let (ux, uy) = (dx / length, dy / length);
         svg.push_str(&format!(
-            "<g>\
-                <circle cx='{x}' cy='{y}' r='{r}' fill='#4a90e2' stroke='#222' stroke-width='2'/>\
-                <text x='{x}' y='{y}' text-anchor='middle' alignment-baseline='middle' fill='#fff' font-size='14'>{label}</text>\
-                <title>{body}</title>\
-            </g>\n",
-            x=x, y=y, r=node_radius, label=escape_html(&node.display_name), body=body
+            "<line x1='{:.1}' y1='{:.1}' x2='{:.1}' y2='{:.1}' stroke='#888' stroke-width='1.5' marker-end='url(#arrow)'/>\n",
+            x1 + ux * radius,
+            y1 + uy * radius,
+            x2 - ux * radius,
+            y2 - uy * radius
         ));
     }
 
-    svg.push_str("</svg>\n");
+    for (node, &(x, y)) in nodes.iter().zip(&positions) {
+        let fill = if filters.is_highlighted(node) {
+            "lightgreen"
+        } else {
+            "lightblue"
+        };
+        svg.push_str(&format!(
+            "<g><title>{}</title><circle cx='{x:.1}' cy='{y:.1}' r='{radius}' fill='{fill}' stroke='#333'/>\
+             <text x='{x:.1}' y='{:.1}' text-anchor='middle' font-size='11'>{}</text></g>\n",
+            escape_xml(&node.symbol),
+            y + radius + 12.0,
+            escape_xml(&node.display_name)
+        ));
+    }
 
-    let mut file = File::create(output_path)?;
-    file.write_all(svg.as_bytes())?;
-    Ok(())
+    svg.push_str("</svg>\n");
+    svg
 }
 
-fn calculate_positions(call_graph: &HashMap<String, FunctionNode>) -> HashMap<String, (i32, i32)> {
-    // Dummy implementation for positions calculation
-    let mut positions = HashMap::new();
-    let mut x = 50;
-    let mut y = 50;
-    for key in call_graph.keys() {
-        positions.insert(key.clone(), (x, y));
-        x += 100;
-        y += 100;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_force_directed_layout() {
+        // Two chains of ten functions each
+        let edges: Vec<(usize, usize)> = (0..20)
+            .filter(|i| i % 10 != 9)
+            .map(|i| (i, i + 1))
+            .collect();
+        let options = SvgLayoutOptions::default().with_canvas(600.0, 400.0);
+        let positions = force_directed_layout(20, &edges, &options);
+        assert_eq!(positions, force_directed_layout(20, &edges, &options));
+
+        for (i, &(x, y)) in positions.iter().enumerate() {
+            assert!((0.0..=600.0).contains(&x) && (0.0..=400.0).contains(&y));
+            for &(ox, oy) in &positions[i + 1..] {
+                let distance = ((x - ox).powi(2) + (y - oy).powi(2)).sqrt();
+                assert!(distance > 2.0 * options.node_radius, "nodes overlap");
+            }
+        }
+
+        // Called functions end up closer than the average pair
+        let distance = |a: usize, b: usize| {
+            let ((x1, y1), (x2, y2)) = (positions[a], positions[b]);
+            ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt()
+        };
+        let mean_edge = edges.iter().map(|&(a, b)| distance(a, b)).sum::<f64>() / 18.0;
+        let mean_pair = (0..20)
+            .flat_map(|a| ((a + 1)..20).map(move |b| (a, b)))
+            .map(|(a, b)| distance(a, b))
+            .sum::<f64>()
+            / 190.0;
+        assert!(mean_edge < mean_pair / 2.0);
     }
-    positions
 }
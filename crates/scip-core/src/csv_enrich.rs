@@ -0,0 +1,724 @@
+//! Join a functions CSV with the metrics of an atoms JSON
+//!
+//! Each row of the CSV names a function in a key column (`function` by
+//! default), optionally with its module in a second column. The row is
+//! matched to an atom by trying [`KeyStrategy`]s in order, and the chosen
+//! metric fields of the atom are appended as columns. All input columns are
+//! kept as they are; a field whose column already exists only fills the
+//! empty cells of that column.
+//!
+//! The settings can be given as flags or loaded from a TOML file:
+//!
+//! ```toml
+//! key_column = "function"
+//! module_column = "module"
+//! strategies = ["qualified-name", "display-name", "module-name", "fuzzy"]
+//! fields = ["ensures_halstead_effort", "proof_depth", "metrics.function_mode"]
+//! ```
+//!
+//! Fields are either one of [`BUILTIN_FIELDS`] (sums over spec clauses,
+//! proof Halstead numbers, lemma and proof step counts) or a dotted path
+//! into the atom JSON, e.g. `proof_metrics.proof_steps.calc_steps`.
+//!
+//! A proof difficulty CSV (`function`, `has_proof`, `trivial_proof`) can be
+//! joined in as well ([`CsvEnricher::with_proof_difficulty`]): its rows are
+//! matched to atoms with the same strategies, so both CSVs may name a
+//! function differently.
+
+use crate::error::{Error, Result};
+use crate::symbol_index::{MatchKind, SymbolEntry, SymbolResolver};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// How the key of a CSV row is matched to an atom
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyStrategy {
+    /// The key as a symbol, path, `Type::method` or trailing path segments
+    /// (see [`SymbolResolver`])
+    QualifiedName,
+    /// The last `::` segment of the key as display name
+    DisplayName,
+    /// The display name, among the atoms in the file of the row's module
+    /// (`my_crate::backend::field` is `src/backend/field.rs`)
+    ModuleName,
+    /// The closest display name within a small edit distance
+    Fuzzy,
+}
+
+impl KeyStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyStrategy::QualifiedName => "qualified-name",
+            KeyStrategy::DisplayName => "display-name",
+            KeyStrategy::ModuleName => "module-name",
+            KeyStrategy::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+impl fmt::Display for KeyStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for KeyStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "qualified-name" => Ok(KeyStrategy::QualifiedName),
+            "display-name" => Ok(KeyStrategy::DisplayName),
+            "module-name" => Ok(KeyStrategy::ModuleName),
+            "fuzzy" => Ok(KeyStrategy::Fuzzy),
+            _ => Err(format!(
+                "unknown key strategy '{s}' (expected qualified-name, display-name, \
+                 module-name or fuzzy)"
+            )),
+        }
+    }
+}
+
+/// Why [`CsvEnricher::find`] found no atom for a row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindError {
+    /// Some strategy found several atoms and none found exactly one
+    Ambiguous,
+    /// No strategy found any atom
+    NotFound,
+}
+
+/// Fields computed from several atom values; any other field is a dotted
+/// path into the atom JSON
+pub const BUILTIN_FIELDS: [&str; 24] = [
+    "cyclomatic",
+    "cognitive",
+    "requires_halstead_length",
+    "requires_halstead_difficulty",
+    "requires_halstead_effort",
+    "ensures_halstead_length",
+    "ensures_halstead_difficulty",
+    "ensures_halstead_effort",
+    "decreases_count",
+    "direct_proof_length",
+    "direct_proof_difficulty",
+    "direct_proof_effort",
+    "transitive_proof_length",
+    "transitive_proof_difficulty",
+    "transitive_proof_effort",
+    "proof_depth",
+    "direct_lemmas_count",
+    "transitive_lemmas_count",
+    "assert_count",
+    "assert_by_count",
+    "calc_step_count",
+    "reveal_count",
+    "bit_vector_count",
+    "nonlinear_arith_count",
+];
+
+/// Which columns identify a function and which fields to append
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnrichConfig {
+    /// Column with the function name
+    pub key_column: String,
+    /// Column with the function's module path, for [`KeyStrategy::ModuleName`]
+    /// and for narrowing ambiguous matches
+    pub module_column: Option<String>,
+    /// Strategies to try, in order; the first one with a single match wins
+    pub strategies: Vec<KeyStrategy>,
+    /// Fields to append, [`BUILTIN_FIELDS`] or dotted atom paths
+    pub fields: Vec<String>,
+}
+
+impl Default for EnrichConfig {
+    fn default() -> Self {
+        Self {
+            key_column: "function".to_string(),
+            module_column: Some("module".to_string()),
+            strategies: vec![
+                KeyStrategy::QualifiedName,
+                KeyStrategy::DisplayName,
+                KeyStrategy::ModuleName,
+            ],
+            fields: BUILTIN_FIELDS.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+impl EnrichConfig {
+    /// Parse settings from TOML; unset keys keep their defaults
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| Error::InvalidData(format!("invalid enrich config: {e}")))
+    }
+
+    /// Load settings from a TOML file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Columns joined from a proof difficulty CSV, in output order
+pub const PROOF_DIFFICULTY_FIELDS: [&str; 2] = ["has_proof", "trivial_proof"];
+
+/// `has_proof` and `trivial_proof` of a proof difficulty CSV, by the
+/// identifier of the atom each row matched and by the row's own key
+#[derive(Debug, Default)]
+struct ProofDifficulty {
+    by_identifier: HashMap<String, [String; 2]>,
+    by_key: HashMap<String, [String; 2]>,
+}
+
+/// Match counts of an enrichment run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EnrichStats {
+    pub rows: usize,
+    /// Rows matched, by the strategy that matched them
+    pub matched: BTreeMap<KeyStrategy, usize>,
+    /// Rows whose key matched several atoms under every strategy
+    pub ambiguous: usize,
+    pub not_found: usize,
+    /// Rows given proof difficulty columns
+    pub with_proof_difficulty: usize,
+}
+
+impl EnrichStats {
+    pub fn matched_total(&self) -> usize {
+        self.matched.values().sum()
+    }
+}
+
+/// Last `::` segment of a name (`Type::method` gives `method`)
+fn last_segment(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name).trim()
+}
+
+/// Whether a source file defines the module path, with or without its
+/// leading crate name (`my_crate::backend::field` is `src/backend/field.rs`,
+/// `src/backend/field/mod.rs` or `backend/field.rs`)
+fn module_file_matches(relative_path: &str, module: &str) -> bool {
+    let mut file: Vec<&str> = relative_path
+        .trim_start_matches("./")
+        .trim_end_matches(".rs")
+        .split('/')
+        .collect();
+    if matches!(file.last(), Some(&"mod" | &"lib" | &"main")) {
+        file.pop();
+    }
+    let module: Vec<&str> = module.split("::").filter(|s| !s.is_empty()).collect();
+    file.ends_with(&module) || module.split_first().is_some_and(|(_, m)| file.ends_with(m))
+}
+
+fn atom_str<'a>(atom: &'a Value, key: &str) -> &'a str {
+    atom[key].as_str().unwrap_or_default()
+}
+
+/// Matches atoms to CSV rows and renders their metric fields
+pub struct CsvEnricher<'a> {
+    config: &'a EnrichConfig,
+    atoms: HashMap<&'a str, &'a Value>,
+    by_display_name: HashMap<&'a str, Vec<&'a str>>,
+    resolver: SymbolResolver,
+    proof_difficulty: Option<ProofDifficulty>,
+}
+
+impl<'a> CsvEnricher<'a> {
+    /// Index atoms (as written by `compute_metrics` / `compute_proof_metrics`)
+    /// by identifier and display name
+    pub fn new(atoms: &'a [Value], config: &'a EnrichConfig) -> Self {
+        let mut by_identifier = HashMap::new();
+        let mut by_display_name: HashMap<&str, Vec<&str>> = HashMap::new();
+        for atom in atoms {
+            let identifier = atom_str(atom, "identifier");
+            by_identifier.insert(identifier, atom);
+            by_display_name
+                .entry(atom_str(atom, "display_name"))
+                .or_default()
+                .push(identifier);
+        }
+        for identifiers in by_display_name.values_mut() {
+            identifiers.sort_unstable();
+            identifiers.dedup();
+        }
+        let resolver = SymbolResolver::new(atoms.iter().map(|atom| SymbolEntry {
+            symbol: atom_str(atom, "identifier").to_string(),
+            display_name: atom_str(atom, "display_name").to_string(),
+            relative_path: atom_str(atom, "relative_path").to_string(),
+        }));
+        Self {
+            config,
+            atoms: by_identifier,
+            by_display_name,
+            resolver,
+            proof_difficulty: None,
+        }
+    }
+
+    /// Also append [`PROOF_DIFFICULTY_FIELDS`] from a CSV with `function`,
+    /// `has_proof` and `trivial_proof` columns.
+    ///
+    /// Its rows are matched to atoms like the rows of the enriched CSV, so
+    /// a row gets the values of the difficulty row naming the same atom, or
+    /// else of the one with the same key.
+    pub fn with_proof_difficulty<R: Read>(mut self, reader: R) -> Result<Self> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| {
+            headers.iter().position(|h| h == name).ok_or_else(|| {
+                Error::InvalidData(format!("proof difficulty CSV has no '{name}' column"))
+            })
+        };
+        let function = column("function")?;
+        let has_proof = column("has_proof")?;
+        let trivial_proof = column("trivial_proof")?;
+
+        let mut difficulty = ProofDifficulty::default();
+        for record in reader.records() {
+            let record = record?;
+            let cell = |i: usize| record.get(i).unwrap_or_default().to_string();
+            let key = cell(function);
+            let values = [cell(has_proof), cell(trivial_proof)];
+            if let Ok((atom, _)) = self.find(&key, None) {
+                let identifier = atom_str(atom, "identifier").to_string();
+                difficulty.by_identifier.insert(identifier, values.clone());
+            }
+            difficulty.by_key.insert(key, values);
+        }
+        self.proof_difficulty = Some(difficulty);
+        Ok(self)
+    }
+
+    /// Proof difficulty values of a row with `key`, matched to `atom`
+    fn proof_difficulty_of(&self, key: &str, atom: Option<&Value>) -> Option<&[String; 2]> {
+        let difficulty = self.proof_difficulty.as_ref()?;
+        atom.and_then(|atom| difficulty.by_identifier.get(atom_str(atom, "identifier")))
+            .or_else(|| difficulty.by_key.get(key))
+    }
+
+    /// Identifiers of the atoms a strategy finds for a key
+    fn candidates(&self, strategy: KeyStrategy, key: &str, module: Option<&str>) -> Vec<&str> {
+        let display_name_matches = || {
+            self.by_display_name
+                .get(last_segment(key))
+                .cloned()
+                .unwrap_or_default()
+        };
+        match strategy {
+            KeyStrategy::QualifiedName => self
+                .resolver
+                .resolve(key)
+                .into_iter()
+                .filter(|m| !matches!(m.kind, MatchKind::Fuzzy(_)))
+                .map(|m| m.entry.symbol.as_str())
+                .collect(),
+            KeyStrategy::DisplayName => display_name_matches(),
+            KeyStrategy::ModuleName => match module {
+                Some(module) => display_name_matches()
+                    .into_iter()
+                    .filter(|id| {
+                        module_file_matches(atom_str(self.atoms[*id], "relative_path"), module)
+                    })
+                    .collect(),
+                None => Vec::new(),
+            },
+            KeyStrategy::Fuzzy => {
+                let matches = self.resolver.resolve(last_segment(key));
+                let best = matches.first().map(|m| m.kind);
+                matches
+                    .iter()
+                    .filter(|m| Some(m.kind) == best)
+                    .map(|m| m.entry.symbol.as_str())
+                    .collect()
+            }
+        }
+    }
+
+    /// The atom of a row and the strategy that found it
+    pub fn find(
+        &self,
+        key: &str,
+        module: Option<&str>,
+    ) -> Result<(&'a Value, KeyStrategy), FindError> {
+        let mut error = FindError::NotFound;
+        for &strategy in &self.config.strategies {
+            let mut candidates = self.candidates(strategy, key, module);
+            if candidates.len() > 1 {
+                if let Some(module) = module {
+                    candidates.retain(|id| {
+                        module_file_matches(atom_str(self.atoms[*id], "relative_path"), module)
+                    });
+                }
+            }
+            match candidates.as_slice() {
+                [id] => return Ok((self.atoms[*id], strategy)),
+                [] => {}
+                _ => error = FindError::Ambiguous,
+            }
+        }
+        Err(error)
+    }
+
+    /// Copy the CSV from `reader` to `writer`, appending the proof
+    /// difficulty columns (if joined) and the configured fields of each
+    /// row's atom (empty cells for unmatched rows)
+    pub fn enrich<R: Read, W: Write>(&self, reader: R, writer: W) -> Result<EnrichStats> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let mut writer = csv::Writer::from_writer(writer);
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|h| h == name);
+
+        let key_index = column(&self.config.key_column).ok_or_else(|| {
+            Error::InvalidData(format!(
+                "input CSV has no '{}' column",
+                self.config.key_column
+            ))
+        })?;
+        let module_index = self.config.module_column.as_deref().and_then(column);
+        let difficulty_fields: &[&str] = match self.proof_difficulty {
+            Some(_) => &PROOF_DIFFICULTY_FIELDS,
+            None => &[],
+        };
+        // Fields with an existing column fill its empty cells instead
+        let difficulty_targets: Vec<Option<usize>> =
+            difficulty_fields.iter().map(|f| column(f)).collect();
+        let targets: Vec<Option<usize>> = self.config.fields.iter().map(|f| column(f)).collect();
+
+        let mut output_headers = headers.clone();
+        let fields = difficulty_fields
+            .iter()
+            .copied()
+            .zip(&difficulty_targets)
+            .chain(self.config.fields.iter().map(String::as_str).zip(&targets));
+        for (field, target) in fields {
+            if target.is_none() {
+                output_headers.push_field(field);
+            }
+        }
+        writer.write_record(&output_headers)?;
+
+        let mut stats = EnrichStats::default();
+        for record in reader.records() {
+            let record = record?;
+            stats.rows += 1;
+            let key = record.get(key_index).unwrap_or_default();
+            let module = module_index
+                .and_then(|i| record.get(i))
+                .filter(|m| !m.is_empty());
+
+            let atom = match self.find(key, module) {
+                Ok((atom, strategy)) => {
+                    *stats.matched.entry(strategy).or_default() += 1;
+                    Some(atom)
+                }
+                Err(FindError::Ambiguous) => {
+                    stats.ambiguous += 1;
+                    None
+                }
+                Err(FindError::NotFound) => {
+                    stats.not_found += 1;
+                    None
+                }
+            };
+
+            let mut row: Vec<String> = record.iter().map(str::to_string).collect();
+            let mut set = |target: &Option<usize>, value: String| match target {
+                Some(i) if row[*i].is_empty() => row[*i] = value,
+                Some(_) => {}
+                None => row.push(value),
+            };
+            if self.proof_difficulty.is_some() {
+                let difficulty = self.proof_difficulty_of(key, atom);
+                if difficulty.is_some() {
+                    stats.with_proof_difficulty += 1;
+                }
+                for (i, target) in difficulty_targets.iter().enumerate() {
+                    set(target, difficulty.map(|d| d[i].clone()).unwrap_or_default());
+                }
+            }
+            for (field, target) in self.config.fields.iter().zip(&targets) {
+                set(
+                    target,
+                    atom.map(|a| field_value(a, field)).unwrap_or_default(),
+                );
+            }
+            writer.write_record(&row)?;
+        }
+        writer.flush()?;
+        Ok(stats)
+    }
+}
+
+/// Sums of the Halstead length, difficulty and effort of a spec list
+fn spec_sums(atom: &Value, specs: &str) -> (f64, f64, f64) {
+    let mut sums = (0.0, 0.0, 0.0);
+    for spec in atom["metrics"][specs].as_array().into_iter().flatten() {
+        sums.0 += spec["halstead_length"].as_f64().unwrap_or(0.0);
+        sums.1 += spec["halstead_difficulty"].as_f64().unwrap_or(0.0);
+        sums.2 += spec["halstead_effort"].as_f64().unwrap_or(0.0);
+    }
+    sums
+}
+
+/// A count as a cell, empty for zero like the other metric columns
+fn count_cell(count: f64) -> String {
+    if count > 0.0 {
+        format!("{}", count as u64)
+    } else {
+        String::new()
+    }
+}
+
+/// A real number as a cell with two decimals, empty for zero
+fn real_cell(value: f64) -> String {
+    if value > 0.0 {
+        format!("{:.2}", value)
+    } else {
+        String::new()
+    }
+}
+
+/// A JSON value as a cell: arrays by their length, null as empty
+fn json_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.len().to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Cell of one field of an atom (see [`BUILTIN_FIELDS`])
+pub fn field_value(atom: &Value, field: &str) -> String {
+    let proof = &atom["proof_metrics"];
+    let number = |value: &Value| value.as_f64().unwrap_or(0.0);
+    let length = |value: &Value| value.as_array().map_or(0, Vec::len) as f64;
+    let spec_field = |specs: &str, part: &str| {
+        let (len, difficulty, effort) = spec_sums(atom, specs);
+        match part {
+            "length" => count_cell(len),
+            "difficulty" => real_cell(difficulty),
+            _ => real_cell(effort),
+        }
+    };
+    let proof_field = |halstead: &str, part: &str| {
+        let value = number(&proof[halstead][part]);
+        match part {
+            "length" => count_cell(value),
+            _ => real_cell(value),
+        }
+    };
+    let step = |name: &str| count_cell(number(&proof["proof_steps"][name]));
+
+    match field {
+        "cyclomatic" | "cognitive" => json_cell(&atom["metrics"][field]),
+        "requires_halstead_length" => spec_field("requires_specs", "length"),
+        "requires_halstead_difficulty" => spec_field("requires_specs", "difficulty"),
+        "requires_halstead_effort" => spec_field("requires_specs", "effort"),
+        "ensures_halstead_length" => spec_field("ensures_specs", "length"),
+        "ensures_halstead_difficulty" => spec_field("ensures_specs", "difficulty"),
+        "ensures_halstead_effort" => spec_field("ensures_specs", "effort"),
+        "decreases_count" => count_cell(length(&atom["metrics"]["decreases_specs"])),
+        "direct_proof_length" => proof_field("direct_proof_halstead", "length"),
+        "direct_proof_difficulty" => proof_field("direct_proof_halstead", "difficulty"),
+        "direct_proof_effort" => proof_field("direct_proof_halstead", "effort"),
+        "transitive_proof_length" => proof_field("transitive_proof_halstead", "length"),
+        "transitive_proof_difficulty" => proof_field("transitive_proof_halstead", "difficulty"),
+        "transitive_proof_effort" => proof_field("transitive_proof_halstead", "effort"),
+        "proof_depth" => count_cell(number(&proof["proof_depth"])),
+        "direct_lemmas_count" => count_cell(length(&proof["direct_lemmas"])),
+        "transitive_lemmas_count" => count_cell(length(&proof["transitive_lemmas"])),
+        "assert_count" => step("asserts"),
+        "assert_by_count" => step("assert_by_blocks"),
+        "calc_step_count" => step("calc_steps"),
+        "reveal_count" => step("reveals"),
+        "bit_vector_count" => step("bit_vector"),
+        "nonlinear_arith_count" => step("nonlinear_arith"),
+        path => json_cell(
+            path.split('.')
+                .try_fold(atom, |value, key| value.get(key))
+                .unwrap_or(&Value::Null),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn atoms() -> Vec<Value> {
+        vec![
+            json!({
+                "identifier": "crate/backend/field/FieldElement#sub().",
+                "display_name": "sub",
+                "relative_path": "src/backend/field.rs",
+                "metrics": {
+                    "function_mode": "exec",
+                    "cyclomatic": 2,
+                    "requires_specs": [],
+                    "ensures_specs": [
+                        {"halstead_length": 5, "halstead_difficulty": 1.5, "halstead_effort": 10.0},
+                        {"halstead_length": 3, "halstead_difficulty": 1.0, "halstead_effort": 4.5}
+                    ],
+                    "decreases_specs": []
+                },
+                "proof_metrics": {
+                    "proof_depth": 2,
+                    "direct_lemmas": ["lemma_a"],
+                    "transitive_lemmas": ["lemma_a", "lemma_b"],
+                    "proof_steps": {"asserts": 3, "calc_steps": 0}
+                }
+            }),
+            json!({
+                "identifier": "crate/scalar/Scalar#sub().",
+                "display_name": "sub",
+                "relative_path": "src/scalar.rs",
+                "metrics": {"function_mode": "exec"}
+            }),
+            json!({
+                "identifier": "crate/scalar/lemma_reduce().",
+                "display_name": "lemma_reduce",
+                "relative_path": "src/scalar.rs",
+                "metrics": {"function_mode": "proof"}
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_strategies() {
+        let atoms = atoms();
+        let mut config = EnrichConfig::default();
+        let enricher = CsvEnricher::new(&atoms, &config);
+
+        let (atom, strategy) = enricher.find("FieldElement::sub", None).unwrap();
+        assert_eq!(strategy, KeyStrategy::QualifiedName);
+        assert_eq!(atom["relative_path"], "src/backend/field.rs");
+
+        // `sub` alone is ambiguous until the module narrows it down
+        assert_eq!(
+            enricher.find("sub", None).unwrap_err(),
+            FindError::Ambiguous
+        );
+        let (atom, _) = enricher.find("sub", Some("my_crate::scalar")).unwrap();
+        assert_eq!(atom["relative_path"], "src/scalar.rs");
+
+        assert_eq!(
+            enricher.find("lemma_reduse", None).unwrap_err(),
+            FindError::NotFound
+        );
+        config.strategies.push(KeyStrategy::Fuzzy);
+        let enricher = CsvEnricher::new(&atoms, &config);
+        let (atom, strategy) = enricher.find("lemma_reduse", None).unwrap();
+        assert_eq!(strategy, KeyStrategy::Fuzzy);
+        assert_eq!(atom["display_name"], "lemma_reduce");
+    }
+
+    #[test]
+    fn test_module_file_matches() {
+        assert!(module_file_matches(
+            "src/backend/field.rs",
+            "my_crate::backend::field"
+        ));
+        assert!(module_file_matches(
+            "./src/backend/field/mod.rs",
+            "backend::field"
+        ));
+        assert!(!module_file_matches(
+            "src/scalar.rs",
+            "my_crate::backend::field"
+        ));
+    }
+
+    #[test]
+    fn test_enrich_csv() {
+        let atoms = atoms();
+        let config = EnrichConfig::from_toml_str(
+            r#"
+            key_column = "name"
+            fields = ["cyclomatic", "ensures_halstead_effort", "proof_depth",
+                      "transitive_lemmas_count", "assert_count", "metrics.function_mode"]
+            "#,
+        )
+        .unwrap();
+        let input = "name,module,cyclomatic\n\
+                     FieldElement::sub,my_crate::backend::field,\n\
+                     sub,my_crate::scalar,7\n\
+                     missing,,\n";
+
+        let mut output = Vec::new();
+        let stats = CsvEnricher::new(&atoms, &config)
+            .enrich(input.as_bytes(), &mut output)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "name,module,cyclomatic,ensures_halstead_effort,proof_depth,\
+             transitive_lemmas_count,assert_count,metrics.function_mode\n\
+             FieldElement::sub,my_crate::backend::field,2,14.50,2,2,3,exec\n\
+             sub,my_crate::scalar,7,,,,,exec\n\
+             missing,,,,,,,\n"
+        );
+        assert_eq!(stats.rows, 3);
+        assert_eq!(stats.matched_total(), 2);
+        assert_eq!(stats.not_found, 1);
+    }
+
+    #[test]
+    fn test_enrich_csv_with_proof_difficulty() {
+        let atoms = atoms();
+        let config = EnrichConfig {
+            fields: vec!["proof_depth".to_string()],
+            ..EnrichConfig::default()
+        };
+        // Named by qualified path here, by `Type::method` in the input
+        let difficulty = "function,has_proof,trivial_proof\n\
+                          crate/backend/field/FieldElement#sub().,yes,no\n\
+                          lemma_reduce,yes,yes\n";
+        let input = "function,module,note\n\
+                     FieldElement::sub,,a\n\
+                     lemma_reduce,,b\n\
+                     missing,,c\n";
+
+        let enricher = CsvEnricher::new(&atoms, &config)
+            .with_proof_difficulty(difficulty.as_bytes())
+            .unwrap();
+        let mut output = Vec::new();
+        let stats = enricher.enrich(input.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "function,module,note,has_proof,trivial_proof,proof_depth\n\
+             FieldElement::sub,,a,yes,no,2\n\
+             lemma_reduce,,b,yes,yes,\n\
+             missing,,c,,,\n"
+        );
+        assert_eq!(stats.with_proof_difficulty, 2);
+
+        let no_columns =
+            CsvEnricher::new(&atoms, &config).with_proof_difficulty("name\n".as_bytes());
+        assert!(matches!(no_columns, Err(Error::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_config_errors() {
+        assert!(matches!(
+            EnrichConfig::from_toml_str("strategies = [\"soundex\"]"),
+            Err(Error::InvalidData(_))
+        ));
+        let atoms = atoms();
+        let config = EnrichConfig::default();
+        let result = CsvEnricher::new(&atoms, &config).enrich("name\nsub\n".as_bytes(), Vec::new());
+        assert!(
+            matches!(result, Err(Error::InvalidData(message)) if message.contains("'function'"))
+        );
+        assert!("module-name".parse::<KeyStrategy>().is_ok());
+        assert!("soundex".parse::<KeyStrategy>().is_err());
+    }
+}
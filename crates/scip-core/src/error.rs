@@ -0,0 +1,178 @@
+//! The crate-wide error type
+//!
+//! Fallible public APIs return [`Result`], so library users can match on
+//! the kind of failure (a missing Graphviz install, an unresolved function
+//! name with its suggestions, a malformed index) instead of parsing
+//! messages. Analyses that report problems as data (verification
+//! diagnostics, gate violations) keep doing so.
+
+use crate::monitor::Cancelled;
+use crate::symbol_index::ResolveError;
+use std::io;
+use std::process::ExitStatus;
+
+/// Why an operation of this crate failed
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading or writing a file failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// A SCIP index (JSON or protobuf) is malformed
+    #[error("Failed to parse SCIP index: {0}")]
+    ScipParse(String),
+
+    /// An atoms JSON file is malformed or of an unknown format version
+    #[error("Failed to parse atoms JSON: {0}")]
+    AtomsParse(String),
+
+    /// Another input file (run history, lemma index) is malformed
+    #[error("{0}")]
+    InvalidData(String),
+
+    /// Serializing an output to JSON failed
+    #[error("Failed to serialize JSON: {0}")]
+    Serialize(#[source] serde_json::Error),
+
+    /// Reading or writing a CSV file failed
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    /// Writing a SQLite database failed
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// Writing a Parquet file failed
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    /// Writing an Excel workbook failed
+    #[cfg(feature = "xlsx")]
+    #[error("Excel export error: {0}")]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+
+    /// The output needs a cargo feature scip-core was built without
+    #[error("{output} export requires scip-core to be built with the `{feature}` feature")]
+    FeatureDisabled {
+        output: &'static str,
+        feature: &'static str,
+    },
+
+    /// A function name did not resolve to a function of the graph; see
+    /// [`ResolveError::NotFound`] for suggestions
+    #[error(transparent)]
+    SymbolResolution(#[from] ResolveError),
+
+    /// No function is defined in the requested source files;
+    /// `similar_paths` are indexed paths containing the request
+    #[error("{}", file_not_found_message(paths, similar_paths))]
+    FileNotFound {
+        paths: Vec<String>,
+        similar_paths: Vec<String>,
+    },
+
+    /// A path given as input does not exist or has the wrong type
+    #[error("{0}")]
+    InvalidPath(String),
+
+    /// Graphviz's `dot` is not installed (or not on `PATH`)
+    #[error(
+        "Graphviz `dot` was not found on PATH; install Graphviz \
+         (e.g. `apt install graphviz` or `brew install graphviz`) to render DOT files"
+    )]
+    GraphvizMissing,
+
+    /// `dot` failed to render a DOT file
+    #[error("Failed to generate {format}: dot exited with {status}")]
+    Graphviz { format: String, status: ExitStatus },
+
+    /// The in-process renderer could not lay out a DOT file
+    #[error("Failed to render DOT file in process: {0}")]
+    Layout(String),
+
+    /// An external tool (verus-analyzer, scip) failed
+    #[error("{tool} failed with {status}{}", stderr_suffix(stderr))]
+    ToolFailed {
+        tool: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+
+    /// `cargo verus` could not be run, or gave no usable result
+    #[error("Verification failed: {0}")]
+    VerificationFailed(String),
+
+    /// The operation was cancelled through its [`crate::Monitor`]
+    #[error(transparent)]
+    Cancelled(#[from] Cancelled),
+}
+
+/// Result of the fallible operations of this crate
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+fn file_not_found_message(paths: &[String], similar_paths: &[String]) -> String {
+    let mut message = format!("No functions found in file path(s): {}", paths.join(", "));
+    if !similar_paths.is_empty() {
+        message.push_str("\n\nHere are some similar paths:\n");
+        for path in similar_paths {
+            message.push_str(&format!("  - {path}\n"));
+        }
+    }
+    message
+}
+
+fn stderr_suffix(stderr: &str) -> String {
+    match stderr.trim() {
+        "" => String::new(),
+        stderr => format!("\nstderr: {stderr}"),
+    }
+}
+
+impl Error {
+    /// A malformed SCIP index, from any displayable parse error
+    pub(crate) fn scip_parse(error: impl std::fmt::Display) -> Self {
+        Error::ScipParse(error.to_string())
+    }
+
+    /// A malformed atoms JSON file, from any displayable parse error
+    pub(crate) fn atoms_parse(error: impl std::fmt::Display) -> Self {
+        Error::AtomsParse(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        let error: Error = ResolveError::NotFound {
+            query: "sqaure".to_string(),
+            suggestions: vec!["square".to_string()],
+        }
+        .into();
+        assert!(matches!(
+            &error,
+            Error::SymbolResolution(ResolveError::NotFound { suggestions, .. })
+                if suggestions == &["square"]
+        ));
+        assert_eq!(
+            error.to_string(),
+            "No functions matching 'sqaure' found. Did you mean: square?"
+        );
+
+        let error = Error::FileNotFound {
+            paths: vec!["field.rs".to_string()],
+            similar_paths: vec!["src/field.rs".to_string()],
+        };
+        assert!(error
+            .to_string()
+            .ends_with("similar paths:\n  - src/field.rs\n"));
+
+        let io_error: Error = io::Error::new(io::ErrorKind::NotFound, "missing").into();
+        assert!(matches!(io_error, Error::Io(e) if e.kind() == io::ErrorKind::NotFound));
+    }
+}
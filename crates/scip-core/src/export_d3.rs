@@ -2,38 +2,79 @@
 //!
 //! This module provides functions to export call graphs for web visualization:
 //! - `export_call_graph_d3` - Export to D3.js force-directed graph format
-//! - `write_call_graph_as_atoms_json` - Export as JSON array of Atom objects
+//!   (returns the written graph for further enrichment)
+//! - `export_call_graph_d3_filtered` - Same, keeping only edges of given `EdgeKind`s
+//! - `call_graph_to_d3` - Build the D3.js graph structure without writing it
+//! - `write_node_bodies` - Write the function bodies (or signatures) viewers
+//!   load on demand next to the graph
+//! - `assign_stable_ids` - Give D3 nodes IDs that survive re-indexing
+//! - `module_graph_to_d3` - One node per source file, with weighted links
+//! - `write_call_graph_as_atoms_json` - Export as versioned JSON of Atom objects
+//!   (`call_graph_to_atoms` and `write_atoms_json` for the two halves)
+//!
+//! Atoms, nodes, links and dependency lists are sorted by symbol unless the
+//! [`NodeFilters`] turn that off, so the written files are byte-stable.
 
-use crate::call_graph::{detect_decl_kind, symbol_to_path};
+use crate::call_graph::{detect_decl_kind, filter_by_edge_kinds};
+use crate::error::{Error, Result};
+use crate::module_graph::ModuleGraph;
+use crate::node_filter::{is_test_function, NodeFilters};
+use crate::scip_utils::fnv1a;
+use crate::symbol_scheme::{strip_generics, SymbolNormalizer};
 use crate::types::{
-    Atom, D3Graph, D3GraphMetadata, D3Link, D3Node, DeclKind, FunctionNode, ScipIndex,
+    Atom, AtomsFile, CallGraph, D3Graph, D3GraphMetadata, D3Link, D3Node, DeclKind, EdgeKind,
+    ScipIndex,
 };
 use log::debug;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
-/// Helper function to determine if a node is from libsignal
-fn is_libsignal_node(node: &FunctionNode) -> bool {
-    node.symbol.contains("libsignal-protocol")
-        || node.symbol.contains("libsignal-core")
-        || node.symbol.contains("libsignal-net")
-        || node.symbol.contains("libsignal-keytrans")
-        || node.symbol.contains("libsignal-svrb")
-        || node.symbol.contains("libsignal")
-        || node.relative_path.contains("libsignal")
-        || node.symbol.contains("zkgroup")
-        || node.symbol.contains("poksho")
-        || node.symbol.contains("zkcredential")
-        || node.symbol.contains("usernames")
-}
-
-/// Write the call graph as a JSON array of Atom objects
+/// Write the call graph as Atom objects in a versioned envelope
+/// (`{"format_version": 2, "atoms": [...]}`, see `AtomsFile`)
 pub fn write_call_graph_as_atoms_json<P: AsRef<std::path::Path>>(
-    call_graph: &HashMap<String, FunctionNode>,
+    call_graph: &CallGraph,
     output_path: P,
-) -> std::io::Result<()> {
-    let atoms: Vec<Atom> = call_graph
-        .values()
+) -> Result<()> {
+    write_atoms_json(call_graph_to_atoms(call_graph), output_path)
+}
+
+/// Write atoms in the versioned envelope of [`write_call_graph_as_atoms_json`]
+pub fn write_atoms_json<P: AsRef<std::path::Path>>(atoms: Vec<Atom>, output_path: P) -> Result<()> {
+    let json = serde_json::to_string_pretty(&AtomsFile::new(atoms)).map_err(Error::Serialize)?;
+    std::fs::write(output_path, json)?;
+    Ok(())
+}
+
+/// Convert the call graph to the atoms written by
+/// [`write_call_graph_as_atoms_json`], without signatures, sorted by symbol
+///
+/// Atom identifiers are unique: functions whose paths collide (see
+/// [`SymbolNormalizer`]) are told apart by a suffix and logged.
+pub fn call_graph_to_atoms(call_graph: &CallGraph) -> Vec<Atom> {
+    call_graph_to_atoms_with_filters(call_graph, &NodeFilters::default())
+}
+
+/// Like [`call_graph_to_atoms`], leaving out the functions skipped by
+/// `filters` and ordering the atoms as they say
+pub fn call_graph_to_atoms_with_filters(
+    call_graph: &CallGraph,
+    filters: &NodeFilters,
+) -> Vec<Atom> {
+    let call_graph = &filters.apply(call_graph);
+    let normalizer = SymbolNormalizer::new(
+        call_graph
+            .values()
+            .map(|node| (node.symbol.as_str(), node.display_name.as_str())),
+    );
+    let identifier = |symbol: &str| {
+        normalizer
+            .identifier(symbol)
+            .expect("every node is normalized")
+            .to_string()
+    };
+    filters
+        .order_nodes(call_graph.values())
+        .into_iter()
         .map(|node| {
             let body_content = node.body.clone().unwrap_or_default();
 
@@ -49,15 +90,13 @@ pub fn write_call_graph_as_atoms_json<P: AsRef<std::path::Path>>(
                 .to_string();
 
             Atom {
-                identifier: symbol_to_path(&node.symbol, &node.display_name),
-                statement_type: "function".to_string(),
-                deps: node
-                    .callees
-                    .iter()
-                    .filter_map(|callee| call_graph.get(callee))
-                    .map(|callee_node| {
-                        symbol_to_path(&callee_node.symbol, &callee_node.display_name)
-                    })
+                identifier: identifier(&node.symbol),
+                statement_type: node.node_kind.as_str().to_string(),
+                deps: filters
+                    .order(&node.callees)
+                    .into_iter()
+                    .filter(|callee| call_graph.contains_key(*callee))
+                    .map(|callee| identifier(callee))
                     .collect(),
                 body: body_content,
                 display_name: node.display_name.clone(),
@@ -69,24 +108,96 @@ pub fn write_call_graph_as_atoms_json<P: AsRef<std::path::Path>>(
                     .to_string_lossy()
                     .to_string(),
                 parent_folder,
+                doc: node.doc.clone(),
+                attributes: node.attributes.clone(),
+                start_line: node.start_line,
+                end_line: node.end_line,
+                requires_text: node.requires_text.clone(),
+                ensures_text: node.ensures_text.clone(),
+                signature: None,
+                graph_metrics: None,
+                spec_support: Vec::new(),
             }
         })
-        .collect();
-
-    let json = serde_json::to_string_pretty(&atoms)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-    std::fs::write(output_path, json)
+        .collect()
 }
 
 /// Export the call graph in D3.js force-directed graph format
+///
+/// Skipped nodes are left out; `is_libsignal` marks the nodes matched by the
+/// highlight filters. Bodies are written to a separate file (see
+/// [`write_node_bodies`]). Returns the graph that was written.
 pub fn export_call_graph_d3<P: AsRef<std::path::Path>>(
-    call_graph: &HashMap<String, FunctionNode>,
+    call_graph: &CallGraph,
     scip_data: &ScipIndex,
     output_path: P,
-) -> std::io::Result<()> {
-    // Create nodes
-    let nodes: Vec<D3Node> = call_graph
+    filters: &NodeFilters,
+) -> Result<D3Graph> {
+    let mut graph = call_graph_to_d3(call_graph, &scip_data.metadata.project_root, filters);
+    graph.metadata.bodies_file = write_node_bodies(call_graph, output_path.as_ref(), filters)?;
+
+    // Write to file
+    let json = serde_json::to_string_pretty(&graph).map_err(Error::Serialize)?;
+    std::fs::write(output_path, json)?;
+    Ok(graph)
+}
+
+/// Path of the bodies file written next to a graph: `graph.json` gives
+/// `graph.bodies.json`
+pub fn bodies_path(graph_path: &Path) -> std::path::PathBuf {
+    graph_path.with_extension("bodies.json")
+}
+
+/// Write the [`TooltipContent`](crate::tooltip::TooltipContent) of the
+/// filters for each function to a JSON object keyed by node id, next to
+/// `graph_path` (see [`bodies_path`]).
+///
+/// Keeping the bodies out of the graph keeps it small; viewers fetch them
+/// when a function is selected. Returns the file name, for
+/// [`D3GraphMetadata::bodies_file`], or `None` without writing anything when
+/// there is nothing to show.
+pub fn write_node_bodies(
+    call_graph: &CallGraph,
+    graph_path: &Path,
+    filters: &NodeFilters,
+) -> Result<Option<String>> {
+    let content = filters.tooltip_content();
+    let bodies: BTreeMap<&str, String> = call_graph
         .values()
+        .filter(|node| !filters.is_skipped(node))
+        .filter_map(|node| Some((node.symbol.as_str(), content.text(node)?)))
+        .collect();
+    if bodies.is_empty() {
+        return Ok(None);
+    }
+    let path = bodies_path(graph_path);
+    let json = serde_json::to_string(&bodies).map_err(Error::Serialize)?;
+    std::fs::write(&path, json)?;
+    debug!(
+        "Wrote {} function bodies to {}",
+        bodies.len(),
+        path.display()
+    );
+    Ok(path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned()))
+}
+
+/// Link type (`type` of D3 links) of uses of type nodes
+pub const REFERENCES_LINK_TYPE: &str = "references";
+
+/// Convert the call graph to the D3.js graph structure written by
+/// [`export_call_graph_d3`]
+pub fn call_graph_to_d3(
+    call_graph: &CallGraph,
+    project_root: &str,
+    filters: &NodeFilters,
+) -> D3Graph {
+    let call_graph = &filters.apply(call_graph);
+    let ordered_nodes = filters.order_nodes(call_graph.values());
+    // Create nodes
+    let nodes: Vec<D3Node> = ordered_nodes
+        .iter()
         .map(|node| {
             let is_external = node.file_path.starts_with("external:");
 
@@ -113,8 +224,11 @@ pub fn export_call_graph_d3<P: AsRef<std::path::Path>>(
                 (file, parent)
             };
 
-            // Extract line numbers from range (SCIP uses 0-based, convert to 1-based)
-            let (start_line, end_line) = if node.range.len() >= 4 {
+            // Line numbers of the extracted body, else from the range (SCIP
+            // uses 0-based, convert to 1-based)
+            let (start_line, end_line) = if node.start_line.is_some() {
+                (node.start_line, node.end_line)
+            } else if node.range.len() >= 4 {
                 (
                     Some(node.range[0] as usize + 1),
                     Some(node.range[2] as usize + 1),
@@ -144,44 +258,75 @@ pub fn export_call_graph_d3<P: AsRef<std::path::Path>>(
                 parent_folder,
                 start_line,
                 end_line,
-                is_libsignal: is_libsignal_node(node),
-                dependencies: node.callees.iter().cloned().collect(),
-                dependents: node.callers.iter().cloned().collect(),
+                is_libsignal: filters.is_highlighted(node),
+                dependencies: filters.order(&node.callees).into_iter().cloned().collect(),
+                dependents: filters.order(&node.callers).into_iter().cloned().collect(),
                 kind,
+                node_kind: node.node_kind,
+                crate_name: None,
+                is_test: is_test_function(node),
+                doc: node.doc.clone(),
+                attributes: node.attributes.clone(),
+                requires_text: node.requires_text.clone(),
+                ensures_text: node.ensures_text.clone(),
+                signature: node.signature.clone(),
+                source_url: None,
+                ..Default::default()
             }
         })
         .collect();
 
-    // Create links from the callee occurrences (with call location classification)
-    let mut link_set: HashSet<(String, String, String)> = HashSet::new();
+    // Create links from the callee occurrences (with call location
+    // classification, or `references` for uses of types), one per caller,
+    // callee, location and edge kind with the lines of its calls
+    let mut link_index: HashMap<(String, String, String, Option<EdgeKind>), usize> = HashMap::new();
     let mut links: Vec<D3Link> = Vec::new();
 
-    for node in call_graph.values() {
+    for node in &ordered_nodes {
         for occurrence in &node.callee_occurrences {
-            if call_graph.contains_key(&occurrence.symbol) {
-                let link_type = occurrence
-                    .location
-                    .as_ref()
-                    .map(|loc| loc.as_str())
-                    .unwrap_or("inner")
-                    .to_string();
+            if let Some(callee) = call_graph.get(&occurrence.symbol) {
+                let link_type = if callee.is_type() {
+                    REFERENCES_LINK_TYPE
+                } else {
+                    occurrence
+                        .location
+                        .as_ref()
+                        .map(|loc| loc.as_str())
+                        .unwrap_or("inner")
+                }
+                .to_string();
 
                 let key = (
                     node.symbol.clone(),
                     occurrence.symbol.clone(),
                     link_type.clone(),
+                    occurrence.edge_kind,
                 );
 
-                if link_set.insert(key) {
+                let index = *link_index.entry(key).or_insert_with(|| {
                     links.push(D3Link {
                         source: node.symbol.clone(),
                         target: occurrence.symbol.clone(),
                         link_type,
+                        edge_kind: occurrence.edge_kind,
+                        weight: None,
+                        lines: Vec::new(),
                     });
-                }
+                    links.len() - 1
+                });
+                links[index].lines.push(occurrence.line.max(0) as usize + 1);
             }
         }
     }
+    for link in &mut links {
+        link.lines.sort_unstable();
+        link.lines.dedup();
+    }
+    if filters.is_deterministic() {
+        links.sort_by(|a, b| {
+            (&a.source, &a.target, &a.link_type).cmp(&(&b.source, &b.target, &b.link_type))
+        });
+    }
 
     // Generate timestamp
     let now = chrono::Utc::now();
@@ -191,19 +336,417 @@ pub fn export_call_graph_d3<P: AsRef<std::path::Path>>(
     let metadata = D3GraphMetadata {
         total_nodes: nodes.len(),
         total_edges: links.len(),
-        project_root: scip_data.metadata.project_root.clone(),
+        project_root: project_root.to_string(),
         generated_at: timestamp,
         github_url: None,
+        crates: Vec::new(),
+        ..Default::default()
     };
 
     // Create the full graph structure
-    let graph = D3Graph {
+    let mut graph = D3Graph {
         nodes,
         links,
         metadata,
     };
+    assign_stable_ids(&mut graph);
+    graph
+}
 
-    // Write to file
-    let json = serde_json::to_string_pretty(&graph)?;
-    std::fs::write(output_path, json)
+/// Crate name and generic-free descriptors of a symbol, leaving out the
+/// crate version and generic arguments
+///
+/// Handles SCIP symbols (`scheme manager package version descriptors`) and
+/// probe-verus names (`probe:package/version/descriptors`).
+fn symbol_identity(symbol: &str) -> (String, String) {
+    let fields: Vec<&str> = match symbol.strip_prefix("probe:") {
+        Some(name) => name.splitn(3, '/').collect(),
+        None => symbol.splitn(5, ' ').skip(2).collect(),
+    };
+    match fields.as_slice() {
+        [package, version, descriptors] if version.starts_with(|c: char| c.is_ascii_digit()) => {
+            (package.to_string(), strip_generics(descriptors))
+        }
+        _ => (String::new(), strip_generics(symbol)),
+    }
+}
+
+/// Deterministic ID of a function from its crate, file, display name and
+/// signature (the generic-free descriptor path of its symbol)
+///
+/// Unlike symbols, these IDs don't change when the crate version or the
+/// generic parameters do, so saved viewer layouts and links keep working.
+pub fn stable_node_id(
+    crate_name: &str,
+    relative_path: &str,
+    display_name: &str,
+    signature: &str,
+) -> String {
+    let key = [crate_name, relative_path, display_name, signature].join("\0");
+    format!("{:016x}", fnv1a(key.bytes()))
+}
+
+/// Set `stable_id` on every node (see [`stable_node_id`])
+///
+/// Nodes that hash to the same ID (e.g. impls of a method for different
+/// instantiations of one generic type in the same file) are told apart by a
+/// `-2`, `-3`, ... suffix, in order of start line and symbol.
+pub fn assign_stable_ids(graph: &mut D3Graph) {
+    let mut by_id: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let (crate_name, signature) = symbol_identity(&node.symbol);
+        let id = stable_node_id(
+            &crate_name,
+            &node.relative_path,
+            &node.display_name,
+            &signature,
+        );
+        by_id.entry(id).or_default().push(index);
+    }
+
+    for (id, mut indices) in by_id {
+        if indices.len() > 1 {
+            debug!("{} nodes share the stable ID {}", indices.len(), id);
+            indices.sort_by(|a, b| {
+                let (a, b) = (&graph.nodes[*a], &graph.nodes[*b]);
+                a.start_line
+                    .cmp(&b.start_line)
+                    .then_with(|| a.symbol.cmp(&b.symbol))
+            });
+        }
+        for (n, index) in indices.into_iter().enumerate() {
+            graph.nodes[index].stable_id = match n {
+                0 => id.clone(),
+                n => format!("{}-{}", id, n + 1),
+            };
+        }
+    }
+}
+
+/// Convert a module graph to the D3.js graph structure
+///
+/// Each source file becomes a node whose `functions` field lists the
+/// functions it defines (`symbol` and `display_name`), so the viewer can
+/// click through to them. Links carry the number of calls as `weight`.
+pub fn module_graph_to_d3(module_graph: &ModuleGraph, project_root: &str) -> D3Graph {
+    let nodes: Vec<D3Node> = module_graph
+        .modules
+        .iter()
+        .map(|module| {
+            let path = Path::new(&module.path);
+            let name = |p: Option<&std::ffi::OsStr>| {
+                p.and_then(|name| name.to_str())
+                    .unwrap_or("unknown")
+                    .to_string()
+            };
+            let mut extra = serde_json::Map::new();
+            extra.insert(
+                "functions".to_string(),
+                serde_json::to_value(&module.functions).unwrap_or_default(),
+            );
+            extra.insert("internal_calls".to_string(), module.internal_calls.into());
+            D3Node {
+                id: module.path.clone(),
+                display_name: name(path.file_name()),
+                symbol: module.path.clone(),
+                full_path: module.path.clone(),
+                relative_path: module.path.clone(),
+                file_name: name(path.file_name()),
+                parent_folder: name(path.parent().and_then(|p| p.file_name())),
+                dependencies: module_graph
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.source == module.path)
+                    .map(|edge| edge.target.clone())
+                    .collect(),
+                dependents: module_graph
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.target == module.path)
+                    .map(|edge| edge.source.clone())
+                    .collect(),
+                extra,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    let links: Vec<D3Link> = module_graph
+        .edges
+        .iter()
+        .map(|edge| D3Link {
+            source: edge.source.clone(),
+            target: edge.target.clone(),
+            link_type: "inner".to_string(),
+            edge_kind: None,
+            weight: Some(edge.calls),
+            lines: Vec::new(),
+        })
+        .collect();
+
+    let metadata = D3GraphMetadata {
+        total_nodes: nodes.len(),
+        total_edges: links.len(),
+        project_root: project_root.to_string(),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        ..Default::default()
+    };
+    let mut graph = D3Graph {
+        nodes,
+        links,
+        metadata,
+    };
+    assign_stable_ids(&mut graph);
+    graph
+}
+
+/// Export the call graph in D3.js format, keeping only calls of the given
+/// [`EdgeKind`]s (e.g. `ProofToProof` for the proof-to-lemma subgraph)
+pub fn export_call_graph_d3_filtered<P: AsRef<std::path::Path>>(
+    call_graph: &CallGraph,
+    scip_data: &ScipIndex,
+    output_path: P,
+    edge_kinds: &[EdgeKind],
+    filters: &NodeFilters,
+) -> Result<D3Graph> {
+    let filtered = filter_by_edge_kinds(call_graph, edge_kinds);
+    export_call_graph_d3(&filtered, scip_data, output_path, filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d3_node(symbol: &str, display_name: &str, start_line: usize) -> D3Node {
+        D3Node {
+            id: symbol.to_string(),
+            symbol: symbol.to_string(),
+            display_name: display_name.to_string(),
+            relative_path: "src/field.rs".to_string(),
+            start_line: Some(start_line),
+            ..Default::default()
+        }
+    }
+
+    fn stable_ids(nodes: Vec<D3Node>) -> Vec<String> {
+        let mut graph = D3Graph {
+            nodes,
+            links: Vec::new(),
+            metadata: D3GraphMetadata::default(),
+        };
+        assign_stable_ids(&mut graph);
+        graph.nodes.into_iter().map(|n| n.stable_id).collect()
+    }
+
+    #[test]
+    fn test_stable_ids_ignore_versions_and_generics() {
+        let id = |symbol: &str, start_line| stable_ids(vec![d3_node(symbol, "square", start_line)]);
+
+        let scip = id(
+            "rust-analyzer cargo field 0.1.0 field/Field<T>#square().",
+            10,
+        );
+        assert_eq!(scip[0].len(), 16);
+        assert_eq!(
+            scip,
+            id(
+                "rust-analyzer cargo field 0.2.0 field/Field<Vec<u64>>#square().",
+                12
+            )
+        );
+        assert_eq!(
+            id("probe:field/0.1.0/field/Field<T>#square().", 10),
+            id("probe:field/0.2.0/field/Field<U>#square().", 12)
+        );
+        assert_ne!(
+            scip,
+            id("rust-analyzer cargo field 0.1.0 field/Field#cube().", 10)
+        );
+    }
+
+    #[test]
+    fn test_stable_ids_disambiguate_collisions() {
+        let ids = stable_ids(vec![
+            d3_node(
+                "rust-analyzer cargo f 1.0.0 field/Field<u32>#add().",
+                "add",
+                30,
+            ),
+            d3_node(
+                "rust-analyzer cargo f 1.0.0 field/Field<u64>#add().",
+                "add",
+                20,
+            ),
+        ]);
+        assert_eq!(ids[0], format!("{}-2", ids[1]));
+    }
+    #[test]
+    fn test_module_graph_to_d3() {
+        use crate::module_graph::{ModuleEdge, ModuleFunction, ModuleNode};
+
+        let module = |path: &str, functions: &[&str]| ModuleNode {
+            path: path.to_string(),
+            functions: functions
+                .iter()
+                .map(|name| ModuleFunction {
+                    symbol: format!("m/{name}()."),
+                    display_name: name.to_string(),
+                })
+                .collect(),
+            internal_calls: 0,
+        };
+        let module_graph = ModuleGraph {
+            modules: vec![
+                module("src/lemmas.rs", &["lemma_a", "lemma_b"]),
+                module("src/main.rs", &["main"]),
+            ],
+            edges: vec![ModuleEdge {
+                source: "src/main.rs".to_string(),
+                target: "src/lemmas.rs".to_string(),
+                calls: 3,
+            }],
+        };
+
+        let graph = module_graph_to_d3(&module_graph, "/p");
+        assert_eq!(graph.metadata.total_nodes, 2);
+        assert_eq!(graph.links[0].weight, Some(3));
+
+        let lemmas = &graph.nodes[0];
+        assert_eq!(lemmas.display_name, "lemmas.rs");
+        assert_eq!(lemmas.parent_folder, "src");
+        assert_eq!(lemmas.dependents, vec!["src/main.rs"]);
+        assert_eq!(lemmas.extra["functions"][1]["display_name"], "lemma_b");
+
+        let json = serde_json::to_value(&graph).unwrap();
+        assert_eq!(json["links"][0]["weight"], 3);
+        assert_eq!(json["nodes"][1]["functions"][0]["symbol"], "m/main().");
+    }
+
+    #[test]
+    fn test_call_lines_and_multiplicity() {
+        use crate::test_support;
+        use crate::types::{CalleeOccurrence, FunctionNode};
+
+        let node = |symbol: &str, calls: &[(&str, i32)]| {
+            let callees: Vec<&str> = calls.iter().map(|(callee, _)| *callee).collect();
+            FunctionNode {
+                callee_occurrences: calls
+                    .iter()
+                    .map(|(callee, line)| CalleeOccurrence {
+                        symbol: callee.to_string(),
+                        line: *line,
+                        column: None,
+                        location: None,
+                        edge_kind: None,
+                    })
+                    .collect(),
+                ..test_support::node(symbol, &callees)
+            }
+        };
+        // 0-based lines, the one on line 7 recorded twice
+        let call_graph: CallGraph = [
+            node(
+                "main",
+                &[("helper", 7), ("helper", 2), ("helper", 7), ("other", 4)],
+            ),
+            node("helper", &[]),
+            node("other", &[]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(call_graph["main"].call_sites("helper").len(), 2);
+        let graph = call_graph_to_d3(&call_graph, "/p", &NodeFilters::default());
+        let lines = |target: &str| {
+            graph
+                .links
+                .iter()
+                .find(|link| link.target == target)
+                .map(|link| link.lines.clone())
+                .unwrap()
+        };
+        assert_eq!(graph.links.len(), 2);
+        assert_eq!(lines("helper"), vec![3, 8]);
+        assert_eq!(lines("other"), vec![5]);
+
+        let dot =
+            crate::export_dot::generate_call_graph_dot_string(&call_graph, &NodeFilters::default());
+        assert!(dot.contains("\"main\" -> \"helper\" [label=\"×2\"]"));
+        assert!(dot.contains("\"main\" -> \"other\"\n"));
+    }
+
+    /// Compare `actual` with `testdata/golden/<name>`; `UPDATE_GOLDEN=1`
+    /// rewrites the file instead
+    fn assert_golden(name: &str, actual: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata/golden")
+            .join(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, actual).unwrap();
+        }
+        let expected = std::fs::read_to_string(&path).unwrap();
+        assert!(actual == expected, "{name} differs from {}", path.display());
+    }
+
+    #[test]
+    fn test_exports_match_golden_files() {
+        use crate::types::{CalleeOccurrence, FunctionNode};
+
+        let node = |name: &str, file: &str, line: usize, calls: &[&str]| FunctionNode {
+            symbol: format!(
+                "rust-analyzer cargo demo 0.1.0 {}/{name}().",
+                &file[..file.len() - 3]
+            ),
+            display_name: name.to_string(),
+            file_path: format!("/p/src/{file}"),
+            relative_path: format!("src/{file}"),
+            callees: calls.iter().map(|callee| callee.to_string()).collect(),
+            callee_occurrences: calls
+                .iter()
+                .enumerate()
+                .map(|(i, callee)| CalleeOccurrence {
+                    symbol: callee.to_string(),
+                    line: (line + i) as i32,
+                    column: None,
+                    location: None,
+                    edge_kind: None,
+                })
+                .collect(),
+            body: Some(format!("fn {name}() {{}}")),
+            start_line: Some(line),
+            end_line: Some(line + calls.len() + 1),
+            ..Default::default()
+        };
+        let sym =
+            |path: &str, name: &str| format!("rust-analyzer cargo demo 0.1.0 {path}/{name}().");
+        let (parse, check, lemma_a, lemma_b) = (
+            sym("main", "parse"),
+            sym("main", "check"),
+            sym("lemmas", "lemma_a"),
+            sym("lemmas", "lemma_b"),
+        );
+        let nodes = vec![
+            node("main", "main.rs", 1, &[&parse, &check, &lemma_a]),
+            node("parse", "main.rs", 10, &[&lemma_b]),
+            node("check", "main.rs", 20, &[&lemma_a, &lemma_b, "std/fmt()."]),
+            node("lemma_a", "lemmas.rs", 1, &[&lemma_b]),
+            node("lemma_b", "lemmas.rs", 8, &[]),
+        ];
+
+        // Graphs built in different orders export the same bytes
+        for nodes in [nodes.clone(), nodes.into_iter().rev().collect()] {
+            let call_graph: CallGraph = nodes.into_iter().collect();
+            let filters = NodeFilters::default();
+
+            let atoms = serde_json::to_string_pretty(&call_graph_to_atoms(&call_graph)).unwrap();
+            assert_golden("atoms.json", &atoms);
+
+            let mut graph = call_graph_to_d3(&call_graph, "/p", &filters);
+            graph.metadata.generated_at = "1970-01-01T00:00:00+00:00".to_string();
+            assert_golden("d3.json", &serde_json::to_string_pretty(&graph).unwrap());
+
+            let dot = crate::export_dot::generate_call_graph_dot_string(&call_graph, &filters);
+            assert_golden("call_graph.dot", &dot);
+        }
+    }
 }
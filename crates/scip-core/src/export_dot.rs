@@ -2,99 +2,385 @@
 //!
 //! This module provides functions to export call graphs as DOT files:
 //! - `generate_call_graph_dot` - Full call graph as DOT
+//! - `generate_edge_kind_subgraph_dot` - Only calls of given `EdgeKind`s (e.g. proof-to-proof)
+//! - `generate_module_graph_dot` - One node per source file, edges weighted by call counts
 //! - `generate_file_subgraph_dot` - Subgraph for a specific file
 //! - `generate_files_subgraph_dot` - Subgraph for multiple files
 //! - `generate_function_subgraph_dot` - Subgraph starting from specific functions
-//! - `generate_call_graph_svg` - Simple SVG visualization
-
-use crate::types::FunctionNode;
-use log::debug;
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+//! - `generate_function_subgraph_dot_with_paths` - Same, highlighting call paths to a target
+//!   and, optionally, the trusted functions the matched functions rely on; large subgraphs
+//!   can be sampled down to a node budget
+//! - `generate_call_graph_svg` - SVG drawn in process with a force-directed layout
+//! - `add_source_links_to_dot` / `link_dot_file` - Make function nodes link to
+//!   their source on GitHub (`URL` attributes, clickable in the SVG)
+//!
+//! Every exporter takes [`NodeFilters`] deciding which functions are drawn as
+//! project sources and which are left out, and whether nodes and edges are
+//! written sorted (the default, for stable diffs of committed DOT files). Functions only referenced from
+//! requires/ensures clauses (`FunctionNode::spec_refs`) are linked by dashed
+//! (dotted in function subgraphs) edges, calls from spawned tasks
+//! (`EdgeKind::Spawn`) by green ones.
+//!
+//! Next to each DOT file, the exporters render SVG and PNG images with the
+//! [`RenderBackend`] set on the filters. The default ([`RenderBackend::Auto`])
+//! uses Graphviz when `dot` is installed and otherwise falls back to an
+//! in-process SVG layout (with the `layout` feature) or to the DOT file alone,
+//! so a missing Graphviz never fails an export.
+
+use crate::analysis::trust::trust_base;
+use crate::call_graph::{collect_neighborhood, filter_by_edge_kinds, find_call_paths};
+use crate::call_graph_svg::{render_call_graph_svg, SvgLayoutOptions};
+use crate::error::{Error, Result};
+use crate::module_graph::{build_module_graph, ModuleGraph};
+use crate::node_filter::NodeFilters;
+use crate::sampling::{is_sample_placeholder, sample_call_graph};
+use crate::source_links::SourceLinks;
+use crate::symbol_index::{name_matches, SymbolResolver};
+use crate::types::{CallGraph, EdgeKind, FunctionNode};
+use log::{debug, warn};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use std::process::Command;
 
-/// Helper function to generate both SVG and PNG files from a DOT file using Graphviz
-pub fn generate_svg_and_png_from_dot(dot_path: &str) -> std::io::Result<()> {
-    let svg_path = if let Some(stripped) = dot_path.strip_suffix(".dot") {
-        format!("{stripped}.svg")
-    } else {
-        format!("{dot_path}.svg")
-    };
+/// Attributes of edges into spawned tasks
+const SPAWN_EDGE_STYLE: &str = "color=darkgreen, arrowhead=empty";
+
+/// Attributes of edges from functions to the types they use
+const REFERENCES_EDGE_STYLE: &str = "color=steelblue, style=dashed, arrowhead=odiamond";
 
-    let png_path = if let Some(stripped) = dot_path.strip_suffix(".dot") {
-        format!("{stripped}.png")
+/// Shape and fill of type nodes (see `CallGraphOptions::with_types`)
+const TYPE_NODE_STYLE: &str = "shape=note, fillcolor=lightcyan";
+
+/// Whether `callee` is referenced only from the requires/ensures clauses of
+/// `node`, not called at runtime
+fn is_spec_only_ref(node: &FunctionNode, callee: &str) -> bool {
+    node.spec_refs.contains(callee) && !node.calls().any(|call| call == callee)
+}
+
+/// Whether `node` calls `callee` from a spawned task or `async` block
+fn is_spawn_edge(node: &FunctionNode, callee: &str) -> bool {
+    node.callee_occurrences.iter().any(|occurrence| {
+        occurrence.symbol == callee && occurrence.edge_kind == Some(EdgeKind::Spawn)
+    })
+}
+
+/// `×N` label of an edge standing for more than one call site
+fn multiplicity_label(node: &FunctionNode, callee: &str) -> Option<String> {
+    match node.call_sites(callee).len() {
+        0 | 1 => None,
+        calls => Some(format!("label=\"×{calls}\"")),
+    }
+}
+
+/// Edge attributes drawing spec-only references dashed, spawn edges green
+/// with an empty arrowhead and uses of types (`callee_is_type`) as blue
+/// dashed references, and labeling edges with their number of call sites
+fn edge_attributes(node: &FunctionNode, callee: &str, callee_is_type: bool) -> String {
+    if callee_is_type {
+        return format!(" [{REFERENCES_EDGE_STYLE}]");
+    }
+    let attributes: Vec<String> = is_spec_only_ref(node, callee)
+        .then(|| "style=dashed".to_string())
+        .into_iter()
+        .chain(is_spawn_edge(node, callee).then(|| SPAWN_EDGE_STYLE.to_string()))
+        .chain(multiplicity_label(node, callee))
+        .collect();
+    if attributes.is_empty() {
+        String::new()
     } else {
-        format!("{dot_path}.png")
-    };
+        format!(" [{}]", attributes.join(", "))
+    }
+}
+
+/// Write the edges between `nodes`, keeping those whose both ends are in
+/// `clusters` (symbol to cluster index).
+///
+/// With [`NodeFilters::aggregate_cluster_edges`], calls within a cluster stay
+/// function-level edges, while all calls from one cluster to another become a
+/// single edge between the two clusters, labeled with the number of calls it
+/// stands for and drawn thicker the more there are. Such an edge connects the
+/// first function of each cluster, clipped to the cluster borders, so the
+/// graph needs `compound=true`.
+fn push_call_edges(
+    dot: &mut String,
+    nodes: &[&FunctionNode],
+    clusters: &HashMap<&str, usize>,
+    filters: &NodeFilters,
+) {
+    let aggregate = filters.aggregates_cluster_edges();
+    let types: HashSet<&str> = nodes
+        .iter()
+        .filter(|node| node.is_type())
+        .map(|node| node.symbol.as_str())
+        .collect();
+    let mut anchors: BTreeMap<usize, &str> = BTreeMap::new();
+    let mut cluster_calls: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+    for node in nodes {
+        let symbol = node.symbol.as_str();
+        let Some(&from) = clusters.get(symbol) else {
+            continue;
+        };
+        anchors.entry(from).or_insert(symbol);
+        for callee in filters.order(&node.callees) {
+            let Some(&to) = clusters.get(callee.as_str()) else {
+                continue;
+            };
+            if aggregate && from != to {
+                *cluster_calls.entry((from, to)).or_default() += 1;
+                continue;
+            }
+            let style = edge_attributes(node, callee, types.contains(callee.as_str()));
+            dot.push_str(&format!("  \"{symbol}\" -> \"{callee}\"{style}\n"));
+        }
+    }
+
+    for ((from, to), calls) in cluster_calls {
+        let (source, target) = (anchors[&from], anchors[&to]);
+        let penwidth = 1.0 + (calls as f64).log2();
+        dot.push_str(&format!(
+            "  \"{source}\" -> \"{target}\" [ltail=cluster_{from}, lhead=cluster_{to}, label=\"{calls}\", penwidth={penwidth:.1}, weight={calls}]\n"
+        ));
+    }
+}
 
-    // Generate SVG
-    let svg_status = Command::new("dot")
-        .args(["-Tsvg", dot_path, "-o", &svg_path])
-        .status()?;
-    if !svg_status.success() {
-        return Err(std::io::Error::other(format!(
-            "Failed to generate SVG: dot exited with {svg_status}"
-        )));
+/// Cluster index of every symbol in `groups`, numbered like the
+/// `cluster_N` subgraphs
+fn cluster_index<'a>(groups: &BTreeMap<String, Vec<&'a FunctionNode>>) -> HashMap<&'a str, usize> {
+    groups
+        .values()
+        .enumerate()
+        .flat_map(|(cluster_id, nodes)| {
+            nodes
+                .iter()
+                .map(move |node| (node.symbol.as_str(), cluster_id))
+        })
+        .collect()
+}
+
+/// Largest graph (in nodes) [`RenderBackend::InProcess`] lays out; its
+/// layout gets slow and cluttered well before Graphviz's does
+pub const IN_PROCESS_MAX_NODES: usize = 300;
+
+/// How the DOT exporters render images next to the DOT file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderBackend {
+    /// Write the DOT file only
+    None,
+    /// SVG and PNG with Graphviz's `dot`, failing with
+    /// [`Error::GraphvizMissing`] if it is not installed
+    GraphvizCli,
+    /// SVG only, laid out in process (needs the `layout` feature); graphs
+    /// of more than [`IN_PROCESS_MAX_NODES`] nodes are skipped with a warning
+    InProcess,
+    /// Graphviz if `dot` is installed, else [`RenderBackend::InProcess`]
+    /// when built with the `layout` feature, else the DOT file only
+    #[default]
+    Auto,
+}
+
+impl RenderBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenderBackend::None => "none",
+            RenderBackend::GraphvizCli => "graphviz",
+            RenderBackend::InProcess => "in-process",
+            RenderBackend::Auto => "auto",
+        }
     }
 
-    // Generate PNG
-    let png_status = Command::new("dot")
-        .args(["-Tpng", dot_path, "-o", &png_path])
-        .status()?;
-    if !png_status.success() {
-        return Err(std::io::Error::other(format!(
-            "Failed to generate PNG: dot exited with {png_status}"
-        )));
+    /// The backend to use on this machine, checked up front so a long
+    /// export doesn't fail (or silently skip images) at the very end.
+    ///
+    /// `Auto` becomes a concrete backend, warning with how to get Graphviz if
+    /// `dot` is missing. `GraphvizCli` fails if `dot` is missing, and
+    /// `InProcess` if scip-core was built without the `layout` feature.
+    pub fn resolve(self) -> Result<Self> {
+        match self {
+            RenderBackend::None => Ok(self),
+            RenderBackend::GraphvizCli if graphviz_available() => Ok(self),
+            RenderBackend::GraphvizCli => Err(Error::GraphvizMissing),
+            RenderBackend::InProcess if cfg!(feature = "layout") => Ok(self),
+            RenderBackend::InProcess => Err(Error::FeatureDisabled {
+                output: "In-process rendering",
+                feature: "layout",
+            }),
+            RenderBackend::Auto if graphviz_available() => Ok(RenderBackend::GraphvizCli),
+            RenderBackend::Auto if cfg!(feature = "layout") => {
+                warn!(
+                    "Graphviz `dot` was not found; rendering SVG only, in process. \
+                     Install Graphviz (e.g. `apt install graphviz` or `brew install graphviz`) \
+                     for PNG output and better layouts"
+                );
+                Ok(RenderBackend::InProcess)
+            }
+            RenderBackend::Auto => {
+                warn!(
+                    "Graphviz `dot` was not found; writing DOT files only. \
+                     Install Graphviz (e.g. `apt install graphviz` or `brew install graphviz`) \
+                     to also get SVG and PNG, or pass `--render none` to silence this"
+                );
+                Ok(RenderBackend::None)
+            }
+        }
+    }
+
+    /// The image files rendered next to `dot_path` (for `Auto`, those of
+    /// Graphviz)
+    pub fn output_paths(&self, dot_path: &str) -> Vec<String> {
+        match self {
+            RenderBackend::None => Vec::new(),
+            RenderBackend::InProcess => vec![rendered_path(dot_path, "svg")],
+            RenderBackend::GraphvizCli | RenderBackend::Auto => {
+                vec![
+                    rendered_path(dot_path, "svg"),
+                    rendered_path(dot_path, "png"),
+                ]
+            }
+        }
     }
+}
+
+impl std::fmt::Display for RenderBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for RenderBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(RenderBackend::None),
+            "graphviz" | "graphviz-cli" | "dot" => Ok(RenderBackend::GraphvizCli),
+            "in-process" | "layout" => Ok(RenderBackend::InProcess),
+            "auto" => Ok(RenderBackend::Auto),
+            _ => Err(format!(
+                "unknown render backend: {s} (expected auto, graphviz, in-process or none)"
+            )),
+        }
+    }
+}
+
+/// Whether Graphviz's `dot` can be run
+pub fn graphviz_available() -> bool {
+    Command::new("dot")
+        .arg("-V")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// `dot_path` with its `.dot` extension replaced by (or, without one,
+/// followed by) `extension`
+fn rendered_path(dot_path: &str, extension: &str) -> String {
+    match dot_path.strip_suffix(".dot") {
+        Some(stripped) => format!("{stripped}.{extension}"),
+        None => format!("{dot_path}.{extension}"),
+    }
+}
+
+/// Render the images of a DOT file with `backend` (see [`RenderBackend`])
+pub fn render_dot_file(dot_path: &str, backend: RenderBackend) -> Result<()> {
+    match backend {
+        RenderBackend::None => Ok(()),
+        RenderBackend::GraphvizCli => generate_svg_and_png_from_dot(dot_path),
+        RenderBackend::InProcess => render_svg_in_process(dot_path),
+        RenderBackend::Auto => match generate_svg_and_png_from_dot(dot_path) {
+            Err(Error::GraphvizMissing) if cfg!(feature = "layout") => {
+                render_svg_in_process(dot_path)
+            }
+            Err(Error::GraphvizMissing) => {
+                debug!("Graphviz `dot` not found; wrote {dot_path} only");
+                Ok(())
+            }
+            result => result,
+        },
+    }
+}
 
+/// Helper function to generate both SVG and PNG files from a DOT file using Graphviz
+pub fn generate_svg_and_png_from_dot(dot_path: &str) -> Result<()> {
+    run_dot("-Tsvg", "SVG", dot_path, &rendered_path(dot_path, "svg"))?;
+    run_dot("-Tpng", "PNG", dot_path, &rendered_path(dot_path, "png"))?;
+    Ok(())
+}
+
+/// Lay out a DOT file with layout-rs and write its SVG
+#[cfg(feature = "layout")]
+fn render_svg_in_process(dot_path: &str) -> Result<()> {
+    use layout::backends::svg::SVGWriter;
+    use layout::gv::{DotParser, GraphBuilder};
+
+    let dot = std::fs::read_to_string(dot_path)?;
+    let graph = DotParser::new(&dot).process().map_err(Error::Layout)?;
+    let mut builder = GraphBuilder::new();
+    builder.visit_graph(&graph);
+    let mut visual_graph = builder.get();
+    if visual_graph.num_nodes() > IN_PROCESS_MAX_NODES {
+        warn!(
+            "{dot_path} has {} nodes, more than the {IN_PROCESS_MAX_NODES} rendered in process; \
+             install Graphviz to render it",
+            visual_graph.num_nodes()
+        );
+        return Ok(());
+    }
+    let mut svg = SVGWriter::new();
+    visual_graph.do_it(false, false, false, &mut svg);
+    std::fs::write(rendered_path(dot_path, "svg"), svg.finalize())?;
     Ok(())
 }
 
-/// Helper function to determine if a node is from libsignal
-fn is_libsignal_node(node: &FunctionNode) -> bool {
-    node.symbol.contains("libsignal-protocol")
-        || node.symbol.contains("libsignal-core")
-        || node.symbol.contains("libsignal-net")
-        || node.symbol.contains("libsignal-keytrans")
-        || node.symbol.contains("libsignal-svrb")
-        || node.symbol.contains("libsignal")
-        || node.relative_path.contains("libsignal")
-        || node.symbol.contains("zkgroup")
-        || node.symbol.contains("poksho")
-        || node.symbol.contains("zkcredential")
-        || node.symbol.contains("usernames")
+#[cfg(not(feature = "layout"))]
+fn render_svg_in_process(_dot_path: &str) -> Result<()> {
+    Err(Error::FeatureDisabled {
+        output: "In-process rendering",
+        feature: "layout",
+    })
+}
+
+/// Render `dot_path` to `output_path` with Graphviz's `dot`
+fn run_dot(format_flag: &str, format: &str, dot_path: &str, output_path: &str) -> Result<()> {
+    let status = Command::new("dot")
+        .args([format_flag, dot_path, "-o", output_path])
+        .status()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::GraphvizMissing,
+            _ => Error::Io(e),
+        })?;
+    if !status.success() {
+        return Err(Error::Graphviz {
+            format: format.to_string(),
+            status,
+        });
+    }
+    Ok(())
 }
 
 /// Generate a DOT format string for the call graph
 ///
 /// This function returns the DOT content as a String, which is useful when you
-/// want to print to stdout or process the content before writing.
-pub fn generate_call_graph_dot_string(call_graph: &HashMap<String, FunctionNode>) -> String {
+/// want to print to stdout or process the content before writing. When
+/// highlight filters are set, functions they don't match are drawn in grey.
+pub fn generate_call_graph_dot_string(call_graph: &CallGraph, filters: &NodeFilters) -> String {
+    let call_graph = &filters.apply(call_graph);
     let mut dot = String::from("digraph call_graph {\n");
     dot.push_str("  rankdir=LR;\n");
+    if filters.aggregates_cluster_edges() {
+        dot.push_str("  compound=true;\n");
+    }
     dot.push_str("  node [shape=box, style=filled, fillcolor=lightblue, fontname=Helvetica];\n");
     dot.push_str("  edge [color=black];\n\n");
 
-    // Filter out unwanted paths
-    let skip_paths = [
-        "libsignal/rust/protocol/benches",
-        "libsignal/rust/protocol/tests",
-        "libsignal/rust/protocol/examples",
-    ];
-    let filtered_nodes: Vec<&FunctionNode> = call_graph
-        .values()
-        .filter(|node| !skip_paths.iter().any(|p| node.file_path.contains(p)))
-        .collect();
-
     // Group nodes by module/directory
+    let ordered_nodes = filters.order_nodes(call_graph.values());
     let mut module_groups: BTreeMap<String, Vec<&FunctionNode>> = BTreeMap::new();
-    for node in &filtered_nodes {
+    for &node in &ordered_nodes {
         let path = std::path::Path::new(&node.file_path);
         let module = path
             .parent()
             .map(|p| p.display().to_string())
             .unwrap_or_else(|| "root".to_string());
-        module_groups.entry(module).or_default().push(*node);
+        module_groups.entry(module).or_default().push(node);
     }
 
     for (cluster_id, (module, nodes)) in module_groups.iter().enumerate() {
@@ -103,20 +389,20 @@ pub fn generate_call_graph_dot_string(call_graph: &HashMap<String, FunctionNode>
         ));
         for node in nodes {
             let label = node.display_name.clone();
-            let tooltip = if let Some(body) = &node.body {
-                let plain = body.replace(['\n', '\r'], " ").replace('"', "' ");
-                if plain.len() > 200 {
-                    let truncated = &plain[..200];
-                    format!("{truncated}...")
-                } else {
-                    plain
-                }
+            let tooltip = filters.tooltip_content().dot_tooltip(node);
+            let symbol = &node.symbol;
+            let fill = if filters.has_highlights() && !filters.is_highlighted(node) {
+                ", fillcolor=lightgray"
             } else {
-                "".to_string()
+                ""
+            };
+            let fill = if node.is_type() {
+                format!(", {TYPE_NODE_STYLE}{fill}")
+            } else {
+                fill.to_string()
             };
-            let symbol = &node.symbol;
             dot.push_str(&format!(
-                "    \"{symbol}\" [label=\"{label}\", tooltip=\"{tooltip}\"]\n"
+                "    \"{symbol}\" [label=\"{label}\", tooltip=\"{tooltip}\"{fill}]\n"
             ));
         }
         dot.push_str("  }\n");
@@ -125,47 +411,124 @@ pub fn generate_call_graph_dot_string(call_graph: &HashMap<String, FunctionNode>
     dot.push('\n');
 
     // Add edges
-    let filtered_symbols: HashSet<_> = filtered_nodes.iter().map(|n| &n.symbol).collect();
-    for node in &filtered_nodes {
-        for callee in &node.callees {
-            if filtered_symbols.contains(callee) {
-                let symbol = &node.symbol;
-                dot.push_str(&format!("  \"{symbol}\" -> \"{callee}\"\n"));
-            }
-        }
+    let clusters = cluster_index(&module_groups);
+    push_call_edges(&mut dot, &ordered_nodes, &clusters, filters);
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Generate a DOT format string for a module graph
+///
+/// Nodes are labeled with the file path and function count, and list the
+/// functions in their tooltip; edges are labeled with the number of calls
+/// and drawn thicker the more calls they stand for.
+pub fn generate_module_graph_dot_string(module_graph: &ModuleGraph) -> String {
+    let mut dot = String::from("digraph module_graph {\n");
+    dot.push_str("  rankdir=LR;\n");
+    dot.push_str("  node [shape=box, style=filled, fillcolor=lightblue, fontname=Helvetica];\n");
+    dot.push_str("  edge [color=black, fontname=Helvetica, fontsize=10];\n\n");
+
+    for module in &module_graph.modules {
+        let path = &module.path;
+        let count = module.functions.len();
+        let names: Vec<&str> = module
+            .functions
+            .iter()
+            .map(|function| function.display_name.as_str())
+            .collect();
+        let tooltip = names.join(", ").replace('"', "'");
+        dot.push_str(&format!(
+            "  \"{path}\" [label=\"{path}\\n{count} functions\", tooltip=\"{tooltip}\"]\n"
+        ));
+    }
+
+    dot.push('\n');
+    for edge in &module_graph.edges {
+        let (source, target, calls) = (&edge.source, &edge.target, edge.calls);
+        let penwidth = 1.0 + (calls as f64).log2();
+        dot.push_str(&format!(
+            "  \"{source}\" -> \"{target}\" [label=\"{calls}\", penwidth={penwidth:.1}]\n"
+        ));
     }
 
     dot.push_str("}\n");
     dot
 }
 
+/// Generate a DOT file of the module graph (see `module_graph`), leaving
+/// out the functions skipped by `filters`
+///
+/// Like [`generate_call_graph_dot`], this also generates SVG and PNG files.
+pub fn generate_module_graph_dot(
+    call_graph: &CallGraph,
+    output_path: &str,
+    filters: &NodeFilters,
+) -> Result<()> {
+    let module_graph = build_module_graph(&filters.apply(call_graph));
+    debug!(
+        "Module graph has {} modules and {} edges",
+        module_graph.modules.len(),
+        module_graph.edges.len()
+    );
+    std::fs::write(output_path, generate_module_graph_dot_string(&module_graph))?;
+    render_dot_file(output_path, filters.render_backend())?;
+    Ok(())
+}
+
 /// Generate a DOT file format for the call graph that can be rendered by Graphviz
 ///
 /// This writes the DOT file and also generates SVG and PNG files using Graphviz.
 pub fn generate_call_graph_dot(
-    call_graph: &HashMap<String, FunctionNode>,
+    call_graph: &CallGraph,
     output_path: &str,
-) -> std::io::Result<()> {
-    let dot = generate_call_graph_dot_string(call_graph);
+    filters: &NodeFilters,
+) -> Result<()> {
+    let dot = generate_call_graph_dot_string(call_graph, filters);
     std::fs::write(output_path, &dot)?;
-    generate_svg_and_png_from_dot(output_path)?;
+    render_dot_file(output_path, filters.render_backend())?;
     Ok(())
 }
 
+/// Generate a DOT file containing only calls of the given [`EdgeKind`]s and
+/// the functions taking part in them, e.g. `&[EdgeKind::ProofToProof]` for
+/// the proof-to-lemma subgraph.
+///
+/// Like [`generate_call_graph_dot`], this also generates SVG and PNG files.
+pub fn generate_edge_kind_subgraph_dot(
+    call_graph: &CallGraph,
+    edge_kinds: &[EdgeKind],
+    output_path: &str,
+    filters: &NodeFilters,
+) -> Result<()> {
+    let filtered = filter_by_edge_kinds(call_graph, edge_kinds);
+    let kinds: Vec<&str> = edge_kinds.iter().map(|kind| kind.as_str()).collect();
+    debug!(
+        "Edge kinds [{}] keep {} of {} functions",
+        kinds.join(", "),
+        filtered.len(),
+        call_graph.len()
+    );
+    generate_call_graph_dot(&filtered, output_path, filters)
+}
+
 /// Generate a DOT file for a subgraph containing only nodes from a specific file path
 pub fn generate_file_subgraph_dot(
-    call_graph: &HashMap<String, FunctionNode>,
+    call_graph: &CallGraph,
     file_path: &str,
     output_path: &str,
-) -> std::io::Result<()> {
+    filters: &NodeFilters,
+) -> Result<()> {
+    let call_graph = &filters.apply(call_graph);
     let mut dot = String::from("digraph file_subgraph {\n");
     dot.push_str("  rankdir=LR;\n");
     dot.push_str("  node [shape=box, style=filled, fontname=Helvetica];\n");
     dot.push_str("  edge [color=black];\n\n");
 
     // Find nodes that belong to the specified file
-    let file_nodes: Vec<&FunctionNode> = call_graph
-        .values()
+    let file_nodes: Vec<&FunctionNode> = filters
+        .order_nodes(call_graph.values())
+        .into_iter()
         .filter(|node| {
             let requested_filename = Path::new(file_path)
                 .file_name()
@@ -179,26 +542,16 @@ pub fn generate_file_subgraph_dot(
         .collect();
 
     if file_nodes.is_empty() {
-        let matching_paths: HashSet<_> = call_graph
+        let similar_paths: BTreeSet<_> = call_graph
             .values()
             .filter(|node| node.file_path.contains(file_path))
-            .map(|node| &node.file_path)
+            .map(|node| node.file_path.clone())
             .collect();
 
-        if !matching_paths.is_empty() {
-            let mut message = format!(
-                "No exact match for file path: {file_path}\n\nHere are some similar paths:\n"
-            );
-            for path in matching_paths {
-                message.push_str(&format!("  - {path}\n"));
-            }
-            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, message));
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("No functions found in file path: {file_path}"),
-            ));
-        }
+        return Err(Error::FileNotFound {
+            paths: vec![file_path.to_string()],
+            similar_paths: similar_paths.into_iter().collect(),
+        });
     }
 
     let file_symbols: HashSet<_> = file_nodes.iter().map(|n| &n.symbol).collect();
@@ -220,8 +573,13 @@ pub fn generate_file_subgraph_dot(
         };
 
         let symbol = &node.symbol;
+        let (fillcolor, shape) = if node.is_type() {
+            ("lightcyan", ", shape=note")
+        } else {
+            (fillcolor, "")
+        };
         dot.push_str(&format!(
-            "  \"{symbol}\" [label=\"{label}\", fillcolor={fillcolor}]\n"
+            "  \"{symbol}\" [label=\"{label}\", fillcolor={fillcolor}{shape}]\n"
         ));
     }
 
@@ -229,34 +587,42 @@ pub fn generate_file_subgraph_dot(
 
     // Add edges
     for node in &file_nodes {
-        for callee in &node.callees {
+        for callee in filters.order(&node.callees) {
             if file_symbols.contains(callee) {
                 let symbol = &node.symbol;
-                dot.push_str(&format!("  \"{symbol}\" -> \"{callee}\"\n"));
+                let callee_is_type = call_graph.get(callee).is_some_and(FunctionNode::is_type);
+                let style = edge_attributes(node, callee, callee_is_type);
+                dot.push_str(&format!("  \"{symbol}\" -> \"{callee}\"{style}\n"));
             }
         }
     }
 
     dot.push_str("}\n");
     std::fs::write(output_path, &dot)?;
-    generate_svg_and_png_from_dot(output_path)?;
+    render_dot_file(output_path, filters.render_backend())?;
     Ok(())
 }
 
 /// Generate a DOT file for a subgraph containing nodes from multiple files
 pub fn generate_files_subgraph_dot(
-    call_graph: &HashMap<String, FunctionNode>,
+    call_graph: &CallGraph,
     file_paths: &[String],
     output_path: &str,
-) -> std::io::Result<()> {
+    filters: &NodeFilters,
+) -> Result<()> {
+    let call_graph = &filters.apply(call_graph);
     let mut dot = String::from("digraph files_subgraph {\n");
     dot.push_str("  rankdir=LR;\n");
+    if filters.aggregates_cluster_edges() {
+        dot.push_str("  compound=true;\n");
+    }
     dot.push_str("  node [shape=box, style=filled, fontname=Helvetica];\n");
     dot.push_str("  edge [color=black];\n\n");
 
     // Find nodes that belong to any of the specified files
-    let file_nodes: Vec<&FunctionNode> = call_graph
-        .values()
+    let file_nodes: Vec<&FunctionNode> = filters
+        .order_nodes(call_graph.values())
+        .into_iter()
         .filter(|node| {
             file_paths.iter().any(|file_path| {
                 let requested_filename = Path::new(file_path)
@@ -272,14 +638,12 @@ pub fn generate_files_subgraph_dot(
         .collect();
 
     if file_nodes.is_empty() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("No functions found in file paths: {file_paths:?}"),
-        ));
+        return Err(Error::FileNotFound {
+            paths: file_paths.to_vec(),
+            similar_paths: Vec::new(),
+        });
     }
 
-    let file_symbols: HashSet<_> = file_nodes.iter().map(|n| &n.symbol).collect();
-
     // Group by file
     let mut file_groups: BTreeMap<String, Vec<&FunctionNode>> = BTreeMap::new();
     for node in &file_nodes {
@@ -305,9 +669,12 @@ pub fn generate_files_subgraph_dot(
         for node in nodes {
             let label = &node.display_name;
             let symbol = &node.symbol;
-            dot.push_str(&format!(
-                "    \"{symbol}\" [label=\"{label}\", fillcolor=lightblue]\n"
-            ));
+            let style = if node.is_type() {
+                TYPE_NODE_STYLE
+            } else {
+                "fillcolor=lightblue"
+            };
+            dot.push_str(&format!("    \"{symbol}\" [label=\"{label}\", {style}]\n"));
         }
 
         dot.push_str("  }\n");
@@ -316,218 +683,272 @@ pub fn generate_files_subgraph_dot(
     dot.push('\n');
 
     // Add edges
-    for node in &file_nodes {
-        for callee in &node.callees {
-            if file_symbols.contains(callee) {
-                let symbol = &node.symbol;
-                dot.push_str(&format!("  \"{symbol}\" -> \"{callee}\"\n"));
-            }
-        }
-    }
+    let clusters = cluster_index(&file_groups);
+    push_call_edges(&mut dot, &file_nodes, &clusters, filters);
 
     dot.push_str("}\n");
     std::fs::write(output_path, &dot)?;
-    generate_svg_and_png_from_dot(output_path)?;
+    render_dot_file(output_path, filters.render_backend())?;
     Ok(())
 }
 
+/// Check whether a node matches a user-supplied function name or symbol.
+///
+/// Accepts full SCIP symbols (with or without the trailing `.`), display
+/// names, `Type::method` / `Type#method` and path suffixes; see
+/// [`SymbolResolver`] for lookups over a whole graph.
+pub fn function_name_matches(node: &FunctionNode, function_name: &str) -> bool {
+    name_matches(&node.symbol, &node.display_name, function_name)
+}
+
 /// Generate a DOT file for a subgraph starting from specific functions with transitive dependencies
+///
+/// Functions matched by the highlight filters are drawn as project sources.
+/// With `highlighted_sources_only` (callers-only subgraphs), only the callers
+/// reachable from highlighted entry points are kept.
+#[allow(clippy::too_many_arguments)]
 pub fn generate_function_subgraph_dot(
-    call_graph: &HashMap<String, FunctionNode>,
+    call_graph: &CallGraph,
     function_names: &[String],
     output_path: &str,
     include_callees: bool,
     include_callers: bool,
     depth: Option<usize>,
-    filter_non_libsignal_sources: bool,
-) -> std::io::Result<()> {
+    highlighted_sources_only: bool,
+    filters: &NodeFilters,
+) -> Result<()> {
+    generate_function_subgraph_dot_with_paths(
+        call_graph,
+        function_names,
+        output_path,
+        include_callees,
+        include_callers,
+        depth,
+        highlighted_sources_only,
+        None,
+        0,
+        false,
+        None,
+        filters,
+    )
+}
+
+/// Like [`generate_function_subgraph_dot`], but additionally highlights up to
+/// `max_paths` call chains from the matched functions to `highlight_paths_to`.
+///
+/// Functions on a highlighted path are always included in the subgraph, and
+/// path edges are drawn in bold red.
+///
+/// With `highlight_untrusted`, the trust base of every matched function (see
+/// [`trust_base`]) is included as well, and its trusted functions are drawn
+/// in orange with the reasons they are trusted.
+///
+/// With `max_nodes`, larger subgraphs are sampled down to that many nodes
+/// (see [`sample_call_graph`]): the matched, path and trusted functions are
+/// kept, and the rest beyond the budget is drawn as one dashed `… N more in
+/// module X` node per module.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_function_subgraph_dot_with_paths(
+    call_graph: &CallGraph,
+    function_names: &[String],
+    output_path: &str,
+    include_callees: bool,
+    include_callers: bool,
+    depth: Option<usize>,
+    highlighted_sources_only: bool,
+    highlight_paths_to: Option<&str>,
+    max_paths: usize,
+    highlight_untrusted: bool,
+    max_nodes: Option<usize>,
+    filters: &NodeFilters,
+) -> Result<()> {
+    let call_graph = &filters.apply(call_graph);
     let mut dot = String::from("digraph function_subgraph {\n");
     dot.push_str("  rankdir=LR;\n");
     dot.push_str("  node [shape=box, style=filled, fontname=Helvetica];\n");
     dot.push_str("  edge [color=black];\n\n");
 
     // Find nodes that match the specified function names
-    let mut matched_nodes = Vec::new();
+    let resolver = SymbolResolver::from_call_graph(call_graph);
     let mut matched_symbols = HashSet::new();
+    let mut unresolved = Vec::new();
 
     for function_name in function_names {
-        let matches: Vec<_> = call_graph
-            .values()
-            .filter(|node| {
-                if node.symbol == *function_name {
-                    return true;
-                }
-                let normalized_symbol_query = function_name.trim_end_matches('.');
-                if node.symbol.trim_end_matches('.') == normalized_symbol_query {
-                    return true;
-                }
-                if node.display_name == *function_name {
-                    return true;
-                }
-                let normalized_name = function_name.trim_end_matches("()");
-                if let Some(func_part) = node.symbol.rsplit('#').next() {
-                    let clean_func = func_part.trim_end_matches('.').trim_end_matches("()");
-                    if clean_func == normalized_name {
-                        return true;
-                    }
-                }
-                if function_name.contains('#') {
-                    if let Some(symbol_suffix) = node.symbol.rsplit('/').next() {
-                        let clean_suffix =
-                            symbol_suffix.trim_end_matches('.').trim_end_matches("()");
-                        let clean_query =
-                            function_name.trim_end_matches('.').trim_end_matches("()");
-                        if clean_suffix.contains(clean_query) || clean_suffix.ends_with(clean_query)
-                        {
-                            return true;
-                        }
-                    }
-                }
-                if node.symbol.contains(&format!("#{}", normalized_name))
-                    && (node.symbol.ends_with(&format!("#{}().", normalized_name))
-                        || node.symbol.ends_with(&format!("#{}.", normalized_name))
-                        || node.symbol.contains(&format!("#{}/", normalized_name)))
-                {
-                    return true;
-                }
-                false
-            })
-            .collect();
-
-        for node in matches {
-            matched_nodes.push(node);
-            matched_symbols.insert(node.symbol.clone());
+        match resolver.resolve_all(function_name) {
+            Ok(symbols) => matched_symbols.extend(symbols.into_iter().map(str::to_string)),
+            Err(e) => unresolved.push(e),
         }
     }
 
-    if matched_nodes.is_empty() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("No functions found matching the provided names: {function_names:?}"),
-        ));
+    let mut unresolved = unresolved.into_iter();
+    if matched_symbols.is_empty() {
+        if let Some(first) = unresolved.next() {
+            for e in unresolved {
+                warn!("{e}");
+            }
+            return Err(first.into());
+        }
+    }
+    for e in unresolved {
+        warn!("{e}");
     }
 
     debug!(
         "Found {} functions matching the provided names",
-        matched_nodes.len()
+        matched_symbols.len()
     );
 
     // Build the transitive closure of dependencies
-    let mut included_symbols = matched_symbols.clone();
-    let mut queue = VecDeque::new();
-
-    for symbol in &matched_symbols {
-        queue.push_back((symbol.clone(), 0));
-    }
-
-    while let Some((symbol, current_depth)) = queue.pop_front() {
-        if let Some(node) = call_graph.get(&symbol) {
-            if include_callees {
-                let should_include_callees = match depth {
-                    Some(max_depth) => current_depth < max_depth,
-                    None => true,
-                };
+    let included_symbols = collect_neighborhood(
+        call_graph,
+        &matched_symbols,
+        include_callees,
+        include_callers,
+        depth,
+    );
 
-                if should_include_callees {
-                    for callee in &node.callees {
-                        if !included_symbols.contains(callee) {
-                            included_symbols.insert(callee.clone());
-                            queue.push_back((callee.clone(), current_depth + 1));
-                        }
+    // Keep only what is reachable from highlighted sources if requested
+    let final_included_symbols = if highlighted_sources_only && include_callers && !include_callees
+    {
+        let mut has_incoming_edge = HashSet::new();
+        for symbol in &included_symbols {
+            if let Some(node) = call_graph.get(symbol) {
+                for callee in &node.callees {
+                    if included_symbols.contains(callee) {
+                        has_incoming_edge.insert(callee.clone());
                     }
                 }
             }
+        }
 
-            if include_callers {
-                let should_include_callers = match depth {
-                    Some(max_depth) => current_depth < max_depth,
-                    None => true,
-                };
+        let source_nodes: Vec<_> = included_symbols
+            .iter()
+            .filter(|symbol| !has_incoming_edge.contains(*symbol))
+            .cloned()
+            .collect();
 
-                if should_include_callers {
-                    for caller in &node.callers {
-                        if !included_symbols.contains(caller) {
-                            included_symbols.insert(caller.clone());
-                            queue.push_back((caller.clone(), current_depth + 1));
-                        }
-                    }
+        let highlighted_sources: Vec<_> = source_nodes
+            .iter()
+            .filter(|symbol| {
+                call_graph
+                    .get(*symbol)
+                    .is_some_and(|node| filters.is_highlighted(node))
+            })
+            .cloned()
+            .collect();
+
+        let mut filtered_symbols = HashSet::new();
+        for source in &highlighted_sources {
+            let mut stack = vec![source.clone()];
+            let mut visited = HashSet::new();
+
+            while let Some(symbol) = stack.pop() {
+                if visited.contains(&symbol) {
+                    continue;
                 }
-            }
-        }
-    }
+                visited.insert(symbol.clone());
+                filtered_symbols.insert(symbol.clone());
 
-    // Filter for libsignal sources if requested
-    let final_included_symbols =
-        if filter_non_libsignal_sources && include_callers && !include_callees {
-            let mut has_incoming_edge = HashSet::new();
-            for symbol in &included_symbols {
-                if let Some(node) = call_graph.get(symbol) {
+                if let Some(node) = call_graph.get(&symbol) {
                     for callee in &node.callees {
-                        if included_symbols.contains(callee) {
-                            has_incoming_edge.insert(callee.clone());
+                        if included_symbols.contains(callee) && !visited.contains(callee) {
+                            stack.push(callee.clone());
                         }
                     }
                 }
             }
+        }
+        filtered_symbols
+    } else {
+        included_symbols
+    };
 
-            let source_nodes: Vec<_> = included_symbols
-                .iter()
-                .filter(|symbol| !has_incoming_edge.contains(*symbol))
-                .cloned()
-                .collect();
-
-            let libsignal_sources: Vec<_> = source_nodes
-                .iter()
-                .filter(|symbol| {
-                    call_graph
-                        .get(*symbol)
-                        .map(is_libsignal_node)
-                        .unwrap_or(false)
-                })
-                .cloned()
-                .collect();
-
-            let mut filtered_symbols = HashSet::new();
-            for source in &libsignal_sources {
-                let mut stack = vec![source.clone()];
-                let mut visited = HashSet::new();
-
-                while let Some(symbol) = stack.pop() {
-                    if visited.contains(&symbol) {
-                        continue;
-                    }
-                    visited.insert(symbol.clone());
-                    filtered_symbols.insert(symbol.clone());
-
-                    if let Some(node) = call_graph.get(&symbol) {
-                        for callee in &node.callees {
-                            if included_symbols.contains(callee) && !visited.contains(callee) {
-                                stack.push(callee.clone());
-                            }
-                        }
+    // Find the call chains to highlight and make sure they are drawn
+    let mut final_included_symbols = final_included_symbols;
+    let mut path_symbols: HashSet<String> = HashSet::new();
+    let mut path_edges: HashSet<(String, String)> = HashSet::new();
+    if let Some(target_name) = highlight_paths_to {
+        let target_symbols = resolver.resolve_all(target_name).unwrap_or_else(|e| {
+            warn!("{e}");
+            Vec::new()
+        });
+        let mut source_symbols: Vec<&String> = matched_symbols.iter().collect();
+        source_symbols.sort();
+
+        let mut path_count = 0;
+        for source in &source_symbols {
+            for target in &target_symbols {
+                let remaining = max_paths.saturating_sub(path_count);
+                for path in find_call_paths(call_graph, source, target, remaining, None) {
+                    for pair in path.windows(2) {
+                        path_edges.insert((pair[0].clone(), pair[1].clone()));
                     }
+                    path_symbols.extend(path);
+                    path_count += 1;
                 }
             }
-            filtered_symbols
+        }
+
+        if path_count == 0 {
+            warn!("No call paths found to '{target_name}'");
         } else {
-            included_symbols
-        };
+            debug!("Highlighting {path_count} call path(s) to '{target_name}'");
+        }
+        final_included_symbols.extend(path_symbols.iter().cloned());
+    }
+
+    // Find the trusted functions the matched functions rely on
+    let mut untrusted: HashMap<String, BTreeSet<&'static str>> = HashMap::new();
+    if highlight_untrusted {
+        for symbol in &matched_symbols {
+            let report = trust_base(call_graph, symbol);
+            for function in report.trusted {
+                let reasons = untrusted.entry(function.symbol).or_default();
+                reasons.extend(function.reasons.iter().map(|reason| reason.as_str()));
+            }
+        }
+        debug!("Highlighting {} trusted function(s)", untrusted.len());
+        final_included_symbols.extend(untrusted.keys().cloned());
+    }
 
-    // Separate libsignal nodes from non-libsignal nodes
-    let mut libsignal_symbols = HashSet::new();
+    // Collapse what doesn't fit the node budget into per-module placeholders
+    let sampled;
+    let call_graph = match max_nodes {
+        Some(max_nodes) if final_included_symbols.len() > max_nodes => {
+            let mut pinned = matched_symbols.clone();
+            pinned.extend(path_symbols.iter().cloned());
+            pinned.extend(untrusted.keys().cloned());
+            sampled = sample_call_graph(
+                &call_graph.subgraph(&final_included_symbols),
+                &pinned,
+                max_nodes,
+            );
+            debug!(
+                "Sampled {} functions down to {} nodes",
+                final_included_symbols.len(),
+                sampled.len()
+            );
+            final_included_symbols = sampled.keys().cloned().collect();
+            &sampled
+        }
+        _ => call_graph,
+    };
+
+    // Separate highlighted (project source) nodes from the rest
+    let mut highlighted_symbols = HashSet::new();
     for symbol in &final_included_symbols {
         if call_graph
             .get(symbol)
-            .map(is_libsignal_node)
-            .unwrap_or(false)
+            .is_some_and(|node| filters.is_highlighted(node))
         {
-            libsignal_symbols.insert(symbol.clone());
+            highlighted_symbols.insert(symbol.clone());
         }
     }
 
     // Group nodes by file path
+    let ordered_symbols = filters.order(&final_included_symbols);
     let mut file_groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
-    for symbol in &final_included_symbols {
+    for &symbol in &ordered_symbols {
         if let Some(node) = call_graph.get(symbol) {
             file_groups
                 .entry(node.file_path.clone())
@@ -547,11 +968,9 @@ pub fn generate_function_subgraph_dot(
         dot.push_str(&format!("    label = \"{file_label}\";\n"));
         dot.push_str("    style=filled;\n");
 
-        let is_libsignal_cluster = symbols
-            .iter()
-            .any(|s| call_graph.get(s).map(is_libsignal_node).unwrap_or(false));
+        let is_highlighted_cluster = symbols.iter().any(|s| highlighted_symbols.contains(s));
 
-        if is_libsignal_cluster {
+        if is_highlighted_cluster {
             dot.push_str("    color=lightblue;\n");
         } else {
             dot.push_str("    color=lightgrey;\n");
@@ -562,32 +981,46 @@ pub fn generate_function_subgraph_dot(
         for symbol in symbols {
             if let Some(node) = call_graph.get(symbol) {
                 let label = &node.display_name;
-                let tooltip = if let Some(body) = &node.body {
-                    let plain = body.replace(['\n', '\r'], " ").replace('"', "' ");
-                    if plain.len() > 200 {
-                        format!("{}...", &plain[..200])
-                    } else {
-                        plain
-                    }
-                } else {
-                    "".to_string()
-                };
-
-                let (fillcolor, style) = if matched_symbols.contains(symbol) {
-                    if libsignal_symbols.contains(symbol) {
+                let tooltip = filters.tooltip_content().dot_tooltip(node);
+
+                let (fillcolor, style) = if is_sample_placeholder(node) {
+                    ("lightyellow", "filled,dashed")
+                } else if node.is_type() {
+                    ("lightcyan", "filled")
+                } else if matched_symbols.contains(symbol) {
+                    if highlighted_symbols.contains(symbol) {
                         ("blue", "filled")
                     } else {
                         ("green", "filled,dotted")
                     }
-                } else if libsignal_symbols.contains(symbol) {
+                } else if highlighted_symbols.contains(symbol) {
                     ("white", "filled")
                 } else {
                     ("lightgray", "filled,dotted")
                 };
 
+                let (label, fillcolor) = match untrusted.get(symbol) {
+                    Some(reasons) => (
+                        format!(
+                            "{}\\n[{}]",
+                            label,
+                            reasons.iter().copied().collect::<Vec<_>>().join(", ")
+                        ),
+                        "orange",
+                    ),
+                    None => (label.clone(), fillcolor),
+                };
+
+                let path_border = if path_symbols.contains(symbol) {
+                    ", color=red, penwidth=2"
+                } else {
+                    ""
+                };
+                let shape = if node.is_type() { ", shape=note" } else { "" };
+
                 dot.push_str(&format!(
-                    "    \"{}\" [label=\"{}\", tooltip=\"{}\", fillcolor={}, style=\"{}\"]\n",
-                    node.symbol, label, tooltip, fillcolor, style
+                    "    \"{}\" [label=\"{}\", tooltip=\"{}\", fillcolor={}, style=\"{}\"{}{}]\n",
+                    node.symbol, label, tooltip, fillcolor, style, shape, path_border
                 ));
             }
         }
@@ -598,26 +1031,39 @@ pub fn generate_function_subgraph_dot(
     dot.push('\n');
 
     // Draw edges
-    for symbol in &final_included_symbols {
+    for &symbol in &ordered_symbols {
         if let Some(node) = call_graph.get(symbol) {
-            for callee in &node.callees {
+            for callee in filters.order(&node.callees) {
                 if final_included_symbols.contains(callee) {
-                    let caller_is_libsignal = libsignal_symbols.contains(symbol);
-                    let callee_is_libsignal = libsignal_symbols.contains(callee);
-
-                    let edge_style = if caller_is_libsignal && callee_is_libsignal {
+                    let caller_is_highlighted = highlighted_symbols.contains(symbol);
+                    let callee_is_highlighted = highlighted_symbols.contains(callee);
+
+                    let on_path = path_edges.contains(&(symbol.clone(), callee.clone()));
+
+                    let edge_style = if on_path {
+                        "color=red, penwidth=2.5"
+                    } else if call_graph.get(callee).is_some_and(FunctionNode::is_type) {
+                        REFERENCES_EDGE_STYLE
+                    } else if is_spawn_edge(node, callee) {
+                        SPAWN_EDGE_STYLE
+                    } else if is_spec_only_ref(node, callee) {
+                        "color=purple, style=dotted"
+                    } else if caller_is_highlighted && callee_is_highlighted {
                         "color=blue, style=dashed"
-                    } else if caller_is_libsignal && !callee_is_libsignal {
+                    } else if caller_is_highlighted && !callee_is_highlighted {
                         "color=blue"
-                    } else if !caller_is_libsignal && callee_is_libsignal {
+                    } else if !caller_is_highlighted && callee_is_highlighted {
                         "color=orange, style=dashed"
                     } else {
                         "color=black, style=dashed"
                     };
 
+                    let label = multiplicity_label(node, callee)
+                        .map(|label| format!(", {label}"))
+                        .unwrap_or_default();
                     dot.push_str(&format!(
-                        "  \"{}\" -> \"{}\" [{}]\n",
-                        node.symbol, callee, edge_style
+                        "  \"{}\" -> \"{}\" [{}{}]\n",
+                        node.symbol, callee, edge_style, label
                     ));
                 }
             }
@@ -626,74 +1072,152 @@ pub fn generate_function_subgraph_dot(
 
     dot.push_str("}\n");
 
-    let final_output_path = if let Some(d) = depth {
-        if let Some(stripped) = output_path.strip_suffix(".dot") {
-            format!("{stripped}_depth_{d}.dot")
-        } else {
-            format!("{output_path}_depth_{d}")
-        }
-    } else {
-        output_path.to_string()
-    };
-
+    let final_output_path = function_subgraph_output_path(output_path, depth);
     std::fs::write(&final_output_path, &dot)?;
-    generate_svg_and_png_from_dot(&final_output_path)?;
+    render_dot_file(&final_output_path, filters.render_backend())?;
     Ok(())
 }
 
-/// Generate a simple SVG visualization of the call graph
-pub fn generate_call_graph_svg(
-    call_graph: &HashMap<String, FunctionNode>,
-    output_path: &str,
-) -> std::io::Result<()> {
-    let width = 1200;
-    let height = 800;
-    let mut svg = format!(
-        r#"<svg xmlns='http://www.w3.org/2000/svg' width='{width}' height='{height}' style='background:#fff;font-family:sans-serif'>\n"#
-    );
-
-    let nodes: Vec<_> = call_graph.values().collect();
-
-    // Initial positions based on hash
-    let mut positions = HashMap::new();
-    let mut rng = std::hash::DefaultHasher::new();
-    for node in &nodes {
-        use std::hash::{Hash, Hasher};
-        node.symbol.hash(&mut rng);
-        let x = (rng.finish() % 800) as f64 + 200.0;
-        rng.write_u8(1);
-        let y = (rng.finish() % 600) as f64 + 100.0;
-        positions.insert(&node.symbol, (x, y));
+/// Path of the DOT file written by [`generate_function_subgraph_dot`] for
+/// `output_path`: with a depth limit, `_depth_<depth>` is added to the name
+pub fn function_subgraph_output_path(output_path: &str, depth: Option<usize>) -> String {
+    match depth {
+        Some(d) => match output_path.strip_suffix(".dot") {
+            Some(stripped) => format!("{stripped}_depth_{d}.dot"),
+            None => format!("{output_path}_depth_{d}"),
+        },
+        None => output_path.to_string(),
     }
+}
 
-    // Arrow marker
-    svg.push_str("<defs><marker id='arrow' markerWidth='10' markerHeight='10' refX='10' refY='5' orient='auto' markerUnits='strokeWidth'><path d='M0,0 L10,5 L0,10 z' fill='#888'/></marker></defs>\n");
-
-    // Draw edges
-    for node in call_graph.values() {
-        let (x1, y1) = positions[&node.symbol];
-        for callee in &node.callees {
-            if let Some(&(x2, y2)) = positions.get(callee) {
-                svg.push_str(&format!(
-                    "<line x1='{x1}' y1='{y1}' x2='{x2}' y2='{y2}' stroke='#888' stroke-width='2' marker-end='url(#arrow)'/>\n"
+/// Add `URL` attributes linking the function nodes of a DOT graph to their
+/// source, so they are clickable in the rendered SVG
+///
+/// Node statements are matched by their quoted symbol; nodes that are not
+/// functions of `call_graph` (e.g. files in a module graph) or have no
+/// source link are left as they are.
+pub fn add_source_links_to_dot(dot: &str, call_graph: &CallGraph, links: &SourceLinks) -> String {
+    let mut linked = String::with_capacity(dot.len());
+    for line in dot.lines() {
+        let trimmed = line.trim_start();
+        let symbol = trimmed
+            .strip_prefix('"')
+            .and_then(|rest| rest.split_once("\" ["))
+            .map(|(symbol, _)| symbol)
+            .filter(|symbol| !symbol.contains('"'));
+        let url = symbol
+            .and_then(|symbol| call_graph.get(symbol))
+            .and_then(|node| links.node_url(node));
+        match (symbol, url) {
+            (Some(symbol), Some(url)) => {
+                // Indentation, quoted symbol and ` [`
+                let (statement, attributes) =
+                    line.split_at(line.len() - trimmed.len() + symbol.len() + 4);
+                linked.push_str(&format!(
+                    "{statement}URL=\"{url}\", target=\"_blank\", {attributes}"
                 ));
             }
+            _ => linked.push_str(line),
         }
+        linked.push('\n');
     }
+    linked
+}
 
-    // Draw nodes
-    for node in call_graph.values() {
-        let (x, y) = positions[&node.symbol];
-        let label = &node.display_name;
-        svg.push_str(&format!(
-            "<circle cx='{x}' cy='{y}' r='30' fill='lightblue' stroke='#333'/>\n"
-        ));
-        svg.push_str(&format!(
-            "<text x='{x}' y='{}' text-anchor='middle' font-size='10'>{label}</text>\n",
-            y + 4.0
-        ));
+/// Add source links to a DOT file written by one of the exporters and render
+/// its images again with `backend`
+pub fn link_dot_file(
+    dot_path: &str,
+    call_graph: &CallGraph,
+    links: &SourceLinks,
+    backend: RenderBackend,
+) -> Result<()> {
+    let dot = std::fs::read_to_string(dot_path)?;
+    std::fs::write(dot_path, add_source_links_to_dot(&dot, call_graph, links))?;
+    render_dot_file(dot_path, backend)
+}
+
+/// Draw the call graph as an SVG with the in-process force-directed layout
+/// of [`render_call_graph_svg`], without Graphviz
+pub fn generate_call_graph_svg(
+    call_graph: &CallGraph,
+    output_path: &str,
+    filters: &NodeFilters,
+    options: &SvgLayoutOptions,
+) -> Result<()> {
+    std::fs::write(
+        output_path,
+        render_call_graph_svg(call_graph, filters, options),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_render_backend() {
+        assert_eq!("auto".parse(), Ok(RenderBackend::Auto));
+        assert_eq!("dot".parse(), Ok(RenderBackend::GraphvizCli));
+        assert_eq!("in-process".parse(), Ok(RenderBackend::InProcess));
+        assert!("png".parse::<RenderBackend>().is_err());
+        assert_eq!(
+            RenderBackend::GraphvizCli.output_paths("graph.dot"),
+            ["graph.svg", "graph.png"]
+        );
+        assert_eq!(
+            RenderBackend::InProcess.output_paths("graph"),
+            ["graph.svg"]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let dot_path = dir.path().join("graph.dot");
+        let dot_path = dot_path.to_str().unwrap();
+        std::fs::write(dot_path, "digraph g {\n  \"a\" -> \"b\"\n}\n").unwrap();
+        render_dot_file(dot_path, RenderBackend::None).unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let rendered = render_dot_file(dot_path, RenderBackend::InProcess);
+        if cfg!(feature = "layout") {
+            rendered.unwrap();
+            let svg = std::fs::read_to_string(dir.path().join("graph.svg")).unwrap();
+            assert!(svg.contains("<svg"));
+        } else {
+            assert!(matches!(rendered, Err(Error::FeatureDisabled { .. })));
+        }
     }
 
-    svg.push_str("</svg>");
-    std::fs::write(output_path, svg)
+    #[test]
+    fn test_aggregate_cluster_edges() {
+        let node = |symbol: &str, dir: &str, callees: &[&str]| {
+            test_support::node(symbol, callees).in_file(&format!("{dir}/lib.rs"))
+        };
+        let call_graph: CallGraph = [
+            node("a", "x", &["b", "c", "d"]),
+            node("b", "x", &["c"]),
+            node("c", "y", &["d"]),
+            node("d", "y", &[]),
+        ]
+        .into_iter()
+        .collect();
+
+        let dot = generate_call_graph_dot_string(&call_graph, &NodeFilters::new());
+        assert_eq!(dot.matches(" -> ").count(), 5);
+        assert!(!dot.contains("compound"));
+
+        let filters = NodeFilters::new().aggregate_cluster_edges(true);
+        let dot = generate_call_graph_dot_string(&call_graph, &filters);
+        let edges: Vec<&str> = dot.lines().filter(|line| line.contains(" -> ")).collect();
+        assert_eq!(
+            edges,
+            [
+                "  \"a\" -> \"b\"",
+                "  \"c\" -> \"d\"",
+                "  \"a\" -> \"c\" [ltail=cluster_0, lhead=cluster_1, label=\"3\", penwidth=2.6, weight=3]",
+            ]
+        );
+        assert!(dot.contains("compound=true;"));
+    }
 }
@@ -0,0 +1,218 @@
+//! Self-contained interactive HTML report.
+//!
+//! `write_interactive_report` writes a single HTML file embedding the D3 graph
+//! JSON together with a small viewer (no external scripts), so the report can
+//! be opened straight from disk without running the `web/` dev server:
+//!
+//! - search by function or file name
+//! - collapsible clusters, one per source file
+//! - node coloring by verification status, Halstead effort or function mode
+//!
+//! Function signatures are written to a bodies file next to the report (see
+//! [`write_node_bodies`]) and fetched when a function is selected, which
+//! browsers only allow when the report is served over HTTP.
+
+use crate::error::{Error, Result};
+use crate::export_d3::{call_graph_to_d3, write_node_bodies};
+use crate::node_filter::NodeFilters;
+pub use crate::types::VerificationStatus;
+use crate::types::{CallGraph, D3Graph};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+const REPORT_TEMPLATE: &str = include_str!("../templates/report.html");
+
+/// Per-function values shown in the report
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeReportMetrics {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_status: Option<VerificationStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halstead_effort: Option<f64>,
+}
+
+/// Report metrics keyed by node id (the SCIP symbol for graphs built from
+/// SCIP data)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReportMetrics {
+    nodes: HashMap<String, NodeReportMetrics>,
+}
+
+impl ReportMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, node_id: &str) -> Option<&NodeReportMetrics> {
+        self.nodes.get(node_id)
+    }
+
+    pub fn set_verification_status(&mut self, node_id: &str, status: VerificationStatus) {
+        self.nodes
+            .entry(node_id.to_string())
+            .or_default()
+            .verification_status = Some(status);
+    }
+
+    pub fn set_halstead_effort(&mut self, node_id: &str, effort: f64) {
+        self.nodes
+            .entry(node_id.to_string())
+            .or_default()
+            .halstead_effort = Some(effort);
+    }
+
+    /// Read `verification_status` and `halstead_effort` from the nodes of an
+    /// enriched graph
+    pub fn from_graph(graph: &D3Graph) -> Self {
+        let mut metrics = Self::new();
+        for node in &graph.nodes {
+            if let Some(status) = node.verification_status {
+                metrics.set_verification_status(&node.id, status);
+            }
+            if let Some(effort) = node.extra.get("halstead_effort").and_then(|e| e.as_f64()) {
+                metrics.set_halstead_effort(&node.id, effort);
+            }
+        }
+        metrics
+    }
+
+    /// Same as [`ReportMetrics::from_graph`], for an untyped graph.json (as
+    /// written by the pipeline)
+    pub fn from_graph_json(graph: &serde_json::Value) -> Self {
+        let mut metrics = Self::new();
+        let nodes = graph["nodes"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for node in nodes {
+            let Some(id) = node["id"].as_str() else {
+                continue;
+            };
+            if let Ok(status) = VerificationStatus::deserialize(&node["verification_status"]) {
+                metrics.set_verification_status(id, status);
+            }
+            if let Some(effort) = node["halstead_effort"].as_f64() {
+                metrics.set_halstead_effort(id, effort);
+            }
+        }
+        metrics
+    }
+}
+
+#[derive(Serialize)]
+struct ReportData<'a> {
+    graph: &'a D3Graph,
+    metrics: &'a ReportMetrics,
+}
+
+/// Render the report for an already-built D3 graph
+pub fn render_interactive_report(
+    graph: &D3Graph,
+    metrics: &ReportMetrics,
+    title: &str,
+) -> Result<String> {
+    let data = serde_json::to_string(&ReportData { graph, metrics }).map_err(Error::Serialize)?;
+    // `</script>` inside a string must not end the embedding script element
+    let data = data.replace('<', "\\u003c");
+    let title = html_escape::encode_text(title);
+    Ok(REPORT_TEMPLATE
+        .replace("__TITLE__", &title)
+        .replace("__DATA__", &data))
+}
+
+/// Write the call graph as a self-contained interactive HTML report, with
+/// the signatures of its functions next to it
+pub fn write_interactive_report<P: AsRef<Path>>(
+    call_graph: &CallGraph,
+    metrics: &ReportMetrics,
+    output_path: P,
+) -> Result<()> {
+    let filters = NodeFilters::default();
+    let mut graph = call_graph_to_d3(call_graph, "", &filters);
+    graph.metadata.bodies_file = write_node_bodies(call_graph, output_path.as_ref(), &filters)?;
+    let html = render_interactive_report(&graph, metrics, "Call graph report")?;
+    std::fs::write(output_path, html)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::node;
+
+    fn embedded_data(html: &str) -> serde_json::Value {
+        let start = html.find("id=\"report-data\">").unwrap() + "id=\"report-data\">".len();
+        let end = start + html[start..].find("</script>").unwrap();
+        serde_json::from_str(&html[start..end]).unwrap()
+    }
+
+    #[test]
+    fn test_report_embeds_graph_and_metrics() {
+        let main = node("a", &["b"])
+            .with_display_name("main")
+            .with_body("fn main() {\n    helper();\n}");
+        let helper = node("b", &[]).with_display_name("helper");
+        let call_graph: CallGraph = [main, helper].into_iter().collect();
+        let mut metrics = ReportMetrics::new();
+        metrics.set_verification_status("a", VerificationStatus::Verified);
+        metrics.set_halstead_effort("b", 42.5);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.html");
+        write_interactive_report(&call_graph, &metrics, &path).unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+
+        assert!(!html.contains("__DATA__"));
+        assert!(!html.contains("<script src"));
+        let data = embedded_data(&html);
+        assert_eq!(data["graph"]["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(data["metrics"]["a"]["verification_status"], "verified");
+        assert_eq!(data["metrics"]["b"]["halstead_effort"], 42.5);
+
+        // Signatures are left out of the page, in a file fetched on demand
+        assert!(!html.contains("fn main()"));
+        assert_eq!(
+            data["graph"]["metadata"]["bodies_file"],
+            "report.bodies.json"
+        );
+        let bodies = std::fs::read_to_string(dir.path().join("report.bodies.json")).unwrap();
+        assert_eq!(bodies, r#"{"a":"fn main()"}"#);
+    }
+
+    #[test]
+    fn test_report_escapes_script_end_tags() {
+        let call_graph: CallGraph = [node("a", &[]).with_display_name("</script><b>")]
+            .into_iter()
+            .collect();
+        let graph = call_graph_to_d3(&call_graph, "", &NodeFilters::default());
+        let html = render_interactive_report(&graph, &ReportMetrics::new(), "<Report>").unwrap();
+
+        assert!(html.contains("<title>&lt;Report&gt;</title>"));
+        assert_eq!(
+            embedded_data(&html)["graph"]["nodes"][0]["display_name"],
+            "</script><b>"
+        );
+    }
+
+    #[test]
+    fn test_metrics_from_graph_json() {
+        let graph = serde_json::json!({
+            "nodes": [
+                {"id": "a", "verification_status": "failed", "halstead_effort": 10.0},
+                {"id": "b", "verification_status": "bogus"},
+                {"id": "c"}
+            ]
+        });
+        let metrics = ReportMetrics::from_graph_json(&graph);
+
+        assert_eq!(
+            metrics.get("a").unwrap().verification_status,
+            Some(VerificationStatus::Failed)
+        );
+        assert_eq!(metrics.get("a").unwrap().halstead_effort, Some(10.0));
+        assert!(metrics.get("b").is_none());
+        assert!(metrics.get("c").is_none());
+    }
+}
@@ -0,0 +1,498 @@
+//! Per-function markdown dossiers.
+//!
+//! A dossier collects what a design doc usually says about one function:
+//! its signature, spec clauses (with Halstead metrics), the lemmas its proof
+//! uses, caller and callee tables, verification status and a mermaid
+//! diagram of its direct calls.
+//!
+//! Spec and proof metrics come from atoms JSON as written by
+//! `compute_metrics` / `compute_proof_metrics`, matched to graph nodes by
+//! `identifier` (a node's symbol, or its identifier as normalized by
+//! [`SymbolNormalizer`]), and verification status from [`ReportMetrics`]. Without
+//! them the dossier lists the spec clauses found in the body and leaves the
+//! rest out. With [`SourceLinks`], source locations link to GitHub.
+
+use crate::call_graph::{detect_decl_kind, parse_function_sections};
+use crate::error::Result;
+use crate::export_html::ReportMetrics;
+use crate::source_links::SourceLinks;
+use crate::symbol_index::{SymbolEntry, SymbolResolver};
+use crate::symbol_scheme::SymbolNormalizer;
+use crate::types::{CallGraph, CallLocation, DeclKind, FunctionNode};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Spec clause lists in the `metrics` object of an atom, by clause name
+const SPEC_CLAUSES: [(&str, &str); 3] = [
+    ("requires", "requires_specs"),
+    ("ensures", "ensures_specs"),
+    ("decreases", "decreases_specs"),
+];
+
+/// Keywords ending the signature part of a function body
+const SPEC_KEYWORDS: [&str; 5] = ["requires", "ensures", "recommends", "decreases", "returns"];
+
+/// Render the dossiers of all project functions matching `function_name` (a
+/// symbol, display name, `Type::method` or path suffix), separated by rules
+///
+/// `atoms` may be empty. Fails with [`crate::Error::SymbolResolution`] if no
+/// function matches, with suggestions for misspelled names.
+pub fn render_dossiers(
+    call_graph: &CallGraph,
+    function_name: &str,
+    metrics: &ReportMetrics,
+    atoms: &[Value],
+    links: Option<&SourceLinks>,
+) -> Result<String> {
+    let resolver = SymbolResolver::new(
+        call_graph
+            .values()
+            .filter(|node| !node.file_path.starts_with("external:"))
+            .map(SymbolEntry::from),
+    );
+    let matches = resolver.resolve_all(function_name)?;
+    let normalizer = SymbolNormalizer::new(
+        call_graph
+            .values()
+            .map(|node| (node.symbol.as_str(), node.display_name.as_str())),
+    );
+
+    let dossiers: Vec<String> = matches
+        .into_iter()
+        .map(|symbol| {
+            let atom = atoms.iter().find(|atom| {
+                let identifier = atom["identifier"].as_str();
+                identifier == Some(symbol) || identifier == normalizer.identifier(symbol)
+            });
+            render_dossier(call_graph, symbol, metrics, atom, links)
+        })
+        .collect();
+    Ok(dossiers.join("\n---\n\n"))
+}
+
+/// Render the dossier of the function `symbol`, with spec and proof metrics
+/// from its atom when given
+///
+/// # Panics
+/// If `symbol` is not in the graph.
+pub fn render_dossier(
+    call_graph: &CallGraph,
+    symbol: &str,
+    metrics: &ReportMetrics,
+    atom: Option<&Value>,
+    links: Option<&SourceLinks>,
+) -> String {
+    let node = &call_graph[symbol];
+    let mut md = String::new();
+
+    let _ = writeln!(md, "# `{}`\n", node.display_name);
+    let _ = writeln!(md, "| | |\n|---|---|");
+    let _ = writeln!(md, "| Symbol | `{}` |", cell(&node.symbol));
+    let _ = writeln!(md, "| Location | {} |", location(node, links));
+    let _ = writeln!(md, "| Mode | {} |", mode(node).as_str());
+    let _ = writeln!(md, "| Verification | {} |", status(metrics, symbol));
+    md.push('\n');
+
+    if let Some(doc) = &node.doc {
+        let _ = writeln!(md, "{}\n", doc.trim());
+    }
+
+    let signature = atom
+        .and_then(|atom| atom["signature"]["signature"].as_str())
+        .map(str::to_string)
+        .or_else(|| node.body.as_deref().map(signature_of));
+    if let Some(signature) = signature.filter(|s| !s.is_empty()) {
+        let _ = writeln!(md, "## Signature\n\n```rust\n{}\n```\n", signature);
+    }
+
+    write_specs(&mut md, node, atom);
+    write_proof(&mut md, atom);
+
+    let _ = writeln!(md, "## Callers\n");
+    let callers: BTreeSet<&String> = node.callers.iter().collect();
+    write_function_table(&mut md, call_graph, metrics, links, callers, |caller| {
+        call_graph
+            .get(caller)
+            .map(|caller| call_locations(caller, symbol))
+            .unwrap_or_default()
+    });
+
+    let _ = writeln!(md, "## Callees\n");
+    let callees: BTreeSet<&String> = node.callees.iter().collect();
+    write_function_table(&mut md, call_graph, metrics, links, callees, |callee| {
+        call_locations(node, callee)
+    });
+
+    write_mermaid(&mut md, call_graph, metrics, node);
+    md
+}
+
+/// The body up to its spec clauses or opening brace, whitespace collapsed
+pub(crate) fn signature_of(body: &str) -> String {
+    let mut parts = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("#[") || trimmed.starts_with("//") {
+            continue;
+        }
+        let first_word = trimmed.split(|c: char| !c.is_alphanumeric()).next();
+        if first_word.is_some_and(|word| SPEC_KEYWORDS.contains(&word)) {
+            break;
+        }
+        if let Some((before, _)) = trimmed.split_once('{') {
+            parts.push(before);
+            break;
+        }
+        parts.push(trimmed);
+    }
+    parts
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn write_specs(md: &mut String, node: &FunctionNode, atom: Option<&Value>) {
+    let mut rows = Vec::new();
+    if let Some(atom) = atom {
+        for (clause, key) in SPEC_CLAUSES {
+            let specs = atom["metrics"][key].as_array().map(Vec::as_slice);
+            for spec in specs.unwrap_or_default() {
+                rows.push(format!(
+                    "| {} | `{}` | {} | {} | {} |",
+                    clause,
+                    cell(spec["text"].as_str().unwrap_or_default()),
+                    number(&spec["halstead_length"]),
+                    number(&spec["halstead_effort"]),
+                    number(&spec["quantifier_count"]),
+                ));
+            }
+        }
+    }
+    if !rows.is_empty() {
+        let _ = writeln!(md, "## Specification\n");
+        let _ = writeln!(
+            md,
+            "| Clause | Text | Halstead length | Halstead effort | Quantifiers |"
+        );
+        let _ = writeln!(md, "|---|---|---:|---:|---:|");
+        let _ = writeln!(md, "{}\n", rows.join("\n"));
+        return;
+    }
+
+    // No metrics: quote the clauses from the body
+    let (Some(body), Some(&start)) = (&node.body, node.range.first()) else {
+        return;
+    };
+    let sections = parse_function_sections(body, start);
+    let lines: Vec<&str> = body.lines().collect();
+    let clause_text = |range: Option<(i32, i32)>| {
+        range.map(|(first, last)| {
+            lines[(first - start) as usize..=((last - start) as usize).min(lines.len() - 1)]
+                .join("\n")
+        })
+    };
+    let clauses: Vec<String> = [sections.requires_range, sections.ensures_range]
+        .into_iter()
+        .filter_map(clause_text)
+        .collect();
+    if !clauses.is_empty() {
+        let _ = writeln!(
+            md,
+            "## Specification\n\n```rust\n{}\n```\n",
+            clauses.join("\n")
+        );
+    }
+}
+
+fn write_proof(md: &mut String, atom: Option<&Value>) {
+    let Some(proof) = atom.map(|atom| &atom["proof_metrics"]) else {
+        return;
+    };
+    if !proof.is_object() {
+        return;
+    }
+    let _ = writeln!(md, "## Proof\n");
+    let _ = writeln!(md, "| | |\n|---|---|");
+    let _ = writeln!(
+        md,
+        "| Direct proof effort | {} |",
+        number(&proof["direct_proof_halstead"]["effort"])
+    );
+    let _ = writeln!(
+        md,
+        "| Transitive proof effort | {} |",
+        number(&proof["transitive_proof_halstead"]["effort"])
+    );
+    let _ = writeln!(md, "| Proof depth | {} |\n", number(&proof["proof_depth"]));
+
+    for (title, key) in [
+        ("Direct lemmas", "direct_lemmas"),
+        ("Transitive lemmas", "transitive_lemmas"),
+    ] {
+        let lemmas = proof[key].as_array().map(Vec::as_slice).unwrap_or_default();
+        let _ = writeln!(md, "### {} ({})\n", title, lemmas.len());
+        if lemmas.is_empty() {
+            let _ = writeln!(md, "None.\n");
+            continue;
+        }
+        for lemma in lemmas {
+            let _ = writeln!(md, "- `{}`", lemma.as_str().unwrap_or_default());
+        }
+        md.push('\n');
+    }
+}
+
+/// A table of functions, with the call locations given by `locations`
+fn write_function_table<'a>(
+    md: &mut String,
+    call_graph: &CallGraph,
+    metrics: &ReportMetrics,
+    links: Option<&SourceLinks>,
+    symbols: BTreeSet<&'a String>,
+    locations: impl Fn(&'a str) -> String,
+) {
+    if symbols.is_empty() {
+        let _ = writeln!(md, "None.\n");
+        return;
+    }
+    let _ = writeln!(
+        md,
+        "| Function | Mode | Location | Called in | Verification |"
+    );
+    let _ = writeln!(md, "|---|---|---|---|---|");
+    for symbol in symbols {
+        let (name, node_mode, node_location) = match call_graph.get(symbol) {
+            Some(node) => (
+                node.display_name.as_str(),
+                mode(node).as_str(),
+                location(node, links),
+            ),
+            None => (symbol.as_str(), "", String::new()),
+        };
+        let _ = writeln!(
+            md,
+            "| `{}` | {} | {} | {} | {} |",
+            cell(name),
+            node_mode,
+            node_location,
+            locations(symbol),
+            status(metrics, symbol),
+        );
+    }
+    md.push('\n');
+}
+
+/// Where in `caller` the calls to `callee` are (`precondition`, `inner`,
+/// ...), comma-separated
+fn call_locations(caller: &FunctionNode, callee: &str) -> String {
+    let locations: BTreeSet<&str> = caller
+        .callee_occurrences
+        .iter()
+        .filter(|occurrence| occurrence.symbol == callee)
+        .map(|occurrence| {
+            occurrence
+                .location
+                .as_ref()
+                .map_or("unknown", CallLocation::as_str)
+        })
+        .collect();
+    locations.into_iter().collect::<Vec<_>>().join(", ")
+}
+
+/// A flowchart of the direct callers and callees of `node`
+fn write_mermaid(
+    md: &mut String,
+    call_graph: &CallGraph,
+    metrics: &ReportMetrics,
+    node: &FunctionNode,
+) {
+    let callers: BTreeSet<&String> = node.callers.iter().collect();
+    let callees: BTreeSet<&String> = node.callees.iter().collect();
+    let neighbors: BTreeSet<&String> = callers.union(&callees).copied().collect();
+
+    let _ = writeln!(md, "## Call graph\n\n```mermaid\nflowchart LR");
+    let _ = writeln!(md, "    n0[\"{}\"]", label(&node.display_name));
+    let ids: Vec<(&String, String)> = neighbors
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| (*symbol, format!("n{}", i + 1)))
+        .collect();
+    for (symbol, id) in &ids {
+        let name = call_graph
+            .get(symbol.as_str())
+            .map_or(symbol.as_str(), |n| n.display_name.as_str());
+        let _ = writeln!(md, "    {}[\"{}\"]", id, label(name));
+    }
+    for (symbol, id) in &ids {
+        if callers.contains(symbol) {
+            let _ = writeln!(md, "    {} --> n0", id);
+        }
+        if callees.contains(symbol) {
+            let _ = writeln!(md, "    n0 --> {}", id);
+        }
+    }
+
+    let _ = writeln!(md, "    classDef verified fill:#c8e6c9,stroke:#2e7d32");
+    let _ = writeln!(md, "    classDef failed fill:#ffcdd2,stroke:#c62828");
+    let _ = writeln!(md, "    classDef unverified fill:#fff9c4,stroke:#f9a825");
+    let all = std::iter::once((&node.symbol, "n0".to_string())).chain(ids.iter().cloned());
+    for (symbol, id) in all {
+        if let Some(status) = metrics.get(symbol).and_then(|m| m.verification_status) {
+            let _ = writeln!(md, "    class {} {}", id, status.as_str());
+        }
+    }
+    let _ = writeln!(md, "    style n0 stroke-width:3px");
+    let _ = writeln!(md, "```");
+}
+
+fn mode(node: &FunctionNode) -> DeclKind {
+    node.body
+        .as_deref()
+        .map(detect_decl_kind)
+        .unwrap_or(DeclKind::Exec)
+}
+
+/// `path:line` (1-based), linked to the source when `links` are given, or
+/// `external` for functions outside the project
+fn location(node: &FunctionNode, links: Option<&SourceLinks>) -> String {
+    if node.file_path.starts_with("external:") {
+        return "external".to_string();
+    }
+    let start_line = node
+        .start_line
+        .or_else(|| node.range.first().map(|line| *line as usize + 1));
+    let text = match start_line {
+        Some(line) => format!("`{}:{}`", node.relative_path, line),
+        None => format!("`{}`", node.relative_path),
+    };
+    match links.and_then(|links| links.node_url(node)) {
+        Some(url) => format!("[{}]({})", text, url),
+        None => text,
+    }
+}
+
+fn status(metrics: &ReportMetrics, symbol: &str) -> &'static str {
+    metrics
+        .get(symbol)
+        .and_then(|m| m.verification_status)
+        .map_or("unknown", |status| status.as_str())
+}
+
+/// A JSON number as table text, `-` when missing
+fn number(value: &Value) -> String {
+    match value.as_f64() {
+        Some(n) if n.fract() == 0.0 => format!("{}", n as i64),
+        Some(n) => format!("{:.1}", n),
+        None => "-".to_string(),
+    }
+}
+
+/// Text safe to put in a table cell
+fn cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// Text safe to put in a quoted mermaid label
+fn label(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::types::{CalleeOccurrence, VerificationStatus};
+    use serde_json::json;
+
+    fn node(symbol: &str, body: &str, callees: &[&str], callers: &[&str]) -> FunctionNode {
+        FunctionNode {
+            range: vec![9, 0, 20, 1],
+            ..test_support::node(symbol, callees)
+                .with_display_name(symbol.trim_end_matches("()."))
+                .with_callers(callers)
+                .with_body(body)
+        }
+    }
+
+    #[test]
+    fn test_render_dossier() {
+        let mut add = node(
+            "add().",
+            "pub fn add(a: u64,\n    b: u64) -> (r: u64)\n    requires a < 10,\n    ensures r == a + b,\n{\n    lemma_add(a, b);\n    a + b\n}",
+            &["lemma_add()."],
+            &["main()."],
+        );
+        add.doc = Some("Adds two numbers".to_string());
+        add.callee_occurrences.push(CalleeOccurrence {
+            symbol: "lemma_add().".to_string(),
+            line: 14,
+            column: None,
+            location: Some(CallLocation::Inner),
+            edge_kind: None,
+        });
+        let call_graph: CallGraph = [
+            add,
+            node(
+                "lemma_add().",
+                "proof fn lemma_add(a: u64, b: u64) {}",
+                &[],
+                &["add()."],
+            ),
+            node("main().", "fn main() { add(1, 2); }", &["add()."], &[]),
+        ]
+        .into_iter()
+        .collect();
+        let mut metrics = ReportMetrics::new();
+        metrics.set_verification_status("add().", VerificationStatus::Failed);
+        metrics.set_verification_status("lemma_add().", VerificationStatus::Verified);
+
+        // Without metrics the clauses are quoted from the body
+        let md = render_dossiers(&call_graph, "add", &metrics, &[], None).unwrap();
+        assert!(md.starts_with("# `add`\n"));
+        assert!(md.contains("| Location | `src/lib.rs:10` |"));
+        assert!(md.contains("| Verification | failed |"));
+        assert!(md.contains("Adds two numbers"));
+        assert!(md.contains("```rust\npub fn add(a: u64, b: u64) -> (r: u64)\n```"));
+        assert!(md.contains("    requires a < 10,\n    ensures r == a + b,"));
+        assert!(md.contains("| `lemma_add` | proof | `src/lib.rs:10` | inner | verified |"));
+        assert!(md.contains("| `main` | exec | `src/lib.rs:10` |  | unknown |"));
+        assert!(md.contains("    n1[\"lemma_add\"]\n    n2[\"main\"]\n"));
+        assert!(md.contains("    n0 --> n1\n    n2 --> n0\n"));
+        assert!(md.contains("    class n0 failed\n    class n1 verified\n"));
+        assert!(!md.contains("## Proof"));
+
+        let atoms = vec![json!({
+            "identifier": "add().",
+            "metrics": {
+                "requires_specs": [{"text": "a < 10", "halstead_length": 3, "halstead_effort": 4.75, "quantifier_count": 0}],
+                "ensures_specs": [{"text": "r == a | b", "halstead_length": 5}]
+            },
+            "proof_metrics": {
+                "direct_proof_halstead": {"effort": 12.0},
+                "direct_lemmas": ["lemma_add"],
+                "transitive_lemmas": [],
+                "proof_depth": 1
+            }
+        })];
+        let links = SourceLinks::new("https://github.com/o/r", "main");
+        let md = render_dossiers(&call_graph, "add().", &metrics, &atoms, Some(&links)).unwrap();
+        assert!(md.contains(
+            "| Location | [`src/lib.rs:10`](https://github.com/o/r/blob/main/src/lib.rs#L10) |"
+        ));
+        assert!(md.contains("| requires | `a < 10` | 3 | 4.8 | 0 |"));
+        assert!(md.contains("| ensures | `r == a \\| b` | 5 | - | - |"));
+        assert!(md.contains("| Direct proof effort | 12 |"));
+        assert!(md.contains("### Direct lemmas (1)\n\n- `lemma_add`\n"));
+        assert!(md.contains("### Transitive lemmas (0)\n\nNone.\n"));
+
+        assert!(matches!(
+            render_dossiers(&call_graph, "missing", &metrics, &[], None),
+            Err(crate::Error::SymbolResolution(_))
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! Prometheus / OpenMetrics gauges of verification health
+//!
+//! [`render_prometheus`] turns atoms with metrics (and, once
+//! `add_verification_status_to_atoms` ran, verification results) into the
+//! text exposition format, for node_exporter's textfile collector or a
+//! scrape endpoint:
+//!
+//! ```text
+//! # HELP verus_verified_functions Functions that verified
+//! # TYPE verus_verified_functions gauge
+//! verus_verified_functions 118
+//! ...
+//! verus_module_verified_functions{module="backend::serial::u64::field"} 12
+//! ```
+//!
+//! Project totals and per-module values are separate metric families, so
+//! summing a family over its labels never double counts.
+
+use crate::metrics_rollup::{group_key, GroupBy};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// Prefix of every metric name
+pub const METRIC_PREFIX: &str = "verus_";
+
+/// Verification health of a set of functions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthMetrics {
+    pub functions: usize,
+    pub verified_functions: usize,
+    pub failed_functions: usize,
+    pub exec_functions: usize,
+    /// Exec functions with `requires` or `ensures`
+    pub specified_exec_functions: usize,
+    /// Functions with proof metrics
+    pub proof_functions: usize,
+    pub proof_depth_sum: usize,
+}
+
+impl HealthMetrics {
+    fn add(&mut self, atom: &Value) {
+        self.functions += 1;
+        match atom["verification_status"].as_str() {
+            Some("verified") => self.verified_functions += 1,
+            Some("failed") => self.failed_functions += 1,
+            _ => {}
+        }
+
+        let metrics = &atom["metrics"];
+        if metrics["function_mode"].as_str().unwrap_or("exec") == "exec" {
+            self.exec_functions += 1;
+            let count = |key: &str| metrics[key].as_array().map_or(0, Vec::len);
+            if count("requires_specs") + count("ensures_specs") > 0 {
+                self.specified_exec_functions += 1;
+            }
+        }
+
+        let proof = &atom["proof_metrics"];
+        if proof.is_object() {
+            self.proof_functions += 1;
+            self.proof_depth_sum += proof["proof_depth"].as_u64().unwrap_or(0) as usize;
+        }
+    }
+
+    /// Share of exec functions with `requires` or `ensures` (NaN without
+    /// exec functions)
+    pub fn spec_coverage_ratio(&self) -> f64 {
+        self.specified_exec_functions as f64 / self.exec_functions as f64
+    }
+
+    /// Mean proof depth of the functions with proof metrics (0 without any)
+    pub fn mean_proof_depth(&self) -> f64 {
+        if self.proof_functions == 0 {
+            0.0
+        } else {
+            self.proof_depth_sum as f64 / self.proof_functions as f64
+        }
+    }
+}
+
+/// Health of all atoms and of each module (the Rust module path of their
+/// source file)
+pub fn health_metrics(atoms: &[Value]) -> (HealthMetrics, BTreeMap<String, HealthMetrics>) {
+    let mut totals = HealthMetrics::default();
+    let mut modules: BTreeMap<String, HealthMetrics> = BTreeMap::new();
+    for atom in atoms {
+        totals.add(atom);
+        if let Some(module) = group_key(atom, GroupBy::Module) {
+            modules.entry(module).or_default().add(atom);
+        }
+    }
+    (totals, modules)
+}
+
+/// Escape a label value (backslash, double quote and newline)
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Gauges as (name, help, value)
+fn gauges(health: &HealthMetrics) -> [(&'static str, &'static str, f64); 5] {
+    [
+        (
+            "functions",
+            "Functions with metrics",
+            health.functions as f64,
+        ),
+        (
+            "verified_functions",
+            "Functions that verified",
+            health.verified_functions as f64,
+        ),
+        (
+            "failed_functions",
+            "Functions that failed verification",
+            health.failed_functions as f64,
+        ),
+        (
+            "spec_coverage_ratio",
+            "Share of exec functions with requires or ensures",
+            health.spec_coverage_ratio(),
+        ),
+        (
+            "mean_proof_depth",
+            "Mean lemma call chain length of functions with proofs",
+            health.mean_proof_depth(),
+        ),
+    ]
+}
+
+/// The totals and per-module gauges of the atoms in the Prometheus text
+/// exposition format (also valid OpenMetrics, minus the `# EOF` marker)
+pub fn render_prometheus(atoms: &[Value]) -> String {
+    let (totals, modules) = health_metrics(atoms);
+    let module_gauges: Vec<(&String, _)> = modules
+        .iter()
+        .map(|(module, health)| (module, gauges(health)))
+        .collect();
+
+    let mut out = String::new();
+    for (i, (name, help, value)) in gauges(&totals).into_iter().enumerate() {
+        let _ = writeln!(out, "# HELP {METRIC_PREFIX}{name} {help}");
+        let _ = writeln!(out, "# TYPE {METRIC_PREFIX}{name} gauge");
+        let _ = writeln!(out, "{METRIC_PREFIX}{name} {value}");
+
+        let _ = writeln!(
+            out,
+            "# HELP {METRIC_PREFIX}module_{name} {help}, per module"
+        );
+        let _ = writeln!(out, "# TYPE {METRIC_PREFIX}module_{name} gauge");
+        for (module, values) in &module_gauges {
+            let _ = writeln!(
+                out,
+                "{METRIC_PREFIX}module_{name}{{module=\"{}\"}} {}",
+                escape_label(module),
+                values[i].2
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_prometheus() {
+        let atoms = vec![
+            json!({
+                "relative_path": "src/field.rs",
+                "verification_status": "verified",
+                "metrics": {"function_mode": "exec", "ensures_specs": [{}]},
+                "proof_metrics": {"proof_depth": 3}
+            }),
+            json!({
+                "relative_path": "src/field.rs",
+                "verification_status": "failed",
+                "metrics": {"function_mode": "exec"}
+            }),
+            json!({
+                "relative_path": "src/scalar.rs",
+                "metrics": {"function_mode": "proof"},
+                "proof_metrics": {"proof_depth": 1}
+            }),
+        ];
+
+        let (totals, modules) = health_metrics(&atoms);
+        assert_eq!((totals.verified_functions, totals.failed_functions), (1, 1));
+        assert_eq!(totals.spec_coverage_ratio(), 0.5);
+        assert_eq!(totals.mean_proof_depth(), 2.0);
+        assert_eq!(modules.len(), 2);
+
+        let text = render_prometheus(&atoms);
+        assert!(text.contains("# TYPE verus_failed_functions gauge\nverus_failed_functions 1\n"));
+        assert!(text.contains("verus_spec_coverage_ratio 0.5\n"));
+        assert!(text.contains("verus_mean_proof_depth 2\n"));
+        assert!(text.contains("verus_module_failed_functions{module=\"field\"} 1\n"));
+        // No exec functions, no coverage
+        assert!(text.contains("verus_module_spec_coverage_ratio{module=\"scalar\"} NaN\n"));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
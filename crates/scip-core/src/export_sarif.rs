@@ -0,0 +1,289 @@
+//! SARIF 2.1.0 output for analysis findings
+//!
+//! Code review tools (GitHub code scanning among them) show SARIF results
+//! inline on pull requests. Each analysis turns its findings into
+//! [`SarifFinding`]s (rule id, level, message, file and line) and
+//! [`sarif_log`] wraps them, with the rule descriptions, into a SARIF log.
+//! Dead code and spec coverage reports implement [`SarifReport`]; the
+//! assumption audit and unused spec detection build findings in their
+//! binaries.
+//!
+//! File paths are relative to the project root (`%SRCROOT%`), so upload the
+//! log from the root of the checkout.
+
+use crate::analysis::reachability::{DeadCodeCategory, DeadCodeReport, Severity};
+use crate::analysis::spec_coverage::SpecCoverageReport;
+use serde_json::{json, Value};
+
+/// JSON schema of the logs written here
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Level of a SARIF result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SarifLevel {
+    Note,
+    Warning,
+    Error,
+}
+
+impl SarifLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SarifLevel::Note => "note",
+            SarifLevel::Warning => "warning",
+            SarifLevel::Error => "error",
+        }
+    }
+}
+
+impl From<Severity> for SarifLevel {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Low => SarifLevel::Note,
+            Severity::Medium => SarifLevel::Warning,
+            Severity::High => SarifLevel::Error,
+        }
+    }
+}
+
+/// A rule (kind of finding) reported by an analysis
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarifRule {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub default_level: SarifLevel,
+}
+
+/// One finding, a SARIF result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarifFinding {
+    pub rule_id: &'static str,
+    pub level: SarifLevel,
+    pub message: String,
+    /// Path relative to the project root
+    pub relative_path: String,
+    /// 1-based line; the result points at the whole file without one
+    pub line: Option<u32>,
+    /// Fully qualified name of the function (its SCIP symbol)
+    pub symbol: Option<String>,
+}
+
+/// An analysis report that can be written as SARIF
+pub trait SarifReport {
+    /// Every rule the report may use
+    fn sarif_rules() -> Vec<SarifRule>;
+
+    fn sarif_findings(&self) -> Vec<SarifFinding>;
+
+    /// The report as a SARIF log
+    fn to_sarif(&self) -> Value {
+        sarif_log(&Self::sarif_rules(), &self.sarif_findings())
+    }
+}
+
+fn sarif_result(rule_index: Option<usize>, finding: &SarifFinding) -> Value {
+    let mut physical = json!({
+        "artifactLocation": {
+            "uri": finding.relative_path.trim_start_matches("./"),
+            "uriBaseId": "%SRCROOT%",
+        }
+    });
+    if let Some(line) = finding.line {
+        physical["region"] = json!({ "startLine": line });
+    }
+    let mut location = json!({ "physicalLocation": physical });
+    if let Some(symbol) = &finding.symbol {
+        location["logicalLocations"] = json!([{
+            "fullyQualifiedName": symbol,
+            "kind": "function",
+        }]);
+    }
+
+    let mut result = json!({
+        "ruleId": finding.rule_id,
+        "level": finding.level.as_str(),
+        "message": { "text": finding.message },
+        "locations": [location],
+    });
+    if let Some(index) = rule_index {
+        result["ruleIndex"] = json!(index);
+    }
+    result
+}
+
+/// A SARIF log with one run of the `scip-callgraph` tool
+pub fn sarif_log(rules: &[SarifRule], findings: &[SarifFinding]) -> Value {
+    let rule_values: Vec<Value> = rules
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+                "defaultConfiguration": { "level": rule.default_level.as_str() },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|finding| {
+            let index = rules.iter().position(|rule| rule.id == finding.rule_id);
+            sarif_result(index, finding)
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "scip-callgraph",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/Beneficial-AI-Foundation/scip-callgraph",
+                    "rules": rule_values,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+impl SarifReport for DeadCodeReport {
+    fn sarif_rules() -> Vec<SarifRule> {
+        vec![
+            SarifRule {
+                id: DeadCodeCategory::UnreachableExec.as_str(),
+                description: "Exec function not reachable from any entry point",
+                default_level: SarifLevel::Warning,
+            },
+            SarifRule {
+                id: DeadCodeCategory::UnreachableProof.as_str(),
+                description: "Proof function no reachable proof depends on",
+                default_level: SarifLevel::Warning,
+            },
+            SarifRule {
+                id: DeadCodeCategory::OrphanedSpec.as_str(),
+                description: "Spec function no signature or proof refers to",
+                default_level: SarifLevel::Note,
+            },
+        ]
+    }
+
+    fn sarif_findings(&self) -> Vec<SarifFinding> {
+        self.findings
+            .iter()
+            .map(|finding| {
+                let what = match finding.category {
+                    DeadCodeCategory::UnreachableExec => "is not reachable from any entry point",
+                    DeadCodeCategory::UnreachableProof => "is not used by any reachable proof",
+                    DeadCodeCategory::OrphanedSpec => "is not referred to by any spec or proof",
+                };
+                SarifFinding {
+                    rule_id: finding.category.as_str(),
+                    level: finding.severity.into(),
+                    message: format!(
+                        "`{}` {} ({} callers)",
+                        finding.display_name, what, finding.callers
+                    ),
+                    relative_path: finding.relative_path.clone(),
+                    line: finding.line,
+                    symbol: Some(finding.symbol.clone()),
+                }
+            })
+            .collect()
+    }
+}
+
+impl SarifReport for SpecCoverageReport {
+    fn sarif_rules() -> Vec<SarifRule> {
+        vec![SarifRule {
+            id: "spec_gap",
+            description: "Exec function with neither requires nor ensures",
+            default_level: SarifLevel::Note,
+        }]
+    }
+
+    /// Public spec gaps are warnings, private ones notes
+    fn sarif_findings(&self) -> Vec<SarifFinding> {
+        self.gaps
+            .iter()
+            .map(|gap| SarifFinding {
+                rule_id: "spec_gap",
+                level: if gap.is_public {
+                    SarifLevel::Warning
+                } else {
+                    SarifLevel::Note
+                },
+                message: format!(
+                    "{}exec function `{}` has no requires or ensures ({} callers)",
+                    if gap.is_public { "Public " } else { "" },
+                    gap.display_name,
+                    gap.callers
+                ),
+                relative_path: gap.relative_path.clone(),
+                line: gap.line,
+                symbol: Some(gap.symbol.clone()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::reachability::{DeadCodeFinding, DeadCodeSummary};
+
+    #[test]
+    fn test_dead_code_sarif() {
+        let report = DeadCodeReport {
+            summary: DeadCodeSummary::default(),
+            entry_points: Vec::new(),
+            findings: vec![DeadCodeFinding {
+                symbol: "m/helper().".to_string(),
+                display_name: "helper".to_string(),
+                relative_path: "./src/m.rs".to_string(),
+                line: Some(12),
+                category: DeadCodeCategory::UnreachableExec,
+                severity: Severity::High,
+                callers: 0,
+            }],
+        };
+        let log = report.to_sarif();
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 3);
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "unreachable_exec");
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["message"]["text"],
+            "`helper` is not reachable from any entry point (0 callers)"
+        );
+        let location = &result["locations"][0];
+        assert_eq!(
+            location["physicalLocation"]["artifactLocation"]["uri"],
+            "src/m.rs"
+        );
+        assert_eq!(location["physicalLocation"]["region"]["startLine"], 12);
+        assert_eq!(
+            location["logicalLocations"][0]["fullyQualifiedName"],
+            "m/helper()."
+        );
+
+        let finding = SarifFinding {
+            rule_id: "unknown",
+            level: SarifLevel::Note,
+            message: "file-level".to_string(),
+            relative_path: "src/lib.rs".to_string(),
+            line: None,
+            symbol: None,
+        };
+        let result = &sarif_log(&[], &[finding])["runs"][0]["results"][0];
+        assert!(result.get("ruleIndex").is_none());
+        assert!(result["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+    }
+}
@@ -0,0 +1,360 @@
+//! SQLite export and query backend.
+//!
+//! `export_sqlite` writes the call graph and its metrics into a single SQLite
+//! file, so later queries don't have to load a multi-hundred-MB graph JSON:
+//!
+//! - `functions`: one row per node (the columns of the tabular node rows)
+//! - `edges`: one row per call site, indexed on caller and callee
+//! - `specs`: one row per `requires` / `ensures` / `decreases` clause
+//! - `proof_metrics`: direct and transitive proof effort per function
+//!
+//! `specs` and `proof_metrics` are filled from atoms JSON as written by
+//! `compute_metrics` / `compute_proof_metrics`, matched to graph nodes by
+//! `identifier` (a node's symbol, or its identifier as normalized by
+//! [`SymbolNormalizer`]); both tables key rows by symbol. [`SqliteGraph`]
+//! answers the common queries on the result.
+
+use crate::error::Result;
+use crate::export_html::ReportMetrics;
+use crate::export_tabular::{edge_rows, node_rows};
+use crate::graph_integrity::repaired;
+use crate::symbol_scheme::SymbolNormalizer;
+use crate::types::CallGraph;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE functions (
+    symbol TEXT PRIMARY KEY,
+    display_name TEXT NOT NULL,
+    relative_path TEXT NOT NULL,
+    start_line INTEGER,
+    mode TEXT NOT NULL,
+    node_kind TEXT NOT NULL,
+    is_external INTEGER NOT NULL,
+    callers INTEGER NOT NULL,
+    callees INTEGER NOT NULL,
+    body_lines INTEGER,
+    verification_status TEXT,
+    halstead_effort REAL,
+    signature TEXT
+);
+CREATE INDEX functions_display_name ON functions (display_name);
+CREATE INDEX functions_relative_path ON functions (relative_path);
+
+CREATE TABLE edges (
+    caller TEXT NOT NULL,
+    callee TEXT NOT NULL,
+    location TEXT NOT NULL,
+    line INTEGER NOT NULL,
+    edge_kind TEXT
+);
+CREATE INDEX edges_caller ON edges (caller);
+CREATE INDEX edges_callee ON edges (callee);
+
+CREATE TABLE specs (
+    symbol TEXT NOT NULL,
+    clause TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    halstead_length INTEGER,
+    halstead_effort REAL,
+    quantifier_count INTEGER
+);
+CREATE INDEX specs_symbol ON specs (symbol);
+
+CREATE TABLE proof_metrics (
+    symbol TEXT PRIMARY KEY,
+    direct_effort REAL,
+    transitive_effort REAL,
+    direct_lemmas INTEGER,
+    transitive_lemmas INTEGER,
+    proof_depth INTEGER
+);
+";
+
+/// Spec clause lists in the `metrics` object of an atom, by clause name
+const SPEC_CLAUSES: [(&str, &str); 3] = [
+    ("requires", "requires_specs"),
+    ("ensures", "ensures_specs"),
+    ("decreases", "decreases_specs"),
+];
+
+/// Write the call graph into a new SQLite database at `path`.
+///
+/// An existing file is replaced. `atoms` may be empty, leaving the `specs`
+/// and `proof_metrics` tables empty.
+pub fn export_sqlite<P: AsRef<Path>>(
+    call_graph: &CallGraph,
+    metrics: &ReportMetrics,
+    atoms: &[serde_json::Value],
+    path: P,
+) -> Result<()> {
+    let call_graph = &*repaired(call_graph);
+    let path = path.as_ref();
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let mut conn = Connection::open(path)?;
+    write_graph(&mut conn, call_graph, metrics, atoms)?;
+    Ok(())
+}
+
+fn write_graph(
+    conn: &mut Connection,
+    call_graph: &CallGraph,
+    metrics: &ReportMetrics,
+    atoms: &[serde_json::Value],
+) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO functions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        )?;
+        for row in node_rows(call_graph, metrics) {
+            insert.execute(params![
+                row.symbol,
+                row.display_name,
+                row.relative_path,
+                row.start_line,
+                row.mode.as_str(),
+                row.node_kind.as_str(),
+                row.is_external,
+                row.callers,
+                row.callees,
+                row.body_lines,
+                row.verification_status.map(|status| status.as_str()),
+                row.halstead_effort,
+                row.signature,
+            ])?;
+        }
+
+        let mut insert = tx.prepare("INSERT INTO edges VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for row in edge_rows(call_graph) {
+            insert.execute(params![
+                row.caller,
+                row.callee,
+                row.location,
+                row.line,
+                row.edge_kind
+            ])?;
+        }
+
+        let mut insert_spec =
+            tx.prepare("INSERT INTO specs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+        let mut insert_proof =
+            tx.prepare("INSERT OR REPLACE INTO proof_metrics VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+        let normalizer = SymbolNormalizer::new(
+            call_graph
+                .values()
+                .map(|node| (node.symbol.as_str(), node.display_name.as_str())),
+        );
+        for atom in atoms {
+            let Some(identifier) = atom["identifier"].as_str() else {
+                continue;
+            };
+            let symbol = match normalizer.symbol(identifier) {
+                Some(symbol) if !call_graph.contains_key(identifier) => symbol,
+                _ => identifier,
+            };
+            for (clause, key) in SPEC_CLAUSES {
+                let specs = atom["metrics"][key].as_array().map(Vec::as_slice);
+                for (position, spec) in specs.unwrap_or_default().iter().enumerate() {
+                    insert_spec.execute(params![
+                        symbol,
+                        clause,
+                        position,
+                        spec["text"].as_str().unwrap_or_default(),
+                        spec["halstead_length"].as_u64(),
+                        spec["halstead_effort"].as_f64(),
+                        spec["quantifier_count"].as_u64(),
+                    ])?;
+                }
+            }
+
+            let proof = &atom["proof_metrics"];
+            if proof.is_object() {
+                let count = |key: &str| proof[key].as_array().map(|lemmas| lemmas.len());
+                insert_proof.execute(params![
+                    symbol,
+                    proof["direct_proof_halstead"]["effort"].as_f64(),
+                    proof["transitive_proof_halstead"]["effort"].as_f64(),
+                    count("direct_lemmas"),
+                    count("transitive_lemmas"),
+                    proof["proof_depth"].as_u64(),
+                ])?;
+            }
+        }
+    }
+    tx.commit()
+}
+
+/// Read access to a database written by [`export_sqlite`]
+pub struct SqliteGraph {
+    conn: Connection,
+}
+
+impl SqliteGraph {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self {
+            conn: Connection::open(path)?,
+        })
+    }
+
+    /// The underlying connection, for ad-hoc SQL
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Symbols of the functions calling `symbol`, sorted
+    pub fn callers(&self, symbol: &str) -> Result<Vec<String>> {
+        self.symbols(
+            "SELECT DISTINCT caller FROM edges WHERE callee = ?1 ORDER BY caller",
+            symbol,
+        )
+    }
+
+    /// Symbols of the functions called by `symbol`, sorted
+    pub fn callees(&self, symbol: &str) -> Result<Vec<String>> {
+        self.symbols(
+            "SELECT DISTINCT callee FROM edges WHERE caller = ?1 ORDER BY callee",
+            symbol,
+        )
+    }
+
+    /// Symbols of the functions with this display name, sorted
+    pub fn find_by_name(&self, display_name: &str) -> Result<Vec<String>> {
+        self.symbols(
+            "SELECT symbol FROM functions WHERE display_name = ?1 ORDER BY symbol",
+            display_name,
+        )
+    }
+
+    /// Function mode (`exec`, `proof` or `spec`) of a function, if present
+    pub fn mode(&self, symbol: &str) -> Result<Option<String>> {
+        let mode = self
+            .conn
+            .query_row(
+                "SELECT mode FROM functions WHERE symbol = ?1",
+                [symbol],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(mode)
+    }
+
+    fn symbols(&self, sql: &str, arg: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let rows = stmt.query_map([arg], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::types::{CalleeOccurrence, FunctionNode};
+
+    fn node(symbol: &str, callees: &[&str], callers: &[&str]) -> FunctionNode {
+        FunctionNode {
+            callee_occurrences: callees
+                .iter()
+                .map(|c| CalleeOccurrence {
+                    symbol: c.to_string(),
+                    line: 1,
+                    column: None,
+                    location: None,
+                    edge_kind: None,
+                })
+                .collect(),
+            range: vec![0, 0, 3],
+            ..test_support::node(symbol, callees)
+                .with_callers(callers)
+                .with_body(&format!("proof fn {symbol}() {{}}"))
+        }
+    }
+
+    #[test]
+    fn test_export_and_query() {
+        let call_graph: CallGraph = [
+            node("main", &["helper", "lemma"], &[]),
+            node("helper", &["lemma"], &["main"]),
+            node("lemma", &[], &["main", "helper"]),
+        ]
+        .into_iter()
+        .collect();
+        let atoms = vec![serde_json::json!({
+            "identifier": "lemma",
+            "metrics": {
+                "requires_specs": [{"text": "x > 0", "halstead_length": 3}],
+                "ensures_specs": [{"text": "x + 1 > 1"}, {"text": "true"}]
+            },
+            "proof_metrics": {
+                "direct_proof_halstead": {"effort": 5.0},
+                "transitive_proof_halstead": {"effort": 8.0},
+                "direct_lemmas": [],
+                "transitive_lemmas": [],
+                "proof_depth": 0
+            }
+        })];
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.db");
+        export_sqlite(&call_graph, &ReportMetrics::new(), &atoms, &path).unwrap();
+        // Exporting again replaces the file
+        export_sqlite(&call_graph, &ReportMetrics::new(), &atoms, &path).unwrap();
+
+        let graph = SqliteGraph::open(&path).unwrap();
+        assert_eq!(graph.callers("lemma").unwrap(), vec!["helper", "main"]);
+        assert_eq!(graph.callees("helper").unwrap(), vec!["lemma"]);
+        assert!(graph.callers("main").unwrap().is_empty());
+        assert_eq!(graph.find_by_name("helper").unwrap(), vec!["helper"]);
+        assert_eq!(graph.mode("lemma").unwrap().as_deref(), Some("proof"));
+        assert_eq!(graph.mode("missing").unwrap(), None);
+
+        let conn = graph.connection();
+        let spec_count: i64 = conn
+            .query_row(
+                "SELECT count(*) FROM specs WHERE symbol = 'lemma'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(spec_count, 3);
+        let transitive_effort: f64 = conn
+            .query_row(
+                "SELECT transitive_effort FROM proof_metrics WHERE symbol = 'lemma'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(transitive_effort, 8.0);
+    }
+
+    #[test]
+    fn test_atoms_match_by_normalized_identifier() {
+        let symbol = "rust-analyzer cargo c 0.1.0 lemmas/lemma_add().";
+        let lemma = FunctionNode {
+            display_name: "lemma_add".to_string(),
+            ..node(symbol, &[], &[])
+        };
+        let call_graph: CallGraph = [lemma].into_iter().collect();
+        let atoms = vec![serde_json::json!({
+            "identifier": "lemmas::lemma_add",
+            "metrics": { "ensures_specs": [{"text": "true"}] }
+        })];
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.db");
+        export_sqlite(&call_graph, &ReportMetrics::new(), &atoms, &path).unwrap();
+
+        let graph = SqliteGraph::open(&path).unwrap();
+        let spec_symbol: String = graph
+            .connection()
+            .query_row("SELECT symbol FROM specs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(spec_symbol, symbol);
+    }
+}
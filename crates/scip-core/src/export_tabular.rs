@@ -0,0 +1,465 @@
+//! Tabular export of the call graph as a node table and an edge table.
+//!
+//! `export_tabular` writes `nodes.csv` / `edges.csv`, or `nodes.parquet` /
+//! `edges.parquet` when scip-core is built with the `parquet` feature, for
+//! analysis in DuckDB, pandas and the like:
+//!
+//! ```sql
+//! SELECT n.display_name, count(*) AS lemma_calls
+//! FROM 'edges.parquet' e JOIN 'nodes.parquet' n ON n.symbol = e.caller
+//! WHERE e.edge_kind = 'exec_to_proof'
+//! GROUP BY 1 ORDER BY 2 DESC;
+//! ```
+
+use crate::call_graph::detect_decl_kind;
+use crate::error::Result;
+use crate::export_d3::REFERENCES_LINK_TYPE;
+use crate::export_html::{ReportMetrics, VerificationStatus};
+use crate::graph_integrity::repaired;
+use crate::types::{CallGraph, DeclKind, FunctionNode, NodeKind};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// File format of the node and edge tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabularFormat {
+    Csv,
+    Parquet,
+}
+
+impl TabularFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TabularFormat::Csv => "csv",
+            TabularFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl fmt::Display for TabularFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+impl FromStr for TabularFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(TabularFormat::Csv),
+            "parquet" => Ok(TabularFormat::Parquet),
+            _ => Err(format!("unknown tabular format: {s}")),
+        }
+    }
+}
+
+/// One row of the node table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeRow {
+    pub symbol: String,
+    pub display_name: String,
+    pub relative_path: String,
+    /// 1-based first line of the definition
+    pub start_line: Option<u32>,
+    pub mode: DeclKind,
+    /// `function`, or `type` for types, constants and statics
+    pub node_kind: NodeKind,
+    pub is_external: bool,
+    /// Number of distinct callers (fan-in); of a type node, the functions
+    /// referencing it
+    pub callers: u32,
+    /// Number of distinct callees (fan-out), not counting referenced types
+    pub callees: u32,
+    pub body_lines: Option<u32>,
+    pub verification_status: Option<VerificationStatus>,
+    pub halstead_effort: Option<f64>,
+    /// Signature as written, when the body parses
+    pub signature: Option<String>,
+}
+
+/// One row of the edge table: a single call site
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EdgeRow {
+    pub caller: String,
+    pub callee: String,
+    /// `precondition`, `postcondition` or `inner`, or `references` for uses
+    /// of types
+    pub location: String,
+    /// 1-based line of the call
+    pub line: u32,
+    pub edge_kind: Option<String>,
+}
+
+/// Node rows sorted by symbol; metrics columns are filled from `metrics`
+pub fn node_rows(call_graph: &CallGraph, metrics: &ReportMetrics) -> Vec<NodeRow> {
+    let mut rows: Vec<NodeRow> = call_graph
+        .values()
+        .map(|node| {
+            let node_metrics = metrics.get(&node.symbol);
+            NodeRow {
+                symbol: node.symbol.clone(),
+                display_name: node.display_name.clone(),
+                relative_path: node.relative_path.clone(),
+                start_line: node.range.first().map(|line| *line as u32 + 1),
+                mode: node
+                    .body
+                    .as_deref()
+                    .map(detect_decl_kind)
+                    .unwrap_or(DeclKind::Exec),
+                node_kind: node.node_kind,
+                is_external: node.file_path.starts_with("external:"),
+                callers: node.callers.len() as u32,
+                callees: node
+                    .callees
+                    .iter()
+                    .filter(|callee| !call_graph.get(*callee).is_some_and(FunctionNode::is_type))
+                    .count() as u32,
+                body_lines: node.body.as_ref().map(|body| body.lines().count() as u32),
+                verification_status: node_metrics.and_then(|m| m.verification_status),
+                halstead_effort: node_metrics.and_then(|m| m.halstead_effort),
+                signature: node.signature.clone(),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    rows
+}
+
+/// Edge rows (one per distinct call site) sorted by caller, line and callee
+pub fn edge_rows(call_graph: &CallGraph) -> Vec<EdgeRow> {
+    let mut seen = HashSet::new();
+    let mut rows = Vec::new();
+    for node in call_graph.values() {
+        for occurrence in &node.callee_occurrences {
+            let Some(callee) = call_graph.get(&occurrence.symbol) else {
+                continue;
+            };
+            if !seen.insert((&node.symbol, &occurrence.symbol, occurrence.line)) {
+                continue;
+            }
+            let location = if callee.is_type() {
+                REFERENCES_LINK_TYPE
+            } else {
+                occurrence
+                    .location
+                    .as_ref()
+                    .map(|loc| loc.as_str())
+                    .unwrap_or("inner")
+            };
+            rows.push(EdgeRow {
+                caller: node.symbol.clone(),
+                callee: occurrence.symbol.clone(),
+                location: location.to_string(),
+                line: occurrence.line.max(0) as u32 + 1,
+                edge_kind: occurrence.edge_kind.map(|kind| kind.as_str().to_string()),
+            });
+        }
+    }
+    rows.sort_by(|a, b| (&a.caller, a.line, &a.callee).cmp(&(&b.caller, b.line, &b.callee)));
+    rows
+}
+
+/// Write `nodes.<ext>` and `edges.<ext>` into `output_dir` (created if missing)
+pub fn export_tabular<P: AsRef<Path>>(
+    call_graph: &CallGraph,
+    metrics: &ReportMetrics,
+    output_dir: P,
+    format: TabularFormat,
+) -> Result<()> {
+    let call_graph = &*repaired(call_graph);
+    let output_dir = output_dir.as_ref();
+    std::fs::create_dir_all(output_dir)?;
+    let nodes_path = output_dir.join(format!("nodes.{}", format.extension()));
+    let edges_path = output_dir.join(format!("edges.{}", format.extension()));
+    let nodes = node_rows(call_graph, metrics);
+    let edges = edge_rows(call_graph);
+
+    match format {
+        TabularFormat::Csv => {
+            write_csv(&nodes_path, &nodes)?;
+            write_csv(&edges_path, &edges)?;
+            Ok(())
+        }
+        TabularFormat::Parquet => {
+            parquet_tables::write_nodes(&nodes_path, &nodes)?;
+            parquet_tables::write_edges(&edges_path, &edges)
+        }
+    }
+}
+
+fn write_csv<T: Serialize>(path: &Path, rows: &[T]) -> std::io::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_tables {
+    use super::{EdgeRow, NodeRow};
+    use crate::error::Result;
+    use arrow_array::{
+        ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, UInt32Array,
+    };
+    use parquet::arrow::ArrowWriter;
+    use parquet::errors::ParquetError;
+    use std::fs::File;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn strings<'a>(values: impl Iterator<Item = Option<&'a str>>) -> ArrayRef {
+        Arc::new(values.collect::<StringArray>())
+    }
+
+    fn write(path: &Path, columns: Vec<(&str, ArrayRef)>) -> Result<()> {
+        let batch = RecordBatch::try_from_iter(columns).map_err(ParquetError::from)?;
+        let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+
+    pub fn write_nodes(path: &Path, rows: &[NodeRow]) -> Result<()> {
+        write(
+            path,
+            vec![
+                (
+                    "symbol",
+                    strings(rows.iter().map(|r| Some(r.symbol.as_str()))),
+                ),
+                (
+                    "display_name",
+                    strings(rows.iter().map(|r| Some(r.display_name.as_str()))),
+                ),
+                (
+                    "relative_path",
+                    strings(rows.iter().map(|r| Some(r.relative_path.as_str()))),
+                ),
+                (
+                    "start_line",
+                    Arc::new(rows.iter().map(|r| r.start_line).collect::<UInt32Array>()),
+                ),
+                ("mode", strings(rows.iter().map(|r| Some(r.mode.as_str())))),
+                (
+                    "node_kind",
+                    strings(rows.iter().map(|r| Some(r.node_kind.as_str()))),
+                ),
+                (
+                    "is_external",
+                    Arc::new(
+                        rows.iter()
+                            .map(|r| Some(r.is_external))
+                            .collect::<BooleanArray>(),
+                    ),
+                ),
+                (
+                    "callers",
+                    Arc::new(
+                        rows.iter()
+                            .map(|r| Some(r.callers))
+                            .collect::<UInt32Array>(),
+                    ),
+                ),
+                (
+                    "callees",
+                    Arc::new(
+                        rows.iter()
+                            .map(|r| Some(r.callees))
+                            .collect::<UInt32Array>(),
+                    ),
+                ),
+                (
+                    "body_lines",
+                    Arc::new(rows.iter().map(|r| r.body_lines).collect::<UInt32Array>()),
+                ),
+                (
+                    "verification_status",
+                    strings(
+                        rows.iter()
+                            .map(|r| r.verification_status.map(|status| status.as_str())),
+                    ),
+                ),
+                (
+                    "halstead_effort",
+                    Arc::new(
+                        rows.iter()
+                            .map(|r| r.halstead_effort)
+                            .collect::<Float64Array>(),
+                    ),
+                ),
+                (
+                    "signature",
+                    strings(rows.iter().map(|r| r.signature.as_deref())),
+                ),
+            ],
+        )
+    }
+
+    pub fn write_edges(path: &Path, rows: &[EdgeRow]) -> Result<()> {
+        write(
+            path,
+            vec![
+                (
+                    "caller",
+                    strings(rows.iter().map(|r| Some(r.caller.as_str()))),
+                ),
+                (
+                    "callee",
+                    strings(rows.iter().map(|r| Some(r.callee.as_str()))),
+                ),
+                (
+                    "location",
+                    strings(rows.iter().map(|r| Some(r.location.as_str()))),
+                ),
+                (
+                    "line",
+                    Arc::new(rows.iter().map(|r| Some(r.line)).collect::<UInt32Array>()),
+                ),
+                (
+                    "edge_kind",
+                    strings(rows.iter().map(|r| r.edge_kind.as_deref())),
+                ),
+            ],
+        )
+    }
+}
+
+#[cfg(not(feature = "parquet"))]
+mod parquet_tables {
+    use super::{EdgeRow, NodeRow};
+    use crate::error::{Error, Result};
+    use std::path::Path;
+
+    fn unsupported() -> Error {
+        Error::FeatureDisabled {
+            output: "Parquet",
+            feature: "parquet",
+        }
+    }
+
+    pub fn write_nodes(_path: &Path, _rows: &[NodeRow]) -> Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn write_edges(_path: &Path, _rows: &[EdgeRow]) -> Result<()> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CallLocation, CalleeOccurrence, EdgeKind};
+
+    fn sample_graph() -> CallGraph {
+        let main = FunctionNode {
+            symbol: "demo/main().".to_string(),
+            display_name: "main".to_string(),
+            file_path: "/project/src/main.rs".to_string(),
+            relative_path: "src/main.rs".to_string(),
+            callees: ["demo/lemma().".to_string()].into_iter().collect(),
+            callee_occurrences: vec![
+                CalleeOccurrence {
+                    symbol: "demo/lemma().".to_string(),
+                    line: 4,
+                    column: None,
+                    location: Some(CallLocation::Inner),
+                    edge_kind: Some(EdgeKind::ExecToProof),
+                },
+                // Same call site reported twice
+                CalleeOccurrence {
+                    symbol: "demo/lemma().".to_string(),
+                    line: 4,
+                    column: None,
+                    location: Some(CallLocation::Inner),
+                    edge_kind: Some(EdgeKind::ExecToProof),
+                },
+                CalleeOccurrence {
+                    symbol: "demo/lemma().".to_string(),
+                    line: 1,
+                    column: None,
+                    location: Some(CallLocation::Precondition),
+                    edge_kind: Some(EdgeKind::ExecToProof),
+                },
+            ],
+            range: vec![0, 3, 7],
+            body: Some("fn main()\n    requires lemma()\n{\n\n    lemma();\n}".to_string()),
+            signature: Some("fn main()".to_string()),
+            ..Default::default()
+        };
+        let lemma = FunctionNode {
+            symbol: "demo/lemma().".to_string(),
+            display_name: "lemma".to_string(),
+            file_path: "/project/src/lemmas.rs".to_string(),
+            relative_path: "src/lemmas.rs".to_string(),
+            callers: ["demo/main().".to_string()].into_iter().collect(),
+            range: vec![9, 10, 15],
+            body: Some("proof fn lemma() {}".to_string()),
+            ..Default::default()
+        };
+        [main, lemma].into_iter().collect()
+    }
+
+    #[test]
+    fn test_node_rows() {
+        let mut metrics = ReportMetrics::new();
+        metrics.set_halstead_effort("demo/lemma().", 12.5);
+        let rows = node_rows(&sample_graph(), &metrics);
+
+        assert_eq!(rows.len(), 2);
+        let lemma = &rows[0];
+        assert_eq!(lemma.display_name, "lemma");
+        assert_eq!(lemma.mode, DeclKind::Proof);
+        assert_eq!(lemma.start_line, Some(10));
+        assert_eq!(lemma.callers, 1);
+        assert_eq!(lemma.halstead_effort, Some(12.5));
+        assert_eq!(rows[1].callees, 1);
+        assert_eq!(rows[1].body_lines, Some(6));
+        assert_eq!(rows[1].signature.as_deref(), Some("fn main()"));
+    }
+
+    #[test]
+    fn test_edge_rows_one_per_call_site() {
+        let rows = edge_rows(&sample_graph());
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].location, "precondition");
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[1].location, "inner");
+        assert_eq!(rows[1].edge_kind.as_deref(), Some("exec_to_proof"));
+    }
+
+    #[test]
+    fn test_export_tabular_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("tables");
+        export_tabular(
+            &sample_graph(),
+            &ReportMetrics::new(),
+            &out,
+            TabularFormat::Csv,
+        )
+        .unwrap();
+
+        let nodes = std::fs::read_to_string(out.join("nodes.csv")).unwrap();
+        let header = nodes.lines().next().unwrap();
+        assert_eq!(
+            header,
+            "symbol,display_name,relative_path,start_line,mode,node_kind,is_external,callers,callees,body_lines,verification_status,halstead_effort,signature"
+        );
+        assert_eq!(nodes.lines().count(), 3);
+
+        let edges = std::fs::read_to_string(out.join("edges.csv")).unwrap();
+        assert!(edges.starts_with("caller,callee,location,line,edge_kind\n"));
+        assert!(edges.contains("demo/main().,demo/lemma().,inner,5,exec_to_proof"));
+        assert_eq!(
+            "parquet".parse::<TabularFormat>(),
+            Ok(TabularFormat::Parquet)
+        );
+    }
+}
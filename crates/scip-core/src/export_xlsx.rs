@@ -0,0 +1,326 @@
+//! Excel workbook of metrics, for readers who live in spreadsheets
+//!
+//! [`MetricsWorkbook`] writes one `.xlsx` file with four sheets:
+//!
+//! - `Functions`: one row per atom with the [`BUILTIN_FIELDS`] columns of
+//!   `enrich-csv`
+//! - `Modules`: the [`MetricsRollup`] groups
+//! - `Violations`: broken complexity budgets ([`GateViolation`]s)
+//! - `Trends`: project totals of each run of the history store
+//!
+//! Every sheet has a bold, frozen header row with autofilters, and effort
+//! columns get a green-to-red color scale.
+
+use crate::csv_enrich::{field_value, BUILTIN_FIELDS};
+use crate::error::Result;
+use crate::gate::GateViolation;
+use crate::history::{RunSummary, REPORT_COLUMNS};
+use crate::metrics_rollup::MetricsRollup;
+use rust_xlsxwriter::{Color, ConditionalFormat3ColorScale, Format, Workbook, XlsxError};
+use serde_json::Value;
+use std::path::Path;
+
+/// A spreadsheet cell
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl Cell {
+    /// A metric cell: numbers for numeric text, so spreadsheets can sort
+    /// and sum them
+    fn parse(text: String) -> Self {
+        if text.is_empty() {
+            Cell::Empty
+        } else if let Ok(number) = text.parse::<f64>() {
+            Cell::Number(number)
+        } else {
+            Cell::Text(text)
+        }
+    }
+}
+
+/// One sheet: header and rows
+struct Sheet {
+    name: &'static str,
+    columns: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+}
+
+/// The data of a metrics workbook
+pub struct MetricsWorkbook<'a> {
+    /// Atoms with metrics (from `compute_metrics` / `compute_proof_metrics`)
+    pub atoms: &'a [Value],
+    pub rollup: &'a MetricsRollup,
+    pub violations: &'a [GateViolation],
+    /// Runs of the history store, oldest first
+    pub history: &'a [RunSummary],
+}
+
+impl MetricsWorkbook<'_> {
+    fn functions_sheet(&self) -> Sheet {
+        let mut columns: Vec<String> = ["identifier", "display_name", "relative_path", "mode"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        columns.extend(BUILTIN_FIELDS.iter().map(|f| f.to_string()));
+
+        let mut atoms: Vec<&Value> = self.atoms.iter().collect();
+        atoms.sort_by(|a, b| a["identifier"].as_str().cmp(&b["identifier"].as_str()));
+        let rows = atoms
+            .iter()
+            .map(|atom| {
+                let text = |value: &Value| match value.as_str() {
+                    Some(text) if !text.is_empty() => Cell::Text(text.to_string()),
+                    _ => Cell::Empty,
+                };
+                let mut row = vec![
+                    text(&atom["identifier"]),
+                    text(&atom["display_name"]),
+                    text(&atom["relative_path"]),
+                    text(&atom["metrics"]["function_mode"]),
+                ];
+                row.extend(
+                    BUILTIN_FIELDS
+                        .iter()
+                        .map(|field| Cell::parse(field_value(atom, field))),
+                );
+                row
+            })
+            .collect();
+        Sheet {
+            name: "Functions",
+            columns,
+            rows,
+        }
+    }
+
+    fn modules_sheet(&self) -> Sheet {
+        let columns = [
+            self.rollup.group_by.to_string().as_str(),
+            "functions",
+            "spec_effort_sum",
+            "spec_effort_mean",
+            "spec_effort_max",
+            "proof_depth_sum",
+            "proof_depth_mean",
+            "proof_depth_max",
+            "verification_failures",
+        ]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+        let rows = self
+            .rollup
+            .groups
+            .iter()
+            .map(|group| {
+                vec![
+                    Cell::Text(group.group.clone()),
+                    Cell::Number(group.functions as f64),
+                    Cell::Number(group.spec_effort.sum),
+                    Cell::Number(group.spec_effort.mean),
+                    Cell::Number(group.spec_effort.max),
+                    Cell::Number(group.proof_depth.sum),
+                    Cell::Number(group.proof_depth.mean),
+                    Cell::Number(group.proof_depth.max),
+                    Cell::Number(group.verification_failures as f64),
+                ]
+            })
+            .collect();
+        Sheet {
+            name: "Modules",
+            columns,
+            rows,
+        }
+    }
+
+    fn violations_sheet(&self) -> Sheet {
+        let columns = [
+            "rule",
+            "function",
+            "relative_path",
+            "value",
+            "limit",
+            "detail",
+        ]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+        let optional = |text: &Option<String>| text.clone().map_or(Cell::Empty, Cell::Text);
+        let rows = self
+            .violations
+            .iter()
+            .map(|violation| {
+                vec![
+                    Cell::Text(violation.rule.as_str().to_string()),
+                    optional(&violation.function),
+                    optional(&violation.relative_path),
+                    Cell::Number(violation.value),
+                    Cell::Number(violation.limit),
+                    Cell::Text(violation.detail.clone()),
+                ]
+            })
+            .collect();
+        Sheet {
+            name: "Violations",
+            columns,
+            rows,
+        }
+    }
+
+    fn trends_sheet(&self) -> Sheet {
+        let mut columns = vec!["commit".to_string(), "timestamp".to_string()];
+        columns.extend(REPORT_COLUMNS.iter().map(|c| c.to_string()));
+        let rows = self
+            .history
+            .iter()
+            .map(|run| {
+                let mut row = vec![
+                    Cell::Text(run.commit.clone()),
+                    Cell::Text(run.timestamp.clone()),
+                ];
+                row.extend(
+                    run.totals
+                        .report_values()
+                        .map(|value| Cell::Number(value.as_f64())),
+                );
+                row
+            })
+            .collect();
+        Sheet {
+            name: "Trends",
+            columns,
+            rows,
+        }
+    }
+
+    fn workbook(&self) -> Result<Workbook, XlsxError> {
+        let mut workbook = Workbook::new();
+        let header = Format::new()
+            .set_bold()
+            .set_background_color(Color::RGB(0xD9E1F2));
+        let decimal = Format::new().set_num_format("0.00");
+        // Low effort is good: green at the minimum, red at the maximum
+        let effort_scale = ConditionalFormat3ColorScale::new()
+            .set_minimum_color(Color::RGB(0x63BE7B))
+            .set_midpoint_color(Color::RGB(0xFFEB84))
+            .set_maximum_color(Color::RGB(0xF8696B));
+
+        for sheet in [
+            self.functions_sheet(),
+            self.modules_sheet(),
+            self.violations_sheet(),
+            self.trends_sheet(),
+        ] {
+            let worksheet = workbook.add_worksheet();
+            worksheet.set_name(sheet.name)?;
+            for (col, column) in sheet.columns.iter().enumerate() {
+                worksheet.write_string_with_format(0, col as u16, column, &header)?;
+            }
+            for (row, cells) in sheet.rows.iter().enumerate() {
+                let row = row as u32 + 1;
+                for (col, cell) in cells.iter().enumerate() {
+                    let col = col as u16;
+                    match cell {
+                        Cell::Text(text) => {
+                            worksheet.write_string(row, col, text)?;
+                        }
+                        Cell::Number(n) if n.fract() == 0.0 => {
+                            worksheet.write_number(row, col, *n)?;
+                        }
+                        Cell::Number(n) => {
+                            worksheet.write_number_with_format(row, col, *n, &decimal)?;
+                        }
+                        Cell::Empty => {}
+                    }
+                }
+            }
+
+            let last_col = sheet.columns.len().saturating_sub(1) as u16;
+            let last_row = sheet.rows.len() as u32;
+            worksheet.set_freeze_panes(1, 0)?;
+            worksheet.autofilter(0, 0, last_row, last_col)?;
+            if !sheet.rows.is_empty() {
+                for (col, column) in sheet.columns.iter().enumerate() {
+                    if column.contains("effort") {
+                        let col = col as u16;
+                        worksheet.add_conditional_format(1, col, last_row, col, &effort_scale)?;
+                    }
+                }
+            }
+            worksheet.autofit();
+        }
+        Ok(workbook)
+    }
+
+    /// The workbook as `.xlsx` bytes
+    pub fn to_buffer(&self) -> Result<Vec<u8>> {
+        Ok(self.workbook()?.save_to_buffer()?)
+    }
+
+    /// Write the workbook to an `.xlsx` file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.workbook()?.save(path.as_ref())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gate::GateRule;
+    use crate::metrics_rollup::{rollup_metrics, GroupBy};
+    use serde_json::json;
+
+    fn atoms() -> Vec<Value> {
+        vec![json!({
+            "identifier": "crate/field/sub().",
+            "display_name": "sub",
+            "relative_path": "src/field.rs",
+            "metrics": {
+                "function_mode": "exec",
+                "ensures_specs": [{"halstead_length": 4, "halstead_effort": 12.5}]
+            },
+            "proof_metrics": {"proof_depth": 2}
+        })]
+    }
+
+    #[test]
+    fn test_sheets() {
+        let atoms = atoms();
+        let rollup = rollup_metrics(&atoms, GroupBy::File);
+        let violations = vec![GateViolation {
+            rule: GateRule::ProofDepth,
+            function: Some("sub".to_string()),
+            relative_path: None,
+            value: 2.0,
+            limit: 1.0,
+            detail: "proof depth 2".to_string(),
+        }];
+        let workbook = MetricsWorkbook {
+            atoms: &atoms,
+            rollup: &rollup,
+            violations: &violations,
+            history: &[],
+        };
+
+        let functions = workbook.functions_sheet();
+        let column = |name: &str| functions.columns.iter().position(|c| c == name).unwrap();
+        let row = &functions.rows[0];
+        assert_eq!(row[column("mode")], Cell::Text("exec".to_string()));
+        assert_eq!(row[column("ensures_halstead_effort")], Cell::Number(12.5));
+        assert_eq!(row[column("proof_depth")], Cell::Number(2.0));
+        assert_eq!(row[column("requires_halstead_effort")], Cell::Empty);
+
+        assert_eq!(workbook.modules_sheet().rows.len(), 1);
+        assert_eq!(workbook.violations_sheet().rows[0][2], Cell::Empty);
+        assert!(workbook.trends_sheet().rows.is_empty());
+
+        // An .xlsx file is a zip archive
+        let bytes = workbook.to_buffer().unwrap();
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
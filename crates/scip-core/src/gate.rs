@@ -0,0 +1,380 @@
+//! Complexity budgets for CI
+//!
+//! [`check_gate`] checks an atoms JSON with metrics (as written by
+//! `compute_metrics` / `compute_proof_metrics`) against [`GateThresholds`],
+//! usually loaded from a TOML file:
+//!
+//! ```toml
+//! max_spec_effort = 20000.0   # Halstead effort of one requires/ensures/decreases clause
+//! max_proof_depth = 8         # longest lemma call chain of a function
+//! min_spec_coverage = 0.6     # share of exec functions with requires/ensures
+//! max_new_assumes = 0         # assume(...)/admit() calls added since the baseline
+//! ```
+//!
+//! Unset thresholds are not checked. New assumes are counted per function
+//! against a baseline atoms JSON; without one every assume counts as new.
+
+use crate::analysis::trust::{count_calls, strip_line_comments};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Budgets to enforce; `None` disables a check
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GateThresholds {
+    /// Maximum Halstead effort of a single spec clause
+    pub max_spec_effort: Option<f64>,
+    /// Maximum proof depth of a function
+    pub max_proof_depth: Option<usize>,
+    /// Minimum share (0 to 1) of exec functions with `requires` or `ensures`
+    pub min_spec_coverage: Option<f64>,
+    /// Maximum number of `assume`/`admit` calls added since the baseline
+    pub max_new_assumes: Option<usize>,
+}
+
+impl GateThresholds {
+    /// Parse thresholds from TOML
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| Error::InvalidData(format!("invalid gate thresholds: {}", e)))
+    }
+
+    /// Load thresholds from a TOML file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Budget a violation breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GateRule {
+    SpecEffort,
+    ProofDepth,
+    SpecCoverage,
+    NewAssumes,
+}
+
+impl GateRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GateRule::SpecEffort => "spec_effort",
+            GateRule::ProofDepth => "proof_depth",
+            GateRule::SpecCoverage => "spec_coverage",
+            GateRule::NewAssumes => "new_assumes",
+        }
+    }
+}
+
+/// A broken budget
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GateViolation {
+    pub rule: GateRule,
+    /// Offending function, `None` for project-wide budgets
+    pub function: Option<String>,
+    pub relative_path: Option<String>,
+    pub value: f64,
+    pub limit: f64,
+    /// What exceeded the budget (the spec clause, the new assumes, ...)
+    pub detail: String,
+}
+
+/// Result of [`check_gate`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GateReport {
+    pub functions: usize,
+    /// Share of exec functions with `requires` or `ensures` (`None` without
+    /// exec functions)
+    pub spec_coverage: Option<f64>,
+    /// `assume`/`admit` calls added since the baseline
+    pub new_assumes: usize,
+    /// Sorted by rule, then path and function
+    pub violations: Vec<GateViolation>,
+}
+
+/// Number of `assume(...)` and `admit()` calls in an atom's body
+fn assume_count(atom: &Value) -> usize {
+    let code = strip_line_comments(atom["body"].as_str().unwrap_or_default());
+    count_calls(&code, "assume") + count_calls(&code, "admit")
+}
+
+fn atom_key(atom: &Value) -> &str {
+    atom["identifier"]
+        .as_str()
+        .or_else(|| atom["display_name"].as_str())
+        .unwrap_or_default()
+}
+
+fn atom_name(atom: &Value) -> String {
+    atom["display_name"]
+        .as_str()
+        .unwrap_or_else(|| atom_key(atom))
+        .to_string()
+}
+
+fn atom_path(atom: &Value) -> Option<String> {
+    atom["relative_path"].as_str().map(str::to_string)
+}
+
+/// Shorten a spec clause to one line for the report
+fn clause_summary(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(80) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// Check atoms with metrics against the thresholds; `baseline` holds the
+/// atoms of the base revision, for counting new assumes
+pub fn check_gate(
+    atoms: &[Value],
+    baseline: Option<&[Value]>,
+    thresholds: &GateThresholds,
+) -> GateReport {
+    let mut violations = Vec::new();
+    let mut exec_functions = 0;
+    let mut specified = 0;
+    let baseline_assumes: HashMap<&str, usize> = baseline
+        .unwrap_or_default()
+        .iter()
+        .map(|atom| (atom_key(atom), assume_count(atom)))
+        .collect();
+    let mut new_assumes: Vec<(String, usize)> = Vec::new();
+
+    for atom in atoms {
+        let metrics = &atom["metrics"];
+        let count = |key: &str| metrics[key].as_array().map_or(0, Vec::len);
+        if metrics["function_mode"].as_str().unwrap_or("exec") == "exec" {
+            exec_functions += 1;
+            if count("requires_specs") + count("ensures_specs") > 0 {
+                specified += 1;
+            }
+        }
+
+        if let Some(limit) = thresholds.max_spec_effort {
+            for key in ["requires_specs", "ensures_specs", "decreases_specs"] {
+                let specs = metrics[key].as_array().map(Vec::as_slice);
+                for spec in specs.unwrap_or_default() {
+                    let effort = spec["halstead_effort"].as_f64().unwrap_or(0.0);
+                    if effort > limit {
+                        violations.push(GateViolation {
+                            rule: GateRule::SpecEffort,
+                            function: Some(atom_name(atom)),
+                            relative_path: atom_path(atom),
+                            value: effort,
+                            limit,
+                            detail: format!(
+                                "{} {}",
+                                key.trim_end_matches("_specs"),
+                                clause_summary(spec["text"].as_str().unwrap_or_default())
+                            )
+                            .trim_end()
+                            .to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(limit) = thresholds.max_proof_depth {
+            let depth = atom["proof_metrics"]["proof_depth"].as_u64().unwrap_or(0) as usize;
+            if depth > limit {
+                violations.push(GateViolation {
+                    rule: GateRule::ProofDepth,
+                    function: Some(atom_name(atom)),
+                    relative_path: atom_path(atom),
+                    value: depth as f64,
+                    limit: limit as f64,
+                    detail: format!("proof depth {}", depth),
+                });
+            }
+        }
+
+        let assumes = assume_count(atom);
+        let before = baseline_assumes
+            .get(atom_key(atom))
+            .copied()
+            .unwrap_or_default();
+        if assumes > before {
+            new_assumes.push((atom_name(atom), assumes - before));
+        }
+    }
+
+    let spec_coverage = (exec_functions > 0).then(|| specified as f64 / exec_functions as f64);
+    if let (Some(limit), Some(coverage)) = (thresholds.min_spec_coverage, spec_coverage) {
+        if coverage < limit {
+            violations.push(GateViolation {
+                rule: GateRule::SpecCoverage,
+                function: None,
+                relative_path: None,
+                value: coverage,
+                limit,
+                detail: format!(
+                    "{} of {} exec functions have requires/ensures",
+                    specified, exec_functions
+                ),
+            });
+        }
+    }
+
+    let new_assume_count = new_assumes.iter().map(|(_, count)| count).sum();
+    if let Some(limit) = thresholds.max_new_assumes {
+        if new_assume_count > limit {
+            new_assumes.sort();
+            let functions: Vec<String> = new_assumes
+                .iter()
+                .map(|(name, count)| format!("{} (+{})", name, count))
+                .collect();
+            violations.push(GateViolation {
+                rule: GateRule::NewAssumes,
+                function: None,
+                relative_path: None,
+                value: new_assume_count as f64,
+                limit: limit as f64,
+                detail: format!("new assume/admit calls in {}", functions.join(", ")),
+            });
+        }
+    }
+
+    violations.sort_by(|a, b| {
+        a.rule
+            .cmp(&b.rule)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+            .then_with(|| a.function.cmp(&b.function))
+    });
+    GateReport {
+        functions: atoms.len(),
+        spec_coverage,
+        new_assumes: new_assume_count,
+        violations,
+    }
+}
+
+impl GateReport {
+    /// Whether every budget holds
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Render the report as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Complexity gate\n\n");
+        let _ = writeln!(out, "| | Value |\n|---|---:|");
+        let _ = writeln!(out, "| Functions checked | {} |", self.functions);
+        if let Some(coverage) = self.spec_coverage {
+            let _ = writeln!(out, "| Spec coverage | {:.1}% |", coverage * 100.0);
+        }
+        let _ = writeln!(out, "| New assumes | {} |", self.new_assumes);
+        let _ = writeln!(out, "| Violations | {} |", self.violations.len());
+
+        if self.passed() {
+            out.push_str("\nAll budgets hold.\n");
+            return out;
+        }
+
+        out.push_str("\n## Violations\n\n");
+        out.push_str("| Rule | Function | Location | Value | Limit | Detail |\n");
+        out.push_str("|---|---|---|---:|---:|---|\n");
+        for violation in &self.violations {
+            let (value, limit) = if violation.rule == GateRule::SpecCoverage {
+                (
+                    format!("{:.1}%", violation.value * 100.0),
+                    format!("{:.1}%", violation.limit * 100.0),
+                )
+            } else {
+                (
+                    format!("{:.0}", violation.value),
+                    format!("{:.0}", violation.limit),
+                )
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} |",
+                violation.rule.as_str(),
+                violation
+                    .function
+                    .as_deref()
+                    .map_or(String::new(), |f| format!("`{}`", f)),
+                violation.relative_path.as_deref().unwrap_or_default(),
+                value,
+                limit,
+                violation.detail.replace('|', "\\|")
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_gate() {
+        let thresholds = GateThresholds::from_toml_str(
+            "max_spec_effort = 100.0\nmax_proof_depth = 2\nmin_spec_coverage = 0.75\nmax_new_assumes = 0\n",
+        )
+        .unwrap();
+        assert!(GateThresholds::from_toml_str("max_effort = 1.0").is_err());
+
+        let atoms = vec![
+            json!({
+                "identifier": "m/add().",
+                "display_name": "add",
+                "relative_path": "src/m.rs",
+                "body": "fn add() {\n    assume(x > 0); // assume(y)\n    admit();\n}",
+                "metrics": {
+                    "function_mode": "exec",
+                    "requires_specs": [{"text": "x > 0", "halstead_effort": 250.0}],
+                    "ensures_specs": [{"text": "r == x", "halstead_effort": 50.0}]
+                },
+                "proof_metrics": {"proof_depth": 3}
+            }),
+            json!({
+                "identifier": "m/sub().",
+                "display_name": "sub",
+                "relative_path": "src/m.rs",
+                "body": "fn sub() {}",
+                "metrics": {"function_mode": "exec"}
+            }),
+        ];
+        let baseline = vec![json!({
+            "identifier": "m/add().",
+            "body": "fn add() {\n    assume(x > 0);\n}"
+        })];
+
+        let report = check_gate(&atoms, Some(&baseline), &thresholds);
+        assert!(!report.passed());
+        assert_eq!(report.spec_coverage, Some(0.5));
+        assert_eq!(report.new_assumes, 1);
+        let rules: Vec<GateRule> = report.violations.iter().map(|v| v.rule).collect();
+        assert_eq!(
+            rules,
+            vec![
+                GateRule::SpecEffort,
+                GateRule::ProofDepth,
+                GateRule::SpecCoverage,
+                GateRule::NewAssumes
+            ]
+        );
+        assert_eq!(report.violations[0].detail, "requires x > 0");
+        assert_eq!(
+            report.violations[3].detail,
+            "new assume/admit calls in add (+1)"
+        );
+        assert!(report
+            .to_markdown()
+            .contains("| spec_coverage |  |  | 50.0% | 75.0% |"));
+
+        // Without a baseline every assume is new
+        assert_eq!(check_gate(&atoms, None, &thresholds).new_assumes, 2);
+        let report = check_gate(&atoms, None, &GateThresholds::default());
+        assert!(report.passed());
+        assert!(report.to_markdown().contains("All budgets hold."));
+    }
+}
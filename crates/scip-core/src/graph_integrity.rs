@@ -0,0 +1,344 @@
+//! Integrity of the caller/callee links of a call graph
+//!
+//! [`CallGraph`] keeps `callers` and `callees` symmetric through its own
+//! methods, but builders, filters and merges that edit nodes in place can
+//! leave them out of step, and exports then show edges in one direction
+//! only. [`CallGraph::verify_integrity`] lists the broken links and
+//! [`CallGraph::repair`] fixes them, taking each node's `callees` as
+//! authoritative:
+//!
+//! - a callee in the graph that doesn't list its caller gets it added;
+//! - callers that don't call the node, or are not in the graph, are dropped;
+//! - spec references and call occurrences of functions that are not callees
+//!   are dropped.
+//!
+//! Callees outside the graph stay by default, as one-sided entries (calls
+//! into external code, or into functions a filter left out). With
+//! [`IntegrityOptions::with_dangling_callees`] they are reported too, and
+//! [`CallGraph::repair_with`] drops them along with their spec references
+//! and call occurrences. [`repaired`] runs the default check before exports,
+//! warning when it had to fix anything.
+
+use crate::types::CallGraph;
+use log::warn;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt;
+
+/// Number of violations listed in the warning of [`repaired`]
+const WARN_EXAMPLES: usize = 3;
+
+/// A broken link between two functions of a call graph
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IntegrityViolation {
+    /// `caller` calls `callee`, which doesn't list it among its callers
+    MissingCaller { caller: String, callee: String },
+    /// `symbol` lists `caller`, which doesn't call it
+    StaleCaller { symbol: String, caller: String },
+    /// `symbol` lists a caller that is not in the graph
+    DanglingCaller { symbol: String, caller: String },
+    /// `symbol` calls `callee`, which is not in the graph (only reported
+    /// with [`IntegrityOptions::with_dangling_callees`])
+    DanglingCallee { symbol: String, callee: String },
+    /// `symbol` references `callee` in its specs without calling it
+    StraySpecRef { symbol: String, callee: String },
+    /// `symbol` has a call occurrence of `callee` without calling it
+    StrayOccurrence { symbol: String, callee: String },
+}
+
+impl fmt::Display for IntegrityViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityViolation::MissingCaller { caller, callee } => {
+                write!(f, "{callee} does not list its caller {caller}")
+            }
+            IntegrityViolation::StaleCaller { symbol, caller } => {
+                write!(
+                    f,
+                    "{symbol} lists {caller} as caller, which does not call it"
+                )
+            }
+            IntegrityViolation::DanglingCaller { symbol, caller } => {
+                write!(
+                    f,
+                    "{symbol} lists {caller} as caller, which is not in the graph"
+                )
+            }
+            IntegrityViolation::DanglingCallee { symbol, callee } => {
+                write!(f, "{symbol} calls {callee}, which is not in the graph")
+            }
+            IntegrityViolation::StraySpecRef { symbol, callee } => {
+                write!(
+                    f,
+                    "{symbol} has a spec reference to {callee}, which is not a callee"
+                )
+            }
+            IntegrityViolation::StrayOccurrence { symbol, callee } => {
+                write!(
+                    f,
+                    "{symbol} has a call occurrence of {callee}, which is not a callee"
+                )
+            }
+        }
+    }
+}
+
+/// Which links [`CallGraph::verify_integrity_with`] checks, beyond the
+/// caller/callee symmetry always checked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IntegrityOptions {
+    /// Report callees that are not in the graph
+    /// ([`IntegrityViolation::DanglingCallee`]), so that repairing drops them
+    pub dangling_callees: bool,
+}
+
+impl IntegrityOptions {
+    pub fn with_dangling_callees(mut self, dangling_callees: bool) -> Self {
+        self.dangling_callees = dangling_callees;
+        self
+    }
+}
+
+impl CallGraph {
+    /// The broken caller/callee links of the graph, sorted
+    pub fn verify_integrity(&self) -> Vec<IntegrityViolation> {
+        self.verify_integrity_with(IntegrityOptions::default())
+    }
+
+    /// The broken links of the graph under `options`, sorted
+    pub fn verify_integrity_with(&self, options: IntegrityOptions) -> Vec<IntegrityViolation> {
+        let mut violations = Vec::new();
+        for (symbol, node) in self.iter() {
+            for callee in &node.callees {
+                match self.get(callee) {
+                    None if options.dangling_callees => {
+                        violations.push(IntegrityViolation::DanglingCallee {
+                            symbol: symbol.clone(),
+                            callee: callee.clone(),
+                        })
+                    }
+                    Some(callee_node) if !callee_node.callers.contains(symbol) => {
+                        violations.push(IntegrityViolation::MissingCaller {
+                            caller: symbol.clone(),
+                            callee: callee.clone(),
+                        })
+                    }
+                    _ => {}
+                }
+            }
+            for caller in &node.callers {
+                match self.get(caller) {
+                    None => violations.push(IntegrityViolation::DanglingCaller {
+                        symbol: symbol.clone(),
+                        caller: caller.clone(),
+                    }),
+                    Some(caller_node) if !caller_node.callees.contains(symbol) => {
+                        violations.push(IntegrityViolation::StaleCaller {
+                            symbol: symbol.clone(),
+                            caller: caller.clone(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            for callee in node.spec_refs.difference(&node.callees) {
+                violations.push(IntegrityViolation::StraySpecRef {
+                    symbol: symbol.clone(),
+                    callee: callee.clone(),
+                });
+            }
+            for occurrence in &node.callee_occurrences {
+                if !node.callees.contains(&occurrence.symbol) {
+                    violations.push(IntegrityViolation::StrayOccurrence {
+                        symbol: symbol.clone(),
+                        callee: occurrence.symbol.clone(),
+                    });
+                }
+            }
+        }
+        violations.sort();
+        violations.dedup();
+        violations
+    }
+
+    /// Fix the links reported by [`CallGraph::verify_integrity`] and return
+    /// them (empty if the graph was consistent)
+    pub fn repair(&mut self) -> Vec<IntegrityViolation> {
+        self.repair_with(IntegrityOptions::default())
+    }
+
+    /// Fix the links reported by [`CallGraph::verify_integrity_with`] and
+    /// return them
+    pub fn repair_with(&mut self, options: IntegrityOptions) -> Vec<IntegrityViolation> {
+        let violations = self.verify_integrity_with(options);
+        let nodes = self.nodes_mut();
+        for violation in &violations {
+            match violation {
+                IntegrityViolation::MissingCaller { caller, callee } => {
+                    if let Some(node) = nodes.get_mut(callee) {
+                        node.callers.insert(caller.clone());
+                    }
+                }
+                IntegrityViolation::StaleCaller { symbol, caller }
+                | IntegrityViolation::DanglingCaller { symbol, caller } => {
+                    if let Some(node) = nodes.get_mut(symbol) {
+                        node.callers.remove(caller);
+                    }
+                }
+                IntegrityViolation::DanglingCallee { symbol, callee } => {
+                    if let Some(node) = nodes.get_mut(symbol) {
+                        node.callees.remove(callee);
+                        node.spec_refs.remove(callee);
+                        node.callee_occurrences
+                            .retain(|occurrence| &occurrence.symbol != callee);
+                    }
+                }
+                IntegrityViolation::StraySpecRef { symbol, callee } => {
+                    if let Some(node) = nodes.get_mut(symbol) {
+                        node.spec_refs.remove(callee);
+                    }
+                }
+                IntegrityViolation::StrayOccurrence { symbol, callee } => {
+                    if let Some(node) = nodes.get_mut(symbol) {
+                        node.callee_occurrences
+                            .retain(|occurrence| &occurrence.symbol != callee);
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// The graph, or a repaired copy (with a warning) if it has broken links;
+/// exporters call this before writing
+pub fn repaired(call_graph: &CallGraph) -> Cow<'_, CallGraph> {
+    let violations = call_graph.verify_integrity();
+    if violations.is_empty() {
+        return Cow::Borrowed(call_graph);
+    }
+    let examples: Vec<String> = violations
+        .iter()
+        .take(WARN_EXAMPLES)
+        .map(ToString::to_string)
+        .collect();
+    warn!(
+        "Repaired {} broken caller/callee links in the call graph (e.g. {})",
+        violations.len(),
+        examples.join("; ")
+    );
+    let mut call_graph = call_graph.clone();
+    call_graph.repair();
+    Cow::Owned(call_graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::node;
+    use crate::types::CalleeOccurrence;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_verify_and_repair() {
+        let mut graph = CallGraph::from(
+            [
+                node("a", &["b", "external"]),
+                node("b", &["c"]),
+                node("c", &[]),
+            ]
+            .into_iter()
+            .map(|node| (node.symbol.clone(), node))
+            .collect::<HashMap<_, _>>(),
+        );
+        assert!(graph.verify_integrity().is_empty());
+
+        // Edit nodes behind the graph's back, as a careless merge would
+        let nodes = graph.nodes_mut();
+        nodes.get_mut("c").unwrap().callers.clear();
+        let a = nodes.get_mut("a").unwrap();
+        a.callers.extend(["c".to_string(), "gone".to_string()]);
+        a.spec_refs.insert("c".to_string());
+        a.callee_occurrences.push(CalleeOccurrence {
+            symbol: "c".to_string(),
+            line: 3,
+            column: None,
+            location: None,
+            edge_kind: None,
+        });
+
+        let violations = graph.verify_integrity();
+        assert_eq!(
+            violations,
+            [
+                IntegrityViolation::MissingCaller {
+                    caller: "b".to_string(),
+                    callee: "c".to_string()
+                },
+                IntegrityViolation::StaleCaller {
+                    symbol: "a".to_string(),
+                    caller: "c".to_string()
+                },
+                IntegrityViolation::DanglingCaller {
+                    symbol: "a".to_string(),
+                    caller: "gone".to_string()
+                },
+                IntegrityViolation::StraySpecRef {
+                    symbol: "a".to_string(),
+                    callee: "c".to_string()
+                },
+                IntegrityViolation::StrayOccurrence {
+                    symbol: "a".to_string(),
+                    callee: "c".to_string()
+                },
+            ]
+        );
+        assert!(matches!(repaired(&graph), Cow::Owned(_)));
+
+        assert_eq!(graph.repair(), violations);
+        assert!(graph.verify_integrity().is_empty());
+        assert!(matches!(repaired(&graph), Cow::Borrowed(_)));
+        assert!(graph["c"].callers.contains("b"));
+        assert!(graph["a"].callers.is_empty());
+        assert!(graph["a"].spec_refs.is_empty() && graph["a"].callee_occurrences.is_empty());
+        // Callees outside the graph are kept
+        assert!(graph["a"].callees.contains("external"));
+    }
+
+    #[test]
+    fn test_repair_dangling_callees() {
+        let mut graph = CallGraph::from(
+            [
+                node("a", &["b", "gone"]),
+                node("b", &[]).with_callers(&["a"]),
+            ]
+            .into_iter()
+            .map(|node| (node.symbol.clone(), node))
+            .collect::<HashMap<_, _>>(),
+        );
+        let a = graph.nodes_mut().get_mut("a").unwrap();
+        a.spec_refs.insert("gone".to_string());
+        a.callee_occurrences.push(CalleeOccurrence {
+            symbol: "gone".to_string(),
+            line: 2,
+            column: None,
+            location: None,
+            edge_kind: None,
+        });
+        assert!(graph.verify_integrity().is_empty());
+
+        let options = IntegrityOptions::default().with_dangling_callees(true);
+        let dangling = IntegrityViolation::DanglingCallee {
+            symbol: "a".to_string(),
+            callee: "gone".to_string(),
+        };
+        assert_eq!(graph.verify_integrity_with(options), [dangling.clone()]);
+        assert_eq!(graph.repair_with(options), [dangling]);
+        assert!(graph.verify_integrity_with(options).is_empty());
+
+        let a = &graph["a"];
+        assert_eq!(a.callees.len(), 1);
+        assert!(a.spec_refs.is_empty() && a.callee_occurrences.is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+//! History of metrics over time
+//!
+//! Each run of the metrics steps can be summarized into a [`RunSummary`]
+//! (commit, totals and per-module aggregates, computed from atoms JSON as
+//! written by `compute_metrics` / `compute_proof_metrics`) and appended to a
+//! JSONL store, one run per line. [`write_report_csv`] turns the store into
+//! a time series for plotting.
+//!
+//! The store format is versioned by `schema_version`. Records only gain
+//! fields, and every field has a default, so stores written by older
+//! versions keep loading; the CSV report columns are only ever appended to.
+
+use crate::error::{Error, Result};
+use crate::verification::module_path;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// Version of the records written by this version
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Spec clause lists in the `metrics` object of an atom
+const SPEC_CLAUSE_KEYS: [&str; 3] = ["requires_specs", "ensures_specs", "decreases_specs"];
+
+/// Metrics summed over a set of functions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsAggregate {
+    pub functions: usize,
+    pub exec_functions: usize,
+    pub proof_functions: usize,
+    pub spec_functions: usize,
+    /// Exec and proof functions with at least one `requires` or `ensures`
+    pub specified_functions: usize,
+    /// `requires`, `ensures` and `decreases` clauses
+    pub spec_clauses: usize,
+    /// Halstead effort summed over all spec clauses
+    pub spec_effort: f64,
+    /// Halstead effort of the proof blocks (direct, not transitive)
+    pub proof_effort: f64,
+    /// Proof functions called directly, summed over functions
+    pub direct_lemmas: usize,
+    /// Longest lemma call chain
+    pub max_proof_depth: usize,
+}
+
+impl MetricsAggregate {
+    fn add(&mut self, atom: &Value) {
+        let metrics = &atom["metrics"];
+        self.functions += 1;
+        let mode = metrics["function_mode"].as_str().unwrap_or("exec");
+        match mode {
+            "proof" => self.proof_functions += 1,
+            "spec" => self.spec_functions += 1,
+            _ => self.exec_functions += 1,
+        }
+
+        let count = |key: &str| metrics[key].as_array().map_or(0, Vec::len);
+        if mode != "spec" && count("requires_specs") + count("ensures_specs") > 0 {
+            self.specified_functions += 1;
+        }
+        for key in SPEC_CLAUSE_KEYS {
+            let specs = metrics[key].as_array().map(Vec::as_slice);
+            for spec in specs.unwrap_or_default() {
+                self.spec_clauses += 1;
+                self.spec_effort += spec["halstead_effort"].as_f64().unwrap_or(0.0);
+            }
+        }
+
+        let proof = &atom["proof_metrics"];
+        self.proof_effort += proof["direct_proof_halstead"]["effort"]
+            .as_f64()
+            .unwrap_or(0.0);
+        self.direct_lemmas += proof["direct_lemmas"].as_array().map_or(0, Vec::len);
+        let depth = proof["proof_depth"].as_u64().unwrap_or(0) as usize;
+        self.max_proof_depth = self.max_proof_depth.max(depth);
+    }
+}
+
+/// Aggregate of one module (source file path, see `module_path`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModuleSummary {
+    /// Module path (`""` for the crate root)
+    pub module: String,
+    #[serde(flatten)]
+    pub metrics: MetricsAggregate,
+}
+
+/// Summary of one run, a line of the history store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunSummary {
+    pub schema_version: u32,
+    /// Commit the metrics were computed on
+    pub commit: String,
+    /// RFC 3339 time of the run
+    pub timestamp: String,
+    pub totals: MetricsAggregate,
+    /// Sorted by module path
+    pub modules: Vec<ModuleSummary>,
+}
+
+/// Summarize atoms with metrics into a run record for `commit`
+pub fn summarize_run(atoms: &[Value], commit: &str) -> RunSummary {
+    let mut totals = MetricsAggregate::default();
+    let mut modules: BTreeMap<String, MetricsAggregate> = BTreeMap::new();
+    for atom in atoms {
+        totals.add(atom);
+        let module = module_path(atom["relative_path"].as_str().unwrap_or_default());
+        modules.entry(module).or_default().add(atom);
+    }
+    RunSummary {
+        schema_version: SCHEMA_VERSION,
+        commit: commit.to_string(),
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        totals,
+        modules: modules
+            .into_iter()
+            .map(|(module, metrics)| ModuleSummary { module, metrics })
+            .collect(),
+    }
+}
+
+/// Append a run to the JSONL store at `path`, creating it if needed
+pub fn append_run(path: impl AsRef<Path>, run: &RunSummary) -> Result<()> {
+    let line = serde_json::to_string(run).map_err(Error::Serialize)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Read the runs of a JSONL store, oldest first; blank lines are skipped
+pub fn read_history(path: impl AsRef<Path>) -> Result<Vec<RunSummary>> {
+    let file = std::fs::File::open(path)?;
+    let mut runs = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let run = serde_json::from_str(&line)
+            .map_err(|e| Error::InvalidData(format!("history line {}: {}", i + 1, e)))?;
+        runs.push(run);
+    }
+    Ok(runs)
+}
+
+/// Metric columns of the CSV report, after `commit,timestamp,module`;
+/// new columns go at the end
+pub const REPORT_COLUMNS: [&str; 10] = [
+    "functions",
+    "exec_functions",
+    "proof_functions",
+    "spec_functions",
+    "specified_functions",
+    "spec_clauses",
+    "spec_effort",
+    "proof_effort",
+    "direct_lemmas",
+    "max_proof_depth",
+];
+
+/// A value of a [`REPORT_COLUMNS`] column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportValue {
+    Count(usize),
+    /// Written with two decimals
+    Real(f64),
+}
+
+impl ReportValue {
+    pub fn as_f64(self) -> f64 {
+        match self {
+            ReportValue::Count(count) => count as f64,
+            ReportValue::Real(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for ReportValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportValue::Count(count) => write!(f, "{}", count),
+            ReportValue::Real(value) => write!(f, "{:.2}", value),
+        }
+    }
+}
+
+impl MetricsAggregate {
+    /// Values in [`REPORT_COLUMNS`] order
+    pub fn report_values(&self) -> [ReportValue; 10] {
+        [
+            ReportValue::Count(self.functions),
+            ReportValue::Count(self.exec_functions),
+            ReportValue::Count(self.proof_functions),
+            ReportValue::Count(self.spec_functions),
+            ReportValue::Count(self.specified_functions),
+            ReportValue::Count(self.spec_clauses),
+            ReportValue::Real(self.spec_effort),
+            ReportValue::Real(self.proof_effort),
+            ReportValue::Count(self.direct_lemmas),
+            ReportValue::Count(self.max_proof_depth),
+        ]
+    }
+}
+
+/// Write the runs as a time series CSV (`commit,timestamp,module,functions,...`),
+/// one row per run with module `*` for the totals, plus one per module and
+/// run with `modules`
+pub fn write_report_csv<W: Write>(runs: &[RunSummary], modules: bool, writer: W) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(
+        ["commit", "timestamp", "module"]
+            .iter()
+            .chain(&REPORT_COLUMNS),
+    )?;
+    let mut write_row = |run: &RunSummary, module: &str, metrics: &MetricsAggregate| {
+        let row = [run.commit.as_str(), run.timestamp.as_str(), module];
+        let values = metrics.report_values().map(|value| value.to_string());
+        writer.write_record(row.into_iter().chain(values.iter().map(String::as_str)))
+    };
+    for run in runs {
+        write_row(run, "*", &run.totals)?;
+        if modules {
+            for module in &run.modules {
+                write_row(run, &module.module, &module.metrics)?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_history_round_trip() {
+        let atoms = vec![
+            json!({
+                "relative_path": "src/field/mod.rs",
+                "metrics": {
+                    "function_mode": "exec",
+                    "requires_specs": [{"halstead_effort": 10.0}],
+                    "ensures_specs": [{"halstead_effort": 5.5}, {"text": "r > 0"}]
+                },
+                "proof_metrics": {
+                    "direct_proof_halstead": {"effort": 20.0},
+                    "direct_lemmas": ["lemma_a", "lemma_b"],
+                    "proof_depth": 2
+                }
+            }),
+            json!({
+                "relative_path": "src/lib.rs",
+                "metrics": {"function_mode": "spec"}
+            }),
+        ];
+        let run = summarize_run(&atoms, "abc123");
+        assert_eq!(run.schema_version, SCHEMA_VERSION);
+        assert_eq!(run.totals.functions, 2);
+        assert_eq!(run.totals.specified_functions, 1);
+        assert_eq!(run.totals.spec_clauses, 3);
+        assert_eq!(run.totals.spec_effort, 15.5);
+        assert_eq!(run.totals.direct_lemmas, 2);
+        assert_eq!(run.totals.max_proof_depth, 2);
+        let modules: Vec<&str> = run.modules.iter().map(|m| m.module.as_str()).collect();
+        assert_eq!(modules, vec!["", "field"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("history.jsonl");
+        append_run(&store, &run).unwrap();
+        // Older records without newer fields still load
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&store)
+            .unwrap()
+            .write_all(
+                b"\n{\"schema_version\":1,\"commit\":\"def456\",\"totals\":{\"functions\":3}}\n",
+            )
+            .unwrap();
+        let runs = read_history(&store).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0], run);
+        assert_eq!(runs[1].totals.functions, 3);
+
+        let mut csv = Vec::new();
+        write_report_csv(&runs, true, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "commit,timestamp,module,functions,exec_functions,proof_functions,spec_functions,specified_functions,spec_clauses,spec_effort,proof_effort,direct_lemmas,max_proof_depth"
+        );
+        assert_eq!(lines.len(), 1 + 3 + 1);
+        assert!(lines[1].ends_with(",*,2,1,0,1,1,3,15.50,20.00,2,2"));
+        assert!(lines[2].ends_with(",,1,0,0,1,0,0,0.00,0.00,0,0"));
+        assert!(lines[3].ends_with(",field,1,1,0,0,1,3,15.50,20.00,2,2"));
+        assert_eq!(lines[4], "def456,,*,3,0,0,0,0,0,0.00,0.00,0,0");
+    }
+}
@@ -0,0 +1,114 @@
+//! Line spans of the items of a source file, parsed with verus_syn.
+//!
+//! SCIP definition occurrences usually only cover the function name, so
+//! body extraction needs to find where the function ends. Counting braces is
+//! fooled by braces in string literals and comments; a parser is not.
+//! Functions inside `verus!` blocks are found by parsing the macro contents.
+
+use verus_syn::spanned::Spanned;
+use verus_syn::visit::{self, Visit};
+use verus_syn::{
+    File, ImplItemConst, ImplItemFn, ItemConst, ItemFn, ItemMacro, ItemStatic, TraitItemConst,
+    TraitItemFn,
+};
+
+/// Where an item ends: 0-based line and character column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ItemEnd {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Spans of the functions, constants and statics of a file
+#[derive(Debug, Default)]
+pub(crate) struct ItemSpans {
+    /// (first line, end) of each item, lines 0-based
+    spans: Vec<(usize, ItemEnd)>,
+}
+
+impl ItemSpans {
+    /// Parse a source file; `None` if it does not parse
+    pub(crate) fn parse(source: &str) -> Option<Self> {
+        let file = verus_syn::parse_file(source).ok()?;
+        let mut spans = Self::default();
+        spans.visit_file(&file);
+        Some(spans)
+    }
+
+    /// End of the innermost item whose span contains the 0-based `line`
+    pub(crate) fn end_of_item_at(&self, line: usize) -> Option<ItemEnd> {
+        self.spans
+            .iter()
+            .filter(|(start, end)| *start <= line && line <= end.line)
+            .min_by_key(|(start, end)| end.line - start)
+            .map(|(_, end)| *end)
+    }
+
+    fn push(&mut self, item: &impl Spanned) {
+        let span = item.span();
+        let (start, end) = (span.start(), span.end());
+        if start.line == 0 || end.line == 0 {
+            // No location information
+            return;
+        }
+        self.spans.push((
+            start.line - 1,
+            ItemEnd {
+                line: end.line - 1,
+                column: end.column,
+            },
+        ));
+    }
+}
+
+impl<'ast> Visit<'ast> for ItemSpans {
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.push(item);
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast ImplItemFn) {
+        self.push(item);
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast TraitItemFn) {
+        self.push(item);
+        visit::visit_trait_item_fn(self, item);
+    }
+
+    fn visit_item_const(&mut self, item: &'ast ItemConst) {
+        self.push(item);
+        visit::visit_item_const(self, item);
+    }
+
+    fn visit_impl_item_const(&mut self, item: &'ast ImplItemConst) {
+        self.push(item);
+        visit::visit_impl_item_const(self, item);
+    }
+
+    fn visit_trait_item_const(&mut self, item: &'ast TraitItemConst) {
+        self.push(item);
+        visit::visit_trait_item_const(self, item);
+    }
+
+    fn visit_item_static(&mut self, item: &'ast ItemStatic) {
+        self.push(item);
+        visit::visit_item_static(self, item);
+    }
+
+    fn visit_item_macro(&mut self, item: &'ast ItemMacro) {
+        let is_verus = item
+            .mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "verus");
+        if is_verus {
+            // Tokens keep their positions in the original file
+            if let Ok(file) = verus_syn::parse2::<File>(item.mac.tokens.clone()) {
+                self.visit_file(&file);
+            }
+        }
+    }
+}
@@ -0,0 +1,415 @@
+//! Similar-lemma suggestions for functions that fail or lack verification
+//!
+//! Lemmas from a vstd index JSON are turned into TF-IDF vectors over the
+//! tokens of their name, signature and `requires`/`ensures` clauses
+//! (identifiers are split at `_` and case changes, so `lemma_mul_is_commutative`
+//! matches a query for `mul_commutative`). Queries are scored by cosine
+//! similarity, and [`annotate_similar_lemmas`] stores the best matches of
+//! each failed or unverified node in its `similar_lemmas` field, the field
+//! read by the web viewer.
+//!
+//! The index may be a JSON array of lemmas or an object with a `lemmas`
+//! array. Each lemma needs a `name`; `file_path`, `line_number`,
+//! `signature`, `requires`, `ensures` (a string or a list of clauses) and
+//! `source` are optional.
+
+use crate::error::{Error, Result};
+use crate::types::{D3Graph, VerificationStatus};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Number of suggestions per node used by the pipeline
+pub const DEFAULT_TOP_K: usize = 3;
+
+/// A lemma read from the index
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct IndexedLemma {
+    #[serde(alias = "lemma_name", alias = "display_name")]
+    pub name: String,
+    #[serde(default, alias = "file", alias = "path")]
+    pub file_path: String,
+    #[serde(default, alias = "line")]
+    pub line_number: Option<usize>,
+    #[serde(default)]
+    pub signature: String,
+    #[serde(default, deserialize_with = "clauses")]
+    pub requires: Vec<String>,
+    #[serde(default, deserialize_with = "clauses")]
+    pub ensures: Vec<String>,
+    /// `vstd` or `project`; lemmas without one are from vstd
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// A suggestion, in the shape the web viewer expects
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimilarLemma {
+    pub name: String,
+    /// Cosine similarity in `(0.0, 1.0]`
+    pub score: f64,
+    pub file_path: String,
+    pub line_number: Option<usize>,
+    pub signature: String,
+    pub source: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IndexFile {
+    Lemmas(Vec<IndexedLemma>),
+    Wrapped { lemmas: Vec<IndexedLemma> },
+}
+
+/// `requires`/`ensures` given as a single string or a list of clauses
+fn clauses<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Clauses {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Option::<Clauses>::deserialize(deserializer)? {
+        Some(Clauses::One(clause)) => vec![clause],
+        Some(Clauses::Many(clauses)) => clauses,
+        None => Vec::new(),
+    })
+}
+
+/// Lowercase word tokens of `text`, with identifiers split at `_`, `::`
+/// and lower-to-upper case changes; single characters are dropped
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let mut current = String::new();
+        let mut prev_lower = false;
+        for c in word.chars() {
+            if c.is_uppercase() && prev_lower {
+                tokens.push(std::mem::take(&mut current));
+            }
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+            current.extend(c.to_lowercase());
+        }
+        tokens.push(current);
+    }
+    tokens.retain(|token| token.chars().count() > 1);
+    tokens
+}
+
+/// Term frequencies of a token list
+fn term_counts(tokens: Vec<String>) -> HashMap<String, f64> {
+    let mut counts = HashMap::new();
+    for token in tokens {
+        *counts.entry(token).or_insert(0.0) += 1.0;
+    }
+    counts
+}
+
+/// A unit-length sparse vector
+type Vector = HashMap<String, f64>;
+
+fn normalized(mut vector: Vector) -> Vector {
+    let norm = vector.values().map(|w| w * w).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        vector.values_mut().for_each(|w| *w /= norm);
+    }
+    vector
+}
+
+/// TF-IDF vectors of a set of lemmas
+#[derive(Debug, Clone, Default)]
+pub struct LemmaIndex {
+    lemmas: Vec<IndexedLemma>,
+    vectors: Vec<Vector>,
+    idf: HashMap<String, f64>,
+}
+
+impl LemmaIndex {
+    /// Build the index of `lemmas`
+    pub fn from_lemmas(lemmas: Vec<IndexedLemma>) -> Self {
+        let counts: Vec<HashMap<String, f64>> = lemmas
+            .iter()
+            .map(|lemma| {
+                let text = [
+                    lemma.name.as_str(),
+                    lemma.signature.as_str(),
+                    &lemma.requires.join(" "),
+                    &lemma.ensures.join(" "),
+                ]
+                .join(" ");
+                term_counts(tokenize(&text))
+            })
+            .collect();
+
+        let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+        for terms in &counts {
+            for term in terms.keys() {
+                *document_frequency.entry(term).or_default() += 1;
+            }
+        }
+        // Smoothed IDF, so terms in every lemma still count a little
+        let n = lemmas.len() as f64;
+        let idf: HashMap<String, f64> = document_frequency
+            .into_iter()
+            .map(|(term, df)| (term.to_string(), ((n + 1.0) / (df as f64 + 1.0)).ln() + 1.0))
+            .collect();
+
+        let vectors = counts
+            .into_iter()
+            .map(|terms| {
+                normalized(
+                    terms
+                        .into_iter()
+                        .map(|(term, tf)| {
+                            let weight = tf * idf[&term];
+                            (term, weight)
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+
+        Self {
+            lemmas,
+            vectors,
+            idf,
+        }
+    }
+
+    /// Read and index a lemma index JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let lemmas = match serde_json::from_str(&contents)
+            .map_err(|e| Error::InvalidData(format!("lemma index: {e}")))?
+        {
+            IndexFile::Lemmas(lemmas) | IndexFile::Wrapped { lemmas } => lemmas,
+        };
+        Ok(Self::from_lemmas(lemmas))
+    }
+
+    pub fn len(&self) -> usize {
+        self.lemmas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lemmas.is_empty()
+    }
+
+    /// The `top_k` lemmas most similar to `query`, best first; lemmas named
+    /// in `exclude` and lemmas sharing no term with the query are left out
+    pub fn similar(&self, query: &str, top_k: usize, exclude: &HashSet<&str>) -> Vec<SimilarLemma> {
+        let query = normalized(
+            term_counts(tokenize(query))
+                .into_iter()
+                .filter_map(|(term, tf)| self.idf.get(&term).map(|idf| (term, tf * idf)))
+                .collect(),
+        );
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<(usize, f64)> = self
+            .vectors
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !exclude.contains(self.lemmas[*i].name.as_str()))
+            .map(|(i, vector)| {
+                let score = query
+                    .iter()
+                    .filter_map(|(term, w)| vector.get(term).map(|v| v * w))
+                    .sum::<f64>();
+                (i, score)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        scored.sort_by(|a, b| {
+            b.1.total_cmp(&a.1)
+                .then_with(|| self.lemmas[a.0].name.cmp(&self.lemmas[b.0].name))
+        });
+
+        scored
+            .into_iter()
+            .take(top_k)
+            .map(|(i, score)| {
+                let lemma = &self.lemmas[i];
+                SimilarLemma {
+                    name: lemma.name.clone(),
+                    score,
+                    file_path: lemma.file_path.clone(),
+                    line_number: lemma.line_number,
+                    signature: lemma.signature.clone(),
+                    source: lemma.source.clone().unwrap_or_else(|| "vstd".to_string()),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Store the `top_k` lemmas most similar to each failed or unverified node
+/// in its `similar_lemmas` field; returns the number of nodes annotated
+///
+/// The query is the node's name, doc comment, signature and
+/// `requires`/`ensures` clauses, the same parts lemmas are indexed by. A
+/// node is never suggested to itself.
+pub fn annotate_similar_lemmas(graph: &mut D3Graph, index: &LemmaIndex, top_k: usize) -> usize {
+    let mut annotated = 0;
+    for node in &mut graph.nodes {
+        if !matches!(
+            node.verification_status,
+            Some(VerificationStatus::Failed | VerificationStatus::Unverified)
+        ) {
+            continue;
+        }
+        let query: Vec<&str> = [node.display_name.as_str()]
+            .into_iter()
+            .chain(node.doc.as_deref())
+            .chain(node.signature.as_deref())
+            .chain(node.requires_text.iter().map(String::as_str))
+            .chain(node.ensures_text.iter().map(String::as_str))
+            .collect();
+        let query = query.join(" ");
+        let exclude = HashSet::from([node.display_name.as_str()]);
+        let similar = index.similar(&query, top_k, &exclude);
+        if similar.is_empty() {
+            continue;
+        }
+        let value = serde_json::to_value(&similar).expect("similar lemmas serialize");
+        node.extra.insert("similar_lemmas".to_string(), value);
+        annotated += 1;
+    }
+    annotated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{D3GraphMetadata, D3Node};
+
+    const INDEX: &str = r#"{
+        "lemmas": [
+            {
+                "name": "lemma_mul_is_commutative",
+                "file_path": "vstd/arithmetic/mul.rs",
+                "line_number": 120,
+                "signature": "pub proof fn lemma_mul_is_commutative(x: int, y: int)",
+                "ensures": "x * y == y * x"
+            },
+            {
+                "name": "lemma_add_zero",
+                "file_path": "vstd/arithmetic/add.rs",
+                "signature": "pub proof fn lemma_add_zero(x: int)",
+                "ensures": ["x + 0 == x"]
+            },
+            {
+                "name": "lemma_seq_len",
+                "signature": "pub proof fn lemma_seq_len<A>(s: Seq<A>)",
+                "requires": ["s.len() > 0"]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_similar_lemmas() {
+        assert_eq!(
+            tokenize("lemma_mulIsCommutative(x: u64)"),
+            vec!["lemma", "mul", "is", "commutative", "u64"]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.json");
+        std::fs::write(&path, INDEX).unwrap();
+        let index = LemmaIndex::load(&path).unwrap();
+        assert_eq!(index.len(), 3);
+
+        let similar = index.similar("mul_commutative_u64", 3, &HashSet::new());
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].name, "lemma_mul_is_commutative");
+        assert_eq!(similar[0].line_number, Some(120));
+        assert_eq!(similar[0].source, "vstd");
+
+        // Shared terms like `lemma` rank every lemma; excluded names are skipped
+        let exclude = HashSet::from(["lemma_add_zero"]);
+        let names: Vec<String> = index
+            .similar("lemma_add_zero", 3, &exclude)
+            .into_iter()
+            .map(|lemma| lemma.name)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(!names.contains(&"lemma_add_zero".to_string()));
+
+        assert!(index.similar("", 3, &HashSet::new()).is_empty());
+
+        let node = |name: &str, status| D3Node {
+            id: name.to_string(),
+            display_name: name.to_string(),
+            verification_status: status,
+            ..Default::default()
+        };
+        let mut graph = D3Graph {
+            nodes: vec![
+                node("mul_commutes", Some(VerificationStatus::Failed)),
+                node("mul_ok", Some(VerificationStatus::Verified)),
+                node("lemma_add_zero", Some(VerificationStatus::Unverified)),
+            ],
+            links: Vec::new(),
+            metadata: D3GraphMetadata::default(),
+        };
+        assert_eq!(annotate_similar_lemmas(&mut graph, &index, 1), 2);
+        assert_eq!(
+            graph.nodes[0].extra["similar_lemmas"][0]["name"],
+            "lemma_mul_is_commutative"
+        );
+        assert!(!graph.nodes[1].extra.contains_key("similar_lemmas"));
+        assert_ne!(
+            graph.nodes[2].extra["similar_lemmas"][0]["name"],
+            "lemma_add_zero"
+        );
+    }
+
+    #[test]
+    fn test_query_includes_signature_and_clauses() {
+        let index = LemmaIndex::from_lemmas(
+            serde_json::from_str::<Vec<IndexedLemma>>(
+                r#"[
+                    {"name": "lemma_mul_is_commutative", "ensures": "mul(x, y) == mul(y, x)"},
+                    {"name": "lemma_seq_len", "signature": "proof fn lemma_seq_len<A>(s: Seq<A>)"}
+                ]"#,
+            )
+            .unwrap(),
+        );
+        // The name alone shares no term with either lemma
+        let node = D3Node {
+            id: "check".to_string(),
+            display_name: "check".to_string(),
+            verification_status: Some(VerificationStatus::Failed),
+            ..Default::default()
+        };
+        let annotate = |node: D3Node| {
+            let mut graph = D3Graph {
+                nodes: vec![node],
+                links: Vec::new(),
+                metadata: D3GraphMetadata::default(),
+            };
+            annotate_similar_lemmas(&mut graph, &index, 1);
+            graph.nodes.remove(0).extra.remove("similar_lemmas")
+        };
+        assert_eq!(annotate(node.clone()), None);
+
+        let with_signature = D3Node {
+            signature: Some("fn check(s: Seq<u8>)".to_string()),
+            ..node.clone()
+        };
+        assert_eq!(
+            annotate(with_signature).unwrap()[0]["name"],
+            "lemma_seq_len"
+        );
+        let with_ensures = D3Node {
+            ensures_text: vec!["mul(a, b) == mul(b, a)".to_string()],
+            ..node
+        };
+        assert_eq!(
+            annotate(with_ensures).unwrap()[0]["name"],
+            "lemma_mul_is_commutative"
+        );
+    }
+}
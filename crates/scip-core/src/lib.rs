@@ -8,84 +8,225 @@
 //! The library is organized into focused modules:
 //!
 //! - [`types`]: All shared data structures (SCIP types, graph types, D3 types)
-//! - [`parser`]: SCIP JSON parsing utilities
+//! - [`parser`]: SCIP JSON parsing and atoms JSON loading
 //! - [`call_graph`]: Core call graph building and analysis
+//! - [`error`]: The [`Error`] enum returned by fallible public APIs
 //! - [`export_d3`]: D3.js/web export functionality
 //! - [`export_dot`]: DOT/Graphviz export for CLI visualization
+//! - [`export_html`]: Self-contained interactive HTML report
+//! - [`export_markdown`]: Per-function markdown dossiers
+//! - [`export_prometheus`]: Prometheus/OpenMetrics gauges of verification health for CI dashboards
+//! - [`export_sarif`]: SARIF output of analysis findings for code review tools
+//! - [`export_tabular`]: CSV/Parquet node and edge tables
+//! - [`module_graph`]: Per-file aggregation of the call graph
+//! - `export_sqlite`: SQLite database export and queries (`sqlite` feature)
+//! - `export_xlsx`: Multi-sheet Excel workbooks of metrics (`xlsx` feature)
 //!
 //! ## Additional Modules
 //!
-//! - [`scip_reader`]: Alternative SCIP file reader
+//! - [`csv_enrich`]: Joining functions CSVs with atom metrics by configurable key strategies
+//! - [`scip_merge`]: Merging the SCIP indices of several repositories into one graph
+//! - [`scip_reader`]: Binary (protobuf) and streaming JSON SCIP index readers
 //! - [`scip_utils`]: Utility functions for SCIP data manipulation
-//! - [`call_graph_svg`]: Legacy SVG visualization
+//! - [`symbol_scheme`]: Per-indexer SCIP symbol grammars (Rust, TypeScript, Python, Go)
+//! - [`symbol_index`]: Resolving human-written function names to symbols
+//! - [`analysis`]: Whole-graph analyses (graph metrics, dominators, dead-code detection, spec
+//!   coverage, trust base, external lemma usage)
+//! - [`node_filter`]: Pluggable filters choosing highlighted and skipped nodes in exports
+//! - [`gate`]: Complexity budgets (spec effort, proof depth, spec coverage, new assumes) for CI
+//! - [`graph_integrity`]: Caller/callee symmetry checks and repairs of call graphs before export
+//! - [`history`]: Per-commit metrics summaries and time-series reports
+//! - [`metrics_compare`]: Delta report between the metrics of two revisions
+//! - [`metrics_rollup`]: Metrics rolled up by file, module, impl block or trait
+//! - [`lemma_similarity`]: TF-IDF similar-lemma suggestions from a vstd lemma index
+//! - [`sampling`]: Shrinking large graphs to a node budget for visualization
+//! - [`source_links`]: GitHub links to function sources for the exporters
+//! - [`spec_text`]: `requires`/`ensures` clause texts of functions, for the exports
+//! - [`statistics`]: Distributions (percentiles, histograms) of per-function metrics
+//! - [`tooltip`]: How much of each function's source the exports show
+//! - [`validate`]: Schema, duplicate identifier and dangling dep checks of atoms JSON files
+//! - [`verification`]: Per-function verification times from Verus output
+//! - [`verification_cache`]: Re-verifying only the functions that changed since the last run
+//! - [`verus_toolchain`]: Discovery of the Verus release that verifies a project
+//! - [`workspace`]: Cargo workspace detection and per-crate summaries
+//! - [`call_graph_svg`]: Force-directed SVG drawing of call graphs without Graphviz
 //! - [`atoms_to_d3`]: Convert probe-verus output to D3.js graph format
-//! - [`logging`]: Logging utilities
+//! - [`logging`]: Logging utilities, with JSON logs and progress events for CI
+//! - [`monitor`]: Progress reporting, cancellation and atomic output writes for long operations
 //!
 //! ## Quick Start
 //!
 //! ```ignore
-//! use scip_core::{parse_scip_json, build_call_graph, export_call_graph_d3};
+//! use scip_core::{parse_scip_json, build_call_graph, export_call_graph_d3, NodeFilters};
 //!
 //! let scip_data = parse_scip_json("index.scip.json")?;
 //! let call_graph = build_call_graph(&scip_data);
-//! export_call_graph_d3(&call_graph, &scip_data, "graph.json")?;
+//! export_call_graph_d3(&call_graph, &scip_data, "graph.json", &NodeFilters::default())?;
 //! ```
 
 // Core modules (new architecture)
 pub mod call_graph;
+pub mod error;
 pub mod export_d3;
 pub mod export_dot;
+pub mod export_html;
+pub mod export_markdown;
+pub mod export_prometheus;
+pub mod export_sarif;
+#[cfg(feature = "sqlite")]
+pub mod export_sqlite;
+pub mod export_tabular;
+#[cfg(feature = "xlsx")]
+pub mod export_xlsx;
+pub mod module_graph;
 pub mod parser;
 pub mod types;
 
 // Additional/legacy modules
+pub mod analysis;
 pub mod atoms_to_d3;
 pub mod call_graph_svg;
+pub mod csv_enrich;
+pub mod gate;
+pub mod graph_integrity;
+pub mod history;
+mod item_spans;
+pub mod lemma_similarity;
+pub mod logging;
+pub mod metrics_compare;
+pub mod metrics_rollup;
+pub mod monitor;
+pub mod node_filter;
+pub mod sampling;
+pub mod scip_merge;
 pub mod scip_reader;
 pub mod scip_utils;
-
-/// Logging utilities
-pub mod logging {
-    use log::LevelFilter;
-    use std::env;
-
-    /// Initialize logger based on debug flag or environment variable
-    pub fn init_logger(debug: bool) {
-        let log_level = if debug {
-            LevelFilter::Debug
-        } else if env::var("RUST_LOG").is_ok() {
-            // Allow RUST_LOG to override if set
-            env_logger::init();
-            return;
-        } else {
-            LevelFilter::Warn
-        };
-
-        env_logger::Builder::new().filter_level(log_level).init();
-    }
-
-    /// Check if debug logging should be enabled from command line args
-    pub fn should_enable_debug(args: &[String]) -> bool {
-        args.iter().any(|arg| arg == "--debug" || arg == "-d")
-    }
-}
+pub mod source_links;
+pub mod spec_text;
+pub mod statistics;
+pub mod symbol_index;
+pub mod symbol_scheme;
+#[cfg(test)]
+mod test_support;
+pub mod tooltip;
+pub mod validate;
+pub mod verification;
+pub mod verification_cache;
+pub mod verus_toolchain;
+pub mod workspace;
 
 // Re-export commonly used types and functions for convenience
+pub use analysis::graph_metrics::{
+    add_graph_metrics_to_atoms, add_graph_metrics_to_d3, compute_graph_metrics, dominators,
+    gatekeepers, DominatorTree, Gatekeeper, GatekeeperReport,
+};
+pub use analysis::lemma_usage::{
+    lemma_usage, ExternalCrateUsage, ExternalFunctionUsage, LemmaUsageReport, ModuleLemmaUsage,
+};
+pub use analysis::reachability::{
+    entry_point_kind, find_dead_code, resolve_entry_profiles, DeadCodeCategory, DeadCodeFinding,
+    DeadCodeReport, DeadCodeSummary, EntryPointKind, EntryProfile, ReachabilityOptions, Severity,
+};
+pub use analysis::spec_coverage::{
+    spec_coverage, CoverageStats, GroupCoverage, SpecCoverageReport, SpecGap,
+};
+pub use analysis::trust::{
+    trust_base, trust_base_with_status, trust_reasons, TrustReason, TrustReport, TrustedFunction,
+};
 pub use call_graph::{
-    build_call_graph, classify_call_location, detect_decl_kind, generate_filtered_call_graph,
-    is_function_like, parse_function_sections, print_call_graph_summary, symbol_to_path,
+    build_call_graph, build_call_graph_monitored, build_call_graph_streaming,
+    build_call_graph_with_options, classify_call_location, collapse_external_crates, collapse_sccs,
+    collect_neighborhood, count_calls_by_origin, detect_decl_kind, filter_by_edge_kinds,
+    find_call_paths, find_sccs, generate_filtered_call_graph, generate_reverse_filtered_call_graph,
+    is_external_crate_node, is_function_like, neighborhood_by_depth, parse_function_sections,
+    print_call_graph_summary, symbol_to_path, update_call_graph, CallCounts, CallGraphOptions,
+    EXTERNAL_CRATE_PREFIX,
+};
+pub use call_graph_svg::{force_directed_layout, render_call_graph_svg, SvgLayoutOptions};
+pub use csv_enrich::{
+    CsvEnricher, EnrichConfig, EnrichStats, FindError, KeyStrategy, BUILTIN_FIELDS,
+};
+pub use error::{Error, Result};
+pub use export_d3::{
+    assign_stable_ids, bodies_path, call_graph_to_atoms, call_graph_to_atoms_with_filters,
+    call_graph_to_d3, export_call_graph_d3, export_call_graph_d3_filtered, module_graph_to_d3,
+    stable_node_id, write_atoms_json, write_call_graph_as_atoms_json, write_node_bodies,
+    REFERENCES_LINK_TYPE,
 };
-pub use export_d3::{export_call_graph_d3, write_call_graph_as_atoms_json};
 pub use export_dot::{
+    add_source_links_to_dot, function_name_matches, function_subgraph_output_path,
     generate_call_graph_dot, generate_call_graph_dot_string, generate_call_graph_svg,
-    generate_file_subgraph_dot, generate_files_subgraph_dot, generate_function_subgraph_dot,
-    generate_svg_and_png_from_dot,
+    generate_edge_kind_subgraph_dot, generate_file_subgraph_dot, generate_files_subgraph_dot,
+    generate_function_subgraph_dot, generate_function_subgraph_dot_with_paths,
+    generate_module_graph_dot, generate_module_graph_dot_string, generate_svg_and_png_from_dot,
+    graphviz_available, link_dot_file, render_dot_file, RenderBackend, IN_PROCESS_MAX_NODES,
+};
+pub use export_html::{
+    render_interactive_report, write_interactive_report, NodeReportMetrics, ReportMetrics,
+    VerificationStatus,
+};
+pub use export_markdown::{render_dossier, render_dossiers};
+pub use export_prometheus::{health_metrics, render_prometheus, HealthMetrics};
+pub use export_sarif::{sarif_log, SarifFinding, SarifLevel, SarifReport, SarifRule};
+#[cfg(feature = "sqlite")]
+pub use export_sqlite::{export_sqlite, SqliteGraph};
+pub use export_tabular::{edge_rows, export_tabular, node_rows, EdgeRow, NodeRow, TabularFormat};
+#[cfg(feature = "xlsx")]
+pub use export_xlsx::MetricsWorkbook;
+pub use gate::{check_gate, GateReport, GateRule, GateThresholds, GateViolation};
+pub use graph_integrity::{repaired, IntegrityOptions, IntegrityViolation};
+pub use history::{
+    append_run, read_history, summarize_run, write_report_csv, MetricsAggregate, ModuleSummary,
+    ReportValue, RunSummary, REPORT_COLUMNS,
+};
+pub use lemma_similarity::{annotate_similar_lemmas, IndexedLemma, LemmaIndex, SimilarLemma};
+pub use metrics_compare::{
+    compare_metrics, ComparedFunction, EffortIncrease, LemmaGrowth, MetricsComparison,
+};
+pub use metrics_rollup::{
+    add_verification_status_to_atoms, group_key, impl_block, rollup_metrics, trait_name, GroupBy,
+    GroupMetrics, MetricsRollup, Rollup,
+};
+pub use module_graph::{build_module_graph, ModuleEdge, ModuleFunction, ModuleGraph, ModuleNode};
+pub use monitor::{write_atomic, CancellationToken, Cancelled, Monitor, NoProgress, Progress};
+pub use node_filter::{
+    is_test_function, CratePrefixFilter, NodeFilter, NodeFilters, NodeKindFilter, PathPrefixFilter,
+    RegexFilter, TestFilter,
 };
 pub use parser::{
-    extract_display_name_from_symbol, extract_path_info_from_symbol, parse_scip_json,
+    atoms_to_call_graph, extract_display_name_from_symbol, extract_path_info_from_symbol,
+    load_call_graph, parse_atoms_json, parse_atoms_json_str, parse_scip_json,
+    parse_scip_json_str_with_mode, parse_scip_json_with_mode, read_atoms_json, ParseMode,
+    ParseWarning, ParseWarningKind, ParsedScipIndex,
+};
+pub use sampling::{is_sample_placeholder, sample_call_graph, PLACEHOLDER_PREFIX};
+pub use scip_merge::merge_scip_indices;
+pub use scip_reader::{parse_scip_protobuf, stream_documents, DocumentStream};
+pub use source_links::SourceLinks;
+pub use spec_text::{add_spec_texts_to_d3, signature_text, spec_clauses};
+pub use statistics::{
+    render_distribution_table, Distribution, HistogramBin, MetricDistribution, HISTOGRAM_BINS,
+};
+pub use symbol_index::{
+    name_matches, MatchKind, ResolveError, SymbolEntry, SymbolMatch, SymbolResolver,
 };
+pub use symbol_scheme::{
+    parse_symbol, scheme_for_symbol, strip_generics, GoScheme, IdentifierCollision, PythonScheme,
+    RustAnalyzerScheme, SymbolNormalizer, SymbolScheme, TypeScriptScheme,
+};
+pub use tooltip::TooltipContent;
 pub use types::{
-    Atom, CallLocation, CalleeOccurrence, D3Graph, D3GraphMetadata, D3Link, D3Node, Document,
-    DeclKind, FunctionNode, FunctionSections, Metadata, Occurrence, ScipIndex,
-    SignatureDocumentation, Symbol, ToolInfo,
+    Atom, AtomsFile, CallGraph, CallLocation, CalleeOccurrence, CrateSummary, D3Graph,
+    D3GraphMetadata, D3Link, D3Node, DeclKind, Document, EdgeKind, EnsuresSupport, FormatVersion,
+    FunctionNode, FunctionParam, FunctionSections, FunctionSignature, GraphMetrics, Metadata,
+    NodeKind, Occurrence, ScipIndex, SignatureDocumentation, SourceSpan, Symbol, ToolInfo,
+};
+pub use validate::{validate_atoms_json, ValidationIssue, ValidationReport, ValidationRule};
+pub use verification::{
+    modules_from_paths, run_cargo_verus, run_cargo_verus_by_module, run_cargo_verus_with_retry,
+    verify_modules_parallel, verify_with_retry, FunctionTiming, ModuleVerification,
+    ParallelVerification, RetryPolicy, VerificationAttempt, VerificationRun, VerificationTimings,
 };
+pub use verification_cache::{body_hashes, CachedVerification, VerificationCache};
+pub use verus_toolchain::{ToolchainConfig, ToolchainSource, VerusToolchain};
+pub use workspace::{summarize_by_crate, Workspace, WorkspaceMember};
@@ -0,0 +1,232 @@
+//! Logging utilities
+//!
+//! Logs go to stderr, as `env_logger` text by default. With
+//! [`LogFormat::Json`] every record is one JSON object per line, and the
+//! binaries also report progress as structured events that CI wrappers can
+//! time and alert on:
+//!
+//! ```text
+//! {"ts":"2025-01-01T12:00:00.000Z","level":"INFO","event":"step_started","step":"verification"}
+//! {"ts":"2025-01-01T12:00:05.120Z","level":"WARN","target":"pipeline","message":"Verification failed: ..."}
+//! {"ts":"2025-01-01T12:00:05.121Z","level":"INFO","event":"step_finished","step":"verification","duration_ms":5121,"counts":{"verified":12}}
+//! ```
+//!
+//! Steps end with `step_finished`, `step_failed` (with an `error`) or
+//! `step_skipped` (with a `reason`). Events are written whatever the log level; with text logs they are only
+//! logged at debug level, since the binaries print their own progress.
+
+use chrono::{SecondsFormat, Utc};
+use log::{debug, LevelFilter};
+use serde_json::{json, Map, Value};
+use std::env;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Output format of the logger
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!(
+                "unknown log format '{}' (expected text or json)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        })
+    }
+}
+
+static FORMAT: OnceLock<LogFormat> = OnceLock::new();
+
+/// Initialize logger based on debug flag or environment variable
+pub fn init_logger(debug: bool) {
+    init_logger_with_format(debug, LogFormat::Text);
+}
+
+/// Initialize logger with the given output format
+pub fn init_logger_with_format(debug: bool, format: LogFormat) {
+    let _ = FORMAT.set(format);
+
+    let mut builder = if debug {
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(LevelFilter::Debug);
+        builder
+    } else if env::var("RUST_LOG").is_ok() {
+        // Allow RUST_LOG to override if set
+        env_logger::Builder::from_default_env()
+    } else {
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(LevelFilter::Warn);
+        builder
+    };
+
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let line = json!({
+                "ts": timestamp(),
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", line)
+        });
+    }
+    builder.init();
+}
+
+/// `println!` for human-readable progress output, left out with JSON logs
+/// (which report progress as events instead)
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::logging::json_logs() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Format chosen at initialization ([`LogFormat::Text`] before that)
+pub fn log_format() -> LogFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// Whether logs are JSON; binaries skip their human-readable output then
+pub fn json_logs() -> bool {
+    log_format() == LogFormat::Json
+}
+
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// The JSON line of an event; the fields of an object are inlined
+fn event_line(name: &str, fields: Value) -> Value {
+    let mut line = Map::new();
+    line.insert("ts".to_string(), Value::String(timestamp()));
+    line.insert("level".to_string(), Value::String("INFO".to_string()));
+    line.insert("event".to_string(), Value::String(name.to_string()));
+    match fields {
+        Value::Object(fields) => line.extend(fields),
+        Value::Null => {}
+        other => {
+            line.insert("value".to_string(), other);
+        }
+    }
+    Value::Object(line)
+}
+
+/// Report a structured event, e.g. `event("summary", json!({"functions": 10}))`
+pub fn event(name: &str, fields: Value) {
+    if json_logs() {
+        eprintln!("{}", event_line(name, fields));
+    } else {
+        debug!("{}: {}", name, fields);
+    }
+}
+
+/// A timed step, reported as a `step_started` event followed by
+/// `step_finished`, `step_failed` or `step_skipped`
+#[derive(Debug)]
+pub struct Step {
+    name: String,
+    started: Instant,
+}
+
+impl Step {
+    pub fn start(name: &str) -> Self {
+        event("step_started", json!({ "step": name }));
+        Step {
+            name: name.to_string(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Milliseconds since the step started
+    pub fn elapsed_ms(&self) -> u128 {
+        self.started.elapsed().as_millis()
+    }
+
+    /// Finish the step, reporting its duration and `counts` (an object of
+    /// named counts, or `Value::Null`)
+    pub fn finish(self, counts: Value) {
+        let mut fields = json!({
+            "step": self.name,
+            "duration_ms": self.elapsed_ms(),
+        });
+        if !counts.is_null() {
+            fields["counts"] = counts;
+        }
+        event("step_finished", fields);
+    }
+
+    /// Report the step as failed, with the error
+    pub fn fail(self, error: &str) {
+        event(
+            "step_failed",
+            json!({
+                "step": self.name,
+                "duration_ms": self.elapsed_ms(),
+                "error": error,
+            }),
+        );
+    }
+
+    /// Report the step as skipped, with the reason
+    pub fn skip(self, reason: &str) {
+        event(
+            "step_skipped",
+            json!({ "step": self.name, "reason": reason }),
+        );
+    }
+}
+
+/// Check if debug logging should be enabled from command line args
+pub fn should_enable_debug(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--debug" || arg == "-d")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_line() {
+        assert_eq!("json".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert!("yaml".parse::<LogFormat>().is_err());
+
+        let line = event_line(
+            "step_finished",
+            json!({ "step": "export", "duration_ms": 12 }),
+        );
+        assert_eq!(line["event"], "step_finished");
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["step"], "export");
+        assert_eq!(line["duration_ms"], 12);
+        assert!(line["ts"].as_str().unwrap().ends_with('Z'));
+
+        assert!(event_line("done", Value::Null).get("value").is_none());
+        assert_eq!(event_line("count", json!(3))["value"], 3);
+    }
+}
@@ -0,0 +1,338 @@
+//! Compare the metrics of two revisions
+//!
+//! [`compare_metrics`] matches the atoms of a baseline and a current metrics
+//! JSON by identifier and reports what got worse: spec clauses whose
+//! Halstead effort grew by more than a given percentage, new exec/proof
+//! functions without `requires`/`ensures`, and proofs relying on more lemmas
+//! transitively. [`MetricsComparison::to_markdown`] renders it as a PR
+//! comment.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// A function in the comparison
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComparedFunction {
+    pub identifier: String,
+    pub display_name: String,
+    pub relative_path: String,
+}
+
+impl ComparedFunction {
+    fn of(atom: &Value) -> Self {
+        let identifier = atom["identifier"].as_str().unwrap_or_default().to_string();
+        Self {
+            display_name: atom["display_name"]
+                .as_str()
+                .map_or_else(|| identifier.clone(), str::to_string),
+            identifier,
+            relative_path: atom["relative_path"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+/// Spec effort of a function that grew past the allowed increase
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffortIncrease {
+    pub function: ComparedFunction,
+    pub before: f64,
+    pub after: f64,
+    /// Increase in percent of `before`
+    pub increase_pct: f64,
+}
+
+/// A proof whose transitive lemma count grew
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LemmaGrowth {
+    pub function: ComparedFunction,
+    pub before: usize,
+    pub after: usize,
+    /// Transitive lemmas that were not used before
+    pub new_lemmas: Vec<String>,
+}
+
+/// Result of [`compare_metrics`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetricsComparison {
+    pub baseline_functions: usize,
+    pub current_functions: usize,
+    pub added: usize,
+    pub removed: usize,
+    /// Increase (in percent) above which spec effort is reported
+    pub effort_threshold_pct: f64,
+    /// Sorted by decreasing increase
+    pub effort_increases: Vec<EffortIncrease>,
+    /// New exec/proof functions without `requires` or `ensures`, by path
+    pub new_without_specs: Vec<ComparedFunction>,
+    /// Sorted by decreasing growth
+    pub lemma_growth: Vec<LemmaGrowth>,
+}
+
+/// Halstead effort summed over the spec clauses of an atom
+pub(crate) fn spec_effort(atom: &Value) -> f64 {
+    ["requires_specs", "ensures_specs", "decreases_specs"]
+        .iter()
+        .filter_map(|key| atom["metrics"][key].as_array())
+        .flatten()
+        .filter_map(|spec| spec["halstead_effort"].as_f64())
+        .sum()
+}
+
+fn has_specs(atom: &Value) -> bool {
+    ["requires_specs", "ensures_specs"].iter().any(|key| {
+        atom["metrics"][key]
+            .as_array()
+            .is_some_and(|a| !a.is_empty())
+    })
+}
+
+fn transitive_lemmas(atom: &Value) -> Vec<&str> {
+    atom["proof_metrics"]["transitive_lemmas"]
+        .as_array()
+        .map(|lemmas| lemmas.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Compare current metrics against a baseline; spec effort increases of
+/// more than `effort_threshold_pct` percent are reported
+pub fn compare_metrics(
+    baseline: &[Value],
+    current: &[Value],
+    effort_threshold_pct: f64,
+) -> MetricsComparison {
+    let before: HashMap<&str, &Value> = baseline
+        .iter()
+        .filter_map(|atom| Some((atom["identifier"].as_str()?, atom)))
+        .collect();
+    let mut comparison = MetricsComparison {
+        baseline_functions: baseline.len(),
+        current_functions: current.len(),
+        effort_threshold_pct,
+        ..Default::default()
+    };
+
+    for atom in current {
+        let Some(old) = atom["identifier"]
+            .as_str()
+            .and_then(|id| before.get(id).copied())
+        else {
+            comparison.added += 1;
+            let mode = atom["metrics"]["function_mode"].as_str().unwrap_or("exec");
+            if mode != "spec" && !has_specs(atom) {
+                comparison
+                    .new_without_specs
+                    .push(ComparedFunction::of(atom));
+            }
+            continue;
+        };
+
+        let (effort_before, effort_after) = (spec_effort(old), spec_effort(atom));
+        if effort_before > 0.0 {
+            let increase_pct = (effort_after - effort_before) / effort_before * 100.0;
+            if increase_pct > effort_threshold_pct {
+                comparison.effort_increases.push(EffortIncrease {
+                    function: ComparedFunction::of(atom),
+                    before: effort_before,
+                    after: effort_after,
+                    increase_pct,
+                });
+            }
+        }
+
+        let (lemmas_before, lemmas_after) = (transitive_lemmas(old), transitive_lemmas(atom));
+        if lemmas_after.len() > lemmas_before.len() {
+            let mut new_lemmas: Vec<String> = lemmas_after
+                .iter()
+                .filter(|lemma| !lemmas_before.contains(lemma))
+                .map(|lemma| lemma.to_string())
+                .collect();
+            new_lemmas.sort();
+            comparison.lemma_growth.push(LemmaGrowth {
+                function: ComparedFunction::of(atom),
+                before: lemmas_before.len(),
+                after: lemmas_after.len(),
+                new_lemmas,
+            });
+        }
+    }
+    // Counted by identifier: an identifier listed twice in `current` is
+    // still a single baseline function
+    let after: HashSet<&str> = current
+        .iter()
+        .filter_map(|atom| atom["identifier"].as_str())
+        .collect();
+    comparison.removed = before.keys().filter(|id| !after.contains(*id)).count();
+
+    comparison.effort_increases.sort_by(|a, b| {
+        b.increase_pct
+            .total_cmp(&a.increase_pct)
+            .then_with(|| a.function.identifier.cmp(&b.function.identifier))
+    });
+    comparison
+        .new_without_specs
+        .sort_by(|a, b| (&a.relative_path, &a.identifier).cmp(&(&b.relative_path, &b.identifier)));
+    comparison.lemma_growth.sort_by(|a, b| {
+        (b.after - b.before)
+            .cmp(&(a.after - a.before))
+            .then_with(|| a.function.identifier.cmp(&b.function.identifier))
+    });
+    comparison
+}
+
+impl MetricsComparison {
+    /// Whether anything got worse
+    pub fn has_regressions(&self) -> bool {
+        !self.effort_increases.is_empty()
+            || !self.new_without_specs.is_empty()
+            || !self.lemma_growth.is_empty()
+    }
+
+    /// Render the comparison as a markdown document (e.g. a PR comment)
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Metrics comparison\n\n");
+        let _ = writeln!(out, "| | Baseline | Current |\n|---|---:|---:|");
+        let _ = writeln!(
+            out,
+            "| Functions | {} | {} |",
+            self.baseline_functions, self.current_functions
+        );
+        let _ = writeln!(
+            out,
+            "\n{} functions added, {} removed.",
+            self.added, self.removed
+        );
+
+        if !self.has_regressions() {
+            out.push_str("\nNo metrics regressions.\n");
+            return out;
+        }
+
+        if !self.effort_increases.is_empty() {
+            let _ = writeln!(
+                out,
+                "\n## Spec effort increased by more than {}%\n",
+                self.effort_threshold_pct
+            );
+            out.push_str("| Function | Location | Before | After | Change |\n");
+            out.push_str("|---|---|---:|---:|---:|\n");
+            for increase in &self.effort_increases {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {:.0} | {:.0} | +{:.1}% |",
+                    increase.function.display_name,
+                    increase.function.relative_path,
+                    increase.before,
+                    increase.after,
+                    increase.increase_pct
+                );
+            }
+        }
+
+        if !self.new_without_specs.is_empty() {
+            out.push_str("\n## New functions without specs\n\n");
+            out.push_str("| Function | Location |\n|---|---|\n");
+            for function in &self.new_without_specs {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} |",
+                    function.display_name, function.relative_path
+                );
+            }
+        }
+
+        if !self.lemma_growth.is_empty() {
+            out.push_str("\n## Proofs using more lemmas\n\n");
+            out.push_str("| Function | Location | Before | After | New lemmas |\n");
+            out.push_str("|---|---|---:|---:|---|\n");
+            for growth in &self.lemma_growth {
+                let new_lemmas: Vec<String> = growth
+                    .new_lemmas
+                    .iter()
+                    .map(|lemma| format!("`{}`", lemma))
+                    .collect();
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} | {} |",
+                    growth.function.display_name,
+                    growth.function.relative_path,
+                    growth.before,
+                    growth.after,
+                    new_lemmas.join(", ")
+                );
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn atom(id: &str, mode: &str, effort: Option<f64>, lemmas: &[&str]) -> Value {
+        let specs: Vec<Value> = effort
+            .map(|effort| json!({ "halstead_effort": effort }))
+            .into_iter()
+            .collect();
+        json!({
+            "identifier": id,
+            "display_name": id,
+            "relative_path": "src/lib.rs",
+            "metrics": { "function_mode": mode, "ensures_specs": specs },
+            "proof_metrics": { "transitive_lemmas": lemmas }
+        })
+    }
+
+    #[test]
+    fn test_compare_metrics() {
+        let baseline = vec![
+            atom("grow", "exec", Some(100.0), &[]),
+            atom("same", "exec", Some(100.0), &[]),
+            atom("lemma", "proof", Some(10.0), &["a"]),
+            atom("gone", "exec", None, &[]),
+        ];
+        let current = vec![
+            atom("grow", "exec", Some(150.0), &[]),
+            atom("same", "exec", Some(105.0), &[]),
+            atom("lemma", "proof", Some(10.0), &["a", "b", "c"]),
+            atom("new_exec", "exec", None, &[]),
+            atom("new_spec", "spec", None, &[]),
+            atom("new_specified", "exec", Some(5.0), &[]),
+        ];
+
+        let comparison = compare_metrics(&baseline, &current, 10.0);
+        assert!(comparison.has_regressions());
+        assert_eq!((comparison.added, comparison.removed), (3, 1));
+        assert_eq!(comparison.effort_increases.len(), 1);
+        assert_eq!(comparison.effort_increases[0].function.identifier, "grow");
+        assert_eq!(comparison.effort_increases[0].increase_pct, 50.0);
+        let unspecified: Vec<&str> = comparison
+            .new_without_specs
+            .iter()
+            .map(|f| f.identifier.as_str())
+            .collect();
+        assert_eq!(unspecified, vec!["new_exec"]);
+        assert_eq!(comparison.lemma_growth[0].new_lemmas, vec!["b", "c"]);
+
+        let markdown = comparison.to_markdown();
+        assert!(markdown.contains("| `grow` | src/lib.rs | 100 | 150 | +50.0% |"));
+        assert!(markdown.contains("| `lemma` | src/lib.rs | 1 | 3 | `b`, `c` |"));
+
+        assert!(!compare_metrics(&baseline, &baseline, 10.0).has_regressions());
+    }
+
+    #[test]
+    fn test_compare_metrics_with_duplicate_identifiers() {
+        let baseline = vec![atom("f", "exec", None, &[]), atom("g", "exec", None, &[])];
+        let current = vec![atom("f", "exec", None, &[]), atom("f", "exec", None, &[])];
+
+        let comparison = compare_metrics(&baseline, &current, 10.0);
+        assert_eq!((comparison.added, comparison.removed), (0, 1));
+    }
+}
@@ -0,0 +1,439 @@
+//! Metrics rolled up by file, module, impl block or trait
+//!
+//! `compute_metrics` and `compute_proof_metrics` write one entry per
+//! function. [`rollup_metrics`] groups those atoms ([`GroupBy`]) and reports,
+//! for each group, the sum, mean and maximum of spec effort and proof depth
+//! and the number of functions that failed verification.
+//!
+//! Impl blocks and traits come from the SCIP descriptors in the atom
+//! identifiers: a method of `ops/impl#[Foo][Bar]` belongs to the impl block
+//! `<ops::Foo as Bar>` and to the trait `Bar`, a method of `ops/impl#[Foo]`
+//! to the impl block `ops::Foo`, and a trait method `Bar#baz().` to the
+//! trait `Bar`. Impl blocks are named by the module-qualified type, so
+//! same-named types of different modules stay apart. Functions outside impl
+//! blocks (or traits) are left out of those groupings and only counted.
+//!
+//! [`add_verification_status_to_atoms`] matches graph nodes to atoms by
+//! identifier, the normalized symbol written by
+//! `write_call_graph_as_atoms_json`.
+
+use crate::error::Result;
+use crate::metrics_compare::spec_effort;
+use crate::symbol_scheme::{strip_generics, SymbolNormalizer};
+use crate::types::D3Graph;
+use crate::verification::module_path;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// What to group functions by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// Source file (`relative_path`)
+    File,
+    /// Module path of the source file
+    Module,
+    /// Impl block (`module::Foo` or `<module::Foo as Bar>`)
+    Impl,
+    /// Trait, implemented or declared
+    Trait,
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GroupBy::File => "file",
+            GroupBy::Module => "module",
+            GroupBy::Impl => "impl",
+            GroupBy::Trait => "trait",
+        })
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "file" => Ok(GroupBy::File),
+            "module" => Ok(GroupBy::Module),
+            "impl" => Ok(GroupBy::Impl),
+            "trait" => Ok(GroupBy::Trait),
+            _ => Err(format!(
+                "unknown grouping: {s} (expected file, module, impl or trait)"
+            )),
+        }
+    }
+}
+
+/// Sum, mean and maximum of one metric over a group
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Rollup {
+    pub sum: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+impl Rollup {
+    fn of(values: &[f64]) -> Self {
+        let sum: f64 = values.iter().sum();
+        Self {
+            sum,
+            mean: if values.is_empty() {
+                0.0
+            } else {
+                sum / values.len() as f64
+            },
+            max: values.iter().copied().fold(0.0, f64::max),
+        }
+    }
+}
+
+/// Rolled-up metrics of one group
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupMetrics {
+    pub group: String,
+    pub functions: usize,
+    /// Halstead effort of each function's spec clauses
+    pub spec_effort: Rollup,
+    /// Longest lemma call chain of each function (0 without proof metrics)
+    pub proof_depth: Rollup,
+    /// Functions with `verification_status` `failed`
+    pub verification_failures: usize,
+}
+
+/// Result of [`rollup_metrics`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricsRollup {
+    pub group_by: GroupBy,
+    /// Sorted by group name
+    pub groups: Vec<GroupMetrics>,
+    /// Functions that belong to no group (free functions when grouping by
+    /// impl or trait)
+    pub ungrouped: usize,
+}
+
+/// `impl#[Foo]` or `impl#[Foo][Bar]`, also as `impl::[...]` in identifiers
+/// built by `symbol_to_path`
+fn impl_block_regex() -> &'static Regex {
+    static IMPL_BLOCK: OnceLock<Regex> = OnceLock::new();
+    IMPL_BLOCK.get_or_init(|| {
+        Regex::new(r"(?:^|[/:\s])impl(?:#|::)\[([^\]]+)\](?:\[([^\]]+)\])?").unwrap()
+    })
+}
+
+/// `Bar#baz().`, a method declared in a trait
+fn trait_method_regex() -> &'static Regex {
+    static TRAIT_METHOD: OnceLock<Regex> = OnceLock::new();
+    TRAIT_METHOD.get_or_init(|| Regex::new(r"(?:^|[/\s])(\w+)#[\w`]+\(").unwrap())
+}
+
+/// Module-qualified self type and trait of the impl block an identifier
+/// belongs to
+fn impl_of(identifier: &str) -> Option<(String, Option<String>)> {
+    let identifier = strip_generics(identifier);
+    let captures = impl_block_regex().captures_iter(&identifier).last()?;
+    let module = identifier[..captures.get(0)?.start()]
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches([':', '/'])
+        .replace('/', "::");
+    let self_type = if module.is_empty() {
+        captures[1].to_string()
+    } else {
+        format!("{}::{}", module, &captures[1])
+    };
+    Some((self_type, captures.get(2).map(|t| t.as_str().to_string())))
+}
+
+/// Impl block of an identifier: `module::Foo`, or `<module::Foo as Bar>`
+/// for a trait impl
+pub fn impl_block(identifier: &str) -> Option<String> {
+    match impl_of(identifier)? {
+        (self_type, Some(trait_name)) => Some(format!("<{self_type} as {trait_name}>")),
+        (self_type, None) => Some(self_type),
+    }
+}
+
+/// Trait an identifier implements a method of, or declares it in
+pub fn trait_name(identifier: &str) -> Option<String> {
+    if let Some((_, trait_name)) = impl_of(identifier) {
+        return trait_name;
+    }
+    let identifier = strip_generics(identifier);
+    let captures = trait_method_regex().captures_iter(&identifier).last()?;
+    Some(captures[1].to_string()).filter(|name| name != "impl")
+}
+
+/// The group of an atom, `None` if it belongs to none
+pub fn group_key(atom: &Value, group_by: GroupBy) -> Option<String> {
+    let relative_path = atom["relative_path"].as_str().unwrap_or_default();
+    let identifier = atom["identifier"].as_str().unwrap_or_default();
+    match group_by {
+        GroupBy::File => Some(relative_path.to_string()),
+        GroupBy::Module => Some(module_path(relative_path)),
+        GroupBy::Impl => impl_block(identifier),
+        GroupBy::Trait => trait_name(identifier),
+    }
+}
+
+/// Group atoms with metrics (as written by `compute_metrics` /
+/// `compute_proof_metrics`) and roll up their metrics
+pub fn rollup_metrics(atoms: &[Value], group_by: GroupBy) -> MetricsRollup {
+    let mut grouped: BTreeMap<String, Vec<&Value>> = BTreeMap::new();
+    let mut ungrouped = 0;
+    for atom in atoms {
+        match group_key(atom, group_by) {
+            Some(group) => grouped.entry(group).or_default().push(atom),
+            None => ungrouped += 1,
+        }
+    }
+
+    let groups = grouped
+        .into_iter()
+        .map(|(group, atoms)| {
+            let efforts: Vec<f64> = atoms.iter().map(|atom| spec_effort(atom)).collect();
+            let depths: Vec<f64> = atoms
+                .iter()
+                .map(|atom| atom["proof_metrics"]["proof_depth"].as_f64().unwrap_or(0.0))
+                .collect();
+            GroupMetrics {
+                group,
+                functions: atoms.len(),
+                spec_effort: Rollup::of(&efforts),
+                proof_depth: Rollup::of(&depths),
+                verification_failures: atoms
+                    .iter()
+                    .filter(|atom| atom["verification_status"] == "failed")
+                    .count(),
+            }
+        })
+        .collect();
+    MetricsRollup {
+        group_by,
+        groups,
+        ungrouped,
+    }
+}
+
+/// Copy the `verification_status` of the graph's nodes onto the atoms with
+/// the same identifier; returns how many atoms got one
+///
+/// A node matches by its symbol's identifier (see [`SymbolNormalizer`]), or
+/// by its symbol for graphs built from atoms, whose symbols are identifiers.
+pub fn add_verification_status_to_atoms(atoms: &mut [Value], graph: &D3Graph) -> usize {
+    let normalizer = SymbolNormalizer::new(
+        graph
+            .nodes
+            .iter()
+            .map(|node| (node.symbol.as_str(), node.display_name.as_str())),
+    );
+    let mut statuses: HashMap<&str, &str> = HashMap::new();
+    for node in &graph.nodes {
+        let Some(status) = node.verification_status else {
+            continue;
+        };
+        statuses.insert(node.symbol.as_str(), status.as_str());
+        if let Some(identifier) = normalizer.identifier(&node.symbol) {
+            statuses.insert(identifier, status.as_str());
+        }
+    }
+
+    let mut added = 0;
+    for atom in atoms {
+        let identifier = atom["identifier"].as_str().unwrap_or_default();
+        if let Some(status) = statuses.get(identifier).copied() {
+            atom["verification_status"] = Value::from(status);
+            added += 1;
+        }
+    }
+    added
+}
+
+impl MetricsRollup {
+    /// Write the groups as CSV, one row per group
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record([
+            self.group_by.to_string().as_str(),
+            "functions",
+            "spec_effort_sum",
+            "spec_effort_mean",
+            "spec_effort_max",
+            "proof_depth_sum",
+            "proof_depth_mean",
+            "proof_depth_max",
+            "verification_failures",
+        ])?;
+        for group in &self.groups {
+            writer.write_record([
+                group.group.clone(),
+                group.functions.to_string(),
+                format!("{:.2}", group.spec_effort.sum),
+                format!("{:.2}", group.spec_effort.mean),
+                format!("{:.2}", group.spec_effort.max),
+                format!("{}", group.proof_depth.sum),
+                format!("{:.2}", group.proof_depth.mean),
+                format!("{}", group.proof_depth.max),
+                group.verification_failures.to_string(),
+            ])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{D3Node, VerificationStatus};
+    use serde_json::json;
+
+    fn atom(identifier: &str, path: &str, effort: f64, depth: u64, status: &str) -> Value {
+        json!({
+            "identifier": identifier,
+            "display_name": identifier.rsplit(['/', ']', ':']).next().unwrap(),
+            "relative_path": path,
+            "metrics": { "requires_specs": [{ "halstead_effort": effort }] },
+            "proof_metrics": { "proof_depth": depth },
+            "verification_status": status,
+        })
+    }
+
+    #[test]
+    fn test_rollup_by_impl_and_trait() {
+        let atoms = vec![
+            atom(
+                "field/impl#[FieldElement<T>][AddAssign]add_assign().",
+                "src/field.rs",
+                10.0,
+                2,
+                "verified",
+            ),
+            atom(
+                "field/impl#[FieldElement]new().",
+                "src/field.rs",
+                30.0,
+                0,
+                "failed",
+            ),
+            atom(
+                "field::impl::[FieldElement][Neg]neg::neg",
+                "src/field.rs",
+                20.0,
+                4,
+                "verified",
+            ),
+            atom("ops/Neg#neg().", "src/ops.rs", 0.0, 0, "verified"),
+            atom("field/reduce().", "src/field.rs", 5.0, 1, "failed"),
+            atom(
+                "backend/field/impl#[FieldElement]new().",
+                "src/backend/field.rs",
+                8.0,
+                0,
+                "verified",
+            ),
+        ];
+
+        let by_impl = rollup_metrics(&atoms, GroupBy::Impl);
+        let names: Vec<&str> = by_impl.groups.iter().map(|g| g.group.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "<field::FieldElement as AddAssign>",
+                "<field::FieldElement as Neg>",
+                "backend::field::FieldElement",
+                "field::FieldElement"
+            ]
+        );
+        assert_eq!(by_impl.ungrouped, 2);
+        assert_eq!(by_impl.groups[3].verification_failures, 1);
+
+        let by_trait = rollup_metrics(&atoms, GroupBy::Trait);
+        let neg = &by_trait.groups[1];
+        assert_eq!((neg.group.as_str(), neg.functions), ("Neg", 2));
+        assert_eq!(
+            neg.spec_effort,
+            Rollup {
+                sum: 20.0,
+                mean: 10.0,
+                max: 20.0
+            }
+        );
+        assert_eq!(neg.proof_depth.max, 4.0);
+        assert_eq!(by_trait.ungrouped, 2);
+
+        let by_file = rollup_metrics(&atoms, GroupBy::File);
+        assert_eq!(by_file.groups[1].group, "src/field.rs");
+        assert_eq!(by_file.groups[1].functions, 4);
+        assert_eq!(by_file.groups[1].verification_failures, 2);
+        assert_eq!(by_file.ungrouped, 0);
+
+        let mut csv = Vec::new();
+        by_impl.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("impl,functions,spec_effort_sum,"));
+        assert!(csv.contains("\nfield::FieldElement,1,30.00,30.00,30.00,0,0.00,0,1\n"));
+        assert_eq!("Trait".parse::<GroupBy>(), Ok(GroupBy::Trait));
+        assert!("struct".parse::<GroupBy>().is_err());
+    }
+
+    #[test]
+    fn test_add_verification_status_matches_by_identifier() {
+        let node = |symbol: &str, status| D3Node {
+            id: symbol.to_string(),
+            symbol: symbol.to_string(),
+            display_name: "new".to_string(),
+            relative_path: "src/field.rs".to_string(),
+            verification_status: Some(status),
+            ..Default::default()
+        };
+        let graph = D3Graph {
+            nodes: vec![
+                node(
+                    "rust-analyzer cargo dalek 1.0.0 field/impl#[FieldElement]new().",
+                    VerificationStatus::Failed,
+                ),
+                node(
+                    "backend/field/impl#[FieldElement]new().",
+                    VerificationStatus::Verified,
+                ),
+            ],
+            links: Vec::new(),
+            metadata: Default::default(),
+        };
+        // The first node's symbol normalizes to the first atom's identifier
+        let mut atoms = vec![
+            atom(
+                "field::impl::[FieldElement]new::new",
+                "src/field.rs",
+                0.0,
+                0,
+                "",
+            ),
+            atom(
+                "backend/field/impl#[FieldElement]new().",
+                "src/field.rs",
+                0.0,
+                0,
+                "",
+            ),
+            atom("field/reduce().", "src/field.rs", 0.0, 0, ""),
+        ];
+        for atom in &mut atoms {
+            atom.as_object_mut().unwrap().remove("verification_status");
+        }
+
+        assert_eq!(add_verification_status_to_atoms(&mut atoms, &graph), 2);
+        assert_eq!(atoms[0]["verification_status"], "failed");
+        assert_eq!(atoms[1]["verification_status"], "verified");
+        assert!(atoms[2].get("verification_status").is_none());
+    }
+}
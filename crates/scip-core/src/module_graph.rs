@@ -0,0 +1,179 @@
+//! Module-level view of the call graph.
+//!
+//! Function graphs of large projects are unreadable. [`build_module_graph`]
+//! aggregates the call graph per source file: one node per file listing the
+//! functions it defines, and one weighted edge per pair of files whose
+//! functions call each other. External functions (without a source path in
+//! the project) are left out.
+//!
+//! The module graph is exported with `generate_module_graph_dot` (DOT) and
+//! `module_graph_to_d3` (web viewer).
+
+use crate::types::CallGraph;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A function defined in a module
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ModuleFunction {
+    pub symbol: String,
+    pub display_name: String,
+}
+
+/// A source file and the functions it defines
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleNode {
+    /// Source path relative to the project root
+    pub path: String,
+    /// Functions defined in the file, sorted by display name
+    pub functions: Vec<ModuleFunction>,
+    /// Calls between functions of this file
+    pub internal_calls: usize,
+}
+
+/// Calls from functions of one file to functions of another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleEdge {
+    pub source: String,
+    pub target: String,
+    /// Number of distinct caller-callee pairs
+    pub calls: usize,
+}
+
+/// Source files and the calls between them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModuleGraph {
+    /// Modules sorted by path
+    pub modules: Vec<ModuleNode>,
+    /// Edges sorted by source and target
+    pub edges: Vec<ModuleEdge>,
+}
+
+impl ModuleGraph {
+    /// The module with the given path
+    pub fn module(&self, path: &str) -> Option<&ModuleNode> {
+        self.modules
+            .binary_search_by(|module| module.path.as_str().cmp(path))
+            .ok()
+            .map(|index| &self.modules[index])
+    }
+}
+
+/// Aggregate the call graph into a graph of source files
+pub fn build_module_graph(call_graph: &CallGraph) -> ModuleGraph {
+    let module_of = |symbol: &str| {
+        call_graph
+            .get(symbol)
+            .map(|node| node.relative_path.as_str())
+            .filter(|path| !path.is_empty())
+    };
+
+    let mut modules: BTreeMap<&str, ModuleNode> = BTreeMap::new();
+    let mut edges: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for node in call_graph.values() {
+        let Some(path) = module_of(&node.symbol) else {
+            continue;
+        };
+        let module = modules.entry(path).or_insert_with(|| ModuleNode {
+            path: path.to_string(),
+            functions: Vec::new(),
+            internal_calls: 0,
+        });
+        module.functions.push(ModuleFunction {
+            symbol: node.symbol.clone(),
+            display_name: node.display_name.clone(),
+        });
+
+        for callee_path in node.callees.iter().filter_map(|callee| module_of(callee)) {
+            if callee_path == path {
+                module.internal_calls += 1;
+            } else {
+                *edges.entry((path, callee_path)).or_default() += 1;
+            }
+        }
+    }
+
+    ModuleGraph {
+        modules: modules
+            .into_values()
+            .map(|mut module| {
+                module.functions.sort_by(|a, b| {
+                    (&a.display_name, &a.symbol).cmp(&(&b.display_name, &b.symbol))
+                });
+                module
+            })
+            .collect(),
+        edges: edges
+            .into_iter()
+            .map(|((source, target), calls)| ModuleEdge {
+                source: source.to_string(),
+                target: target.to_string(),
+                calls,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::types::FunctionNode;
+
+    fn node(symbol: &str, relative_path: &str, callees: &[&str]) -> FunctionNode {
+        test_support::node(symbol, callees).in_file(relative_path)
+    }
+
+    #[test]
+    fn test_build_module_graph() {
+        let call_graph: CallGraph = [
+            node("main", "src/main.rs", &["parse", "check", "vec_push"]),
+            node("parse", "src/parser.rs", &["lex", "check"]),
+            node("lex", "src/parser.rs", &[]),
+            node("check", "src/lemmas.rs", &[]),
+            node("vec_push", "", &[]),
+        ]
+        .into_iter()
+        .collect();
+
+        let module_graph = build_module_graph(&call_graph);
+
+        let paths: Vec<&str> = module_graph
+            .modules
+            .iter()
+            .map(|module| module.path.as_str())
+            .collect();
+        assert_eq!(paths, ["src/lemmas.rs", "src/main.rs", "src/parser.rs"]);
+
+        let parser = module_graph.module("src/parser.rs").unwrap();
+        let names: Vec<&str> = parser
+            .functions
+            .iter()
+            .map(|f| f.display_name.as_str())
+            .collect();
+        assert_eq!(names, ["lex", "parse"]);
+        assert_eq!(parser.internal_calls, 1);
+
+        let edges: Vec<(&str, &str, usize)> = module_graph
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.calls))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                ("src/main.rs", "src/lemmas.rs", 1),
+                ("src/main.rs", "src/parser.rs", 1),
+                ("src/parser.rs", "src/lemmas.rs", 1),
+            ]
+        );
+
+        let dot = crate::export_dot::generate_module_graph_dot_string(&module_graph);
+        assert!(dot.contains(
+            "  \"src/parser.rs\" [label=\"src/parser.rs\\n2 functions\", tooltip=\"lex, parse\"]\n"
+        ));
+        assert!(
+            dot.contains("  \"src/main.rs\" -> \"src/parser.rs\" [label=\"1\", penwidth=1.0]\n")
+        );
+    }
+}
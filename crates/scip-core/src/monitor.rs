@@ -0,0 +1,183 @@
+//! Progress reporting and cancellation of long operations
+//!
+//! Building the call graph of a large project or verifying it module by
+//! module takes minutes. A [`Monitor`] passed to the `*_monitored` variants
+//! of these operations reports each phase to a [`Progress`] implementation
+//! (the CLIs draw progress bars) and stops the work at the next document,
+//! file or module once its [`CancellationToken`] is cancelled, e.g. from a
+//! ctrl-c handler. Operations that can keep what they finished return it;
+//! the others return [`Cancelled`].
+//!
+//! [`write_atomic`] writes outputs through a temporary file, so an
+//! interrupted run never leaves a truncated file behind.
+
+use crate::error::Result;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Receives the progress of an operation, one phase at a time
+pub trait Progress: Send + Sync {
+    /// A phase starts, with `total` units of work if known
+    fn start(&self, phase: &str, total: Option<u64>);
+    /// `delta` more units of the current phase are done
+    fn advance(&self, delta: u64);
+    /// The current phase is done
+    fn finish(&self);
+}
+
+/// Reports nothing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn start(&self, _phase: &str, _total: Option<u64>) {}
+    fn advance(&self, _delta: u64) {}
+    fn finish(&self) {}
+}
+
+/// Error of an operation stopped by its [`CancellationToken`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("operation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Shared flag asking running operations to stop; clones share the flag
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Progress reporting and cancellation of an operation; the default
+/// reports nothing and is never cancelled
+#[derive(Clone)]
+pub struct Monitor {
+    progress: Arc<dyn Progress>,
+    cancellation: CancellationToken,
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new(Arc::new(NoProgress), CancellationToken::new())
+    }
+}
+
+impl fmt::Debug for Monitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Monitor")
+            .field("cancelled", &self.is_cancelled())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Monitor {
+    pub fn new(progress: Arc<dyn Progress>, cancellation: CancellationToken) -> Self {
+        Self {
+            progress,
+            cancellation,
+        }
+    }
+
+    pub fn progress(&self) -> &dyn Progress {
+        self.progress.as_ref()
+    }
+
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// `Err(Cancelled)` once cancelled, for `?` between units of work
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Write `contents` to `path` through a temporary file in the same
+/// directory, renamed into place once complete
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let mut temp_name = file_name.to_os_string();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    std::fs::write(&temp_path, contents)?;
+    std::fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Progress for Recorder {
+        fn start(&self, phase: &str, total: Option<u64>) {
+            self.0.lock().unwrap().push(format!("{phase} {total:?}"));
+        }
+        fn advance(&self, delta: u64) {
+            self.0.lock().unwrap().push(format!("+{delta}"));
+        }
+        fn finish(&self) {
+            self.0.lock().unwrap().push("done".to_string());
+        }
+    }
+
+    #[test]
+    fn test_monitor() {
+        let recorder = Arc::new(Recorder::default());
+        let token = CancellationToken::new();
+        let monitor = Monitor::new(recorder.clone(), token.clone());
+        monitor.progress().start("files", Some(2));
+        monitor.progress().advance(1);
+        monitor.progress().finish();
+        assert_eq!(*recorder.0.lock().unwrap(), ["files Some(2)", "+1", "done"]);
+
+        assert_eq!(monitor.check(), Ok(()));
+        token.cancel();
+        assert!(monitor.clone().is_cancelled());
+        assert_eq!(monitor.check(), Err(Cancelled));
+        assert!(!Monitor::default().is_cancelled());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.json");
+        write_atomic(&path, "{}").unwrap();
+        write_atomic(&path, "[]").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[]");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,423 @@
+//! Pluggable node filters for exports
+//!
+//! Exporters color the project's own functions differently from library
+//! code and can leave some functions out entirely. Which nodes count as
+//! "project sources" is decided by [`NodeFilter`]s collected in
+//! [`NodeFilters`]:
+//!
+//! - [`PathPrefixFilter`] - source file path starts with a prefix
+//! - [`CratePrefixFilter`] - crate (SCIP package) name starts with a prefix
+//! - [`RegexFilter`] - symbol or source file path matches a regex
+//! - [`TestFilter`] - test functions (see [`is_test_function`])
+//! - [`NodeKindFilter`] - functions, or types (see [`NodeKind`])
+//!
+//! Any `Fn(&FunctionNode) -> bool` closure is a filter as well.
+//!
+//! [`NodeFilters`] also decide the output order: by default exporters write
+//! nodes and edges sorted by symbol, so re-exporting an unchanged graph
+//! gives the same bytes (see [`NodeFilters::deterministic`]). They also
+//! carry the [`RenderBackend`] the DOT exporters render images with,
+//! whether those draw calls between clusters one by one or aggregated (see
+//! [`NodeFilters::aggregate_cluster_edges`]), and the [`TooltipContent`]
+//! shown of each function's source.
+
+use crate::analysis::is_test_attribute;
+use crate::export_dot::RenderBackend;
+use crate::graph_integrity::repaired;
+use crate::symbol_scheme::parse_symbol;
+use crate::tooltip::TooltipContent;
+use crate::types::{CallGraph, FunctionNode, NodeKind};
+use regex::Regex;
+use std::path::Path;
+
+/// Decides whether a call graph node matches
+pub trait NodeFilter: Send + Sync {
+    fn matches(&self, node: &FunctionNode) -> bool;
+}
+
+impl<F> NodeFilter for F
+where
+    F: Fn(&FunctionNode) -> bool + Send + Sync,
+{
+    fn matches(&self, node: &FunctionNode) -> bool {
+        self(node)
+    }
+}
+
+/// Matches nodes whose relative source path starts with one of the prefixes
+#[derive(Debug, Clone, Default)]
+pub struct PathPrefixFilter {
+    prefixes: Vec<String>,
+}
+
+impl PathPrefixFilter {
+    pub fn new(prefixes: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            prefixes: prefixes.into_iter().collect(),
+        }
+    }
+}
+
+impl NodeFilter for PathPrefixFilter {
+    fn matches(&self, node: &FunctionNode) -> bool {
+        let path = node
+            .relative_path
+            .trim_start_matches('/')
+            .trim_start_matches("./");
+        self.prefixes
+            .iter()
+            .any(|prefix| path.starts_with(prefix.trim_start_matches("./")))
+    }
+}
+
+/// Matches nodes whose crate (the package field of the SCIP symbol) starts
+/// with one of the prefixes; this covers external functions too
+#[derive(Debug, Clone, Default)]
+pub struct CratePrefixFilter {
+    prefixes: Vec<String>,
+}
+
+impl CratePrefixFilter {
+    pub fn new(prefixes: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            prefixes: prefixes.into_iter().collect(),
+        }
+    }
+}
+
+impl NodeFilter for CratePrefixFilter {
+    fn matches(&self, node: &FunctionNode) -> bool {
+        let Some(parsed) = parse_symbol(&node.symbol) else {
+            return false;
+        };
+        self.prefixes
+            .iter()
+            .any(|prefix| parsed.package.starts_with(prefix.as_str()))
+    }
+}
+
+/// Matches nodes whose symbol or relative source path matches a regex
+#[derive(Debug, Clone)]
+pub struct RegexFilter {
+    regex: Regex,
+}
+
+impl RegexFilter {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            regex: Regex::new(pattern)?,
+        })
+    }
+}
+
+impl NodeFilter for RegexFilter {
+    fn matches(&self, node: &FunctionNode) -> bool {
+        self.regex.is_match(&node.symbol) || self.regex.is_match(&node.relative_path)
+    }
+}
+
+/// Matches test functions (see [`is_test_function`])
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TestFilter;
+
+impl NodeFilter for TestFilter {
+    fn matches(&self, node: &FunctionNode) -> bool {
+        is_test_function(node)
+    }
+}
+
+/// Matches nodes of one kind, e.g. to skip the type nodes of a graph built
+/// with `CallGraphOptions::with_types`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeKindFilter(pub NodeKind);
+
+impl NodeFilter for NodeKindFilter {
+    fn matches(&self, node: &FunctionNode) -> bool {
+        node.node_kind == self.0
+    }
+}
+
+/// Whether the node is a test function.
+///
+/// A function is a test if it carries a test attribute (`#[test]`,
+/// `#[tokio::test]`, `#[rstest]`, `#[cfg(test)]`), either among its captured
+/// attributes or at the top of its body, or if it lives in a test file: under
+/// a `tests` directory, or in `tests.rs`, `*_test.rs` or `*_tests.rs`.
+pub fn is_test_function(node: &FunctionNode) -> bool {
+    let body_attributes = node
+        .body
+        .iter()
+        .flat_map(|body| body.lines())
+        .map(str::trim)
+        .take_while(|line| line.starts_with("#[") || line.starts_with("///"));
+    let attributes: Vec<&str> = node
+        .attributes
+        .iter()
+        .map(String::as_str)
+        .chain(body_attributes)
+        .collect();
+    if is_test_attribute(&attributes) {
+        return true;
+    }
+
+    let path = Path::new(&node.relative_path);
+    let in_tests_dir = path
+        .parent()
+        .is_some_and(|dir| dir.components().any(|c| c.as_os_str() == "tests"));
+    let file_stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    in_tests_dir
+        || file_stem == "tests"
+        || file_stem.ends_with("_test")
+        || file_stem.ends_with("_tests")
+}
+
+/// The filters applied by an export.
+///
+/// A node is highlighted (drawn as project source) if any highlight filter
+/// matches it, and left out of the export if any skip filter matches it.
+/// The default highlights nothing, skips nothing, sorts the output, draws
+/// every call, shows signatures in tooltips and renders DOT files with
+/// [`RenderBackend::Auto`].
+pub struct NodeFilters {
+    highlight: Vec<Box<dyn NodeFilter>>,
+    skip: Vec<Box<dyn NodeFilter>>,
+    deterministic: bool,
+    render: RenderBackend,
+    aggregate_cluster_edges: bool,
+    tooltips: TooltipContent,
+}
+
+impl Default for NodeFilters {
+    fn default() -> Self {
+        Self {
+            highlight: Vec::new(),
+            skip: Vec::new(),
+            deterministic: true,
+            render: RenderBackend::default(),
+            aggregate_cluster_edges: false,
+            tooltips: TooltipContent::default(),
+        }
+    }
+}
+
+impl NodeFilters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Highlight crates or source paths starting with any of
+    /// `highlight_prefixes`, and skip source paths starting with any of
+    /// `skip_paths` (the `--highlight-prefix` / `--skip-path` CLI options)
+    pub fn from_prefixes(highlight_prefixes: &[String], skip_paths: &[String]) -> Self {
+        let mut filters = Self::new();
+        if !highlight_prefixes.is_empty() {
+            filters = filters
+                .highlight(CratePrefixFilter::new(highlight_prefixes.iter().cloned()))
+                .highlight(PathPrefixFilter::new(highlight_prefixes.iter().cloned()));
+        }
+        if !skip_paths.is_empty() {
+            filters = filters.skip(PathPrefixFilter::new(skip_paths.iter().cloned()));
+        }
+        filters
+    }
+
+    /// Also highlight nodes matching `filter`
+    pub fn highlight(mut self, filter: impl NodeFilter + 'static) -> Self {
+        self.highlight.push(Box::new(filter));
+        self
+    }
+
+    /// Also leave out nodes matching `filter`
+    pub fn skip(mut self, filter: impl NodeFilter + 'static) -> Self {
+        self.skip.push(Box::new(filter));
+        self
+    }
+
+    /// Write nodes and edges sorted by symbol (the default), or in hash map
+    /// order, which changes from run to run but skips the sorting
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Render the images of DOT exports with `backend` (the `--render` CLI
+    /// option)
+    pub fn render(mut self, backend: RenderBackend) -> Self {
+        self.render = backend;
+        self
+    }
+
+    pub fn render_backend(&self) -> RenderBackend {
+        self.render
+    }
+
+    /// In DOT exports with clusters, draw all calls from one cluster to
+    /// another as a single weighted edge, keeping calls within a cluster at
+    /// the function level (the `--aggregate-cluster-edges` CLI option). On
+    /// large graphs this cuts thousands of parallel edges and makes Graphviz
+    /// much faster.
+    pub fn aggregate_cluster_edges(mut self, aggregate: bool) -> Self {
+        self.aggregate_cluster_edges = aggregate;
+        self
+    }
+
+    pub fn aggregates_cluster_edges(&self) -> bool {
+        self.aggregate_cluster_edges
+    }
+
+    /// Show `content` of each function's body in DOT tooltips and the
+    /// bodies file of D3 exports (the `--tooltips` CLI option)
+    pub fn tooltips(mut self, content: TooltipContent) -> Self {
+        self.tooltips = content;
+        self
+    }
+
+    pub fn tooltip_content(&self) -> TooltipContent {
+        self.tooltips
+    }
+
+    /// `nodes` sorted by symbol if deterministic, else as given
+    pub fn order_nodes<'a>(
+        &self,
+        nodes: impl IntoIterator<Item = &'a FunctionNode>,
+    ) -> Vec<&'a FunctionNode> {
+        let mut nodes: Vec<&FunctionNode> = nodes.into_iter().collect();
+        if self.deterministic {
+            nodes.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        }
+        nodes
+    }
+
+    /// `items` (symbols, edges) sorted if deterministic, else as given
+    pub fn order<'a, T: Ord + ?Sized + 'a>(
+        &self,
+        items: impl IntoIterator<Item = &'a T>,
+    ) -> Vec<&'a T> {
+        let mut items: Vec<&T> = items.into_iter().collect();
+        if self.deterministic {
+            items.sort();
+        }
+        items
+    }
+
+    /// Whether any highlight filters are set
+    pub fn has_highlights(&self) -> bool {
+        !self.highlight.is_empty()
+    }
+
+    pub fn is_highlighted(&self, node: &FunctionNode) -> bool {
+        self.highlight.iter().any(|filter| filter.matches(node))
+    }
+
+    pub fn is_skipped(&self, node: &FunctionNode) -> bool {
+        self.skip.iter().any(|filter| filter.matches(node))
+    }
+
+    /// A copy of the graph without the skipped nodes and their edges, with
+    /// broken caller/callee links repaired (see [`repaired`])
+    pub fn apply(&self, call_graph: &CallGraph) -> CallGraph {
+        let mut filtered = repaired(call_graph).into_owned();
+        let skipped: Vec<String> = call_graph
+            .values()
+            .filter(|node| self.is_skipped(node))
+            .map(|node| node.symbol.clone())
+            .collect();
+        for symbol in &skipped {
+            filtered.remove_node(symbol);
+        }
+        filtered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn node(symbol: &str, relative_path: &str) -> FunctionNode {
+        test_support::node(symbol, &[]).in_file(relative_path)
+    }
+
+    #[test]
+    fn test_path_and_crate_prefix_filters() {
+        let local = node(
+            "rust-analyzer cargo libsignal-protocol 0.1.0 session/process().",
+            "rust/protocol/src/session.rs",
+        );
+        let dependency = node(
+            "rust-analyzer cargo curve25519-dalek 4.1.0 scalar/Scalar#invert().",
+            "",
+        );
+
+        let paths = PathPrefixFilter::new(["./rust/protocol".to_string()]);
+        assert!(paths.matches(&local));
+        assert!(!paths.matches(&dependency));
+
+        let crates = CratePrefixFilter::new(["libsignal".to_string(), "zkgroup".to_string()]);
+        assert!(crates.matches(&local));
+        assert!(!crates.matches(&dependency));
+    }
+
+    #[test]
+    fn test_regex_filter_and_closures() {
+        let lemma = node(
+            "rust-analyzer cargo demo 0.1.0 lemmas/lemma_add().",
+            "src/lemmas.rs",
+        );
+        let regex = RegexFilter::new(r"/lemma_\w+\(\)").unwrap();
+        assert!(regex.matches(&lemma));
+        assert!(RegexFilter::new("(").is_err());
+
+        let closure = |node: &FunctionNode| node.relative_path.ends_with("lemmas.rs");
+        assert!(closure.matches(&lemma));
+    }
+
+    #[test]
+    fn test_is_test_function() {
+        let mut unit_test = node("m/t().", "src/m.rs");
+        unit_test.attributes = vec!["#[test]".to_string()];
+        assert!(is_test_function(&unit_test));
+
+        let mut async_test = node("m/t().", "src/m.rs");
+        async_test.body = Some("#[tokio::test]\nasync fn t() {}".to_string());
+        assert!(is_test_function(&async_test));
+
+        let mut cfg_test = node("m/t().", "src/m.rs");
+        cfg_test.attributes = vec!["#[cfg( test )]".to_string()];
+        assert!(TestFilter.matches(&cfg_test));
+
+        assert!(is_test_function(&node("t().", "tests/integration.rs")));
+        assert!(is_test_function(&node("t().", "src/field/tests.rs")));
+        assert!(is_test_function(&node("t().", "src/field_tests.rs")));
+
+        let mut production = node("m/f().", "src/testing_utils.rs");
+        production.attributes = vec!["#[verifier::external_body]".to_string()];
+        production.body = Some("fn f() {\n    #[test]\n}".to_string());
+        assert!(!is_test_function(&production));
+        assert!(!is_test_function(&node("m/f().", "tests.md/src/m.rs")));
+    }
+
+    #[test]
+    fn test_node_filters_apply_removes_skipped_nodes() {
+        let mut main = node("main", "src/main.rs");
+        let mut bench = node("bench", "benches/bench.rs");
+        main.callers.insert("bench".to_string());
+        bench.callees.insert("main".to_string());
+        let call_graph: CallGraph = [main, bench].into_iter().collect();
+
+        let filters = NodeFilters::from_prefixes(&["src".to_string()], &["benches".to_string()]);
+        let filtered = filters.apply(&call_graph);
+
+        assert_eq!(filtered.len(), 1);
+        assert!(filtered["main"].callers.is_empty());
+        assert!(filters.is_highlighted(&filtered["main"]));
+        assert!(!NodeFilters::default().has_highlights());
+    }
+}
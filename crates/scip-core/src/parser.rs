@@ -1,12 +1,77 @@
 //! SCIP JSON parsing utilities.
 //!
 //! This module provides functions to parse SCIP (Source Code Intelligence Protocol)
-//! JSON index files into structured Rust types.
+//! JSON index files into structured Rust types, and to read back atoms JSON
+//! written by `write_call_graph_as_atoms_json`, either as raw atoms or as a
+//! call graph. [`load_call_graph`] builds a call graph from whichever of
+//! these (or a binary SCIP index) a file holds.
+//!
+//! [`parse_scip_json_with_mode`] in [`ParseMode::Lenient`] keeps going past
+//! documents, symbols and occurrences that don't match the schema, dropping
+//! them with a [`ParseWarning`] each instead of failing the whole index.
 
-use crate::types::ScipIndex;
+use crate::call_graph::{assign_edge_kinds, build_call_graph};
+use crate::error::{Error, Result};
+use crate::scip_reader::parse_scip_protobuf;
+use crate::types::{
+    Atom, AtomsFile, CallGraph, CallLocation, CalleeOccurrence, Document, FormatVersion,
+    FunctionNode, Metadata, NodeKind, Occurrence, ScipIndex, Symbol,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
+/// How [`parse_scip_json_with_mode`] handles parts of an index that don't
+/// match the schema
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail on the first malformed document, symbol or occurrence
+    #[default]
+    Strict,
+    /// Drop malformed symbols, occurrences and documents (and fall back to
+    /// default metadata), with a warning for each document affected
+    Lenient,
+}
+
+/// What [`ParseMode::Lenient`] parsing dropped for a [`ParseWarning`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseWarningKind {
+    /// The metadata didn't parse and was replaced by defaults
+    MalformedMetadata,
+    /// Some symbols or occurrences of a document were dropped
+    PartialDocument,
+    /// A whole document was dropped
+    SkippedDocument,
+}
+
+/// A part of a SCIP index dropped by [`ParseMode::Lenient`] parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    /// Relative path of the document (`documents[<index>]` if it has
+    /// none), or `None` for the metadata
+    pub document: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.document {
+            Some(document) => write!(f, "{}: {}", document, self.message),
+            None => write!(f, "metadata: {}", self.message),
+        }
+    }
+}
+
+/// A parsed SCIP index with the warnings of lenient parsing
+#[derive(Debug)]
+pub struct ParsedScipIndex {
+    pub index: ScipIndex,
+    pub warnings: Vec<ParseWarning>,
+}
+
 /// Parse a SCIP JSON file into a ScipIndex structure.
 ///
 /// # Arguments
@@ -23,11 +88,241 @@ use std::path::Path;
 /// let scip_data = parse_scip_json("index.scip.json")?;
 /// println!("Project root: {}", scip_data.metadata.project_root);
 /// ```
-pub fn parse_scip_json(file_path: &str) -> Result<ScipIndex, Box<dyn std::error::Error>> {
+pub fn parse_scip_json(file_path: &str) -> Result<ScipIndex> {
     let path = Path::new(file_path);
     let contents = fs::read_to_string(path)?;
-    let index: ScipIndex = serde_json::from_str(&contents)?;
-    Ok(index)
+    serde_json::from_str(&contents).map_err(Error::scip_parse)
+}
+
+/// Parse a SCIP JSON file, with lenient handling of schema drift if asked
+///
+/// [`ParseMode::Strict`] behaves like [`parse_scip_json`] and never
+/// returns warnings. In [`ParseMode::Lenient`] only unreadable files and
+/// files without a `documents` array are errors.
+pub fn parse_scip_json_with_mode(file_path: &str, mode: ParseMode) -> Result<ParsedScipIndex> {
+    let contents = fs::read_to_string(Path::new(file_path))?;
+    parse_scip_json_str_with_mode(&contents, mode)
+}
+
+/// Parse SCIP JSON from a string (see [`parse_scip_json_with_mode`])
+pub fn parse_scip_json_str_with_mode(contents: &str, mode: ParseMode) -> Result<ParsedScipIndex> {
+    if mode == ParseMode::Strict {
+        return Ok(ParsedScipIndex {
+            index: serde_json::from_str(contents).map_err(Error::scip_parse)?,
+            warnings: Vec::new(),
+        });
+    }
+
+    let mut value: Value = serde_json::from_str(contents).map_err(Error::scip_parse)?;
+    let Some(documents) = value.get_mut("documents").and_then(Value::as_array_mut) else {
+        return Err(Error::scip_parse("no documents array"));
+    };
+    let documents = std::mem::take(documents);
+
+    let mut warnings = Vec::new();
+    let metadata = match serde_json::from_value::<Metadata>(value["metadata"].take()) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            warnings.push(ParseWarning {
+                kind: ParseWarningKind::MalformedMetadata,
+                document: None,
+                message: format!("malformed, using defaults ({})", e),
+            });
+            Metadata::default()
+        }
+    };
+
+    let documents = documents
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, document)| {
+            let name = document
+                .get("relative_path")
+                .and_then(Value::as_str)
+                .map_or_else(|| format!("documents[{}]", index), str::to_string);
+            match parse_document_lenient(document) {
+                Ok((document, None)) => Some(document),
+                Ok((document, Some(message))) => {
+                    warnings.push(ParseWarning {
+                        kind: ParseWarningKind::PartialDocument,
+                        document: Some(name),
+                        message,
+                    });
+                    Some(document)
+                }
+                Err(e) => {
+                    warnings.push(ParseWarning {
+                        kind: ParseWarningKind::SkippedDocument,
+                        document: Some(name),
+                        message: format!("skipped ({})", e),
+                    });
+                    None
+                }
+            }
+        })
+        .collect();
+
+    Ok(ParsedScipIndex {
+        index: ScipIndex {
+            metadata,
+            documents,
+        },
+        warnings,
+    })
+}
+
+/// Parse a document, dropping the symbols and occurrences that don't parse;
+/// returns what was dropped as a message
+fn parse_document_lenient(
+    mut document: Value,
+) -> Result<(Document, Option<String>), serde_json::Error> {
+    let first_error = match serde_json::from_value::<Document>(document.clone()) {
+        Ok(parsed) => return Ok((parsed, None)),
+        Err(e) => e,
+    };
+
+    let occurrences = retain_parsable::<Occurrence>(&mut document, "occurrences");
+    let symbols = retain_parsable::<Symbol>(&mut document, "symbols");
+    if occurrences == 0 && symbols == 0 {
+        return Err(first_error);
+    }
+    let parsed = serde_json::from_value::<Document>(document)?;
+    Ok((
+        parsed,
+        Some(format!(
+            "dropped {} malformed occurrence(s) and {} symbol(s) (first error: {})",
+            occurrences, symbols, first_error
+        )),
+    ))
+}
+
+/// Remove the elements of `document[field]` that don't parse as `T`,
+/// returning how many were removed
+fn retain_parsable<T: DeserializeOwned>(document: &mut Value, field: &str) -> usize {
+    let Some(items) = document.get_mut(field).and_then(Value::as_array_mut) else {
+        return 0;
+    };
+    let before = items.len();
+    items.retain(|item| serde_json::from_value::<T>(item.clone()).is_ok());
+    before - items.len()
+}
+
+/// Parse an atoms JSON file in any supported format version.
+///
+/// Accepts both the legacy v1 format (a bare array of atoms) and the
+/// versioned envelope (`{"format_version": 2, "atoms": [...]}`). Legacy files
+/// are returned with `format_version` set to `FormatVersion::V1`.
+///
+/// # Example
+/// ```ignore
+/// use scip_core::parser::parse_atoms_json;
+///
+/// let atoms_file = parse_atoms_json("atoms.json")?;
+/// println!("{} atoms (v{})", atoms_file.atoms.len(), u32::from(atoms_file.format_version));
+/// ```
+pub fn parse_atoms_json(file_path: &str) -> Result<AtomsFile> {
+    let contents = fs::read_to_string(Path::new(file_path))?;
+    parse_atoms_json_str(&contents)
+}
+
+/// Parse atoms JSON from a string (see [`parse_atoms_json`])
+pub fn parse_atoms_json_str(contents: &str) -> Result<AtomsFile> {
+    let value: serde_json::Value = serde_json::from_str(contents).map_err(Error::atoms_parse)?;
+    if value.is_array() {
+        return Ok(AtomsFile {
+            format_version: FormatVersion::V1,
+            atoms: serde_json::from_value(value).map_err(Error::atoms_parse)?,
+        });
+    }
+    serde_json::from_value(value).map_err(Error::atoms_parse)
+}
+
+/// Load atoms JSON back into a call graph.
+///
+/// This is the inverse of `write_call_graph_as_atoms_json`: nodes are keyed
+/// by atom identifier, callees come from `deps` and callers are recomputed.
+/// Source ranges and call lines are not stored in atoms, so `range` is empty
+/// and call occurrences report line 0 in the function body.
+///
+/// # Example
+/// ```ignore
+/// use scip_core::parser::read_atoms_json;
+///
+/// let call_graph = read_atoms_json("atoms.json")?;
+/// println!("{} functions", call_graph.len());
+/// ```
+pub fn read_atoms_json(file_path: &str) -> Result<CallGraph> {
+    Ok(atoms_to_call_graph(parse_atoms_json(file_path)?.atoms))
+}
+
+/// Load a call graph from atoms JSON, SCIP JSON or a binary `.scip` index.
+///
+/// Binary indexes are recognized by extension, SCIP JSON by its
+/// `documents` field; anything else is read as atoms.
+pub fn load_call_graph(file_path: &str) -> Result<CallGraph> {
+    if file_path.ends_with(".scip") {
+        return Ok(build_call_graph(&parse_scip_protobuf(file_path)?));
+    }
+
+    let contents = fs::read_to_string(Path::new(file_path))?;
+    let value: serde_json::Value = serde_json::from_str(&contents).map_err(Error::atoms_parse)?;
+    if value.get("documents").is_some() {
+        let scip_data: ScipIndex = serde_json::from_value(value).map_err(Error::scip_parse)?;
+        return Ok(build_call_graph(&scip_data));
+    }
+
+    drop(value);
+    Ok(atoms_to_call_graph(parse_atoms_json_str(&contents)?.atoms))
+}
+
+/// Rebuild call graph nodes from atoms, keyed by identifier.
+///
+/// Callers are derived from the dependencies. Dependencies on identifiers
+/// that are not among the atoms are kept as one-sided callees, matching how
+/// external calls are dropped on export.
+pub fn atoms_to_call_graph(atoms: Vec<Atom>) -> CallGraph {
+    let mut call_graph: CallGraph = atoms
+        .into_iter()
+        .map(|atom| {
+            let callee_occurrences = atom
+                .deps
+                .iter()
+                .map(|dep| CalleeOccurrence {
+                    symbol: dep.clone(),
+                    line: 0,
+                    column: None,
+                    location: Some(CallLocation::Inner),
+                    edge_kind: None,
+                })
+                .collect();
+            let node_kind = if atom.statement_type == NodeKind::Type.as_str() {
+                NodeKind::Type
+            } else {
+                NodeKind::Function
+            };
+            FunctionNode {
+                symbol: atom.identifier,
+                display_name: atom.display_name,
+                file_path: atom.full_path,
+                relative_path: atom.relative_path,
+                callees: atom.deps.into_iter().collect(),
+                callee_occurrences,
+                body: Some(atom.body),
+                doc: atom.doc,
+                attributes: atom.attributes,
+                start_line: atom.start_line,
+                end_line: atom.end_line,
+                requires_text: atom.requires_text,
+                ensures_text: atom.ensures_text,
+                signature: atom.signature.map(|signature| signature.signature),
+                node_kind,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    assign_edge_kinds(call_graph.nodes_mut());
+    call_graph
 }
 
 /// Extract display name from a SCIP symbol string.
@@ -105,6 +400,141 @@ pub fn extract_path_info_from_symbol(symbol: &str) -> (String, String, String) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
+
+    // ==========================================================================
+    // parse_atoms_json tests
+    // ==========================================================================
+
+    const ATOM_JSON: &str = r#"{"identifier":"m::f","statement_type":"function","deps":["m::g"],"body":"fn f() {}","display_name":"f","full_path":"/p/src/m.rs","relative_path":"src/m.rs","file_name":"m.rs","parent_folder":"src"}"#;
+
+    #[test]
+    fn test_parse_atoms_json_v1_array() {
+        let atoms_file = parse_atoms_json_str(&format!("[{ATOM_JSON}]")).unwrap();
+
+        assert_eq!(atoms_file.format_version, FormatVersion::V1);
+        assert_eq!(atoms_file.atoms.len(), 1);
+        assert_eq!(atoms_file.atoms[0].deps, vec!["m::g"]);
+    }
+
+    #[test]
+    fn test_parse_atoms_json_v2_envelope() {
+        let json = format!(r#"{{"format_version":2,"atoms":[{ATOM_JSON}]}}"#);
+        let atoms_file = parse_atoms_json_str(&json).unwrap();
+
+        assert_eq!(atoms_file.format_version, FormatVersion::V2);
+        assert_eq!(atoms_file.atoms[0].identifier, "m::f");
+    }
+
+    #[test]
+    fn test_parse_atoms_json_unknown_version() {
+        let err = parse_atoms_json_str(r#"{"format_version":99,"atoms":[]}"#).unwrap_err();
+        assert!(err.to_string().contains("format_version"));
+    }
+
+    #[test]
+    fn test_parse_scip_json_modes() {
+        // No signature_documentation, tool_info or position encodings; one
+        // occurrence without a range and one document without a path
+        let json = r#"{
+            "metadata": {"project_root": "file:///p"},
+            "documents": [
+                {"relative_path": "src/lib.rs",
+                 "occurrences": [{"range": [1, 4, 7], "symbol": "f()."}, {"symbol": "g()."}],
+                 "symbols": [{"symbol": "f().", "kind": 17, "display_name": "f"}]},
+                {"occurrences": []},
+                {"relative_path": "src/main.rs", "occurrences": []}
+            ]
+        }"#;
+        assert!(parse_scip_json_str_with_mode(json, ParseMode::Strict).is_err());
+
+        let parsed = parse_scip_json_str_with_mode(json, ParseMode::Lenient).unwrap();
+        let paths: Vec<&str> = parsed
+            .index
+            .documents
+            .iter()
+            .map(|d| d.relative_path.as_str())
+            .collect();
+        assert_eq!(paths, ["src/lib.rs", "src/main.rs"]);
+        assert_eq!(parsed.index.documents[0].occurrences.len(), 1);
+        assert_eq!(parsed.index.documents[0].symbols[0].kind, 17);
+        assert_eq!(parsed.warnings.len(), 2);
+        assert!(parsed.warnings[0]
+            .to_string()
+            .starts_with("src/lib.rs: dropped 1 malformed occurrence(s) and 0 symbol(s)"));
+        assert_eq!(parsed.warnings[0].kind, ParseWarningKind::PartialDocument);
+        assert_eq!(parsed.warnings[1].document.as_deref(), Some("documents[1]"));
+        assert_eq!(parsed.warnings[1].kind, ParseWarningKind::SkippedDocument);
+
+        // Missing optional fields alone parse strictly
+        let valid = r#"{"metadata": {"project_root": "file:///p"},
+            "documents": [{"relative_path": "a.rs", "occurrences": [],
+                "symbols": [{"symbol": "f().", "kind": 17, "display_name": "f"}]}]}"#;
+        let parsed = parse_scip_json_str_with_mode(valid, ParseMode::Strict).unwrap();
+        assert!(parsed.warnings.is_empty());
+        assert!(parse_scip_json_str_with_mode("[]", ParseMode::Lenient).is_err());
+    }
+
+    // ==========================================================================
+    // read_atoms_json tests
+    // ==========================================================================
+
+    #[test]
+    fn test_read_atoms_json_round_trip() {
+        use crate::export_d3::write_call_graph_as_atoms_json;
+        use crate::types::EdgeKind;
+
+        let atoms: Vec<Atom> = [
+            ("m::main", "fn main() { helper(); }", vec!["m::helper"]),
+            ("m::helper", "fn helper() { lemma(); }", vec!["m::lemma"]),
+            ("m::lemma", "proof fn lemma() {}", vec![]),
+        ]
+        .into_iter()
+        .map(|(identifier, body, deps)| Atom {
+            identifier: identifier.to_string(),
+            statement_type: "function".to_string(),
+            deps: deps.into_iter().map(String::from).collect(),
+            body: body.to_string(),
+            display_name: identifier.trim_start_matches("m::").to_string(),
+            full_path: "/p/src/m.rs".to_string(),
+            relative_path: "src/m.rs".to_string(),
+            file_name: "m.rs".to_string(),
+            parent_folder: "src".to_string(),
+            doc: None,
+            attributes: Vec::new(),
+            start_line: None,
+            end_line: None,
+            requires_text: Vec::new(),
+            ensures_text: Vec::new(),
+            signature: None,
+            graph_metrics: None,
+            spec_support: Vec::new(),
+        })
+        .collect();
+        let call_graph = atoms_to_call_graph(atoms);
+
+        let helper = &call_graph["m::helper"];
+        assert_eq!(helper.callers, HashSet::from(["m::main".to_string()]));
+        assert_eq!(helper.callees, HashSet::from(["m::lemma".to_string()]));
+        assert_eq!(
+            helper.callee_occurrences[0].edge_kind,
+            Some(EdgeKind::ExecToProof)
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("atoms.json");
+        write_call_graph_as_atoms_json(&call_graph, &path).unwrap();
+        let reloaded = read_atoms_json(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(reloaded.len(), call_graph.len());
+        for (identifier, node) in &call_graph {
+            let other = &reloaded[identifier];
+            assert_eq!(other.callers, node.callers);
+            assert_eq!(other.callees, node.callees);
+            assert_eq!(other.body, node.body);
+            assert_eq!(other.relative_path, node.relative_path);
+        }
+    }
 
     // ==========================================================================
     // extract_display_name_from_symbol tests
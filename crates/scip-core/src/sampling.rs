@@ -0,0 +1,216 @@
+//! Budget-aware sampling of call graphs too large to draw
+//!
+//! Past a few hundred nodes, Graphviz and D3 layouts of a call graph are
+//! unreadable. [`sample_call_graph`] shrinks a graph to a node budget: the
+//! pinned functions (e.g. the ones a subgraph was asked for) are always
+//! kept, the budget is filled with the functions closest to them, most
+//! central (by PageRank) first, and everything else is collapsed into one
+//! placeholder node per module, labeled `… 57 more in module field`.
+//! Calls into and out of the collapsed functions are redirected to the
+//! placeholders, so the shape of the graph stays visible.
+
+use crate::analysis::graph_metrics::pagerank;
+use crate::call_graph::{external_crate_name, merge_nodes};
+use crate::types::{CallGraph, FunctionNode};
+use crate::verification::module_path;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// Symbol prefix of the placeholder nodes
+pub const PLACEHOLDER_PREFIX: &str = "sampled:";
+
+/// Whether the node stands in for functions left out by
+/// [`sample_call_graph`]
+pub fn is_sample_placeholder(node: &FunctionNode) -> bool {
+    node.symbol.starts_with(PLACEHOLDER_PREFIX)
+}
+
+/// Module (or external crate) whose placeholder a left out function goes to
+fn group_of(node: &FunctionNode) -> String {
+    if let Some(crate_name) = external_crate_name(node) {
+        return format!("external crate {}", crate_name);
+    }
+    let path = node.relative_path.trim_start_matches("./");
+    let module = match module_path(path) {
+        module if module.is_empty() => "crate root".to_string(),
+        module => format!("module {}", module),
+    };
+    match path.rsplit_once("src/") {
+        Some((prefix, _)) if !prefix.is_empty() => {
+            format!("{} of {}", module, prefix.trim_end_matches('/'))
+        }
+        _ => module,
+    }
+}
+
+/// Number of calls (in either direction) from the nearest pinned function
+fn distances(call_graph: &CallGraph, pinned: &HashSet<String>) -> HashMap<String, usize> {
+    let mut distance: HashMap<String, usize> = pinned
+        .iter()
+        .filter(|symbol| call_graph.contains_key(*symbol))
+        .map(|symbol| (symbol.clone(), 0))
+        .collect();
+    let mut queue: VecDeque<String> = distance.keys().cloned().collect();
+    while let Some(symbol) = queue.pop_front() {
+        let next = distance[&symbol] + 1;
+        let node = &call_graph[symbol.as_str()];
+        for neighbor in node.callees.iter().chain(&node.callers) {
+            if call_graph.contains_key(neighbor) && !distance.contains_key(neighbor) {
+                distance.insert(neighbor.clone(), next);
+                queue.push_back(neighbor.clone());
+            }
+        }
+    }
+    distance
+}
+
+/// Shrink `call_graph` to at most `max_nodes` nodes, placeholders included.
+///
+/// The `pinned` functions are kept even if they alone exceed the budget.
+/// The other functions are taken by increasing call distance from the
+/// pinned ones, then by decreasing PageRank, for as long as they and the
+/// placeholders for the rest fit. Each placeholder has the symbol
+/// `sampled:<module>`, the location of its first member and the member
+/// names as its doc comment. Graphs within the budget are returned as is.
+pub fn sample_call_graph(
+    call_graph: &CallGraph,
+    pinned: &HashSet<String>,
+    max_nodes: usize,
+) -> CallGraph {
+    if call_graph.len() <= max_nodes {
+        return call_graph.clone();
+    }
+    let distance = distances(call_graph, pinned);
+    let rank = pagerank(call_graph);
+
+    let mut candidates: Vec<&FunctionNode> = call_graph
+        .values()
+        .filter(|node| !pinned.contains(&node.symbol))
+        .collect();
+    candidates.sort_by(|a, b| {
+        let distance_of =
+            |node: &FunctionNode| distance.get(&node.symbol).copied().unwrap_or(usize::MAX);
+        // Type nodes have no PageRank
+        let rank_of = |node: &FunctionNode| rank.get(&node.symbol).copied().unwrap_or(0.0);
+        distance_of(a)
+            .cmp(&distance_of(b))
+            .then_with(|| rank_of(b).total_cmp(&rank_of(a)))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+
+    let mut left_out: HashMap<String, usize> = HashMap::new();
+    for node in &candidates {
+        *left_out.entry(group_of(node)).or_default() += 1;
+    }
+    let mut kept = call_graph.len() - candidates.len();
+    let mut kept_symbols: HashSet<&str> = HashSet::new();
+    for node in &candidates {
+        let group = group_of(node);
+        // Keeping the last function of a group saves its placeholder
+        let last_of_group = left_out[&group] == 1;
+        let total = kept + 1 + left_out.len() - usize::from(last_of_group);
+        if total > max_nodes {
+            continue;
+        }
+        kept += 1;
+        kept_symbols.insert(&node.symbol);
+        if last_of_group {
+            left_out.remove(&group);
+        } else if let Some(count) = left_out.get_mut(&group) {
+            *count -= 1;
+        }
+    }
+
+    let mut groups: BTreeMap<String, Vec<&FunctionNode>> = BTreeMap::new();
+    for node in candidates {
+        if !kept_symbols.contains(node.symbol.as_str()) {
+            groups.entry(group_of(node)).or_default().push(node);
+        }
+    }
+    let mut merged_into: HashMap<&str, String> = HashMap::new();
+    let mut placeholders = Vec::new();
+    for (group, mut members) in groups {
+        members.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        let symbol = format!("{}{}", PLACEHOLDER_PREFIX, group);
+        for member in &members {
+            merged_into.insert(member.symbol.as_str(), symbol.clone());
+        }
+        let names: Vec<&str> = members
+            .iter()
+            .map(|member| member.display_name.as_str())
+            .collect();
+        placeholders.push(FunctionNode {
+            symbol,
+            display_name: format!("… {} more in {}", members.len(), group),
+            file_path: members[0].file_path.clone(),
+            relative_path: members[0].relative_path.clone(),
+            doc: Some(format!("Left out: {}", names.join(", "))),
+            ..Default::default()
+        });
+    }
+    merge_nodes(call_graph, &merged_into, placeholders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn node(symbol: &str, path: &str, callees: &[&str]) -> FunctionNode {
+        test_support::node(symbol, callees).in_file(path)
+    }
+
+    #[test]
+    fn test_sample_call_graph() {
+        // main -> a -> {f1, f2, f3} in src/field.rs, main -> b -> s1 in src/scalar.rs
+        let graph: CallGraph = [
+            node("main", "src/main.rs", &["a", "b"]),
+            node("a", "src/lib.rs", &["f1", "f2", "f3"]),
+            node("b", "src/lib.rs", &["s1"]),
+            node("f1", "src/field.rs", &[]),
+            node("f2", "src/field.rs", &[]),
+            node("f3", "src/field.rs", &[]),
+            node("s1", "src/scalar.rs", &[]),
+        ]
+        .into_iter()
+        .collect();
+        let pinned: HashSet<String> = ["main".to_string()].into();
+
+        assert_eq!(sample_call_graph(&graph, &pinned, 7).len(), 7);
+
+        let sampled = sample_call_graph(&graph, &pinned, 5);
+        assert_eq!(sampled.len(), 5);
+        for kept in ["main", "a", "b", "s1"] {
+            assert!(sampled.contains_key(kept), "{kept} should be kept");
+        }
+        let placeholder = &sampled["sampled:module field"];
+        assert!(is_sample_placeholder(placeholder));
+        assert_eq!(placeholder.display_name, "… 3 more in module field");
+        assert_eq!(placeholder.doc.as_deref(), Some("Left out: f1, f2, f3"));
+        assert!(sampled["a"].callees.contains("sampled:module field"));
+        assert!(placeholder.callers.contains("a"));
+
+        // Pinned functions are kept even past the budget
+        let sampled = sample_call_graph(&graph, &pinned, 1);
+        assert!(sampled.contains_key("main"));
+        assert!(sampled
+            .keys()
+            .all(|symbol| symbol == "main" || symbol.starts_with("sampled:")));
+    }
+
+    #[test]
+    fn test_sample_call_graph_with_type_nodes() {
+        let graph: CallGraph = [
+            node("main", "src/main.rs", &["a", "Point"]),
+            node("a", "src/lib.rs", &["b"]),
+            node("b", "src/lib.rs", &[]),
+            node("Point", "src/point.rs", &[]).as_type(),
+        ]
+        .into_iter()
+        .collect();
+        let pinned: HashSet<String> = ["main".to_string()].into();
+
+        let sampled = sample_call_graph(&graph, &pinned, 3);
+        assert_eq!(sampled.len(), 3);
+        assert!(sampled.contains_key("main"));
+    }
+}
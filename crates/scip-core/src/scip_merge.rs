@@ -0,0 +1,272 @@
+//! Merging the SCIP indices of separately indexed repositories
+//!
+//! A system split over several repositories is indexed one repository at a
+//! time, so calls into the other repositories end up as external
+//! placeholder nodes. [`merge_scip_indices`] combines the indices into one
+//! whose call graph links them:
+//!
+//! - documents are re-rooted on the common ancestor of the project roots,
+//!   so `src/lib.rs` of two repositories stays apart (as `app/src/lib.rs`
+//!   and `lib/src/lib.rs`);
+//! - symbols an index uses without defining are unified with the
+//!   definitions of the other indices. They only need to match on the
+//!   package and descriptors: the indexer (rust-analyzer or verus-analyzer)
+//!   and the package version often differ, e.g. when one repository depends
+//!   on a published or git version of the other.
+//!
+//! A symbol whose package and descriptors are defined under several
+//! versions is ambiguous and left as it is.
+
+use crate::symbol_scheme::split_header;
+use crate::types::{Metadata, ScipIndex};
+use log::{info, warn};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Manager, package and descriptors of a global symbol
+type UnversionedSymbol = (String, String, String);
+
+fn unversioned(symbol: &str) -> Option<UnversionedSymbol> {
+    let ([_, manager, package, _], descriptors) = split_header(symbol)?;
+    Some((manager, package, descriptors.to_string()))
+}
+
+/// Symbols with a definition occurrence in the index
+fn defined_symbols(index: &ScipIndex) -> HashSet<String> {
+    index
+        .documents
+        .iter()
+        .flat_map(|doc| &doc.occurrences)
+        .filter(|occurrence| occurrence.symbol_roles.unwrap_or(0) & 1 == 1)
+        .map(|occurrence| occurrence.symbol.clone())
+        .collect()
+}
+
+/// Common ancestor of the project roots, and each root's path below it
+/// (empty for the ancestor itself)
+fn common_root(roots: &[&str]) -> (String, Vec<String>) {
+    let components: Vec<Vec<&str>> = roots
+        .iter()
+        .map(|root| root.trim_end_matches('/').split('/').collect())
+        .collect();
+    let common = (0..)
+        .take_while(|&i| {
+            let first = components[0].get(i);
+            first.is_some() && components.iter().all(|parts| parts.get(i) == first)
+        })
+        .count();
+    let below = components
+        .iter()
+        .map(|parts| parts[common..].join("/"))
+        .collect();
+    (components[0][..common].join("/"), below)
+}
+
+/// Renames unifying the symbols each index uses without defining with the
+/// definitions of the other indices
+fn cross_index_renames(indices: &[ScipIndex]) -> Vec<HashMap<String, String>> {
+    let defined: Vec<HashSet<String>> = indices.iter().map(defined_symbols).collect();
+    let mut definitions: HashMap<UnversionedSymbol, BTreeSet<&str>> = HashMap::new();
+    for symbol in defined.iter().flatten() {
+        if let Some(key) = unversioned(symbol) {
+            definitions.entry(key).or_default().insert(symbol);
+        }
+    }
+
+    indices
+        .iter()
+        .zip(&defined)
+        .map(|(index, own)| {
+            let used: BTreeSet<&String> = index
+                .documents
+                .iter()
+                .flat_map(|doc| &doc.occurrences)
+                .map(|occurrence| &occurrence.symbol)
+                .filter(|symbol| !own.contains(*symbol))
+                .collect();
+            let mut renames = HashMap::new();
+            for symbol in used {
+                let Some(targets) = unversioned(symbol).and_then(|key| definitions.get(&key))
+                else {
+                    continue;
+                };
+                match targets.iter().collect::<Vec<_>>()[..] {
+                    [target] if target != symbol => {
+                        renames.insert(symbol.clone(), target.to_string());
+                    }
+                    [_] => {}
+                    _ => warn!(
+                        "{} is defined in several indices ({}), not unifying it",
+                        symbol,
+                        targets.iter().copied().collect::<Vec<_>>().join(", ")
+                    ),
+                }
+            }
+            renames
+        })
+        .collect()
+}
+
+/// Merge SCIP indices (e.g. of different repositories) into one, unifying
+/// cross-index symbols (see the module docs)
+///
+/// Metadata other than the project root comes from the first index. A
+/// document whose re-rooted path is already taken (the same repository
+/// indexed twice) is dropped with a warning.
+pub fn merge_scip_indices(indices: Vec<ScipIndex>) -> ScipIndex {
+    if indices.is_empty() {
+        return ScipIndex {
+            metadata: Metadata::default(),
+            documents: Vec::new(),
+        };
+    }
+
+    let renames = cross_index_renames(&indices);
+    let unified: usize = renames.iter().map(HashMap::len).sum();
+    let roots: Vec<&str> = indices
+        .iter()
+        .map(|index| index.metadata.project_root.as_str())
+        .collect();
+    let (project_root, below_root) = common_root(&roots);
+
+    let mut indices = indices.into_iter();
+    let first = indices.next().expect("checked above");
+    let mut merged = ScipIndex {
+        metadata: Metadata {
+            project_root,
+            ..first.metadata
+        },
+        documents: Vec::new(),
+    };
+    let mut paths: HashSet<String> = HashSet::new();
+    let all = std::iter::once(first.documents).chain(indices.map(|index| index.documents));
+    for ((documents, renames), below) in all.zip(&renames).zip(&below_root) {
+        for mut doc in documents {
+            let relative_path = doc.relative_path.trim_start_matches('/');
+            doc.relative_path = if below.is_empty() {
+                relative_path.to_string()
+            } else {
+                format!("{below}/{relative_path}")
+            };
+            if !paths.insert(doc.relative_path.clone()) {
+                warn!(
+                    "{} is in several SCIP indices, keeping the first",
+                    doc.relative_path
+                );
+                continue;
+            }
+
+            let rename = |symbol: &mut String| {
+                if let Some(target) = renames.get(symbol.as_str()) {
+                    symbol.clone_from(target);
+                }
+            };
+            for occurrence in &mut doc.occurrences {
+                rename(&mut occurrence.symbol);
+            }
+            for symbol in &mut doc.symbols {
+                rename(&mut symbol.symbol);
+                if let Some(enclosing) = &mut symbol.enclosing_symbol {
+                    rename(enclosing);
+                }
+            }
+            merged.documents.push(doc);
+        }
+    }
+
+    info!(
+        "Merged {} SCIP indices ({} documents, {} symbols unified across indices)",
+        renames.len(),
+        merged.documents.len(),
+        unified
+    );
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::build_call_graph;
+    use crate::types::{Document, Occurrence, Symbol};
+
+    fn index(
+        project_root: &str,
+        relative_path: &str,
+        occurrences: &[(i32, &str, bool)],
+    ) -> ScipIndex {
+        let symbols = occurrences
+            .iter()
+            .filter(|(_, _, definition)| *definition)
+            .map(|(_, symbol, _)| Symbol {
+                symbol: symbol.to_string(),
+                kind: 12,
+                display_name: symbol
+                    .rsplit('/')
+                    .next()
+                    .map(|name| name.trim_end_matches("().").to_string()),
+                documentation: None,
+                signature_documentation: Default::default(),
+                enclosing_symbol: None,
+            })
+            .collect();
+        ScipIndex {
+            metadata: Metadata {
+                project_root: project_root.to_string(),
+                ..Metadata::default()
+            },
+            documents: vec![Document {
+                language: "rust".to_string(),
+                relative_path: relative_path.to_string(),
+                occurrences: occurrences
+                    .iter()
+                    .map(|(line, symbol, definition)| Occurrence {
+                        range: vec![*line, 0, 5],
+                        symbol: symbol.to_string(),
+                        symbol_roles: definition.then_some(1),
+                    })
+                    .collect(),
+                symbols,
+                position_encoding: 1,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_merge_scip_indices() {
+        let main = "rust-analyzer cargo app 0.1.0 main().";
+        // The app depends on a released version of the library
+        let used_helper = "rust-analyzer cargo lib 0.2.0 util/helper().";
+        let helper = "verus-analyzer cargo lib 0.3.0-dev util/helper().";
+        let app = index(
+            "file:///work/app",
+            "src/main.rs",
+            &[(1, main, true), (2, used_helper, false)],
+        );
+        let lib = index("file:///work/lib/", "src/main.rs", &[(0, helper, true)]);
+
+        let merged = merge_scip_indices(vec![app, lib]);
+        assert_eq!(merged.metadata.project_root, "file:///work");
+        let paths: Vec<&str> = merged
+            .documents
+            .iter()
+            .map(|doc| doc.relative_path.as_str())
+            .collect();
+        assert_eq!(paths, ["app/src/main.rs", "lib/src/main.rs"]);
+        assert_eq!(merged.documents[0].occurrences[1].symbol, helper);
+
+        let call_graph = build_call_graph(&merged);
+        assert!(call_graph[main].callees.contains(helper));
+        assert!(call_graph[helper].callers.contains(main));
+        assert_eq!(call_graph[helper].relative_path, "lib/src/main.rs");
+        assert!(!call_graph.contains_key(used_helper));
+
+        // The same repository twice keeps its paths and the first documents
+        let twice = merge_scip_indices(vec![
+            index("/work/lib", "src/util.rs", &[(0, helper, true)]),
+            index("/work/lib", "src/util.rs", &[(0, helper, true)]),
+        ]);
+        assert_eq!(twice.metadata.project_root, "/work/lib");
+        assert_eq!(twice.documents.len(), 1);
+        assert_eq!(twice.documents[0].relative_path, "src/util.rs");
+        assert!(merge_scip_indices(Vec::new()).documents.is_empty());
+    }
+}
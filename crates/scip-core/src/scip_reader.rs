@@ -1,5 +1,8 @@
+use crate::error::{Error, Result};
+use crate::types::{Document, Metadata};
+use serde::de::Deserialize;
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, BufRead, BufReader, Read};
 
 /// Structure to represent basic SCIP index information
 pub struct ScipIndex {
@@ -42,7 +45,7 @@ pub enum SymbolKind {
 }
 
 /// Read raw data from a SCIP file
-pub fn read_scip_file(file_path: &str) -> io::Result<Vec<u8>> {
+pub fn read_scip_file(file_path: &str) -> Result<Vec<u8>> {
     let mut file = File::open(file_path)?;
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)?;
@@ -67,14 +70,14 @@ pub fn is_valid_scip_file(data: &[u8]) -> bool {
 
 /// Function that parses a SCIP file (limited implementation)
 /// For a complete implementation, a proper Protocol Buffers parser would be needed
-pub fn parse_scip_file(file_path: &str) -> Result<ScipIndex, Box<dyn std::error::Error>> {
+pub fn parse_scip_file(file_path: &str) -> Result<ScipIndex> {
     println!("Reading SCIP file: {file_path}");
 
     // Read the file
     let data = read_scip_file(file_path)?;
 
     if !is_valid_scip_file(&data) {
-        return Err("Not a valid SCIP file format".into());
+        return Err(Error::scip_parse("Not a valid SCIP file format"));
     }
 
     println!(
@@ -113,6 +116,392 @@ pub fn parse_scip_file(file_path: &str) -> Result<ScipIndex, Box<dyn std::error:
     Ok(mock_index)
 }
 
+/// Protobuf message definitions for the subset of the public SCIP schema
+/// (`scip.proto`) that the call graph builder consumes.
+///
+/// Field tags must match the upstream schema; fields we don't use are simply
+/// skipped by the decoder.
+mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Index {
+        #[prost(message, optional, tag = "1")]
+        pub metadata: Option<Metadata>,
+        #[prost(message, repeated, tag = "2")]
+        pub documents: Vec<Document>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Metadata {
+        #[prost(int32, tag = "1")]
+        pub version: i32,
+        #[prost(message, optional, tag = "2")]
+        pub tool_info: Option<ToolInfo>,
+        #[prost(string, tag = "3")]
+        pub project_root: String,
+        #[prost(int32, tag = "4")]
+        pub text_document_encoding: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ToolInfo {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub version: String,
+        #[prost(string, repeated, tag = "3")]
+        pub arguments: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Document {
+        #[prost(string, tag = "1")]
+        pub relative_path: String,
+        #[prost(message, repeated, tag = "2")]
+        pub occurrences: Vec<Occurrence>,
+        #[prost(message, repeated, tag = "3")]
+        pub symbols: Vec<SymbolInformation>,
+        #[prost(string, tag = "4")]
+        pub language: String,
+        #[prost(string, tag = "5")]
+        pub text: String,
+        #[prost(int32, tag = "6")]
+        pub position_encoding: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Occurrence {
+        #[prost(int32, repeated, tag = "1")]
+        pub range: Vec<i32>,
+        #[prost(string, tag = "2")]
+        pub symbol: String,
+        #[prost(int32, tag = "3")]
+        pub symbol_roles: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SymbolInformation {
+        #[prost(string, tag = "1")]
+        pub symbol: String,
+        #[prost(string, repeated, tag = "3")]
+        pub documentation: Vec<String>,
+        #[prost(int32, tag = "5")]
+        pub kind: i32,
+        #[prost(string, tag = "6")]
+        pub display_name: String,
+        #[prost(message, optional, tag = "7")]
+        pub signature_documentation: Option<Document>,
+        #[prost(string, tag = "8")]
+        pub enclosing_symbol: String,
+    }
+}
+
+fn non_empty(s: String) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+impl From<proto::Occurrence> for crate::types::Occurrence {
+    fn from(occ: proto::Occurrence) -> Self {
+        crate::types::Occurrence {
+            range: occ.range,
+            symbol: occ.symbol,
+            // `scip print --json` omits the field when no roles are set
+            symbol_roles: if occ.symbol_roles == 0 {
+                None
+            } else {
+                Some(occ.symbol_roles)
+            },
+        }
+    }
+}
+
+impl From<proto::SymbolInformation> for crate::types::Symbol {
+    fn from(sym: proto::SymbolInformation) -> Self {
+        let signature_documentation = sym
+            .signature_documentation
+            .map(|doc| crate::types::SignatureDocumentation {
+                language: doc.language,
+                text: doc.text,
+                position_encoding: doc.position_encoding,
+            })
+            .unwrap_or_default();
+
+        crate::types::Symbol {
+            symbol: sym.symbol,
+            kind: sym.kind,
+            display_name: non_empty(sym.display_name),
+            documentation: if sym.documentation.is_empty() {
+                None
+            } else {
+                Some(sym.documentation)
+            },
+            signature_documentation,
+            enclosing_symbol: non_empty(sym.enclosing_symbol),
+        }
+    }
+}
+
+impl From<proto::Document> for crate::types::Document {
+    fn from(doc: proto::Document) -> Self {
+        crate::types::Document {
+            language: doc.language,
+            relative_path: doc.relative_path,
+            occurrences: doc.occurrences.into_iter().map(Into::into).collect(),
+            symbols: doc.symbols.into_iter().map(Into::into).collect(),
+            position_encoding: doc.position_encoding,
+        }
+    }
+}
+
+impl From<proto::Index> for crate::types::ScipIndex {
+    fn from(index: proto::Index) -> Self {
+        let metadata = index.metadata.unwrap_or_default();
+        let tool_info = metadata.tool_info.unwrap_or_default();
+
+        crate::types::ScipIndex {
+            metadata: crate::types::Metadata {
+                tool_info: crate::types::ToolInfo {
+                    name: tool_info.name,
+                    version: tool_info.version,
+                },
+                project_root: metadata.project_root,
+                text_document_encoding: metadata.text_document_encoding,
+            },
+            documents: index.documents.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Decode a binary SCIP index (protobuf) from raw bytes
+pub fn decode_scip_protobuf(data: &[u8]) -> Result<crate::types::ScipIndex> {
+    let index = <proto::Index as prost::Message>::decode(data).map_err(Error::scip_parse)?;
+    Ok(index.into())
+}
+
+/// Parse a binary SCIP index file (e.g. `index.scip`) directly, without
+/// going through `scip print --json`.
+///
+/// Returns the same [`crate::types::ScipIndex`] as
+/// [`crate::parser::parse_scip_json`], so the result can be passed straight
+/// to [`crate::call_graph::build_call_graph`].
+pub fn parse_scip_protobuf(file_path: &str) -> Result<crate::types::ScipIndex> {
+    let data = read_scip_file(file_path)?;
+    decode_scip_protobuf(&data)
+}
+
+/// Incremental reader over the `documents` array of a SCIP JSON index.
+///
+/// Only one [`Document`] is held in memory at a time. Top-level keys other
+/// than `metadata` and `documents` (e.g. `external_symbols`) are skipped
+/// without being materialized.
+pub struct DocumentStream<R: Read> {
+    reader: BufReader<R>,
+    metadata: Option<Metadata>,
+    in_documents: bool,
+    done: bool,
+}
+
+/// Open a SCIP JSON index (as produced by `scip print --json`) for streaming.
+///
+/// The index is read up to the start of the `documents` array, so
+/// [`DocumentStream::metadata`] is available before iterating as long as the
+/// metadata precedes the documents (which is what `scip print` emits).
+pub fn stream_documents(file_path: &str) -> Result<DocumentStream<File>> {
+    DocumentStream::new(File::open(file_path)?)
+}
+
+impl<R: Read> DocumentStream<R> {
+    /// Start streaming documents from any reader over SCIP JSON
+    pub fn new(reader: R) -> Result<Self> {
+        let mut stream = DocumentStream {
+            reader: BufReader::new(reader),
+            metadata: None,
+            in_documents: false,
+            done: false,
+        };
+        stream.expect_byte(b'{')?;
+        stream.advance_to_documents()?;
+        Ok(stream)
+    }
+
+    /// Index metadata, if it has been read so far
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
+
+    fn peek_byte(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.peek_byte()?;
+        if byte.is_some() {
+            self.reader.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn peek_non_whitespace(&mut self) -> io::Result<Option<u8>> {
+        while let Some(byte) = self.peek_byte()? {
+            if !byte.is_ascii_whitespace() {
+                return Ok(Some(byte));
+            }
+            self.reader.consume(1);
+        }
+        Ok(None)
+    }
+
+    fn expect_byte(&mut self, expected: u8) -> Result<()> {
+        match self.peek_non_whitespace()? {
+            Some(byte) if byte == expected => {
+                self.reader.consume(1);
+                Ok(())
+            }
+            Some(byte) => Err(Error::scip_parse(format!(
+                "Expected '{}' in SCIP JSON, found '{}'",
+                expected as char, byte as char
+            ))),
+            None => Err(Error::scip_parse(format!(
+                "Expected '{}' in SCIP JSON, found EOF",
+                expected as char
+            ))),
+        }
+    }
+
+    /// Deserialize a single JSON value starting at the current position.
+    ///
+    /// serde_json reads objects, arrays and strings up to and including their
+    /// closing delimiter, so nothing past the value is consumed.
+    fn read_value<T: for<'de> Deserialize<'de>>(&mut self) -> Result<T> {
+        let mut de = serde_json::Deserializer::from_reader(&mut self.reader);
+        T::deserialize(&mut de).map_err(Error::scip_parse)
+    }
+
+    /// Skip over a JSON string whose opening quote has been consumed
+    fn skip_string(&mut self) -> Result<()> {
+        loop {
+            match self.next_byte()? {
+                Some(b'"') => return Ok(()),
+                Some(b'\\') => {
+                    self.next_byte()?;
+                }
+                Some(_) => {}
+                None => return Err(Error::scip_parse("Unterminated string in SCIP JSON")),
+            }
+        }
+    }
+
+    /// Skip over an arbitrary JSON value without materializing it
+    fn skip_value(&mut self) -> Result<()> {
+        match self.peek_non_whitespace()? {
+            Some(b'{') | Some(b'[') => {
+                let mut depth = 0usize;
+                loop {
+                    match self.next_byte()? {
+                        Some(b'{') | Some(b'[') => depth += 1,
+                        Some(b'}') | Some(b']') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return Ok(());
+                            }
+                        }
+                        Some(b'"') => self.skip_string()?,
+                        Some(_) => {}
+                        None => return Err(Error::scip_parse("Unexpected EOF in SCIP JSON")),
+                    }
+                }
+            }
+            Some(b'"') => {
+                self.reader.consume(1);
+                self.skip_string()
+            }
+            Some(_) => {
+                // Scalar: number, true, false or null
+                while let Some(byte) = self.peek_byte()? {
+                    if matches!(byte, b',' | b'}' | b']') || byte.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.reader.consume(1);
+                }
+                Ok(())
+            }
+            None => Err(Error::scip_parse("Unexpected EOF in SCIP JSON")),
+        }
+    }
+
+    /// Walk top-level keys until the `documents` array is entered or the
+    /// index object ends, capturing `metadata` along the way.
+    fn advance_to_documents(&mut self) -> Result<()> {
+        loop {
+            match self.peek_non_whitespace()? {
+                Some(b'}') => {
+                    self.reader.consume(1);
+                    self.done = true;
+                    return Ok(());
+                }
+                Some(b',') => self.reader.consume(1),
+                Some(b'"') => {
+                    let key: String = self.read_value()?;
+                    self.expect_byte(b':')?;
+                    match key.as_str() {
+                        "metadata" => self.metadata = Some(self.read_value()?),
+                        "documents" => {
+                            self.expect_byte(b'[')?;
+                            self.in_documents = true;
+                            return Ok(());
+                        }
+                        _ => self.skip_value()?,
+                    }
+                }
+                Some(byte) => {
+                    return Err(Error::scip_parse(format!(
+                        "Unexpected '{}' in SCIP JSON",
+                        byte as char
+                    )))
+                }
+                None => return Err(Error::scip_parse("Unexpected EOF in SCIP JSON")),
+            }
+        }
+    }
+
+    fn next_document(&mut self) -> Result<Option<Document>> {
+        while !self.done {
+            if !self.in_documents {
+                self.advance_to_documents()?;
+                continue;
+            }
+            match self.peek_non_whitespace()? {
+                Some(b']') => {
+                    self.reader.consume(1);
+                    self.in_documents = false;
+                }
+                Some(b',') => self.reader.consume(1),
+                Some(_) => return Ok(Some(self.read_value()?)),
+                None => return Err(Error::scip_parse("Unexpected EOF in SCIP JSON documents")),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: Read> Iterator for DocumentStream<R> {
+    type Item = Result<Document>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_document() {
+            Ok(doc) => doc.map(Ok),
+            Err(e) => {
+                // Don't keep yielding errors from a corrupt stream
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
 /// Extract some basic information from the SCIP file binary
 pub fn extract_basic_info(data: &[u8]) -> Vec<String> {
     let mut strings = Vec::new();
@@ -142,7 +531,7 @@ pub fn extract_basic_info(data: &[u8]) -> Vec<String> {
 }
 
 /// Example usage function to print a summary of the SCIP file
-pub fn print_scip_file_summary(file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+pub fn print_scip_file_summary(file_path: &str) -> Result<()> {
     // Read the raw data
     let data = read_scip_file(file_path)?;
 
@@ -171,9 +560,7 @@ pub fn print_scip_file_summary(file_path: &str) -> Result<(), Box<dyn std::error
 }
 
 /// Attempt to identify potential symbols in the SCIP file
-pub fn extract_potential_symbols(
-    file_path: &str,
-) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+pub fn extract_potential_symbols(file_path: &str) -> Result<Vec<String>> {
     let data = read_scip_file(file_path)?;
     let strings = extract_basic_info(&data);
 
@@ -192,3 +579,175 @@ pub fn extract_potential_symbols(
 
     Ok(potential_symbols)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    fn sample_index() -> proto::Index {
+        proto::Index {
+            metadata: Some(proto::Metadata {
+                version: 0,
+                tool_info: Some(proto::ToolInfo {
+                    name: "rust-analyzer".to_string(),
+                    version: "0.3".to_string(),
+                    arguments: vec![],
+                }),
+                project_root: "file:///project".to_string(),
+                text_document_encoding: 1,
+            }),
+            documents: vec![proto::Document {
+                relative_path: "src/lib.rs".to_string(),
+                language: "rust".to_string(),
+                occurrences: vec![
+                    proto::Occurrence {
+                        range: vec![0, 3, 6],
+                        symbol: "rust-analyzer cargo test 0.1.0 foo().".to_string(),
+                        symbol_roles: 1,
+                    },
+                    proto::Occurrence {
+                        range: vec![1, 4, 7],
+                        symbol: "rust-analyzer cargo test 0.1.0 bar().".to_string(),
+                        symbol_roles: 0,
+                    },
+                ],
+                symbols: vec![proto::SymbolInformation {
+                    symbol: "rust-analyzer cargo test 0.1.0 foo().".to_string(),
+                    documentation: vec!["Does foo".to_string()],
+                    kind: 17,
+                    display_name: "foo".to_string(),
+                    signature_documentation: Some(proto::Document {
+                        language: "rust".to_string(),
+                        text: "fn foo()".to_string(),
+                        ..Default::default()
+                    }),
+                    enclosing_symbol: String::new(),
+                }],
+                text: String::new(),
+                position_encoding: 1,
+            }],
+        }
+    }
+
+    // ==========================================================================
+    // Protobuf parsing tests
+    // ==========================================================================
+
+    #[test]
+    fn test_decode_scip_protobuf_round_trip() {
+        let bytes = sample_index().encode_to_vec();
+        let index = decode_scip_protobuf(&bytes).unwrap();
+
+        assert_eq!(index.metadata.tool_info.name, "rust-analyzer");
+        assert_eq!(index.metadata.project_root, "file:///project");
+        assert_eq!(index.documents.len(), 1);
+
+        let doc = &index.documents[0];
+        assert_eq!(doc.relative_path, "src/lib.rs");
+        assert_eq!(doc.occurrences.len(), 2);
+        assert_eq!(doc.occurrences[0].range, vec![0, 3, 6]);
+        assert_eq!(doc.occurrences[0].symbol_roles, Some(1));
+        assert_eq!(doc.occurrences[1].symbol_roles, None);
+
+        let sym = &doc.symbols[0];
+        assert_eq!(sym.kind, 17);
+        assert_eq!(sym.display_name.as_deref(), Some("foo"));
+        assert_eq!(sym.documentation, Some(vec!["Does foo".to_string()]));
+        assert_eq!(sym.signature_documentation.text, "fn foo()");
+        assert_eq!(sym.enclosing_symbol, None);
+    }
+
+    #[test]
+    fn test_decode_scip_protobuf_empty() {
+        let index = decode_scip_protobuf(&[]).unwrap();
+        assert!(index.documents.is_empty());
+        assert!(index.metadata.project_root.is_empty());
+    }
+
+    #[test]
+    fn test_decode_scip_protobuf_invalid() {
+        assert!(decode_scip_protobuf(&[0xff, 0xff, 0xff]).is_err());
+    }
+
+    #[test]
+    fn test_parse_scip_protobuf_builds_call_graph() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, &sample_index().encode_to_vec()).unwrap();
+
+        let index = parse_scip_protobuf(file.path().to_str().unwrap()).unwrap();
+        let graph = crate::call_graph::build_call_graph(&index);
+        assert!(graph.contains_key("rust-analyzer cargo test 0.1.0 foo()."));
+    }
+
+    // ==========================================================================
+    // Streaming JSON tests
+    // ==========================================================================
+
+    const STREAM_JSON: &str = r#"{
+        "metadata": {
+            "tool_info": {"name": "rust-analyzer", "version": "0.3"},
+            "project_root": "file:///project",
+            "text_document_encoding": 1
+        },
+        "documents": [
+            {
+                "language": "rust",
+                "relative_path": "src/a.rs",
+                "occurrences": [{"range": [0, 3, 6], "symbol": "a().", "symbol_roles": 1}],
+                "symbols": [],
+                "position_encoding": 1
+            },
+            {
+                "language": "rust",
+                "relative_path": "src/b.rs",
+                "occurrences": [],
+                "position_encoding": 1
+            }
+        ],
+        "external_symbols": [{"symbol": "odd \"]}[{\" name", "kind": 12}]
+    }"#;
+
+    #[test]
+    fn test_stream_documents_yields_each_document() {
+        let stream = DocumentStream::new(STREAM_JSON.as_bytes()).unwrap();
+        assert_eq!(
+            stream.metadata().map(|m| m.project_root.as_str()),
+            Some("file:///project")
+        );
+
+        let docs: Vec<Document> = stream.collect::<Result<_, _>>().unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].relative_path, "src/a.rs");
+        assert_eq!(docs[0].occurrences[0].symbol_roles, Some(1));
+        assert_eq!(docs[1].relative_path, "src/b.rs");
+        assert!(docs[1].symbols.is_empty());
+    }
+
+    #[test]
+    fn test_stream_documents_metadata_after_documents() {
+        let json = r#"{"documents": [], "version": 3, "metadata": {
+            "tool_info": {"name": "t", "version": "1"},
+            "project_root": "/late",
+            "text_document_encoding": 0
+        }}"#;
+        let mut stream = DocumentStream::new(json.as_bytes()).unwrap();
+        assert!(stream.metadata().is_none());
+        assert!(stream.next().is_none());
+        assert_eq!(stream.metadata().unwrap().project_root, "/late");
+    }
+
+    #[test]
+    fn test_stream_documents_truncated_input() {
+        let truncated = &STREAM_JSON[..STREAM_JSON.find("src/b.rs").unwrap()];
+        let mut stream = DocumentStream::new(truncated.as_bytes()).unwrap();
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_stream_documents_not_an_object() {
+        assert!(DocumentStream::new("[]".as_bytes()).is_err());
+    }
+}
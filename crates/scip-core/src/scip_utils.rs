@@ -1,5 +1,8 @@
 //! Utilities for working with SCIP (Source Code Indexing Protocol)
 
+use crate::error::{Error, Result};
+use crate::types::{Document, ScipIndex};
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::process::Command;
 
@@ -25,16 +28,22 @@ use std::process::Command;
 /// let output_file = generate_scip_json_index("/path/to/project").unwrap();
 /// println!("Generated: {}", output_file);
 /// ```
-pub fn generate_scip_json_index(folder_path: &str) -> Result<String, Box<dyn std::error::Error>> {
+pub fn generate_scip_json_index(folder_path: &str) -> Result<String> {
     let folder_path = Path::new(folder_path);
 
     // Validate that the folder exists
     if !folder_path.exists() {
-        return Err(format!("Path '{}' does not exist", folder_path.display()).into());
+        return Err(Error::InvalidPath(format!(
+            "Path '{}' does not exist",
+            folder_path.display()
+        )));
     }
 
     if !folder_path.is_dir() {
-        return Err(format!("Path '{}' is not a directory", folder_path.display()).into());
+        return Err(Error::InvalidPath(format!(
+            "Path '{}' is not a directory",
+            folder_path.display()
+        )));
     }
 
     println!(
@@ -49,7 +58,11 @@ pub fn generate_scip_json_index(folder_path: &str) -> Result<String, Box<dyn std
         .status()?;
 
     if !status.success() {
-        return Err(format!("verus-analyzer scip command failed with status: {}", status).into());
+        return Err(Error::ToolFailed {
+            tool: "verus-analyzer scip".to_string(),
+            status,
+            stderr: String::new(),
+        });
     }
 
     println!("verus-analyzer scip completed successfully");
@@ -70,12 +83,11 @@ pub fn generate_scip_json_index(folder_path: &str) -> Result<String, Box<dyn std
         .output()?;
 
     if !output.status.success() {
-        return Err(format!(
-            "scip print command failed with status: {}\nstderr: {}",
-            output.status,
-            String::from_utf8_lossy(&output.stderr)
-        )
-        .into());
+        return Err(Error::ToolFailed {
+            tool: "scip print".to_string(),
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
     }
 
     // Write the output to the JSON file
@@ -87,3 +99,126 @@ pub fn generate_scip_json_index(folder_path: &str) -> Result<String, Box<dyn std
 
     Ok(output_filename)
 }
+
+/// Compute a stable content fingerprint for each document in a SCIP index,
+/// keyed by relative path.
+///
+/// Fingerprints are FNV-1a hashes of the serialized occurrences and symbols,
+/// so they are stable across runs and toolchains and can be persisted to
+/// detect which documents changed between two indexes.
+pub fn document_fingerprints(index: &ScipIndex) -> BTreeMap<String, u64> {
+    index
+        .documents
+        .iter()
+        .map(|doc| {
+            (
+                doc.relative_path.trim_start_matches('/').to_string(),
+                document_fingerprint(doc),
+            )
+        })
+        .collect()
+}
+
+/// Fingerprint a single SCIP document (see [`document_fingerprints`])
+pub fn document_fingerprint(doc: &Document) -> u64 {
+    let occurrences = serde_json::to_vec(&doc.occurrences).unwrap_or_default();
+    let symbols = serde_json::to_vec(&doc.symbols).unwrap_or_default();
+
+    fnv1a(occurrences.iter().chain(symbols.iter()).copied())
+}
+
+/// 64-bit FNV-1a hash, stable across runs, platforms and toolchains
+pub(crate) fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    bytes.into_iter().fold(FNV_OFFSET, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// List the documents that were added, modified or removed between two sets
+/// of fingerprints, sorted by relative path.
+pub fn changed_documents(
+    previous: &BTreeMap<String, u64>,
+    current: &BTreeMap<String, u64>,
+) -> Vec<String> {
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|(path, hash)| previous.get(*path) != Some(*hash))
+        .map(|(path, _)| path.clone())
+        .chain(
+            previous
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .cloned(),
+        )
+        .collect();
+    changed.sort();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Metadata, Occurrence, ToolInfo};
+
+    fn index_with(docs: Vec<(&str, i32)>) -> ScipIndex {
+        ScipIndex {
+            metadata: Metadata {
+                tool_info: ToolInfo {
+                    name: "rust-analyzer".to_string(),
+                    version: "0.3".to_string(),
+                },
+                project_root: "/project".to_string(),
+                text_document_encoding: 1,
+            },
+            documents: docs
+                .into_iter()
+                .map(|(path, line)| Document {
+                    language: "rust".to_string(),
+                    relative_path: path.to_string(),
+                    occurrences: vec![Occurrence {
+                        range: vec![line, 0, 3],
+                        symbol: "foo().".to_string(),
+                        symbol_roles: Some(1),
+                    }],
+                    symbols: vec![],
+                    position_encoding: 1,
+                })
+                .collect(),
+        }
+    }
+
+    // ==========================================================================
+    // Document fingerprint tests
+    // ==========================================================================
+
+    #[test]
+    fn test_document_fingerprints_stable() {
+        let a = document_fingerprints(&index_with(vec![("src/a.rs", 1)]));
+        let b = document_fingerprints(&index_with(vec![("src/a.rs", 1)]));
+        assert_eq!(a, b);
+        assert!(a.contains_key("src/a.rs"));
+    }
+
+    #[test]
+    fn test_changed_documents() {
+        let previous = document_fingerprints(&index_with(vec![
+            ("src/a.rs", 1),
+            ("src/b.rs", 1),
+            ("src/gone.rs", 1),
+        ]));
+        let current = document_fingerprints(&index_with(vec![
+            ("src/a.rs", 1),
+            ("src/b.rs", 2),
+            ("src/new.rs", 1),
+        ]));
+
+        assert_eq!(
+            changed_documents(&previous, &current),
+            vec!["src/b.rs", "src/gone.rs", "src/new.rs"]
+        );
+        assert!(changed_documents(&current, &current).is_empty());
+    }
+}
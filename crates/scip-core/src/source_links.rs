@@ -0,0 +1,141 @@
+//! Links from functions to their source on GitHub
+//!
+//! Given the repository URL and a revision (branch, tag or commit), a
+//! function links to
+//! `https://github.com/<owner>/<repo>/blob/<rev>/<path>#L<start>-L<end>`.
+//! The DOT, markdown and HTML exporters use [`SourceLinks`] to make nodes
+//! clickable; functions outside the project have no link.
+
+use crate::types::{D3Graph, FunctionNode};
+
+/// Base URL and revision of the repository the sources are linked to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLinks {
+    base_url: String,
+    rev: String,
+}
+
+impl SourceLinks {
+    /// Links into `github_url` (e.g. `https://github.com/owner/repo`, a
+    /// trailing `/` or `.git` is dropped) at revision `rev`
+    pub fn new(github_url: &str, rev: &str) -> Self {
+        let base_url = github_url.trim_end_matches('/');
+        Self {
+            base_url: base_url
+                .strip_suffix(".git")
+                .unwrap_or(base_url)
+                .to_string(),
+            rev: rev.to_string(),
+        }
+    }
+
+    /// URL of a file, with a `#L<start>-L<end>` fragment for the given
+    /// 1-based lines
+    pub fn url(
+        &self,
+        relative_path: &str,
+        start_line: Option<usize>,
+        end_line: Option<usize>,
+    ) -> String {
+        let path = relative_path
+            .trim_start_matches("./")
+            .trim_start_matches('/');
+        let url = format!("{}/blob/{}/{}", self.base_url, self.rev, path);
+        match (start_line, end_line) {
+            (Some(start), Some(end)) if end > start => format!("{url}#L{start}-L{end}"),
+            (Some(start), _) => format!("{url}#L{start}"),
+            _ => url,
+        }
+    }
+
+    /// URL of a function's source; `None` for functions outside the project
+    pub fn node_url(&self, node: &FunctionNode) -> Option<String> {
+        if node.file_path.starts_with("external:") || node.relative_path.is_empty() {
+            return None;
+        }
+        let start_line = node
+            .start_line
+            .or_else(|| node.range.first().map(|line| *line as usize + 1));
+        Some(self.url(&node.relative_path, start_line, node.end_line))
+    }
+
+    /// Set `source_url` of the D3 graph nodes that have a source file;
+    /// returns the number of nodes linked
+    pub fn link_d3_graph(&self, graph: &mut D3Graph) -> usize {
+        let mut linked = 0;
+        for node in &mut graph.nodes {
+            if node.full_path.starts_with("external:") || node.relative_path.is_empty() {
+                continue;
+            }
+            node.source_url = Some(self.url(&node.relative_path, node.start_line, node.end_line));
+            linked += 1;
+        }
+        linked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export_dot::add_source_links_to_dot;
+    use crate::types::{CallGraph, D3Node};
+
+    #[test]
+    fn test_source_links() {
+        let links = SourceLinks::new("https://github.com/owner/repo.git/", "abc123");
+        assert_eq!(
+            links.url("./src/lib.rs", Some(10), Some(24)),
+            "https://github.com/owner/repo/blob/abc123/src/lib.rs#L10-L24"
+        );
+        assert_eq!(
+            links.url("src/lib.rs", Some(10), Some(10)),
+            "https://github.com/owner/repo/blob/abc123/src/lib.rs#L10"
+        );
+        assert_eq!(
+            links.url("src/lib.rs", None, None),
+            "https://github.com/owner/repo/blob/abc123/src/lib.rs"
+        );
+
+        let mut graph = D3Graph {
+            nodes: vec![
+                D3Node {
+                    relative_path: "src/lib.rs".to_string(),
+                    start_line: Some(3),
+                    end_line: Some(5),
+                    ..Default::default()
+                },
+                D3Node {
+                    full_path: "external:core::len".to_string(),
+                    ..Default::default()
+                },
+            ],
+            links: Vec::new(),
+            metadata: Default::default(),
+        };
+        assert_eq!(links.link_d3_graph(&mut graph), 1);
+        assert_eq!(
+            graph.nodes[0].source_url.as_deref(),
+            Some("https://github.com/owner/repo/blob/abc123/src/lib.rs#L3-L5")
+        );
+        assert_eq!(graph.nodes[1].source_url, None);
+
+        let node = FunctionNode {
+            symbol: "m/add().".to_string(),
+            display_name: "add".to_string(),
+            file_path: "/project/src/m.rs".to_string(),
+            relative_path: "src/m.rs".to_string(),
+            range: vec![6, 7, 6, 10],
+            ..Default::default()
+        };
+        assert_eq!(
+            links.node_url(&node).as_deref(),
+            Some("https://github.com/owner/repo/blob/abc123/src/m.rs#L7")
+        );
+        let call_graph: CallGraph = [node].into_iter().collect();
+        let dot = "digraph g {\n  \"m/add().\" [label=\"add\"]\n  \"m/add().\" -> \"m/add().\" [style=dashed]\n  \"src/m.rs\" [label=\"m\"]\n}\n";
+        assert_eq!(
+            add_source_links_to_dot(dot, &call_graph, &links),
+            "digraph g {\n  \"m/add().\" [URL=\"https://github.com/owner/repo/blob/abc123/src/m.rs#L7\", target=\"_blank\", label=\"add\"]\n  \"m/add().\" -> \"m/add().\" [style=dashed]\n  \"src/m.rs\" [label=\"m\"]\n}\n"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! `requires`/`ensures` clauses of functions, as written
+//!
+//! Body extraction stores the clauses on call graph nodes, and the atoms and
+//! D3 exports carry them along, so viewers can show a function's contract
+//! without its body. Clauses are cut from the source by verus_syn spans,
+//! with whitespace collapsed; functions that don't parse get none. The
+//! signature ([`signature_text`]) travels along the same way.
+
+use crate::types::D3Graph;
+use proc_macro2::LineColumn;
+use std::collections::BTreeMap;
+use std::path::Path;
+use verus_syn::spanned::Spanned;
+use verus_syn::{Expr, ImplItem, ItemFn, ReturnType, Signature, TraitItem, Visibility};
+
+/// The `requires` and the `ensures` clauses of the function in `source`
+/// (its text from the signature on), empty if it doesn't parse
+pub fn spec_clauses(source: &str) -> (Vec<String>, Vec<String>) {
+    let Some((_, sig)) = parse_function(source) else {
+        return (Vec::new(), Vec::new());
+    };
+    let texts = |exprs: Option<Vec<&Expr>>| -> Vec<String> {
+        exprs
+            .unwrap_or_default()
+            .into_iter()
+            .map(|expr| text_of(source, expr))
+            .filter(|text| !text.is_empty())
+            .collect()
+    };
+    let requires = texts(
+        sig.spec
+            .requires
+            .as_ref()
+            .map(|requires| requires.exprs.exprs.iter().collect()),
+    );
+    let ensures = texts(
+        sig.spec
+            .ensures
+            .as_ref()
+            .map(|ensures| ensures.exprs.exprs.iter().collect()),
+    );
+    (requires, ensures)
+}
+
+/// The signature of the function in `source` as written, from the
+/// visibility to the return type (whitespace collapsed, without specs or
+/// body), `None` if it doesn't parse
+pub fn signature_text(source: &str) -> Option<String> {
+    let (vis, sig) = parse_function(source)?;
+    let start = match &vis {
+        Some(Visibility::Inherited) | None => sig.span().start(),
+        Some(vis) => vis.span().start(),
+    };
+    let end = match &sig.output {
+        ReturnType::Default => sig.paren_token.span.close().end(),
+        output => output.span().end(),
+    };
+    Some(text_between(source, start, end))
+}
+
+/// Fill in the clauses and signatures of the D3 nodes that have none, from
+/// their line ranges in the files under `project_root`; returns how many
+/// nodes got clauses. For graphs built from atoms without bodies (the
+/// pipeline).
+pub fn add_spec_texts_to_d3(graph: &mut D3Graph, project_root: &Path) -> usize {
+    let mut by_file: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, node) in graph.nodes.iter().enumerate() {
+        let has_clauses = !node.requires_text.is_empty() || !node.ensures_text.is_empty();
+        let complete = has_clauses && node.signature.is_some();
+        if !complete && !node.relative_path.is_empty() && node.start_line.is_some() {
+            by_file
+                .entry(node.relative_path.clone())
+                .or_default()
+                .push(i);
+        }
+    }
+
+    let mut added = 0;
+    for (relative_path, indices) in by_file {
+        let Ok(source) = std::fs::read_to_string(project_root.join(&relative_path)) else {
+            continue;
+        };
+        let lines: Vec<&str> = source.lines().collect();
+        for i in indices {
+            let node = &mut graph.nodes[i];
+            let start = node.start_line.unwrap_or(1).max(1) - 1;
+            let end = node.end_line.unwrap_or(start + 1).min(lines.len());
+            let Some(function) = lines.get(start..end) else {
+                continue;
+            };
+            let function = function.join("\n");
+            if node.signature.is_none() {
+                node.signature = signature_text(&function);
+            }
+            if !node.requires_text.is_empty() || !node.ensures_text.is_empty() {
+                continue;
+            }
+            let (requires, ensures) = spec_clauses(&function);
+            if !requires.is_empty() || !ensures.is_empty() {
+                node.requires_text = requires;
+                node.ensures_text = ensures;
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+/// Visibility (`None` for trait methods) and signature of a free function,
+/// method or trait method
+fn parse_function(source: &str) -> Option<(Option<Visibility>, Signature)> {
+    if let Ok(item_fn) = verus_syn::parse_str::<ItemFn>(source) {
+        return Some((Some(item_fn.vis), item_fn.sig));
+    }
+    if let Ok(ImplItem::Fn(impl_fn)) = verus_syn::parse_str::<ImplItem>(source) {
+        return Some((Some(impl_fn.vis), impl_fn.sig));
+    }
+    match verus_syn::parse_str::<TraitItem>(source) {
+        Ok(TraitItem::Fn(trait_fn)) => Some((None, trait_fn.sig)),
+        _ => None,
+    }
+}
+
+/// Byte offset of a span position (1-based line, 0-based char column)
+fn byte_offset(source: &str, at: LineColumn) -> usize {
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(at.line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let line = &source[line_start..];
+    line_start
+        + line
+            .char_indices()
+            .nth(at.column)
+            .map_or(line.len(), |(i, _)| i)
+}
+
+/// Source text of `node`, whitespace collapsed to single spaces
+fn text_of(source: &str, node: &impl Spanned) -> String {
+    let span = node.span();
+    text_between(source, span.start(), span.end())
+}
+
+/// Source text from `start` to `end`, whitespace collapsed to single spaces
+fn text_between(source: &str, start: LineColumn, end: LineColumn) -> String {
+    let (start, end) = (byte_offset(source, start), byte_offset(source, end));
+    source
+        .get(start..end.max(start))
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_clauses() {
+        let source = "pub fn add(a: u64, b: u64) -> (r: u64)\n    requires\n        a < 10,\n        b <\n            10,\n    ensures r == a + b,\n{\n    a + b\n}";
+        let (requires, ensures) = spec_clauses(source);
+        assert_eq!(requires, ["a < 10", "b < 10"]);
+        assert_eq!(ensures, ["r == a + b"]);
+
+        let method = "fn get(&self, i: usize) -> u8\n    requires i < self.len(),\n;";
+        assert_eq!(spec_clauses(method).0, ["i < self.len()"]);
+        assert_eq!(spec_clauses("fn main() {}"), (Vec::new(), Vec::new()));
+        assert_eq!(spec_clauses("fn broken( {"), (Vec::new(), Vec::new()));
+
+        assert_eq!(
+            signature_text(source).as_deref(),
+            Some("pub fn add(a: u64, b: u64) -> (r: u64)")
+        );
+        assert_eq!(
+            signature_text(method).as_deref(),
+            Some("fn get(&self, i: usize) -> u8")
+        );
+        assert_eq!(signature_text("fn broken( {"), None);
+    }
+}
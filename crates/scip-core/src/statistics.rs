@@ -0,0 +1,231 @@
+//! Distributions of per-function metrics
+//!
+//! Counts in the metrics summaries hide outliers. [`Distribution`] gives the
+//! minimum, median, 90th percentile, maximum and a histogram of one metric
+//! (spec length, proof depth, fan-in, ...) over a project;
+//! [`render_distribution_table`] prints them as a text table for the CLI
+//! summaries, and they serialize as JSON for `--log-format json` events.
+//!
+//! Percentiles use the nearest-rank method, so they are always values that
+//! occur in the data.
+
+use serde::Serialize;
+use std::fmt::Write as _;
+
+/// Number of histogram bins
+pub const HISTOGRAM_BINS: usize = 10;
+
+/// Width of the histogram bars in [`render_distribution_table`]
+const BAR_WIDTH: usize = 30;
+
+/// Values in `[lower, upper)` (the last bin includes `upper`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistogramBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+}
+
+/// Summary statistics of one metric
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub min: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// [`HISTOGRAM_BINS`] equal-width bins from `min` to `max`; integer
+    /// metrics get integer bin widths, hence possibly fewer bins
+    pub histogram: Vec<HistogramBin>,
+}
+
+impl Distribution {
+    /// Statistics of `values`, `None` if there are none (NaNs are ignored)
+    pub fn of(values: &[f64]) -> Option<Self> {
+        let mut sorted: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            median: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            max: sorted[sorted.len() - 1],
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            histogram: histogram(&sorted, HISTOGRAM_BINS),
+        })
+    }
+
+    /// Statistics of integer counts (see [`Distribution::of`])
+    pub fn of_counts(values: impl IntoIterator<Item = usize>) -> Option<Self> {
+        let values: Vec<f64> = values.into_iter().map(|v| v as f64).collect();
+        Self::of(&values)
+    }
+}
+
+/// Nearest-rank `p`th percentile of sorted, non-empty values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn histogram(sorted: &[f64], bins: usize) -> Vec<HistogramBin> {
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+    let mut width = (max - min) / bins as f64;
+    if sorted.iter().all(|v| v.fract() == 0.0) {
+        width = width.ceil().max(1.0);
+    }
+    if width == 0.0 {
+        return vec![HistogramBin {
+            lower: min,
+            upper: max,
+            count: sorted.len(),
+        }];
+    }
+
+    let bins = (((max - min) / width).floor() as usize + 1).min(bins);
+    let mut histogram: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin {
+            lower: min + i as f64 * width,
+            upper: min + (i + 1) as f64 * width,
+            count: 0,
+        })
+        .collect();
+    for value in sorted {
+        let bin = (((value - min) / width).floor() as usize).min(bins - 1);
+        histogram[bin].count += 1;
+    }
+    histogram
+}
+
+/// A named [`Distribution`], e.g. of `spec_length`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricDistribution {
+    pub metric: String,
+    #[serde(flatten)]
+    pub distribution: Distribution,
+}
+
+impl MetricDistribution {
+    /// Distribution of `metric` over `values`, `None` if there are none
+    pub fn of(metric: &str, values: &[f64]) -> Option<Self> {
+        Some(Self {
+            metric: metric.to_string(),
+            distribution: Distribution::of(values)?,
+        })
+    }
+}
+
+/// Short form of a statistic: integers without decimals, others with one
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.1}")
+    }
+}
+
+/// Text table of the distributions (one row per metric), each followed by
+/// its histogram
+pub fn render_distribution_table(distributions: &[MetricDistribution]) -> String {
+    let name_width = distributions
+        .iter()
+        .map(|d| d.metric.len())
+        .chain(["metric".len()])
+        .max()
+        .unwrap_or_default();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<name_width$}  {:>7}  {:>9}  {:>9}  {:>9}  {:>9}",
+        "metric", "n", "min", "median", "p90", "max"
+    );
+    for d in distributions {
+        let stats = &d.distribution;
+        let _ = writeln!(
+            out,
+            "{:<name_width$}  {:>7}  {:>9}  {:>9}  {:>9}  {:>9}",
+            d.metric,
+            stats.count,
+            format_value(stats.min),
+            format_value(stats.median),
+            format_value(stats.p90),
+            format_value(stats.max)
+        );
+    }
+
+    for d in distributions {
+        let _ = writeln!(out, "\n{}:", d.metric);
+        let largest = d
+            .distribution
+            .histogram
+            .iter()
+            .map(|bin| bin.count)
+            .max()
+            .unwrap_or_default();
+        let ranges: Vec<String> = d
+            .distribution
+            .histogram
+            .iter()
+            .map(|bin| format!("[{}, {})", format_value(bin.lower), format_value(bin.upper)))
+            .collect();
+        let range_width = ranges.iter().map(String::len).max().unwrap_or_default();
+        for (bin, range) in d.distribution.histogram.iter().zip(ranges) {
+            let bar = (bin.count * BAR_WIDTH).div_ceil(largest.max(1));
+            let _ = writeln!(
+                out,
+                "  {range:<range_width$}  {:<BAR_WIDTH$}  {}",
+                "█".repeat(bar),
+                bin.count
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution() {
+        let values: Vec<usize> = vec![1, 2, 2, 3, 3, 3, 4, 5, 8, 40];
+        let stats = Distribution::of_counts(values).unwrap();
+        assert_eq!(stats.count, 10);
+        assert_eq!(
+            (stats.min, stats.median, stats.p90, stats.max),
+            (1.0, 3.0, 8.0, 40.0)
+        );
+        assert_eq!(stats.mean, 7.1);
+        // Width ceil(3.9) = 4: [1, 5), [5, 9), ..., [37, 41)
+        assert_eq!(stats.histogram.len(), 10);
+        assert_eq!(
+            (stats.histogram[0].lower, stats.histogram[0].upper),
+            (1.0, 5.0)
+        );
+        assert_eq!(stats.histogram[0].count, 7);
+        assert_eq!(stats.histogram[1].count, 2);
+        assert_eq!(stats.histogram[9].count, 1);
+
+        // Small integer ranges get one bin per value
+        let depths = Distribution::of_counts([0, 1, 1, 2]).unwrap();
+        let counts: Vec<usize> = depths.histogram.iter().map(|bin| bin.count).collect();
+        assert_eq!(counts, [1, 2, 1]);
+        let same = Distribution::of(&[2.5, 2.5]).unwrap();
+        assert_eq!(same.histogram.len(), 1);
+        assert!(Distribution::of(&[]).is_none());
+
+        let table = render_distribution_table(&[MetricDistribution {
+            metric: "proof_depth".to_string(),
+            distribution: depths,
+        }]);
+        assert!(table.starts_with("metric       "));
+        assert!(
+            table.contains("proof_depth        4          0          1          2          2\n")
+        );
+        assert!(table.contains(&format!("  [1, 2)  {}  2\n", "█".repeat(BAR_WIDTH))));
+    }
+}
@@ -0,0 +1,385 @@
+//! Resolve human-written function names to SCIP symbols
+//!
+//! Nobody types `rust-analyzer cargo curve25519-dalek 4.1.3
+//! backend/serial/u64/field/FieldElement51#sub().` on the command line.
+//! [`SymbolResolver`] indexes the functions of a call graph (or of an atoms
+//! file) and looks up names in tiers, stopping at the first tier with a
+//! match:
+//!
+//! 1. exact: the full symbol, its path or the display name (`sub`)
+//! 2. qualified: `Type::method` (or `Type#method`), also for trait impls
+//!    (`FieldElement51::add_assign` finds `FieldElement51/AddAssign/add_assign`)
+//! 3. suffix: the trailing path segments (`field::FieldElement51::sub`)
+//! 4. fuzzy: display names within a small edit distance, for typos
+//!
+//! Fuzzy matches are suggestions: [`SymbolResolver::resolve_one`] reports
+//! them as "did you mean" rather than picking one.
+
+use crate::call_graph::symbol_to_path;
+use crate::symbol_scheme::strip_generics;
+use crate::types::{CallGraph, FunctionNode};
+use std::fmt;
+
+/// How a function matched a name, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchKind {
+    Exact,
+    Qualified,
+    Suffix,
+    /// Display name within this edit distance
+    Fuzzy(usize),
+}
+
+/// A function known to the resolver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolEntry {
+    pub symbol: String,
+    pub display_name: String,
+    pub relative_path: String,
+}
+
+impl From<&FunctionNode> for SymbolEntry {
+    fn from(node: &FunctionNode) -> Self {
+        Self {
+            symbol: node.symbol.clone(),
+            display_name: node.display_name.clone(),
+            relative_path: node.relative_path.clone(),
+        }
+    }
+}
+
+/// A function matching a name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolMatch<'a> {
+    pub entry: &'a SymbolEntry,
+    pub kind: MatchKind,
+}
+
+/// Why a name did not resolve to a single function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// No function matches; `suggestions` are close display names
+    NotFound {
+        query: String,
+        suggestions: Vec<String>,
+    },
+    /// Several functions match, as (display name, symbol)
+    Ambiguous {
+        query: String,
+        candidates: Vec<(String, String)>,
+    },
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::NotFound { query, suggestions } => {
+                write!(f, "No functions matching '{query}' found")?;
+                if !suggestions.is_empty() {
+                    write!(f, ". Did you mean: {}?", suggestions.join(", "))?;
+                }
+                Ok(())
+            }
+            ResolveError::Ambiguous { query, candidates } => {
+                write!(
+                    f,
+                    "Multiple functions match '{query}'. Please be more specific:"
+                )?;
+                for (i, (display_name, symbol)) in candidates.iter().enumerate() {
+                    write!(f, "\n  {}. {} ({})", i + 1, display_name, symbol)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+/// Identifier-like pieces of a symbol, path or query, without generics
+/// (`impl#[Foo][Bar]baz().` gives `Foo`, `Bar`, `baz`)
+fn segments(name: &str) -> Vec<String> {
+    strip_generics(name)
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|segment| !segment.is_empty() && *segment != "impl")
+        .map(str::to_string)
+        .collect()
+}
+
+/// Largest edit distance accepted as a typo of a name this long
+fn fuzzy_threshold(len: usize) -> usize {
+    (len / 4).clamp(1, 3)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+struct IndexedEntry {
+    entry: SymbolEntry,
+    path: String,
+    segments: Vec<String>,
+}
+
+impl IndexedEntry {
+    fn new(entry: SymbolEntry) -> Self {
+        let path = symbol_to_path(&entry.symbol, &entry.display_name);
+        let segments = match segments(&entry.symbol) {
+            from_symbol if from_symbol.is_empty() => segments(&entry.display_name),
+            from_symbol => from_symbol,
+        };
+        Self {
+            entry,
+            path,
+            segments,
+        }
+    }
+
+    /// Best non-fuzzy match of the query, if any
+    fn match_kind(&self, query: &str, query_segments: &[String]) -> Option<MatchKind> {
+        let entry = &self.entry;
+        if entry.symbol == query
+            || entry.symbol.trim_end_matches('.') == query.trim_end_matches('.')
+            || entry.display_name == query
+            || self.path == query
+        {
+            return Some(MatchKind::Exact);
+        }
+        let (method, qualifiers) = query_segments.split_last()?;
+        let (last, rest) = self.segments.split_last()?;
+        if last != method {
+            return None;
+        }
+        // `Type::method` also matches `Type/Trait/method`
+        if let [qualifier] = qualifiers {
+            if rest
+                .iter()
+                .rev()
+                .take(2)
+                .any(|segment| segment == qualifier)
+            {
+                return Some(MatchKind::Qualified);
+            }
+        }
+        self.segments
+            .ends_with(query_segments)
+            .then_some(MatchKind::Suffix)
+    }
+}
+
+/// Name-to-symbol index of the functions of a call graph or atoms file
+pub struct SymbolResolver {
+    entries: Vec<IndexedEntry>,
+}
+
+impl SymbolResolver {
+    /// Index the given functions; results come back sorted by symbol
+    pub fn new(entries: impl IntoIterator<Item = SymbolEntry>) -> Self {
+        let mut entries: Vec<IndexedEntry> = entries.into_iter().map(IndexedEntry::new).collect();
+        entries.sort_by(|a, b| a.entry.symbol.cmp(&b.entry.symbol));
+        Self { entries }
+    }
+
+    /// Index every function of the call graph, external ones included
+    pub fn from_call_graph(call_graph: &CallGraph) -> Self {
+        Self::new(call_graph.values().map(SymbolEntry::from))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Functions matching `query` in the best matching tier, sorted by
+    /// symbol (fuzzy ones by distance first); empty if nothing is close
+    pub fn resolve(&self, query: &str) -> Vec<SymbolMatch<'_>> {
+        let query = query.trim();
+        let query_segments = segments(query);
+        let mut best: Option<MatchKind> = None;
+        let mut matches = Vec::new();
+        for indexed in &self.entries {
+            let Some(kind) = indexed.match_kind(query, &query_segments) else {
+                continue;
+            };
+            if best.is_some_and(|best| kind > best) {
+                continue;
+            }
+            if best.is_some_and(|best| kind < best) {
+                matches.clear();
+            }
+            best = Some(kind);
+            matches.push(SymbolMatch {
+                entry: &indexed.entry,
+                kind,
+            });
+        }
+        if matches.is_empty() {
+            matches = self.fuzzy(query_segments.last().map_or(query, String::as_str));
+        }
+        matches
+    }
+
+    /// Symbols of the exact, qualified or suffix matches of `query`
+    pub fn resolve_all(&self, query: &str) -> Result<Vec<&str>, ResolveError> {
+        let matches = self.resolve(query);
+        if matches
+            .iter()
+            .any(|m| matches!(m.kind, MatchKind::Fuzzy(_)))
+        {
+            return Err(self.not_found(query, &matches));
+        }
+        if matches.is_empty() {
+            return Err(self.not_found(query, &[]));
+        }
+        Ok(matches.iter().map(|m| m.entry.symbol.as_str()).collect())
+    }
+
+    /// The single function `query` names, or the candidates to choose from
+    pub fn resolve_one(&self, query: &str) -> Result<&SymbolEntry, ResolveError> {
+        let matches = self.resolve(query);
+        match matches.as_slice() {
+            [only] if !matches!(only.kind, MatchKind::Fuzzy(_)) => Ok(only.entry),
+            [] => Err(self.not_found(query, &[])),
+            [first, ..] if matches!(first.kind, MatchKind::Fuzzy(_)) => {
+                Err(self.not_found(query, &matches))
+            }
+            _ => Err(ResolveError::Ambiguous {
+                query: query.to_string(),
+                candidates: matches
+                    .iter()
+                    .map(|m| (m.entry.display_name.clone(), m.entry.symbol.clone()))
+                    .collect(),
+            }),
+        }
+    }
+
+    fn not_found(&self, query: &str, fuzzy: &[SymbolMatch<'_>]) -> ResolveError {
+        let mut suggestions: Vec<String> = Vec::new();
+        for m in fuzzy {
+            let label = symbol_to_path(&m.entry.symbol, &m.entry.display_name);
+            if !suggestions.contains(&label) {
+                suggestions.push(label);
+            }
+        }
+        suggestions.truncate(5);
+        ResolveError::NotFound {
+            query: query.to_string(),
+            suggestions,
+        }
+    }
+
+    /// Functions whose display name is a likely typo of `name`, closest first
+    fn fuzzy(&self, name: &str) -> Vec<SymbolMatch<'_>> {
+        let name = name.to_lowercase();
+        let threshold = fuzzy_threshold(name.chars().count());
+        let mut matches: Vec<SymbolMatch> = self
+            .entries
+            .iter()
+            .filter_map(|indexed| {
+                let distance = edit_distance(&name, &indexed.entry.display_name.to_lowercase());
+                (distance <= threshold).then_some(SymbolMatch {
+                    entry: &indexed.entry,
+                    kind: MatchKind::Fuzzy(distance),
+                })
+            })
+            .collect();
+        matches.sort_by_key(|m| m.kind);
+        matches
+    }
+}
+
+/// Whether `query` names the function with this symbol and display name
+/// (exactly, as `Type::method` or as a path suffix; no fuzzy matching)
+pub fn name_matches(symbol: &str, display_name: &str, query: &str) -> bool {
+    let indexed = IndexedEntry::new(SymbolEntry {
+        symbol: symbol.to_string(),
+        display_name: display_name.to_string(),
+        relative_path: String::new(),
+    });
+    let query = query.trim();
+    indexed.match_kind(query, &segments(query)).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(symbol: &str, display_name: &str) -> SymbolEntry {
+        SymbolEntry {
+            symbol: symbol.to_string(),
+            display_name: display_name.to_string(),
+            relative_path: "src/field.rs".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_symbol_resolver() {
+        let resolver = SymbolResolver::new([
+            entry(
+                "rust-analyzer cargo dalek 4.1.3 backend/serial/u64/field/FieldElement51#sub().",
+                "sub",
+            ),
+            entry(
+                "rust-analyzer cargo dalek 4.1.3 backend/serial/u64/field/impl#[FieldElement51][AddAssign]add_assign().",
+                "add_assign",
+            ),
+            entry(
+                "rust-analyzer cargo dalek 4.1.3 backend/serial/u64/scalar/Scalar52#sub().",
+                "sub",
+            ),
+            entry("rust-analyzer cargo dalek 4.1.3 lemmas/lemma_mul_le().", "lemma_mul_le"),
+        ]);
+        assert_eq!(resolver.len(), 4);
+
+        let kinds = |query: &str| -> Vec<MatchKind> {
+            resolver.resolve(query).iter().map(|m| m.kind).collect()
+        };
+        assert_eq!(kinds("sub"), vec![MatchKind::Exact, MatchKind::Exact]);
+        assert_eq!(kinds("FieldElement51::sub"), vec![MatchKind::Qualified]);
+        assert_eq!(kinds("FieldElement51#sub"), vec![MatchKind::Qualified]);
+        assert_eq!(
+            kinds("FieldElement51::add_assign()"),
+            vec![MatchKind::Qualified]
+        );
+        assert_eq!(kinds("u64::scalar::Scalar52::sub"), vec![MatchKind::Suffix]);
+        assert_eq!(kinds("lemma_mul_el"), vec![MatchKind::Fuzzy(2)]);
+        assert!(kinds("unrelated").is_empty());
+
+        let symbol = "rust-analyzer cargo dalek 4.1.3 lemmas/lemma_mul_le().";
+        assert_eq!(resolver.resolve_one(symbol).unwrap().symbol, symbol);
+        assert_eq!(
+            resolver.resolve_one("lemma_mul_le").unwrap().symbol,
+            resolver.resolve_one("lemmas::lemma_mul_le").unwrap().symbol
+        );
+        match resolver.resolve_one("sub") {
+            Err(error @ ResolveError::Ambiguous { .. }) => {
+                assert!(error.to_string().contains("  2. sub (rust-analyzer"));
+            }
+            other => panic!("expected an ambiguous match, got {other:?}"),
+        }
+        assert_eq!(
+            resolver
+                .resolve_one("lemma_mul_el")
+                .unwrap_err()
+                .to_string(),
+            "No functions matching 'lemma_mul_el' found. Did you mean: lemmas::lemma_mul_le?"
+        );
+        assert_eq!(resolver.resolve_all("sub").unwrap().len(), 2);
+
+        assert!(name_matches(symbol, "lemma_mul_le", "lemma_mul_le"));
+        assert!(!name_matches(symbol, "lemma_mul_le", "lemma_mul_el"));
+    }
+}
@@ -0,0 +1,719 @@
+//! Pluggable parsing of SCIP symbol strings for different indexers.
+//!
+//! SCIP symbols share a common grammar:
+//! `<scheme> <manager> <package> <version> <descriptors>`, where descriptors
+//! encode namespaces (`name/`), types (`name#`), terms (`name.`), methods
+//! (`name().`) and so on. How those pieces map onto a readable identifier
+//! differs per language, so each indexer gets a [`SymbolScheme`]:
+//!
+//! - [`RustAnalyzerScheme`] - `rust-analyzer cargo ...` (also verus-analyzer)
+//! - [`TypeScriptScheme`] - `scip-typescript npm ...`
+//! - [`PythonScheme`] - `scip-python python ...`
+//! - [`GoScheme`] - `scip-go gomod ...`
+//!
+//! [`scheme_for_symbol`] picks the built-in scheme for a symbol; custom
+//! indexers can implement the trait themselves.
+//!
+//! Identifiers drop generic arguments, so distinct symbols (e.g. a method
+//! implemented for `Foo<u8>` and `Foo<u16>`) can map to the same one.
+//! [`SymbolNormalizer`] assigns identifiers to a whole set of symbols and
+//! tells colliding ones apart instead of merging them.
+
+use log::warn;
+use std::collections::{BTreeMap, HashMap};
+
+/// Remove `<...>` generic arguments, including nested ones (`Foo<Bar<T>>`
+/// becomes `Foo`). The `>` of a `->` arrow doesn't close a bracket.
+pub fn strip_generics(name: &str) -> String {
+    let mut depth = 0usize;
+    let mut previous = None;
+    let mut stripped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth > 0 && previous != Some('-') => depth -= 1,
+            _ if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    stripped
+}
+
+/// Kind of a single SCIP descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescriptorKind {
+    /// `name/`
+    Namespace,
+    /// `name#`
+    Type,
+    /// `name.`
+    Term,
+    /// `name(disambiguator).`
+    Method,
+    /// `[name]`
+    TypeParameter,
+    /// `(name)`
+    Parameter,
+    /// `name:`
+    Meta,
+    /// `name!`
+    Macro,
+}
+
+/// A single descriptor of a SCIP symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Descriptor {
+    pub name: String,
+    pub kind: DescriptorKind,
+}
+
+/// A SCIP symbol split into its header fields and descriptors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedSymbol {
+    pub scheme: String,
+    pub manager: String,
+    pub package: String,
+    pub version: String,
+    pub descriptors: Vec<Descriptor>,
+}
+
+impl ParsedSymbol {
+    /// Names of the namespace descriptors, outermost first
+    pub fn namespaces(&self) -> Vec<&str> {
+        self.descriptors
+            .iter()
+            .filter(|d| d.kind == DescriptorKind::Namespace)
+            .map(|d| d.name.as_str())
+            .collect()
+    }
+
+    /// Names of the type/term/method/macro descriptors, outermost first
+    pub fn members(&self) -> Vec<&str> {
+        self.descriptors
+            .iter()
+            .filter(|d| {
+                matches!(
+                    d.kind,
+                    DescriptorKind::Type
+                        | DescriptorKind::Term
+                        | DescriptorKind::Method
+                        | DescriptorKind::Macro
+                )
+            })
+            .map(|d| d.name.as_str())
+            .collect()
+    }
+}
+
+/// Parse a global SCIP symbol. Returns `None` for local symbols and for
+/// strings that don't follow the SCIP grammar.
+pub fn parse_symbol(symbol: &str) -> Option<ParsedSymbol> {
+    let ([scheme, manager, package, version], rest) = split_header(symbol)?;
+    let unset = |f: String| if f == "." { String::new() } else { f };
+    Some(ParsedSymbol {
+        scheme,
+        manager: unset(manager),
+        package: unset(package),
+        version: unset(version),
+        descriptors: parse_descriptors(rest)?,
+    })
+}
+
+/// The unescaped header fields (scheme, manager, package, version) of a
+/// global symbol and the descriptor part after them
+pub(crate) fn split_header(symbol: &str) -> Option<([String; 4], &str)> {
+    if symbol.starts_with("local ") {
+        return None;
+    }
+
+    // Header fields are separated by a single space; a double space escapes
+    // a space inside a field.
+    let mut fields = Vec::with_capacity(4);
+    let mut field = String::new();
+    let mut chars = symbol.char_indices().peekable();
+    let mut rest_start = None;
+    while let Some((i, c)) = chars.next() {
+        if c == ' ' {
+            if chars.peek().is_some_and(|(_, next)| *next == ' ') {
+                chars.next();
+                field.push(' ');
+                continue;
+            }
+            fields.push(std::mem::take(&mut field));
+            if fields.len() == 4 {
+                rest_start = Some(i + 1);
+                break;
+            }
+        } else {
+            field.push(c);
+        }
+    }
+
+    let rest = &symbol[rest_start?..];
+    Some((fields.try_into().ok()?, rest))
+}
+
+/// Parse the descriptor part of a SCIP symbol
+fn parse_descriptors(mut rest: &str) -> Option<Vec<Descriptor>> {
+    let mut descriptors = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            let end = inner.find(']')?;
+            descriptors.push(Descriptor {
+                name: inner[..end].to_string(),
+                kind: DescriptorKind::TypeParameter,
+            });
+            rest = &inner[end + 1..];
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix('(') {
+            let end = inner.find(')')?;
+            descriptors.push(Descriptor {
+                name: inner[..end].to_string(),
+                kind: DescriptorKind::Parameter,
+            });
+            rest = &inner[end + 1..];
+            continue;
+        }
+
+        let (name, after_name) = parse_name(rest)?;
+        let mut suffix = after_name.chars();
+        let kind = match suffix.next()? {
+            '/' => DescriptorKind::Namespace,
+            '#' => DescriptorKind::Type,
+            '.' => DescriptorKind::Term,
+            ':' => DescriptorKind::Meta,
+            '!' => DescriptorKind::Macro,
+            '(' => {
+                // Method with optional disambiguator: `name(disambiguator).`
+                let close = after_name.find(')')?;
+                if after_name[close + 1..].starts_with('.') {
+                    suffix = after_name[close + 2..].chars();
+                    DescriptorKind::Method
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        };
+        descriptors.push(Descriptor { name, kind });
+        rest = suffix.as_str();
+    }
+
+    Some(descriptors)
+}
+
+/// Parse a simple or backtick-escaped name, returning it and the remainder
+fn parse_name(input: &str) -> Option<(String, &str)> {
+    if let Some(inner) = input.strip_prefix('`') {
+        let mut name = String::new();
+        let mut chars = inner.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == '`' {
+                if chars.peek().is_some_and(|(_, next)| *next == '`') {
+                    chars.next();
+                    name.push('`');
+                    continue;
+                }
+                return Some((name, &inner[i + 1..]));
+            }
+            name.push(c);
+        }
+        return None;
+    }
+
+    let end = input
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '+' | '-' | '$')))
+        .unwrap_or(input.len());
+    if end == 0 {
+        return None;
+    }
+    Some((input[..end].to_string(), &input[end..]))
+}
+
+/// Join a qualified name with the display name unless it already ends with it
+fn with_display_name(path: String, separator: &str, display_name: &str) -> String {
+    if display_name.is_empty()
+        || path == display_name
+        || path.ends_with(&format!("{separator}{display_name}"))
+    {
+        path
+    } else if path.is_empty() {
+        display_name.to_string()
+    } else {
+        format!("{path}{separator}{display_name}")
+    }
+}
+
+/// How symbols of one SCIP indexer map onto readable identifiers
+pub trait SymbolScheme: Send + Sync {
+    /// Scheme name as it appears in the first field of the symbol
+    fn name(&self) -> &'static str;
+
+    /// Separator used between path components in identifiers
+    fn separator(&self) -> &'static str;
+
+    /// Check whether this scheme handles the given symbol
+    fn matches(&self, symbol: &str) -> bool {
+        symbol.split(' ').next() == Some(self.name())
+    }
+
+    /// Module (namespace) path of the symbol, e.g. `src/utils` or `pkg.mod`
+    fn module_path(&self, symbol: &str) -> String;
+
+    /// Clean, fully qualified identifier for the symbol ending with
+    /// `display_name`
+    fn to_path(&self, symbol: &str, display_name: &str) -> String;
+
+    /// Human-readable name of the symbol (its innermost member)
+    fn display_name(&self, symbol: &str) -> Option<String> {
+        parse_symbol(symbol)?
+            .members()
+            .last()
+            .map(|name| name.to_string())
+    }
+}
+
+/// Symbols from rust-analyzer and verus-analyzer:
+/// `rust-analyzer cargo my_crate 0.1.0 module/Type#method().`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RustAnalyzerScheme;
+
+impl SymbolScheme for RustAnalyzerScheme {
+    fn name(&self) -> &'static str {
+        "rust-analyzer"
+    }
+
+    fn separator(&self) -> &'static str {
+        "::"
+    }
+
+    fn module_path(&self, symbol: &str) -> String {
+        match parse_symbol(symbol) {
+            Some(parsed) => parsed.namespaces().join("::"),
+            None => String::new(),
+        }
+    }
+
+    fn to_path(&self, symbol: &str, display_name: &str) -> String {
+        let mut parts = symbol.split_whitespace();
+        let mut s = symbol;
+        if parts.next() == Some("rust-analyzer") && parts.next() == Some("cargo") {
+            if let Some(rest) = symbol.find("cargo ").and_then(|pos| symbol.get(pos + 6..)) {
+                s = rest;
+            }
+        }
+
+        if let Some(pos) = s.find(|c: char| c.is_ascii_digit()) {
+            if let Some(space_pos) = s[pos..].find(' ') {
+                s = s[(pos + space_pos + 1)..].trim();
+            }
+        }
+
+        let path = s
+            .replace(['/', '#'], "::")
+            .replace("impl#", "")
+            .replace('`', "");
+
+        let path = strip_generics(&path);
+        let path = path.trim_end_matches('.').trim_end_matches("()");
+
+        if path.ends_with(&format!("::{display_name}")) {
+            path.to_string()
+        } else {
+            format!("{path}::{display_name}")
+        }
+    }
+}
+
+/// Symbols from scip-typescript:
+/// ``scip-typescript npm my-pkg 1.0.0 src/`utils.ts`/Parser#parse().``
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TypeScriptScheme;
+
+impl SymbolScheme for TypeScriptScheme {
+    fn name(&self) -> &'static str {
+        "scip-typescript"
+    }
+
+    fn separator(&self) -> &'static str {
+        "."
+    }
+
+    fn module_path(&self, symbol: &str) -> String {
+        const EXTENSIONS: [&str; 7] = [".d.ts", ".tsx", ".ts", ".mts", ".cts", ".jsx", ".js"];
+
+        let Some(parsed) = parse_symbol(symbol) else {
+            return String::new();
+        };
+        let mut namespaces: Vec<&str> = parsed.namespaces();
+        if let Some(file) = namespaces.last_mut() {
+            if let Some(stem) = EXTENSIONS.iter().find_map(|ext| file.strip_suffix(ext)) {
+                *file = stem;
+            }
+        }
+        namespaces.join("/")
+    }
+
+    fn to_path(&self, symbol: &str, display_name: &str) -> String {
+        let Some(parsed) = parse_symbol(symbol) else {
+            return with_display_name(String::new(), ".", display_name);
+        };
+        let module = self.module_path(symbol);
+        let members = parsed.members().join(".");
+        let path = match (module.is_empty(), members.is_empty()) {
+            (_, true) => module,
+            (true, false) => members,
+            (false, false) => format!("{module}.{members}"),
+        };
+        with_display_name(path, ".", display_name)
+    }
+}
+
+/// Symbols from scip-python:
+/// ``scip-python python my-pkg 0.1.0 `my_pkg.utils`/Parser#parse().``
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PythonScheme;
+
+impl SymbolScheme for PythonScheme {
+    fn name(&self) -> &'static str {
+        "scip-python"
+    }
+
+    fn separator(&self) -> &'static str {
+        "."
+    }
+
+    fn module_path(&self, symbol: &str) -> String {
+        match parse_symbol(symbol) {
+            Some(parsed) => parsed
+                .namespaces()
+                .iter()
+                .map(|ns| ns.trim_end_matches(".__init__"))
+                .collect::<Vec<_>>()
+                .join("."),
+            None => String::new(),
+        }
+    }
+
+    fn to_path(&self, symbol: &str, display_name: &str) -> String {
+        let members = parse_symbol(symbol)
+            .map(|parsed| parsed.members().join("."))
+            .unwrap_or_default();
+        let module = self.module_path(symbol);
+        let path = [module, members]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(".");
+        with_display_name(path, ".", display_name)
+    }
+}
+
+/// Symbols from scip-go:
+/// ``scip-go gomod github.com/org/repo v1.2.0 `github.com/org/repo/pkg`/Server#Start().``
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GoScheme;
+
+impl SymbolScheme for GoScheme {
+    fn name(&self) -> &'static str {
+        "scip-go"
+    }
+
+    fn separator(&self) -> &'static str {
+        "."
+    }
+
+    fn module_path(&self, symbol: &str) -> String {
+        match parse_symbol(symbol) {
+            Some(parsed) => parsed.namespaces().join("/"),
+            None => String::new(),
+        }
+    }
+
+    fn to_path(&self, symbol: &str, display_name: &str) -> String {
+        let members = parse_symbol(symbol)
+            .map(|parsed| parsed.members().join("."))
+            .unwrap_or_default();
+        let module = self.module_path(symbol);
+        let path = [module, members]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(".");
+        with_display_name(path, ".", display_name)
+    }
+}
+
+static BUILTIN_SCHEMES: [&dyn SymbolScheme; 4] = [
+    &RustAnalyzerScheme,
+    &TypeScriptScheme,
+    &PythonScheme,
+    &GoScheme,
+];
+
+/// All built-in symbol schemes
+pub fn builtin_schemes() -> &'static [&'static dyn SymbolScheme] {
+    &BUILTIN_SCHEMES
+}
+
+/// Find the built-in scheme for a symbol, if any
+pub fn scheme_for_symbol(symbol: &str) -> Option<&'static dyn SymbolScheme> {
+    BUILTIN_SCHEMES
+        .iter()
+        .copied()
+        .find(|scheme| scheme.matches(symbol))
+}
+
+/// Distinct symbols that normalize to the same identifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentifierCollision {
+    pub identifier: String,
+    /// Sorted; the first keeps the identifier, the others get a `-2`, `-3`,
+    /// ... suffix
+    pub symbols: Vec<String>,
+}
+
+/// Unique identifiers for a set of symbols (see [`SymbolScheme::to_path`])
+///
+/// Symbols whose identifiers collide are told apart by a `-2`, `-3`, ...
+/// suffix in symbol order, and reported by [`SymbolNormalizer::collisions`].
+#[derive(Debug, Clone, Default)]
+pub struct SymbolNormalizer {
+    identifiers: HashMap<String, String>,
+    symbols: HashMap<String, String>,
+    collisions: Vec<IdentifierCollision>,
+}
+
+impl SymbolNormalizer {
+    /// Normalize `(symbol, display name)` pairs; repeated symbols are
+    /// normalized once
+    pub fn new<'a, I>(symbols: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut by_identifier: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for (symbol, display_name) in symbols {
+            let identifier = scheme_for_symbol(symbol)
+                .unwrap_or(&RustAnalyzerScheme)
+                .to_path(symbol, display_name);
+            by_identifier.entry(identifier).or_default().push(symbol);
+        }
+
+        let mut normalizer = Self::default();
+        for (identifier, mut symbols) in by_identifier {
+            symbols.sort_unstable();
+            symbols.dedup();
+            for (index, symbol) in symbols.iter().enumerate() {
+                let unique = match index {
+                    0 => identifier.clone(),
+                    _ => format!("{}-{}", identifier, index + 1),
+                };
+                normalizer
+                    .symbols
+                    .insert(unique.clone(), symbol.to_string());
+                normalizer.identifiers.insert(symbol.to_string(), unique);
+            }
+            if symbols.len() > 1 {
+                warn!(
+                    "{} symbols normalize to the identifier {}: {}",
+                    symbols.len(),
+                    identifier,
+                    symbols.join(", ")
+                );
+                normalizer.collisions.push(IdentifierCollision {
+                    identifier,
+                    symbols: symbols.iter().map(|s| s.to_string()).collect(),
+                });
+            }
+        }
+        normalizer
+    }
+
+    /// Unique identifier of a normalized symbol
+    pub fn identifier(&self, symbol: &str) -> Option<&str> {
+        self.identifiers.get(symbol).map(String::as_str)
+    }
+
+    /// Symbol whose unique identifier is `identifier`, e.g. to match atoms
+    /// back to call graph nodes
+    pub fn symbol(&self, identifier: &str) -> Option<&str> {
+        self.symbols.get(identifier).map(String::as_str)
+    }
+
+    /// Identifiers shared by several symbols, sorted by identifier
+    pub fn collisions(&self) -> &[IdentifierCollision] {
+        &self.collisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==========================================================================
+    // parse_symbol tests
+    // ==========================================================================
+
+    #[test]
+    fn test_parse_symbol_rust() {
+        let parsed =
+            parse_symbol("rust-analyzer cargo my_crate 0.1.0 module/Type#method().").unwrap();
+
+        assert_eq!(parsed.scheme, "rust-analyzer");
+        assert_eq!(parsed.manager, "cargo");
+        assert_eq!(parsed.package, "my_crate");
+        assert_eq!(parsed.version, "0.1.0");
+        assert_eq!(parsed.namespaces(), vec!["module"]);
+        assert_eq!(parsed.members(), vec!["Type", "method"]);
+        assert_eq!(parsed.descriptors[2].kind, DescriptorKind::Method);
+    }
+
+    #[test]
+    fn test_parse_symbol_escapes() {
+        let parsed =
+            parse_symbol("scip-typescript npm . . src/`foo.ts`/`weird``name`#(arg)[T]").unwrap();
+
+        assert_eq!(parsed.package, "");
+        assert_eq!(parsed.namespaces(), vec!["src", "foo.ts"]);
+        assert_eq!(parsed.members(), vec!["weird`name"]);
+        assert_eq!(parsed.descriptors[3].kind, DescriptorKind::Parameter);
+        assert_eq!(parsed.descriptors[4].kind, DescriptorKind::TypeParameter);
+    }
+
+    #[test]
+    fn test_parse_symbol_method_disambiguator() {
+        let parsed = parse_symbol("scip-go gomod m v1 `m/pkg`/Server#Start(+1).").unwrap();
+        assert_eq!(parsed.members(), vec!["Server", "Start"]);
+    }
+
+    #[test]
+    fn test_parse_symbol_local_and_invalid() {
+        assert!(parse_symbol("local 42").is_none());
+        assert!(parse_symbol("not a symbol").is_none());
+        assert!(parse_symbol("scheme m p v name?").is_none());
+    }
+
+    // ==========================================================================
+    // Scheme tests
+    // ==========================================================================
+
+    #[test]
+    fn test_scheme_for_symbol() {
+        let name = |symbol: &str| scheme_for_symbol(symbol).map(|s| s.name());
+
+        assert_eq!(
+            name("rust-analyzer cargo c 0.1.0 f()."),
+            Some("rust-analyzer")
+        );
+        assert_eq!(
+            name("scip-typescript npm p 1.0.0 f()."),
+            Some("scip-typescript")
+        );
+        assert_eq!(name("scip-python python p 0.1 f()."), Some("scip-python"));
+        assert_eq!(name("scip-go gomod p v1 f()."), Some("scip-go"));
+        assert_eq!(name("scip-java maven p 1 f()."), None);
+    }
+
+    #[test]
+    fn test_typescript_scheme() {
+        let symbol = "scip-typescript npm my-pkg 1.0.0 src/`utils.ts`/Parser#parse().";
+
+        assert_eq!(TypeScriptScheme.module_path(symbol), "src/utils");
+        assert_eq!(
+            TypeScriptScheme.to_path(symbol, "parse"),
+            "src/utils.Parser.parse"
+        );
+        assert_eq!(
+            TypeScriptScheme.display_name(symbol).as_deref(),
+            Some("parse")
+        );
+    }
+
+    #[test]
+    fn test_python_scheme() {
+        let symbol = "scip-python python my-pkg 0.1.0 `my_pkg.utils`/Parser#parse().";
+
+        assert_eq!(PythonScheme.module_path(symbol), "my_pkg.utils");
+        assert_eq!(
+            PythonScheme.to_path(symbol, "parse"),
+            "my_pkg.utils.Parser.parse"
+        );
+
+        let init = "scip-python python my-pkg 0.1.0 `my_pkg.__init__`/setup().";
+        assert_eq!(PythonScheme.to_path(init, "setup"), "my_pkg.setup");
+    }
+
+    #[test]
+    fn test_go_scheme() {
+        let symbol =
+            "scip-go gomod github.com/org/repo v1.2.0 `github.com/org/repo/pkg`/Server#Start().";
+
+        assert_eq!(GoScheme.module_path(symbol), "github.com/org/repo/pkg");
+        assert_eq!(
+            GoScheme.to_path(symbol, "Start"),
+            "github.com/org/repo/pkg.Server.Start"
+        );
+    }
+
+    #[test]
+    fn test_rust_scheme_matches_legacy_paths() {
+        let symbol = "rust-analyzer cargo my_crate 0.1.0 module/Type#method().";
+        assert_eq!(
+            RustAnalyzerScheme.to_path(symbol, "method"),
+            "module::Type::method"
+        );
+        assert_eq!(
+            RustAnalyzerScheme.module_path("rust-analyzer cargo c 0.1.0 a/b/f()."),
+            "a::b"
+        );
+    }
+
+    #[test]
+    fn test_strip_nested_generics() {
+        assert_eq!(strip_generics("Foo<Bar<T>>::new"), "Foo::new");
+        assert_eq!(strip_generics("impl#[Map<K, Vec<V>>]get"), "impl#[Map]get");
+        assert_eq!(strip_generics("apply<F: Fn(u8) -> u8>"), "apply");
+        assert_eq!(
+            RustAnalyzerScheme.to_path(
+                "rust-analyzer cargo c 0.1.0 Wrapper<Inner<T>>/get().",
+                "get"
+            ),
+            "Wrapper::get"
+        );
+    }
+
+    #[test]
+    fn test_symbol_normalizer_collisions() {
+        let u8_add = "rust-analyzer cargo c 0.1.0 field/Foo<u8>#add().";
+        let u16_add = "rust-analyzer cargo c 0.1.0 field/Foo<u16>#add().";
+        let sub = "rust-analyzer cargo c 0.1.0 field/Foo#sub().";
+        let normalizer = SymbolNormalizer::new([
+            (u8_add, "add"),
+            (u16_add, "add"),
+            (sub, "sub"),
+            (sub, "sub"),
+        ]);
+
+        assert_eq!(normalizer.identifier(u16_add), Some("field::Foo::add"));
+        assert_eq!(normalizer.identifier(u8_add), Some("field::Foo::add-2"));
+        assert_eq!(normalizer.identifier(sub), Some("field::Foo::sub"));
+        assert_eq!(normalizer.symbol("field::Foo::add-2"), Some(u8_add));
+        assert_eq!(normalizer.symbol("field::Foo"), None);
+        assert_eq!(
+            normalizer.collisions(),
+            [IdentifierCollision {
+                identifier: "field::Foo::add".to_string(),
+                symbols: vec![u16_add.to_string(), u8_add.to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_to_path_appends_missing_display_name() {
+        let symbol = "scip-go gomod m v1 `m/pkg`/";
+        assert_eq!(GoScheme.to_path(symbol, "init"), "m/pkg.init");
+    }
+}
@@ -0,0 +1,73 @@
+//! Call graph fixtures shared by the unit tests
+//!
+//! [`node`] builds a function in `src/lib.rs`; the `with_*` methods adjust
+//! it the way the extractor would fill it in:
+//!
+//! ```ignore
+//! let lemma = node("lemma", &[])
+//!     .in_file("src/lemmas.rs")
+//!     .with_attributes(&["#[verifier::external_body]"])
+//!     .with_body("proof fn lemma() {}");
+//! ```
+
+use crate::types::FunctionNode;
+use std::collections::HashSet;
+
+fn strings(items: &[&str]) -> HashSet<String> {
+    items.iter().map(|item| item.to_string()).collect()
+}
+
+/// A function `symbol` in `src/lib.rs` calling `callees`, displayed under
+/// its symbol
+pub(crate) fn node(symbol: &str, callees: &[&str]) -> FunctionNode {
+    FunctionNode {
+        symbol: symbol.to_string(),
+        display_name: symbol.to_string(),
+        file_path: "/project/src/lib.rs".to_string(),
+        relative_path: "src/lib.rs".to_string(),
+        callees: strings(callees),
+        ..Default::default()
+    }
+}
+
+impl FunctionNode {
+    /// Move the node to `relative_path` (under `/project`); an empty path
+    /// makes it an external function
+    pub(crate) fn in_file(mut self, relative_path: &str) -> Self {
+        self.file_path = if relative_path.is_empty() {
+            format!("external:{}", self.symbol)
+        } else {
+            format!("/project/{relative_path}")
+        };
+        self.relative_path = relative_path.to_string();
+        self
+    }
+
+    pub(crate) fn with_display_name(mut self, display_name: &str) -> Self {
+        self.display_name = display_name.to_string();
+        self
+    }
+
+    pub(crate) fn with_callers(mut self, callers: &[&str]) -> Self {
+        self.callers = strings(callers);
+        self
+    }
+
+    /// Body as extracted: from the `fn` line on, without doc comments and
+    /// attributes (see [`FunctionNode::with_attributes`])
+    pub(crate) fn with_body(mut self, body: &str) -> Self {
+        self.body = Some(body.to_string());
+        self
+    }
+
+    pub(crate) fn with_attributes(mut self, attributes: &[&str]) -> Self {
+        self.attributes = attributes.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    /// Make the node a type node (see `CallGraphOptions::with_types`)
+    pub(crate) fn as_type(mut self) -> Self {
+        self.node_kind = crate::types::NodeKind::Type;
+        self
+    }
+}
@@ -0,0 +1,138 @@
+//! What the exports show of a function's source
+//!
+//! DOT exports put it in node tooltips, which end up as `<title>` elements
+//! of the rendered SVG; the D3 and HTML exports write it to a separate
+//! bodies file that viewers load on demand, keeping the graph JSON small.
+//! Whole bodies of a large project easily add tens of megabytes, so the
+//! default is the signature only.
+
+use crate::call_graph::is_external_crate_node;
+use crate::export_markdown::signature_of;
+use crate::sampling::is_sample_placeholder;
+use crate::types::FunctionNode;
+
+/// Part of a function's body shown by the exports (the `--tooltips` CLI
+/// option)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TooltipContent {
+    /// Nothing
+    None,
+    /// The signature, up to the spec clauses or the opening brace
+    #[default]
+    Signature,
+    /// The first lines of the body
+    FirstLines(usize),
+    /// The whole body
+    Full,
+}
+
+impl TooltipContent {
+    /// The text shown for `node`, if any. Placeholder nodes of sampled graphs
+    /// and external crate nodes always show their description.
+    pub fn text(&self, node: &FunctionNode) -> Option<String> {
+        if is_sample_placeholder(node) || is_external_crate_node(node) {
+            return node.doc.clone();
+        }
+        let body = node.body.as_deref()?;
+        let text = match *self {
+            TooltipContent::None => return None,
+            TooltipContent::Signature => signature_of(body),
+            TooltipContent::FirstLines(lines) => {
+                let mut text = body.lines().take(lines).collect::<Vec<_>>().join("\n");
+                if body.lines().nth(lines).is_some() {
+                    text.push_str("\n...");
+                }
+                text
+            }
+            TooltipContent::Full => body.to_string(),
+        };
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// [`TooltipContent::text`] escaped for a quoted DOT attribute (empty if
+    /// there is none)
+    pub fn dot_tooltip(&self, node: &FunctionNode) -> String {
+        self.text(node)
+            .map(|text| {
+                text.replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\r', "")
+                    .replace('\n', "\\n")
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for TooltipContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TooltipContent::None => f.write_str("none"),
+            TooltipContent::Signature => f.write_str("signature"),
+            TooltipContent::FirstLines(lines) => write!(f, "lines:{lines}"),
+            TooltipContent::Full => f.write_str("full"),
+        }
+    }
+}
+
+impl std::str::FromStr for TooltipContent {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(TooltipContent::None),
+            "signature" => Ok(TooltipContent::Signature),
+            "full" => Ok(TooltipContent::Full),
+            _ => s
+                .strip_prefix("lines:")
+                .and_then(|lines| lines.parse().ok())
+                .map(TooltipContent::FirstLines)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown tooltip content '{s}' (expected none, signature, lines:N or full)"
+                    )
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tooltip_content() {
+        let node = FunctionNode {
+            symbol: "field/add().".to_string(),
+            display_name: "add".to_string(),
+            file_path: "/p/src/field.rs".to_string(),
+            relative_path: "src/field.rs".to_string(),
+            body: Some(
+                "#[inline]\npub fn add(a: u64,\n    b: u64) -> u64\n    requires a < 10,\n{\n    \"sum\";\n    a + b\n}"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+
+        assert_eq!(TooltipContent::None.text(&node), None);
+        assert_eq!(
+            TooltipContent::Signature.text(&node).as_deref(),
+            Some("pub fn add(a: u64, b: u64) -> u64")
+        );
+        assert_eq!(
+            TooltipContent::FirstLines(2).text(&node).as_deref(),
+            Some("#[inline]\npub fn add(a: u64,\n...")
+        );
+        assert_eq!(TooltipContent::Full.text(&node), node.body);
+        assert!(TooltipContent::Full
+            .dot_tooltip(&node)
+            .contains("{\\n    \\\"sum\\\";"));
+
+        for content in ["none", "signature", "lines:5", "full"] {
+            assert_eq!(
+                content.parse::<TooltipContent>().unwrap().to_string(),
+                content
+            );
+        }
+        assert!("lines:x".parse::<TooltipContent>().is_err());
+    }
+}
@@ -2,12 +2,14 @@
 //!
 //! This module contains all the data structures used across the scip-core library:
 //! - SCIP index types (from SCIP JSON format)
-//! - Call graph types (nodes, edges, occurrences)
+//! - Call graph types (nodes, edges, occurrences, the [`CallGraph`] itself)
 //! - D3.js export types (for web visualization)
 //! - Verus-specific types (function modes, sections)
 
+use crate::verification::VerificationAttempt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
 
 // =============================================================================
 // SCIP Index Types (from SCIP JSON format)
@@ -21,28 +23,39 @@ pub struct ScipIndex {
 }
 
 /// SCIP metadata about the indexed project
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// Fields other than the ones the call graph needs (project root, document
+/// paths, occurrence ranges and symbols) default when missing, so indexes
+/// from older or newer SCIP emitters still parse.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Metadata {
+    #[serde(default)]
     pub tool_info: ToolInfo,
     pub project_root: String,
+    #[serde(default)]
     pub text_document_encoding: i32,
 }
 
 /// Information about the tool that generated the SCIP index
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ToolInfo {
+    #[serde(default)]
     pub name: String,
+    #[serde(default)]
     pub version: String,
 }
 
 /// A document (source file) in the SCIP index
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Document {
+    #[serde(default)]
     pub language: String,
     pub relative_path: String,
+    #[serde(default)]
     pub occurrences: Vec<Occurrence>,
     #[serde(default)]
     pub symbols: Vec<Symbol>,
+    #[serde(default)]
     pub position_encoding: i32,
 }
 
@@ -59,20 +72,25 @@ pub struct Occurrence {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Symbol {
     pub symbol: String,
+    #[serde(default)]
     pub kind: i32,
     pub display_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub documentation: Option<Vec<String>>,
+    #[serde(default)]
     pub signature_documentation: SignatureDocumentation,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enclosing_symbol: Option<String>,
 }
 
 /// Signature documentation for a symbol
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SignatureDocumentation {
+    #[serde(default)]
     pub language: String,
+    #[serde(default)]
     pub text: String,
+    #[serde(default)]
     pub position_encoding: i32,
 }
 
@@ -81,7 +99,8 @@ pub struct SignatureDocumentation {
 // =============================================================================
 
 /// Represents where a function call occurs within its caller
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CallLocation {
     /// Call occurs in a `requires` clause (precondition)
     Precondition,
@@ -102,15 +121,19 @@ impl CallLocation {
 }
 
 /// A callee occurrence with its location information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalleeOccurrence {
     pub symbol: String,
     pub line: i32,
+    /// 0-based character column of the call, when known
+    pub column: Option<i32>,
     pub location: Option<CallLocation>,
+    /// Declaration kinds of caller and callee (set once bodies are extracted)
+    pub edge_kind: Option<EdgeKind>,
 }
 
 /// Represents a node in the call graph
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FunctionNode {
     pub symbol: String,
     pub display_name: String,
@@ -118,9 +141,339 @@ pub struct FunctionNode {
     pub relative_path: String,
     pub callers: HashSet<String>,
     pub callees: HashSet<String>,
+    /// Callees referenced from the requires/ensures clauses (a subset of
+    /// `callees`)
+    pub spec_refs: HashSet<String>,
     pub callee_occurrences: Vec<CalleeOccurrence>,
     pub range: Vec<i32>,
     pub body: Option<String>,
+    /// `///` doc comment preceding the function, without the slashes
+    pub doc: Option<String>,
+    /// Attributes preceding the function, e.g. `#[verifier::external_body]`
+    pub attributes: Vec<String>,
+    /// 1-based line of the definition, once the body is extracted
+    pub start_line: Option<usize>,
+    /// 1-based last line of the body, once the body is extracted
+    pub end_line: Option<usize>,
+    /// `requires` clauses as written (whitespace collapsed), when the body
+    /// parses; see [`crate::spec_text`]
+    pub requires_text: Vec<String>,
+    /// `ensures` clauses as written, like `requires_text`
+    pub ensures_text: Vec<String>,
+    /// Signature as written (whitespace collapsed), without specs or body,
+    /// when the body parses; see [`crate::spec_text::signature_text`]
+    pub signature: Option<String>,
+    /// Function, or a type, constant or static used by functions
+    pub node_kind: NodeKind,
+}
+
+impl FunctionNode {
+    /// Whether the node is a type, constant or static rather than a function
+    pub fn is_type(&self) -> bool {
+        self.node_kind == NodeKind::Type
+    }
+
+    /// Callees called at runtime: all callees except those only referenced
+    /// from spec clauses
+    pub fn calls(&self) -> impl Iterator<Item = &String> {
+        self.callees.iter().filter(|callee| {
+            !self.spec_refs.contains(*callee)
+                || self.callee_occurrences.iter().any(|occurrence| {
+                    occurrence.symbol == **callee
+                        && !matches!(
+                            occurrence.location,
+                            Some(CallLocation::Precondition | CallLocation::Postcondition)
+                        )
+                })
+        })
+    }
+
+    /// Every call site of `callee` in this function, sorted by position,
+    /// counting each position once
+    pub fn call_sites(&self, callee: &str) -> Vec<&CalleeOccurrence> {
+        let mut sites: Vec<&CalleeOccurrence> = self
+            .callee_occurrences
+            .iter()
+            .filter(|occurrence| occurrence.symbol == callee)
+            .collect();
+        sites.sort_by_key(|occurrence| (occurrence.line, occurrence.column));
+        sites.dedup_by_key(|occurrence| (occurrence.line, occurrence.column));
+        sites
+    }
+}
+
+/// A call graph: function nodes keyed by symbol.
+///
+/// Unlike a bare `HashMap<String, FunctionNode>`, a `CallGraph` keeps
+/// `callers` and `callees` symmetric: whenever both ends of a call are in
+/// the graph, the caller lists the callee and the callee lists the caller.
+/// Callees outside the graph (e.g. filtered out or unresolved) are kept as
+/// one-sided entries.
+///
+/// Read access goes through `Deref` to the underlying map, so existing code
+/// using `get`, `values`, `len` or indexing keeps working. Convert from and
+/// into a `HashMap` with `From`/`Into`; converting from a map repairs any
+/// missing reverse links.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    nodes: HashMap<String, FunctionNode>,
+}
+
+impl CallGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The node for `symbol`, if present
+    pub fn node(&self, symbol: &str) -> Option<&FunctionNode> {
+        self.nodes.get(symbol)
+    }
+
+    /// Insert a node, linking it with the nodes it calls and is called by.
+    ///
+    /// If a node with the same symbol was present it is returned; its
+    /// outgoing calls are replaced by those of `node`, while functions that
+    /// called it keep calling the new node.
+    pub fn add_node(&mut self, mut node: FunctionNode) -> Option<FunctionNode> {
+        let symbol = node.symbol.clone();
+        let previous = self.nodes.remove(&symbol);
+
+        if let Some(previous) = &previous {
+            for callee in previous.callees.difference(&node.callees) {
+                if let Some(callee_node) = self.nodes.get_mut(callee) {
+                    callee_node.callers.remove(&symbol);
+                }
+            }
+            for caller in &previous.callers {
+                if self
+                    .nodes
+                    .get(caller)
+                    .is_some_and(|caller_node| caller_node.callees.contains(&symbol))
+                {
+                    node.callers.insert(caller.clone());
+                }
+            }
+        }
+
+        self.nodes.insert(symbol.clone(), node);
+        self.link(&symbol);
+        previous
+    }
+
+    /// Remove a node and every edge pointing at it from nodes in the graph
+    pub fn remove_node(&mut self, symbol: &str) -> Option<FunctionNode> {
+        let node = self.nodes.remove(symbol)?;
+        for callee in &node.callees {
+            if let Some(callee_node) = self.nodes.get_mut(callee) {
+                callee_node.callers.remove(symbol);
+            }
+        }
+        for caller in &node.callers {
+            if let Some(caller_node) = self.nodes.get_mut(caller) {
+                caller_node.callees.remove(symbol);
+                caller_node.spec_refs.remove(symbol);
+                caller_node
+                    .callee_occurrences
+                    .retain(|occurrence| occurrence.symbol != symbol);
+            }
+        }
+        Some(node)
+    }
+
+    /// Record a call from `caller` to `callee`, updating both nodes.
+    ///
+    /// Returns `false` (and changes nothing) unless both functions are in
+    /// the graph. Call occurrences are not touched, since their line and
+    /// location are unknown here.
+    pub fn add_edge(&mut self, caller: &str, callee: &str) -> bool {
+        if !self.nodes.contains_key(caller) || !self.nodes.contains_key(callee) {
+            return false;
+        }
+        if let Some(caller_node) = self.nodes.get_mut(caller) {
+            caller_node.callees.insert(callee.to_string());
+        }
+        if let Some(callee_node) = self.nodes.get_mut(callee) {
+            callee_node.callers.insert(caller.to_string());
+        }
+        true
+    }
+
+    /// Nodes called by `symbol` that are in the graph, sorted by symbol
+    pub fn callees_of(&self, symbol: &str) -> Vec<&FunctionNode> {
+        self.neighbors(symbol, |node| &node.callees)
+    }
+
+    /// Nodes calling `symbol` that are in the graph, sorted by symbol
+    pub fn callers_of(&self, symbol: &str) -> Vec<&FunctionNode> {
+        self.neighbors(symbol, |node| &node.callers)
+    }
+
+    /// Nodes without callers in the graph (entry points), sorted by symbol
+    pub fn roots(&self) -> Vec<&FunctionNode> {
+        self.sorted_nodes(|node| !node.callers.iter().any(|c| self.nodes.contains_key(c)))
+    }
+
+    /// Nodes without callees in the graph, sorted by symbol
+    pub fn leaves(&self) -> Vec<&FunctionNode> {
+        self.sorted_nodes(|node| !node.callees.iter().any(|c| self.nodes.contains_key(c)))
+    }
+
+    /// The subgraph induced by `symbols`.
+    ///
+    /// Symbols not in the graph are ignored. Edges, including call
+    /// occurrences, are kept only when both ends are selected.
+    pub fn subgraph<I, S>(&self, symbols: I) -> CallGraph
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let selected: HashSet<&str> = symbols
+            .into_iter()
+            .filter_map(|symbol| self.nodes.get_key_value(symbol.as_ref()))
+            .map(|(symbol, _)| symbol.as_str())
+            .collect();
+
+        let nodes = selected
+            .iter()
+            .map(|symbol| {
+                let mut node = self.nodes[*symbol].clone();
+                node.callees
+                    .retain(|callee| selected.contains(callee.as_str()));
+                node.spec_refs
+                    .retain(|callee| selected.contains(callee.as_str()));
+                node.callers
+                    .retain(|caller| selected.contains(caller.as_str()));
+                node.callee_occurrences
+                    .retain(|occurrence| selected.contains(occurrence.symbol.as_str()));
+                (symbol.to_string(), node)
+            })
+            .collect();
+        CallGraph { nodes }
+    }
+
+    /// The underlying map of nodes
+    pub fn as_map(&self) -> &HashMap<String, FunctionNode> {
+        &self.nodes
+    }
+
+    /// Mutable access for the graph builders, which maintain symmetry
+    /// themselves
+    pub(crate) fn nodes_mut(&mut self) -> &mut HashMap<String, FunctionNode> {
+        &mut self.nodes
+    }
+
+    fn neighbors<F>(&self, symbol: &str, next: F) -> Vec<&FunctionNode>
+    where
+        F: Fn(&FunctionNode) -> &HashSet<String>,
+    {
+        let mut neighbors: Vec<&FunctionNode> = self
+            .nodes
+            .get(symbol)
+            .into_iter()
+            .flat_map(|node| next(node).iter())
+            .filter_map(|neighbor| self.nodes.get(neighbor))
+            .collect();
+        neighbors.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        neighbors
+    }
+
+    fn sorted_nodes<F>(&self, keep: F) -> Vec<&FunctionNode>
+    where
+        F: Fn(&FunctionNode) -> bool,
+    {
+        let mut nodes: Vec<&FunctionNode> = self.nodes.values().filter(|node| keep(node)).collect();
+        nodes.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        nodes
+    }
+
+    /// Add the reverse of every edge touching `symbol` whose other end is
+    /// in the graph
+    fn link(&mut self, symbol: &str) {
+        let Some(node) = self.nodes.get(symbol) else {
+            return;
+        };
+        let callees: Vec<String> = node.callees.iter().cloned().collect();
+        let callers: Vec<String> = node.callers.iter().cloned().collect();
+
+        for callee in callees {
+            if let Some(callee_node) = self.nodes.get_mut(&callee) {
+                callee_node.callers.insert(symbol.to_string());
+            }
+        }
+        for caller in callers {
+            if let Some(caller_node) = self.nodes.get_mut(&caller) {
+                caller_node.callees.insert(symbol.to_string());
+            }
+        }
+    }
+}
+
+impl Deref for CallGraph {
+    type Target = HashMap<String, FunctionNode>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.nodes
+    }
+}
+
+impl From<HashMap<String, FunctionNode>> for CallGraph {
+    fn from(nodes: HashMap<String, FunctionNode>) -> Self {
+        let symbols: Vec<String> = nodes.keys().cloned().collect();
+        let mut call_graph = CallGraph { nodes };
+        for symbol in &symbols {
+            call_graph.link(symbol);
+        }
+        call_graph
+    }
+}
+
+impl From<CallGraph> for HashMap<String, FunctionNode> {
+    fn from(call_graph: CallGraph) -> Self {
+        call_graph.nodes
+    }
+}
+
+/// Serialized as the map of nodes by symbol
+impl Serialize for CallGraph {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.nodes.serialize(serializer)
+    }
+}
+
+/// Deserialized from a map of nodes, repairing missing reverse links like
+/// `From`
+impl<'de> Deserialize<'de> for CallGraph {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashMap::<String, FunctionNode>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl FromIterator<FunctionNode> for CallGraph {
+    fn from_iter<I: IntoIterator<Item = FunctionNode>>(iter: I) -> Self {
+        let nodes: HashMap<String, FunctionNode> = iter
+            .into_iter()
+            .map(|node| (node.symbol.clone(), node))
+            .collect();
+        nodes.into()
+    }
+}
+
+impl<'a> IntoIterator for &'a CallGraph {
+    type Item = (&'a String, &'a FunctionNode);
+    type IntoIter = std::collections::hash_map::Iter<'a, String, FunctionNode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.iter()
+    }
+}
+
+impl IntoIterator for CallGraph {
+    type Item = (String, FunctionNode);
+    type IntoIter = std::collections::hash_map::IntoIter<String, FunctionNode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.into_iter()
+    }
 }
 
 /// An atom represents a function with its dependencies (for JSON export)
@@ -135,6 +488,284 @@ pub struct Atom {
     pub relative_path: String,
     pub file_name: String,
     pub parent_folder: String,
+    /// `///` doc comment preceding the function, without the slashes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Attributes preceding the function, e.g. `#[verifier::external_body]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    /// 1-based first and last line of the function in its file, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    /// `requires` and `ensures` clauses as written (whitespace collapsed),
+    /// when the body parses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires_text: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ensures_text: Vec<String>,
+    /// Parsed signature (`signature`, `params`, `return_type`, `generics`,
+    /// `is_pub`), when the body could be parsed
+    #[serde(flatten)]
+    pub signature: Option<FunctionSignature>,
+
+    /// Call graph metrics (`fan_in`, `fan_out`, `max_depth`,
+    /// `transitive_callees`, `betweenness`, `pagerank`), when computed
+    #[serde(flatten)]
+    pub graph_metrics: Option<GraphMetrics>,
+
+    /// For each `ensures` clause, the called proof functions whose
+    /// postconditions relate to it, when computed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spec_support: Vec<EnsuresSupport>,
+}
+
+/// Called proof functions whose postconditions relate to one `ensures`
+/// clause, computed by `verus_metrics::spec_support`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnsuresSupport {
+    /// The clause as written (whitespace collapsed)
+    pub clause: String,
+    /// Identifiers of the supporting proof functions; empty if no called
+    /// lemma relates to the clause
+    pub lemmas: Vec<String>,
+}
+
+/// Signature of a function, parsed from its source
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionSignature {
+    /// The signature as written (whitespace collapsed), without attributes,
+    /// specs or body, e.g. `pub fn add(a: u64, b: u64) -> u64`
+    pub signature: String,
+    pub params: Vec<FunctionParam>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<String>,
+    /// Generic parameters, e.g. `'a`, `T: Clone`, `const N: usize`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generics: Vec<String>,
+    /// Declared `pub` (restricted visibility such as `pub(crate)` is not)
+    pub is_pub: bool,
+}
+
+/// A function parameter; `self` receivers have the name `self`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// Position of a function in the call graph, computed by
+/// `analysis::graph_metrics`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphMetrics {
+    /// Number of other functions calling this one
+    pub fan_in: usize,
+    /// Number of other functions this one calls
+    pub fan_out: usize,
+    /// Longest chain of calls below the function; calls within a recursion
+    /// cycle don't add to it
+    pub max_depth: usize,
+    /// Number of other functions reachable through calls
+    pub transitive_callees: usize,
+    /// Share of shortest call paths between other functions passing through
+    /// this one, from 0 to 1
+    pub betweenness: f64,
+    /// PageRank along call edges; sums to 1 over the graph
+    pub pagerank: f64,
+}
+
+impl Atom {
+    /// JSON Schema (draft 2020-12) describing a single atom
+    pub fn json_schema() -> serde_json::Value {
+        let string =
+            |description: &str| serde_json::json!({ "type": "string", "description": description });
+
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Atom",
+            "description": "A function with its dependencies",
+            "type": "object",
+            "properties": {
+                "identifier": string("Unique path-like identifier of the function"),
+                "statement_type": string("Kind of statement: \"function\", or \"type\" for types, constants and statics"),
+                "deps": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Identifiers of the functions this atom calls",
+                },
+                "body": string("Source text of the function"),
+                "display_name": string("Short human-readable name"),
+                "full_path": string("Absolute path of the defining file"),
+                "relative_path": string("Path of the defining file relative to the project root"),
+                "file_name": string("File name of the defining file"),
+                "parent_folder": string("Name of the directory containing the defining file"),
+                "doc": string("Doc comment preceding the function, without the slashes"),
+                "attributes": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Attributes preceding the function, e.g. #[verifier::external_body]",
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "1-based first line of the function in its file",
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "1-based last line of the function in its file",
+                },
+                "signature": string("Signature as written, without attributes, specs or body"),
+                "params": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "type": { "type": "string" },
+                        },
+                        "required": ["name", "type"],
+                    },
+                    "description": "Parameters, in order",
+                },
+                "return_type": string("Return type, absent for unit"),
+                "generics": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Generic parameters",
+                },
+                "is_pub": {
+                    "type": "boolean",
+                    "description": "Whether the function is declared `pub`",
+                },
+                "fan_in": {
+                    "type": "integer",
+                    "description": "Number of other functions calling this one",
+                },
+                "fan_out": {
+                    "type": "integer",
+                    "description": "Number of other functions this one calls",
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "description": "Longest chain of calls below the function, ignoring recursion cycles",
+                },
+                "transitive_callees": {
+                    "type": "integer",
+                    "description": "Number of other functions reachable through calls",
+                },
+                "betweenness": {
+                    "type": "number",
+                    "description": "Betweenness centrality, from 0 to 1",
+                },
+                "pagerank": {
+                    "type": "number",
+                    "description": "PageRank along call edges",
+                },
+                "spec_support": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "clause": { "type": "string" },
+                            "lemmas": { "type": "array", "items": { "type": "string" } },
+                        },
+                        "required": ["clause", "lemmas"],
+                    },
+                    "description": "For each ensures clause, the called proof functions whose postconditions relate to it",
+                },
+            },
+            "required": [
+                "identifier",
+                "statement_type",
+                "deps",
+                "body",
+                "display_name",
+                "full_path",
+                "relative_path",
+                "file_name",
+                "parent_folder",
+            ],
+        })
+    }
+}
+
+/// Version of the atoms JSON output format
+///
+/// - `V1`: a bare JSON array of atoms
+/// - `V2`: an `AtomsFile` envelope `{"format_version": 2, "atoms": [...]}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub enum FormatVersion {
+    V1 = 1,
+    V2 = 2,
+}
+
+impl FormatVersion {
+    /// Version written by `write_call_graph_as_atoms_json`
+    pub const CURRENT: FormatVersion = FormatVersion::V2;
+}
+
+impl From<FormatVersion> for u32 {
+    fn from(version: FormatVersion) -> Self {
+        version as u32
+    }
+}
+
+impl TryFrom<u32> for FormatVersion {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(FormatVersion::V1),
+            2 => Ok(FormatVersion::V2),
+            other => Err(format!("unsupported atoms format_version: {other}")),
+        }
+    }
+}
+
+/// Versioned envelope for atoms JSON output
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AtomsFile {
+    pub format_version: FormatVersion,
+    pub atoms: Vec<Atom>,
+}
+
+impl AtomsFile {
+    /// Wrap atoms in an envelope with the current format version
+    pub fn new(atoms: Vec<Atom>) -> Self {
+        Self {
+            format_version: FormatVersion::CURRENT,
+            atoms,
+        }
+    }
+
+    /// JSON Schema (draft 2020-12) describing the versioned envelope
+    pub fn json_schema() -> serde_json::Value {
+        let mut atom = Atom::json_schema();
+        if let Some(atom) = atom.as_object_mut() {
+            atom.remove("$schema");
+        }
+
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "AtomsFile",
+            "description": "Versioned atoms JSON output",
+            "type": "object",
+            "properties": {
+                "format_version": {
+                    "type": "integer",
+                    "const": u32::from(FormatVersion::CURRENT),
+                },
+                "atoms": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/Atom" },
+                },
+            },
+            "required": ["format_version", "atoms"],
+            "$defs": { "Atom": atom },
+        })
+    }
 }
 
 // =============================================================================
@@ -142,10 +773,11 @@ pub struct Atom {
 // =============================================================================
 
 /// Declaration kind (Verus: exec/proof/spec, Lean: theorem/def/axiom/...)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DeclKind {
     /// Executable code (default Rust functions)
+    #[default]
     Exec,
     /// Proof functions (lemmas, verification helpers)
     Proof,
@@ -163,6 +795,171 @@ impl DeclKind {
     }
 }
 
+/// What a call graph node stands for
+///
+/// Type nodes (structs, enums, unions, type aliases, constants and statics)
+/// are only built with `CallGraphOptions::with_types`; edges from functions
+/// to them are "references" rather than calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    #[default]
+    Function,
+    Type,
+}
+
+impl NodeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeKind::Function => "function",
+            NodeKind::Type => "type",
+        }
+    }
+
+    pub fn is_function(&self) -> bool {
+        *self == NodeKind::Function
+    }
+}
+
+impl std::str::FromStr for NodeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "function" => Ok(NodeKind::Function),
+            "type" => Ok(NodeKind::Type),
+            _ => Err(format!(
+                "unknown node kind: {s} (expected function or type)"
+            )),
+        }
+    }
+}
+
+/// Verification outcome of a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationStatus {
+    Verified,
+    Failed,
+    Unverified,
+}
+
+impl VerificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationStatus::Verified => "verified",
+            VerificationStatus::Failed => "failed",
+            VerificationStatus::Unverified => "unverified",
+        }
+    }
+}
+
+/// Kind of a call edge, given by the declaration kinds of caller and callee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    ExecToExec,
+    ExecToProof,
+    ExecToSpec,
+    ProofToExec,
+    ProofToProof,
+    ProofToSpec,
+    SpecToExec,
+    SpecToProof,
+    SpecToSpec,
+    /// Call inside a spawned task (`tokio::spawn(...)`, `thread::spawn`) or
+    /// an `async` block, crossing a concurrency boundary
+    Spawn,
+}
+
+impl EdgeKind {
+    /// The kinds given by declaration kinds (every kind but `Spawn`)
+    pub const ALL: [EdgeKind; 9] = [
+        EdgeKind::ExecToExec,
+        EdgeKind::ExecToProof,
+        EdgeKind::ExecToSpec,
+        EdgeKind::ProofToExec,
+        EdgeKind::ProofToProof,
+        EdgeKind::ProofToSpec,
+        EdgeKind::SpecToExec,
+        EdgeKind::SpecToProof,
+        EdgeKind::SpecToSpec,
+    ];
+
+    /// Edge kind for a call from a `caller` function to a `callee` function
+    pub fn new(caller: DeclKind, callee: DeclKind) -> Self {
+        match (caller, callee) {
+            (DeclKind::Exec, DeclKind::Exec) => EdgeKind::ExecToExec,
+            (DeclKind::Exec, DeclKind::Proof) => EdgeKind::ExecToProof,
+            (DeclKind::Exec, DeclKind::Spec) => EdgeKind::ExecToSpec,
+            (DeclKind::Proof, DeclKind::Exec) => EdgeKind::ProofToExec,
+            (DeclKind::Proof, DeclKind::Proof) => EdgeKind::ProofToProof,
+            (DeclKind::Proof, DeclKind::Spec) => EdgeKind::ProofToSpec,
+            (DeclKind::Spec, DeclKind::Exec) => EdgeKind::SpecToExec,
+            (DeclKind::Spec, DeclKind::Proof) => EdgeKind::SpecToProof,
+            (DeclKind::Spec, DeclKind::Spec) => EdgeKind::SpecToSpec,
+        }
+    }
+
+    /// Declaration kind of the calling function (exec for spawns)
+    pub fn caller(&self) -> DeclKind {
+        match self {
+            EdgeKind::ExecToExec
+            | EdgeKind::ExecToProof
+            | EdgeKind::ExecToSpec
+            | EdgeKind::Spawn => DeclKind::Exec,
+            EdgeKind::ProofToExec | EdgeKind::ProofToProof | EdgeKind::ProofToSpec => {
+                DeclKind::Proof
+            }
+            EdgeKind::SpecToExec | EdgeKind::SpecToProof | EdgeKind::SpecToSpec => DeclKind::Spec,
+        }
+    }
+
+    /// Declaration kind of the called function (exec for spawns)
+    pub fn callee(&self) -> DeclKind {
+        match self {
+            EdgeKind::ExecToExec
+            | EdgeKind::ProofToExec
+            | EdgeKind::SpecToExec
+            | EdgeKind::Spawn => DeclKind::Exec,
+            EdgeKind::ExecToProof | EdgeKind::ProofToProof | EdgeKind::SpecToProof => {
+                DeclKind::Proof
+            }
+            EdgeKind::ExecToSpec | EdgeKind::ProofToSpec | EdgeKind::SpecToSpec => DeclKind::Spec,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::ExecToExec => "exec_to_exec",
+            EdgeKind::ExecToProof => "exec_to_proof",
+            EdgeKind::ExecToSpec => "exec_to_spec",
+            EdgeKind::ProofToExec => "proof_to_exec",
+            EdgeKind::ProofToProof => "proof_to_proof",
+            EdgeKind::ProofToSpec => "proof_to_spec",
+            EdgeKind::SpecToExec => "spec_to_exec",
+            EdgeKind::SpecToProof => "spec_to_proof",
+            EdgeKind::SpecToSpec => "spec_to_spec",
+            EdgeKind::Spawn => "spawn",
+        }
+    }
+}
+
+impl std::str::FromStr for EdgeKind {
+    type Err = String;
+
+    /// Parse `proof_to_proof`, `proof-to-proof` or `proof->proof`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase().replace(['-', '>'], "_");
+        let normalized = normalized.replace("__", "_to_");
+        EdgeKind::ALL
+            .into_iter()
+            .chain([EdgeKind::Spawn])
+            .find(|kind| kind.as_str() == normalized)
+            .ok_or_else(|| format!("unknown edge kind: {s}"))
+    }
+}
+
 /// Represents the line ranges of different sections in a Verus function
 #[derive(Debug, Clone, Default)]
 pub struct FunctionSections {
@@ -174,6 +971,26 @@ pub struct FunctionSections {
     pub ensures_range: Option<(i32, i32)>,
     /// Line number where the function body starts (the `{`) - 0-based
     pub body_start_line: Option<i32>,
+    /// Exact extent of the `requires` clause, when the function tokenizes
+    pub requires_span: Option<SourceSpan>,
+    /// Exact extent of the `ensures` (and `decreases`) clauses, when the
+    /// function tokenizes
+    pub ensures_span: Option<SourceSpan>,
+}
+
+/// A range of source positions: 0-based lines and character columns, end
+/// exclusive
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub start: (i32, i32),
+    pub end: (i32, i32),
+}
+
+impl SourceSpan {
+    /// Whether the position `(line, column)` lies within the span
+    pub fn contains(&self, line: i32, column: i32) -> bool {
+        self.start <= (line, column) && (line, column) < self.end
+    }
 }
 
 // =============================================================================
@@ -181,9 +998,17 @@ pub struct FunctionSections {
 // =============================================================================
 
 /// A node in the D3.js force-directed graph
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// The optional fields are filled in by enrichment steps (the pipeline's
+/// verification step, `audit_assumptions`) on the typed graph before it is
+/// written.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct D3Node {
     pub id: String,
+    /// ID that stays the same across runs (see `export_d3::assign_stable_ids`);
+    /// `id` is the symbol and changes with crate versions and generics
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stable_id: String,
     pub display_name: String,
     pub symbol: String,
     pub full_path: String,
@@ -194,6 +1019,8 @@ pub struct D3Node {
     pub start_line: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end_line: Option<usize>,
+    /// Drawn as a project source (matched by the export's highlight filters;
+    /// the name predates configurable filters)
     pub is_libsignal: bool,
     /// Functions this function calls (outgoing edges) - scip_names for O(1) lookup
     pub dependencies: Vec<String>,
@@ -201,19 +1028,71 @@ pub struct D3Node {
     pub dependents: Vec<String>,
     /// Declaration kind: exec, proof, or spec
     pub kind: DeclKind,
+    /// Function or type node (omitted for functions)
+    #[serde(default, skip_serializing_if = "NodeKind::is_function")]
+    pub node_kind: NodeKind,
+    /// Workspace member crate defining this function (workspace projects only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crate_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_status: Option<VerificationStatus>,
+    /// SMT time spent verifying this function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_time_ms: Option<f64>,
+    /// SMT resource count, when reported by Verus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_rlimit: Option<u64>,
+    /// Escape hatches in the function (`assume`, `admit`, `external_body`, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unsafety_flags: Vec<String>,
+    /// Fan-in/fan-out, call depth and centrality, when computed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph_metrics: Option<GraphMetrics>,
+    /// Test function (`#[test]`, `#[cfg(test)]` or in a test file)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_test: bool,
+    /// `///` doc comment preceding the function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Attributes preceding the function, e.g. `#[verifier::external_body]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    /// `requires` and `ensures` clauses as written (whitespace collapsed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires_text: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ensures_text: Vec<String>,
+    /// Signature as written (whitespace collapsed), without specs or body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Link to the source on GitHub, set by `SourceLinks::link_d3_graph`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Fields added by other tools (e.g. `similar_lemmas`), kept as-is when a
+    /// graph is read and written again
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// A link (edge) in the D3.js graph
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct D3Link {
     pub source: String,
     pub target: String,
     #[serde(rename = "type")]
     pub link_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_kind: Option<EdgeKind>,
+    /// Number of calls the link stands for (module-level graphs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<usize>,
+    /// 1-based lines of the calls the link stands for, in the caller's file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<usize>,
 }
 
 /// Metadata for the D3.js graph
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct D3GraphMetadata {
     pub total_nodes: usize,
     pub total_edges: usize,
@@ -221,10 +1100,42 @@ pub struct D3GraphMetadata {
     pub generated_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub github_url: Option<String>,
+    /// Per-crate summary (workspace projects only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crates: Vec<CrateSummary>,
+    /// `cargo verus` runs made by the pipeline's retry policy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verification_attempts: Vec<VerificationAttempt>,
+    /// `verus --version` of the toolchain that verified the graph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verus_version: Option<String>,
+    /// File next to the graph with the function bodies (or signatures) by
+    /// node id, see `export_d3::write_node_bodies`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bodies_file: Option<String>,
+    /// Fields added by other tools, kept as-is on round-trips
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Functions and calls of one workspace member crate
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateSummary {
+    pub crate_name: String,
+    pub functions: usize,
+    pub exec_functions: usize,
+    pub proof_functions: usize,
+    pub spec_functions: usize,
+    /// Calls between functions of this crate
+    pub internal_calls: usize,
+    /// Calls from this crate into other workspace crates
+    pub outgoing_cross_crate_calls: usize,
+    /// Calls from other workspace crates into this crate
+    pub incoming_cross_crate_calls: usize,
 }
 
 /// Complete D3.js graph structure
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct D3Graph {
     pub nodes: Vec<D3Node>,
     pub links: Vec<D3Link>,
@@ -234,6 +1145,7 @@ pub struct D3Graph {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::node;
 
     // ==========================================================================
     // DeclKind tests
@@ -289,6 +1201,9 @@ mod tests {
             source: "a".to_string(),
             target: "b".to_string(),
             link_type: "inner".to_string(),
+            edge_kind: None,
+            weight: None,
+            lines: Vec::new(),
         };
 
         let json = serde_json::to_string(&link).unwrap();
@@ -324,6 +1239,15 @@ mod tests {
             relative_path: "src/file.rs".to_string(),
             file_name: "file.rs".to_string(),
             parent_folder: "src".to_string(),
+            doc: None,
+            attributes: Vec::new(),
+            start_line: None,
+            end_line: None,
+            requires_text: Vec::new(),
+            ensures_text: Vec::new(),
+            signature: None,
+            graph_metrics: None,
+            spec_support: Vec::new(),
         };
 
         let json = serde_json::to_string(&atom).unwrap();
@@ -334,6 +1258,146 @@ mod tests {
         assert_eq!(parsed.display_name, atom.display_name);
     }
 
+    #[test]
+    fn test_atom_json_schema_covers_all_fields() {
+        let atom = Atom {
+            identifier: String::new(),
+            statement_type: String::new(),
+            deps: vec![],
+            body: String::new(),
+            display_name: String::new(),
+            full_path: String::new(),
+            relative_path: String::new(),
+            file_name: String::new(),
+            parent_folder: String::new(),
+            doc: None,
+            attributes: Vec::new(),
+            start_line: None,
+            end_line: None,
+            requires_text: Vec::new(),
+            ensures_text: Vec::new(),
+            signature: None,
+            graph_metrics: None,
+            spec_support: Vec::new(),
+        };
+        let serialized = serde_json::to_value(&atom).unwrap();
+        let schema = Atom::json_schema();
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|field| field.as_str())
+            .collect();
+
+        for field in serialized.as_object().unwrap().keys() {
+            assert!(schema["properties"].get(field).is_some(), "{field}");
+            assert!(required.contains(&field.as_str()), "{field}");
+        }
+    }
+
+    #[test]
+    fn test_atom_signature_fields_are_flat_and_optional() {
+        let json = serde_json::json!({
+            "identifier": "m::add",
+            "statement_type": "function",
+            "deps": [],
+            "body": "pub fn add(a: u64) -> u64 { a }",
+            "display_name": "add",
+            "full_path": "/p/src/m.rs",
+            "relative_path": "src/m.rs",
+            "file_name": "m.rs",
+            "parent_folder": "src",
+        });
+        let atom: Atom = serde_json::from_value(json.clone()).unwrap();
+        assert!(atom.signature.is_none());
+
+        let mut with_signature = json;
+        with_signature["signature"] = "pub fn add(a: u64) -> u64".into();
+        with_signature["params"] = serde_json::json!([{"name": "a", "type": "u64"}]);
+        with_signature["return_type"] = "u64".into();
+        with_signature["is_pub"] = true.into();
+        let atom: Atom = serde_json::from_value(with_signature.clone()).unwrap();
+        let signature = atom.signature.as_ref().unwrap();
+        assert_eq!(signature.params[0].ty, "u64");
+        assert!(signature.generics.is_empty());
+        assert_eq!(serde_json::to_value(&atom).unwrap(), with_signature);
+
+        let schema = Atom::json_schema();
+        for field in ["signature", "params", "return_type", "generics", "is_pub"] {
+            assert!(schema["properties"].get(field).is_some(), "{field}");
+        }
+    }
+
+    #[test]
+    fn test_atom_doc_and_attributes_round_trip() {
+        let json = serde_json::json!({
+            "identifier": "m::add",
+            "statement_type": "function",
+            "deps": [],
+            "body": "fn add() {}",
+            "display_name": "add",
+            "full_path": "/p/src/m.rs",
+            "relative_path": "src/m.rs",
+            "file_name": "m.rs",
+            "parent_folder": "src",
+            "doc": "Adds.",
+            "attributes": ["#[verifier::external_body]"],
+        });
+        let atom: Atom = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(atom.doc.as_deref(), Some("Adds."));
+        assert_eq!(atom.attributes, vec!["#[verifier::external_body]"]);
+        assert!(atom.signature.is_none());
+        assert_eq!(serde_json::to_value(&atom).unwrap(), json);
+    }
+
+    // ==========================================================================
+    // EdgeKind tests
+    // ==========================================================================
+
+    #[test]
+    fn test_edge_kind_roundtrip_decl_kinds() {
+        for kind in EdgeKind::ALL {
+            assert_eq!(EdgeKind::new(kind.caller(), kind.callee()), kind);
+        }
+        assert_eq!(
+            EdgeKind::new(DeclKind::Proof, DeclKind::Spec),
+            EdgeKind::ProofToSpec
+        );
+    }
+
+    #[test]
+    fn test_edge_kind_from_str() {
+        for kind in EdgeKind::ALL {
+            assert_eq!(kind.as_str().parse::<EdgeKind>(), Ok(kind));
+        }
+        assert_eq!("proof-to-proof".parse(), Ok(EdgeKind::ProofToProof));
+        assert_eq!("Exec->Spec".parse(), Ok(EdgeKind::ExecToSpec));
+        assert!("proof".parse::<EdgeKind>().is_err());
+        assert_eq!("spawn".parse(), Ok(EdgeKind::Spawn));
+    }
+
+    #[test]
+    fn test_edge_kind_serialization() {
+        let json = serde_json::to_string(&EdgeKind::ExecToProof).unwrap();
+        assert_eq!(json, "\"exec_to_proof\"");
+    }
+
+    // ==========================================================================
+    // FormatVersion / AtomsFile tests
+    // ==========================================================================
+
+    #[test]
+    fn test_atoms_file_serializes_version_as_integer() {
+        let json = serde_json::to_string(&AtomsFile::new(vec![])).unwrap();
+        assert_eq!(json, r#"{"format_version":2,"atoms":[]}"#);
+    }
+
+    #[test]
+    fn test_format_version_rejects_unknown() {
+        assert!(serde_json::from_str::<FormatVersion>("1").is_ok());
+        assert!(serde_json::from_str::<FormatVersion>("99").is_err());
+    }
+
     // ==========================================================================
     // D3Node optional fields tests
     // ==========================================================================
@@ -354,6 +1418,8 @@ mod tests {
             dependencies: vec![],
             dependents: vec![],
             kind: DeclKind::Exec,
+            crate_name: None,
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&node).unwrap();
@@ -379,6 +1445,8 @@ mod tests {
             dependencies: vec![],
             dependents: vec![],
             kind: DeclKind::Exec,
+            crate_name: None,
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&node).unwrap();
@@ -386,4 +1454,112 @@ mod tests {
         assert!(json.contains("\"start_line\":10"));
         assert!(json.contains("\"end_line\":20"));
     }
+
+    #[test]
+    fn test_d3_node_enrichment_round_trip() {
+        let mut node = D3Node {
+            id: "test".to_string(),
+            ..Default::default()
+        };
+        node.verification_status = Some(VerificationStatus::Failed);
+        node.unsafety_flags = vec!["admit".to_string()];
+        let mut value = serde_json::to_value(&node).unwrap();
+        assert_eq!(value["verification_status"], "failed");
+        assert!(value.get("verification_time_ms").is_none());
+
+        // Fields written by other tools survive a read/write cycle
+        value["similar_lemmas"] = serde_json::json!([{"name": "lemma_x"}]);
+        let node: D3Node = serde_json::from_value(value).unwrap();
+        assert_eq!(node.unsafety_flags, vec!["admit"]);
+        let value = serde_json::to_value(&node).unwrap();
+        assert_eq!(value["similar_lemmas"][0]["name"], "lemma_x");
+    }
+
+    // ==========================================================================
+    // CallGraph tests
+    // ==========================================================================
+
+    fn symbols(nodes: Vec<&FunctionNode>) -> Vec<&str> {
+        nodes.iter().map(|node| node.symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn test_call_graph_from_map_repairs_callers() {
+        // a -> b -> c, with c also calling an external function
+        let graph: CallGraph = [
+            node("a", &["b"]),
+            node("b", &["c"]),
+            node("c", &["external"]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(graph["b"].callers, HashSet::from(["a".to_string()]));
+        assert_eq!(symbols(graph.callers_of("c")), vec!["b"]);
+        assert_eq!(symbols(graph.callees_of("c")), Vec::<&str>::new());
+        assert_eq!(symbols(graph.roots()), vec!["a"]);
+        assert_eq!(symbols(graph.leaves()), vec!["c"]);
+    }
+
+    #[test]
+    fn test_call_graph_json_round_trip() {
+        let mut a = node("a", &["b"]);
+        a.callee_occurrences.push(CalleeOccurrence {
+            symbol: "b".to_string(),
+            line: 3,
+            column: Some(4),
+            location: Some(CallLocation::Postcondition),
+            edge_kind: None,
+        });
+        let graph: CallGraph = [a, node("b", &[])].into_iter().collect();
+
+        let json = serde_json::to_value(&graph).unwrap();
+        assert_eq!(
+            json["a"]["callee_occurrences"][0]["location"],
+            "postcondition"
+        );
+        let read: CallGraph = serde_json::from_value(json).unwrap();
+        assert_eq!(read["b"].callers, HashSet::from(["a".to_string()]));
+        assert_eq!(read["a"].call_sites("b")[0].line, 3);
+    }
+
+    #[test]
+    fn test_call_graph_add_edge_and_remove_node() {
+        let mut graph: CallGraph = [node("a", &[]), node("b", &[])].into_iter().collect();
+
+        assert!(graph.add_edge("a", "b"));
+        assert!(!graph.add_edge("a", "missing"));
+        assert_eq!(symbols(graph.callees_of("a")), vec!["b"]);
+        assert_eq!(symbols(graph.callers_of("b")), vec!["a"]);
+
+        graph.remove_node("b");
+        assert!(graph["a"].callees.is_empty());
+        assert!(graph.node("b").is_none());
+    }
+
+    #[test]
+    fn test_call_graph_add_node_replaces_outgoing_calls() {
+        let mut graph: CallGraph = [node("a", &["b"]), node("b", &["c"]), node("c", &[])]
+            .into_iter()
+            .collect();
+
+        let previous = graph.add_node(node("b", &[]));
+
+        assert_eq!(previous.unwrap().callees, HashSet::from(["c".to_string()]));
+        assert_eq!(symbols(graph.callers_of("b")), vec!["a"]);
+        assert!(graph["c"].callers.is_empty());
+    }
+
+    #[test]
+    fn test_call_graph_subgraph_drops_outside_edges() {
+        let graph: CallGraph = [node("a", &["b", "c"]), node("b", &["c"]), node("c", &[])]
+            .into_iter()
+            .collect();
+
+        let sub = graph.subgraph(["a", "c", "missing"]);
+
+        assert_eq!(sub.len(), 2);
+        assert_eq!(sub["a"].callees, HashSet::from(["c".to_string()]));
+        assert_eq!(sub["c"].callers, HashSet::from(["a".to_string()]));
+    }
 }
@@ -0,0 +1,381 @@
+//! Validation of atoms and metrics JSON files
+//!
+//! [`validate_atoms_json`] checks a parsed atoms JSON (a `format_version`
+//! envelope or a bare array, as metrics JSONs are) before downstream scripts
+//! use it:
+//!
+//! - the format version is supported, and the expected one if given;
+//! - every atom has the fields of [`Atom::json_schema`] with the right types;
+//! - identifiers are unique;
+//! - every dep names an atom of the file (no dangling edges).
+//!
+//! Problems are collected as [`ValidationIssue`]s rather than stopping at
+//! the first one, so a CI step can report them all.
+
+use crate::types::{Atom, FormatVersion};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// Kind of problem found by [`validate_atoms_json`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationRule {
+    /// Not an envelope or an array of atoms
+    Structure,
+    /// Unsupported or unexpected `format_version`
+    FormatVersion,
+    /// A required field is missing
+    MissingField,
+    /// A field has the wrong JSON type
+    FieldType,
+    /// Several atoms share an identifier
+    DuplicateIdentifier,
+    /// A dep names no atom of the file
+    DanglingDep,
+}
+
+impl ValidationRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationRule::Structure => "structure",
+            ValidationRule::FormatVersion => "format_version",
+            ValidationRule::MissingField => "missing_field",
+            ValidationRule::FieldType => "field_type",
+            ValidationRule::DuplicateIdentifier => "duplicate_identifier",
+            ValidationRule::DanglingDep => "dangling_dep",
+        }
+    }
+}
+
+/// One problem of the file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationIssue {
+    pub rule: ValidationRule,
+    /// Position of the atom in the file, `None` for file-level problems
+    pub index: Option<usize>,
+    /// Identifier of the atom, when it has one
+    pub identifier: Option<String>,
+    /// Offending field (or the dep for dangling edges)
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// Result of [`validate_atoms_json`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    /// Format version of the file (1 for bare arrays), when readable
+    pub format_version: Option<u32>,
+    pub atoms: usize,
+    /// Deps over all atoms
+    pub edges: usize,
+    pub dangling_edges: usize,
+    pub duplicate_identifiers: usize,
+    /// File-level issues first, then by atom
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Whether the file has no problems
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn file_issue(&mut self, rule: ValidationRule, message: String) {
+        self.issues.push(ValidationIssue {
+            rule,
+            index: None,
+            identifier: None,
+            field: None,
+            message,
+        });
+    }
+
+    /// One line per issue, after a summary line
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} atoms, {} edges: {}",
+            self.atoms,
+            self.edges,
+            if self.is_valid() {
+                "valid".to_string()
+            } else {
+                format!("{} issues", self.issues.len())
+            }
+        );
+        for issue in &self.issues {
+            let location = match (&issue.index, &issue.identifier) {
+                (Some(index), Some(identifier)) => format!("atom {index} ({identifier})"),
+                (Some(index), None) => format!("atom {index}"),
+                _ => "file".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "{}: [{}] {}",
+                location,
+                issue.rule.as_str(),
+                issue.message
+            );
+        }
+        out
+    }
+}
+
+/// JSON type name of a value, as used by JSON Schema
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    let actual = json_type(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+/// Type errors of `value` against a (sub)schema with `type` and `items`
+fn schema_type_error(schema: &Value, value: &Value) -> Option<String> {
+    let expected = schema["type"].as_str()?;
+    if !type_matches(expected, value) {
+        return Some(format!("expected {}, found {}", expected, json_type(value)));
+    }
+    let item_type = schema["items"]["type"].as_str()?;
+    value.as_array()?.iter().find_map(|item| {
+        (!type_matches(item_type, item)).then(|| {
+            format!(
+                "expected an array of {}, found an item of type {}",
+                item_type,
+                json_type(item)
+            )
+        })
+    })
+}
+
+/// Validate parsed atoms JSON; with `expected_version`, files in another
+/// format version are reported as stale
+pub fn validate_atoms_json(
+    value: &Value,
+    expected_version: Option<FormatVersion>,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let atoms = match value {
+        Value::Array(atoms) => {
+            report.format_version = Some(FormatVersion::V1.into());
+            atoms
+        }
+        Value::Object(envelope) => {
+            match envelope.get("format_version").and_then(Value::as_u64) {
+                Some(version) => report.format_version = Some(version as u32),
+                None => report.file_issue(
+                    ValidationRule::FormatVersion,
+                    "envelope without a numeric format_version".to_string(),
+                ),
+            }
+            match envelope.get("atoms") {
+                Some(Value::Array(atoms)) => atoms,
+                _ => {
+                    report.file_issue(
+                        ValidationRule::Structure,
+                        "envelope without an atoms array".to_string(),
+                    );
+                    return report;
+                }
+            }
+        }
+        other => {
+            report.file_issue(
+                ValidationRule::Structure,
+                format!(
+                    "expected an array of atoms or a format_version envelope, found {}",
+                    json_type(other)
+                ),
+            );
+            return report;
+        }
+    };
+
+    if let Some(version) = report.format_version {
+        match FormatVersion::try_from(version) {
+            Err(e) => report.file_issue(ValidationRule::FormatVersion, e),
+            Ok(version) => {
+                if let Some(expected) = expected_version.filter(|expected| *expected != version) {
+                    report.file_issue(
+                        ValidationRule::FormatVersion,
+                        format!(
+                            "format_version {} is stale, expected {}",
+                            u32::from(version),
+                            u32::from(expected)
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    report.atoms = atoms.len();
+    let schema = Atom::json_schema();
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|fields| fields.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let properties = schema["properties"].as_object();
+
+    let identifier_of = |atom: &Value| atom["identifier"].as_str().map(str::to_string);
+    let mut first_index: HashMap<&str, usize> = HashMap::new();
+    for (index, atom) in atoms.iter().enumerate() {
+        let identifier = identifier_of(atom);
+        let issue = |rule, field: Option<&str>, message: String| ValidationIssue {
+            rule,
+            index: Some(index),
+            identifier: identifier.clone(),
+            field: field.map(str::to_string),
+            message,
+        };
+
+        let Some(fields) = atom.as_object() else {
+            report.issues.push(issue(
+                ValidationRule::Structure,
+                None,
+                format!("expected an object, found {}", json_type(atom)),
+            ));
+            continue;
+        };
+        for field in &required {
+            if !fields.contains_key(*field) {
+                report.issues.push(issue(
+                    ValidationRule::MissingField,
+                    Some(*field),
+                    format!("missing required field {field}"),
+                ));
+            }
+        }
+        for (field, value) in fields {
+            let Some(property) = properties.and_then(|p| p.get(field)) else {
+                continue;
+            };
+            // Optional fields may be null
+            if value.is_null() && !required.contains(&field.as_str()) {
+                continue;
+            }
+            if let Some(error) = schema_type_error(property, value) {
+                report.issues.push(issue(
+                    ValidationRule::FieldType,
+                    Some(field.as_str()),
+                    format!("{field}: {error}"),
+                ));
+            }
+        }
+
+        if let Some(identifier) = atom["identifier"].as_str() {
+            if let Some(first) = first_index.get(identifier) {
+                report.duplicate_identifiers += 1;
+                report.issues.push(issue(
+                    ValidationRule::DuplicateIdentifier,
+                    Some("identifier"),
+                    format!("duplicate identifier, first used by atom {first}"),
+                ));
+            } else {
+                first_index.insert(identifier, index);
+            }
+        }
+    }
+
+    let identifiers: HashSet<&str> = first_index.keys().copied().collect();
+    for (index, atom) in atoms.iter().enumerate() {
+        let deps = atom["deps"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for dep in deps.iter().filter_map(Value::as_str) {
+            report.edges += 1;
+            if !identifiers.contains(dep) {
+                report.dangling_edges += 1;
+                report.issues.push(ValidationIssue {
+                    rule: ValidationRule::DanglingDep,
+                    index: Some(index),
+                    identifier: identifier_of(atom),
+                    field: Some(dep.to_string()),
+                    message: format!("dep {dep} names no atom"),
+                });
+            }
+        }
+    }
+    report
+        .issues
+        .sort_by_key(|issue| (issue.index.is_some(), issue.index));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn atom(identifier: &str, deps: &[&str]) -> Value {
+        json!({
+            "identifier": identifier,
+            "statement_type": "function",
+            "deps": deps,
+            "body": "fn f() {}",
+            "display_name": identifier,
+            "full_path": "/p/src/m.rs",
+            "relative_path": "src/m.rs",
+            "file_name": "m.rs",
+            "parent_folder": "src",
+        })
+    }
+
+    #[test]
+    fn test_validate_atoms_json() {
+        let valid =
+            json!({ "format_version": 2, "atoms": [atom("m::f", &["m::g"]), atom("m::g", &[])] });
+        let report = validate_atoms_json(&valid, Some(FormatVersion::V2));
+        assert!(report.is_valid(), "{}", report.to_text());
+        assert_eq!((report.atoms, report.edges), (2, 1));
+
+        let mut broken = atom("m::f", &["m::gone"]);
+        broken["start_line"] = json!("12");
+        broken.as_object_mut().unwrap().remove("body");
+        let mut deps = atom("m::g", &[]);
+        deps["deps"] = json!(["m::f", 3]);
+        let file = json!([broken, atom("m::f", &[]), deps, "m::h"]);
+        let report = validate_atoms_json(&file, Some(FormatVersion::V2));
+        let rules: Vec<(Option<usize>, ValidationRule)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.index, issue.rule))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                (None, ValidationRule::FormatVersion),
+                (Some(0), ValidationRule::MissingField),
+                (Some(0), ValidationRule::FieldType),
+                (Some(0), ValidationRule::DanglingDep),
+                (Some(1), ValidationRule::DuplicateIdentifier),
+                (Some(2), ValidationRule::FieldType),
+                (Some(3), ValidationRule::Structure),
+            ]
+        );
+        assert_eq!(report.issues[3].field.as_deref(), Some("m::gone"));
+        assert_eq!(
+            (report.dangling_edges, report.duplicate_identifiers),
+            (1, 1)
+        );
+        // Bare arrays (metrics JSONs) are fine without an expected version
+        assert!(validate_atoms_json(&json!([atom("m::f", &[])]), None).is_valid());
+
+        let report = validate_atoms_json(&json!({ "format_version": 9, "atoms": [] }), None);
+        assert_eq!(report.issues[0].rule, ValidationRule::FormatVersion);
+        assert!(!validate_atoms_json(&json!("atoms"), None).is_valid());
+    }
+}
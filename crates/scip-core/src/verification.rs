@@ -0,0 +1,912 @@
+//! Per-function verification time attribution.
+//!
+//! With `--time-expanded --output-json`, Verus reports how long the SMT
+//! solver spent on every function (and, for recent versions, the rlimit
+//! resources it used). [`VerificationTimings::parse`] reads this from the
+//! verification output, and [`VerificationTimings::time_for`] matches the
+//! qualified Verus function names back to call graph nodes.
+//!
+//! Solver resource limits make some proofs fail intermittently;
+//! [`verify_with_retry`] re-runs such failures under a [`RetryPolicy`].
+//! [`verify_modules_parallel`] splits a crate's verification by module and
+//! runs the modules on several workers; cancelling its [`Monitor`] keeps
+//! the modules verified so far. The `run_cargo_verus*` functions run the
+//! Verus release of a [`VerusToolchain`].
+
+use crate::error::{Error, Result};
+use crate::monitor::Monitor;
+use crate::verus_toolchain::VerusToolchain;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Extra Verus arguments that make it report per-function times
+pub const TIMING_ARGS: &[&str] = &["--time-expanded", "--output-json"];
+
+/// Verification time of a single function
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionTiming {
+    /// Qualified Verus name, e.g. `my_crate::field::FieldElement::square`
+    pub function: String,
+    pub time_ms: f64,
+    /// SMT resource count, if reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rlimit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+}
+
+/// Per-function verification times of one verification run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerificationTimings {
+    /// Keyed by qualified function name; repeated entries are summed
+    pub functions: BTreeMap<String, FunctionTiming>,
+    /// Total verification time, if reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_ms: Option<f64>,
+}
+
+impl VerificationTimings {
+    /// Read the timing JSON from Verus output.
+    ///
+    /// The output may interleave JSON objects with compiler diagnostics, and
+    /// holds one object per run when several runs were merged; times of all
+    /// of them are collected. Output without timing information gives empty
+    /// timings.
+    pub fn parse(output: &str) -> Self {
+        let mut timings = Self::default();
+        let mut offset = 0;
+        while offset < output.len() {
+            let rest = &output[offset..];
+            let line_len = rest.find('\n').map_or(rest.len(), |i| i + 1);
+            if rest.trim_start_matches([' ', '\t']).starts_with('{') {
+                let mut values =
+                    serde_json::Deserializer::from_str(rest).into_iter::<serde_json::Value>();
+                if let Some(Ok(value)) = values.next() {
+                    timings.collect(&value);
+                    offset += values.byte_offset().max(line_len);
+                    continue;
+                }
+            }
+            offset += line_len;
+        }
+        timings
+    }
+
+    fn collect(&mut self, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(total) = map.get("total").and_then(|t| t.as_f64()) {
+                    self.total_ms.get_or_insert(total);
+                }
+                if let Some(breakdown) = map.get("function-breakdown").and_then(|b| b.as_array()) {
+                    for entry in breakdown {
+                        self.add_entry(entry);
+                    }
+                }
+                for (key, child) in map {
+                    if key != "function-breakdown" {
+                        self.collect(child);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.collect(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_entry(&mut self, entry: &serde_json::Value) {
+        let (Some(function), Some(time_ms)) = (entry["function"].as_str(), entry["time"].as_f64())
+        else {
+            return;
+        };
+        let rlimit = entry["rlimit"].as_u64();
+        let success = entry["success"].as_bool();
+        let timing = self
+            .functions
+            .entry(function.to_string())
+            .or_insert_with(|| FunctionTiming {
+                function: function.to_string(),
+                time_ms: 0.0,
+                rlimit: None,
+                success: None,
+            });
+        timing.time_ms += time_ms;
+        if let Some(rlimit) = rlimit {
+            timing.rlimit = Some(timing.rlimit.unwrap_or(0) + rlimit);
+        }
+        if let Some(success) = success {
+            timing.success = Some(timing.success.unwrap_or(true) && success);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// The timing of a call graph node, by display name (`f` or `Type::f`)
+    /// and source path.
+    ///
+    /// If several functions share the name, the one whose module path
+    /// matches the source file (`src/field/mod.rs` -> `field`) wins; an
+    /// unresolved ambiguity gives `None`.
+    pub fn time_for(&self, display_name: &str, relative_path: &str) -> Option<&FunctionTiming> {
+        let suffix = format!("::{}", display_name);
+        let candidates: Vec<&FunctionTiming> = self
+            .functions
+            .values()
+            .filter(|t| t.function == display_name || t.function.ends_with(&suffix))
+            .collect();
+        if candidates.len() <= 1 {
+            return candidates.into_iter().next();
+        }
+
+        let module = module_path(relative_path);
+        let mut matching = candidates.into_iter().filter(|t| {
+            let qualified = t.function.strip_suffix(&suffix).unwrap_or_default();
+            // Drop the crate name
+            let timing_module = qualified.split_once("::").map(|(_, m)| m).unwrap_or("");
+            timing_module == module || timing_module.ends_with(&format!("::{}", module))
+        });
+        match (matching.next(), matching.next()) {
+            (Some(timing), None) => Some(timing),
+            _ => None,
+        }
+    }
+}
+
+/// Rust module path of a source file relative to its crate
+/// (`src/field/mod.rs` -> `field`, `src/lib.rs` -> ``). Binaries under
+/// `src/bin` are crates of their own (`src/bin/tool/cli.rs` -> `cli`).
+pub(crate) fn module_path(relative_path: &str) -> String {
+    let path = relative_path.trim_start_matches("./");
+    let path = path.rsplit_once("src/").map(|(_, p)| p).unwrap_or(path);
+    let path = path.strip_suffix(".rs").unwrap_or(path);
+    let path = match path.strip_prefix("bin/") {
+        Some(binary) => binary.split_once('/').map(|(_, p)| p).unwrap_or(""),
+        None => path,
+    };
+    path.split('/')
+        .filter(|segment| !matches!(*segment, "mod" | "lib" | "main" | ""))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Run `cargo verus verify` with extra Verus arguments and return the
+/// combined stdout/stderr and exit code
+pub fn run_cargo_verus(
+    project: &Path,
+    toolchain: &VerusToolchain,
+    package: Option<&str>,
+    verus_args: &[impl AsRef<OsStr>],
+) -> Result<(String, i32)> {
+    run_cargo_verus_in(project, toolchain, package, verus_args, None)
+}
+
+fn run_cargo_verus_in(
+    project: &Path,
+    toolchain: &VerusToolchain,
+    package: Option<&str>,
+    verus_args: &[impl AsRef<OsStr>],
+    target_dir: Option<&Path>,
+) -> Result<(String, i32)> {
+    let mut command = toolchain.cargo_command();
+    command.current_dir(project).args(["verus", "verify"]);
+    if let Some(target_dir) = target_dir {
+        command.env("CARGO_TARGET_DIR", target_dir);
+    }
+    if let Some(package) = package {
+        command.args(["-p", package]);
+    }
+    if !verus_args.is_empty() {
+        command.arg("--").args(verus_args);
+    }
+    let output = command
+        .output()
+        .map_err(|e| Error::VerificationFailed(format!("could not run cargo verus: {e}")))?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((text, output.status.code().unwrap_or(-1)))
+}
+
+/// How to retry verification runs that fail only on solver resource limits
+/// or timeouts.
+///
+/// Each retry multiplies the rlimit by `rlimit_factor` and, with
+/// `narrow_to_failed_modules`, only re-verifies the modules whose functions
+/// hit the limit (`--verify-only-module`); diagnostics of other modules are
+/// kept from the first attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: usize,
+    /// rlimit of the first attempt (Verus defaults to 10)
+    pub rlimit: u32,
+    pub rlimit_factor: u32,
+    pub narrow_to_failed_modules: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            rlimit: 10,
+            rlimit_factor: 2,
+            narrow_to_failed_modules: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_rlimit(mut self, rlimit: u32, factor: u32) -> Self {
+        self.rlimit = rlimit;
+        self.rlimit_factor = factor.max(1);
+        self
+    }
+
+    pub fn with_narrowing(mut self, enabled: bool) -> Self {
+        self.narrow_to_failed_modules = enabled;
+        self
+    }
+}
+
+/// One `cargo verus verify` invocation of a [`VerificationRun`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationAttempt {
+    /// 1-based attempt number
+    pub attempt: usize,
+    /// Modules verified (empty: the whole crate)
+    pub modules: Vec<String>,
+    /// rlimit passed to Verus (`None` on a first attempt with the default)
+    pub rlimit: Option<u32>,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    /// Diagnostics caused by rlimit/timeouts
+    pub transient_failures: usize,
+}
+
+/// Result of a verification with retries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationRun {
+    /// Output to analyze: the diagnostics of the first attempt, with those
+    /// of each retried module replaced by its last retry's
+    pub output: String,
+    /// 0 if every failure went away on retry, else the first failing code
+    pub exit_code: i32,
+    pub verification_attempts: Vec<VerificationAttempt>,
+}
+
+fn is_error(diagnostic: &str) -> bool {
+    diagnostic.to_lowercase().starts_with("error")
+}
+
+/// Whether a diagnostic is a solver resource limit or timeout rather than a
+/// genuine verification failure
+pub fn is_transient_failure(diagnostic: &str) -> bool {
+    let lower = diagnostic.to_lowercase();
+    is_error(&lower)
+        && (lower.contains("rlimit")
+            || lower.contains("resource limit")
+            || lower.contains("timed out")
+            || lower.contains("timeout"))
+}
+
+/// Blank-line separated diagnostic blocks of compiler output
+fn diagnostic_blocks(output: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current = String::new();
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    if !current.is_empty() {
+        blocks.push(current);
+    }
+    blocks
+}
+
+/// Module of the first `--> path:line:col` location of a diagnostic
+fn diagnostic_module(diagnostic: &str) -> Option<String> {
+    diagnostic.lines().find_map(|line| {
+        let location = line.trim_start().strip_prefix("--> ")?;
+        let path = location.split(':').next()?;
+        Some(module_path(path))
+    })
+}
+
+/// Run verification with `policy`, calling `run` with the Verus arguments of
+/// each attempt
+pub fn verify_with_retry<F>(
+    base_args: &[String],
+    policy: &RetryPolicy,
+    mut run: F,
+) -> Result<VerificationRun>
+where
+    F: FnMut(&[String]) -> Result<(String, i32)>,
+{
+    let mut attempts = Vec::new();
+    let mut timed_run = |args: Vec<String>, modules: Vec<String>, rlimit: Option<u32>| {
+        let start = Instant::now();
+        let (output, exit_code) = run(&args)?;
+        let transient = diagnostic_blocks(&output)
+            .iter()
+            .filter(|block| is_transient_failure(block))
+            .count();
+        attempts.push(VerificationAttempt {
+            attempt: attempts.len() + 1,
+            modules,
+            rlimit,
+            exit_code,
+            duration_ms: start.elapsed().as_millis() as u64,
+            transient_failures: transient,
+        });
+        Ok::<_, Error>((output, exit_code, transient))
+    };
+
+    let (first_output, first_exit_code, mut transient) =
+        timed_run(base_args.to_vec(), Vec::new(), None)?;
+    let mut kept = diagnostic_blocks(&first_output);
+    let mut last_exit_code = first_exit_code;
+    let mut rlimit = policy.rlimit;
+
+    for _ in 0..policy.max_retries {
+        if last_exit_code == 0 || transient == 0 {
+            break;
+        }
+        let failing: Vec<&String> = kept.iter().filter(|b| is_transient_failure(b)).collect();
+        let modules: BTreeSet<String> = failing
+            .iter()
+            .filter_map(|block| diagnostic_module(block))
+            .collect();
+        let narrow = policy.narrow_to_failed_modules && !modules.is_empty();
+
+        rlimit = rlimit.saturating_mul(policy.rlimit_factor);
+        let mut args = base_args.to_vec();
+        args.extend(["--rlimit".to_string(), rlimit.to_string()]);
+        let modules: Vec<String> = if narrow {
+            for module in &modules {
+                args.extend(["--verify-only-module".to_string(), module.clone()]);
+            }
+            modules.into_iter().collect()
+        } else {
+            Vec::new()
+        };
+
+        let (output, exit_code, retry_transient) = timed_run(args, modules.clone(), Some(rlimit))?;
+        if narrow {
+            // Replace everything reported for the retried modules, so the
+            // failures still left are retried next; errors without a
+            // location (`aborting due to ...`) only repeat the count
+            kept.retain(|block| {
+                diagnostic_module(block).is_none_or(|module| !modules.contains(&module))
+            });
+            kept.extend(
+                diagnostic_blocks(&output)
+                    .into_iter()
+                    .filter(|block| diagnostic_module(block).is_some() || !is_error(block)),
+            );
+            let other_errors = kept.iter().any(|block| is_error(block));
+            last_exit_code = if exit_code == 0 && !other_errors {
+                0
+            } else {
+                exit_code.max(first_exit_code)
+            };
+        } else {
+            kept = diagnostic_blocks(&output);
+            last_exit_code = exit_code;
+        }
+        transient = retry_transient;
+    }
+
+    Ok(VerificationRun {
+        output: kept.join("\n"),
+        exit_code: last_exit_code,
+        verification_attempts: attempts,
+    })
+}
+
+/// [`verify_with_retry`] running `cargo verus verify` in `project`
+pub fn run_cargo_verus_with_retry(
+    project: &Path,
+    toolchain: &VerusToolchain,
+    package: Option<&str>,
+    verus_args: &[&str],
+    policy: &RetryPolicy,
+) -> Result<VerificationRun> {
+    let base_args: Vec<String> = verus_args.iter().map(|arg| arg.to_string()).collect();
+    verify_with_retry(&base_args, policy, |args| {
+        run_cargo_verus(project, toolchain, package, args)
+    })
+}
+
+/// Modules to verify separately, from the source files of the call graph
+/// (`""` is the crate root module). Files of binary, test, example and
+/// bench targets are skipped: they aren't modules of the crate.
+pub fn modules_from_paths<'a>(relative_paths: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let modules: BTreeSet<String> = relative_paths
+        .into_iter()
+        .filter(|path| path.ends_with(".rs") && !is_other_target(path))
+        .map(module_path)
+        .collect();
+    modules.into_iter().collect()
+}
+
+/// Check whether a source file belongs to a target other than the crate's
+/// library or main binary (`src/bin/`, `tests/`, `examples/`, `benches/`)
+fn is_other_target(relative_path: &str) -> bool {
+    let path = relative_path.trim_start_matches("./");
+    match path.rsplit_once("src/") {
+        Some((_, in_src)) => in_src.starts_with("bin/"),
+        None => path
+            .split('/')
+            .any(|dir| matches!(dir, "tests" | "examples" | "benches")),
+    }
+}
+
+/// Verus arguments restricting verification to one module
+fn module_args(module: &str) -> Vec<String> {
+    if module.is_empty() {
+        vec!["--verify-root".to_string()]
+    } else {
+        vec!["--verify-only-module".to_string(), module.to_string()]
+    }
+}
+
+/// Outcome of verifying one module in [`verify_modules_parallel`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleVerification {
+    /// Module path (`""` for the crate root)
+    pub module: String,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    pub attempts: usize,
+    /// Output of the module's run, if logs were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<PathBuf>,
+}
+
+/// Merged result of verifying a crate module by module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParallelVerification {
+    /// Outputs of all modules, in module order
+    pub output: String,
+    /// 0 if every module verified, else the first failing exit code
+    pub exit_code: i32,
+    pub modules: Vec<ModuleVerification>,
+    /// The run was cancelled; `modules` only lists the modules verified
+    /// before that
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Verify `modules` one by one on `jobs` worker threads.
+///
+/// `run` gets the worker index and the Verus arguments of a run; each module
+/// is verified under `policy`. With `log_dir`, every module's output is also
+/// written to `<log_dir>/<module>.log`.
+///
+/// Each finished module advances `monitor`'s progress. Once it is
+/// cancelled, workers start no new module and modules still running
+/// (whose Verus process got the same interrupt) are left out.
+pub fn verify_modules_parallel<F>(
+    modules: &[String],
+    jobs: usize,
+    base_args: &[String],
+    policy: &RetryPolicy,
+    log_dir: Option<&Path>,
+    monitor: &Monitor,
+    run: F,
+) -> Result<ParallelVerification>
+where
+    F: Fn(usize, &[String]) -> Result<(String, i32)> + Sync,
+{
+    if let Some(log_dir) = log_dir {
+        std::fs::create_dir_all(log_dir)?;
+    }
+    let next = AtomicUsize::new(0);
+    type ModuleResult = Result<(ModuleVerification, String)>;
+    let results: Mutex<Vec<Option<ModuleResult>>> =
+        Mutex::new((0..modules.len()).map(|_| None).collect());
+
+    monitor
+        .progress()
+        .start("Verifying modules", Some(modules.len() as u64));
+    std::thread::scope(|scope| {
+        for worker in 0..jobs.clamp(1, modules.len().max(1)) {
+            let (next, results, run) = (&next, &results, &run);
+            scope.spawn(move || loop {
+                if monitor.is_cancelled() {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(module) = modules.get(index) else {
+                    break;
+                };
+                let start = Instant::now();
+                let mut args = base_args.to_vec();
+                args.extend(module_args(module));
+                let result = verify_with_retry(&args, policy, |args| run(worker, args)).and_then(
+                    |verification| {
+                        let log_path = match log_dir {
+                            Some(dir) => {
+                                let name = if module.is_empty() { "crate" } else { module };
+                                let path = dir.join(format!("{}.log", name.replace("::", "-")));
+                                std::fs::write(&path, &verification.output)?;
+                                Some(path)
+                            }
+                            None => None,
+                        };
+                        let summary = ModuleVerification {
+                            module: module.clone(),
+                            exit_code: verification.exit_code,
+                            duration_ms: start.elapsed().as_millis() as u64,
+                            attempts: verification.verification_attempts.len(),
+                            log_path,
+                        };
+                        Ok((summary, verification.output))
+                    },
+                );
+                if monitor.is_cancelled() {
+                    break;
+                }
+                results.lock().unwrap()[index] = Some(result);
+                monitor.progress().advance(1);
+            });
+        }
+    });
+    monitor.progress().finish();
+
+    let mut merged = ParallelVerification {
+        output: String::new(),
+        exit_code: 0,
+        modules: Vec::new(),
+        cancelled: monitor.is_cancelled(),
+    };
+    for result in results.into_inner().unwrap().into_iter().flatten() {
+        let (summary, output) = result?;
+        if merged.exit_code == 0 {
+            merged.exit_code = summary.exit_code;
+        }
+        merged.output.push_str(&output);
+        if !output.ends_with('\n') {
+            merged.output.push('\n');
+        }
+        merged.modules.push(summary);
+    }
+    Ok(merged)
+}
+
+/// [`verify_modules_parallel`] running `cargo verus verify` in `project`.
+///
+/// Workers use separate target directories (`target/verus-worker-<n>`) so
+/// they don't wait on each other's cargo build lock. Each of them builds
+/// the project's dependencies once: the first run with `jobs` workers
+/// costs `jobs` builds and as much disk space, later runs reuse them.
+#[allow(clippy::too_many_arguments)]
+pub fn run_cargo_verus_by_module(
+    project: &Path,
+    toolchain: &VerusToolchain,
+    package: Option<&str>,
+    modules: &[String],
+    jobs: usize,
+    verus_args: &[&str],
+    policy: &RetryPolicy,
+    log_dir: Option<&Path>,
+    monitor: &Monitor,
+) -> Result<ParallelVerification> {
+    let base_args: Vec<String> = verus_args.iter().map(|arg| arg.to_string()).collect();
+    let target_dir = project.join("target");
+    verify_modules_parallel(
+        modules,
+        jobs,
+        &base_args,
+        policy,
+        log_dir,
+        monitor,
+        |worker, args| {
+            let worker_target =
+                (jobs > 1).then(|| target_dir.join(format!("verus-worker-{}", worker)));
+            run_cargo_verus_in(project, toolchain, package, args, worker_target.as_deref())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = r#"warning: unused variable: `x`
+  --> src/lib.rs:3:9
+{
+  "verification-results": { "encountered-vir-error": false, "success": true },
+  "times-ms": {
+    "total": 5120,
+    "smt": {
+      "smt-run-module-times": [
+        {
+          "module": "field",
+          "time": 900,
+          "function-breakdown": [
+            { "function": "demo::field::FieldElement::square", "time": 700, "rlimit": 1200, "success": true },
+            { "function": "demo::field::lemma_square", "time": 200, "success": true }
+          ]
+        },
+        {
+          "module": "scalar",
+          "time": 350,
+          "function-breakdown": [
+            { "function": "demo::scalar::lemma_square", "time": 300, "success": false },
+            { "function": "demo::scalar::lemma_square", "time": 50, "success": true }
+          ]
+        }
+      ]
+    }
+  }
+}
+verification results:: 3 verified, 1 errors
+"#;
+
+    #[test]
+    fn test_parse_function_breakdown() {
+        let timings = VerificationTimings::parse(OUTPUT);
+
+        assert_eq!(timings.total_ms, Some(5120.0));
+        assert_eq!(timings.functions.len(), 3);
+        let square = &timings.functions["demo::field::FieldElement::square"];
+        assert_eq!(square.time_ms, 700.0);
+        assert_eq!(square.rlimit, Some(1200));
+        let scalar_lemma = &timings.functions["demo::scalar::lemma_square"];
+        assert_eq!(scalar_lemma.time_ms, 350.0);
+        assert_eq!(scalar_lemma.success, Some(false));
+
+        // Merged output of two runs
+        let merged = VerificationTimings::parse(&format!("{OUTPUT}{OUTPUT}"));
+        assert_eq!(
+            merged.functions["demo::field::FieldElement::square"].time_ms,
+            1400.0
+        );
+        assert!(VerificationTimings::parse("error: could not compile").is_empty());
+    }
+
+    #[test]
+    fn test_time_for_disambiguates_by_module() {
+        let timings = VerificationTimings::parse(OUTPUT);
+
+        let square = timings.time_for("FieldElement::square", "src/field.rs");
+        assert_eq!(square.unwrap().time_ms, 700.0);
+        let lemma = timings.time_for("lemma_square", "src/scalar/mod.rs");
+        assert_eq!(lemma.unwrap().function, "demo::scalar::lemma_square");
+        assert!(timings.time_for("lemma_square", "src/other.rs").is_none());
+        assert!(timings.time_for("missing", "src/field.rs").is_none());
+    }
+
+    fn diagnostic(path: &str, message: &str) -> String {
+        format!("error: {message}\n  --> {path}:10:5\n   |\n")
+    }
+
+    #[test]
+    fn test_retry_narrows_to_modules_with_rlimit_failures() {
+        let first = format!(
+            "{}\n{}\nverification results:: 8 verified, 2 errors\n",
+            diagnostic("src/field.rs", "Resource limit (rlimit) exceeded"),
+            diagnostic("src/scalar.rs", "postcondition not satisfied"),
+        );
+        let mut calls: Vec<Vec<String>> = Vec::new();
+        let run = verify_with_retry(
+            &["--time".to_string()],
+            &RetryPolicy::default().with_max_retries(3),
+            |args| {
+                calls.push(args.to_vec());
+                if calls.len() == 1 {
+                    Ok((first.clone(), 1))
+                } else {
+                    Ok((
+                        "verification results:: 3 verified, 0 errors\n".to_string(),
+                        0,
+                    ))
+                }
+            },
+        )
+        .unwrap();
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[1],
+            ["--time", "--rlimit", "20", "--verify-only-module", "field"]
+        );
+        // The genuine failure in scalar is kept, so the run still fails
+        assert_eq!(run.exit_code, 1);
+        assert!(run.output.contains("postcondition not satisfied"));
+        assert!(!run.output.contains("rlimit"));
+        let attempts = &run.verification_attempts;
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].transient_failures, 1);
+        assert_eq!(attempts[1].modules, vec!["field"]);
+        assert_eq!(attempts[1].rlimit, Some(20));
+    }
+
+    #[test]
+    fn test_retry_carries_still_failing_modules_forward() {
+        let rlimit = |path| diagnostic(path, "Resource limit (rlimit) exceeded");
+        let outputs = [
+            (
+                format!(
+                    "{}\n{}\nverification results:: 8 verified, 2 errors\n",
+                    rlimit("src/field.rs"),
+                    rlimit("src/scalar.rs")
+                ),
+                1,
+            ),
+            (
+                format!(
+                    "{}\nverification results:: 5 verified, 1 errors\n\nerror: aborting due to 1 previous error\n",
+                    rlimit("src/scalar.rs")
+                ),
+                1,
+            ),
+            ("verification results:: 2 verified, 0 errors\n".to_string(), 0),
+        ];
+        let mut calls: Vec<Vec<String>> = Vec::new();
+        let run = verify_with_retry(&[], &RetryPolicy::default().with_max_retries(3), |args| {
+            calls.push(args.to_vec());
+            Ok(outputs[calls.len() - 1].clone())
+        })
+        .unwrap();
+
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            calls[1],
+            [
+                "--rlimit",
+                "20",
+                "--verify-only-module",
+                "field",
+                "--verify-only-module",
+                "scalar"
+            ]
+        );
+        // Only the module still failing is retried, at the next rlimit
+        assert_eq!(
+            calls[2],
+            ["--rlimit", "40", "--verify-only-module", "scalar"]
+        );
+        assert_eq!(run.exit_code, 0);
+        assert!(!run.output.contains("rlimit"));
+        assert!(run.output.contains("2 verified, 0 errors"));
+        let modules: Vec<&[String]> = run
+            .verification_attempts
+            .iter()
+            .map(|attempt| attempt.modules.as_slice())
+            .collect();
+        assert_eq!(modules[2], ["scalar"]);
+    }
+
+    #[test]
+    fn test_retry_escalates_rlimit_until_exhausted() {
+        let failing = diagnostic(
+            "src/lib.rs",
+            "function body check: Resource limit (rlimit) exceeded",
+        );
+        let mut rlimits = Vec::new();
+        let run = verify_with_retry(
+            &[],
+            &RetryPolicy::default()
+                .with_max_retries(2)
+                .with_rlimit(30, 3)
+                .with_narrowing(false),
+            |args| {
+                rlimits.push(args.get(1).cloned());
+                Ok((failing.clone(), 1))
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            rlimits,
+            vec![None, Some("90".to_string()), Some("270".to_string())]
+        );
+        assert_eq!(run.exit_code, 1);
+        assert_eq!(run.verification_attempts.len(), 3);
+
+        // Genuine failures are not retried
+        let run = verify_with_retry(&[], &RetryPolicy::default().with_max_retries(2), |_| {
+            Ok((diagnostic("src/lib.rs", "assertion failed"), 1))
+        })
+        .unwrap();
+        assert_eq!(run.verification_attempts.len(), 1);
+    }
+
+    #[test]
+    fn test_modules_from_paths() {
+        let modules = modules_from_paths([
+            "src/lib.rs",
+            "src/field/mod.rs",
+            "src/field/square.rs",
+            "src/field.rs",
+            "src/bin/tool.rs",
+            "src/bin/cli/main.rs",
+            "src/bin/cli/args.rs",
+            "src/field/tests.rs",
+            "tests/integration.rs",
+            "examples/demo.rs",
+            "crates/core/benches/bench.rs",
+            "README.md",
+        ]);
+        assert_eq!(modules, vec!["", "field", "field::square", "field::tests"]);
+    }
+
+    #[test]
+    fn test_verify_modules_parallel_merges_in_module_order() {
+        let modules = modules_from_paths(["src/lib.rs", "src/a.rs", "src/b.rs", "src/c.rs"]);
+        let dir = tempfile::tempdir().unwrap();
+        let workers = Mutex::new(BTreeSet::new());
+
+        let merged = verify_modules_parallel(
+            &modules,
+            2,
+            &[],
+            &RetryPolicy::default(),
+            Some(dir.path()),
+            &Monitor::default(),
+            |worker, args| {
+                workers.lock().unwrap().insert(worker);
+                let module = args.last().unwrap().clone();
+                let exit_code = if module == "b" { 1 } else { 0 };
+                Ok((format!("verified {}", module), exit_code))
+            },
+        )
+        .unwrap();
+
+        assert_eq!(merged.exit_code, 1);
+        assert_eq!(
+            merged.output,
+            "verified --verify-root\nverified a\nverified b\nverified c\n"
+        );
+        let names: Vec<&str> = merged.modules.iter().map(|m| m.module.as_str()).collect();
+        assert_eq!(names, vec!["", "a", "b", "c"]);
+        assert!(workers.lock().unwrap().len() <= 2);
+        let log = std::fs::read_to_string(dir.path().join("b.log")).unwrap();
+        assert_eq!(log, "verified b\n");
+        assert!(dir.path().join("crate.log").exists());
+        assert!(!merged.cancelled);
+
+        // Interrupted while verifying `a`: only the crate root is kept
+        let monitor = Monitor::default();
+        let merged = verify_modules_parallel(
+            &modules,
+            1,
+            &[],
+            &RetryPolicy::default(),
+            None,
+            &monitor,
+            |_, args| {
+                if args.last().unwrap() == "a" {
+                    monitor.cancellation().cancel();
+                }
+                Ok(("verified".to_string(), 0))
+            },
+        )
+        .unwrap();
+        assert!(merged.cancelled);
+        let names: Vec<&str> = merged.modules.iter().map(|m| m.module.as_str()).collect();
+        assert_eq!(names, vec![""]);
+    }
+}
@@ -0,0 +1,307 @@
+//! Function-level verification cache.
+//!
+//! Verifying a whole project takes long, while most functions don't change
+//! between two runs. [`VerificationCache`] remembers the verification status
+//! (and time) of every function together with a hash of its source, so the
+//! next run only needs to re-verify the functions that are stale:
+//!
+//! - functions whose source changed, or that are new;
+//! - functions calling a changed function, whose contract may have changed;
+//! - functions using a changed spec function, also through other spec
+//!   functions, since spec function bodies are part of the proof obligations.
+//!
+//! Cached results are filled into the graph for all other functions. The
+//! cache also records the Verus version and arguments of the run; a run
+//! with other ones starts from an empty cache (see
+//! [`VerificationCache::for_run`]).
+
+use crate::error::{Error, Result};
+use crate::monitor::write_atomic;
+use crate::scip_utils::fnv1a;
+use crate::types::{D3Graph, D3Node, DeclKind, VerificationStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+/// Cached verification result of one function
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedVerification {
+    /// Hash of the function's source lines (see [`body_hashes`])
+    pub body_hash: u64,
+    pub status: VerificationStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rlimit: Option<u64>,
+}
+
+/// Verification results of the last run, keyed by graph node id
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerificationCache {
+    /// `verus --version` of the run the results come from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verus_version: Option<String>,
+    /// Verus arguments of that run, including the rlimit
+    #[serde(default)]
+    pub verus_args: Vec<String>,
+    pub functions: BTreeMap<String, CachedVerification>,
+}
+
+impl VerificationCache {
+    /// Read the cache written by [`VerificationCache::save`]; a missing or
+    /// unreadable cache is empty
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// The cache for a run with `verus_version` and `verus_args`: this one
+    /// if it was recorded with the same, else an empty one, since another
+    /// toolchain or rlimit can change any result
+    pub fn for_run(self, verus_version: Option<&str>, verus_args: &[String]) -> Self {
+        if self.verus_version.as_deref() == verus_version && self.verus_args == verus_args {
+            return self;
+        }
+        Self {
+            verus_version: verus_version.map(str::to_string),
+            verus_args: verus_args.to_vec(),
+            functions: BTreeMap::new(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(Error::Serialize)?;
+        write_atomic(path, json)?;
+        Ok(())
+    }
+
+    /// Ids of the graph nodes to re-verify, given the current `hashes` (see
+    /// the [module documentation](self)). Nodes without a hash (external
+    /// functions, or ones without source lines) are never stale.
+    pub fn stale_functions(
+        &self,
+        graph: &D3Graph,
+        hashes: &BTreeMap<String, u64>,
+    ) -> BTreeSet<String> {
+        let changed: BTreeSet<&str> = hashes
+            .iter()
+            .filter(|(id, hash)| self.functions.get(*id).map(|c| c.body_hash) != Some(**hash))
+            .map(|(id, _)| id.as_str())
+            .collect();
+        let nodes: HashMap<&str, &D3Node> = graph
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect();
+
+        graph
+            .nodes
+            .iter()
+            .filter(|node| hashes.contains_key(&node.id))
+            .filter(|node| {
+                changed.contains(node.id.as_str())
+                    || spec_dependencies(node, &nodes)
+                        .iter()
+                        .any(|dependency| changed.contains(dependency))
+            })
+            .map(|node| node.id.clone())
+            .collect()
+    }
+
+    /// Set the cached status, time and rlimit on the nodes that are not
+    /// `stale` and have no verification status yet; returns how many
+    pub fn fill_graph(&self, graph: &mut D3Graph, stale: &BTreeSet<String>) -> usize {
+        let mut filled = 0;
+        for node in &mut graph.nodes {
+            if node.verification_status.is_some() || stale.contains(&node.id) {
+                continue;
+            }
+            let Some(cached) = self.functions.get(&node.id) else {
+                continue;
+            };
+            node.verification_status = Some(cached.status);
+            node.verification_time_ms = node.verification_time_ms.or(cached.time_ms);
+            node.verification_rlimit = node.verification_rlimit.or(cached.rlimit);
+            filled += 1;
+        }
+        filled
+    }
+
+    /// Replace the cache with the results in `graph`, for the nodes that
+    /// have a verification status and a hash
+    pub fn record(&mut self, graph: &D3Graph, hashes: &BTreeMap<String, u64>) {
+        self.functions = graph
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let cached = CachedVerification {
+                    body_hash: *hashes.get(&node.id)?,
+                    status: node.verification_status?,
+                    time_ms: node.verification_time_ms,
+                    rlimit: node.verification_rlimit,
+                };
+                Some((node.id.clone(), cached))
+            })
+            .collect();
+    }
+}
+
+/// Functions whose changes can break the proof of `node`: its callees, and
+/// whatever spec functions among them use, transitively
+fn spec_dependencies<'a>(node: &'a D3Node, nodes: &HashMap<&str, &'a D3Node>) -> BTreeSet<&'a str> {
+    let mut dependencies = BTreeSet::new();
+    let mut queue: Vec<&str> = node.dependencies.iter().map(String::as_str).collect();
+    while let Some(id) = queue.pop() {
+        if !dependencies.insert(id) {
+            continue;
+        }
+        if let Some(dependency) = nodes.get(id).filter(|n| n.kind == DeclKind::Spec) {
+            queue.extend(dependency.dependencies.iter().map(String::as_str));
+        }
+    }
+    dependencies
+}
+
+/// Hash of the source lines of every graph node, read from the files under
+/// `project_root`.
+///
+/// Trailing whitespace is ignored, and so are the line numbers: moving a
+/// function doesn't make it stale. Nodes whose file can't be read or that
+/// have no line range get no hash.
+pub fn body_hashes(graph: &D3Graph, project_root: &Path) -> BTreeMap<String, u64> {
+    let mut by_file: BTreeMap<&str, Vec<&D3Node>> = BTreeMap::new();
+    for node in &graph.nodes {
+        if !node.relative_path.is_empty() && node.start_line.is_some() {
+            by_file.entry(&node.relative_path).or_default().push(node);
+        }
+    }
+
+    let mut hashes = BTreeMap::new();
+    for (relative_path, nodes) in by_file {
+        let Ok(source) = std::fs::read_to_string(project_root.join(relative_path)) else {
+            continue;
+        };
+        let lines: Vec<&str> = source.lines().collect();
+        for node in nodes {
+            let start = node.start_line.unwrap_or(1).max(1) - 1;
+            let end = node.end_line.unwrap_or(start + 1).min(lines.len());
+            let Some(body) = lines.get(start..end) else {
+                continue;
+            };
+            let hash = fnv1a(
+                body.iter()
+                    .flat_map(|line| line.trim_end().bytes().chain(std::iter::once(b'\n'))),
+            );
+            hashes.insert(node.id.clone(), hash);
+        }
+    }
+    hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::D3GraphMetadata;
+
+    fn node(id: &str, kind: DeclKind, lines: (usize, usize), dependencies: &[&str]) -> D3Node {
+        D3Node {
+            id: id.to_string(),
+            display_name: id.to_string(),
+            relative_path: "src/lib.rs".to_string(),
+            start_line: Some(lines.0),
+            end_line: Some(lines.1),
+            kind,
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stale_functions_follow_callees_and_spec_functions() {
+        let mut graph = D3Graph {
+            nodes: vec![
+                node("is_even", DeclKind::Spec, (1, 3), &[]),
+                node("is_small", DeclKind::Spec, (4, 6), &["is_even"]),
+                node("check", DeclKind::Exec, (7, 9), &["is_small"]),
+                node("lemma", DeclKind::Proof, (10, 12), &[]),
+                node("main", DeclKind::Exec, (13, 15), &["lemma"]),
+                node("other", DeclKind::Exec, (16, 18), &[]),
+            ],
+            links: Vec::new(),
+            metadata: D3GraphMetadata::default(),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let source: String = (1..=18).map(|line| format!("line {line}\n")).collect();
+        std::fs::write(dir.path().join("src/lib.rs"), &source).unwrap();
+
+        let hashes = body_hashes(&graph, dir.path());
+        assert_eq!(hashes.len(), 6);
+        let mut cache = VerificationCache::default();
+        assert_eq!(cache.stale_functions(&graph, &hashes).len(), 6);
+
+        for node in &mut graph.nodes {
+            node.verification_status = Some(VerificationStatus::Verified);
+        }
+        graph.nodes[5].verification_time_ms = Some(12.0);
+        cache.record(&graph, &hashes);
+        let path = dir.path().join("graph.verification-cache.json");
+        cache.save(&path).unwrap();
+        let cache = VerificationCache::load(&path);
+        assert!(cache.stale_functions(&graph, &hashes).is_empty());
+
+        // A changed spec function makes its users stale, through other spec
+        // functions; a changed proof function only its callers. Trailing
+        // whitespace and moved lines don't count as changes.
+        let source = source
+            .replace("line 2\n", "line 2 changed\n")
+            .replace("line 11\n", "line 11  \n")
+            .replace("line 14\n", "");
+        std::fs::write(dir.path().join("src/lib.rs"), source).unwrap();
+        for node in &mut graph.nodes {
+            node.verification_status = None;
+        }
+        graph.nodes[4].end_line = Some(14);
+        graph.nodes[5].start_line = Some(15);
+        graph.nodes[5].end_line = Some(17);
+        let hashes = body_hashes(&graph, dir.path());
+        let stale = cache.stale_functions(&graph, &hashes);
+        assert_eq!(
+            stale.iter().map(String::as_str).collect::<Vec<_>>(),
+            ["check", "is_even", "is_small", "main"]
+        );
+
+        assert_eq!(cache.fill_graph(&mut graph, &stale), 2);
+        assert_eq!(graph.nodes[5].verification_time_ms, Some(12.0));
+        assert_eq!(graph.nodes[2].verification_status, None);
+    }
+
+    #[test]
+    fn test_for_run_discards_results_of_other_runs() {
+        let args = vec!["--rlimit".to_string(), "10".to_string()];
+        let mut cache = VerificationCache::default().for_run(Some("0.2025.1"), &args);
+        cache.functions.insert(
+            "f".to_string(),
+            CachedVerification {
+                body_hash: 1,
+                status: VerificationStatus::Verified,
+                time_ms: None,
+                rlimit: None,
+            },
+        );
+
+        let same = cache.clone().for_run(Some("0.2025.1"), &args);
+        assert_eq!(same.functions.len(), 1);
+
+        let upgraded = cache.clone().for_run(Some("0.2025.2"), &args);
+        assert!(upgraded.functions.is_empty());
+        assert_eq!(upgraded.verus_version.as_deref(), Some("0.2025.2"));
+
+        let more_rlimit = vec!["--rlimit".to_string(), "20".to_string()];
+        let rerun = cache.for_run(Some("0.2025.1"), &more_rlimit);
+        assert!(rerun.functions.is_empty());
+        assert_eq!(rerun.verus_args, more_rlimit);
+    }
+}
@@ -0,0 +1,281 @@
+//! Which Verus release verifies a project
+//!
+//! `cargo verus` runs the `cargo-verus` binary found on `PATH`, so a machine
+//! with several Verus releases verifies every project with the same one.
+//! [`VerusToolchain::discover`] picks the release directory (the one holding
+//! `cargo-verus` and `verus`), by priority:
+//!
+//! 1. the `--verus-path` option;
+//! 2. the `VERUS_PATH` environment variable;
+//! 3. `path` in the project's `verus-toolchain.toml`;
+//! 4. `cargo-verus` on `PATH`.
+//!
+//! The toolchain file can also pin the release a project needs:
+//!
+//! ```toml
+//! path = "../verus-release/verus-x86-linux"   # relative to the project
+//! version = "0.2025.06.14"                    # prefix of `verus --version`
+//! ```
+//!
+//! [`VerusToolchain::check_version`] reports the version that actually runs,
+//! which the pipeline stores in the graph metadata so results from different
+//! machines can be compared.
+
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Environment variable with the Verus release directory
+pub const VERUS_PATH_ENV: &str = "VERUS_PATH";
+
+/// Per-project toolchain file, in the project root
+pub const TOOLCHAIN_FILE: &str = "verus-toolchain.toml";
+
+/// Contents of [`TOOLCHAIN_FILE`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolchainConfig {
+    /// Verus release directory (or its `cargo-verus` binary), relative to
+    /// the project root
+    pub path: Option<PathBuf>,
+    /// Required Verus version, or a prefix of it (`0.2025.06.14`)
+    pub version: Option<String>,
+}
+
+impl ToolchainConfig {
+    /// Read the toolchain file of `project`, if it has one
+    pub fn load(project: &Path) -> Result<Option<Self>> {
+        let path = project.join(TOOLCHAIN_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| Error::InvalidData(format!("invalid {}: {}", path.display(), e)))
+    }
+}
+
+/// Where [`VerusToolchain::discover`] found the toolchain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolchainSource {
+    /// The `--verus-path` option
+    Option,
+    /// The `VERUS_PATH` environment variable
+    Environment,
+    /// The project's [`TOOLCHAIN_FILE`]
+    ConfigFile,
+    /// `cargo-verus` on `PATH`
+    Path,
+}
+
+impl std::fmt::Display for ToolchainSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToolchainSource::Option => f.write_str("--verus-path"),
+            ToolchainSource::Environment => f.write_str(VERUS_PATH_ENV),
+            ToolchainSource::ConfigFile => f.write_str(TOOLCHAIN_FILE),
+            ToolchainSource::Path => f.write_str("PATH"),
+        }
+    }
+}
+
+/// The Verus release to run `cargo verus` with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerusToolchain {
+    /// Release directory; `None` runs whatever is on `PATH`
+    pub bin_dir: Option<PathBuf>,
+    pub source: ToolchainSource,
+    /// Version pinned by the project's toolchain file
+    pub required_version: Option<String>,
+}
+
+impl Default for VerusToolchain {
+    fn default() -> Self {
+        Self {
+            bin_dir: None,
+            source: ToolchainSource::Path,
+            required_version: None,
+        }
+    }
+}
+
+impl VerusToolchain {
+    /// Find the toolchain for `project` (see the [module
+    /// documentation](self)); `verus_path` is the `--verus-path` option.
+    ///
+    /// Fails if the chosen directory has no `cargo-verus` or the toolchain
+    /// file is malformed; the version is only checked by
+    /// [`VerusToolchain::check_version`].
+    pub fn discover(project: &Path, verus_path: Option<&Path>) -> Result<Self> {
+        discover_with_env(project, verus_path, std::env::var_os(VERUS_PATH_ENV))
+    }
+
+    /// A `cargo` command that runs this toolchain's `cargo verus`
+    pub fn cargo_command(&self) -> Command {
+        let mut command = Command::new("cargo");
+        if let Some(bin_dir) = &self.bin_dir {
+            // cargo looks up `cargo-verus` on PATH, so put the release first
+            let path = std::env::var_os("PATH").unwrap_or_default();
+            let paths = std::iter::once(bin_dir.clone()).chain(std::env::split_paths(&path));
+            if let Ok(path) = std::env::join_paths(paths) {
+                command.env("PATH", path);
+            }
+        }
+        command
+    }
+
+    /// The output of `verus --version`, reduced to the version number
+    /// (`None` if `verus` can't be run)
+    pub fn version(&self) -> Option<String> {
+        let verus = match &self.bin_dir {
+            Some(bin_dir) => bin_dir.join(format!("verus{}", std::env::consts::EXE_SUFFIX)),
+            None => PathBuf::from("verus"),
+        };
+        let output = Command::new(verus).arg("--version").output().ok()?;
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        parse_version(&text)
+    }
+
+    /// [`VerusToolchain::version`], failing if it doesn't match the version
+    /// the project requires
+    pub fn check_version(&self) -> Result<Option<String>> {
+        let version = self.version();
+        if let Some(required) = &self.required_version {
+            let matches = version
+                .as_deref()
+                .is_some_and(|version| version_matches(required, version));
+            if !matches {
+                return Err(Error::VerificationFailed(format!(
+                    "{} requires Verus {}, but the toolchain from {} is {}",
+                    TOOLCHAIN_FILE,
+                    required,
+                    self.source,
+                    version.as_deref().unwrap_or("unknown")
+                )));
+            }
+        }
+        Ok(version)
+    }
+}
+
+fn discover_with_env(
+    project: &Path,
+    verus_path: Option<&Path>,
+    env_path: Option<OsString>,
+) -> Result<VerusToolchain> {
+    let config = ToolchainConfig::load(project)?.unwrap_or_default();
+    let (path, source) = if let Some(path) = verus_path {
+        (path.to_path_buf(), ToolchainSource::Option)
+    } else if let Some(path) = env_path.filter(|path| !path.is_empty()) {
+        (PathBuf::from(path), ToolchainSource::Environment)
+    } else if let Some(path) = &config.path {
+        (project.join(path), ToolchainSource::ConfigFile)
+    } else {
+        return Ok(VerusToolchain {
+            required_version: config.version,
+            ..VerusToolchain::default()
+        });
+    };
+
+    // Accept the release directory or one of its binaries
+    let bin_dir = if path.is_file() {
+        path.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        path
+    };
+    let cargo_verus = bin_dir.join(format!("cargo-verus{}", std::env::consts::EXE_SUFFIX));
+    if !cargo_verus.is_file() {
+        return Err(Error::InvalidPath(format!(
+            "no cargo-verus in {} (from {})",
+            bin_dir.display(),
+            source
+        )));
+    }
+    Ok(VerusToolchain {
+        bin_dir: Some(bin_dir),
+        source,
+        required_version: config.version,
+    })
+}
+
+/// The version number from `verus --version` output (its `Version:` line),
+/// or the first line for other formats
+fn parse_version(output: &str) -> Option<String> {
+    let version = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Version:"))
+        .or_else(|| output.lines().map(str::trim).find(|line| !line.is_empty()))?;
+    Some(version.trim().to_string())
+}
+
+/// Whether `version` is `required` or a more specific version of it
+/// (`0.2025.06.14.9b557d7` matches `0.2025.06.14`)
+fn version_matches(required: &str, version: &str) -> bool {
+    version
+        .strip_prefix(required)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_priority_and_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let release = |name: &str| {
+            let bin_dir = dir.path().join(name);
+            std::fs::create_dir(&bin_dir).unwrap();
+            let cargo_verus = format!("cargo-verus{}", std::env::consts::EXE_SUFFIX);
+            std::fs::write(bin_dir.join(&cargo_verus), "").unwrap();
+            (bin_dir.clone(), bin_dir.join(cargo_verus))
+        };
+        let (old_dir, old_binary) = release("verus-old");
+        let (new_dir, _) = release("verus-new");
+
+        let toolchain = discover_with_env(&project, None, None).unwrap();
+        assert_eq!(toolchain, VerusToolchain::default());
+
+        std::fs::write(
+            project.join(TOOLCHAIN_FILE),
+            "path = \"../verus-new\"\nversion = \"0.2025.06.14\"\n",
+        )
+        .unwrap();
+        let toolchain = discover_with_env(&project, None, None).unwrap();
+        assert_eq!(toolchain.bin_dir, Some(project.join("../verus-new")));
+        assert_eq!(toolchain.source, ToolchainSource::ConfigFile);
+        assert_eq!(toolchain.required_version.as_deref(), Some("0.2025.06.14"));
+
+        let toolchain = discover_with_env(&project, None, Some(old_dir.clone().into())).unwrap();
+        assert_eq!(toolchain.bin_dir.as_ref(), Some(&old_dir));
+        assert_eq!(toolchain.source, ToolchainSource::Environment);
+        // The binary stands for its directory; the option beats the rest
+        let toolchain =
+            discover_with_env(&project, Some(&old_binary), Some(new_dir.into())).unwrap();
+        assert_eq!(toolchain.bin_dir, Some(old_dir));
+        assert_eq!(toolchain.source, ToolchainSource::Option);
+        assert!(discover_with_env(&project, Some(&project), None).is_err());
+
+        std::fs::write(project.join(TOOLCHAIN_FILE), "release = 1\n").unwrap();
+        assert!(discover_with_env(&project, None, None).is_err());
+
+        let output = "Verus\n  Version: 0.2025.06.14.9b557d7\n  Profile: release\n";
+        assert_eq!(
+            parse_version(output).as_deref(),
+            Some("0.2025.06.14.9b557d7")
+        );
+        assert_eq!(
+            parse_version("verus 0.1.0\n").as_deref(),
+            Some("verus 0.1.0")
+        );
+        assert!(version_matches("0.2025.06.14", "0.2025.06.14.9b557d7"));
+        assert!(version_matches("0.2025.06.14", "0.2025.06.14"));
+        assert!(!version_matches("0.2025.06.1", "0.2025.06.14"));
+    }
+}
@@ -0,0 +1,384 @@
+//! Cargo workspace detection and per-crate attribution of graph nodes
+//!
+//! Large Verus projects are usually Cargo workspaces. Indexing the workspace
+//! root yields one SCIP index covering every member; this module maps each
+//! node back to the member crate whose directory contains its source file
+//! and summarizes the graph per crate.
+
+use crate::error::{Error, Result};
+use crate::types::{CrateSummary, D3Graph, DeclKind};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A member crate of a Cargo workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    /// Package name from the member's Cargo.toml
+    pub name: String,
+    /// Member directory relative to the workspace root (empty for a root package)
+    pub path: String,
+}
+
+/// A Cargo workspace and its member crates
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub members: Vec<WorkspaceMember>,
+}
+
+impl Workspace {
+    /// Detect a Cargo workspace at `root`.
+    ///
+    /// Returns `Ok(None)` if `root/Cargo.toml` has no `[workspace]` section.
+    /// Member entries may end in a `*` pattern (`crates/*`); members listed
+    /// under `exclude` and directories without a Cargo.toml are skipped.
+    pub fn detect(root: &Path) -> Result<Option<Self>> {
+        let manifest = read_manifest(&root.join("Cargo.toml"))?;
+        let Some(workspace) = manifest.get("workspace").and_then(|w| w.as_table()) else {
+            return Ok(None);
+        };
+
+        let strings = |key: &str| -> Vec<String> {
+            workspace
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(normalize_member_path)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let excluded: HashSet<String> = strings("exclude").into_iter().collect();
+
+        let mut paths: Vec<String> = Vec::new();
+        for pattern in strings("members") {
+            for path in expand_member_pattern(root, &pattern) {
+                if !excluded.contains(&path) && !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        // A root package is a member too
+        if manifest.contains_key("package") {
+            paths.push(String::new());
+        }
+
+        let mut members = Vec::new();
+        for path in paths {
+            let member_manifest = read_manifest(&root.join(&path).join("Cargo.toml"))?;
+            let name = member_manifest
+                .get("package")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| path.rsplit('/').next().unwrap_or_default().to_string());
+            members.push(WorkspaceMember { name, path });
+        }
+        members.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Some(Self {
+            root: root.to_path_buf(),
+            members,
+        }))
+    }
+
+    /// The member crate containing a file, given its path relative to the
+    /// workspace root (the most deeply nested member wins)
+    pub fn crate_for_path(&self, relative_path: &str) -> Option<&str> {
+        let relative_path = relative_path.trim_start_matches("./");
+        self.members
+            .iter()
+            .filter(|member| {
+                member.path.is_empty()
+                    || relative_path
+                        .strip_prefix(member.path.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|member| member.path.len())
+            .map(|member| member.name.as_str())
+    }
+
+    /// Set `crate_name` on every node of the graph and store per-crate
+    /// summaries in its metadata
+    pub fn assign_crate_names(&self, graph: &mut D3Graph) {
+        for node in &mut graph.nodes {
+            node.crate_name = self.crate_for_path(&node.relative_path).map(str::to_string);
+        }
+        graph.metadata.crates = summarize_by_crate(graph);
+    }
+}
+
+/// Summarize a graph by the `crate_name` of its nodes.
+///
+/// Nodes without a crate (e.g. external stubs) are left out. Calls are
+/// counted once per caller/callee pair, whatever their link types.
+pub fn summarize_by_crate(graph: &D3Graph) -> Vec<CrateSummary> {
+    let mut summaries: BTreeMap<&str, CrateSummary> = BTreeMap::new();
+    let mut crate_of: HashMap<&str, &str> = HashMap::new();
+
+    for node in &graph.nodes {
+        let Some(crate_name) = node.crate_name.as_deref() else {
+            continue;
+        };
+        crate_of.insert(node.id.as_str(), crate_name);
+
+        let summary = summaries.entry(crate_name).or_insert_with(|| CrateSummary {
+            crate_name: crate_name.to_string(),
+            ..CrateSummary::default()
+        });
+        summary.functions += 1;
+        match node.kind {
+            DeclKind::Exec => summary.exec_functions += 1,
+            DeclKind::Proof => summary.proof_functions += 1,
+            DeclKind::Spec => summary.spec_functions += 1,
+        }
+    }
+
+    let calls: HashSet<(&str, &str)> = graph
+        .links
+        .iter()
+        .map(|link| (link.source.as_str(), link.target.as_str()))
+        .collect();
+    for (source, target) in calls {
+        let (Some(&from), Some(&to)) = (crate_of.get(source), crate_of.get(target)) else {
+            continue;
+        };
+        if from == to {
+            summaries.entry(from).or_default().internal_calls += 1;
+        } else {
+            summaries
+                .entry(from)
+                .or_default()
+                .outgoing_cross_crate_calls += 1;
+            summaries.entry(to).or_default().incoming_cross_crate_calls += 1;
+        }
+    }
+
+    summaries.into_values().collect()
+}
+
+fn read_manifest(path: &Path) -> Result<toml::Table> {
+    let content = fs::read_to_string(path)?;
+    content
+        .parse::<toml::Table>()
+        .map_err(|e| Error::InvalidData(format!("Failed to parse {}: {}", path.display(), e)))
+}
+
+fn normalize_member_path(path: &str) -> String {
+    path.trim_start_matches("./")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Expand a member entry; a `*` in the last path component matches any
+/// directory name with that prefix and suffix
+fn expand_member_pattern(root: &Path, pattern: &str) -> Vec<String> {
+    let (parent, last) = match pattern.rsplit_once('/') {
+        Some((parent, last)) => (parent, last),
+        None => ("", pattern),
+    };
+    let Some((prefix, suffix)) = last.split_once('*') else {
+        return vec![pattern.to_string()];
+    };
+
+    let Ok(entries) = fs::read_dir(root.join(parent)) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("Cargo.toml").is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        })
+        .map(|name| {
+            if parent.is_empty() {
+                name
+            } else {
+                format!("{}/{}", parent, name)
+            }
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{D3GraphMetadata, D3Link, D3Node};
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn sample_workspace() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\nexclude = [\"crates/old\"]\n",
+        );
+        write(
+            dir.path(),
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"my-core\"\n",
+        );
+        write(
+            dir.path(),
+            "crates/old/Cargo.toml",
+            "[package]\nname = \"old\"\n",
+        );
+        write(
+            dir.path(),
+            "tools/cli/Cargo.toml",
+            "[package]\nname = \"my-cli\"\n",
+        );
+        // Not a crate: no Cargo.toml
+        fs::create_dir_all(dir.path().join("crates/docs")).unwrap();
+        dir
+    }
+
+    fn node(id: &str, relative_path: &str, kind: DeclKind) -> D3Node {
+        D3Node {
+            id: id.to_string(),
+            display_name: id.to_string(),
+            symbol: id.to_string(),
+            full_path: relative_path.to_string(),
+            relative_path: relative_path.to_string(),
+            file_name: "lib.rs".to_string(),
+            parent_folder: "src".to_string(),
+            start_line: None,
+            end_line: None,
+            is_libsignal: false,
+            dependencies: vec![],
+            dependents: vec![],
+            kind,
+            crate_name: None,
+            ..Default::default()
+        }
+    }
+
+    fn link(source: &str, target: &str, link_type: &str) -> D3Link {
+        D3Link {
+            source: source.to_string(),
+            target: target.to_string(),
+            link_type: link_type.to_string(),
+            edge_kind: None,
+            weight: None,
+            lines: Vec::new(),
+        }
+    }
+
+    // ==========================================================================
+    // Detection tests
+    // ==========================================================================
+
+    #[test]
+    fn test_detect_workspace_members() {
+        let dir = sample_workspace();
+        let workspace = Workspace::detect(dir.path()).unwrap().unwrap();
+
+        let members: Vec<(&str, &str)> = workspace
+            .members
+            .iter()
+            .map(|m| (m.name.as_str(), m.path.as_str()))
+            .collect();
+        assert_eq!(
+            members,
+            vec![("my-cli", "tools/cli"), ("my-core", "crates/core")]
+        );
+    }
+
+    #[test]
+    fn test_detect_single_package_is_not_a_workspace() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "Cargo.toml", "[package]\nname = \"single\"\n");
+
+        assert!(Workspace::detect(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_crate_for_path_prefers_nested_member() {
+        let workspace = Workspace {
+            root: PathBuf::new(),
+            members: vec![
+                WorkspaceMember {
+                    name: "root".to_string(),
+                    path: String::new(),
+                },
+                WorkspaceMember {
+                    name: "core".to_string(),
+                    path: "crates/core".to_string(),
+                },
+            ],
+        };
+
+        assert_eq!(
+            workspace.crate_for_path("crates/core/src/lib.rs"),
+            Some("core")
+        );
+        assert_eq!(
+            workspace.crate_for_path("crates/core2/src/lib.rs"),
+            Some("root")
+        );
+        assert_eq!(workspace.crate_for_path("src/main.rs"), Some("root"));
+    }
+
+    // ==========================================================================
+    // Summary tests
+    // ==========================================================================
+
+    #[test]
+    fn test_assign_crate_names_and_summarize() {
+        let dir = sample_workspace();
+        let workspace = Workspace::detect(dir.path()).unwrap().unwrap();
+        let mut graph = D3Graph {
+            nodes: vec![
+                node("a", "crates/core/src/lib.rs", DeclKind::Exec),
+                node("b", "crates/core/src/lemmas.rs", DeclKind::Proof),
+                node("c", "tools/cli/src/main.rs", DeclKind::Exec),
+                node("ext", "vstd/seq.rs", DeclKind::Spec),
+            ],
+            links: vec![
+                link("a", "b", "inner"),
+                link("a", "b", "postcondition"),
+                link("c", "a", "inner"),
+                link("c", "ext", "inner"),
+            ],
+            metadata: D3GraphMetadata {
+                total_nodes: 4,
+                total_edges: 4,
+                project_root: String::new(),
+                generated_at: String::new(),
+                github_url: None,
+                crates: Vec::new(),
+                ..Default::default()
+            },
+        };
+
+        workspace.assign_crate_names(&mut graph);
+
+        assert_eq!(graph.nodes[0].crate_name.as_deref(), Some("my-core"));
+        assert_eq!(graph.nodes[3].crate_name, None);
+
+        let crates = &graph.metadata.crates;
+        assert_eq!(crates.len(), 2);
+        assert_eq!(crates[0].crate_name, "my-cli");
+        assert_eq!(crates[0].outgoing_cross_crate_calls, 1);
+        assert_eq!(crates[1].crate_name, "my-core");
+        assert_eq!(crates[1].functions, 2);
+        assert_eq!(crates[1].proof_functions, 1);
+        assert_eq!(crates[1].internal_calls, 1);
+        assert_eq!(crates[1].incoming_cross_crate_calls, 1);
+    }
+}
@@ -0,0 +1,307 @@
+//! Cyclomatic and cognitive complexity for Verus function bodies
+//!
+//! Computed directly on the `verus_syn` AST, so Verus code can be measured
+//! without running rust-code-analysis (which cannot parse `verus! {}`).
+//!
+//! - **Cyclomatic** (McCabe): 1 plus one per `if`, loop, match arm, `&&`,
+//!   `||` and `?`, following rust-code-analysis.
+//! - **Cognitive** (SonarSource): +1 per `if`/`else if`/`else`, `match`,
+//!   loop, labeled `break`/`continue` and sequence of like boolean
+//!   operators; `if`, `match` and loops also add their nesting depth.
+//!
+//! Loop invariants and other Verus clauses attached to loops are not
+//! counted; only the control flow of the body is.
+
+use serde::{Deserialize, Serialize};
+use verus_syn::{visit::Visit, BinOp, Block, Expr, ImplItem, Item, ItemFn, TraitItem};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BodyComplexityMetrics {
+    /// McCabe cyclomatic complexity (1 for straight-line code)
+    pub cyclomatic: usize,
+
+    /// SonarSource cognitive complexity
+    pub cognitive: usize,
+
+    /// Number of `if` / `else if` branches
+    pub branches: usize,
+
+    /// Number of `while`, `for` and `loop` expressions
+    pub loops: usize,
+
+    /// Number of `match` expressions
+    pub matches: usize,
+
+    /// Deepest nesting of control flow structures
+    pub max_nesting: usize,
+}
+
+impl Default for BodyComplexityMetrics {
+    fn default() -> Self {
+        Self {
+            cyclomatic: 1,
+            cognitive: 0,
+            branches: 0,
+            loops: 0,
+            matches: 0,
+            max_nesting: 0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ComplexityVisitor {
+    metrics: BodyComplexityMetrics,
+    nesting: usize,
+    /// Logical operator of the enclosing expression (`Some(true)` for `&&`),
+    /// used to count sequences of like operators once
+    parent_logical: Option<bool>,
+}
+
+impl ComplexityVisitor {
+    /// Count a structure that increments cognitive complexity by nesting
+    fn enter_structure(&mut self) {
+        self.metrics.cognitive += 1 + self.nesting;
+        self.metrics.max_nesting = self.metrics.max_nesting.max(self.nesting + 1);
+    }
+
+    fn visit_nested_block(&mut self, block: &Block) {
+        self.nesting += 1;
+        self.visit_block(block);
+        self.nesting -= 1;
+    }
+
+    fn visit_nested_expr(&mut self, expr: &Expr) {
+        self.nesting += 1;
+        self.visit_expr(expr);
+        self.nesting -= 1;
+    }
+
+    /// `else` / `else if` branches: +1 each, without a nesting increment
+    fn visit_else(&mut self, else_branch: &Expr) {
+        self.metrics.cognitive += 1;
+        match else_branch {
+            Expr::If(else_if) => {
+                self.metrics.branches += 1;
+                self.metrics.cyclomatic += 1;
+                self.visit_expr(&else_if.cond);
+                self.visit_nested_block(&else_if.then_branch);
+                if let Some((_, next)) = &else_if.else_branch {
+                    self.visit_else(next);
+                }
+            }
+            _ => self.visit_nested_expr(else_branch),
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ComplexityVisitor {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        let parent_logical = self.parent_logical.take();
+
+        match expr {
+            Expr::If(expr_if) => {
+                self.enter_structure();
+                self.metrics.branches += 1;
+                self.metrics.cyclomatic += 1;
+                self.visit_expr(&expr_if.cond);
+                self.visit_nested_block(&expr_if.then_branch);
+                if let Some((_, else_branch)) = &expr_if.else_branch {
+                    self.visit_else(else_branch);
+                }
+            }
+            Expr::Match(expr_match) => {
+                self.enter_structure();
+                self.metrics.matches += 1;
+                self.metrics.cyclomatic += expr_match.arms.len();
+                self.visit_expr(&expr_match.expr);
+                self.nesting += 1;
+                for arm in &expr_match.arms {
+                    if let Some((_, guard)) = &arm.guard {
+                        self.visit_expr(guard);
+                    }
+                    self.visit_expr(&arm.body);
+                }
+                self.nesting -= 1;
+            }
+            Expr::While(expr_while) => {
+                self.enter_structure();
+                self.metrics.loops += 1;
+                self.metrics.cyclomatic += 1;
+                self.visit_expr(&expr_while.cond);
+                self.visit_nested_block(&expr_while.body);
+            }
+            Expr::ForLoop(expr_for) => {
+                self.enter_structure();
+                self.metrics.loops += 1;
+                self.metrics.cyclomatic += 1;
+                self.visit_expr(&expr_for.expr);
+                self.visit_nested_block(&expr_for.body);
+            }
+            Expr::Loop(expr_loop) => {
+                self.enter_structure();
+                self.metrics.loops += 1;
+                self.metrics.cyclomatic += 1;
+                self.visit_nested_block(&expr_loop.body);
+            }
+            Expr::Closure(closure) => self.visit_nested_expr(&closure.body),
+            Expr::Try(expr_try) => {
+                self.metrics.cyclomatic += 1;
+                self.visit_expr(&expr_try.expr);
+            }
+            Expr::Break(expr_break) => {
+                if expr_break.label.is_some() {
+                    self.metrics.cognitive += 1;
+                }
+                verus_syn::visit::visit_expr(self, expr);
+            }
+            Expr::Continue(expr_continue) => {
+                if expr_continue.label.is_some() {
+                    self.metrics.cognitive += 1;
+                }
+            }
+            Expr::Binary(bin) if matches!(bin.op, BinOp::And(_) | BinOp::Or(_)) => {
+                let is_and = matches!(bin.op, BinOp::And(_));
+                self.metrics.cyclomatic += 1;
+                if parent_logical != Some(is_and) {
+                    self.metrics.cognitive += 1;
+                }
+                self.parent_logical = Some(is_and);
+                self.visit_expr(&bin.left);
+                self.parent_logical = Some(is_and);
+                self.visit_expr(&bin.right);
+            }
+            _ => verus_syn::visit::visit_expr(self, expr),
+        }
+    }
+}
+
+/// Compute complexity metrics for an already-parsed function body
+pub fn analyze_block_complexity(block: &Block) -> BodyComplexityMetrics {
+    let mut visitor = ComplexityVisitor::default();
+    visitor.visit_block(block);
+    visitor.metrics
+}
+
+/// Compute complexity metrics for a function's source text.
+///
+/// Accepts a whole function item (free function, impl method or trait
+/// method) or just the statements of a body.
+pub fn analyze_body_complexity(body: &str) -> Result<BodyComplexityMetrics, String> {
+    Ok(parse_body_block(body)?
+        .as_ref()
+        .map(analyze_block_complexity)
+        .unwrap_or_default())
+}
+
+/// Parse the body block of a function's source text (see
+/// [`analyze_body_complexity`]). Returns `None` for a trait method without
+/// a default body.
+pub(crate) fn parse_body_block(body: &str) -> Result<Option<Block>, String> {
+    if let Ok(item_fn) = verus_syn::parse_str::<ItemFn>(body) {
+        return Ok(Some(*item_fn.block));
+    }
+    if let Ok(Item::Fn(item_fn)) = verus_syn::parse_str::<Item>(body) {
+        return Ok(Some(*item_fn.block));
+    }
+    if let Ok(ImplItem::Fn(impl_fn)) = verus_syn::parse_str::<ImplItem>(body) {
+        return Ok(Some(impl_fn.block));
+    }
+    if let Ok(TraitItem::Fn(trait_fn)) = verus_syn::parse_str::<TraitItem>(body) {
+        return Ok(trait_fn.default);
+    }
+
+    verus_syn::parse_str(&format!("{{\n{}\n}}", body))
+        .map(Some)
+        .map_err(|e| format!("Failed to parse function body: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_straight_line_code() {
+        let metrics = analyze_body_complexity("fn f(x: u32) -> u32 { x + 1 }").unwrap();
+
+        assert_eq!(metrics, BodyComplexityMetrics::default());
+    }
+
+    #[test]
+    fn test_if_else_chain() {
+        let body = "fn sign(x: i32) -> i32 { if x > 0 { 1 } else if x < 0 { -1 } else { 0 } }";
+        let metrics = analyze_body_complexity(body).unwrap();
+
+        // if, else if: two decision points
+        assert_eq!(metrics.cyclomatic, 3);
+        // if +1, else if +1, else +1
+        assert_eq!(metrics.cognitive, 3);
+        assert_eq!(metrics.branches, 2);
+        assert_eq!(metrics.max_nesting, 1);
+    }
+
+    #[test]
+    fn test_nesting_increments_cognitive() {
+        let body = "fn f(v: Vec<u32>) { for x in v { if x > 0 { while x > 1 { } } } }";
+        let metrics = analyze_body_complexity(body).unwrap();
+
+        assert_eq!(metrics.cyclomatic, 4);
+        // for +1, if +1+1, while +1+2
+        assert_eq!(metrics.cognitive, 6);
+        assert_eq!(metrics.loops, 2);
+        assert_eq!(metrics.max_nesting, 3);
+    }
+
+    #[test]
+    fn test_match_arms_and_boolean_sequences() {
+        let body = r#"
+            fn f(x: Option<u32>, a: bool, b: bool, c: bool) -> u32 {
+                if a && b && c || a {
+                    return 0;
+                }
+                match x {
+                    Some(v) => v,
+                    None => 0,
+                }
+            }
+        "#;
+        let metrics = analyze_body_complexity(body).unwrap();
+
+        // if +1, three logical operators +3, two match arms +2
+        assert_eq!(metrics.cyclomatic, 7);
+        // if +1, `&& &&` +1, `||` +1, match +1
+        assert_eq!(metrics.cognitive, 4);
+        assert_eq!(metrics.matches, 1);
+    }
+
+    #[test]
+    fn test_verus_function_with_invariant() {
+        let body = r#"
+            fn sum(n: u32) -> (r: u32)
+                requires n < 100,
+            {
+                let mut i = 0;
+                let mut s = 0;
+                while i < n
+                    invariant i <= n, s <= i * 100,
+                {
+                    i = i + 1;
+                }
+                s
+            }
+        "#;
+        let metrics = analyze_body_complexity(body).unwrap();
+
+        assert_eq!(metrics.cyclomatic, 2);
+        assert_eq!(metrics.cognitive, 1);
+        assert_eq!(metrics.loops, 1);
+    }
+
+    #[test]
+    fn test_bare_statements() {
+        let metrics = analyze_body_complexity("let y = x?; y").unwrap();
+
+        assert_eq!(metrics.cyclomatic, 2);
+        assert_eq!(metrics.cognitive, 0);
+    }
+}
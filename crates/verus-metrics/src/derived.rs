@@ -0,0 +1,439 @@
+//! Derived ratios over per-function metrics
+//!
+//! Takes the atoms JSON enriched by `compute_metrics` (and optionally
+//! `compute_proof_metrics`) and computes the ratios usually reported for a
+//! verified codebase, per function and per module (source file):
+//!
+//! - **Spec-to-body volume**: Halstead volume of the `requires`, `ensures`
+//!   and `decreases` clauses divided by the Halstead volume of the body
+//! - **Proof-to-exec lines**: lines of ghost code (`proof { }` blocks,
+//!   `assert ... by { }`, `assert`/`assume` statements; every line of a
+//!   proof fn) divided by the remaining lines of executable code
+//! - **Lemmas per exec function**: proof fns in a module divided by its exec
+//!   fns; per function, the number of distinct lemmas called directly
+
+use crate::body_complexity::parse_body_block;
+use crate::spec_halstead::analyze_block_halstead;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::io::Write;
+use std::sync::OnceLock;
+
+/// Halstead metrics of a single spec clause, as written by `compute_metrics`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ClauseMetrics {
+    #[serde(default)]
+    pub halstead_volume: Option<f64>,
+}
+
+/// The parts of `compute_metrics` output used for derived ratios
+#[derive(Debug, Clone, Deserialize)]
+pub struct FunctionMetricsInput {
+    #[serde(default = "unknown_mode")]
+    pub function_mode: String,
+    #[serde(default)]
+    pub requires_specs: Vec<ClauseMetrics>,
+    #[serde(default)]
+    pub ensures_specs: Vec<ClauseMetrics>,
+    #[serde(default)]
+    pub decreases_specs: Vec<ClauseMetrics>,
+}
+
+fn unknown_mode() -> String {
+    "unknown".to_string()
+}
+
+/// An atom with metrics, as written by `compute_metrics`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AtomWithMetrics {
+    pub identifier: String,
+    pub display_name: String,
+    pub relative_path: String,
+    #[serde(default)]
+    pub deps: Vec<String>,
+    #[serde(default)]
+    pub body: String,
+    pub metrics: FunctionMetricsInput,
+}
+
+/// Derived ratios for one function
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionRatios {
+    pub identifier: String,
+    pub display_name: String,
+    pub module: String,
+    pub function_mode: String,
+    pub spec_volume: f64,
+    /// None if the body could not be parsed
+    pub body_volume: Option<f64>,
+    pub spec_to_body_volume: Option<f64>,
+    pub proof_lines: usize,
+    pub exec_lines: usize,
+    pub proof_to_exec_lines: Option<f64>,
+    /// Distinct proof fns among the direct dependencies
+    pub lemma_calls: usize,
+}
+
+/// Derived ratios aggregated over one module (source file)
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleRatios {
+    pub module: String,
+    pub functions: usize,
+    pub exec_functions: usize,
+    pub proof_functions: usize,
+    pub spec_functions: usize,
+    pub spec_volume: f64,
+    pub body_volume: f64,
+    pub spec_to_body_volume: Option<f64>,
+    pub proof_lines: usize,
+    pub exec_lines: usize,
+    pub proof_to_exec_lines: Option<f64>,
+    pub lemmas_per_exec_function: Option<f64>,
+}
+
+/// Per-function and per-module derived ratios
+#[derive(Debug, Clone, Serialize)]
+pub struct DerivedMetrics {
+    pub functions: Vec<FunctionRatios>,
+    pub modules: Vec<ModuleRatios>,
+}
+
+/// `numerator / denominator`, or None when the denominator is zero
+fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    (denominator > 0.0).then(|| numerator / denominator)
+}
+
+fn ghost_statement_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^(assert|assume|reveal|reveal_with_fuel|broadcast\s+use)\b").unwrap()
+    })
+}
+
+fn proof_block_opener_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // Text right before a `{` that opens ghost code: `proof {`, `by {`,
+    // `by (nonlinear_arith) {`
+    RE.get_or_init(|| Regex::new(r"(^|\W)(proof|by(\s*\([^)]*\))?)\s*$").unwrap())
+}
+
+/// Count `(proof_lines, exec_lines)` of a function's source text.
+///
+/// Only lines inside the function's body block are counted; blank and
+/// comment-only lines are skipped. Every line of a proof fn is a proof line
+/// and spec fns count neither. The scan is textual, so braces inside string
+/// literals can confuse it.
+pub fn count_proof_and_exec_lines(body: &str, function_mode: &str) -> (usize, usize) {
+    if function_mode == "spec" {
+        return (0, 0);
+    }
+    let all_proof = function_mode == "proof";
+
+    let code: String = body
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let Some(inner) = body_block_contents(&code) else {
+        return (0, 0);
+    };
+
+    let mut proof_lines = 0;
+    let mut exec_lines = 0;
+    // One entry per open brace: is it (inside) ghost code?
+    let mut stack: Vec<bool> = Vec::new();
+
+    for line in inner.lines() {
+        let in_ghost = stack.last().copied().unwrap_or(false);
+        let mut opens_ghost = false;
+        for (offset, c) in line.char_indices() {
+            match c {
+                '{' => {
+                    let ghost = stack.last().copied().unwrap_or(false)
+                        || proof_block_opener_regex().is_match(&line[..offset]);
+                    opens_ghost |= ghost;
+                    stack.push(ghost);
+                }
+                '}' => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if all_proof || in_ghost || opens_ghost || ghost_statement_regex().is_match(trimmed) {
+            proof_lines += 1;
+        } else {
+            exec_lines += 1;
+        }
+    }
+
+    (proof_lines, exec_lines)
+}
+
+/// The text between the function body's braces, i.e. inside the last `}`
+/// and the `{` matching it
+fn body_block_contents(code: &str) -> Option<&str> {
+    let close = code.rfind('}')?;
+    let mut depth = 0usize;
+    for (offset, c) in code[..=close].char_indices().rev() {
+        match c {
+            '}' => depth += 1,
+            '{' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(&code[offset + 1..close]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Sum of the Halstead volumes of a function's requires/ensures/decreases
+fn spec_volume(metrics: &FunctionMetricsInput) -> f64 {
+    metrics
+        .requires_specs
+        .iter()
+        .chain(&metrics.ensures_specs)
+        .chain(&metrics.decreases_specs)
+        .filter_map(|clause| clause.halstead_volume)
+        .sum()
+}
+
+/// Halstead volume of a function's body, if it parses
+fn body_volume(body: &str) -> Option<f64> {
+    let block = parse_body_block(body).ok()?;
+    Some(
+        block
+            .as_ref()
+            .map_or(0.0, |b| analyze_block_halstead(b).volume),
+    )
+}
+
+/// Compute per-function and per-module ratios
+pub fn compute_derived_metrics(atoms: &[AtomWithMetrics]) -> DerivedMetrics {
+    let modes: HashMap<&str, &str> = atoms
+        .iter()
+        .map(|atom| {
+            (
+                atom.identifier.as_str(),
+                atom.metrics.function_mode.as_str(),
+            )
+        })
+        .collect();
+
+    let mut functions: Vec<FunctionRatios> = atoms
+        .iter()
+        .map(|atom| {
+            let spec_volume = spec_volume(&atom.metrics);
+            let body_volume = body_volume(&atom.body);
+            let (proof_lines, exec_lines) =
+                count_proof_and_exec_lines(&atom.body, &atom.metrics.function_mode);
+            let mut lemmas: Vec<&str> = atom
+                .deps
+                .iter()
+                .map(String::as_str)
+                .filter(|dep| modes.get(dep) == Some(&"proof"))
+                .collect();
+            lemmas.sort_unstable();
+            lemmas.dedup();
+
+            FunctionRatios {
+                identifier: atom.identifier.clone(),
+                display_name: atom.display_name.clone(),
+                module: atom.relative_path.clone(),
+                function_mode: atom.metrics.function_mode.clone(),
+                spec_volume,
+                body_volume,
+                spec_to_body_volume: body_volume.and_then(|volume| ratio(spec_volume, volume)),
+                proof_lines,
+                exec_lines,
+                proof_to_exec_lines: ratio(proof_lines as f64, exec_lines as f64),
+                lemma_calls: lemmas.len(),
+            }
+        })
+        .collect();
+    functions.sort_by(|a, b| {
+        a.module
+            .cmp(&b.module)
+            .then_with(|| a.identifier.cmp(&b.identifier))
+    });
+
+    let mut by_module: BTreeMap<&str, Vec<&FunctionRatios>> = BTreeMap::new();
+    for function in &functions {
+        by_module
+            .entry(&function.module)
+            .or_default()
+            .push(function);
+    }
+
+    let modules = by_module
+        .into_iter()
+        .map(|(module, members)| {
+            let count_mode =
+                |mode: &str| members.iter().filter(|f| f.function_mode == mode).count();
+            let exec_functions = count_mode("exec");
+            let proof_functions = count_mode("proof");
+            let spec_volume: f64 = members.iter().map(|f| f.spec_volume).sum();
+            let body_volume: f64 = members.iter().filter_map(|f| f.body_volume).sum();
+            let proof_lines: usize = members.iter().map(|f| f.proof_lines).sum();
+            let exec_lines: usize = members.iter().map(|f| f.exec_lines).sum();
+
+            ModuleRatios {
+                module: module.to_string(),
+                functions: members.len(),
+                exec_functions,
+                proof_functions,
+                spec_functions: count_mode("spec"),
+                spec_volume,
+                body_volume,
+                spec_to_body_volume: ratio(spec_volume, body_volume),
+                proof_lines,
+                exec_lines,
+                proof_to_exec_lines: ratio(proof_lines as f64, exec_lines as f64),
+                lemmas_per_exec_function: ratio(proof_functions as f64, exec_functions as f64),
+            }
+        })
+        .collect();
+
+    DerivedMetrics { functions, modules }
+}
+
+impl DerivedMetrics {
+    /// Compute derived metrics from the JSON written by `compute_metrics`
+    pub fn from_json_str(json: &str) -> Result<Self, String> {
+        let atoms: Vec<AtomWithMetrics> = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse atoms with metrics: {}", e))?;
+        Ok(compute_derived_metrics(&atoms))
+    }
+
+    /// Write functions and modules as pretty-printed JSON
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Write one CSV row per function
+    pub fn write_functions_csv<W: Write>(&self, writer: W) -> Result<(), Box<dyn Error>> {
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        for function in &self.functions {
+            csv_writer.serialize(function)?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    /// Write one CSV row per module
+    pub fn write_modules_csv<W: Write>(&self, writer: W) -> Result<(), Box<dyn Error>> {
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        for module in &self.modules {
+            csv_writer.serialize(module)?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_and_exec_lines() {
+        let body = r#"fn f(x: u32) -> (r: u32)
+    requires x < 10,
+    ensures r == x + 1,
+{
+    let y = x + 1;
+    // a comment
+    proof {
+        lemma_foo(x);
+    }
+    assert(y > x) by {
+        lemma_bar(x);
+    }
+    assert(y == x + 1);
+
+    y
+}"#;
+        // proof { / lemma_foo / }, assert by { / lemma_bar / }, assert
+        assert_eq!(count_proof_and_exec_lines(body, "exec"), (7, 2));
+    }
+
+    #[test]
+    fn test_proof_fn_lines_are_all_proof() {
+        let body = "proof fn lemma(x: u32)\n    ensures x + 0 == x,\n{\n    assert(x + 0 == x);\n}";
+        assert_eq!(count_proof_and_exec_lines(body, "proof"), (1, 0));
+        assert_eq!(count_proof_and_exec_lines(body, "spec"), (0, 0));
+    }
+
+    #[test]
+    fn test_single_line_body() {
+        assert_eq!(count_proof_and_exec_lines("fn f() { g() }", "exec"), (0, 1));
+    }
+
+    fn atom(
+        identifier: &str,
+        mode: &str,
+        deps: &[&str],
+        body: &str,
+        spec_volume: f64,
+    ) -> AtomWithMetrics {
+        AtomWithMetrics {
+            identifier: identifier.to_string(),
+            display_name: identifier.to_string(),
+            relative_path: "src/lib.rs".to_string(),
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            body: body.to_string(),
+            metrics: FunctionMetricsInput {
+                function_mode: mode.to_string(),
+                requires_specs: vec![ClauseMetrics {
+                    halstead_volume: Some(spec_volume),
+                }],
+                ensures_specs: Vec::new(),
+                decreases_specs: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_compute_derived_metrics() {
+        let atoms = vec![
+            atom(
+                "f",
+                "exec",
+                &["lemma", "lemma", "g"],
+                "fn f(x: u32) {\n    let y = x + 1;\n    proof {\n        lemma(x);\n    }\n}",
+                4.0,
+            ),
+            atom("g", "exec", &[], "fn g() {\n    h();\n}", 0.0),
+            atom("lemma", "proof", &[], "proof fn lemma(x: u32) {\n}", 2.0),
+        ];
+
+        let derived = compute_derived_metrics(&atoms);
+
+        let f = derived
+            .functions
+            .iter()
+            .find(|f| f.identifier == "f")
+            .unwrap();
+        assert_eq!(f.lemma_calls, 1);
+        assert_eq!((f.proof_lines, f.exec_lines), (3, 1));
+        assert_eq!(f.proof_to_exec_lines, Some(3.0));
+        assert!(f.body_volume.unwrap() > 0.0);
+
+        assert_eq!(derived.modules.len(), 1);
+        let module = &derived.modules[0];
+        assert_eq!(module.exec_functions, 2);
+        assert_eq!(module.spec_volume, 6.0);
+        assert_eq!(module.lemmas_per_exec_function, Some(0.5));
+    }
+}
@@ -0,0 +1,219 @@
+//! Verification escape hatches in function source
+//!
+//! Finds the constructs that make Verus trust code instead of proving it:
+//!
+//! - `assume(...)` and `admit()` in a function body
+//! - `#[verifier::external_body]`: the body is not verified at all
+//! - `#[verifier::assume_specification]` and `assume_specification[...]`
+//!   items: the spec of an external function is taken on faith
+//!
+//! Lines are 1-based and relative to the start of the source text, so a
+//! caller that knows where the function starts can turn them into file
+//! lines.
+
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use verus_syn::spanned::Spanned;
+use verus_syn::{
+    visit::Visit, Assume, Attribute, Block, Expr, ExprCall, ImplItem, Item, ItemFn, TraitItem,
+};
+
+/// Which escape hatch was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscapeHatchKind {
+    Assume,
+    Admit,
+    ExternalBody,
+    AssumeSpecification,
+}
+
+impl EscapeHatchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EscapeHatchKind::Assume => "assume",
+            EscapeHatchKind::Admit => "admit",
+            EscapeHatchKind::ExternalBody => "external_body",
+            EscapeHatchKind::AssumeSpecification => "assume_specification",
+        }
+    }
+}
+
+/// One escape hatch and the line it is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscapeHatch {
+    pub kind: EscapeHatchKind,
+    /// 1-based line within the scanned source
+    pub line: usize,
+}
+
+#[derive(Default)]
+struct EscapeHatchVisitor {
+    /// Subtracted from span lines (1 when the source was wrapped in braces)
+    line_offset: usize,
+    hatches: Vec<EscapeHatch>,
+}
+
+impl EscapeHatchVisitor {
+    fn push(&mut self, kind: EscapeHatchKind, node: &impl Spanned) {
+        let line = node.span().start().line.saturating_sub(self.line_offset);
+        self.hatches.push(EscapeHatch {
+            kind,
+            line: line.max(1),
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for EscapeHatchVisitor {
+    fn visit_assume(&mut self, assume: &'ast Assume) {
+        self.push(EscapeHatchKind::Assume, assume);
+        verus_syn::visit::visit_assume(self, assume);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(path) = &*call.func {
+            let name = path.path.segments.last().map(|s| s.ident.to_string());
+            match name.as_deref() {
+                Some("admit") => self.push(EscapeHatchKind::Admit, call),
+                Some("assume") => self.push(EscapeHatchKind::Assume, call),
+                _ => {}
+            }
+        }
+        verus_syn::visit::visit_expr_call(self, call);
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        let text: String = attr
+            .to_token_stream()
+            .to_string()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if !text.starts_with("#[verifier") {
+            return;
+        }
+        if text.contains("external_body") {
+            self.push(EscapeHatchKind::ExternalBody, attr);
+        } else if text.contains("assume_specification") {
+            self.push(EscapeHatchKind::AssumeSpecification, attr);
+        }
+    }
+}
+
+/// Attributes and body of a function's source text, and the line offset of
+/// the parsed text relative to the source
+fn parse_function(source: &str) -> Result<(Vec<Attribute>, Option<Block>, usize), String> {
+    if let Ok(item_fn) = verus_syn::parse_str::<ItemFn>(source) {
+        return Ok((item_fn.attrs, Some(*item_fn.block), 0));
+    }
+    if let Ok(Item::Fn(item_fn)) = verus_syn::parse_str::<Item>(source) {
+        return Ok((item_fn.attrs, Some(*item_fn.block), 0));
+    }
+    if let Ok(ImplItem::Fn(impl_fn)) = verus_syn::parse_str::<ImplItem>(source) {
+        return Ok((impl_fn.attrs, Some(impl_fn.block), 0));
+    }
+    if let Ok(TraitItem::Fn(trait_fn)) = verus_syn::parse_str::<TraitItem>(source) {
+        return Ok((trait_fn.attrs, trait_fn.default, 0));
+    }
+
+    verus_syn::parse_str(&format!("{{\n{}\n}}", source))
+        .map(|block| (Vec::new(), Some(block), 1))
+        .map_err(|e| format!("Failed to parse function body: {}", e))
+}
+
+/// Find the escape hatches of a function's source text, sorted by line.
+///
+/// Accepts a whole function item or just the statements of a body, like
+/// [`crate::analyze_body_complexity`]. `assume_specification[...]` items,
+/// which are not functions, are recognized from their first line.
+pub fn find_escape_hatches(source: &str) -> Result<Vec<EscapeHatch>, String> {
+    let assume_specification = source.lines().enumerate().find(|(_, line)| {
+        let line = line.trim_start();
+        !line.starts_with("//")
+            && !line.starts_with('#')
+            && line
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .any(|word| word == "assume_specification")
+    });
+    if let Some((index, _)) = assume_specification {
+        if verus_syn::parse_str::<ItemFn>(source).is_err() {
+            return Ok(vec![EscapeHatch {
+                kind: EscapeHatchKind::AssumeSpecification,
+                line: index + 1,
+            }]);
+        }
+    }
+
+    let (attrs, block, line_offset) = parse_function(source)?;
+    let mut visitor = EscapeHatchVisitor {
+        line_offset,
+        ..Default::default()
+    };
+    for attr in &attrs {
+        visitor.visit_attribute(attr);
+    }
+    if let Some(block) = &block {
+        visitor.visit_block(block);
+    }
+    visitor
+        .hatches
+        .sort_by_key(|hatch| (hatch.line, hatch.kind));
+    Ok(visitor.hatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hatches(source: &str) -> Vec<(EscapeHatchKind, usize)> {
+        find_escape_hatches(source)
+            .unwrap()
+            .iter()
+            .map(|h| (h.kind, h.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_assume_and_admit() {
+        let source = r#"fn f(x: u32) -> (r: u32)
+    ensures r == x,
+{
+    proof {
+        assume(x < 100);
+    }
+    let g = || {
+        proof { admit(); }
+    };
+    x
+}"#;
+
+        assert_eq!(
+            hatches(source),
+            vec![(EscapeHatchKind::Assume, 5), (EscapeHatchKind::Admit, 8)]
+        );
+    }
+
+    #[test]
+    fn test_verifier_attributes() {
+        let source = "#[inline]\n#[verifier::external_body]\npub fn raw() -> u8 { 0 }";
+        assert_eq!(hatches(source), vec![(EscapeHatchKind::ExternalBody, 2)]);
+
+        let source = "#[verifier(external_body)]\nfn raw() {}";
+        assert_eq!(hatches(source), vec![(EscapeHatchKind::ExternalBody, 1)]);
+
+        let source = "pub assume_specification<T>[ core::mem::swap::<T> ](a: &mut T, b: &mut T);";
+        assert_eq!(
+            hatches(source),
+            vec![(EscapeHatchKind::AssumeSpecification, 1)]
+        );
+    }
+
+    #[test]
+    fn test_statements_only_and_clean_function() {
+        assert_eq!(
+            hatches("let y = x + 1;\nassume(y > x);"),
+            vec![(EscapeHatchKind::Assume, 2)]
+        );
+        assert!(hatches("fn f(x: u32) -> u32 { x + 1 }").is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+//! Ghost and tracked state of function bodies
+//!
+//! Exec functions carry proof-only state alongside their real computation:
+//! `let ghost` / `let tracked` bindings, `tracked` and `Ghost<T>` /
+//! `Tracked<T>` parameters, and `Ghost(..)` / `Tracked(..)` arguments passed
+//! to other functions. How much of it a function declares and uses is a
+//! proxy for its proof annotation burden, next to the Halstead numbers of
+//! its specs.
+//!
+//! Uses are counted by name, so a shadowing exec variable of the same name
+//! counts as a use as well.
+
+use crate::body_complexity::parse_body_block;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use verus_syn::visit::{self, Visit};
+use verus_syn::{
+    Block, Expr, ExprCall, ExprPath, FnArgKind, ImplItem, Item, ItemFn, Local, Pat, PatIdent, Path,
+    Signature, TraitItem, Type,
+};
+
+/// Default [`GhostStateMetrics::is_heavy`] threshold
+pub const HEAVY_GHOST_STATE: usize = 10;
+
+/// Ghost and tracked variables of one function
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GhostStateMetrics {
+    /// `let ghost` bindings, `let Ghost(..)` patterns and `Ghost<T>` parameters
+    pub ghost_declarations: usize,
+    /// `let tracked` bindings, `Tracked(..)` patterns, and `tracked` or
+    /// `Tracked<T>` parameters
+    pub tracked_declarations: usize,
+    /// References to ghost variables in the body
+    pub ghost_uses: usize,
+    /// References to tracked variables in the body
+    pub tracked_uses: usize,
+    /// `Ghost(..)` wrapper expressions, e.g. ghost arguments of exec calls
+    pub ghost_wrappers: usize,
+    /// `Tracked(..)` wrapper expressions
+    pub tracked_wrappers: usize,
+}
+
+impl GhostStateMetrics {
+    /// All declarations, uses and wrappers together
+    pub fn total(&self) -> usize {
+        self.ghost_declarations
+            + self.tracked_declarations
+            + self.ghost_uses
+            + self.tracked_uses
+            + self.ghost_wrappers
+            + self.tracked_wrappers
+    }
+
+    /// Whether the function handles at least `threshold` pieces of ghost
+    /// state (see [`GhostStateMetrics::total`])
+    pub fn is_heavy(&self, threshold: usize) -> bool {
+        self.total() >= threshold
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GhostKind {
+    Ghost,
+    Tracked,
+}
+
+/// `Ghost` or `Tracked` as the last segment of a path
+fn wrapper_kind(path: &Path) -> Option<GhostKind> {
+    let segment = path.segments.last()?;
+    if segment.ident == "Ghost" {
+        Some(GhostKind::Ghost)
+    } else if segment.ident == "Tracked" {
+        Some(GhostKind::Tracked)
+    } else {
+        None
+    }
+}
+
+fn type_kind(ty: &Type) -> Option<GhostKind> {
+    match ty {
+        Type::Path(type_path) => wrapper_kind(&type_path.path),
+        Type::Reference(reference) => type_kind(&reference.elem),
+        _ => None,
+    }
+}
+
+/// Kind of a binding written as `Ghost(x)`, `Tracked(x)` or `x: Ghost<T>`
+fn pat_kind(pat: &Pat) -> Option<GhostKind> {
+    match pat {
+        Pat::TupleStruct(tuple_struct) => wrapper_kind(&tuple_struct.path),
+        Pat::Type(pat_type) => type_kind(&pat_type.ty).or_else(|| pat_kind(&pat_type.pat)),
+        _ => None,
+    }
+}
+
+/// Names bound by a pattern
+#[derive(Default)]
+struct BoundNames(Vec<String>);
+
+impl<'ast> Visit<'ast> for BoundNames {
+    fn visit_pat_ident(&mut self, pat: &'ast PatIdent) {
+        self.0.push(pat.ident.to_string());
+        visit::visit_pat_ident(self, pat);
+    }
+}
+
+fn bound_names(pat: &Pat) -> Vec<String> {
+    let mut names = BoundNames::default();
+    names.visit_pat(pat);
+    names.0
+}
+
+#[derive(Default)]
+struct GhostStateVisitor {
+    metrics: GhostStateMetrics,
+    ghost_names: HashSet<String>,
+    tracked_names: HashSet<String>,
+}
+
+impl GhostStateVisitor {
+    fn declare(&mut self, kind: GhostKind, pat: &Pat) {
+        let names = bound_names(pat);
+        match kind {
+            GhostKind::Ghost => {
+                self.metrics.ghost_declarations += 1;
+                self.ghost_names.extend(names);
+            }
+            GhostKind::Tracked => {
+                self.metrics.tracked_declarations += 1;
+                self.tracked_names.extend(names);
+            }
+        }
+    }
+
+    fn visit_signature_params(&mut self, sig: &Signature) {
+        for arg in &sig.inputs {
+            let FnArgKind::Typed(pat_type) = &arg.kind else {
+                continue;
+            };
+            let kind = if arg.tracked.is_some() {
+                Some(GhostKind::Tracked)
+            } else {
+                type_kind(&pat_type.ty).or_else(|| pat_kind(&pat_type.pat))
+            };
+            if let Some(kind) = kind {
+                self.declare(kind, &pat_type.pat);
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for GhostStateVisitor {
+    fn visit_local(&mut self, local: &'ast Local) {
+        // The initializer is evaluated before the new names are bound
+        if let Some(init) = &local.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+        let kind = if local.tracked.is_some() {
+            Some(GhostKind::Tracked)
+        } else if local.ghost.is_some() {
+            Some(GhostKind::Ghost)
+        } else {
+            pat_kind(&local.pat)
+        };
+        if let Some(kind) = kind {
+            self.declare(kind, &local.pat);
+        }
+    }
+
+    fn visit_expr_path(&mut self, expr: &'ast ExprPath) {
+        if let Some(ident) = expr.path.get_ident() {
+            let name = ident.to_string();
+            if self.ghost_names.contains(&name) {
+                self.metrics.ghost_uses += 1;
+            } else if self.tracked_names.contains(&name) {
+                self.metrics.tracked_uses += 1;
+            }
+        }
+        visit::visit_expr_path(self, expr);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(func) = &*call.func {
+            match wrapper_kind(&func.path) {
+                Some(GhostKind::Ghost) => self.metrics.ghost_wrappers += 1,
+                Some(GhostKind::Tracked) => self.metrics.tracked_wrappers += 1,
+                None => {}
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+/// Ghost state of a parsed function: its parameters and (if any) its body
+pub fn analyze_fn_ghost_state(sig: &Signature, block: Option<&Block>) -> GhostStateMetrics {
+    let mut visitor = GhostStateVisitor::default();
+    visitor.visit_signature_params(sig);
+    if let Some(block) = block {
+        visitor.visit_block(block);
+    }
+    visitor.metrics
+}
+
+/// Ghost state of a function's source text.
+///
+/// Accepts a whole function item or just the statements of a body, like
+/// [`crate::analyze_body_complexity`]; bare statements have no parameters.
+pub fn analyze_ghost_state(source: &str) -> Result<GhostStateMetrics, String> {
+    if let Ok(item_fn) = verus_syn::parse_str::<ItemFn>(source) {
+        return Ok(analyze_fn_ghost_state(&item_fn.sig, Some(&item_fn.block)));
+    }
+    if let Ok(Item::Fn(item_fn)) = verus_syn::parse_str::<Item>(source) {
+        return Ok(analyze_fn_ghost_state(&item_fn.sig, Some(&item_fn.block)));
+    }
+    if let Ok(ImplItem::Fn(impl_fn)) = verus_syn::parse_str::<ImplItem>(source) {
+        return Ok(analyze_fn_ghost_state(&impl_fn.sig, Some(&impl_fn.block)));
+    }
+    if let Ok(TraitItem::Fn(trait_fn)) = verus_syn::parse_str::<TraitItem>(source) {
+        return Ok(analyze_fn_ghost_state(
+            &trait_fn.sig,
+            trait_fn.default.as_ref(),
+        ));
+    }
+
+    let mut visitor = GhostStateVisitor::default();
+    if let Some(block) = parse_body_block(source)? {
+        visitor.visit_block(&block);
+    }
+    Ok(visitor.metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ghost_and_tracked_bindings() {
+        let body = r#"
+            fn push(v: &mut Vec<u64>, x: u64, Tracked(perm): Tracked<&mut PointsTo<u64>>, len: Ghost<nat>)
+                requires old(v).len() < 100,
+            {
+                let ghost old_v = v@;
+                let tracked token = perm.borrow();
+                v.push(x);
+                proof {
+                    let ghost n = old_v.len();
+                    assert(v@ == old_v.push(x));
+                    use_token(token, n);
+                }
+                helper(Ghost(old_v), Tracked(perm));
+            }
+        "#;
+        let metrics = analyze_ghost_state(body).unwrap();
+
+        // len, old_v, n; perm, token
+        assert_eq!(metrics.ghost_declarations, 3);
+        assert_eq!(metrics.tracked_declarations, 2);
+        // old_v three times, n once; perm twice, token once
+        assert_eq!(metrics.ghost_uses, 4);
+        assert_eq!(metrics.tracked_uses, 3);
+        assert_eq!((metrics.ghost_wrappers, metrics.tracked_wrappers), (1, 1));
+        assert_eq!(metrics.total(), 14);
+        assert!(metrics.is_heavy(HEAVY_GHOST_STATE));
+    }
+
+    #[test]
+    fn test_tracked_parameter_and_plain_function() {
+        let metrics =
+            analyze_ghost_state("proof fn lemma(tracked t: Token) { consume(t); }").unwrap();
+        assert_eq!(metrics.tracked_declarations, 1);
+        assert_eq!(metrics.tracked_uses, 1);
+
+        let metrics = analyze_ghost_state("fn add(a: u64, b: u64) -> u64 { a + b }").unwrap();
+        assert_eq!(metrics, GhostStateMetrics::default());
+        assert!(!metrics.is_heavy(1));
+    }
+}
@@ -0,0 +1,332 @@
+//! Halstead operator/operand collection over `verus_syn` syntax trees
+//!
+//! [`HalsteadCollector`] is the single visitor behind every Halstead number
+//! in the workspace: spec clauses (`spec_halstead`), `compute_metrics` and
+//! `compute_proof_metrics` all count tokens through it, so the same
+//! expression gets the same metrics in every tool.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use verus_metrics::halstead::HalsteadCollector;
+//! use verus_syn::visit::Visit;
+//!
+//! let expr: verus_syn::Expr = verus_syn::parse_str("x.len() < 10")?;
+//! let mut collector = HalsteadCollector::default();
+//! collector.visit_expr(&expr);
+//! println!("Volume: {}", collector.metrics().volume);
+//! ```
+
+use crate::spec_halstead::SpecHalsteadMetrics;
+use quote::ToTokens;
+use std::collections::{HashMap, HashSet};
+use verus_syn::{visit::Visit, Expr, Lit, Member};
+
+/// Which syntax counts as an operator or operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HalsteadOptions {
+    /// Name method-call operators after the method (`len`, `push`) instead
+    /// of counting every method call as one `method_call` operator
+    pub method_name_operators: bool,
+
+    /// Render string, byte and char literals by value (escapes resolved)
+    /// instead of by their source tokens
+    pub normalize_literals: bool,
+
+    /// Count field access, indexing, parentheses, casts and references as
+    /// operators (and field names as operands)
+    pub structural_operators: bool,
+
+    /// Report how often each operator and operand occurs
+    /// (`operator_frequencies` / `operand_frequencies` in the metrics)
+    pub frequencies: bool,
+}
+
+impl Default for HalsteadOptions {
+    fn default() -> Self {
+        Self {
+            method_name_operators: true,
+            normalize_literals: true,
+            structural_operators: true,
+            frequencies: false,
+        }
+    }
+}
+
+/// Collects Halstead operators and operands while visiting a syntax tree
+#[derive(Debug, Clone, Default)]
+pub struct HalsteadCollector {
+    options: HalsteadOptions,
+    operators: Vec<String>,
+    operands: Vec<String>,
+    unique_operators: HashSet<String>,
+    unique_operands: HashSet<String>,
+}
+
+impl HalsteadCollector {
+    pub fn new(options: HalsteadOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// All operator occurrences, in visiting order
+    pub fn operators(&self) -> &[String] {
+        &self.operators
+    }
+
+    /// All operand occurrences, in visiting order
+    pub fn operands(&self) -> &[String] {
+        &self.operands
+    }
+
+    /// Number of occurrences of each operator
+    pub fn operator_frequencies(&self) -> HashMap<String, usize> {
+        frequencies(&self.operators)
+    }
+
+    /// Number of occurrences of each operand
+    pub fn operand_frequencies(&self) -> HashMap<String, usize> {
+        frequencies(&self.operands)
+    }
+
+    /// Add another collector's tokens (totals add up, unique sets are unioned)
+    pub fn merge(&mut self, other: &HalsteadCollector) {
+        self.operators.extend(other.operators.iter().cloned());
+        self.operands.extend(other.operands.iter().cloned());
+        self.unique_operators
+            .extend(other.unique_operators.iter().cloned());
+        self.unique_operands
+            .extend(other.unique_operands.iter().cloned());
+    }
+
+    /// Halstead metrics of everything visited so far
+    pub fn metrics(&self) -> SpecHalsteadMetrics {
+        let n1 = self.unique_operators.len();
+        let n2 = self.unique_operands.len();
+        let n1_total = self.operators.len();
+        let n2_total = self.operands.len();
+
+        let length = n1_total + n2_total;
+        let vocabulary = n1 + n2;
+
+        // Handle edge cases
+        let difficulty = if n2 == 0 {
+            0.0
+        } else {
+            (n1 as f64 / 2.0) * (n2_total as f64 / n2 as f64)
+        };
+
+        let volume = if vocabulary == 0 {
+            0.0
+        } else {
+            length as f64 * (vocabulary as f64).log2()
+        };
+
+        let effort = difficulty * volume;
+
+        SpecHalsteadMetrics {
+            halstead_length: length,
+            vocabulary,
+            difficulty,
+            volume,
+            effort,
+            n1_unique_operators: n1,
+            n2_unique_operands: n2,
+            n1_total_operators: n1_total,
+            n2_total_operands: n2_total,
+            operator_frequencies: self
+                .options
+                .frequencies
+                .then(|| self.operator_frequencies()),
+            operand_frequencies: self.options.frequencies.then(|| self.operand_frequencies()),
+            ..Default::default()
+        }
+    }
+
+    fn operator(&mut self, op: String) {
+        self.operators.push(op.clone());
+        self.unique_operators.insert(op);
+    }
+
+    fn operand(&mut self, operand: String) {
+        self.operands.push(operand.clone());
+        self.unique_operands.insert(operand);
+    }
+
+    fn literal(&self, lit: &Lit) -> String {
+        if !self.options.normalize_literals {
+            return lit.to_token_stream().to_string();
+        }
+        match lit {
+            Lit::Str(s) => format!("\"{}\"", s.value()),
+            Lit::ByteStr(b) => format!("{:?}", b.value()),
+            Lit::CStr(c) => format!("{:?}", c.value()),
+            Lit::Byte(b) => format!("{}", b.value()),
+            Lit::Char(c) => format!("'{}'", c.value()),
+            Lit::Int(i) => i.to_string(),
+            Lit::Float(f) => f.to_string(),
+            Lit::Bool(b) => b.value().to_string(),
+            Lit::Verbatim(v) => v.to_string(),
+            _ => "literal".to_string(),
+        }
+    }
+}
+
+fn frequencies(tokens: &[String]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for token in tokens {
+        *counts.entry(token.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+impl<'ast> Visit<'ast> for HalsteadCollector {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        let structural = self.options.structural_operators;
+
+        match expr {
+            Expr::Binary(bin) => {
+                // Count binary operators: +, -, *, ==, <, &&, etc.
+                self.operator(bin.op.to_token_stream().to_string());
+                self.visit_expr(&bin.left);
+                self.visit_expr(&bin.right);
+            }
+            Expr::Unary(un) => {
+                // Count unary operators: !, -, *, forall, exists
+                self.operator(un.op.to_token_stream().to_string());
+                self.visit_expr(&un.expr);
+            }
+            Expr::Path(path) => {
+                // Count variable names, constants, type names as operands
+                let name = path
+                    .path
+                    .segments
+                    .iter()
+                    .map(|seg| seg.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::");
+                self.operand(name);
+            }
+            Expr::Lit(lit) => {
+                let lit_str = self.literal(&lit.lit);
+                self.operand(lit_str);
+            }
+            Expr::Call(call) => {
+                self.operator("call".to_string());
+                self.visit_expr(&call.func);
+                for arg in &call.args {
+                    self.visit_expr(arg);
+                }
+            }
+            Expr::MethodCall(method) => {
+                let op = if self.options.method_name_operators {
+                    method.method.to_string()
+                } else {
+                    "method_call".to_string()
+                };
+                self.operator(op);
+                self.visit_expr(&method.receiver);
+                for arg in &method.args {
+                    self.visit_expr(arg);
+                }
+            }
+            Expr::Field(field) if structural => {
+                self.operator(".".to_string());
+                // Field name is an operand
+                self.operand(match &field.member {
+                    Member::Named(ident) => ident.to_string(),
+                    Member::Unnamed(index) => index.index.to_string(),
+                });
+                self.visit_expr(&field.base);
+            }
+            Expr::Index(index) if structural => {
+                self.operator("[]".to_string());
+                self.visit_expr(&index.expr);
+                self.visit_expr(&index.index);
+            }
+            Expr::Paren(paren) if structural => {
+                self.operator("()".to_string());
+                self.visit_expr(&paren.expr);
+            }
+            Expr::Cast(cast) if structural => {
+                self.operator("as".to_string());
+                self.visit_expr(&cast.expr);
+            }
+            Expr::Reference(reference) if structural => {
+                self.operator("&".to_string());
+                self.visit_expr(&reference.expr);
+            }
+            // For other expression types, use default traversal
+            _ => verus_syn::visit::visit_expr(self, expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(spec: &str, options: HalsteadOptions) -> HalsteadCollector {
+        let expr: Expr = verus_syn::parse_str(spec).unwrap();
+        let mut collector = HalsteadCollector::new(options);
+        collector.visit_expr(&expr);
+        collector
+    }
+
+    #[test]
+    fn test_default_options() {
+        let collector = collect("v.len() < s.0", HalsteadOptions::default());
+
+        assert_eq!(collector.operators(), ["<", "len", "."]);
+        assert_eq!(collector.operands(), ["v", "0", "s"]);
+    }
+
+    #[test]
+    fn test_generic_method_calls_without_structural_operators() {
+        let options = HalsteadOptions {
+            method_name_operators: false,
+            structural_operators: false,
+            ..HalsteadOptions::default()
+        };
+        let collector = collect("v.len() < s.0", options);
+
+        assert_eq!(collector.operators(), ["<", "method_call"]);
+        assert_eq!(collector.operands(), ["v", "s"]);
+    }
+
+    #[test]
+    fn test_frequencies_only_with_option() {
+        let spec = "a * b + a * c";
+        assert!(collect(spec, HalsteadOptions::default())
+            .metrics()
+            .operator_frequencies
+            .is_none());
+
+        let options = HalsteadOptions {
+            frequencies: true,
+            ..HalsteadOptions::default()
+        };
+        let metrics = collect(spec, options).metrics();
+        let operators = metrics.operator_frequencies.unwrap();
+        let operands = metrics.operand_frequencies.unwrap();
+
+        assert_eq!(operators["*"], 2);
+        assert_eq!(operators["+"], 1);
+        assert_eq!(operands["a"], 2);
+        assert_eq!(operands.len(), 3);
+    }
+
+    #[test]
+    fn test_merge_unions_unique_tokens() {
+        let mut collector = collect("x < 1", HalsteadOptions::default());
+        collector.merge(&collect("x < 2", HalsteadOptions::default()));
+        let metrics = collector.metrics();
+
+        assert_eq!(metrics.n1_total_operators, 2);
+        assert_eq!(metrics.n1_unique_operators, 1);
+        assert_eq!(metrics.n2_total_operands, 4);
+        assert_eq!(metrics.n2_unique_operands, 3);
+    }
+}
@@ -7,6 +7,19 @@
 //! - Parse Verus `requires`, `ensures`, and `decreases` clauses
 //! - Compute Halstead metrics (n1, N1, n2, N2, length, difficulty, effort, etc.)
 //! - Handle Verus-specific syntax (quantifiers, implications, ghost operators)
+//! - Report quantifier count, nesting depth, bound variables and triggers
+//! - Inline spec function definitions into clauses for "expanded" Halstead metrics
+//! - Find `proof { }`, `assert ... by { }` and `calc!` blocks in function bodies
+//! - Count proof steps: asserts, `by` blocks, `calc!` steps, reveals and solver switches
+//! - Compute cyclomatic and cognitive complexity of function bodies
+//! - Find `assume`, `admit` and `external_body`/`assume_specification` escape hatches
+//! - Lint quantifier triggers (missing, arithmetic, matching loops) and alternation
+//! - Count ghost and tracked variable declarations and uses per function
+//! - Derive spec-to-body, proof-to-exec and lemma ratios per function and module
+//! - Parse function signatures (parameters, return type, generics) for atoms
+//! - Link `ensures` clauses of exec functions to the called lemmas supporting them
+//! - Skip prose fragments in specs with pluggable, configurable prose detectors
+//! - Find the spec clause at a text range of a file, for editor integrations
 //!
 //! ## Example
 //!
@@ -18,7 +31,40 @@
 //! println!("Halstead length: {:?}", metrics.halstead_length);
 //! ```
 
+pub mod body_complexity;
+pub mod derived;
+pub mod escape_hatches;
+pub mod ghost_state;
+pub mod halstead;
+pub mod proof_blocks;
+pub mod proof_steps;
+pub mod prose;
+pub mod signature;
+pub mod spec_at;
 pub mod spec_halstead;
+pub mod spec_inline;
+pub mod spec_support;
+pub mod trigger_lint;
 
 // Re-export main types
-pub use spec_halstead::{analyze_spec, is_prose, SpecHalsteadMetrics};
+pub use body_complexity::{
+    analyze_block_complexity, analyze_body_complexity, BodyComplexityMetrics,
+};
+pub use derived::{compute_derived_metrics, DerivedMetrics, FunctionRatios, ModuleRatios};
+pub use escape_hatches::{find_escape_hatches, EscapeHatch, EscapeHatchKind};
+pub use ghost_state::{
+    analyze_fn_ghost_state, analyze_ghost_state, GhostStateMetrics, HEAVY_GHOST_STATE,
+};
+pub use halstead::{HalsteadCollector, HalsteadOptions};
+pub use proof_blocks::{collect_proof_blocks, extract_proof_blocks, ProofBlock, ProofBlockKind};
+pub use proof_steps::{analyze_proof_steps, collect_proof_steps, ProofStepMetrics};
+pub use prose::{DefaultProseDetector, ProseDetector, RatioProseDetector, PROSE_THRESHOLD};
+pub use signature::{add_signatures, parse_signature};
+pub use spec_at::{analyze_spec_at, ClauseKind, SpecAtRange};
+pub use spec_halstead::{
+    analyze_expr, analyze_expr_with_options, analyze_spec, analyze_spec_with, analyze_specs,
+    analyze_specs_with, is_prose, BatchResult, BatchStats, SpecHalsteadMetrics, SpecOutcome,
+};
+pub use spec_inline::{ExpandedSpec, SpecFnTable, SpecInliner, DEFAULT_INLINE_DEPTH};
+pub use spec_support::add_spec_support;
+pub use trigger_lint::{lint_triggers, TriggerLint, TriggerLintKind, MAX_QUANTIFIER_ALTERNATION};
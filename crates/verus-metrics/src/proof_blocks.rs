@@ -0,0 +1,196 @@
+//! Proof-mode blocks inside function bodies
+//!
+//! Walks the `verus_syn` AST of a function body and collects every block of
+//! ghost code:
+//!
+//! - `proof { ... }`
+//! - `assert(...) by { ... }` and `assert forall|..| ... by { ... }`
+//! - the braced steps of a `calc! { ... }` chain
+//!
+//! Blocks nested inside closures are found as well. A proof block inside
+//! another proof block is not reported separately, since the outer block
+//! already contains it.
+
+use crate::body_complexity::parse_body_block;
+use verus_syn::parse::ParseStream;
+use verus_syn::{
+    token, visit::Visit, Assert, AssertForall, Block, Expr, ExprMacro, ExprUnary, UnOp,
+};
+
+/// How a proof block is introduced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofBlockKind {
+    /// `proof { ... }`
+    Proof,
+    /// `assert(...) by { ... }`
+    AssertBy,
+    /// `assert forall|..| ... by { ... }`
+    AssertForallBy,
+    /// A braced step of `calc! { ... }`
+    Calc,
+}
+
+/// A block of ghost code found in a function body
+#[derive(Debug, Clone)]
+pub struct ProofBlock {
+    pub kind: ProofBlockKind,
+    pub block: Block,
+}
+
+#[derive(Default)]
+struct ProofBlockVisitor {
+    blocks: Vec<ProofBlock>,
+}
+
+impl ProofBlockVisitor {
+    fn push(&mut self, kind: ProofBlockKind, block: &Block) {
+        self.blocks.push(ProofBlock {
+            kind,
+            block: block.clone(),
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for ProofBlockVisitor {
+    fn visit_expr_unary(&mut self, unary: &'ast ExprUnary) {
+        match (&unary.op, &*unary.expr) {
+            (UnOp::Proof(_), Expr::Block(block)) => self.push(ProofBlockKind::Proof, &block.block),
+            _ => verus_syn::visit::visit_expr_unary(self, unary),
+        }
+    }
+
+    fn visit_assert(&mut self, assert: &'ast Assert) {
+        match &assert.body {
+            Some(body) => self.push(ProofBlockKind::AssertBy, body),
+            None => verus_syn::visit::visit_assert(self, assert),
+        }
+    }
+
+    fn visit_assert_forall(&mut self, assert: &'ast AssertForall) {
+        self.push(ProofBlockKind::AssertForallBy, &assert.body);
+    }
+
+    fn visit_expr_macro(&mut self, mac: &'ast ExprMacro) {
+        if !mac.mac.path.is_ident("calc") {
+            return verus_syn::visit::visit_expr_macro(self, mac);
+        }
+        // calc! { (==) a; { proof } b; ... }: every braced group is a proof step
+        if let Ok(steps) = mac.mac.parse_body_with(parse_calc_steps) {
+            for step in &steps {
+                self.push(ProofBlockKind::Calc, step);
+            }
+        }
+    }
+}
+
+/// Parse the braced steps of a `calc!` body, skipping the relations and terms between them
+pub(crate) fn parse_calc_steps(input: ParseStream) -> verus_syn::Result<Vec<Block>> {
+    let mut steps = Vec::new();
+    while !input.is_empty() {
+        if input.peek(token::Brace) {
+            steps.push(input.parse()?);
+        } else {
+            input.step(|cursor| match cursor.token_tree() {
+                Some((_, rest)) => Ok(((), rest)),
+                None => Err(cursor.error("unexpected end of calc!")),
+            })?;
+        }
+    }
+    Ok(steps)
+}
+
+/// Collect the proof blocks of an already-parsed function body
+pub fn collect_proof_blocks(block: &Block) -> Vec<ProofBlock> {
+    let mut visitor = ProofBlockVisitor::default();
+    visitor.visit_block(block);
+    visitor.blocks
+}
+
+/// Collect the proof blocks of a function's source text.
+///
+/// Accepts a whole function item or just the statements of a body, like
+/// [`crate::analyze_body_complexity`].
+pub fn extract_proof_blocks(body: &str) -> Result<Vec<ProofBlock>, String> {
+    Ok(parse_body_block(body)?
+        .as_ref()
+        .map(collect_proof_blocks)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(body: &str) -> Vec<ProofBlockKind> {
+        extract_proof_blocks(body)
+            .unwrap()
+            .iter()
+            .map(|b| b.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_proof_and_assert_by_blocks() {
+        let body = r#"
+            fn f(x: u32) {
+                proof {
+                    lemma_a(x);
+                    assert(x >= 0) by { lemma_b(x); }
+                }
+                assert(x + 0 == x) by {
+                    lemma_c(x);
+                }
+                assert(x == x);
+            }
+        "#;
+
+        // The assert-by nested in `proof { }` is part of the outer block
+        assert_eq!(
+            kinds(body),
+            vec![ProofBlockKind::Proof, ProofBlockKind::AssertBy]
+        );
+    }
+
+    #[test]
+    fn test_assert_forall_and_closures() {
+        let body = r#"
+            fn f(v: Vec<u32>) {
+                let g = |x: u32| {
+                    proof { lemma_a(x); }
+                    x
+                };
+                assert forall|i: int| 0 <= i < v.len() implies v[i] >= 0 by {
+                    lemma_b(i);
+                }
+            }
+        "#;
+
+        assert_eq!(
+            kinds(body),
+            vec![ProofBlockKind::Proof, ProofBlockKind::AssertForallBy]
+        );
+    }
+
+    #[test]
+    fn test_calc_steps() {
+        let body = r#"
+            proof fn p(a: int, b: int) {
+                calc! {
+                    (==)
+                    a + b; { lemma_add_comm(a, b); }
+                    b + a;
+                }
+            }
+        "#;
+
+        let blocks = extract_proof_blocks(body).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].kind, ProofBlockKind::Calc);
+        assert_eq!(blocks[0].block.stmts.len(), 1);
+    }
+
+    #[test]
+    fn test_no_proof_blocks() {
+        assert!(kinds("fn f(x: u32) -> u32 { x + 1 }").is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+//! Structural step counts of proofs
+//!
+//! Counts the building blocks of a proof rather than its tokens: `assert`
+//! statements, `assert ... by { }` blocks, `calc!` steps, `reveal`s and the
+//! `by(bit_vector)` / `by(nonlinear_arith)` solver switches. These track the
+//! maintenance cost of a proof more closely than its Halstead numbers: each
+//! step is something a reader has to re-check when a spec changes.
+//!
+//! Unlike [`crate::collect_proof_blocks`], nested blocks are visited too, so
+//! an `assert ... by` inside a `proof { }` block counts as well.
+
+use crate::body_complexity::parse_body_block;
+use crate::proof_blocks::parse_calc_steps;
+use serde::{Deserialize, Serialize};
+use verus_syn::visit::{self, Visit};
+use verus_syn::{Assert, AssertForall, Block, ExprMacro, RevealHide};
+
+/// Proof steps of one function body
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStepMetrics {
+    /// `assert(..)` and `assert forall` statements, with or without `by`
+    pub asserts: usize,
+    /// `assert(..) by { }` and `assert forall .. by { }` blocks
+    pub assert_by_blocks: usize,
+    /// Braced steps of `calc! { }` chains
+    pub calc_steps: usize,
+    /// `reveal(..)` and `reveal_with_fuel(..)` of opaque spec fns
+    pub reveals: usize,
+    /// `assert(..) by(bit_vector)`
+    pub bit_vector: usize,
+    /// `assert(..) by(nonlinear_arith)`
+    pub nonlinear_arith: usize,
+}
+
+impl ProofStepMetrics {
+    /// All asserts, calc steps and reveals; `by` blocks and solver switches
+    /// are refinements of the asserts and not counted again
+    pub fn total(&self) -> usize {
+        self.asserts + self.calc_steps + self.reveals
+    }
+}
+
+#[derive(Default)]
+struct ProofStepVisitor {
+    metrics: ProofStepMetrics,
+}
+
+impl<'ast> Visit<'ast> for ProofStepVisitor {
+    fn visit_assert(&mut self, assert: &'ast Assert) {
+        self.metrics.asserts += 1;
+        if assert.body.is_some() {
+            self.metrics.assert_by_blocks += 1;
+        }
+        if let Some((_, prover)) = &assert.prover {
+            if prover == "bit_vector" {
+                self.metrics.bit_vector += 1;
+            } else if prover == "nonlinear_arith" {
+                self.metrics.nonlinear_arith += 1;
+            }
+        }
+        visit::visit_assert(self, assert);
+    }
+
+    fn visit_assert_forall(&mut self, assert: &'ast AssertForall) {
+        self.metrics.asserts += 1;
+        self.metrics.assert_by_blocks += 1;
+        visit::visit_assert_forall(self, assert);
+    }
+
+    fn visit_reveal_hide(&mut self, reveal: &'ast RevealHide) {
+        if reveal.hide_token.is_none() {
+            self.metrics.reveals += 1;
+        }
+        visit::visit_reveal_hide(self, reveal);
+    }
+
+    fn visit_expr_macro(&mut self, mac: &'ast ExprMacro) {
+        if !mac.mac.path.is_ident("calc") {
+            return visit::visit_expr_macro(self, mac);
+        }
+        if let Ok(steps) = mac.mac.parse_body_with(parse_calc_steps) {
+            self.metrics.calc_steps += steps.len();
+            for step in &steps {
+                self.visit_block(step);
+            }
+        }
+    }
+}
+
+/// Proof steps of an already-parsed function body
+pub fn collect_proof_steps(block: &Block) -> ProofStepMetrics {
+    let mut visitor = ProofStepVisitor::default();
+    visitor.visit_block(block);
+    visitor.metrics
+}
+
+/// Proof steps of a function's source text.
+///
+/// Accepts a whole function item or just the statements of a body, like
+/// [`crate::analyze_body_complexity`].
+pub fn analyze_proof_steps(body: &str) -> Result<ProofStepMetrics, String> {
+    Ok(parse_body_block(body)?
+        .as_ref()
+        .map(collect_proof_steps)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asserts_and_solver_switches() {
+        let body = r#"
+            proof fn lemma_mul(x: u64, y: u64) {
+                assert(x * y == y * x) by(nonlinear_arith);
+                assert(x & 0 == 0) by(bit_vector);
+                assert(x + 0 == x);
+                assert(x <= x) by {
+                    lemma_le_refl(x);
+                    assert(x == x);
+                }
+                reveal(spec_mul);
+                reveal_with_fuel(spec_pow, 3);
+            }
+        "#;
+        let metrics = analyze_proof_steps(body).unwrap();
+
+        assert_eq!(metrics.asserts, 5);
+        assert_eq!(metrics.assert_by_blocks, 1);
+        assert_eq!((metrics.bit_vector, metrics.nonlinear_arith), (1, 1));
+        assert_eq!(metrics.reveals, 2);
+        assert_eq!(metrics.total(), 7);
+    }
+
+    #[test]
+    fn test_calc_and_nested_blocks() {
+        let body = r#"
+            fn f(a: u64, b: u64) {
+                proof {
+                    calc! {
+                        (==)
+                        a + b; { lemma_add_comm(a, b); }
+                        b + a; { assert(b + a == b + a); }
+                        b + a;
+                    }
+                    assert forall|i: int| 0 <= i < 10 implies i < 11 by {
+                        assert(i < 10);
+                    }
+                }
+            }
+        "#;
+        let metrics = analyze_proof_steps(body).unwrap();
+
+        assert_eq!(metrics.calc_steps, 2);
+        // The assert in the second calc step, the forall and the one inside it
+        assert_eq!(metrics.asserts, 3);
+        assert_eq!(metrics.assert_by_blocks, 1);
+    }
+
+    #[test]
+    fn test_exec_function_without_proof() {
+        let metrics = analyze_proof_steps("fn add(a: u64, b: u64) -> u64 { a + b }").unwrap();
+        assert_eq!(metrics, ProofStepMetrics::default());
+    }
+}
@@ -0,0 +1,270 @@
+//! Detection of natural-language prose in extracted specifications
+//!
+//! Spec text scraped from source files sometimes contains comment fragments
+//! instead of expressions. A [`ProseDetector`] scores how likely a string is
+//! prose, from 0.0 (certainly code) to 1.0 (certainly prose); the Halstead
+//! analysis skips specs whose score reaches [`PROSE_THRESHOLD`].
+//!
+//! Two detectors are provided:
+//! - [`DefaultProseDetector`]: English phrase and starter-word lists plus a
+//!   letter/operator check; the phrase list can be extended, e.g. with
+//!   phrases in another language loaded from a file
+//! - [`RatioProseDetector`]: language independent, scores by how few
+//!   operator characters there are per letter
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Score at or above which a spec is treated as prose
+pub const PROSE_THRESHOLD: f64 = 0.5;
+
+/// Characters that indicate code rather than prose
+const OPERATOR_CHARS: [char; 11] = ['=', '<', '>', '!', '&', '|', '+', '-', '*', '/', '%'];
+
+/// Phrases that only appear in English prose
+const DEFAULT_INDICATORS: [&str; 21] = [
+    "However,",
+    "Thus,",
+    "Therefore,",
+    "i.e.",
+    "e.g.",
+    "must be clear",
+    "should be",
+    "cannot add",
+    "are swapped",
+    "remain unchanged",
+    "is equivalent to",
+    "We have that",
+    "only changing",
+    "in either case",
+    "returns if",
+    "given input",
+    "an inverse",
+    "each coset",
+    "is the multiplicative inverse",
+    "is zero",
+    "similarly for the",
+];
+
+/// Capitalized English words that start a sentence rather than an expression
+const DEFAULT_STARTERS: [&str; 20] = [
+    "However",
+    "Thus",
+    "Therefore",
+    "Given",
+    "When",
+    "If",
+    "The ",
+    "A ",
+    "An ",
+    "This ",
+    "That ",
+    "These ",
+    "Those ",
+    "We ",
+    "It ",
+    "As ",
+    "For ",
+    "In ",
+    "On ",
+    "At ",
+];
+
+/// Scores how likely a specification string is natural-language prose
+pub trait ProseDetector {
+    /// Confidence in `[0.0, 1.0]` that `spec` is prose rather than code
+    fn prose_confidence(&self, spec: &str) -> f64;
+
+    /// Whether the confidence reaches [`PROSE_THRESHOLD`]
+    fn is_prose(&self, spec: &str) -> bool {
+        self.prose_confidence(spec) >= PROSE_THRESHOLD
+    }
+}
+
+/// Phrase-list heuristic, extensible with custom indicator phrases
+#[derive(Debug, Clone)]
+pub struct DefaultProseDetector {
+    indicators: Vec<String>,
+    starters: Vec<String>,
+}
+
+impl Default for DefaultProseDetector {
+    fn default() -> Self {
+        Self {
+            indicators: DEFAULT_INDICATORS.iter().map(|s| s.to_string()).collect(),
+            starters: DEFAULT_STARTERS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl DefaultProseDetector {
+    /// Add indicator phrases to the built-in English list
+    pub fn with_indicators<I, S>(mut self, indicators: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.indicators
+            .extend(indicators.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add indicator phrases read from a file, one per line
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn with_indicators_from_file(self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(self.with_indicators(parse_pattern_list(&content)))
+    }
+}
+
+impl ProseDetector for DefaultProseDetector {
+    fn prose_confidence(&self, spec: &str) -> f64 {
+        let spec_trimmed = spec.trim();
+
+        // Empty or very short strings are not prose
+        if spec_trimmed.len() < 10 {
+            return 0.0;
+        }
+
+        // Doc comment markers
+        if spec_trimmed.starts_with("///") || spec_trimmed.starts_with("//!") {
+            return 1.0;
+        }
+
+        if self
+            .indicators
+            .iter()
+            .any(|pattern| spec_trimmed.contains(pattern.as_str()))
+        {
+            return 0.9;
+        }
+
+        // End of a comment fragment
+        if spec_trimmed.ends_with("*/") && !spec_trimmed.starts_with("/*") {
+            return 0.8;
+        }
+
+        // A starter word followed by no comparison (which would indicate code)
+        let has_comparison = ["==", "!=", "<=", ">="]
+            .iter()
+            .any(|op| spec_trimmed.contains(op));
+        if !has_comparison
+            && self
+                .starters
+                .iter()
+                .any(|starter| spec_trimmed.starts_with(starter.as_str()))
+        {
+            return 0.7;
+        }
+
+        // Lots of text but very few operators
+        let (letter_count, operator_count) = letters_and_operators(spec_trimmed);
+        if letter_count > 50 && operator_count < 3 {
+            return 0.6;
+        }
+
+        0.0
+    }
+}
+
+/// Language-independent detector scoring by operator density
+///
+/// The confidence falls linearly from 1.0 with no operators to 0.0 at
+/// `max_operator_ratio` operator characters per letter.
+#[derive(Debug, Clone, Copy)]
+pub struct RatioProseDetector {
+    /// Strings with fewer letters are never prose
+    pub min_letters: usize,
+
+    /// Operators per letter at which a string is certainly code
+    pub max_operator_ratio: f64,
+}
+
+impl Default for RatioProseDetector {
+    fn default() -> Self {
+        Self {
+            min_letters: 20,
+            max_operator_ratio: 0.1,
+        }
+    }
+}
+
+impl ProseDetector for RatioProseDetector {
+    fn prose_confidence(&self, spec: &str) -> f64 {
+        let (letter_count, operator_count) = letters_and_operators(spec.trim());
+        if letter_count < self.min_letters.max(1) {
+            return 0.0;
+        }
+        let ratio = operator_count as f64 / letter_count as f64;
+        (1.0 - ratio / self.max_operator_ratio).clamp(0.0, 1.0)
+    }
+}
+
+/// Parse a pattern list: one pattern per line, skipping blanks and `#` comments
+pub fn parse_pattern_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn letters_and_operators(text: &str) -> (usize, usize) {
+    let letter_count = text.chars().filter(|c| c.is_alphabetic()).count();
+    let operator_count = text.chars().filter(|c| OPERATOR_CHARS.contains(c)).count();
+    (letter_count, operator_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_detector_scores() {
+        let detector = DefaultProseDetector::default();
+
+        assert_eq!(detector.prose_confidence("/// Explains the function"), 1.0);
+        assert_eq!(
+            detector.prose_confidence("scalars should be canonical"),
+            0.9
+        );
+        assert_eq!(detector.prose_confidence("The value must be positive"), 0.7);
+        assert_eq!(detector.prose_confidence("The value == 0"), 0.0);
+        assert_eq!(detector.prose_confidence("x > 0 && y > 0"), 0.0);
+        assert!(!detector.is_prose("i.e."));
+    }
+
+    #[test]
+    fn test_custom_indicators() {
+        let spec = "o resultado fica reduzido";
+        assert!(!DefaultProseDetector::default().is_prose(spec));
+
+        let patterns = parse_pattern_list("# Portuguese\n\nfica reduzido\n  sempre que \n");
+        assert_eq!(patterns, vec!["fica reduzido", "sempre que"]);
+
+        let detector = DefaultProseDetector::default().with_indicators(patterns);
+        assert!(detector.is_prose(spec));
+    }
+
+    #[test]
+    fn test_ratio_detector() {
+        let detector = RatioProseDetector::default();
+
+        assert_eq!(
+            detector.prose_confidence("o limite inferior nunca ultrapassa o superior"),
+            1.0
+        );
+        assert_eq!(
+            detector.prose_confidence("forall|i: int| 0 <= i < n ==> a[i] > 0"),
+            0.0
+        );
+        // Too short to judge
+        assert_eq!(detector.prose_confidence("sempre"), 0.0);
+
+        let code = "self.is_valid() && other.is_valid()";
+        assert!(!detector.is_prose(code));
+    }
+}
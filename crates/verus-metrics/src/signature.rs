@@ -0,0 +1,173 @@
+//! Function signatures parsed from source
+//!
+//! [`parse_signature`] turns the source text of a function (as stored in
+//! atom bodies) into a [`FunctionSignature`]: the signature text, parameter
+//! names and types, return type, generic parameters and visibility. Texts
+//! are cut from the source by span, so types keep their original spelling.
+
+use proc_macro2::LineColumn;
+use scip_core::{Atom, FunctionParam, FunctionSignature};
+use verus_syn::spanned::Spanned;
+use verus_syn::{FnArgKind, ImplItem, Item, ItemFn, ReturnType, Signature, TraitItem, Visibility};
+
+/// Byte offset of a span position (1-based line, 0-based char column)
+pub(crate) fn byte_offset(source: &str, at: LineColumn) -> usize {
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(at.line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let line = &source[line_start..];
+    line_start
+        + line
+            .char_indices()
+            .nth(at.column)
+            .map_or(line.len(), |(i, _)| i)
+}
+
+/// Source text from `start` to `end`, whitespace collapsed to single spaces
+fn text_between(source: &str, start: LineColumn, end: LineColumn) -> String {
+    let (start, end) = (byte_offset(source, start), byte_offset(source, end));
+    source
+        .get(start..end.max(start))
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn text_of(source: &str, node: &impl Spanned) -> String {
+    let span = node.span();
+    text_between(source, span.start(), span.end())
+}
+
+/// Visibility (`None` for trait methods) and signature of a function
+pub(crate) fn parse_function(source: &str) -> Result<(Option<Visibility>, Signature), String> {
+    if let Ok(item_fn) = verus_syn::parse_str::<ItemFn>(source) {
+        return Ok((Some(item_fn.vis), item_fn.sig));
+    }
+    if let Ok(Item::Fn(item_fn)) = verus_syn::parse_str::<Item>(source) {
+        return Ok((Some(item_fn.vis), item_fn.sig));
+    }
+    if let Ok(ImplItem::Fn(impl_fn)) = verus_syn::parse_str::<ImplItem>(source) {
+        return Ok((Some(impl_fn.vis), impl_fn.sig));
+    }
+    match verus_syn::parse_str::<TraitItem>(source) {
+        Ok(TraitItem::Fn(trait_fn)) => Ok((None, trait_fn.sig)),
+        Ok(_) => Err("Not a function".to_string()),
+        Err(e) => Err(format!("Failed to parse function: {}", e)),
+    }
+}
+
+/// Parse the signature of a function from its source text
+///
+/// Trait methods have no visibility of their own and are reported with
+/// `is_pub: false`.
+pub fn parse_signature(source: &str) -> Result<FunctionSignature, String> {
+    let (vis, sig) = parse_function(source)?;
+
+    let is_pub = matches!(vis, Some(Visibility::Public(_)));
+    let start = match &vis {
+        Some(Visibility::Inherited) | None => sig.span().start(),
+        Some(vis) => vis.span().start(),
+    };
+    let end = match &sig.output {
+        ReturnType::Default => sig.paren_token.span.close().end(),
+        output => output.span().end(),
+    };
+
+    let params = sig
+        .inputs
+        .iter()
+        .map(|arg| match &arg.kind {
+            FnArgKind::Receiver(receiver) => FunctionParam {
+                name: "self".to_string(),
+                ty: text_of(source, receiver),
+            },
+            FnArgKind::Typed(typed) => FunctionParam {
+                name: text_of(source, &typed.pat),
+                ty: text_of(source, &typed.ty),
+            },
+        })
+        .collect();
+    let return_type = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, _, _, ty) => Some(text_of(source, ty)),
+    };
+
+    Ok(FunctionSignature {
+        signature: text_between(source, start, end),
+        params,
+        return_type,
+        generics: sig
+            .generics
+            .params
+            .iter()
+            .map(|param| text_of(source, param))
+            .collect(),
+        is_pub,
+    })
+}
+
+/// Fill in the signature of every atom whose body parses; returns how many
+/// were filled in
+pub fn add_signatures(atoms: &mut [Atom]) -> usize {
+    let mut added = 0;
+    for atom in atoms {
+        if let Ok(signature) = parse_signature(&atom.body) {
+            atom.signature = Some(signature);
+            added += 1;
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signature() {
+        let source = "pub fn add<T: Copy, const N: usize>(\n    a: [T; N],\n    b: Vec<u64>,\n) -> (r: u64)\n    requires b.len() > 0,\n    ensures r > 0,\n{\n    b[0]\n}";
+        let signature = parse_signature(source).unwrap();
+
+        assert_eq!(
+            signature.signature,
+            "pub fn add<T: Copy, const N: usize>( a: [T; N], b: Vec<u64>, ) -> (r: u64)"
+        );
+        assert_eq!(
+            signature.params,
+            vec![
+                FunctionParam {
+                    name: "a".to_string(),
+                    ty: "[T; N]".to_string()
+                },
+                FunctionParam {
+                    name: "b".to_string(),
+                    ty: "Vec<u64>".to_string()
+                },
+            ]
+        );
+        assert_eq!(signature.return_type.as_deref(), Some("u64"));
+        assert_eq!(signature.generics, vec!["T: Copy", "const N: usize"]);
+        assert!(signature.is_pub);
+    }
+
+    #[test]
+    fn test_parse_method_signature() {
+        let signature = parse_signature("pub(crate) open spec fn len(&self) -> nat { 0 }").unwrap();
+        assert_eq!(
+            signature.signature,
+            "pub(crate) open spec fn len(&self) -> nat"
+        );
+        assert_eq!(signature.params[0].name, "self");
+        assert_eq!(signature.params[0].ty, "&self");
+        assert!(!signature.is_pub);
+
+        let unit = parse_signature("fn reset(&mut self) {}").unwrap();
+        assert_eq!(unit.signature, "fn reset(&mut self)");
+        assert_eq!(unit.return_type, None);
+
+        assert!(parse_signature("struct S;").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! Spec metrics for a text range, for editor integrations
+//!
+//! An editor knows the file under the cursor, not the spec clause.
+//! [`analyze_spec_at`] parses the whole file (including the items of
+//! `verus! { ... }` blocks), finds the innermost `requires`, `ensures` or
+//! `decreases` clause containing a byte range and returns its Halstead
+//! metrics with the exact byte span of the clause, ready to highlight.
+
+use crate::signature::byte_offset;
+use crate::spec_halstead::{analyze_expr, SpecHalsteadMetrics};
+use serde::Serialize;
+use std::ops::Range;
+use verus_syn::spanned::Spanned;
+use verus_syn::visit::Visit;
+use verus_syn::{Expr, File, Ident, ItemMacro, Signature};
+
+/// Which spec clause of a signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClauseKind {
+    Requires,
+    Ensures,
+    Decreases,
+}
+
+impl ClauseKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClauseKind::Requires => "requires",
+            ClauseKind::Ensures => "ensures",
+            ClauseKind::Decreases => "decreases",
+        }
+    }
+}
+
+/// The spec clause found by [`analyze_spec_at`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SpecAtRange {
+    pub kind: ClauseKind,
+    /// Name of the function the clause belongs to
+    pub function: String,
+    /// Source text of the clause (one comma-separated expression)
+    pub text: String,
+    /// Byte range of the clause in the file
+    pub span: Range<usize>,
+    pub metrics: SpecHalsteadMetrics,
+}
+
+struct ClauseVisitor<'s> {
+    source: &'s str,
+    range: Range<usize>,
+    found: Option<SpecAtRange>,
+}
+
+impl ClauseVisitor<'_> {
+    /// Keep the clause if it contains the range and is smaller than the one
+    /// found so far
+    fn consider(&mut self, kind: ClauseKind, function: &Ident, expr: &Expr) {
+        let span = expr.span();
+        let start = byte_offset(self.source, span.start());
+        let end = byte_offset(self.source, span.end());
+        if start > self.range.start || self.range.end > end {
+            return;
+        }
+        if self
+            .found
+            .as_ref()
+            .is_some_and(|found| found.span.len() <= end - start)
+        {
+            return;
+        }
+        self.found = Some(SpecAtRange {
+            kind,
+            function: function.to_string(),
+            text: self.source[start..end].to_string(),
+            span: start..end,
+            metrics: analyze_expr(expr),
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for ClauseVisitor<'_> {
+    fn visit_item_macro(&mut self, item: &'ast ItemMacro) {
+        // Tokens of `verus! { ... }` keep their spans in the file
+        if item.mac.path.is_ident("verus") {
+            if let Ok(file) = verus_syn::parse2::<File>(item.mac.tokens.clone()) {
+                Visit::visit_file(self, &file);
+            }
+        }
+        verus_syn::visit::visit_item_macro(self, item);
+    }
+
+    fn visit_signature(&mut self, sig: &'ast Signature) {
+        let spec = &sig.spec;
+        let clauses = [
+            (
+                ClauseKind::Requires,
+                spec.requires.as_ref().map(|clause| &clause.exprs.exprs),
+            ),
+            (
+                ClauseKind::Ensures,
+                spec.ensures.as_ref().map(|clause| &clause.exprs.exprs),
+            ),
+            (
+                ClauseKind::Decreases,
+                spec.decreases
+                    .as_ref()
+                    .map(|clause| &clause.decreases.exprs.exprs),
+            ),
+        ];
+        for (kind, exprs) in clauses {
+            for expr in exprs.into_iter().flatten() {
+                self.consider(kind, &sig.ident, expr);
+            }
+        }
+        verus_syn::visit::visit_signature(self, sig);
+    }
+}
+
+/// Find the spec clause around `byte_range` of a source file and compute
+/// its metrics.
+///
+/// A clause is one expression of a `requires`, `ensures` or `decreases`
+/// list; the innermost clause containing the whole range is returned
+/// (an empty range is a cursor position). `Ok(None)` means the range is
+/// not inside a clause; files that do not parse are an error.
+pub fn analyze_spec_at(
+    file_contents: &str,
+    byte_range: Range<usize>,
+) -> Result<Option<SpecAtRange>, String> {
+    if byte_range.start > byte_range.end || byte_range.end > file_contents.len() {
+        return Err(format!(
+            "Invalid byte range {}..{} for a file of {} bytes",
+            byte_range.start,
+            byte_range.end,
+            file_contents.len()
+        ));
+    }
+    let file =
+        verus_syn::parse_file(file_contents).map_err(|e| format!("Failed to parse file: {}", e))?;
+
+    let mut visitor = ClauseVisitor {
+        source: file_contents,
+        range: byte_range,
+        found: None,
+    };
+    visitor.visit_file(&file);
+    Ok(visitor.found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"use vstd::prelude::*;
+
+verus! {
+
+fn add_one(x: u32) -> (r: u32)
+    requires
+        x > 0,
+        x < 100,
+    ensures
+        r == x + 1,
+{
+    x + 1
+}
+
+}
+"#;
+
+    fn offset_of(needle: &str) -> usize {
+        SOURCE.find(needle).unwrap()
+    }
+
+    #[test]
+    fn test_analyze_spec_at() {
+        let cursor = offset_of("x < 100") + 2;
+        let found = analyze_spec_at(SOURCE, cursor..cursor).unwrap().unwrap();
+        assert_eq!(found.kind, ClauseKind::Requires);
+        assert_eq!(found.function, "add_one");
+        assert_eq!(found.text, "x < 100");
+        assert_eq!(found.span, offset_of("x < 100")..offset_of("x < 100") + 7);
+        assert!(found.metrics.halstead_length > 0);
+
+        let start = offset_of("r == x + 1");
+        let found = analyze_spec_at(SOURCE, start..start + 10).unwrap().unwrap();
+        assert_eq!(found.kind, ClauseKind::Ensures);
+        assert_eq!(found.text, "r == x + 1");
+
+        // The body and a range spanning two clauses are not inside a clause
+        let body = offset_of("    x + 1\n}") + 4;
+        assert!(analyze_spec_at(SOURCE, body..body).unwrap().is_none());
+        let across = offset_of("x > 0")..offset_of("x < 100") + 3;
+        assert!(analyze_spec_at(SOURCE, across).unwrap().is_none());
+
+        assert!(analyze_spec_at(SOURCE, 0..SOURCE.len() + 1).is_err());
+        assert!(analyze_spec_at("fn broken(", 0..0).is_err());
+    }
+}
@@ -14,11 +14,17 @@
 //! - `==>` implication
 //! - `#![trigger]` annotations
 //! - `old()` expressions
+//!
+//! Halstead counts treat a quantifier body like any other expression, so
+//! quantifier structure (count, nesting, bound variables, triggers) is
+//! reported separately; quantifier alternation is usually what makes a spec
+//! expensive for the SMT solver.
 
-use quote::ToTokens;
+use crate::halstead::{HalsteadCollector, HalsteadOptions};
+use crate::prose::{DefaultProseDetector, ProseDetector, PROSE_THRESHOLD};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use verus_syn::{visit::Visit, Expr};
+use std::collections::HashMap;
+use verus_syn::{visit::Visit, Attribute, Block, Expr, ExprUnary, UnOp};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SpecHalsteadMetrics {
@@ -48,175 +54,102 @@ pub struct SpecHalsteadMetrics {
 
     /// Total operand occurrences
     pub n2_total_operands: usize,
-}
 
-#[derive(Default)]
-struct HalsteadVisitor {
-    operators: Vec<String>,
-    operands: Vec<String>,
-    unique_operators: HashSet<String>,
-    unique_operands: HashSet<String>,
-}
+    /// Number of `forall` / `exists` quantifiers
+    #[serde(default)]
+    pub quantifier_count: usize,
 
-impl<'ast> Visit<'ast> for HalsteadVisitor {
-    fn visit_expr(&mut self, expr: &'ast Expr) {
-        match expr {
-            Expr::Binary(bin) => {
-                // Count binary operators: +, -, *, ==, <, &&, etc.
-                let op = bin.op.to_token_stream().to_string();
-                self.operators.push(op.clone());
-                self.unique_operators.insert(op);
-
-                // Visit operands recursively
-                self.visit_expr(&bin.left);
-                self.visit_expr(&bin.right);
-            }
-            Expr::Unary(un) => {
-                // Count unary operators: !, -, *
-                let op = un.op.to_token_stream().to_string();
-                self.operators.push(op.clone());
-                self.unique_operators.insert(op);
+    /// Deepest nesting of quantifiers (0 without quantifiers)
+    #[serde(default)]
+    pub max_quantifier_depth: usize,
 
-                self.visit_expr(&un.expr);
-            }
-            Expr::Path(path) => {
-                // Count variable names, constants, type names as operands
-                let name = path
-                    .path
-                    .segments
-                    .iter()
-                    .map(|seg| seg.ident.to_string())
-                    .collect::<Vec<_>>()
-                    .join("::");
-                self.operands.push(name.clone());
-                self.unique_operands.insert(name);
-            }
-            Expr::Lit(lit) => {
-                // Count literals as operands
-                let lit_str = match &lit.lit {
-                    verus_syn::Lit::Str(s) => format!("\"{}\"", s.value()),
-                    verus_syn::Lit::ByteStr(b) => format!("{:?}", b.value()),
-                    verus_syn::Lit::CStr(c) => format!("{:?}", c.value()),
-                    verus_syn::Lit::Byte(b) => format!("{}", b.value()),
-                    verus_syn::Lit::Char(c) => format!("'{}'", c.value()),
-                    verus_syn::Lit::Int(i) => i.to_string(),
-                    verus_syn::Lit::Float(f) => f.to_string(),
-                    verus_syn::Lit::Bool(b) => b.value().to_string(),
-                    verus_syn::Lit::Verbatim(v) => v.to_string(),
-                    _ => "literal".to_string(),
-                };
-                self.operands.push(lit_str.clone());
-                self.unique_operands.insert(lit_str);
-            }
-            Expr::Call(call) => {
-                // Count function calls as operators
-                self.operators.push("call".to_string());
-                self.unique_operators.insert("call".to_string());
-
-                self.visit_expr(&call.func);
-                for arg in &call.args {
-                    self.visit_expr(arg);
-                }
-            }
-            Expr::MethodCall(method) => {
-                // Count method calls as operators
-                let method_name = method.method.to_string();
-                self.operators.push(method_name.clone());
-                self.unique_operators.insert(method_name);
-
-                self.visit_expr(&method.receiver);
-                for arg in &method.args {
-                    self.visit_expr(arg);
-                }
-            }
-            Expr::Field(field) => {
-                // Count field access as operator
-                self.operators.push(".".to_string());
-                self.unique_operators.insert(".".to_string());
-
-                // Field name is an operand
-                let field_name = match &field.member {
-                    verus_syn::Member::Named(ident) => ident.to_string(),
-                    verus_syn::Member::Unnamed(index) => index.index.to_string(),
-                };
-                self.operands.push(field_name.clone());
-                self.unique_operands.insert(field_name);
-
-                self.visit_expr(&field.base);
-            }
-            Expr::Index(index) => {
-                // Count array indexing as operator
-                self.operators.push("[]".to_string());
-                self.unique_operators.insert("[]".to_string());
+    /// Number of `#[trigger]` and `#![trigger ...]` annotations
+    #[serde(default)]
+    pub trigger_count: usize,
 
-                self.visit_expr(&index.expr);
-                self.visit_expr(&index.index);
-            }
-            Expr::Paren(paren) => {
-                // Count parentheses as operators
-                self.operators.push("()".to_string());
-                self.unique_operators.insert("()".to_string());
+    /// Number of variables bound by quantifiers
+    #[serde(default)]
+    pub quantified_variables: usize,
 
-                self.visit_expr(&paren.expr);
-            }
-            Expr::Cast(cast) => {
-                // Count casts as operators
-                self.operators.push("as".to_string());
-                self.unique_operators.insert("as".to_string());
+    /// Occurrences of each operator (only with [`HalsteadOptions::frequencies`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_frequencies: Option<HashMap<String, usize>>,
 
-                self.visit_expr(&cast.expr);
-                // Type is counted when we visit it
-            }
-            Expr::Reference(reference) => {
-                // Count reference operator
-                self.operators.push("&".to_string());
-                self.unique_operators.insert("&".to_string());
+    /// Occurrences of each operand (only with [`HalsteadOptions::frequencies`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operand_frequencies: Option<HashMap<String, usize>>,
+}
 
-                self.visit_expr(&reference.expr);
-            }
-            // For other expression types, use default traversal
-            _ => verus_syn::visit::visit_expr(self, expr),
+#[derive(Default)]
+struct QuantifierVisitor {
+    depth: usize,
+    quantifier_count: usize,
+    max_quantifier_depth: usize,
+    trigger_count: usize,
+    quantified_variables: usize,
+}
+
+impl<'ast> Visit<'ast> for QuantifierVisitor {
+    fn visit_expr_unary(&mut self, unary: &'ast ExprUnary) {
+        if !matches!(unary.op, UnOp::Forall(_) | UnOp::Exists(_)) {
+            return verus_syn::visit::visit_expr_unary(self, unary);
+        }
+
+        self.quantifier_count += 1;
+        self.depth += 1;
+        self.max_quantifier_depth = self.max_quantifier_depth.max(self.depth);
+        // `forall|i: int, j: int| body` parses as the quantifier applied to a closure
+        if let Expr::Closure(closure) = &*unary.expr {
+            self.quantified_variables += closure.inputs.len();
+        }
+        verus_syn::visit::visit_expr_unary(self, unary);
+        self.depth -= 1;
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        if attr.path().is_ident("trigger") {
+            self.trigger_count += 1;
         }
     }
 }
 
-impl HalsteadVisitor {
-    fn compute_metrics(&self) -> SpecHalsteadMetrics {
-        let n1 = self.unique_operators.len();
-        let n2 = self.unique_operands.len();
-        let n1_total = self.operators.len();
-        let n2_total = self.operands.len();
+impl QuantifierVisitor {
+    fn record(self, metrics: &mut SpecHalsteadMetrics) {
+        metrics.quantifier_count = self.quantifier_count;
+        metrics.max_quantifier_depth = self.max_quantifier_depth;
+        metrics.trigger_count = self.trigger_count;
+        metrics.quantified_variables = self.quantified_variables;
+    }
+}
 
-        let length = n1_total + n2_total;
-        let vocabulary = n1 + n2;
+/// Compute Halstead and quantifier metrics for an already-parsed expression
+pub fn analyze_expr(expr: &Expr) -> SpecHalsteadMetrics {
+    analyze_expr_with_options(expr, HalsteadOptions::default())
+}
 
-        // Handle edge cases
-        let difficulty = if n2 == 0 {
-            0.0
-        } else {
-            (n1 as f64 / 2.0) * (n2_total as f64 / n2 as f64)
-        };
+/// [`analyze_expr`] with explicit counting options, e.g. to get operator and
+/// operand frequency tables
+pub fn analyze_expr_with_options(expr: &Expr, options: HalsteadOptions) -> SpecHalsteadMetrics {
+    let mut collector = HalsteadCollector::new(options);
+    collector.visit_expr(expr);
+    let mut metrics = collector.metrics();
+
+    let mut quantifiers = QuantifierVisitor::default();
+    quantifiers.visit_expr(expr);
+    quantifiers.record(&mut metrics);
+    metrics
+}
 
-        let volume = if vocabulary == 0 {
-            0.0
-        } else {
-            length as f64 * (vocabulary as f64).log2()
-        };
+/// Compute Halstead metrics over all statements of a block (e.g. a function body)
+pub(crate) fn analyze_block_halstead(block: &Block) -> SpecHalsteadMetrics {
+    let mut collector = HalsteadCollector::default();
+    collector.visit_block(block);
+    let mut metrics = collector.metrics();
 
-        let effort = difficulty * volume;
-
-        SpecHalsteadMetrics {
-            halstead_length: length,
-            vocabulary,
-            difficulty,
-            volume,
-            effort,
-            n1_unique_operators: n1,
-            n2_unique_operands: n2,
-            n1_total_operators: n1_total,
-            n2_total_operands: n2_total,
-        }
-    }
+    let mut quantifiers = QuantifierVisitor::default();
+    quantifiers.visit_block(block);
+    quantifiers.record(&mut metrics);
+    metrics
 }
 
 // REMOVED: Type cast removal and chained comparison expansion
@@ -232,107 +165,10 @@ impl HalsteadVisitor {
 // - All other Verus-specific syntax
 
 /// Detect if a spec is primarily natural language prose rather than code
+///
+/// Uses [`DefaultProseDetector`]; see [`analyze_spec_with`] for other detectors.
 pub fn is_prose(spec: &str) -> bool {
-    let spec_trimmed = spec.trim();
-
-    // Empty or very short strings are not prose
-    if spec_trimmed.len() < 10 {
-        return false;
-    }
-
-    // Check for doc comment markers
-    if spec_trimmed.starts_with("///") || spec_trimmed.starts_with("//!") {
-        return true;
-    }
-
-    // Check for common prose patterns (English phrases without operators)
-    let prose_indicators = [
-        "However,",
-        "Thus,",
-        "Therefore,",
-        "i.e.",
-        "e.g.",
-        "must be clear",
-        "should be",
-        "cannot add",
-        "are swapped",
-        "remain unchanged",
-        "is equivalent to",
-        "We have that",
-        "only changing",
-        "in either case",
-        "returns if",
-        "given input",
-        "an inverse",
-        "each coset",
-        "is the multiplicative inverse",
-        "is zero",
-        "similarly for the",
-    ];
-
-    for pattern in &prose_indicators {
-        if spec_trimmed.contains(pattern) {
-            return true;
-        }
-    }
-
-    // Check if it starts with a capitalized English word (not a type name)
-    // Common English starter words that indicate prose
-    let prose_starters = [
-        "However",
-        "Thus",
-        "Therefore",
-        "Given",
-        "When",
-        "If",
-        "The ",
-        "A ",
-        "An ",
-        "This ",
-        "That ",
-        "These ",
-        "Those ",
-        "We ",
-        "It ",
-        "As ",
-        "For ",
-        "In ",
-        "On ",
-        "At ",
-    ];
-
-    for starter in &prose_starters {
-        if spec_trimmed.starts_with(starter) {
-            // Make sure it's not followed by operators (which would indicate code)
-            if !spec_trimmed.contains("==")
-                && !spec_trimmed.contains("!=")
-                && !spec_trimmed.contains("<=")
-                && !spec_trimmed.contains(">=")
-            {
-                return true;
-            }
-        }
-    }
-
-    // Check for statement/comment only fragments
-    if spec_trimmed.ends_with("*/") && !spec_trimmed.starts_with("/*") {
-        return true; // End of comment fragment
-    }
-
-    // Check if it's mostly prose (ratio of letters to operators)
-    let letter_count = spec_trimmed.chars().filter(|c| c.is_alphabetic()).count();
-    let operator_chars = ['=', '<', '>', '!', '&', '|', '+', '-', '*', '/', '%'];
-    let operator_count = spec_trimmed
-        .chars()
-        .filter(|c| operator_chars.contains(c))
-        .count();
-
-    // If we have lots of text but very few operators, likely prose
-    if letter_count > 50 && operator_count < 3 {
-        return true;
-    }
-
-    false
+    DefaultProseDetector::default().is_prose(spec)
 }
 
 /// Preprocess specifications for verus_syn parsing
@@ -379,20 +215,123 @@ fn preprocess_verus_spec(spec: &str) -> String {
 
 /// Compute Halstead metrics for a specification string
 pub fn analyze_spec(spec_text: &str) -> Result<SpecHalsteadMetrics, String> {
+    analyze_spec_with(spec_text, &DefaultProseDetector::default())
+}
+
+/// Compute Halstead metrics for a specification string, skipping prose as
+/// judged by `detector`
+pub fn analyze_spec_with(
+    spec_text: &str,
+    detector: &dyn ProseDetector,
+) -> Result<SpecHalsteadMetrics, String> {
+    match spec_outcome(spec_text, detector) {
+        SpecOutcome::Ok(metrics) => Ok(metrics),
+        SpecOutcome::SkippedProse { confidence } => Err(format!(
+            "Skipped prose/documentation (confidence {:.2}): '{}'",
+            confidence,
+            abbreviate(spec_text)
+        )),
+        SpecOutcome::ParseError { span: None, .. } => Err(format!(
+            "Skipped non-expression clause: '{}'",
+            abbreviate(spec_text)
+        )),
+        SpecOutcome::ParseError { message, .. } => {
+            Err(format!("Failed to parse spec '{}': {}", spec_text, message))
+        }
+    }
+}
+
+/// Result of analyzing one specification in a batch
+#[derive(Debug, Clone, Serialize)]
+pub enum SpecOutcome {
+    /// The spec parsed as an expression
+    Ok(SpecHalsteadMetrics),
+
+    /// The spec was judged to be prose and not parsed
+    SkippedProse { confidence: f64 },
+
+    /// The spec is not an expression
+    ParseError {
+        message: String,
+        /// Where the parser gave up: 1-based line and 0-based column in the
+        /// spec with comments removed; `None` for clauses that are empty or
+        /// incomplete before parsing
+        span: Option<(usize, usize)>,
+    },
+}
+
+/// Counts of spec outcomes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BatchStats {
+    pub total: usize,
+    pub parsed: usize,
+    pub skipped_prose: usize,
+    pub parse_errors: usize,
+}
+
+impl BatchStats {
+    /// Count one outcome
+    pub fn record(&mut self, outcome: &SpecOutcome) {
+        self.total += 1;
+        match outcome {
+            SpecOutcome::Ok(_) => self.parsed += 1,
+            SpecOutcome::SkippedProse { .. } => self.skipped_prose += 1,
+            SpecOutcome::ParseError { .. } => self.parse_errors += 1,
+        }
+    }
+
+    /// Add the counts of another batch
+    pub fn merge(&mut self, other: &BatchStats) {
+        self.total += other.total;
+        self.parsed += other.parsed;
+        self.skipped_prose += other.skipped_prose;
+        self.parse_errors += other.parse_errors;
+    }
+
+    /// Fraction of the specs that are not prose which parsed (1.0 if none)
+    pub fn parse_success_rate(&self) -> f64 {
+        let attempted = self.parsed + self.parse_errors;
+        if attempted == 0 {
+            1.0
+        } else {
+            self.parsed as f64 / attempted as f64
+        }
+    }
+}
+
+/// Outcomes of analyzing a batch of specifications, in input order
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchResult {
+    pub outcomes: Vec<SpecOutcome>,
+    pub stats: BatchStats,
+}
+
+/// Compute Halstead metrics for each of `specs`, keeping prose and parse
+/// failures apart
+pub fn analyze_specs(specs: &[&str]) -> BatchResult {
+    analyze_specs_with(specs, &DefaultProseDetector::default())
+}
+
+/// [`analyze_specs`] with a custom prose detector
+pub fn analyze_specs_with(specs: &[&str], detector: &dyn ProseDetector) -> BatchResult {
+    let mut result = BatchResult::default();
+    for spec in specs {
+        let outcome = spec_outcome(spec, detector);
+        result.stats.record(&outcome);
+        result.outcomes.push(outcome);
+    }
+    result
+}
+
+fn spec_outcome(spec_text: &str, detector: &dyn ProseDetector) -> SpecOutcome {
     if spec_text.is_empty() {
-        return Ok(SpecHalsteadMetrics::default());
+        return SpecOutcome::Ok(SpecHalsteadMetrics::default());
     }
 
     // Check for prose before preprocessing
-    if is_prose(spec_text) {
-        return Err(format!(
-            "Skipped prose/documentation: '{}'",
-            if spec_text.len() > 60 {
-                format!("{}...", &spec_text[..60])
-            } else {
-                spec_text.to_string()
-            }
-        ));
+    let confidence = detector.prose_confidence(spec_text);
+    if confidence >= PROSE_THRESHOLD {
+        return SpecOutcome::SkippedProse { confidence };
     }
 
     // Preprocess Verus-specific syntax
@@ -400,25 +339,32 @@ pub fn analyze_spec(spec_text: &str) -> Result<SpecHalsteadMetrics, String> {
 
     // If preprocessing resulted in empty string (e.g., decreases clause), skip it
     if preprocessed.is_empty() {
-        return Err(format!(
-            "Skipped non-expression clause: '{}'",
-            if spec_text.len() > 60 {
-                format!("{}...", &spec_text[..60])
-            } else {
-                spec_text.to_string()
-            }
-        ));
+        return SpecOutcome::ParseError {
+            message: "non-expression clause".to_string(),
+            span: None,
+        };
     }
 
     // Try to parse as expression using verus_syn (handles Verus syntax natively)
-    let expr: Expr = verus_syn::parse_str(&preprocessed)
-        .map_err(|e| format!("Failed to parse spec '{}': {}", spec_text, e))?;
-
-    // Visit AST and count tokens
-    let mut visitor = HalsteadVisitor::default();
-    visitor.visit_expr(&expr);
+    match verus_syn::parse_str::<Expr>(&preprocessed) {
+        // Visit AST and count tokens
+        Ok(expr) => SpecOutcome::Ok(analyze_expr(&expr)),
+        Err(e) => {
+            let start = e.span().start();
+            SpecOutcome::ParseError {
+                message: e.to_string(),
+                span: Some((start.line, start.column)),
+            }
+        }
+    }
+}
 
-    Ok(visitor.compute_metrics())
+/// The first 60 characters of a spec, for messages
+fn abbreviate(spec_text: &str) -> String {
+    match spec_text.char_indices().nth(60) {
+        Some((end, _)) => format!("{}...", &spec_text[..end]),
+        None => spec_text.to_string(),
+    }
 }
 
 #[cfg(test)]
@@ -459,6 +405,36 @@ mod tests {
         assert!(metrics.halstead_length > 0);
     }
 
+    #[test]
+    fn test_quantifier_metrics() {
+        let spec = "forall|i: int, j: int| 0 <= i < j < n ==> exists|k: int| #[trigger] f(i, k) <= #[trigger] g(j, k)";
+        let metrics = analyze_spec(spec).unwrap();
+
+        assert_eq!(metrics.quantifier_count, 2);
+        assert_eq!(metrics.max_quantifier_depth, 2);
+        assert_eq!(metrics.quantified_variables, 3);
+        assert_eq!(metrics.trigger_count, 2);
+    }
+
+    #[test]
+    fn test_sibling_quantifiers_and_inner_trigger() {
+        let spec = "(forall|i: int| #![trigger s[i]] s[i] > 0) && (exists|j: int| s[j] == 0)";
+        let metrics = analyze_spec(spec).unwrap();
+
+        assert_eq!(metrics.quantifier_count, 2);
+        assert_eq!(metrics.max_quantifier_depth, 1);
+        assert_eq!(metrics.quantified_variables, 2);
+        assert_eq!(metrics.trigger_count, 1);
+    }
+
+    #[test]
+    fn test_no_quantifiers() {
+        let metrics = analyze_spec("x < 10").unwrap();
+
+        assert_eq!(metrics.quantifier_count, 0);
+        assert_eq!(metrics.max_quantifier_depth, 0);
+    }
+
     #[test]
     fn test_empty_spec() {
         let spec = "";
@@ -555,6 +531,42 @@ mod tests {
         assert!(metrics.halstead_length >= 5);
     }
 
+    #[test]
+    fn test_analyze_specs_outcomes() {
+        let result = analyze_specs(&["x < 10", "scalars should be canonical", "x <", "a &&\n (b"]);
+
+        assert!(matches!(result.outcomes[0], SpecOutcome::Ok(_)));
+        assert!(matches!(
+            result.outcomes[1],
+            SpecOutcome::SkippedProse { confidence } if confidence >= PROSE_THRESHOLD
+        ));
+        assert!(matches!(
+            result.outcomes[2],
+            SpecOutcome::ParseError {
+                span: Some((1, _)),
+                ..
+            }
+        ));
+        assert!(matches!(
+            result.outcomes[3],
+            SpecOutcome::ParseError {
+                span: Some((2, _)),
+                ..
+            }
+        ));
+
+        assert_eq!(
+            result.stats,
+            BatchStats {
+                total: 4,
+                parsed: 1,
+                skipped_prose: 1,
+                parse_errors: 2,
+            }
+        );
+        assert!((result.stats.parse_success_rate() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_prose_detection_simple() {
         let spec = "scalars should be canonical";
@@ -0,0 +1,333 @@
+//! Spec function inlining for "expanded" Halstead metrics
+//!
+//! Halstead metrics of a clause are syntactic: `ensures r == sum_of(s)`
+//! counts as a handful of tokens however much `sum_of` unfolds to. For
+//! effort estimates closer to what the verifier sees, [`SpecInliner`]
+//! replaces calls of spec functions in a clause by their definitions, with
+//! the arguments substituted for the parameters, and measures the result.
+//!
+//! Calls are resolved through the call graph: a call `f(..)` (or method call
+//! `x.f(..)`) is inlined when exactly one dep of the calling function is a
+//! spec function named `f` with a single-expression body. Definitions are
+//! expanded recursively up to a depth limit, and a spec function is never
+//! inlined into its own expansion, so recursive definitions stay calls.
+//!
+//! Substitution is by name and ignores shadowing (a quantifier binding a
+//! variable named like a parameter), which is rare in spec functions and
+//! only affects the counts, not the clause.
+
+use crate::spec_halstead::{analyze_expr, SpecHalsteadMetrics};
+use scip_core::Atom;
+use std::collections::HashMap;
+use verus_syn::visit_mut::{self, VisitMut};
+use verus_syn::{
+    Block, Expr, ExprParen, FnArgKind, FnMode, ImplItem, Item, ItemFn, Pat, Signature, Stmt,
+    TraitItem,
+};
+
+/// Default depth limit of [`SpecInliner`]
+pub const DEFAULT_INLINE_DEPTH: usize = 3;
+
+/// An inlinable spec function
+struct SpecFnDef {
+    name: String,
+    /// Parameter names, `self` for the receiver
+    params: Vec<String>,
+    body: Expr,
+    deps: Vec<String>,
+}
+
+/// Signature and body of a function's source
+fn parse_fn_with_body(source: &str) -> Option<(Signature, Block)> {
+    if let Ok(item_fn) = verus_syn::parse_str::<ItemFn>(source) {
+        return Some((item_fn.sig, *item_fn.block));
+    }
+    if let Ok(Item::Fn(item_fn)) = verus_syn::parse_str::<Item>(source) {
+        return Some((item_fn.sig, *item_fn.block));
+    }
+    if let Ok(ImplItem::Fn(impl_fn)) = verus_syn::parse_str::<ImplItem>(source) {
+        return Some((impl_fn.sig, impl_fn.block));
+    }
+    match verus_syn::parse_str::<TraitItem>(source) {
+        Ok(TraitItem::Fn(trait_fn)) => Some((trait_fn.sig, trait_fn.default?)),
+        _ => None,
+    }
+}
+
+fn parse_spec_fn(atom: &Atom) -> Option<SpecFnDef> {
+    let (sig, block) = parse_fn_with_body(&atom.body)?;
+    if !matches!(sig.mode, FnMode::Spec(_) | FnMode::SpecChecked(_)) {
+        return None;
+    }
+    let body = match &block.stmts[..] {
+        [Stmt::Expr(expr, None)] => expr.clone(),
+        _ => return None,
+    };
+    let params = sig
+        .inputs
+        .iter()
+        .map(|arg| match &arg.kind {
+            FnArgKind::Receiver(_) => Some("self".to_string()),
+            FnArgKind::Typed(typed) => match &*typed.pat {
+                Pat::Ident(pat) => Some(pat.ident.to_string()),
+                _ => None,
+            },
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(SpecFnDef {
+        name: sig.ident.to_string(),
+        params,
+        body,
+        deps: atom.deps.clone(),
+    })
+}
+
+/// The inlinable spec functions of a project, by atom identifier
+#[derive(Default)]
+pub struct SpecFnTable {
+    fns: HashMap<String, SpecFnDef>,
+}
+
+impl SpecFnTable {
+    /// Spec functions of `atoms` whose body is a single expression
+    pub fn from_atoms(atoms: &[Atom]) -> Self {
+        Self {
+            fns: atoms
+                .iter()
+                .filter_map(|atom| Some((atom.identifier.clone(), parse_spec_fn(atom)?)))
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fns.is_empty()
+    }
+
+    /// The spec function named `name` among `deps`, if exactly one
+    fn resolve<'a>(&'a self, deps: &'a [String], name: &str) -> Option<(&'a str, &'a SpecFnDef)> {
+        let mut found = deps
+            .iter()
+            .filter_map(|dep| Some((dep.as_str(), self.fns.get(dep)?)))
+            .filter(|(_, def)| def.name == name);
+        let first = found.next()?;
+        found.all(|(dep, _)| dep == first.0).then_some(first)
+    }
+}
+
+/// A clause with its spec function calls inlined
+pub struct ExpandedSpec {
+    pub expr: Expr,
+    /// Calls replaced by definitions, nested ones included
+    pub inlined_calls: usize,
+}
+
+/// Inlines spec function calls into the clauses of one function
+pub struct SpecInliner<'a> {
+    table: &'a SpecFnTable,
+    /// Deps of the function whose clauses are expanded
+    deps: &'a [String],
+    max_depth: usize,
+}
+
+impl<'a> SpecInliner<'a> {
+    pub fn new(table: &'a SpecFnTable, deps: &'a [String], max_depth: usize) -> Self {
+        Self {
+            table,
+            deps,
+            max_depth,
+        }
+    }
+
+    /// `expr` with the spec function calls inlined
+    pub fn expand(&self, expr: &Expr) -> ExpandedSpec {
+        let mut expander = Expander {
+            table: self.table,
+            root_deps: self.deps,
+            stack: Vec::new(),
+            max_depth: self.max_depth,
+            inlined_calls: 0,
+        };
+        let mut expr = expr.clone();
+        expander.visit_expr_mut(&mut expr);
+        ExpandedSpec {
+            expr,
+            inlined_calls: expander.inlined_calls,
+        }
+    }
+
+    /// Halstead metrics of the expanded clause and the number of inlined
+    /// calls
+    pub fn analyze(&self, expr: &Expr) -> (SpecHalsteadMetrics, usize) {
+        let expanded = self.expand(expr);
+        (analyze_expr(&expanded.expr), expanded.inlined_calls)
+    }
+}
+
+struct Expander<'a> {
+    table: &'a SpecFnTable,
+    root_deps: &'a [String],
+    /// Spec functions being expanded, outermost first
+    stack: Vec<&'a str>,
+    max_depth: usize,
+    inlined_calls: usize,
+}
+
+impl<'a> Expander<'a> {
+    /// Definition and arguments (receiver first) of an inlinable call
+    fn inlinable(&self, expr: &Expr) -> Option<(&'a str, &'a SpecFnDef, Vec<Expr>)> {
+        if self.stack.len() >= self.max_depth {
+            return None;
+        }
+        let table = self.table;
+        let deps = match self.stack.last() {
+            Some(caller) => &table.fns[*caller].deps[..],
+            None => self.root_deps,
+        };
+        let (name, args): (String, Vec<Expr>) = match expr {
+            Expr::Call(call) => match &*call.func {
+                Expr::Path(path) => (
+                    path.path.segments.last()?.ident.to_string(),
+                    call.args.iter().cloned().collect(),
+                ),
+                _ => return None,
+            },
+            Expr::MethodCall(call) => (
+                call.method.to_string(),
+                std::iter::once((*call.receiver).clone())
+                    .chain(call.args.iter().cloned())
+                    .collect(),
+            ),
+            _ => return None,
+        };
+        let (identifier, def) = table.resolve(deps, &name)?;
+        let arity_matches = def.params.len() == args.len()
+            && matches!(expr, Expr::MethodCall(_))
+                == (def.params.first().map(String::as_str) == Some("self"));
+        (arity_matches && !self.stack.contains(&identifier)).then_some((identifier, def, args))
+    }
+}
+
+impl VisitMut for Expander<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        // Arguments first, so they are substituted expanded
+        visit_mut::visit_expr_mut(self, expr);
+        let Some((identifier, def, args)) = self.inlinable(expr) else {
+            return;
+        };
+
+        let mut body = def.body.clone();
+        self.stack.push(identifier);
+        self.visit_expr_mut(&mut body);
+        self.stack.pop();
+        Substitution(def.params.iter().map(String::as_str).zip(args).collect())
+            .visit_expr_mut(&mut body);
+
+        self.inlined_calls += 1;
+        *expr = parenthesized(body);
+    }
+}
+
+fn parenthesized(expr: Expr) -> Expr {
+    Expr::Paren(ExprParen {
+        attrs: Vec::new(),
+        paren_token: Default::default(),
+        expr: Box::new(expr),
+    })
+}
+
+/// Replaces parameter names by the (parenthesized) arguments
+struct Substitution<'a>(HashMap<&'a str, Expr>);
+
+impl VisitMut for Substitution<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Path(path) = expr {
+            if path.qself.is_none() && path.path.segments.len() == 1 {
+                let name = path.path.segments[0].ident.to_string();
+                if let Some(arg) = self.0.get(name.as_str()) {
+                    *expr = parenthesized(arg.clone());
+                    return;
+                }
+            }
+        }
+        visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(identifier: &str, body: &str, deps: &[&str]) -> Atom {
+        Atom {
+            identifier: identifier.to_string(),
+            statement_type: "function".to_string(),
+            deps: deps.iter().map(|dep| dep.to_string()).collect(),
+            body: body.to_string(),
+            display_name: identifier.to_string(),
+            full_path: "/p/src/m.rs".to_string(),
+            relative_path: "src/m.rs".to_string(),
+            file_name: "m.rs".to_string(),
+            parent_folder: "src".to_string(),
+            doc: None,
+            attributes: Vec::new(),
+            start_line: None,
+            end_line: None,
+            requires_text: Vec::new(),
+            ensures_text: Vec::new(),
+            signature: None,
+            graph_metrics: None,
+            spec_support: Vec::new(),
+        }
+    }
+
+    fn expand(table: &SpecFnTable, deps: &[&str], clause: &str, depth: usize) -> (String, usize) {
+        let deps: Vec<String> = deps.iter().map(|dep| dep.to_string()).collect();
+        let expr: Expr = verus_syn::parse_str(clause).unwrap();
+        let expanded = SpecInliner::new(table, &deps, depth).expand(&expr);
+        let text = quote::ToTokens::to_token_stream(&expanded.expr).to_string();
+        (text, expanded.inlined_calls)
+    }
+
+    #[test]
+    fn test_spec_inliner() {
+        let table = SpecFnTable::from_atoms(&[
+            atom("m::double", "spec fn double(x: int) -> int { x + x }", &[]),
+            atom(
+                "m::quad",
+                "spec fn quad(y: int) -> int { double(double(y)) }",
+                &["m::double"],
+            ),
+            atom(
+                "m::count",
+                "spec fn count(n: nat) -> nat decreases n { if n == 0 { 0 } else { count((n - 1) as nat) } }",
+                &["m::count"],
+            ),
+            atom("m::S::valid", "spec fn valid(&self) -> bool { self.len > 0 }", &[]),
+            atom("m::lemma", "proof fn lemma(x: int) ensures x == x {}", &[]),
+        ]);
+        assert_eq!(table.len(), 4);
+
+        let (text, inlined) = expand(&table, &["m::quad"], "r == quad(a + 1)", 3);
+        assert_eq!(inlined, 3);
+        assert!(!text.contains("quad") && !text.contains("double"));
+        assert_eq!(text.matches("a + 1").count(), 4);
+        // Depth 1 inlines the outer call only
+        assert_eq!(expand(&table, &["m::quad"], "quad(a)", 1).1, 1);
+        // Recursive definitions are expanded once
+        assert_eq!(expand(&table, &["m::count"], "count(k) > 0", 3).1, 1);
+        let (text, inlined) = expand(&table, &["m::S::valid"], "s.valid()", 3);
+        assert_eq!(inlined, 1);
+        assert!(text.contains("len") && !text.contains("self"));
+        // Calls resolve through the caller's deps only
+        assert_eq!(expand(&table, &[], "double(a)", 3).1, 0);
+
+        let expr: Expr = verus_syn::parse_str("quad(a) > 0").unwrap();
+        let deps = vec!["m::quad".to_string()];
+        let (expanded, _) = SpecInliner::new(&table, &deps, 3).analyze(&expr);
+        assert!(expanded.halstead_length > analyze_expr(&expr).halstead_length);
+    }
+}
@@ -0,0 +1,191 @@
+//! Proof-to-spec linkage: which called lemmas relate to each `ensures` clause
+//!
+//! A called proof function supports an `ensures` clause of an exec function
+//! when its own postconditions mention some of the same spec terms: spec
+//! function calls, method names, field names and named constants. Plain
+//! local names are left out, since a lemma's parameters rarely share the
+//! caller's names. The link is syntactic, so it suggests rather than proves
+//! which lemma discharges a clause; clauses with no supporting lemma are the
+//! ones worth a closer look.
+
+use crate::signature::{parse_function, text_of};
+use scip_core::{Atom, EnsuresSupport};
+use std::collections::{HashMap, HashSet};
+use verus_syn::visit::{self, Visit};
+use verus_syn::{Expr, ExprCall, ExprField, ExprMethodCall, ExprPath, FnMode, Member};
+
+/// Spec terms mentioned in an expression
+#[derive(Default)]
+struct SpecTerms(HashSet<String>);
+
+impl<'ast> Visit<'ast> for SpecTerms {
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        self.0.insert(call.method.to_string());
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_field(&mut self, field: &'ast ExprField) {
+        if let Member::Named(name) = &field.member {
+            self.0.insert(name.to_string());
+        }
+        visit::visit_expr_field(self, field);
+    }
+
+    fn visit_expr_path(&mut self, path: &'ast ExprPath) {
+        // Single lowercase names are local variables, unless called (see
+        // `visit_expr_call`)
+        let segments = &path.path.segments;
+        if let Some(last) = segments.last() {
+            let name = last.ident.to_string();
+            if segments.len() > 1 || name.starts_with(char::is_uppercase) {
+                self.0.insert(name);
+            }
+        }
+        visit::visit_expr_path(self, path);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(path) = &*call.func {
+            if let Some(last) = path.path.segments.last() {
+                self.0.insert(last.ident.to_string());
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+fn spec_terms(expr: &Expr) -> HashSet<String> {
+    let mut terms = SpecTerms::default();
+    terms.visit_expr(expr);
+    terms.0
+}
+
+/// Mode and `ensures` clauses (text and spec terms) of a function's source
+fn parse_ensures(source: &str) -> Option<(FnMode, Vec<(String, HashSet<String>)>)> {
+    let (_, sig) = parse_function(source).ok()?;
+    let clauses = sig
+        .spec
+        .ensures
+        .iter()
+        .flat_map(|ensures| ensures.exprs.exprs.iter())
+        .map(|expr| (text_of(source, expr), spec_terms(expr)))
+        .collect();
+    Some((sig.mode, clauses))
+}
+
+/// Fill in `spec_support` of every exec function with `ensures` clauses;
+/// returns how many clauses have no supporting lemma
+pub fn add_spec_support(atoms: &mut [Atom]) -> usize {
+    let parsed: Vec<_> = atoms.iter().map(|atom| parse_ensures(&atom.body)).collect();
+
+    // Postcondition terms of each proof function
+    let mut lemma_terms: HashMap<String, HashSet<String>> = HashMap::new();
+    for (atom, parsed) in atoms.iter().zip(&parsed) {
+        if let Some((FnMode::Proof(_) | FnMode::ProofAxiom(_), clauses)) = parsed {
+            let terms = clauses
+                .iter()
+                .flat_map(|(_, terms)| terms.iter().cloned())
+                .collect();
+            lemma_terms.insert(atom.identifier.clone(), terms);
+        }
+    }
+
+    let mut unsupported = 0;
+    for (atom, parsed) in atoms.iter_mut().zip(parsed) {
+        let Some((FnMode::Default | FnMode::Exec(_), clauses)) = parsed else {
+            continue;
+        };
+
+        let mut lemmas_called: Vec<&String> = atom
+            .deps
+            .iter()
+            .filter(|dep| lemma_terms.contains_key(dep.as_str()))
+            .collect();
+        lemmas_called.sort();
+        lemmas_called.dedup();
+
+        let support: Vec<EnsuresSupport> = clauses
+            .into_iter()
+            .map(|(clause, terms)| EnsuresSupport {
+                clause,
+                lemmas: lemmas_called
+                    .iter()
+                    .filter(|lemma| !lemma_terms[lemma.as_str()].is_disjoint(&terms))
+                    .map(|lemma| lemma.to_string())
+                    .collect(),
+            })
+            .collect();
+        unsupported += support.iter().filter(|s| s.lemmas.is_empty()).count();
+        atom.spec_support = support;
+    }
+    unsupported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(identifier: &str, body: &str, deps: &[&str]) -> Atom {
+        Atom {
+            identifier: identifier.to_string(),
+            statement_type: "function".to_string(),
+            deps: deps.iter().map(|dep| dep.to_string()).collect(),
+            body: body.to_string(),
+            display_name: identifier.to_string(),
+            full_path: "/p/src/m.rs".to_string(),
+            relative_path: "src/m.rs".to_string(),
+            file_name: "m.rs".to_string(),
+            parent_folder: "src".to_string(),
+            doc: None,
+            attributes: Vec::new(),
+            start_line: None,
+            end_line: None,
+            requires_text: Vec::new(),
+            ensures_text: Vec::new(),
+            signature: None,
+            graph_metrics: None,
+            spec_support: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_add_spec_support() {
+        let mut atoms = vec![
+            atom(
+                "m::add",
+                "fn add(a: u64, b: u64) -> (r: u64)\n    ensures\n        r == spec_add(a, b),\n        r > 0,\n{\n    proof { lemma_add(a, b); lemma_len(); }\n    a + b\n}",
+                &["m::lemma_add", "m::lemma_len", "m::spec_add"],
+            ),
+            atom(
+                "m::lemma_add",
+                "proof fn lemma_add(x: u64, y: u64)\n    ensures spec_add(x, y) == x + y,\n{}",
+                &["m::spec_add"],
+            ),
+            atom(
+                "m::lemma_len",
+                "proof fn lemma_len()\n    ensures s.len() == 0,\n{}",
+                &[],
+            ),
+            atom("m::spec_add", "spec fn spec_add(a: u64, b: u64) -> u64 { a + b }", &[]),
+        ];
+
+        let unsupported = add_spec_support(&mut atoms);
+
+        assert_eq!(unsupported, 1);
+        assert_eq!(
+            atoms[0].spec_support,
+            vec![
+                EnsuresSupport {
+                    clause: "r == spec_add(a, b)".to_string(),
+                    lemmas: vec!["m::lemma_add".to_string()],
+                },
+                EnsuresSupport {
+                    clause: "r > 0".to_string(),
+                    lemmas: vec![],
+                },
+            ]
+        );
+        // Only exec functions are annotated
+        assert!(atoms[1].spec_support.is_empty());
+    }
+}
@@ -0,0 +1,432 @@
+//! Lints of quantifier triggers in specs
+//!
+//! Quantifiers are instantiated by the SMT solver whenever a term matching
+//! one of their triggers shows up, so badly chosen triggers make
+//! verification slow or flaky. [`lint_triggers`] flags the usual suspects:
+//!
+//! - `missing_trigger`: a `forall`/`exists` with neither a `#[trigger]` nor
+//!   a `#![trigger ...]`, leaving the choice to Verus (`#![auto]` accepts
+//!   its choice explicitly and is not flagged);
+//! - `arithmetic_trigger`: a trigger term containing `+`, `-`, `*`, `/` or
+//!   `%`, which Z3 treats as interpreted and rarely matches;
+//! - `quantifier_alternation`: `forall`/`exists` alternating more than
+//!   [`MAX_QUANTIFIER_ALTERNATION`] levels deep;
+//! - `matching_loop`: the body contains the trigger's function applied to
+//!   a bigger term of the bound variables (`#[trigger] f(i)` next to
+//!   `f(i + 1)`), so each instantiation can produce a new match.
+//!
+//! `#[trigger]` is recognized on calls, method calls, indexing and field
+//! accesses, the terms Verus accepts as triggers. Lines are 1-based and
+//! relative to the start of the source text, as for
+//! [`crate::find_escape_hatches`].
+
+use proc_macro2::{Delimiter, TokenTree};
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use verus_syn::spanned::Spanned;
+use verus_syn::visit::{self, Visit};
+use verus_syn::{
+    AttrStyle, Attribute, BinOp, Expr, ExprCall, ExprClosure, ExprField, ExprIndex, ExprMethodCall,
+    ExprPath, ExprUnary, ImplItem, Item, ItemFn, Pat, TraitItem, UnOp,
+};
+
+/// Deepest `forall`/`exists` alternation not flagged
+pub const MAX_QUANTIFIER_ALTERNATION: usize = 2;
+
+/// Which lint fired
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerLintKind {
+    MissingTrigger,
+    ArithmeticTrigger,
+    QuantifierAlternation,
+    MatchingLoop,
+}
+
+impl TriggerLintKind {
+    pub const ALL: [TriggerLintKind; 4] = [
+        TriggerLintKind::MissingTrigger,
+        TriggerLintKind::ArithmeticTrigger,
+        TriggerLintKind::QuantifierAlternation,
+        TriggerLintKind::MatchingLoop,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TriggerLintKind::MissingTrigger => "missing_trigger",
+            TriggerLintKind::ArithmeticTrigger => "arithmetic_trigger",
+            TriggerLintKind::QuantifierAlternation => "quantifier_alternation",
+            TriggerLintKind::MatchingLoop => "matching_loop",
+        }
+    }
+
+    /// One-line description of what the lint flags
+    pub fn description(&self) -> &'static str {
+        match self {
+            TriggerLintKind::MissingTrigger => "Quantifier without an explicit trigger",
+            TriggerLintKind::ArithmeticTrigger => "Trigger term containing arithmetic",
+            TriggerLintKind::QuantifierAlternation => {
+                "forall/exists alternation nested more than two levels deep"
+            }
+            TriggerLintKind::MatchingLoop => {
+                "Trigger matching a bigger term of the quantifier's own body (matching loop)"
+            }
+        }
+    }
+}
+
+/// One finding and the line it is on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriggerLint {
+    pub kind: TriggerLintKind,
+    /// 1-based line within the linted source
+    pub line: usize,
+    pub message: String,
+}
+
+fn line_of(node: &impl Spanned) -> usize {
+    node.span().start().line.max(1)
+}
+
+fn text_of(node: &impl ToTokens) -> String {
+    node.to_token_stream().to_string()
+}
+
+fn has_trigger_attribute(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("trigger"))
+}
+
+/// Terms of a `#![trigger f(x), g(x)]` attribute
+fn inner_trigger_terms(attr: &Attribute) -> Vec<Expr> {
+    let tokens: Vec<TokenTree> = attr.meta.to_token_stream().into_iter().skip(1).collect();
+    let tokens: Vec<TokenTree> = match &tokens[..] {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Parenthesis => {
+            group.stream().into_iter().collect()
+        }
+        _ => tokens,
+    };
+    tokens
+        .split(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ','))
+        .filter(|term| !term.is_empty())
+        .filter_map(|term| verus_syn::parse2::<Expr>(term.iter().cloned().collect()).ok())
+        .collect()
+}
+
+fn is_quantifier(unary: &ExprUnary) -> bool {
+    matches!(unary.op, UnOp::Forall(_) | UnOp::Exists(_))
+}
+
+/// Triggers of one quantifier, leaving out those of nested quantifiers
+#[derive(Default)]
+struct TriggerCollector {
+    terms: Vec<Expr>,
+    auto: bool,
+}
+
+impl TriggerCollector {
+    fn record(&mut self, attrs: &[Attribute], term: impl FnOnce() -> Expr) {
+        if has_trigger_attribute(attrs) {
+            self.terms.push(term());
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for TriggerCollector {
+    fn visit_expr_unary(&mut self, unary: &'ast ExprUnary) {
+        if !is_quantifier(unary) {
+            visit::visit_expr_unary(self, unary);
+        }
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        if attr.path().is_ident("auto") {
+            self.auto = true;
+        } else if attr.path().is_ident("trigger") && matches!(attr.style, AttrStyle::Inner(_)) {
+            self.terms.extend(inner_trigger_terms(attr));
+        }
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        self.record(&call.attrs, || Expr::Call(call.clone()));
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        self.record(&call.attrs, || Expr::MethodCall(call.clone()));
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_index(&mut self, index: &'ast ExprIndex) {
+        self.record(&index.attrs, || Expr::Index(index.clone()));
+        visit::visit_expr_index(self, index);
+    }
+
+    fn visit_expr_field(&mut self, field: &'ast ExprField) {
+        self.record(&field.attrs, || Expr::Field(field.clone()));
+        visit::visit_expr_field(self, field);
+    }
+}
+
+/// Whether an expression uses `+`, `-`, `*`, `/` or `%`
+#[derive(Default)]
+struct ArithmeticFinder(bool);
+
+impl<'ast> Visit<'ast> for ArithmeticFinder {
+    fn visit_expr_binary(&mut self, binary: &'ast verus_syn::ExprBinary) {
+        if matches!(
+            binary.op,
+            BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_)
+        ) {
+            self.0 = true;
+        }
+        visit::visit_expr_binary(self, binary);
+    }
+}
+
+fn has_arithmetic(expr: &Expr) -> bool {
+    let mut finder = ArithmeticFinder::default();
+    finder.visit_expr(expr);
+    finder.0
+}
+
+/// Whether an expression mentions one of the variables
+struct VariableFinder<'a> {
+    variables: &'a [String],
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for VariableFinder<'_> {
+    fn visit_expr_path(&mut self, path: &'ast ExprPath) {
+        if path.path.segments.len() == 1 {
+            let name = path.path.segments[0].ident.to_string();
+            self.found |= self.variables.contains(&name);
+        }
+        visit::visit_expr_path(self, path);
+    }
+}
+
+fn mentions(expr: &Expr, variables: &[String]) -> bool {
+    let mut finder = VariableFinder {
+        variables,
+        found: false,
+    };
+    finder.visit_expr(expr);
+    finder.found
+}
+
+/// Function (or indexed collection) a trigger-like term applies, and its
+/// arguments
+fn term_head(expr: &Expr) -> Option<(String, Vec<&Expr>)> {
+    match expr {
+        Expr::Call(call) => Some((text_of(&call.func), call.args.iter().collect())),
+        Expr::MethodCall(call) => Some((format!(".{}", call.method), call.args.iter().collect())),
+        Expr::Index(index) => Some((format!("{}[]", text_of(&index.expr)), vec![&*index.index])),
+        _ => None,
+    }
+}
+
+/// Calls, method calls and indexing in an expression
+#[derive(Default)]
+struct TermCollector<'ast>(Vec<&'ast Expr>);
+
+impl<'ast> Visit<'ast> for TermCollector<'ast> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if matches!(expr, Expr::Call(_) | Expr::MethodCall(_) | Expr::Index(_)) {
+            self.0.push(expr);
+        }
+        visit::visit_expr(self, expr);
+    }
+}
+
+fn bound_variables(closure: &ExprClosure) -> Vec<String> {
+    closure
+        .inputs
+        .iter()
+        .filter_map(|input| {
+            let pat = match input {
+                Pat::Type(typed) => &*typed.pat,
+                pat => pat,
+            };
+            match pat {
+                Pat::Ident(ident) => Some(ident.ident.to_string()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct TriggerLintVisitor {
+    /// Enclosing quantifiers (`forall`/`exists`) and their alternation depth
+    quantifiers: Vec<(&'static str, usize)>,
+    lints: Vec<TriggerLint>,
+}
+
+impl TriggerLintVisitor {
+    fn push(&mut self, kind: TriggerLintKind, line: usize, message: String) {
+        self.lints.push(TriggerLint {
+            kind,
+            line,
+            message,
+        });
+    }
+
+    fn check_triggers(
+        &mut self,
+        quantifier: &'static str,
+        unary: &ExprUnary,
+        closure: &ExprClosure,
+    ) {
+        let mut triggers = TriggerCollector::default();
+        triggers.visit_expr_closure(closure);
+        if triggers.terms.is_empty() {
+            if !triggers.auto {
+                self.push(
+                    TriggerLintKind::MissingTrigger,
+                    line_of(unary),
+                    format!("`{quantifier}` without an explicit trigger; Verus picks one"),
+                );
+            }
+            return;
+        }
+
+        let variables = bound_variables(closure);
+        let mut body_terms = TermCollector::default();
+        body_terms.visit_expr(&closure.body);
+        for term in &triggers.terms {
+            let text = text_of(term);
+            if has_arithmetic(term) {
+                self.push(
+                    TriggerLintKind::ArithmeticTrigger,
+                    line_of(term),
+                    format!("trigger `{text}` contains arithmetic"),
+                );
+            }
+
+            let Some((head, _)) = term_head(term) else {
+                continue;
+            };
+            // A bigger term of the bound variables under the same head
+            let looping = body_terms.0.iter().find(|other| {
+                term_head(other).is_some_and(|(other_head, args)| {
+                    other_head == head
+                        && text_of(*other) != text
+                        && args
+                            .iter()
+                            .any(|arg| !matches!(arg, Expr::Path(_)) && mentions(arg, &variables))
+                })
+            });
+            if let Some(other) = looping {
+                self.push(
+                    TriggerLintKind::MatchingLoop,
+                    line_of(term),
+                    format!(
+                        "trigger `{text}` matches `{}` of its own body, which may instantiate \
+                         the quantifier again",
+                        text_of(*other)
+                    ),
+                );
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for TriggerLintVisitor {
+    fn visit_expr_unary(&mut self, unary: &'ast ExprUnary) {
+        let quantifier = match unary.op {
+            UnOp::Forall(_) => "forall",
+            UnOp::Exists(_) => "exists",
+            _ => return visit::visit_expr_unary(self, unary),
+        };
+        let Expr::Closure(closure) = &*unary.expr else {
+            return visit::visit_expr_unary(self, unary);
+        };
+
+        let outer_depth = self.quantifiers.last().map_or(0, |(_, depth)| *depth);
+        let depth = match self.quantifiers.last() {
+            Some((outer, depth)) if *outer == quantifier => *depth,
+            _ => outer_depth + 1,
+        };
+        if depth > MAX_QUANTIFIER_ALTERNATION && outer_depth <= MAX_QUANTIFIER_ALTERNATION {
+            let mut chain: Vec<&str> = self.quantifiers.iter().map(|(q, _)| *q).collect();
+            chain.push(quantifier);
+            chain.dedup();
+            self.push(
+                TriggerLintKind::QuantifierAlternation,
+                line_of(unary),
+                format!(
+                    "quantifiers alternate {} levels deep ({})",
+                    depth,
+                    chain.join("/")
+                ),
+            );
+        }
+
+        self.check_triggers(quantifier, unary, closure);
+        self.quantifiers.push((quantifier, depth));
+        visit::visit_expr_unary(self, unary);
+        self.quantifiers.pop();
+    }
+}
+
+/// Lint the quantifiers of a function's source text (or of a single spec
+/// expression), sorted by line
+pub fn lint_triggers(source: &str) -> Result<Vec<TriggerLint>, String> {
+    let mut visitor = TriggerLintVisitor::default();
+    if let Ok(item_fn) = verus_syn::parse_str::<ItemFn>(source) {
+        visitor.visit_item_fn(&item_fn);
+    } else if let Ok(item) = verus_syn::parse_str::<Item>(source) {
+        visitor.visit_item(&item);
+    } else if let Ok(item) = verus_syn::parse_str::<ImplItem>(source) {
+        visitor.visit_impl_item(&item);
+    } else if let Ok(item) = verus_syn::parse_str::<TraitItem>(source) {
+        visitor.visit_trait_item(&item);
+    } else {
+        let expr: Expr = verus_syn::parse_str(source)
+            .map_err(|e| format!("Failed to parse function or spec: {}", e))?;
+        visitor.visit_expr(&expr);
+    }
+    visitor.lints.sort_by_key(|lint| lint.line);
+    Ok(visitor.lints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TriggerLintKind> {
+        lint_triggers(source)
+            .unwrap()
+            .into_iter()
+            .map(|lint| lint.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_triggers() {
+        assert!(kinds("forall|i: int| 0 <= i < n ==> #[trigger] s[i] > 0").is_empty());
+        assert!(kinds("forall|i: int| #![trigger s[i]] 0 <= i < n ==> s[i] > 0").is_empty());
+        assert!(kinds("forall|i: int| #![auto] 0 <= i < n ==> s[i] > 0").is_empty());
+        assert_eq!(
+            kinds("exists|i: int| 0 <= i < n && s[i] == x"),
+            [TriggerLintKind::MissingTrigger]
+        );
+        assert_eq!(
+            kinds("forall|i: int| 0 <= i < n ==> #[trigger] s[i + 1] > 0"),
+            [TriggerLintKind::ArithmeticTrigger]
+        );
+        assert_eq!(
+            kinds("forall|i: int| 0 <= i ==> #[trigger] f(i) < f(i + 1)"),
+            [TriggerLintKind::MatchingLoop]
+        );
+        // Two bound variables in the same position are no loop
+        assert!(kinds("forall|i: int, j: int| #[trigger] f(i) <= f(j)").is_empty());
+
+        let source = "proof fn lemma()\n    ensures\n        forall|a: int| #[trigger] p(a) ==>\n            exists|b: int| #[trigger] q(a, b) &&\n                forall|c: int| #[trigger] r(b, c),\n{\n}";
+        let lints = lint_triggers(source).unwrap();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, TriggerLintKind::QuantifierAlternation);
+        assert_eq!(lints[0].line, 5);
+        assert!(lints[0].message.contains("forall/exists/forall"));
+    }
+}
@@ -22,7 +22,9 @@ function createNode(props: {
   relative_path?: string;
   crate_name?: string;
   kind?: string;
+  node_kind?: 'type';
   is_libsignal?: boolean;
+  attributes?: string[];
 }): D3Node {
   return {
     id: props.id,
@@ -37,6 +39,8 @@ function createNode(props: {
     dependencies: [],
     dependents: [],
     kind: props.kind || 'exec',
+    node_kind: props.node_kind,
+    attributes: props.attributes,
   };
 }
 
@@ -60,6 +64,7 @@ function createFilters(overrides: Partial<FilterOptions> = {}): FilterOptions {
     showSpecFunctions: true,
     showRustNodes: true,
     showLeanNodes: true,
+    showTypeNodes: true,
     showVerifiedNodes: true,
     showFailedNodes: true,
     showUnverifiedNodes: true,
@@ -611,6 +616,29 @@ describe('Filter Behavior', () => {
       expect(nodeNames).toContain('proof_fn');
       expect(nodeNames).not.toContain('spec_fn');
     });
+
+    it('hiding type nodes removes them and their references', () => {
+      const graph: D3Graph = {
+        nodes: [
+          createNode({ id: 'main', display_name: 'main' }),
+          createNode({ id: 'helper', display_name: 'helper' }),
+          createNode({ id: 'Point', display_name: 'Point', node_kind: 'type' }),
+        ],
+        links: [
+          createLink('main', 'helper'),
+          createLink('main', 'Point', 'references'),
+        ],
+        metadata: { total_nodes: 3, total_edges: 2, project_root: '/test', generated_at: '2024-01-01' },
+      };
+
+      const shown = applyFilters(graph, createFilters({ sourceQuery: 'main', maxDepth: 1 }));
+      expect(shown.nodes.map(n => n.id)).toContain('Point');
+
+      const filters = createFilters({ sourceQuery: 'main', maxDepth: 1, showTypeNodes: false });
+      const result = applyFilters(graph, filters);
+      expect(result.nodes.map(n => n.id).sort()).toEqual(['helper', 'main']);
+      expect(result.links.every(l => l.type !== 'references')).toBe(true);
+    });
   });
 
   describe('Hidden nodes', () => {
@@ -989,6 +1017,20 @@ describe('Crate-level source/sink queries', () => {
     });
   });
 
+  describe('matchesQuery with attr: prefix', () => {
+    it('matches nodes by attribute', () => {
+      const node = createNode({
+        id: 'x',
+        display_name: 'foo',
+        attributes: ['#[inline]', '#[verifier::external_body]'],
+      });
+      expect(matchesQuery(node, 'attr:external_body')).toBe(true);
+      expect(matchesQuery(node, 'attr:#[inline]')).toBe(true);
+      expect(matchesQuery(node, 'attr:rlimit')).toBe(false);
+      expect(matchesQuery(createNode({ id: 'y', display_name: 'bar' }), 'attr:inline')).toBe(false);
+    });
+  });
+
   describe('direct crate boundary mode', () => {
     it('shows only cross-crate edges from source to sink crate', () => {
       const graph = createTwoCrateGraph();
@@ -72,7 +72,7 @@ export interface SelectedNodeOptions {
 /**
  * Check if a node matches a search query
  * 
- * Supports two modes:
+ * Supports three modes:
  * - Substring match (default): "foo" matches anything containing "foo" in display_name.
  *   Glob wildcards (* and ?) opt into anchored matching instead (e.g. "p_*" matches
  *   only names starting with "p_").
@@ -81,6 +81,8 @@ export interface SelectedNodeOptions {
  *   Examples:
  *   - "edwards::decompress" -> decompress in edwards.rs
  *   - "ristretto::*compress*" -> explicit glob in path-qualified mode
+ * - Attribute match: "attr:external_body" matches functions with an attribute
+ *   containing "external_body" (e.g. #[verifier::external_body]).
  *
  * @public Exported for testing
  */
@@ -91,6 +93,11 @@ export function matchesQuery(node: D3Node, query: string): boolean {
     return crateRegex.test(node.crate_name);
   }
 
+  if (query.startsWith('attr:')) {
+    const attrRegex = globToRegex(asSubstringGlob(query.slice(5)));
+    return (node.attributes ?? []).some(attr => attrRegex.test(attr));
+  }
+
   const doubleColonIndex = query.indexOf('::');
   if (doubleColonIndex > 0 && doubleColonIndex < query.length - 2) {
     const pathPart = query.slice(0, doubleColonIndex);
@@ -220,6 +220,17 @@ function assignTopologicalPositions(
   return { depths, maxDepth, maxLayerWidth, nodesByDepth };
 }
 
+/**
+ * Tooltip text of a link: the call it stands for and the lines of its call sites.
+ */
+function linkTitle(link: D3Link): string {
+  const name = (end: string | D3Node) => (typeof end === 'string' ? end : end.display_name);
+  const title = `${name(link.source)} → ${name(link.target)}`;
+  if (!link.lines || link.lines.length === 0) return title;
+  const label = link.lines.length === 1 ? 'line' : 'lines';
+  return `${title}\nCalled on ${label} ${link.lines.join(', ')}`;
+}
+
 export class CallGraphVisualization {
   private svg: d3.Selection<SVGSVGElement, unknown, null, undefined>;
   private g: d3.Selection<SVGGElement, unknown, null, undefined>;
@@ -387,23 +398,29 @@ export class CallGraphVisualization {
       .attr('fill', 'none')
       .attr('stroke', (d) => {
         const linkType = d.type || 'inner';
+        if (d.edge_kind === 'spawn') return '#2e7d32';
         if (linkType === 'precondition') return '#e65100';
         if (linkType === 'postcondition') return '#c2185b';
         if (linkType === 'mapping') return '#7c3aed';
         if (linkType === 'spec') return '#0891b2';
+        if (linkType === 'references') return '#4682b4';
         return '#999';
       })
       .attr('stroke-opacity', 0.6)
-      .attr('stroke-width', 1.5)
+      .attr('stroke-width', (d) => (d.weight ? 1.5 + Math.log2(d.weight) : 1.5))
       .attr('stroke-dasharray', (d) => {
         const linkType = d.type || 'inner';
+        if (d.edge_kind === 'spawn') return '8,2,2,2';
         if (linkType === 'precondition' || linkType === 'postcondition') return '5,3';
         if (linkType === 'mapping') return '2,4';
         if (linkType === 'spec') return '3,3';
+        if (linkType === 'references') return '1,3';
         return 'none';
       })
       .attr('marker-end', 'url(#arrowhead)');
 
+    linkEnter.append('title').text(linkTitle);
+
     this.linkElements = linkEnter.merge(this.linkElements);
 
     // Update nodes
@@ -482,8 +499,10 @@ export class CallGraphVisualization {
    * - failed: red (#ef4444)
    * - unverified: grey (#9ca3af)
    * - unknown (no status): blue (#3b82f6)
+   * Type nodes (export --include-types) are cyan (#22d3ee).
    */
   private getNodeColor(node: D3Node): string {
+    if (node.node_kind === 'type') return '#22d3ee';
     switch (node.verification_status) {
       case 'verified':
         return '#22c55e';  // Green
@@ -110,6 +110,32 @@ let deferredGraphUrl: string | null = null;
 // Prevent multiple simultaneous deferred graph loads
 let isDeferredLoadInProgress = false;
 
+// URL of the loaded graph, to find its bodies file (metadata.bodies_file)
+let graphUrl: string | null = null;
+// Function bodies by node id, fetched on first use
+let bodiesPromise: Promise<Record<string, string>> | null = null;
+
+function setGraphUrl(url: string | null): void {
+  graphUrl = url;
+  bodiesPromise = null;
+}
+
+/**
+ * Load the function bodies (or signatures) the export wrote next to the graph.
+ * Resolves to an empty map for graphs without a bodies file, or loaded from disk.
+ */
+function loadBodies(): Promise<Record<string, string>> {
+  const file = state.fullGraph?.metadata.bodies_file;
+  if (!file || !graphUrl) return Promise.resolve({});
+  if (!bodiesPromise) {
+    const url = new URL(file, new URL(graphUrl, window.location.href)).toString();
+    bodiesPromise = fetch(url)
+      .then(response => (response.ok ? response.json() : {}))
+      .catch(() => ({}));
+  }
+  return bodiesPromise;
+}
+
 // Focus set URL (from ?focus= URL parameter)
 let focusJsonUrl: string | null = null;
 
@@ -190,6 +216,8 @@ function appendLineFragment(link: string, start?: number, end?: number): string
  * falling back to the global githubBaseUrl / githubBranch / githubPathPrefix.
  */
 function buildGitHubLink(node: D3Node): string | null {
+  // Links set by the exporter (--github-url/--rev) point at a fixed revision
+  if (node.source_url) return node.source_url;
   if (!node.relative_path) return null;
 
   // Try per-language source config (from Schema 2.0 envelope metadata)
@@ -257,6 +285,8 @@ function parseFiltersFromURL(): Partial<FilterOptions> {
   if (post !== undefined) filters.showPostconditionCalls = post;
   if (mapping !== undefined) filters.showMappingLinks = mapping;
   if (specLinks !== undefined) filters.showSpecLinks = specLinks;
+  const types = parseBool('types');
+  if (types !== undefined) filters.showTypeNodes = types;
   if (libsignal !== undefined) filters.showLibsignal = libsignal;
   if (external !== undefined) filters.showNonLibsignal = external;
 
@@ -335,6 +365,7 @@ function generateShareableURL(): string {
     if (state.filters.showPostconditionCalls) params.set('post', '1');
     if (!state.filters.showMappingLinks) params.set('mapping', '0');
     if (!state.filters.showSpecLinks) params.set('speclinks', '0');
+    if (!state.filters.showTypeNodes) params.set('types', '0');
   }
   if (!state.filters.showLibsignal) params.set('libsignal', '0');
   if (!state.filters.showNonLibsignal) params.set('external', '0');
@@ -445,6 +476,7 @@ const initialFilters: FilterOptions = {
   showSpecFunctions: false,       // Hide spec functions by default
   showRustNodes: true,            // Show Rust/Verus nodes by default
   showLeanNodes: true,            // Show Lean nodes by default
+  showTypeNodes: true,            // Show type nodes (export --include-types) by default
   showVerifiedNodes: true,        // Show verified nodes by default
   showFailedNodes: true,          // Show failed nodes by default
   showUnverifiedNodes: true,      // Show unverified/unknown nodes by default
@@ -1136,6 +1168,7 @@ async function autoLoadGraph(): Promise<void> {
       const graph = parseAndNormalizeGraph(rawData);
       
       const source = jsonUrlParam ? 'URL parameter' : 'configured default';
+      setGraphUrl(jsonUrl);
       loadGraph(graph, `Loaded from ${source}: ${jsonUrl}`);
       return;
     } catch (error) {
@@ -1179,6 +1212,7 @@ async function autoLoadGraph(): Promise<void> {
     // Yield again before loadGraph (deep copy + initialization)
     await new Promise(r => setTimeout(r, 0));
 
+    setGraphUrl('./graph.json');
     loadGraph(graph, 'Auto-loaded from local file');
   } catch (error) {
     console.log('Could not auto-load graph.json:', error);
@@ -1227,6 +1261,7 @@ async function loadDeferredGraph(): Promise<void> {
     const rawData = JSON.parse(text);
     const graph = parseAndNormalizeGraph(rawData);
     
+    setGraphUrl(deferredGraphUrl);
     deferredGraphUrl = null; // Clear the deferred URL
     loadGraph(graph, 'Loaded from deferred graph');
   } catch (error) {
@@ -1278,6 +1313,7 @@ async function loadDeferredGraphWithDisambiguation(): Promise<void> {
     const rawData = JSON.parse(text);
     const graph = parseAndNormalizeGraph(rawData);
     
+    setGraphUrl(deferredGraphUrl);
     deferredGraphUrl = null; // Clear the deferred URL
     
     // Load the graph but DON'T apply filters yet
@@ -1647,8 +1683,9 @@ function renderCallTypeFilters(lang: ProjectLanguage): void {
   const isVerus = lang === 'verus' || lang === 'mixed';
   const hasMappingLinks = state.fullGraph?.links.some(l => l.type === 'mapping') ?? false;
   const hasSpecLinks = state.fullGraph?.links.some(l => l.type === 'spec') ?? false;
+  const hasTypeNodes = state.fullGraph?.nodes.some(n => n.node_kind === 'type') ?? false;
 
-  if (!isVerus && !hasMappingLinks && !hasSpecLinks) {
+  if (!isVerus && !hasMappingLinks && !hasSpecLinks && !hasTypeNodes) {
     container.style.display = 'none';
     state.filters.showInnerCalls = true;
     state.filters.showPreconditionCalls = false;
@@ -1690,6 +1727,13 @@ function renderCallTypeFilters(lang: ProjectLanguage): void {
       <span class="spec-link-badge">Specifications</span>
     </label>`;
   }
+  if (hasTypeNodes) {
+    html += `
+    <label class="checkbox-label">
+      <input type="checkbox" id="show-type-nodes" checked />
+      <span class="type-badge">Type References</span>
+    </label>`;
+  }
 
   if (isVerus && !hasMappingLinks && !hasSpecLinks) {
     html += `
@@ -1720,6 +1764,10 @@ function renderCallTypeFilters(lang: ProjectLanguage): void {
     state.filters.showSpecLinks = (e.target as HTMLInputElement).checked;
     applyFiltersAndUpdate();
   });
+  document.getElementById('show-type-nodes')?.addEventListener('change', (e) => {
+    state.filters.showTypeNodes = (e.target as HTMLInputElement).checked;
+    applyFiltersAndUpdate();
+  });
 
   const setCheckbox = (id: string, checked: boolean) => {
     const el = document.getElementById(id) as HTMLInputElement | null;
@@ -1730,6 +1778,7 @@ function renderCallTypeFilters(lang: ProjectLanguage): void {
   setCheckbox('show-postcondition-calls', state.filters.showPostconditionCalls);
   setCheckbox('show-mapping-links', state.filters.showMappingLinks);
   setCheckbox('show-spec-links', state.filters.showSpecLinks);
+  setCheckbox('show-type-nodes', state.filters.showTypeNodes);
 }
 
 /**
@@ -1893,6 +1942,7 @@ async function handleFileLoad(event: Event): Promise<void> {
     const rawData = JSON.parse(text);
     const graph = parseAndNormalizeGraph(rawData);
     
+    setGraphUrl(null);
     loadGraph(graph, `Loaded from file: ${file.name}`);
   } catch (error) {
     console.error('Error loading graph:', error);
@@ -2178,6 +2228,66 @@ function updateNodeInfo(): void {
     return '';
   };
 
+  // Verification time (from pipeline --verification-times)
+  const verificationTimeHtml = node.verification_time_ms !== undefined
+    ? `<div class="node-detail">
+        <strong>Verification time:</strong> ${(node.verification_time_ms / 1000).toFixed(2)} s
+        ${node.verification_rlimit !== undefined ? `<span style="color: #888; margin-left: 0.5rem;">(rlimit ${node.verification_rlimit})</span>` : ''}
+      </div>`
+    : '';
+
+  // Escape hatches (from audit_assumptions --annotate-graph)
+  const unsafetyHtml = node.unsafety_flags && node.unsafety_flags.length > 0
+    ? `<div class="node-detail">
+        <strong>Trusted:</strong> <span style="color: #d9534f;">${node.unsafety_flags.join(', ')}</span>
+      </div>`
+    : '';
+
+  // Call graph metrics (from pipeline --graph-metrics)
+  const metrics = node.graph_metrics;
+  const graphMetricsHtml = metrics
+    ? `<div class="node-detail">
+        <strong>Graph:</strong> fan-in ${metrics.fan_in}, fan-out ${metrics.fan_out}, depth ${metrics.max_depth}
+        <span style="color: #888; margin-left: 0.5rem;">(reaches ${metrics.transitive_callees}, betweenness ${metrics.betweenness.toFixed(3)})</span>
+      </div>`
+    : '';
+
+  // Functions of a module node (module-level graphs)
+  const moduleFunctionsHtml = node.functions && node.functions.length > 0
+    ? `<div class="node-detail">
+        <strong>Functions (${node.functions.length}):</strong>
+        <ul class="node-list">${node.functions.map(f => `<li>${escapeHtml(f.display_name)}</li>`).join('')}</ul>
+      </div>`
+    : '';
+
+  // Doc comment and attributes preceding the function
+  const attributesHtml = node.attributes && node.attributes.length > 0
+    ? `<div class="node-detail">
+        <strong>Attributes:</strong> <code>${escapeHtml(node.attributes.join(' '))}</code>
+      </div>`
+    : '';
+  const docHtml = node.doc
+    ? `<div class="node-detail">
+        <strong>Documentation:</strong>
+        <div style="white-space: pre-wrap; color: #555; font-size: 0.85rem;">${escapeHtml(node.doc)}</div>
+      </div>`
+    : '';
+
+  const signatureHtml = node.signature
+    ? `<div class="node-detail">
+        <strong>Signature:</strong> <code>${escapeHtml(node.signature)}</code>
+      </div>`
+    : '';
+
+  // requires/ensures clauses, shown without loading the body
+  const clausesHtml = (label: string, clauses?: string[]) => clauses && clauses.length > 0
+    ? `<div class="node-detail">
+        <strong>${label}:</strong>
+        <ul class="node-list">${clauses.map(c => `<li><code>${escapeHtml(c)}</code></li>`).join('')}</ul>
+      </div>`
+    : '';
+  const specClausesHtml = clausesHtml('Requires', node.requires_text) + clausesHtml('Ensures', node.ensures_text);
+
   // Build Lean Translation section (for Rust nodes with a mapping to Lean)
   let mappingHtml = '';
   if (node.mapping_id && state.fullGraph) {
@@ -2235,9 +2345,19 @@ function updateNodeInfo(): void {
         </div>
         ${getVerificationBadge(node.verification_status)}
         ${getKindBadge(node.kind)}
+        ${node.is_test ? '<div class="node-badge badge-other">Test</div>' : ''}
+        ${node.node_kind === 'type' ? '<div class="node-badge badge-type">Type</div>' : ''}
         ${getLanguageBadge(node.language)}
       </div>
     </div>
+    ${verificationTimeHtml}
+    ${unsafetyHtml}
+    ${graphMetricsHtml}
+    ${moduleFunctionsHtml}
+    ${docHtml}
+    ${attributesHtml}
+    ${signatureHtml}
+    ${specClausesHtml}
     <div class="node-detail">
       <strong>File:</strong> ${node.file_name}
       ${lineInfo ? `<span style="color: #888; margin-left: 0.5rem;">(${lineInfo})</span>` : ''}
@@ -2280,6 +2400,19 @@ function updateNodeInfo(): void {
     ` : ''}
   `;
 
+  // Source from the bodies file, appended once loaded
+  loadBodies().then(bodies => {
+    const body = bodies[node.id];
+    const current = state.selectedNode || state.hoveredNode;
+    if (body && current?.id === node.id) {
+      const section = document.createElement('div');
+      section.className = 'node-detail';
+      section.innerHTML = `<strong>Source:</strong>
+        <code class="code-block" style="font-size: 0.85rem; white-space: pre;">${escapeHtml(body)}</code>`;
+      nodeInfoDiv.appendChild(section);
+    }
+  });
+
   // Add click handler for navigate button
   const navigateBtn = document.getElementById('navigate-to-source-btn');
   if (navigateBtn && node) {
@@ -3109,6 +3242,7 @@ function handleVSCodeMessage(event: MessageEvent): void {
           selectedNodeId = null;
         }
         
+        setGraphUrl(null);
         loadGraph(normalizedGraph, 'Loaded from VS Code extension');
         
         // Apply initial query if provided
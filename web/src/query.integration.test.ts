@@ -38,6 +38,7 @@ function createFilters(overrides: Partial<FilterOptions> = {}): FilterOptions {
     showSpecFunctions: true,
     showRustNodes: true,
     showLeanNodes: true,
+    showTypeNodes: true,
     showVerifiedNodes: true,
     showFailedNodes: true,
     showUnverifiedNodes: true,
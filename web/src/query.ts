@@ -67,6 +67,7 @@ export interface DisplayPredicates {
   showUnverifiedNodes: boolean;
   showRustNodes: boolean;
   showLeanNodes: boolean;
+  showTypeNodes: boolean;
 }
 
 export interface FocusConfig {
@@ -600,6 +601,7 @@ export function compileQuery(
     showUnverifiedNodes: filters.showUnverifiedNodes,
     showRustNodes: filters.showRustNodes ?? true,
     showLeanNodes: filters.showLeanNodes ?? true,
+    showTypeNodes: filters.showTypeNodes ?? true,
   };
 
   const focusConfig: FocusConfig = {
@@ -785,6 +787,12 @@ export function executeQuery(
     });
   }
 
+  // Type nodes (post-traversal, like the language filter); their
+  // `references` links go with them
+  if (!displayPredicates.showTypeNodes) {
+    resultNodes = resultNodes.filter(n => n.node_kind !== 'type');
+  }
+
   // Re-apply kind filter on result nodes (for depthFromSelected, the traversal
   // used the traversableGraph which already has kind-filtered nodes, but we
   // still need kind filtering for the no-traversal case that also post-filters)
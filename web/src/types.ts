@@ -132,8 +132,19 @@ export type BorderStatus = 'verified' | 'ready' | 'blocked' | 'not_ready' | 'unk
 /** Derived fill status (subtree completeness) */
 export type FillStatus = 'fully_verified' | 'verified' | 'ready' | 'none';
 
+/** Position of a function in the call graph */
+export interface GraphMetrics {
+  fan_in: number;
+  fan_out: number;
+  max_depth: number;  // Longest chain of calls below the function
+  transitive_callees: number;
+  betweenness: number;  // 0 to 1
+  pagerank: number;
+}
+
 export interface D3Node {
   id: string;
+  stable_id?: string;  // Hash of crate, file, name and signature; unlike id, unchanged across runs
   display_name: string;
   symbol: string;
   full_path: string;
@@ -144,13 +155,26 @@ export interface D3Node {
   // Note: body removed - use start_line/end_line to fetch code on demand
   start_line?: number;
   end_line?: number;
+  source_url?: string;  // GitHub link to the source, when exported with --github-url/--rev
   is_libsignal: boolean;
   // Pre-computed for O(1) lookups in browser
   dependencies: string[];   // scip_names of functions this calls (outgoing)
   dependents: string[];     // scip_names of functions that call this (incoming)
   similar_lemmas?: SimilarLemma[];
   kind: DeclKind;  // Declaration kind: exec, proof, spec (Verus) or theorem, def, axiom, ... (Lean)
+  node_kind?: 'type';  // Struct, enum, constant or static used by functions (export --include-types); absent for functions
   verification_status?: VerificationStatus;  // Verification status: verified, failed, unverified
+  verification_time_ms?: number;  // SMT time spent verifying this function (pipeline --verification-times)
+  verification_rlimit?: number;  // SMT resource count, when reported by Verus
+  unsafety_flags?: string[];  // Escape hatches in the function: assume, admit, external_body, assume_specification (audit_assumptions --annotate-graph)
+  functions?: { symbol: string; display_name: string }[];  // Functions of a module node (export_call_graph_d3 --modules)
+  graph_metrics?: GraphMetrics;  // Fan-in/fan-out, call depth and centrality (pipeline --graph-metrics)
+  is_test?: boolean;  // Test function: #[test], #[cfg(test)] or in a test file
+  doc?: string;  // Doc comment preceding the function
+  attributes?: string[];  // Attributes preceding the function, e.g. #[verifier::external_body]
+  requires_text?: string[];  // requires clauses as written (whitespace collapsed)
+  ensures_text?: string[];  // ensures clauses as written
+  signature?: string;  // Signature as written (whitespace collapsed), without specs or body
   language?: string;  // Per-atom language: "rust" or "lean"
   // Cross-language links (merged Rust/Lean atoms)
   mapping_id?: string;  // Lean translation probe ID (on Rust nodes)
@@ -171,12 +195,15 @@ export interface D3Node {
 }
 
 /** The type of a call/dependency link */
-export type LinkType = 'inner' | 'precondition' | 'postcondition' | 'mapping' | 'spec';
+export type LinkType = 'inner' | 'precondition' | 'postcondition' | 'mapping' | 'spec' | 'references';
 
 export interface D3Link {
   source: string | D3Node;
   target: string | D3Node;
   type: LinkType | string;  // 'inner' | 'precondition' | 'postcondition' (or legacy 'calls')
+  edge_kind?: string;  // Caller/callee modes (e.g. 'exec_to_proof') or 'spawn' for calls in spawned tasks
+  weight?: number;  // Number of calls the link stands for (module graphs)
+  lines?: number[];  // 1-based lines of the call sites, in the source's file
 }
 
 /** Per-language GitHub source config derived from Schema 2.0 envelope inputs. */
@@ -194,6 +221,8 @@ export interface D3GraphMetadata {
   generated_at: string;
   github_url?: string;
   source_configs?: SourceConfig[];
+  bodies_file?: string;  // File next to the graph with function bodies (or signatures) by node id
+  verus_version?: string;  // `verus --version` of the toolchain that verified the graph
 }
 
 export interface D3Graph {
@@ -218,6 +247,7 @@ export interface FilterOptions {
   // Language filters (for multi-language projects)
   showRustNodes: boolean;          // Show Rust/Verus nodes (default: true)
   showLeanNodes: boolean;          // Show Lean nodes (default: true)
+  showTypeNodes: boolean;          // Show type nodes from export --include-types (default: true)
   // Verification status filters
   showVerifiedNodes: boolean;      // Show verified nodes (default: true)
   showFailedNodes: boolean;        // Show failed nodes (default: true)